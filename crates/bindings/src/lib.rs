@@ -1,6 +1,7 @@
 //! A crate for generating bindings with `cargo-component`.
 
 #![deny(missing_docs)]
+#![no_std]
 
 // Export the `generate` macro.
 pub use cargo_component_macro::generate;
@@ -0,0 +1,435 @@
+//! Module for auditing locked registry dependencies.
+//!
+//! An advisory database is a directory (or a git repository) of TOML files,
+//! each describing one advisory for a registry package:
+//!
+//! ```toml
+//! [advisory]
+//! id = "CMA-2023-0001"
+//! package = "wasi:http"
+//! title = "Request headers are not validated"
+//! url = "https://example.com/advisories/CMA-2023-0001"
+//!
+//! [versions]
+//! patched = [">=0.2.1"]
+//! unaffected = ["<0.2.0"]
+//! ```
+//!
+//! A version is affected by an advisory unless it matches one of the
+//! `patched` or `unaffected` requirements.
+
+use crate::{
+    git::{self, GitReference},
+    lock::LockFile,
+    oci::{self, OciClient},
+    registry::{create_client, find_url, DEFAULT_REGISTRY_NAME},
+    static_registry::{self, StaticClient},
+    terminal::Terminal,
+};
+use anyhow::{bail, Context, Result};
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use url::Url;
+use warg_client::{storage::RegistryStorage, ClientError};
+use warg_protocol::registry::PackageId;
+
+/// Represents the metadata of an advisory.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdvisoryMetadata {
+    /// The identifier of the advisory.
+    pub id: String,
+    /// The registry package the advisory is for.
+    pub package: PackageId,
+    /// The title of the advisory.
+    pub title: String,
+    /// The description of the advisory.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The date the advisory was reported.
+    #[serde(default)]
+    pub date: Option<String>,
+    /// The URL with more information about the advisory.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Represents the affected versions of an advisory.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdvisoryVersions {
+    /// The requirements of the versions that are patched.
+    pub patched: Vec<VersionReq>,
+    /// The requirements of the versions that were never affected.
+    pub unaffected: Vec<VersionReq>,
+}
+
+/// Represents an advisory for a registry package.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Advisory {
+    /// The metadata of the advisory.
+    pub advisory: AdvisoryMetadata,
+    /// The affected versions of the advisory.
+    #[serde(default)]
+    pub versions: AdvisoryVersions,
+}
+
+impl Advisory {
+    /// Determines if the given version is affected by the advisory.
+    pub fn affects(&self, version: &Version) -> bool {
+        !self
+            .versions
+            .patched
+            .iter()
+            .chain(self.versions.unaffected.iter())
+            .any(|req| req.matches(version))
+    }
+}
+
+/// Represents a database of advisories.
+#[derive(Debug, Default, Clone)]
+pub struct AdvisoryDatabase {
+    advisories: HashMap<PackageId, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Opens an advisory database.
+    ///
+    /// The source is either the path to a local directory or the URL of a
+    /// git repository, which is fetched into the given cache directory.
+    pub fn open(
+        source: &str,
+        cache_dir: &Path,
+        network_allowed: bool,
+        terminal: &Terminal,
+    ) -> Result<Self> {
+        let path = Path::new(source);
+        if path.is_dir() {
+            return Self::load(path);
+        }
+
+        if Url::parse(source).is_err() {
+            bail!("advisory database `{source}` is not a directory or the URL of a git repository");
+        }
+
+        terminal.status("Fetching", format!("advisory database `{source}`"))?;
+        let (_, dir) = git::checkout(
+            cache_dir,
+            source,
+            &GitReference::DefaultBranch,
+            None,
+            network_allowed,
+            terminal,
+        )
+        .with_context(|| format!("failed to fetch advisory database `{source}`"))?;
+
+        Self::load(&dir)
+    }
+
+    /// Loads an advisory database from the TOML files in a directory.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut files = Vec::new();
+        find_advisory_files(dir, &mut files)?;
+        files.sort();
+
+        let mut db = Self::default();
+        for path in files {
+            let contents = fs::read_to_string(&path).with_context(|| {
+                format!("failed to read advisory `{path}`", path = path.display())
+            })?;
+
+            let advisory: Advisory = toml_edit::de::from_str(&contents).with_context(|| {
+                format!("failed to parse advisory `{path}`", path = path.display())
+            })?;
+
+            db.advisories
+                .entry(advisory.advisory.package.clone())
+                .or_default()
+                .push(advisory);
+        }
+
+        Ok(db)
+    }
+
+    /// Gets the number of advisories in the database.
+    pub fn len(&self) -> usize {
+        self.advisories.values().map(Vec::len).sum()
+    }
+
+    /// Determines if the database has no advisories.
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    /// Gets the advisories affecting the given version of a package.
+    pub fn affecting<'a>(
+        &'a self,
+        id: &PackageId,
+        version: &'a Version,
+    ) -> impl Iterator<Item = &'a Advisory> {
+        self.advisories
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter(move |a| a.affects(version))
+    }
+}
+
+fn find_advisory_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory `{path}`", path = dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with('.'))
+            .unwrap_or(false);
+
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            find_advisory_files(&path, files)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Represents the kind of a problem found with a locked dependency.
+#[derive(Debug, Clone)]
+pub enum FindingKind {
+    /// The locked version is affected by an advisory.
+    Vulnerable(Box<Advisory>),
+    /// The locked version was yanked from its registry.
+    Yanked,
+    /// The locked version no longer exists in its registry.
+    Unpublished,
+}
+
+/// Represents a problem found with a locked dependency.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// The id of the locked package.
+    pub id: PackageId,
+    /// The name of the registry the package was locked from.
+    pub registry: String,
+    /// The locked version.
+    pub version: Version,
+    /// The kind of the problem.
+    pub kind: FindingKind,
+}
+
+impl Finding {
+    /// Determines if the finding is an error rather than a warning.
+    ///
+    /// Vulnerabilities are errors; retracted releases are warnings.
+    pub fn is_error(&self) -> bool {
+        matches!(self.kind, FindingKind::Vulnerable(_))
+    }
+
+    /// Describes the finding as a JSON object for machine-readable output.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "package": self.id.to_string(),
+            "registry": self.registry,
+            "version": self.version.to_string(),
+        });
+
+        match &self.kind {
+            FindingKind::Vulnerable(advisory) => {
+                value["kind"] = "vulnerable".into();
+                value["advisory"] = serde_json::json!({
+                    "id": advisory.advisory.id,
+                    "title": advisory.advisory.title,
+                    "date": advisory.advisory.date,
+                    "url": advisory.advisory.url,
+                    "patched": advisory
+                        .versions
+                        .patched
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                });
+            }
+            FindingKind::Yanked => value["kind"] = "yanked".into(),
+            FindingKind::Unpublished => value["kind"] = "unpublished".into(),
+        }
+
+        value
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            id,
+            registry,
+            version,
+            ..
+        } = self;
+
+        match &self.kind {
+            FindingKind::Vulnerable(advisory) => {
+                write!(
+                    f,
+                    "version {version} of package `{id}` is affected by advisory {advisory_id}: {title}",
+                    advisory_id = advisory.advisory.id,
+                    title = advisory.advisory.title
+                )?;
+
+                if !advisory.versions.patched.is_empty() {
+                    write!(
+                        f,
+                        " (patched in {patched})",
+                        patched = advisory
+                            .versions
+                            .patched
+                            .iter()
+                            .map(|r| format!("`{r}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )?;
+                }
+
+                if let Some(url) = &advisory.advisory.url {
+                    write!(f, "\nsee {url}")?;
+                }
+
+                Ok(())
+            }
+            FindingKind::Yanked => write!(
+                f,
+                "version {version} of package `{id}` was yanked from component registry `{registry}`"
+            ),
+            FindingKind::Unpublished => write!(
+                f,
+                "version {version} of package `{id}` no longer exists in component registry `{registry}`"
+            ),
+        }
+    }
+}
+
+/// Audits the registry packages of a lock file.
+///
+/// Each locked version is checked against the advisory database, if one is
+/// given, and against the releases of its registry for yanked or unpublished
+/// versions.
+///
+/// Returns the findings sorted by package id and version.
+pub async fn audit(
+    warg_config: &warg_client::Config,
+    registry_urls: &HashMap<String, Url>,
+    lock_file: &LockFile,
+    db: Option<&AdvisoryDatabase>,
+    terminal: &Terminal,
+    network_allowed: bool,
+) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+
+    for package in &lock_file.packages {
+        let registry = package.registry.as_deref().unwrap_or(DEFAULT_REGISTRY_NAME);
+        let url = find_url(
+            Some(registry),
+            registry_urls,
+            warg_config.default_url.as_deref(),
+        )?;
+        let id = &package.id;
+
+        if let Some(db) = db {
+            for locked in &package.versions {
+                for advisory in db.affecting(id, &locked.version) {
+                    findings.push(Finding {
+                        id: id.clone(),
+                        registry: registry.to_string(),
+                        version: locked.version.clone(),
+                        kind: FindingKind::Vulnerable(Box::new(advisory.clone())),
+                    });
+                }
+            }
+        }
+
+        // The versions of the package in the registry and whether they were yanked
+        let releases: HashMap<Version, bool> = if oci::is_oci_url(url) {
+            if !network_allowed {
+                continue;
+            }
+
+            let client = OciClient::new(url, oci::cache_dir(warg_config)?)?;
+            client
+                .list_versions(id)
+                .await
+                .with_context(|| format!("failed to list the versions of package `{id}`"))?
+                .into_iter()
+                .map(|v| (v, false))
+                .collect()
+        } else if static_registry::is_static_url(url) {
+            if !network_allowed {
+                continue;
+            }
+
+            let client = StaticClient::new(url, static_registry::cache_dir(warg_config)?)?;
+            client
+                .list_versions(id)
+                .await
+                .with_context(|| format!("failed to list the versions of package `{id}`"))?
+                .into_iter()
+                .collect()
+        } else {
+            let client = create_client(warg_config, url, terminal)?;
+            if network_allowed {
+                match client.upsert([id]).await {
+                    Ok(()) | Err(ClientError::PackageDoesNotExist { .. }) => {}
+                    Err(e) => {
+                        return Err(e)
+                            .with_context(|| format!("failed to update package log for `{id}`"))
+                    }
+                }
+            }
+
+            match client.registry().load_package(id).await? {
+                Some(p) => p
+                    .state
+                    .releases()
+                    .map(|r| (r.version.clone(), r.content().is_none()))
+                    .collect(),
+                None if network_allowed => HashMap::new(),
+                None => continue,
+            }
+        };
+
+        for locked in &package.versions {
+            let kind = match releases.get(&locked.version) {
+                Some(false) => continue,
+                Some(true) => FindingKind::Yanked,
+                None => FindingKind::Unpublished,
+            };
+
+            findings.push(Finding {
+                id: id.clone(),
+                registry: registry.to_string(),
+                version: locked.version.clone(),
+                kind,
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        a.id.to_string()
+            .cmp(&b.id.to_string())
+            .then_with(|| a.version.cmp(&b.version))
+    });
+
+    Ok(findings)
+}
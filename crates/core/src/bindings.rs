@@ -0,0 +1,174 @@
+//! Module for post-processing generated Rust bindings.
+//!
+//! Both `cargo component bindings` and the `generate!` macro of
+//! `cargo-component-bindings` emit the bindings of a world with these
+//! functions, so that the two produce the same bindings.
+
+use std::fmt::Write;
+
+/// The marker of a deprecated item in a WIT doc comment.
+pub const DEPRECATED_MARKER: &str = "@deprecated";
+
+/// Annotates the deprecated items of generated Rust bindings.
+///
+/// The generated bindings have a `///` doc comment line for every line of
+/// the documentation of an item; a `#[deprecated]` attribute is inserted
+/// after the doc comment of every item with a line starting with
+/// `@deprecated`, with the rest of the line as its note.
+pub fn annotate_deprecations(source: &str) -> String {
+    let mut annotated = String::with_capacity(source.len());
+    let mut pending: Option<(&str, &str)> = None;
+
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        match trimmed.strip_prefix("///") {
+            Some(doc) => {
+                if let Some(note) = doc.trim().strip_prefix(DEPRECATED_MARKER) {
+                    pending = Some((&line[..line.len() - trimmed.len()], note.trim()));
+                }
+            }
+            None => {
+                if let Some((indent, note)) = pending.take() {
+                    if note.is_empty() {
+                        writeln!(annotated, "{indent}#[deprecated]").unwrap();
+                    } else {
+                        writeln!(annotated, "{indent}#[deprecated(note = {note:?})]").unwrap();
+                    }
+                }
+            }
+        }
+
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+
+    annotated
+}
+
+/// Replaces the body of the given nested module of generated Rust bindings
+/// with a glob re-export of the module at the given Rust path.
+///
+/// Returns `None` if the bindings have no such module.
+pub fn remap_module(source: &str, module: &[String], path: &str) -> Option<String> {
+    let (start, end) = find_module(source, module)?;
+    let mut remapped = source.to_string();
+    remapped.replace_range(start..end, &format!("\npub use {path}::*;\n"));
+    Some(remapped)
+}
+
+/// Finds the range of the body of a nested module in Rust source.
+///
+/// Comments and string literals are skipped, so braces within them are not
+/// mistaken for the start or end of a block.
+fn find_module(source: &str, module: &[String]) -> Option<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let mut words: Vec<&str> = Vec::new();
+    // The name of the module opened by each block, if the block is a module
+    let mut blocks: Vec<Option<&str>> = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'{' => {
+                let name = match words[..] {
+                    [.., "mod", name] => Some(name),
+                    _ => None,
+                };
+                words.clear();
+                blocks.push(name);
+
+                if start.is_none()
+                    && blocks.len() == module.len()
+                    && blocks
+                        .iter()
+                        .zip(module)
+                        .all(|(block, name)| *block == Some(name.as_str()))
+                {
+                    start = Some((i + 1, blocks.len()));
+                }
+            }
+            b'}' => {
+                words.clear();
+                if let Some((body, depth)) = start {
+                    if blocks.len() == depth {
+                        return Some((body, i));
+                    }
+                }
+                blocks.pop();
+            }
+            b';' => words.clear(),
+            c if c.is_ascii_alphanumeric() || c == b'_' => {
+                let begin = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                words.push(&source[begin..i]);
+                continue;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_remaps_nested_modules() {
+        let source = r#"pub mod my {
+    pub mod types {
+        /// Ignores `}` in comments.
+        pub mod shapes {
+            pub fn name() -> &'static str { "}" }
+        }
+    }
+}
+"#;
+        let module = ["my", "types", "shapes"].map(String::from);
+        let remapped = remap_module(source, &module, "other::shapes").unwrap();
+        assert_eq!(
+            remapped,
+            r#"pub mod my {
+    pub mod types {
+        /// Ignores `}` in comments.
+        pub mod shapes {
+pub use other::shapes::*;
+}
+    }
+}
+"#
+        );
+
+        assert!(remap_module(source, &["my", "shapes"].map(String::from), "other").is_none());
+    }
+
+    #[test]
+    fn it_annotates_deprecations() {
+        let source = "    /// Gets the value.\n    ///\n    /// @deprecated use `get-value` instead\n    pub fn get() {}\n    /// @deprecated\n    pub fn old() {}\n";
+        assert_eq!(
+            annotate_deprecations(source),
+            "    /// Gets the value.\n    ///\n    /// @deprecated use `get-value` instead\n    #[deprecated(note = \"use `get-value` instead\")]\n    pub fn get() {}\n    /// @deprecated\n    #[deprecated]\n    pub fn old() {}\n"
+        );
+    }
+}
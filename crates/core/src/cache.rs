@@ -0,0 +1,309 @@
+//! Module for the on-disk content cache shared by registry clients.
+//!
+//! Content downloaded from OCI and static registries is stored by digest
+//! (i.e. `<dir>/sha256/<hex>`), so content already downloaded from one
+//! registry is not downloaded again from another.
+//!
+//! Content is written to a uniquely-named temporary file before it is moved
+//! into place, so concurrent downloads of the same content (by this or
+//! another process) never observe a partially-written file.
+//!
+//! Together with the content downloaded from warg registries and the git
+//! repositories of git dependencies, these caches are shared by every project
+//! of the user; `cache_dirs` returns each of them so they may be inspected,
+//! cleaned, or garbage collected.
+
+use crate::git;
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+/// Represents the kind of a cache directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// A directory of content named by digest.
+    Content,
+    /// A cache of git repositories and their checkouts.
+    Git,
+}
+
+/// Represents a cache directory shared by every project.
+#[derive(Debug, Clone)]
+pub struct CacheDir {
+    /// The name of the cache.
+    pub name: &'static str,
+    /// The kind of the cache.
+    pub kind: CacheKind,
+    /// The path of the cache directory.
+    pub path: PathBuf,
+}
+
+/// Represents the number of files (and their total size) in a cache.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Usage {
+    /// The number of files.
+    pub files: u64,
+    /// The total size of the files, in bytes.
+    pub bytes: u64,
+}
+
+impl Usage {
+    /// Adds the given usage to this usage.
+    pub fn add(&mut self, other: Usage) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Gets the cache directories shared by every project.
+pub fn cache_dirs(config: &warg_client::Config) -> Result<Vec<CacheDir>> {
+    // Only the digest directories of the content caches are included, as the
+    // warg content directory may also contain the other caches
+    let warg = match &config.content_dir {
+        Some(dir) => dir.clone(),
+        None => dirs::cache_dir()
+            .context("failed to determine the cache directory")?
+            .join("warg")
+            .join("content"),
+    };
+
+    Ok(vec![
+        CacheDir {
+            name: "warg",
+            kind: CacheKind::Content,
+            path: warg.join("sha256"),
+        },
+        CacheDir {
+            name: "content",
+            kind: CacheKind::Content,
+            path: content_dir(config)?.join("sha256"),
+        },
+        CacheDir {
+            name: "git",
+            kind: CacheKind::Git,
+            path: git::cache_dir(config)?,
+        },
+    ])
+}
+
+/// Gets the usage of a cache directory.
+pub fn usage(dir: &Path) -> Result<Usage> {
+    let mut usage = Usage::default();
+    if !dir.is_dir() {
+        return Ok(usage);
+    }
+
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read `{path}`", path = dir.display()))?
+    {
+        let entry =
+            entry.with_context(|| format!("failed to read `{path}`", path = dir.display()))?;
+        let metadata = entry
+            .metadata()
+            .with_context(|| format!("failed to read `{path}`", path = entry.path().display()))?;
+
+        if metadata.is_dir() {
+            usage.add(self::usage(&entry.path())?);
+        } else {
+            usage.files += 1;
+            usage.bytes += metadata.len();
+        }
+    }
+
+    Ok(usage)
+}
+
+/// Removes a cache directory, returning the usage of what was removed.
+pub fn clean(dir: &CacheDir) -> Result<Usage> {
+    let usage = usage(&dir.path)?;
+    if dir.path.is_dir() {
+        fs::remove_dir_all(&dir.path).with_context(|| {
+            format!(
+                "failed to remove cache directory `{path}`",
+                path = dir.path.display()
+            )
+        })?;
+    }
+
+    Ok(usage)
+}
+
+/// Removes the entries of a cache directory that were not added within the
+/// given duration, returning the usage of what was removed.
+///
+/// For content caches, the entries are the content files; for git caches,
+/// the entries are the checkouts of a commit. Removed entries are downloaded
+/// again when next needed.
+pub fn gc(dir: &CacheDir, max_age: Duration) -> Result<Usage> {
+    let (root, depth) = match dir.kind {
+        CacheKind::Content => (dir.path.clone(), 1),
+        CacheKind::Git => (dir.path.join("checkouts"), 2),
+    };
+
+    let now = SystemTime::now();
+    let mut removed = Usage::default();
+    for path in entries(&root, depth)? {
+        let modified = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+        if now.duration_since(modified).unwrap_or_default() <= max_age {
+            continue;
+        }
+
+        log::debug!("removing cache entry `{path}`", path = path.display());
+
+        if path.is_dir() {
+            removed.add(usage(&path)?);
+            fs::remove_dir_all(&path)
+        } else {
+            removed.add(Usage {
+                files: 1,
+                bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or_default(),
+            });
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("failed to remove `{path}`", path = path.display()))?;
+    }
+
+    Ok(removed)
+}
+
+/// Gets the paths at the given depth below a directory.
+fn entries(dir: &Path, depth: usize) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read `{path}`", path = dir.display()))?
+    {
+        let path = entry
+            .with_context(|| format!("failed to read `{path}`", path = dir.display()))?
+            .path();
+        if depth > 1 {
+            paths.extend(entries(&path, depth - 1)?);
+        } else {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Gets the directory of the shared content cache.
+pub fn content_dir(config: &warg_client::Config) -> Result<PathBuf> {
+    match &config.content_dir {
+        Some(dir) => Ok(dir.join("components")),
+        None => Ok(dirs::cache_dir()
+            .context("failed to determine the cache directory")?
+            .join("cargo-component")
+            .join("content")),
+    }
+}
+
+/// Gets the path of the given content digest in a content cache directory.
+///
+/// Returns `None` if the digest algorithm is not supported.
+pub fn content_path(dir: &Path, digest: &str) -> Option<PathBuf> {
+    let hash = digest.strip_prefix("sha256:")?;
+    Some(dir.join("sha256").join(hash))
+}
+
+/// Writes content to the given path of a content cache.
+pub fn write_content(path: &Path, bytes: &[u8]) -> Result<()> {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    let parent = path.parent().unwrap();
+    fs::create_dir_all(parent).with_context(|| {
+        format!(
+            "failed to create directory `{path}`",
+            path = parent.display()
+        )
+    })?;
+
+    // Write to a temporary file first so a partial download is never observed
+    let temp = path.with_extension(format!(
+        "{pid}.{id}.tmp",
+        pid = std::process::id(),
+        id = NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp, bytes)
+        .with_context(|| format!("failed to write `{path}`", path = temp.display()))?;
+
+    match fs::rename(&temp, path) {
+        Ok(()) => Ok(()),
+        // Replacing the file may fail on some platforms if another download
+        // is moving the same content into place at the same time
+        Err(_) if fs::read(path).ok().as_deref() == Some(bytes) => {
+            fs::remove_file(&temp).ok();
+            Ok(())
+        }
+        Err(e) => {
+            fs::remove_file(&temp).ok();
+            Err(e).with_context(|| format!("failed to write `{path}`", path = path.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        sync::{Arc, Barrier},
+        thread,
+    };
+
+    #[test]
+    fn it_writes_content_from_racing_downloads() -> Result<()> {
+        const DOWNLOADS: usize = 8;
+
+        let dir = tempfile::tempdir()?;
+        let path = content_path(dir.path(), "sha256:0123").unwrap();
+        let bytes = vec![42; 64 * 1024];
+
+        let barrier = Arc::new(Barrier::new(DOWNLOADS));
+        let downloads: Vec<_> = (0..DOWNLOADS)
+            .map(|_| {
+                let barrier = barrier.clone();
+                let path = path.clone();
+                let bytes = bytes.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    write_content(&path, &bytes)
+                })
+            })
+            .collect();
+
+        for download in downloads {
+            download.join().unwrap()?;
+        }
+
+        assert_eq!(fs::read(&path)?, bytes);
+
+        // No temporary files are left behind
+        let files: Vec<_> = fs::read_dir(path.parent().unwrap())?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(files, [path]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_replaces_partially_written_content() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = content_path(dir.path(), "sha256:0123").unwrap();
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, b"partial")?;
+
+        write_content(&path, b"complete")?;
+        assert_eq!(fs::read(&path)?, b"complete");
+        Ok(())
+    }
+}
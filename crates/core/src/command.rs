@@ -1,6 +1,6 @@
 //! Module for common command implementation.
 
-use crate::terminal::{Color, Terminal, Verbosity};
+use crate::terminal::{Color, MessageFormat, Terminal, Verbosity};
 use clap::{ArgAction, Args};
 
 /// Common options for commands.
@@ -21,6 +21,10 @@ pub struct CommonOptions {
     /// Coloring: auto, always, never
     #[clap(long = "color", value_name = "WHEN")]
     pub color: Option<Color>,
+
+    /// The output format for messages: human, json
+    #[clap(long = "message-format", value_name = "FMT")]
+    pub message_format: Option<MessageFormat>,
 }
 
 impl CommonOptions {
@@ -37,5 +41,34 @@ impl CommonOptions {
             },
             self.color.unwrap_or_default(),
         )
+        .with_message_format(self.message_format.unwrap_or_default())
     }
 }
+
+/// Detects the message format requested by the given command line arguments.
+///
+/// This is used to report errors in the requested format when the parsed
+/// options of a command are not available. Any of cargo's `json` message
+/// formats (e.g. `json-diagnostic-short`) selects the JSON format.
+pub fn detect_message_format(args: impl IntoIterator<Item = String>) -> MessageFormat {
+    let mut format = MessageFormat::Human;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+
+        let value = match arg.strip_prefix("--message-format") {
+            Some("") => args.next(),
+            Some(value) => value.strip_prefix('=').map(ToString::to_string),
+            None => continue,
+        };
+
+        format = match value {
+            Some(value) if value.starts_with("json") => MessageFormat::Json,
+            _ => MessageFormat::Human,
+        };
+    }
+
+    format
+}
@@ -0,0 +1,424 @@
+//! Module for resolving dependencies from git repositories.
+//!
+//! Repositories are fetched with the `git` command line tool into a bare
+//! database in the cache directory; each resolved commit is then checked out
+//! into its own directory, which is never modified once created.
+//!
+//! The WIT of a checkout is found in the following order:
+//!
+//! * the path of the target world of a `cargo-component` crate, if the
+//!   checkout contains a `Cargo.toml` with such a path.
+//! * the `wit` directory of the checkout, if it has one.
+//! * the checkout itself.
+//!
+//! The module also gets the version control state of a package being
+//! published, so the published component can be traced back to its source.
+
+use crate::terminal::Terminal;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use toml_edit::Document;
+
+/// The name of the file written to a checkout once it is complete.
+pub const CHECKOUT_COMPLETE_FILE: &str = ".cargo-component-ok";
+
+/// Represents the reference of a git repository to resolve.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum GitReference {
+    /// The default branch (i.e. `HEAD`) of the repository.
+    #[default]
+    DefaultBranch,
+    /// A branch of the repository.
+    Branch(String),
+    /// A tag of the repository.
+    Tag(String),
+    /// A revision (e.g. a commit hash) of the repository.
+    Rev(String),
+}
+
+impl GitReference {
+    /// Gets the revision to resolve in the local database of a repository.
+    fn revision(&self) -> String {
+        match self {
+            Self::DefaultBranch => "refs/remotes/origin/HEAD^{commit}".to_string(),
+            Self::Branch(branch) => format!("refs/remotes/origin/{branch}^{{commit}}"),
+            Self::Tag(tag) => format!("refs/remotes/origin/tags/{tag}^{{commit}}"),
+            Self::Rev(rev) => format!("{rev}^{{commit}}"),
+        }
+    }
+}
+
+impl fmt::Display for GitReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DefaultBranch => write!(f, "HEAD"),
+            Self::Branch(branch) => write!(f, "branch={branch}"),
+            Self::Tag(tag) => write!(f, "tag={tag}"),
+            Self::Rev(rev) => write!(f, "rev={rev}"),
+        }
+    }
+}
+
+impl FromStr for GitReference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s == "HEAD" {
+            return Ok(Self::DefaultBranch);
+        }
+
+        match s.split_once('=') {
+            Some(("branch", branch)) => Ok(Self::Branch(branch.to_string())),
+            Some(("tag", tag)) => Ok(Self::Tag(tag.to_string())),
+            Some(("rev", rev)) => Ok(Self::Rev(rev.to_string())),
+            _ => bail!("invalid git reference `{s}`"),
+        }
+    }
+}
+
+impl Serialize for GitReference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for GitReference {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Represents a dependency on a git repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitDependency {
+    /// The URL of the repository.
+    pub url: String,
+    /// The reference of the repository to resolve.
+    pub reference: GitReference,
+    /// The path within the repository containing the dependency.
+    pub path: Option<PathBuf>,
+}
+
+/// Gets the directory used to cache git repositories.
+pub fn cache_dir(config: &warg_client::Config) -> Result<PathBuf> {
+    match &config.content_dir {
+        Some(dir) => Ok(dir.join("git")),
+        None => Ok(dirs::cache_dir()
+            .context("failed to determine the cache directory")?
+            .join("cargo-component")
+            .join("git")),
+    }
+}
+
+/// Checks out a reference of a git repository.
+///
+/// If a locked commit is given, it is checked out instead of resolving the
+/// reference; the repository is only fetched if the commit is not already
+/// in the cache.
+///
+/// Returns the resolved commit and the path to its checkout.
+pub fn checkout(
+    cache_dir: &Path,
+    url: &str,
+    reference: &GitReference,
+    locked: Option<&str>,
+    network_allowed: bool,
+    terminal: &Terminal,
+) -> Result<(String, PathBuf)> {
+    let ident = ident(url);
+    let checkouts = cache_dir.join("checkouts").join(&ident);
+
+    if let Some(commit) = locked {
+        let dir = checkouts.join(commit);
+        if dir.join(CHECKOUT_COMPLETE_FILE).is_file() {
+            log::info!("using existing checkout of commit `{commit}` of git repository `{url}`");
+            return Ok((commit.to_string(), dir));
+        }
+    }
+
+    let db = cache_dir.join("db").join(&ident);
+    if !db.join("HEAD").is_file() {
+        fs::create_dir_all(&db)
+            .with_context(|| format!("failed to create directory `{path}`", path = db.display()))?;
+        git(Some(&db), ["init", "--quiet", "--bare"])?;
+    }
+
+    let commit = match locked.filter(|commit| rev_parse(&db, &format!("{commit}^{{commit}}"))) {
+        Some(commit) => commit.to_string(),
+        None => {
+            if !network_allowed {
+                bail!("cannot fetch git repository `{url}` because network access is disabled");
+            }
+
+            terminal.status("Updating", format!("git repository `{url}`"))?;
+            fetch(&db, url, reference)?;
+
+            let revision = match locked {
+                Some(commit) => format!("{commit}^{{commit}}"),
+                None => reference.revision(),
+            };
+
+            git(Some(&db), ["rev-parse", "--verify", "--quiet", &revision])
+                .map(|commit| commit.trim().to_string())
+                .with_context(|| match locked {
+                    Some(commit) => {
+                        format!("locked commit `{commit}` was not found in git repository `{url}`")
+                    }
+                    None => format!("failed to find `{reference}` in git repository `{url}`"),
+                })?
+        }
+    };
+
+    let dir = checkouts.join(&commit);
+    if !dir.join(CHECKOUT_COMPLETE_FILE).is_file() {
+        log::info!("checking out commit `{commit}` of git repository `{url}`");
+
+        if dir.exists() {
+            fs::remove_dir_all(&dir).with_context(|| {
+                format!(
+                    "failed to remove incomplete checkout `{path}`",
+                    path = dir.display()
+                )
+            })?;
+        }
+
+        fs::create_dir_all(&checkouts).with_context(|| {
+            format!(
+                "failed to create directory `{path}`",
+                path = checkouts.display()
+            )
+        })?;
+
+        let db = db.to_str().context("git database path is not UTF-8")?;
+        let path = dir.to_str().context("git checkout path is not UTF-8")?;
+        git(
+            None,
+            ["clone", "--quiet", "--shared", "--no-checkout", db, path],
+        )?;
+        git(Some(&dir), ["checkout", "--quiet", "--detach", &commit])?;
+
+        fs::write(dir.join(CHECKOUT_COMPLETE_FILE), "").with_context(|| {
+            format!("failed to write to checkout `{path}`", path = dir.display())
+        })?;
+    }
+
+    Ok((commit, dir))
+}
+
+/// Finds the WIT of a dependency in a checkout of a git repository.
+///
+/// The given path is relative to the root of the checkout.
+pub fn find_wit(checkout: &Path, path: Option<&Path>) -> Result<PathBuf> {
+    let root = match path {
+        Some(path) => checkout.join(path),
+        None => checkout.to_path_buf(),
+    };
+
+    if !root.exists() {
+        bail!(
+            "path `{path}` does not exist in the git repository",
+            path = path.unwrap_or(Path::new(".")).display()
+        );
+    }
+
+    // Use the target world of a `cargo-component` crate
+    let manifest = root.join("Cargo.toml");
+    if manifest.is_file() {
+        let contents = fs::read_to_string(&manifest).with_context(|| {
+            format!(
+                "failed to read manifest `{path}`",
+                path = manifest.display()
+            )
+        })?;
+
+        let document: Document = contents.parse().with_context(|| {
+            format!(
+                "failed to parse manifest `{path}`",
+                path = manifest.display()
+            )
+        })?;
+
+        if let Some(path) = document
+            .get("package")
+            .and_then(|i| i.get("metadata"))
+            .and_then(|i| i.get("component"))
+            .and_then(|i| i.get("target"))
+            .and_then(|i| i.get("path"))
+            .and_then(|i| i.as_str())
+        {
+            return Ok(root.join(path));
+        }
+    }
+
+    let wit = root.join("wit");
+    if wit.is_dir() {
+        return Ok(wit);
+    }
+
+    Ok(root)
+}
+
+/// Represents the version control state of a package being published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitProvenance {
+    /// The commit checked out in the repository.
+    pub commit: String,
+    /// The files of the package with uncommitted changes, relative to the
+    /// root of the repository.
+    pub dirty: Vec<String>,
+    /// The URL of the `origin` remote of the repository, if it has one.
+    pub repository: Option<String>,
+    /// The path of the package directory relative to the root of the
+    /// repository, using `/` separators.
+    pub path_in_vcs: String,
+}
+
+impl GitProvenance {
+    /// Gets the version recorded for the commit, which is suffixed with
+    /// `-dirty` if the package has uncommitted changes.
+    pub fn version(&self) -> String {
+        if self.dirty.is_empty() {
+            self.commit.clone()
+        } else {
+            format!("{commit}-dirty", commit = self.commit)
+        }
+    }
+}
+
+/// Gets the version control state of the package in the given directory.
+///
+/// Returns `Ok(None)` if the directory is not in a git repository.
+pub fn provenance(dir: &Path) -> Result<Option<GitProvenance>> {
+    let root = match git(Some(dir), ["rev-parse", "--show-toplevel"]) {
+        Ok(root) => PathBuf::from(root.trim()),
+        Err(_) => return Ok(None),
+    };
+
+    let commit = git(Some(dir), ["rev-parse", "--verify", "HEAD"])
+        .context("failed to get the commit of the git repository; has anything been committed?")?
+        .trim()
+        .to_string();
+
+    // Only changes to the package's own files make it dirty
+    let dirty = git(
+        Some(dir),
+        ["status", "--porcelain", "--untracked-files=all", "--", "."],
+    )?
+    .lines()
+    .filter_map(|line| line.get(3..))
+    .map(ToString::to_string)
+    .collect();
+
+    let repository = git(Some(dir), ["remote", "get-url", "origin"])
+        .ok()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+
+    let path_in_vcs = dir
+        .canonicalize()
+        .ok()
+        .zip(root.canonicalize().ok())
+        .and_then(|(dir, root)| {
+            dir.strip_prefix(root).ok().map(|p| {
+                p.components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+        })
+        .unwrap_or_default();
+
+    Ok(Some(GitProvenance {
+        commit,
+        dirty,
+        repository,
+        path_in_vcs,
+    }))
+}
+
+/// Fetches the branches and tags of a repository into its database.
+fn fetch(db: &Path, url: &str, reference: &GitReference) -> Result<()> {
+    git(
+        Some(db),
+        [
+            "fetch",
+            "--quiet",
+            "--force",
+            "--no-tags",
+            url,
+            "+refs/heads/*:refs/remotes/origin/*",
+            "+refs/tags/*:refs/remotes/origin/tags/*",
+            "+HEAD:refs/remotes/origin/HEAD",
+        ],
+    )?;
+
+    // A revision may not be reachable from any branch or tag, so fetch it directly
+    if let GitReference::Rev(rev) = reference {
+        if !rev_parse(db, &reference.revision()) {
+            git(Some(db), ["fetch", "--quiet", "--force", url, rev])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Determines if a revision exists in a repository.
+fn rev_parse(dir: &Path, revision: &str) -> bool {
+    git(Some(dir), ["rev-parse", "--verify", "--quiet", revision]).is_ok()
+}
+
+/// Runs a git command, returning its standard output.
+fn git<'a>(dir: Option<&Path>, args: impl IntoIterator<Item = &'a str>) -> Result<String> {
+    let args: Vec<_> = args.into_iter().collect();
+
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+
+    log::debug!("running git command `git {args}`", args = args.join(" "));
+
+    let output = cmd
+        .args(&args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .context("failed to run `git`; make sure it is installed")?;
+
+    if !output.status.success() {
+        bail!(
+            "git command `git {args}` failed: {stderr}",
+            args = args.join(" "),
+            stderr = String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8(output.stdout).context("git command output was not UTF-8")
+}
+
+/// Gets the name of the cache directories of a repository.
+fn ident(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':', '\\'])
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("repo");
+
+    let hash = hex::encode(Sha256::digest(url.as_bytes()));
+    format!("{name}-{hash}", hash = &hash[..16])
+}
@@ -0,0 +1,26 @@
+//! Module for fetching files over HTTP.
+
+use anyhow::{bail, Context, Result};
+
+/// Determines if the given string is an HTTP or HTTPS URL.
+pub fn is_http_url(s: &str) -> bool {
+    s.starts_with("https://") || s.starts_with("http://")
+}
+
+/// Fetches the contents of the given HTTP or HTTPS URL.
+pub async fn fetch(url: &str) -> Result<Vec<u8>> {
+    let res = reqwest::get(url)
+        .await
+        .with_context(|| format!("failed to send request to `{url}`"))?;
+
+    let status = res.status();
+    if !status.is_success() {
+        bail!("failed to fetch `{url}`: server returned status {status}");
+    }
+
+    Ok(res
+        .bytes()
+        .await
+        .with_context(|| format!("failed to fetch `{url}`"))?
+        .to_vec())
+}
@@ -0,0 +1,371 @@
+//! Module for querying component registries for information about packages.
+
+use crate::{
+    oci::{self, OciClient},
+    registry::{
+        create_client, find_namespace_registry, find_url, Dependency, DependencyResolution,
+        DependencyResolver, NamespaceRegistries, RegistryPackage, DEFAULT_REGISTRY_NAME,
+    },
+    static_registry::{self, StaticClient},
+    terminal::Terminal,
+};
+use anyhow::{bail, Context, Result};
+use semver::{Version, VersionReq};
+use std::{collections::HashMap, fmt, fs};
+use url::Url;
+use warg_client::{storage::RegistryStorage, ClientError};
+use warg_protocol::registry::PackageId;
+use wasm_metadata::{LinkType, RegistryMetadata};
+use wit_component::DecodedWasm;
+use wit_parser::{Resolve, WorldItem, WorldKey};
+
+/// Represents a release of a registry package.
+#[derive(Debug, Clone)]
+pub struct Release {
+    /// The version of the release.
+    pub version: Version,
+    /// Whether or not the release was yanked.
+    pub yanked: bool,
+}
+
+/// Represents a package found by a registry search.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// The id of the package.
+    pub id: PackageId,
+    /// The latest version of the package that was not yanked, if any.
+    pub latest: Option<Version>,
+}
+
+/// Represents information about a registry package.
+pub struct PackageDetails {
+    /// The id of the package.
+    pub id: PackageId,
+    /// The name of the registry the package is from.
+    pub registry: String,
+    /// The URL of the registry the package is from.
+    pub url: String,
+    /// The releases of the package, sorted by version.
+    pub releases: Vec<Release>,
+    /// The version of the package that was inspected.
+    pub version: Version,
+    /// The registry metadata of the inspected version.
+    pub metadata: Option<RegistryMetadata>,
+    /// The summary of the worlds and interfaces of the inspected version.
+    pub summary: Vec<String>,
+}
+
+impl fmt::Display for PackageDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "package: {id}", id = self.id)?;
+        writeln!(
+            f,
+            "registry: {registry} ({url})",
+            registry = self.registry,
+            url = self.url
+        )?;
+
+        let versions = self
+            .releases
+            .iter()
+            .map(|r| {
+                if r.yanked {
+                    format!("{version} (yanked)", version = r.version)
+                } else {
+                    r.version.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(f, "versions: {versions}")?;
+        writeln!(f, "inspected version: {version}", version = self.version)?;
+
+        if let Some(metadata) = &self.metadata {
+            write_registry_metadata(f, metadata)?;
+        }
+
+        for line in &self.summary {
+            writeln!(f, "{line}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the registry metadata of a package or component, one field per line.
+pub fn write_registry_metadata(
+    f: &mut impl fmt::Write,
+    metadata: &RegistryMetadata,
+) -> fmt::Result {
+    if let Some(description) = metadata.get_description() {
+        writeln!(f, "description: {description}")?;
+    }
+
+    if let Some(authors) = metadata.get_authors() {
+        writeln!(f, "authors: {authors}", authors = authors.join(", "))?;
+    }
+
+    if let Some(license) = metadata.get_license() {
+        writeln!(f, "license: {license}")?;
+    }
+
+    if let Some(categories) = metadata.get_categories() {
+        writeln!(
+            f,
+            "categories: {categories}",
+            categories = categories.join(", ")
+        )?;
+    }
+
+    for link in metadata.get_links().into_iter().flatten() {
+        let kind = match &link.ty {
+            LinkType::Documentation => "documentation",
+            LinkType::Homepage => "homepage",
+            LinkType::Repository => "repository",
+            LinkType::Custom(kind) => kind.as_str(),
+            _ => "link",
+        };
+        writeln!(f, "{kind}: {value}", value = link.value)?;
+    }
+
+    Ok(())
+}
+
+/// Searches a registry for packages whose id contains the given query.
+///
+/// Warg registries do not provide a search API, so the packages searched are
+/// those known to the local registry client: each is updated to the latest
+/// registry checkpoint first. If the query is a complete package id, that
+/// package is also fetched from the registry.
+///
+/// OCI and static registries cannot be searched.
+pub async fn search(
+    warg_config: &warg_client::Config,
+    url: &str,
+    query: &str,
+    terminal: &Terminal,
+    network_allowed: bool,
+) -> Result<Vec<SearchResult>> {
+    if oci::is_oci_url(url) {
+        bail!("searching OCI registry `{url}` is not supported; use the `info` command with a package id instead");
+    }
+
+    if static_registry::is_static_url(url) {
+        bail!("searching static registry `{url}` is not supported; use the `info` command with a package id instead");
+    }
+
+    let client = create_client(warg_config, url, terminal)?;
+    if network_allowed {
+        terminal.status("Updating", "component registry package logs")?;
+        client
+            .update()
+            .await
+            .context("failed to update component registry package logs")?;
+
+        if let Ok(id) = query.parse::<PackageId>() {
+            match client.upsert([&id]).await {
+                Ok(()) | Err(ClientError::PackageDoesNotExist { .. }) => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("failed to update package log for `{id}`"))
+                }
+            }
+        }
+    }
+
+    let mut results: Vec<_> = client
+        .registry()
+        .load_packages()
+        .await?
+        .into_iter()
+        .filter(|p| p.id.to_string().contains(query))
+        .map(|p| SearchResult {
+            latest: p
+                .state
+                .releases()
+                .filter(|r| r.content().is_some())
+                .map(|r| r.version.clone())
+                .max(),
+            id: p.id,
+        })
+        .collect();
+
+    results.sort_by(|a, b| a.id.to_string().cmp(&b.id.to_string()));
+    Ok(results)
+}
+
+/// Gets information about a registry package.
+///
+/// The latest release matching `version` (or the latest release if `None`) is
+/// downloaded to summarize its worlds and interfaces and read its registry
+/// metadata.
+#[allow(clippy::too_many_arguments)]
+pub async fn package_details(
+    warg_config: &warg_client::Config,
+    registry_urls: &HashMap<String, Url>,
+    namespaces: &HashMap<String, NamespaceRegistries>,
+    id: &PackageId,
+    version: Option<&VersionReq>,
+    registry: Option<&str>,
+    terminal: &Terminal,
+    network_allowed: bool,
+) -> Result<PackageDetails> {
+    let registry = registry
+        .or_else(|| find_namespace_registry(namespaces, id))
+        .unwrap_or(DEFAULT_REGISTRY_NAME);
+    let url = find_url(
+        Some(registry),
+        registry_urls,
+        warg_config.default_url.as_deref(),
+    )?;
+
+    let mut releases = if oci::is_oci_url(url) {
+        let client = OciClient::new(url, oci::cache_dir(warg_config)?)?;
+        client
+            .list_versions(id)
+            .await?
+            .into_iter()
+            .map(|version| Release {
+                version,
+                yanked: false,
+            })
+            .collect::<Vec<_>>()
+    } else if static_registry::is_static_url(url) {
+        let client = StaticClient::new(url, static_registry::cache_dir(warg_config)?)?;
+        client
+            .list_versions(id)
+            .await?
+            .into_iter()
+            .map(|(version, yanked)| Release { version, yanked })
+            .collect::<Vec<_>>()
+    } else {
+        let client = create_client(warg_config, url, terminal)?;
+        if network_allowed {
+            match client.upsert([id]).await {
+                Ok(()) => {}
+                Err(ClientError::PackageDoesNotExist { .. }) => {
+                    bail!("package `{id}` was not found in component registry `{registry}`")
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("failed to update package log for `{id}`"))
+                }
+            }
+        }
+
+        client
+            .registry()
+            .load_package(id)
+            .await?
+            .map(|p| {
+                p.state
+                    .releases()
+                    .map(|r| Release {
+                        version: r.version.clone(),
+                        yanked: r.content().is_none(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    if releases.is_empty() {
+        bail!("package `{id}` has no releases in component registry `{registry}`");
+    }
+
+    releases.sort_by(|a, b| a.version.cmp(&b.version));
+
+    let dependency = Dependency::Package(RegistryPackage {
+        id: None,
+        version: version.cloned().unwrap_or(VersionReq::STAR),
+        registry: Some(registry.to_string()),
+        interfaces: Vec::new(),
+    });
+
+    let mut resolver =
+        DependencyResolver::new(warg_config, registry_urls, None, terminal, network_allowed)?;
+    resolver.add_dependency(id, &dependency).await?;
+
+    let map = resolver.resolve().await?;
+    let resolution = map.values().next().expect("expected a resolution");
+    let version = match resolution {
+        DependencyResolution::Registry(resolution) => resolution.version.clone(),
+        DependencyResolution::Local(_) | DependencyResolution::Git(_) => {
+            unreachable!("expected a registry resolution")
+        }
+    };
+
+    let bytes = fs::read(resolution.path()).with_context(|| {
+        format!(
+            "failed to read content of package `{id}` at path `{path}`",
+            path = resolution.path().display()
+        )
+    })?;
+
+    let metadata = RegistryMetadata::from_wasm(&bytes)
+        .with_context(|| format!("failed to parse registry metadata of package `{id}`"))?;
+
+    let summary =
+        match wit_component::decode(&bytes)
+            .with_context(|| format!("failed to decode content of package `{id}`"))?
+        {
+            DecodedWasm::WitPackage(resolve, package) => {
+                let package = &resolve.packages[package];
+                let mut summary = vec!["kind: WIT package".to_string()];
+                summary.extend(package.worlds.keys().map(|name| format!("world: {name}")));
+                summary.extend(
+                    package
+                        .interfaces
+                        .keys()
+                        .map(|name| format!("interface: {name}")),
+                );
+                summary
+            }
+            DecodedWasm::Component(resolve, world) => {
+                let world = &resolve.worlds[world];
+                let mut summary = vec!["kind: component".to_string()];
+                summary.extend(
+                    world.imports.iter().map(|(key, item)| {
+                        format!("import: {}", describe_item(&resolve, key, item))
+                    }),
+                );
+                summary.extend(
+                    world.exports.iter().map(|(key, item)| {
+                        format!("export: {}", describe_item(&resolve, key, item))
+                    }),
+                );
+                summary
+            }
+        };
+
+    Ok(PackageDetails {
+        id: id.clone(),
+        registry: registry.to_string(),
+        url: url.to_string(),
+        releases,
+        version,
+        metadata,
+        summary,
+    })
+}
+
+/// Describes an import or export of a world by its kind and name.
+pub fn describe_item(resolve: &Resolve, key: &WorldKey, item: &WorldItem) -> String {
+    let name = match key {
+        WorldKey::Name(name) => name.clone(),
+        WorldKey::Interface(id) => {
+            let iface = &resolve.interfaces[*id];
+            match (iface.package, &iface.name) {
+                (Some(package), Some(name)) => resolve.packages[package].name.interface_id(name),
+                (_, Some(name)) => name.clone(),
+                _ => "<unnamed>".to_string(),
+            }
+        }
+    };
+
+    match item {
+        WorldItem::Interface(_) => format!("interface `{name}`"),
+        WorldItem::Function(_) => format!("function `{name}`"),
+        WorldItem::Type(_) => format!("type `{name}`"),
+    }
+}
@@ -3,9 +3,96 @@
 use anyhow::{bail, Context, Result};
 use keyring::Entry;
 pub use keyring::Error;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
 use warg_client::RegistryUrl;
 use warg_crypto::signing::PrivateKey;
 
+/// The name of the file that records the signing keys in the keyring.
+const KEY_INDEX_FILE_NAME: &str = "signing-keys.json";
+
+/// Represents a signing key recorded in the key index.
+///
+/// OS keyrings cannot enumerate their entries, so the registry URL and name
+/// of each key set through this module are recorded in an index file in the
+/// user's configuration directory. The keys themselves are only stored in
+/// the keyring.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SigningKeyEntry {
+    /// The URL of the registry the key is for.
+    pub registry: String,
+    /// The name of the key.
+    pub name: String,
+}
+
+fn key_index_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("cargo-component").join(KEY_INDEX_FILE_NAME))
+}
+
+/// Lists the signing keys recorded in the key index, sorted by registry and name.
+///
+/// Keys that were added to the keyring by other tools are not listed.
+pub fn list_signing_keys() -> Result<Vec<SigningKeyEntry>> {
+    let path = match key_index_path() {
+        Some(path) if path.is_file() => path,
+        _ => return Ok(Vec::new()),
+    };
+
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read key index `{path}`", path = path.display()))?;
+    let mut entries: Vec<SigningKeyEntry> = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse key index `{path}`", path = path.display()))?;
+    entries.sort();
+    Ok(entries)
+}
+
+fn update_key_index(registry_url: &RegistryUrl, key_name: &str, present: bool) -> Result<()> {
+    let path = match key_index_path() {
+        Some(path) => path,
+        None => {
+            log::debug!("not recording signing key as there is no configuration directory");
+            return Ok(());
+        }
+    };
+
+    let entry = SigningKeyEntry {
+        registry: registry_url.to_string(),
+        name: key_name.to_string(),
+    };
+
+    let mut entries = list_signing_keys()?;
+    entries.retain(|e| *e != entry);
+    if present {
+        entries.push(entry);
+        entries.sort();
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "failed to create directory `{path}`",
+                path = parent.display()
+            )
+        })?;
+    }
+
+    fs::write(&path, serde_json::to_string_pretty(&entries)?)
+        .with_context(|| format!("failed to write key index `{path}`", path = path.display()))
+}
+
+/// Determines if a signing key exists for the given registry url and key name.
+pub fn signing_key_exists(registry_url: &RegistryUrl, key_name: &str) -> Result<bool> {
+    let entry = get_signing_key_entry(registry_url, key_name)?;
+
+    match entry.get_password() {
+        Ok(_) | Err(keyring::Error::Ambiguous(_)) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => {
+            bail!("failed to get signing key with name `{key_name}` for registry `{registry_url}`: {e}");
+        }
+    }
+}
+
 /// Gets the signing key entry for the given registry and key name.
 pub fn get_signing_key_entry(registry_url: &RegistryUrl, key_name: &str) -> Result<Entry> {
     let label = format!("warg-signing-key:{}", registry_url.safe_label());
@@ -34,7 +121,7 @@ pub fn get_signing_key(registry_url: &RegistryUrl, key_name: &str) -> Result<Pri
 pub fn set_signing_key(registry_url: &RegistryUrl, key_name: &str, key: &PrivateKey) -> Result<()> {
     let entry = get_signing_key_entry(registry_url, key_name)?;
     match entry.set_password(&key.encode()) {
-        Ok(()) => Ok(()),
+        Ok(()) => update_key_index(registry_url, key_name, true),
         Err(keyring::Error::NoEntry) => {
             bail!("no signing key found with name `{key_name}` for registry `{registry_url}`");
         }
@@ -51,7 +138,7 @@ pub fn set_signing_key(registry_url: &RegistryUrl, key_name: &str, key: &Private
 pub fn delete_signing_key(registry_url: &RegistryUrl, key_name: &str) -> Result<()> {
     let entry = get_signing_key_entry(registry_url, key_name)?;
     match entry.delete_password() {
-        Ok(()) => Ok(()),
+        Ok(()) => update_key_index(registry_url, key_name, false),
         Err(keyring::Error::NoEntry) => {
             bail!("no signing key found with name `{key_name}` for registry `{registry_url}`");
         }
@@ -63,3 +150,74 @@ pub fn delete_signing_key(registry_url: &RegistryUrl, key_name: &str) -> Result<
         }
     }
 }
+
+/// The user name of the keyring entry of a token for a whole registry.
+const REGISTRY_TOKEN_USER: &str = "default";
+
+/// Gets the keyring entry of the authentication token for the given registry.
+///
+/// A token may be scoped to a namespace of the registry; otherwise it is used
+/// for every namespace.
+pub fn get_registry_token_entry(registry: &str, namespace: Option<&str>) -> Result<Entry> {
+    let label = format!("cargo-component-registry-token:{registry}");
+    Entry::new(&label, namespace.unwrap_or(REGISTRY_TOKEN_USER))
+        .context("failed to get keyring entry")
+}
+
+/// Gets the authentication token for the given registry and namespace.
+///
+/// A token scoped to the namespace takes precedence over a token for the
+/// whole registry.
+pub fn get_registry_token(registry: &str, namespace: Option<&str>) -> Result<Option<String>> {
+    for namespace in namespace.map(Some).into_iter().chain([None]) {
+        let entry = get_registry_token_entry(registry, namespace)?;
+        match entry.get_password() {
+            Ok(token) => return Ok(Some(token)),
+            Err(keyring::Error::NoEntry) => continue,
+            Err(keyring::Error::Ambiguous(_)) => {
+                bail!("more than one authentication token found for registry `{registry}`");
+            }
+            Err(e) => {
+                bail!("failed to get authentication token for registry `{registry}`: {e}");
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Finds the authentication token to send to the given registry, if any.
+///
+/// Unlike `get_registry_token`, a keyring that cannot be accessed is treated
+/// as having no token, so that public registries can be used without one.
+pub fn find_registry_token(registry: &str, namespace: Option<&str>) -> Option<String> {
+    match get_registry_token(registry, namespace) {
+        Ok(token) => token,
+        Err(e) => {
+            log::debug!("not using an authentication token: {e:#}");
+            None
+        }
+    }
+}
+
+/// Sets the authentication token for the given registry and namespace.
+pub fn set_registry_token(registry: &str, namespace: Option<&str>, token: &str) -> Result<()> {
+    let entry = get_registry_token_entry(registry, namespace)?;
+    entry
+        .set_password(token)
+        .with_context(|| format!("failed to set authentication token for registry `{registry}`"))
+}
+
+/// Deletes the authentication token for the given registry and namespace.
+///
+/// Returns `false` if there was no token to delete.
+pub fn delete_registry_token(registry: &str, namespace: Option<&str>) -> Result<bool> {
+    let entry = get_registry_token_entry(registry, namespace)?;
+    match entry.delete_password() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => {
+            bail!("failed to delete authentication token for registry `{registry}`: {e}");
+        }
+    }
+}
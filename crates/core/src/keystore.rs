@@ -0,0 +1,365 @@
+//! Module for the encrypted file-based store of signing keys.
+//!
+//! A keystore is an alternative to the system keyring for environments where
+//! the keyring is unavailable, such as headless CI machines. The keys are
+//! encrypted with a key derived from a passphrase, which is read from the
+//! `SIGNING_KEYSTORE_PASSPHRASE` environment variable, so that a CI job only
+//! needs the keystore file and a passphrase secret rather than raw private
+//! keys.
+//!
+//! The key used to encrypt the signing keys is derived from the passphrase
+//! with Argon2id, and each signing key is encrypted with XChaCha20-Poly1305
+//! under a random nonce, with the registry and name of the key as associated
+//! data so that an entry cannot be moved to another key without detection.
+
+use anyhow::{anyhow, bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use warg_client::RegistryUrl;
+use warg_crypto::signing::PrivateKey;
+
+/// The environment variable containing the passphrase of a keystore.
+pub const KEYSTORE_PASSPHRASE_ENV_VAR: &str = "SIGNING_KEYSTORE_PASSPHRASE";
+
+/// The name of the default keystore file in the user's configuration directory.
+const KEYSTORE_FILE_NAME: &str = "keystore.json";
+
+/// The version of the keystore file format.
+const KEYSTORE_VERSION: u32 = 1;
+
+/// The name of the only supported key derivation algorithm.
+const KDF_ALGORITHM: &str = "argon2id";
+
+/// The Argon2id memory cost, in KiB, used for new keystores.
+const KDF_MEMORY: u32 = 19 * 1024;
+
+/// The Argon2id number of passes used for new keystores.
+const KDF_ITERATIONS: u32 = 2;
+
+/// The Argon2id degree of parallelism used for new keystores.
+const KDF_PARALLELISM: u32 = 1;
+
+/// The length of the nonce of an encrypted signing key.
+const NONCE_LEN: usize = 24;
+
+/// Gets the path to the default keystore in the user's configuration directory.
+///
+/// The default keystore is used when the system keyring cannot be accessed.
+pub fn default_keystore_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("cargo-component").join(KEYSTORE_FILE_NAME))
+}
+
+/// Reads the passphrase of the given keystore from the environment.
+pub fn passphrase_from_env(path: &Path) -> Result<String> {
+    match std::env::var(KEYSTORE_PASSPHRASE_ENV_VAR) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => bail!(
+            "the `{KEYSTORE_PASSPHRASE_ENV_VAR}` environment variable must be set to the passphrase of keystore `{path}`",
+            path = path.display()
+        ),
+    }
+}
+
+/// Represents the parameters used to derive the key of a keystore from its
+/// passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyDerivation {
+    algorithm: String,
+    memory: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+/// Represents an encrypted signing key in a keystore.
+///
+/// The ciphertext includes the authentication tag of the key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeystoreEntry {
+    registry: String,
+    name: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Represents an encrypted file-based store of signing keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    version: u32,
+    kdf: KeyDerivation,
+    keys: Vec<KeystoreEntry>,
+}
+
+impl Keystore {
+    /// Creates a new, empty keystore.
+    pub fn new() -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        Self {
+            version: KEYSTORE_VERSION,
+            kdf: KeyDerivation {
+                algorithm: KDF_ALGORITHM.to_string(),
+                memory: KDF_MEMORY,
+                iterations: KDF_ITERATIONS,
+                parallelism: KDF_PARALLELISM,
+                salt: hex::encode(salt),
+            },
+            keys: Vec::new(),
+        }
+    }
+
+    /// Opens the keystore at the given path.
+    ///
+    /// Returns a new, empty keystore if the file does not exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::new());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read keystore `{path}`", path = path.display()))?;
+        let keystore: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse keystore `{path}`", path = path.display()))?;
+
+        if keystore.version != KEYSTORE_VERSION {
+            bail!(
+                "keystore `{path}` has unsupported version {version}",
+                path = path.display(),
+                version = keystore.version
+            );
+        }
+
+        if keystore.kdf.algorithm != KDF_ALGORITHM {
+            bail!(
+                "keystore `{path}` uses unsupported key derivation algorithm `{algorithm}`",
+                path = path.display(),
+                algorithm = keystore.kdf.algorithm
+            );
+        }
+
+        Ok(keystore)
+    }
+
+    /// Saves the keystore to the given path.
+    ///
+    /// On Unix, the file is only readable and writable by its owner.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{path}`",
+                    path = parent.display()
+                )
+            })?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path).with_context(|| {
+            format!("failed to create keystore `{path}`", path = path.display())
+        })?;
+
+        // The mode only applies to new files, so also restrict an existing one
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))
+                .with_context(|| {
+                    format!(
+                        "failed to set permissions of keystore `{path}`",
+                        path = path.display()
+                    )
+                })?;
+        }
+
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())
+            .with_context(|| format!("failed to write keystore `{path}`", path = path.display()))
+    }
+
+    /// Lists the registry URLs and names of the keys in the keystore.
+    ///
+    /// Listing the keys does not require the passphrase.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.keys
+            .iter()
+            .map(|e| (e.registry.as_str(), e.name.as_str()))
+    }
+
+    /// Determines if the keystore has a key for the given registry and name.
+    pub fn contains(&self, registry_url: &RegistryUrl, key_name: &str) -> bool {
+        self.find(registry_url, key_name).is_some()
+    }
+
+    /// Gets the signing key for the given registry and key name.
+    pub fn get(
+        &self,
+        registry_url: &RegistryUrl,
+        key_name: &str,
+        passphrase: &str,
+    ) -> Result<PrivateKey> {
+        let entry = self.find(registry_url, key_name).with_context(|| {
+            format!("no signing key found with name `{key_name}` for registry `{registry_url}` in the keystore")
+        })?;
+
+        let cipher = self.cipher(passphrase)?;
+        let secret = decrypt(&cipher, entry).with_context(|| {
+            format!("failed to decrypt signing key `{key_name}` for registry `{registry_url}`")
+        })?;
+
+        PrivateKey::decode(secret).context("failed to parse signing key")
+    }
+
+    /// Sets the signing key for the given registry and key name.
+    ///
+    /// If the keystore already has keys, the passphrase must be the one they
+    /// were encrypted with.
+    pub fn set(
+        &mut self,
+        registry_url: &RegistryUrl,
+        key_name: &str,
+        key: &PrivateKey,
+        passphrase: &str,
+    ) -> Result<()> {
+        let cipher = self.cipher(passphrase)?;
+        if let Some(existing) = self.keys.first() {
+            decrypt(&cipher, existing).context("the passphrase does not match the keystore")?;
+        }
+
+        let entry = encrypt(
+            &cipher,
+            &registry_url.to_string(),
+            key_name,
+            key.encode().as_bytes(),
+        )?;
+        match self
+            .keys
+            .iter_mut()
+            .find(|e| e.registry == entry.registry && e.name == entry.name)
+        {
+            Some(existing) => *existing = entry,
+            None => self.keys.push(entry),
+        }
+
+        Ok(())
+    }
+
+    /// Removes the signing key for the given registry and key name.
+    ///
+    /// Returns `false` if the keystore has no such key.
+    pub fn remove(&mut self, registry_url: &RegistryUrl, key_name: &str) -> bool {
+        let registry = registry_url.to_string();
+        let len = self.keys.len();
+        self.keys
+            .retain(|e| e.registry != registry || e.name != key_name);
+        self.keys.len() != len
+    }
+
+    fn find(&self, registry_url: &RegistryUrl, key_name: &str) -> Option<&KeystoreEntry> {
+        let registry = registry_url.to_string();
+        self.keys
+            .iter()
+            .find(|e| e.registry == registry && e.name == key_name)
+    }
+
+    fn cipher(&self, passphrase: &str) -> Result<XChaCha20Poly1305> {
+        let salt = hex::decode(&self.kdf.salt).context("invalid keystore salt")?;
+        let params = Params::new(
+            self.kdf.memory,
+            self.kdf.iterations,
+            self.kdf.parallelism,
+            Some(32),
+        )
+        .map_err(|e| anyhow!("invalid keystore key derivation parameters: {e}"))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| anyhow!("failed to derive the keystore key: {e}"))?;
+
+        Ok(XChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+}
+
+impl Default for Keystore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Encrypts a signing key for the given registry and key name.
+fn encrypt(
+    cipher: &XChaCha20Poly1305,
+    registry: &str,
+    name: &str,
+    plaintext: &[u8],
+) -> Result<KeystoreEntry> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &associated_data(registry, name),
+            },
+        )
+        .map_err(|_| anyhow!("failed to encrypt signing key"))?;
+
+    Ok(KeystoreEntry {
+        registry: registry.to_string(),
+        name: name.to_string(),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+/// Decrypts the signing key of a keystore entry.
+fn decrypt(cipher: &XChaCha20Poly1305, entry: &KeystoreEntry) -> Result<String> {
+    let nonce = hex::decode(&entry.nonce).context("invalid keystore entry nonce")?;
+    if nonce.len() != NONCE_LEN {
+        bail!("invalid keystore entry nonce");
+    }
+
+    let ciphertext = hex::decode(&entry.ciphertext).context("invalid keystore entry ciphertext")?;
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &associated_data(&entry.registry, &entry.name),
+            },
+        )
+        .map_err(|_| anyhow!("the passphrase is incorrect or the keystore was modified"))?;
+
+    String::from_utf8(plaintext).context("decrypted signing key is not valid UTF-8")
+}
+
+/// Gets the associated data binding an encrypted signing key to its registry
+/// and name.
+fn associated_data(registry: &str, name: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    for part in [registry, name] {
+        data.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        data.extend_from_slice(part.as_bytes());
+    }
+    data
+}
@@ -7,11 +7,21 @@ use semver::VersionReq;
 use std::str::FromStr;
 use warg_protocol::registry::PackageId;
 
+pub mod audit;
+pub mod bindings;
+pub mod cache;
 pub mod command;
+pub mod git;
+pub mod http;
+pub mod info;
 pub mod keyring;
+pub mod keystore;
 pub mod lock;
+pub mod oci;
 pub mod progress;
 pub mod registry;
+pub mod signing;
+pub mod static_registry;
 pub mod terminal;
 
 /// Represents a versioned component package identifier.
@@ -1,6 +1,9 @@
 //! Module for the lock file implementation.
 
-use crate::registry::DEFAULT_REGISTRY_NAME;
+use crate::{
+    git::GitReference,
+    registry::{DependencyResolution, DEFAULT_REGISTRY_NAME},
+};
 use anyhow::{anyhow, bail, Context, Result};
 use semver::{Version, VersionReq};
 use serde::{de::IntoDeserializer, Deserialize, Serialize};
@@ -63,6 +66,24 @@ impl LockedPackageVersion {
     }
 }
 
+/// Represents a locked git repository in a lock file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LockedGitSource {
+    /// The URL of the repository.
+    pub url: String,
+    /// The reference of the repository that was resolved.
+    pub reference: GitReference,
+    /// The commit the reference is locked to.
+    pub commit: String,
+}
+
+impl LockedGitSource {
+    /// Gets the key used in sorting and searching the git source list.
+    pub fn key(&self) -> (&str, String) {
+        (&self.url, self.reference.to_string())
+    }
+}
+
 /// Represents a resolver for a lock file.
 #[derive(Clone, Copy, Debug)]
 pub struct LockFileResolver<'a>(&'a LockFile);
@@ -104,6 +125,23 @@ impl<'a> LockFileResolver<'a> {
         log::info!("dependency package `{id}` from registry `{registry}` with requirement `{requirement}` was not in the lock file");
         Ok(None)
     }
+
+    /// Resolves the commit of a git repository reference from the lock file.
+    ///
+    /// Returns `None` if the reference is not in the lock file.
+    pub fn resolve_git(self, url: &str, reference: &GitReference) -> Option<&'a str> {
+        let source = self
+            .0
+            .git
+            .iter()
+            .find(|s| s.url == url && s.reference == *reference)?;
+
+        log::info!(
+            "git repository `{url}` with reference `{reference}` was resolved by the lock file to commit `{commit}`",
+            commit = source.commit
+        );
+        Some(&source.commit)
+    }
 }
 
 /// Represents a resolved dependency lock file.
@@ -117,11 +155,21 @@ pub struct LockFile {
     ///
     /// Currently this is always `1`.
     pub version: i64,
+    /// The directory containing vendored dependency contents.
+    ///
+    /// The path is relative to the directory containing the lock file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<PathBuf>,
     /// The locked dependencies in the lock file.
     ///
     /// This list is sorted by the key of the locked package.
     #[serde(rename = "package", default, skip_serializing_if = "Vec::is_empty")]
     pub packages: Vec<LockedPackage>,
+    /// The locked git repositories in the lock file.
+    ///
+    /// This list is sorted by the key of the locked git source.
+    #[serde(rename = "git", default, skip_serializing_if = "Vec::is_empty")]
+    pub git: Vec<LockedGitSource>,
 }
 
 impl LockFile {
@@ -131,10 +179,32 @@ impl LockFile {
     pub fn new(packages: impl Into<Vec<LockedPackage>>) -> Self {
         Self {
             version: LOCK_FILE_VERSION,
+            vendor: None,
             packages: packages.into(),
+            git: Vec::new(),
         }
     }
 
+    /// Adds the git repositories of the given resolutions to the lock file.
+    pub fn with_git_sources<'a>(
+        mut self,
+        resolutions: impl IntoIterator<Item = &'a DependencyResolution>,
+    ) -> Self {
+        for resolution in resolutions {
+            if let DependencyResolution::Git(res) = resolution {
+                self.git.push(LockedGitSource {
+                    url: res.url.clone(),
+                    reference: res.reference.clone(),
+                    commit: res.commit.clone(),
+                });
+            }
+        }
+
+        self.git.sort_by(|a, b| a.key().cmp(&b.key()));
+        self.git.dedup_by(|a, b| a.key() == b.key());
+        self
+    }
+
     /// Reads the lock file from the given file object.
     pub fn read(mut file: &File) -> Result<Self> {
         let mut contents = String::new();
@@ -178,7 +248,9 @@ impl Default for LockFile {
     fn default() -> Self {
         Self {
             version: LOCK_FILE_VERSION,
+            vendor: None,
             packages: Vec::new(),
+            git: Vec::new(),
         }
     }
 }
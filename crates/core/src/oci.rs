@@ -0,0 +1,630 @@
+//! Module for interacting with OCI registries.
+//!
+//! Packages are stored in an OCI registry as artifacts with a single
+//! `application/wasm` layer containing the component or WIT package.
+//!
+//! A package `<namespace>:<name>` is stored in the repository
+//! `<prefix>/<namespace>/<name>`, where `<prefix>` is the path of the
+//! registry URL; each package version is a tag of that repository.
+//!
+//! OCI registries are configured with a URL using the `oci` scheme
+//! (e.g. `oci://ghcr.io/my-org`); the `oci+http` scheme may be used
+//! for registries that are not served over TLS.
+
+use crate::{cache, keyring};
+use anyhow::{bail, Context, Result};
+use reqwest::{header, Client, RequestBuilder, Response, StatusCode};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Mutex,
+};
+use url::Url;
+use warg_crypto::hash::AnyHash;
+use warg_protocol::registry::PackageId;
+
+/// The URL scheme of OCI registries served over HTTPS.
+pub const OCI_SCHEME: &str = "oci";
+
+/// The URL scheme of OCI registries served over plain HTTP.
+pub const OCI_HTTP_SCHEME: &str = "oci+http";
+
+/// The media type of OCI image manifests.
+pub const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+
+/// The media type of the config blob of a Wasm artifact.
+pub const WASM_CONFIG_MEDIA_TYPE: &str = "application/vnd.wasm.config.v0+json";
+
+/// The media type of the Wasm layer of an artifact.
+pub const WASM_LAYER_MEDIA_TYPE: &str = "application/wasm";
+
+/// The environment variable used for the OCI registry user name.
+pub const USERNAME_ENV_VAR: &str = "OCI_USERNAME";
+
+/// The environment variable used for the OCI registry password or token.
+pub const PASSWORD_ENV_VAR: &str = "OCI_PASSWORD";
+
+/// The user name sent with a token set by `cargo component login`.
+///
+/// OCI registries that accept tokens as passwords ignore the user name.
+const TOKEN_USERNAME: &str = "token";
+
+/// The number of tags to request per page when listing the tags of a repository.
+const TAGS_PAGE_SIZE: usize = 1000;
+
+/// Determines if the given registry URL refers to an OCI registry.
+pub fn is_oci_url(url: &str) -> bool {
+    url.starts_with("oci://") || url.starts_with("oci+http://")
+}
+
+/// Gets the directory used to cache content downloaded from OCI registries.
+///
+/// The cache is shared with the other registry clients.
+pub fn cache_dir(config: &warg_client::Config) -> Result<PathBuf> {
+    cache::content_dir(config)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    media_type: String,
+    digest: String,
+    size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    media_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_type: Option<String>,
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+}
+
+#[derive(Deserialize)]
+struct TagList {
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// Represents a parsed `WWW-Authenticate` challenge.
+#[derive(Debug, PartialEq, Eq)]
+struct Challenge {
+    scheme: String,
+    params: HashMap<String, String>,
+}
+
+impl Challenge {
+    fn parse(s: &str) -> Option<Self> {
+        let (scheme, rest) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        let mut params = HashMap::new();
+        let mut rest = rest.trim();
+        while !rest.is_empty() {
+            let (key, value) = rest.split_once('=')?;
+            let value = value.trim_start();
+            let (value, remaining) = match value.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"')?;
+                    (&quoted[..end], &quoted[end + 1..])
+                }
+                None => match value.find(',') {
+                    Some(end) => (&value[..end], &value[end..]),
+                    None => (value, ""),
+                },
+            };
+
+            params.insert(key.trim().to_ascii_lowercase(), value.to_string());
+            rest = remaining.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        }
+
+        Some(Self {
+            scheme: scheme.to_ascii_lowercase(),
+            params,
+        })
+    }
+}
+
+fn sha256_digest(bytes: &[u8]) -> String {
+    format!("sha256:{hash}", hash = hex::encode(Sha256::digest(bytes)))
+}
+
+fn version_to_tag(version: &Version) -> String {
+    // OCI tags cannot contain `+`, so build metadata uses `_` instead
+    version.to_string().replace('+', "_")
+}
+
+fn tag_to_version(tag: &str) -> Option<Version> {
+    tag.replace('_', "+").parse().ok()
+}
+
+/// A client for an OCI registry.
+pub struct OciClient {
+    client: Client,
+    url: String,
+    base: Url,
+    prefix: String,
+    cache_dir: PathBuf,
+    tokens: Mutex<HashMap<String, String>>,
+}
+
+impl OciClient {
+    /// Creates a new OCI registry client for the given registry URL.
+    ///
+    /// Downloaded content is cached in the given directory.
+    pub fn new(url: &str, cache_dir: PathBuf) -> Result<Self> {
+        let parsed: Url = url
+            .parse()
+            .with_context(|| format!("failed to parse OCI registry URL `{url}`"))?;
+
+        let scheme = match parsed.scheme() {
+            OCI_SCHEME => "https",
+            OCI_HTTP_SCHEME => "http",
+            scheme => bail!("unsupported OCI registry URL scheme `{scheme}`"),
+        };
+
+        let host = parsed
+            .host_str()
+            .with_context(|| format!("OCI registry URL `{url}` does not have a host"))?;
+
+        let base = match parsed.port() {
+            Some(port) => format!("{scheme}://{host}:{port}/"),
+            None => format!("{scheme}://{host}/"),
+        };
+
+        Ok(Self {
+            client: Client::new(),
+            url: url.to_string(),
+            base: base.parse()?,
+            prefix: parsed.path().trim_matches('/').to_string(),
+            cache_dir,
+            tokens: Default::default(),
+        })
+    }
+
+    /// Gets the name of the repository used for the given package.
+    pub fn repository(&self, id: &PackageId) -> String {
+        let path = id.to_string().replace(':', "/");
+        if self.prefix.is_empty() {
+            path
+        } else {
+            format!("{prefix}/{path}", prefix = self.prefix)
+        }
+    }
+
+    /// Gets the location of the given content in the cache, if present.
+    pub fn content_location(&self, digest: &AnyHash) -> Option<PathBuf> {
+        let path = self.content_path(&digest.to_string())?;
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Lists the versions of the given package in the registry.
+    ///
+    /// Tags that are not valid semantic versions are ignored.
+    ///
+    /// The tags are requested in pages, following the `Link` header of each
+    /// page; registries that do not send one are asked for the tags after the
+    /// last tag of a full page.
+    pub async fn list_versions(&self, id: &PackageId) -> Result<Vec<Version>> {
+        let repository = self.repository(id);
+        let mut url = self.tags_url(&repository, None)?;
+        let mut tags = Vec::new();
+        let mut seen = HashSet::new();
+
+        loop {
+            let res = self
+                .send(&repository, false, || self.client.get(url.clone()))
+                .await?;
+
+            if res.status() == StatusCode::NOT_FOUND && tags.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let res = Self::check(res, || format!("failed to list tags of `{repository}`")).await?;
+            let next = self.next_page(&res);
+            let list: TagList = res
+                .json()
+                .await
+                .with_context(|| format!("failed to parse tag list of `{repository}`"))?;
+
+            let page = list.tags.unwrap_or_default();
+            let full = page.len() >= TAGS_PAGE_SIZE;
+            let last = page.last().cloned();
+            let count = tags.len();
+            tags.extend(page.into_iter().filter(|t| seen.insert(t.clone())));
+
+            // Stop if the page had no new tags, in case the registry ignores `last`
+            if tags.len() == count {
+                break;
+            }
+
+            url = match (next, last) {
+                (Some(next), _) => next,
+                (None, Some(last)) if full => self.tags_url(&repository, Some(&last))?,
+                _ => break,
+            };
+        }
+
+        Ok(tags.iter().filter_map(|t| tag_to_version(t)).collect())
+    }
+
+    /// Downloads the given version of a package.
+    ///
+    /// Returns the digest of the content and the path to it in the cache.
+    pub async fn download(&self, id: &PackageId, version: &Version) -> Result<(AnyHash, PathBuf)> {
+        let repository = self.repository(id);
+        let tag = version_to_tag(version);
+        let url = self.url(&format!("v2/{repository}/manifests/{tag}"))?;
+        let res = self
+            .send(&repository, false, || {
+                self.client
+                    .get(url.clone())
+                    .header(header::ACCEPT, MANIFEST_MEDIA_TYPE)
+            })
+            .await?;
+
+        let manifest: Manifest = Self::check(res, || {
+            format!("failed to fetch manifest `{repository}:{tag}`")
+        })
+        .await?
+        .json()
+        .await
+        .with_context(|| format!("failed to parse manifest `{repository}:{tag}`"))?;
+
+        let layer = match manifest
+            .layers
+            .iter()
+            .find(|l| l.media_type == WASM_LAYER_MEDIA_TYPE)
+        {
+            Some(layer) => layer,
+            None => bail!(
+                "manifest `{repository}:{tag}` does not contain a layer of type `{WASM_LAYER_MEDIA_TYPE}`"
+            ),
+        };
+
+        let digest: AnyHash = layer
+            .digest
+            .parse()
+            .with_context(|| format!("invalid layer digest `{digest}`", digest = layer.digest))?;
+
+        if let Some(path) = self.content_location(&digest) {
+            return Ok((digest, path));
+        }
+
+        let url = self.url(&format!(
+            "v2/{repository}/blobs/{digest}",
+            digest = layer.digest
+        ))?;
+        let res = self
+            .send(&repository, false, || self.client.get(url.clone()))
+            .await?;
+        let bytes = Self::check(res, || {
+            format!(
+                "failed to download blob `{digest}` from `{repository}`",
+                digest = layer.digest
+            )
+        })
+        .await?
+        .bytes()
+        .await?;
+
+        let actual = sha256_digest(&bytes);
+        if actual != layer.digest {
+            bail!(
+                "content of `{repository}:{tag}` has digest `{actual}` but the manifest specifies digest `{expected}`",
+                expected = layer.digest
+            );
+        }
+
+        let path = self
+            .content_path(&layer.digest)
+            .context("unsupported content digest algorithm")?;
+        cache::write_content(&path, &bytes)?;
+
+        Ok((digest, path))
+    }
+
+    /// Publishes the given content as a version of a package.
+    ///
+    /// Returns the digest of the published content.
+    pub async fn publish(
+        &self,
+        id: &PackageId,
+        version: &Version,
+        content: &[u8],
+    ) -> Result<AnyHash> {
+        let repository = self.repository(id);
+        let tag = version_to_tag(version);
+
+        let config = b"{}";
+        let config_digest = self.upload_blob(&repository, config).await?;
+        let layer_digest = self.upload_blob(&repository, content).await?;
+
+        let manifest = Manifest {
+            schema_version: 2,
+            media_type: Some(MANIFEST_MEDIA_TYPE.to_string()),
+            artifact_type: Some(WASM_CONFIG_MEDIA_TYPE.to_string()),
+            config: Descriptor {
+                media_type: WASM_CONFIG_MEDIA_TYPE.to_string(),
+                digest: config_digest,
+                size: config.len() as u64,
+            },
+            layers: vec![Descriptor {
+                media_type: WASM_LAYER_MEDIA_TYPE.to_string(),
+                digest: layer_digest.clone(),
+                size: content.len() as u64,
+            }],
+        };
+
+        let body = serde_json::to_vec(&manifest)?;
+        let url = self.url(&format!("v2/{repository}/manifests/{tag}"))?;
+        let res = self
+            .send(&repository, true, || {
+                self.client
+                    .put(url.clone())
+                    .header(header::CONTENT_TYPE, MANIFEST_MEDIA_TYPE)
+                    .body(body.clone())
+            })
+            .await?;
+
+        Self::check(res, || {
+            format!("failed to push manifest `{repository}:{tag}`")
+        })
+        .await?;
+
+        // Cache the published content so that it does not need to be downloaded again
+        if let Some(path) = self.content_path(&layer_digest) {
+            cache::write_content(&path, content)?;
+        }
+
+        layer_digest
+            .parse()
+            .with_context(|| format!("invalid layer digest `{layer_digest}`"))
+    }
+
+    async fn upload_blob(&self, repository: &str, bytes: &[u8]) -> Result<String> {
+        let digest = sha256_digest(bytes);
+
+        // Skip the upload if the registry already has the blob
+        let url = self.url(&format!("v2/{repository}/blobs/{digest}"))?;
+        let res = self
+            .send(repository, true, || self.client.head(url.clone()))
+            .await?;
+        if res.status().is_success() {
+            return Ok(digest);
+        }
+
+        let url = self.url(&format!("v2/{repository}/blobs/uploads/"))?;
+        let res = self
+            .send(repository, true, || self.client.post(url.clone()))
+            .await?;
+        let res = Self::check(res, || {
+            format!("failed to start blob upload to `{repository}`")
+        })
+        .await?;
+
+        let location = res
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .with_context(|| {
+                format!("blob upload to `{repository}` did not return an upload location")
+            })?;
+
+        let mut url = self.base.join(location).with_context(|| {
+            format!("blob upload to `{repository}` returned an invalid location `{location}`")
+        })?;
+        url.query_pairs_mut().append_pair("digest", &digest);
+
+        let res = self
+            .send(repository, true, || {
+                self.client
+                    .put(url.clone())
+                    .header(header::CONTENT_TYPE, "application/octet-stream")
+                    .body(bytes.to_vec())
+            })
+            .await?;
+
+        Self::check(res, || {
+            format!("failed to upload blob `{digest}` to `{repository}`")
+        })
+        .await?;
+
+        Ok(digest)
+    }
+
+    fn tags_url(&self, repository: &str, last: Option<&str>) -> Result<Url> {
+        let mut url = self.url(&format!("v2/{repository}/tags/list"))?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.append_pair("n", &TAGS_PAGE_SIZE.to_string());
+            if let Some(last) = last {
+                pairs.append_pair("last", last);
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// Gets the URL of the next page of a response from its `Link` header.
+    ///
+    /// The header is of the form `</v2/<name>/tags/list?n=<n>&last=<tag>>; rel="next"`.
+    fn next_page(&self, res: &Response) -> Option<Url> {
+        let link = res.headers().get(header::LINK)?.to_str().ok()?;
+        link.split(',').find_map(|link| {
+            let (target, params) = link.split_once(';')?;
+            if !params
+                .split(';')
+                .any(|p| p.trim().eq_ignore_ascii_case(r#"rel="next""#))
+            {
+                return None;
+            }
+
+            let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+            self.base.join(target).ok()
+        })
+    }
+
+    fn url(&self, path: &str) -> Result<Url> {
+        self.base
+            .join(path)
+            .with_context(|| format!("failed to create OCI registry URL for `{path}`"))
+    }
+
+    fn content_path(&self, digest: &str) -> Option<PathBuf> {
+        cache::content_path(&self.cache_dir, digest)
+    }
+
+    /// Gets the credentials for the given repository.
+    ///
+    /// The environment variables take precedence over a token in the keyring.
+    fn credentials(&self, repository: &str) -> Option<(String, String)> {
+        if let Ok(username) = std::env::var(USERNAME_ENV_VAR) {
+            let password = std::env::var(PASSWORD_ENV_VAR).unwrap_or_default();
+            return Some((username, password));
+        }
+
+        // The namespace is the second to last component of the repository
+        let namespace = repository.rsplit('/').nth(1);
+        keyring::find_registry_token(&self.url, namespace)
+            .map(|token| (TOKEN_USERNAME.to_string(), token))
+    }
+
+    async fn check(res: Response, msg: impl FnOnce() -> String) -> Result<Response> {
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+
+        let body = res.text().await.unwrap_or_default();
+        bail!(
+            "{msg}: registry returned status {status}{sep}{body}",
+            msg = msg(),
+            sep = if body.is_empty() { "" } else { ": " },
+            body = body.trim()
+        )
+    }
+
+    /// Sends a request, authenticating with the registry if challenged.
+    async fn send(
+        &self,
+        repository: &str,
+        push: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response> {
+        let scope = format!(
+            "repository:{repository}:{actions}",
+            actions = if push { "pull,push" } else { "pull" }
+        );
+
+        let token = self.tokens.lock().unwrap().get(&scope).cloned();
+        let mut builder = request();
+        if let Some(token) = &token {
+            builder = builder.bearer_auth(token);
+        }
+
+        let res = builder
+            .send()
+            .await
+            .context("failed to send request to OCI registry")?;
+        if res.status() != StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
+
+        let challenge = match res
+            .headers()
+            .get(header::WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(Challenge::parse)
+        {
+            Some(challenge) => challenge,
+            None => return Ok(res),
+        };
+
+        let builder = match challenge.scheme.as_str() {
+            "bearer" => {
+                let token = self.fetch_token(&challenge, repository, &scope).await?;
+                self.tokens
+                    .lock()
+                    .unwrap()
+                    .insert(scope.clone(), token.clone());
+                request().bearer_auth(token)
+            }
+            "basic" => match self.credentials(repository) {
+                Some((username, password)) => request().basic_auth(username, Some(password)),
+                None => bail!(
+                    "OCI registry requires authentication; run `cargo component login {url}` or set the `{USERNAME_ENV_VAR}` and `{PASSWORD_ENV_VAR}` environment variables",
+                    url = self.url
+                ),
+            },
+            _ => return Ok(res),
+        };
+
+        builder
+            .send()
+            .await
+            .context("failed to send request to OCI registry")
+    }
+
+    async fn fetch_token(
+        &self,
+        challenge: &Challenge,
+        repository: &str,
+        scope: &str,
+    ) -> Result<String> {
+        let realm = challenge
+            .params
+            .get("realm")
+            .context("OCI registry authentication challenge does not specify a realm")?;
+
+        let mut url: Url = realm
+            .parse()
+            .with_context(|| format!("invalid OCI registry authentication realm `{realm}`"))?;
+
+        {
+            let mut pairs = url.query_pairs_mut();
+            if let Some(service) = challenge.params.get("service") {
+                pairs.append_pair("service", service);
+            }
+            pairs.append_pair("scope", scope);
+        }
+
+        let mut builder = self.client.get(url);
+        if let Some((username, password)) = self.credentials(repository) {
+            builder = builder.basic_auth(username, Some(password));
+        }
+
+        let res = builder
+            .send()
+            .await
+            .with_context(|| format!("failed to request token from `{realm}`"))?;
+
+        let token: TokenResponse = Self::check(res, || {
+            format!("failed to authenticate with OCI registry at `{realm}`")
+        })
+        .await?
+        .json()
+        .await
+        .with_context(|| format!("failed to parse token response from `{realm}`"))?;
+
+        token
+            .token
+            .or(token.access_token)
+            .with_context(|| format!("token response from `{realm}` did not contain a token"))
+    }
+}
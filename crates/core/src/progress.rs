@@ -3,7 +3,7 @@
 //! This is heavily influenced by the `cargo` implementation so
 //! that it has the same appearance.
 
-use crate::terminal::{Terminal, Verbosity};
+use crate::terminal::{MessageFormat, Terminal, Verbosity};
 use anyhow::Result;
 use owo_colors::OwoColorize;
 use std::io::{stderr, Write};
@@ -77,7 +77,7 @@ impl<'a> ProgressBar<'a> {
     /// The progress bar may be created in a disabled state if the user has
     /// disabled progress display (such as with quiet verbosity).
     pub fn with_style(name: &str, style: ProgressStyle, terminal: &'a Terminal) -> Self {
-        // report no progress when -q (for quiet) or TERM=dumb are set
+        // report no progress when -q (for quiet), JSON messages, or TERM=dumb are set
         // or if running on Continuous Integration service like Travis where the
         // output logs get mangled.
         let dumb = match std::env::var("TERM") {
@@ -86,7 +86,11 @@ impl<'a> ProgressBar<'a> {
         };
 
         let verbosity = terminal.verbosity();
-        if verbosity == Verbosity::Quiet || dumb || is_ci() {
+        if verbosity == Verbosity::Quiet
+            || terminal.message_format() == MessageFormat::Json
+            || dumb
+            || is_ci()
+        {
             return Self { state: None };
         }
 
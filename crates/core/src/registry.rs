@@ -1,38 +1,52 @@
 //! Module for resolving dependencies from a component registry.
 
 use crate::{
+    git::{self, GitDependency, GitReference},
     lock::{LockFileResolver, LockedPackageVersion},
+    oci::{self, OciClient},
     progress::{ProgressBar, ProgressStyle},
+    static_registry::{self, StaticClient},
     terminal::{Colors, Terminal},
 };
 use anyhow::{bail, Context, Result};
-use futures::{stream::FuturesUnordered, StreamExt};
+use futures::{
+    stream::{self, FuturesUnordered},
+    StreamExt, TryStreamExt,
+};
 use indexmap::IndexMap;
 use semver::{Comparator, Op, Version, VersionReq};
 use serde::{
     de::{self, value::MapAccessDeserializer},
     Deserialize, Serialize,
 };
+use sha2::{Digest, Sha256};
 use std::{
     collections::{hash_map, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
+    time::Duration,
 };
 use url::Url;
 use warg_client::{
-    storage::{ContentStorage, PackageInfo, RegistryStorage},
-    Config, FileSystemClient, StorageLockResult,
+    storage::{ContentStorage, PackageInfo, PublishEntry, PublishInfo, RegistryStorage},
+    ClientError, Config, FileSystemClient, StorageLockResult,
 };
-use warg_crypto::hash::AnyHash;
-use warg_protocol::registry::PackageId;
+use warg_crypto::{hash::AnyHash, signing::PrivateKey};
+use warg_protocol::{package, registry::PackageId};
 use wit_component::DecodedWasm;
-use wit_parser::{PackageName, Resolve, UnresolvedPackage, WorldId};
+use wit_parser::{
+    PackageName, Resolve, Type, TypeDefKind, TypeId, TypeOwner, UnresolvedPackage, WorldId,
+    WorldItem,
+};
 
 /// The name of the default registry.
 pub const DEFAULT_REGISTRY_NAME: &str = "default";
 
+/// The maximum number of packages to download from registries at a time.
+const MAX_CONCURRENT_DOWNLOADS: usize = 16;
+
 /// Finds the URL for the given registry name.
 pub fn find_url<'a>(
     name: Option<&str>,
@@ -49,6 +63,135 @@ pub fn find_url<'a>(
     }
 }
 
+/// Represents the registries a namespace is mapped to.
+///
+/// This is either the name of a registry or a list of registry names tried
+/// in order when resolving dependencies (e.g. an internal mirror followed by
+/// the public registry).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NamespaceRegistries {
+    /// The namespace is mapped to a single registry.
+    Single(String),
+    /// The namespace is mapped to registries tried in order.
+    Fallbacks(Vec<String>),
+}
+
+impl NamespaceRegistries {
+    /// Gets the names of the registries in the order they are tried.
+    pub fn names(&self) -> &[String] {
+        match self {
+            Self::Single(name) => std::slice::from_ref(name),
+            Self::Fallbacks(names) => names,
+        }
+    }
+}
+
+/// Finds the name of the registry mapped to the namespace of the given package.
+///
+/// If the namespace is mapped to several registries, this is the first of
+/// them, which packages of the namespace are published to.
+///
+/// Returns `None` if the namespace of the package is not mapped to a registry.
+pub fn find_namespace_registry<'a>(
+    namespaces: &'a HashMap<String, NamespaceRegistries>,
+    id: &PackageId,
+) -> Option<&'a str> {
+    find_namespace_registries(namespaces, id)
+        .first()
+        .map(String::as_str)
+}
+
+/// Finds the names of the registries mapped to the namespace of the given
+/// package, in the order they are tried.
+///
+/// A key of the mapping is either a namespace (e.g. `mycorp`) or a pattern
+/// where `*` matches any characters; a pattern containing `:` is matched
+/// against the whole package name (e.g. `wasi:*` or `mycorp:internal-*`) and
+/// any other pattern against the namespace (e.g. `mycorp-*`). A namespace
+/// takes precedence over a pattern, and a pattern with more characters other
+/// than `*` takes precedence over one with fewer.
+///
+/// Returns an empty slice if the package is not mapped to a registry.
+pub fn find_namespace_registries<'a>(
+    namespaces: &'a HashMap<String, NamespaceRegistries>,
+    id: &PackageId,
+) -> &'a [String] {
+    if let Some(registries) = namespaces.get(id.namespace()) {
+        return registries.names();
+    }
+
+    let name = id.to_string();
+    namespaces
+        .iter()
+        .filter(|(pattern, _)| {
+            if pattern.contains(':') {
+                matches_pattern(pattern, &name)
+            } else {
+                matches_pattern(pattern, id.namespace())
+            }
+        })
+        // Prefer the most specific pattern; ties are broken by the pattern itself
+        .max_by_key(|(pattern, _)| {
+            (
+                pattern.chars().filter(|c| *c != '*').count(),
+                pattern.as_str(),
+            )
+        })
+        .map(|(_, registries)| registries.names())
+        .unwrap_or_default()
+}
+
+/// Determines if a string matches a pattern where `*` matches any characters.
+fn matches_pattern(pattern: &str, s: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, rest)) => match s.strip_prefix(prefix) {
+            Some(s) => s
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(s.len()))
+                .any(|i| matches_pattern(rest, &s[i..])),
+            None => false,
+        },
+        None => pattern == s,
+    }
+}
+
+/// Gets the path of vendored content with the given digest in a vendor directory.
+pub fn vendored_path(dir: &Path, digest: &AnyHash) -> PathBuf {
+    dir.join(format!("{digest}.wasm").replace(':', "-"))
+}
+
+/// Computes the digest of the given content, as registries identify it.
+pub fn content_digest(bytes: &[u8]) -> AnyHash {
+    format!("sha256:{hash}", hash = hex::encode(Sha256::digest(bytes)))
+        .parse()
+        .expect("a SHA-256 digest should be valid")
+}
+
+/// Verifies that the content at the given path has the given digest.
+///
+/// This guards against cached or vendored content that was modified after it
+/// was downloaded.
+pub fn verify_digest(path: &Path, digest: &AnyHash) -> Result<()> {
+    let expected = digest.to_string();
+    if !expected.starts_with("sha256:") {
+        bail!("unsupported content digest algorithm for digest `{expected}`");
+    }
+
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read content `{path}`", path = path.display()))?;
+    let actual = content_digest(&bytes).to_string();
+    if actual != expected {
+        bail!(
+            "content `{path}` has digest `{actual}` but digest `{expected}` was expected; the content may have been modified or corrupted",
+            path = path.display()
+        );
+    }
+
+    Ok(())
+}
+
 /// Creates a registry client with the given warg configuration.
 pub fn create_client(
     config: &warg_client::Config,
@@ -69,6 +212,80 @@ pub fn create_client(
     }
 }
 
+/// Yanks a release of a package from a component registry.
+///
+/// Yanked releases are no longer selected when resolving dependencies, but
+/// dependencies already locked to a yanked release continue to use it while
+/// its content is cached.
+///
+/// Only warg registries support yanking releases.
+pub async fn yank(
+    config: &warg_client::Config,
+    url: &str,
+    id: &PackageId,
+    version: &Version,
+    signing_key: &PrivateKey,
+    terminal: &Terminal,
+) -> Result<()> {
+    if oci::is_oci_url(url) {
+        bail!("yanking releases from OCI registry `{url}` is not supported");
+    }
+
+    if static_registry::is_static_url(url) {
+        bail!("yanking releases from static registry `{url}` is not supported; mark the version as yanked in the package's `index.json` instead");
+    }
+
+    let client = create_client(config, url, terminal)?;
+    match client.upsert([id]).await {
+        Ok(()) => {}
+        Err(ClientError::PackageDoesNotExist { .. }) => {
+            bail!("package `{id}` was not found in component registry `{url}`")
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to update package log for `{id}`"))
+        }
+    }
+
+    let package =
+        client.registry().load_package(id).await?.with_context(|| {
+            format!("package `{id}` was not found in component registry `{url}`")
+        })?;
+
+    match package.state.release(version) {
+        Some(release) if release.content().is_none() => {
+            bail!("version {version} of package `{id}` is already yanked")
+        }
+        Some(_) => {}
+        None => bail!("package `{id}` has no release with version {version}"),
+    }
+
+    terminal.status("Yanking", format!("package `{id}` v{version}"))?;
+
+    let info = PublishInfo {
+        id: id.clone(),
+        head: None,
+        entries: vec![PublishEntry::Yank {
+            version: version.clone(),
+        }],
+    };
+
+    let record_id = client.publish_with_info(signing_key, info).await?;
+    client
+        .wait_for_publish(id, &record_id, Duration::from_secs(1))
+        .await?;
+
+    terminal.status("Yanked", format!("package `{id}` v{version}"))?;
+    terminal.event(
+        "yanked",
+        serde_json::json!({
+            "package": id.to_string(),
+            "version": version.to_string(),
+            "registry": url,
+            "record": record_id.to_string(),
+        }),
+    )
+}
+
 /// Represents a WIT package dependency.
 #[derive(Debug, Clone)]
 pub enum Dependency {
@@ -77,6 +294,9 @@ pub enum Dependency {
 
     /// The dependency is a path to a local directory or file.
     Local(PathBuf),
+
+    /// The dependency is a git repository.
+    Git(GitDependency),
 }
 
 impl Serialize for Dependency {
@@ -86,7 +306,10 @@ impl Serialize for Dependency {
     {
         match self {
             Self::Package(package) => {
-                if package.id.is_none() && package.registry.is_none() {
+                if package.id.is_none()
+                    && package.registry.is_none()
+                    && package.interfaces.is_empty()
+                {
                     let version = package.version.to_string();
                     version.trim_start_matches('^').serialize(serializer)
                 } else {
@@ -95,12 +318,15 @@ impl Serialize for Dependency {
                         package: Option<&'a PackageId>,
                         version: &'a str,
                         registry: Option<&'a str>,
+                        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+                        interfaces: &'a [String],
                     }
 
                     Entry {
                         package: package.id.as_ref(),
                         version: package.version.to_string().trim_start_matches('^'),
                         registry: package.registry.as_deref(),
+                        interfaces: &package.interfaces,
                     }
                     .serialize(serializer)
                 }
@@ -113,6 +339,36 @@ impl Serialize for Dependency {
 
                 Entry { path }.serialize(serializer)
             }
+            Self::Git(git) => {
+                #[derive(Serialize)]
+                struct Entry<'a> {
+                    git: &'a str,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    branch: Option<&'a str>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    tag: Option<&'a str>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    rev: Option<&'a str>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    path: Option<&'a PathBuf>,
+                }
+
+                let (branch, tag, rev) = match &git.reference {
+                    GitReference::DefaultBranch => (None, None, None),
+                    GitReference::Branch(branch) => (Some(branch.as_str()), None, None),
+                    GitReference::Tag(tag) => (None, Some(tag.as_str()), None),
+                    GitReference::Rev(rev) => (None, None, Some(rev.as_str())),
+                };
+
+                Entry {
+                    git: &git.url,
+                    branch,
+                    tag,
+                    rev,
+                    path: git.path.as_ref(),
+                }
+                .serialize(serializer)
+            }
         }
     }
 }
@@ -149,10 +405,56 @@ impl<'de> Deserialize<'de> for Dependency {
                     package: Option<PackageId>,
                     version: Option<VersionReq>,
                     registry: Option<String>,
+                    git: Option<String>,
+                    branch: Option<String>,
+                    tag: Option<String>,
+                    rev: Option<String>,
+                    interfaces: Vec<String>,
                 }
 
                 let entry = Entry::deserialize(MapAccessDeserializer::new(map))?;
 
+                if !entry.interfaces.is_empty() && (entry.git.is_some() || entry.path.is_some()) {
+                    return Err(de::Error::custom(
+                        "the `interfaces` field may only be specified in a registry dependency entry",
+                    ));
+                }
+
+                if let Some(url) = entry.git {
+                    if entry.package.is_some()
+                        || entry.version.is_some()
+                        || entry.registry.is_some()
+                    {
+                        return Err(de::Error::custom(
+                            "cannot specify `package`, `version`, or `registry` fields in a git dependency entry",
+                        ));
+                    }
+
+                    let reference = match (entry.branch, entry.tag, entry.rev) {
+                        (None, None, None) => GitReference::DefaultBranch,
+                        (Some(branch), None, None) => GitReference::Branch(branch),
+                        (None, Some(tag), None) => GitReference::Tag(tag),
+                        (None, None, Some(rev)) => GitReference::Rev(rev),
+                        _ => {
+                            return Err(de::Error::custom(
+                                "only one of `branch`, `tag`, or `rev` may be specified in a git dependency entry",
+                            ))
+                        }
+                    };
+
+                    return Ok(Self::Value::Git(GitDependency {
+                        url,
+                        reference,
+                        path: entry.path,
+                    }));
+                }
+
+                if entry.branch.is_some() || entry.tag.is_some() || entry.rev.is_some() {
+                    return Err(de::Error::custom(
+                        "the `branch`, `tag`, and `rev` fields may only be specified with a `git` field",
+                    ));
+                }
+
                 match (entry.path, entry.package, entry.version, entry.registry) {
                     (Some(path), None, None, None) => Ok(Self::Value::Local(path)),
                     (None, id, Some(version), registry) => {
@@ -160,6 +462,7 @@ impl<'de> Deserialize<'de> for Dependency {
                             id,
                             version,
                             registry,
+                            interfaces: entry.interfaces,
                         }))
                     }
                     (Some(_), None, Some(_), _) => Err(de::Error::custom(
@@ -190,7 +493,7 @@ impl FromStr for Dependency {
 }
 
 /// Represents a reference to a registry package.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RegistryPackage {
     /// The id of the package.
@@ -205,6 +508,12 @@ pub struct RegistryPackage {
     ///
     /// If not specified, the default registry is used.
     pub registry: Option<String>,
+
+    /// The names of the interfaces and worlds of the package to use.
+    ///
+    /// If empty, every interface and world of the package is used.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<String>,
 }
 
 impl FromStr for RegistryPackage {
@@ -215,6 +524,7 @@ impl FromStr for RegistryPackage {
             id: None,
             version: s.parse()?,
             registry: None,
+            interfaces: Vec::new(),
         })
     }
 }
@@ -240,6 +550,10 @@ pub struct RegistryResolution {
     pub digest: AnyHash,
     /// The path to the resolved dependency.
     pub path: PathBuf,
+    /// The names of the interfaces and worlds of the package that are used.
+    ///
+    /// An empty list indicates that every interface and world is used.
+    pub interfaces: Vec<String>,
 }
 
 /// Represents information about a resolution of a local file.
@@ -251,6 +565,21 @@ pub struct LocalResolution {
     pub path: PathBuf,
 }
 
+/// Represents information about a resolution of a git repository.
+#[derive(Clone, Debug)]
+pub struct GitResolution {
+    /// The id of the dependency that was resolved.
+    pub id: PackageId,
+    /// The URL of the git repository.
+    pub url: String,
+    /// The reference of the repository that was resolved.
+    pub reference: GitReference,
+    /// The commit the reference was resolved to.
+    pub commit: String,
+    /// The path to the WIT of the dependency in the checkout of the commit.
+    pub path: PathBuf,
+}
+
 /// Represents a resolution of a dependency.
 #[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
@@ -259,6 +588,8 @@ pub enum DependencyResolution {
     Registry(RegistryResolution),
     /// The dependency is resolved from a local path.
     Local(LocalResolution),
+    /// The dependency is resolved from a git repository.
+    Git(GitResolution),
 }
 
 impl DependencyResolution {
@@ -267,6 +598,7 @@ impl DependencyResolution {
         match self {
             Self::Registry(res) => &res.id,
             Self::Local(res) => &res.id,
+            Self::Git(res) => &res.id,
         }
     }
 
@@ -275,6 +607,37 @@ impl DependencyResolution {
         match self {
             Self::Registry(res) => &res.path,
             Self::Local(res) => &res.path,
+            Self::Git(res) => &res.path,
+        }
+    }
+
+    /// Describes the resolved dependency as a JSON object for
+    /// machine-readable output.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Registry(res) => serde_json::json!({
+                "dependency": self.id().to_string(),
+                "source": "registry",
+                "package": res.package.to_string(),
+                "registry": res.registry.as_deref().unwrap_or(DEFAULT_REGISTRY_NAME),
+                "requirement": res.requirement.to_string(),
+                "version": res.version.to_string(),
+                "digest": res.digest.to_string(),
+                "path": res.path,
+            }),
+            Self::Local(res) => serde_json::json!({
+                "dependency": self.id().to_string(),
+                "source": "local",
+                "path": res.path,
+            }),
+            Self::Git(res) => serde_json::json!({
+                "dependency": self.id().to_string(),
+                "source": "git",
+                "url": res.url,
+                "reference": res.reference.to_string(),
+                "commit": res.commit,
+                "path": res.path,
+            }),
         }
     }
 
@@ -284,7 +647,7 @@ impl DependencyResolution {
     pub fn key(&self) -> Option<(&PackageId, Option<&str>)> {
         match self {
             DependencyResolution::Registry(pkg) => Some((&pkg.package, pkg.registry.as_deref())),
-            DependencyResolution::Local(_) => None,
+            DependencyResolution::Local(_) | DependencyResolution::Git(_) => None,
         }
     }
 
@@ -312,6 +675,15 @@ impl DependencyResolution {
         })?;
 
         if &bytes[0..4] != b"\0asm" {
+            if let Self::Registry(res) = self {
+                if !res.interfaces.is_empty() {
+                    bail!(
+                        "interfaces cannot be selected from dependency `{id}` as it is not a binary-encoded WIT package",
+                        id = res.id
+                    );
+                }
+            }
+
             return Ok(DecodedDependency::Wit {
                 resolution: self,
                 package: UnresolvedPackage::parse(
@@ -326,19 +698,103 @@ impl DependencyResolution {
             });
         }
 
+        let mut decoded = wit_component::decode(&bytes).with_context(|| {
+            format!(
+                "failed to decode content of dependency `{id}` at path `{path}`",
+                id = self.id(),
+                path = self.path().display()
+            )
+        })?;
+
+        if let Self::Registry(res) = self {
+            if !res.interfaces.is_empty() {
+                match &mut decoded {
+                    DecodedWasm::WitPackage(resolve, package) => {
+                        select_interfaces(resolve, *package, &res.interfaces).with_context(
+                            || {
+                                format!(
+                                    "failed to select interfaces of dependency `{id}`",
+                                    id = res.id
+                                )
+                            },
+                        )?
+                    }
+                    DecodedWasm::Component(..) => bail!(
+                        "interfaces cannot be selected from dependency `{id}` as it is a component",
+                        id = res.id
+                    ),
+                }
+            }
+        }
+
         Ok(DecodedDependency::Wasm {
             resolution: self,
-            decoded: wit_component::decode(&bytes).with_context(|| {
-                format!(
-                    "failed to decode content of dependency `{id}` at path `{path}`",
-                    id = self.id(),
-                    path = self.path().display()
-                )
-            })?,
+            decoded,
         })
     }
 }
 
+/// Restricts the interfaces and worlds of a decoded WIT package to the given names.
+///
+/// The interfaces that the selected interfaces and worlds use are kept as
+/// well; all others are removed from the package so that they can't be
+/// referenced by dependent packages.
+fn select_interfaces(
+    resolve: &mut Resolve,
+    package: wit_parser::PackageId,
+    names: &[String],
+) -> Result<()> {
+    // Gets the interface owning the type aliased by the given type, if any
+    let used_interface = |resolve: &Resolve, ty: TypeId| match resolve.types[ty].kind {
+        TypeDefKind::Type(Type::Id(other)) => match resolve.types[other].owner {
+            TypeOwner::Interface(owner) => Some(owner),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let pkg = &resolve.packages[package];
+    let mut pending = Vec::new();
+    let mut worlds = HashSet::new();
+    for name in names {
+        if let Some(id) = pkg.interfaces.get(name) {
+            pending.push(*id);
+        } else if let Some(id) = pkg.worlds.get(name) {
+            worlds.insert(*id);
+            let world = &resolve.worlds[*id];
+            for item in world.imports.values().chain(world.exports.values()) {
+                match item {
+                    WorldItem::Interface(id) => pending.push(*id),
+                    WorldItem::Type(ty) => pending.extend(used_interface(resolve, *ty)),
+                    WorldItem::Function(_) => {}
+                }
+            }
+        } else {
+            bail!(
+                "package `{name}` has no interface or world named `{item}`",
+                name = pkg.name,
+                item = name
+            );
+        }
+    }
+
+    let mut interfaces = HashSet::new();
+    while let Some(id) = pending.pop() {
+        if !interfaces.insert(id) {
+            continue;
+        }
+
+        for ty in resolve.interfaces[id].types.values() {
+            pending.extend(used_interface(resolve, *ty));
+        }
+    }
+
+    let pkg = &mut resolve.packages[package];
+    pkg.interfaces.retain(|_, id| interfaces.contains(id));
+    pkg.worlds.retain(|_, id| worlds.contains(id));
+    Ok(())
+}
+
 /// Represents a decoded dependency.
 pub enum DecodedDependency<'a> {
     /// The dependency decoded from an unresolved WIT package.
@@ -408,7 +864,13 @@ pub struct DependencyResolver<'a> {
     registry_urls: &'a HashMap<String, Url>,
     warg_config: &'a Config,
     lock_file: Option<LockFileResolver<'a>>,
+    vendor_dir: Option<&'a Path>,
+    namespaces: Option<&'a HashMap<String, NamespaceRegistries>>,
+    precise: Option<&'a HashMap<PackageId, Version>>,
+    patches: Option<&'a HashMap<PackageId, Dependency>>,
+    pre_releases: bool,
     registries: IndexMap<&'a str, Registry<'a>>,
+    index_dependencies: Vec<IndexDependency<'a>>,
     resolutions: HashMap<PackageId, DependencyResolution>,
     network_allowed: bool,
 }
@@ -427,54 +889,200 @@ impl<'a> DependencyResolver<'a> {
             registry_urls,
             warg_config,
             lock_file,
+            vendor_dir: None,
+            namespaces: None,
+            precise: None,
+            patches: None,
+            pre_releases: false,
             registries: Default::default(),
+            index_dependencies: Default::default(),
             resolutions: Default::default(),
             network_allowed,
         })
     }
 
+    /// Sets the directory containing vendored dependency contents.
+    ///
+    /// Locked dependencies with vendored contents are resolved without
+    /// accessing the registry.
+    pub fn with_vendor_dir(mut self, dir: Option<&'a Path>) -> Self {
+        self.vendor_dir = dir;
+        self
+    }
+
+    /// Sets the mapping of package namespaces to registry names.
+    ///
+    /// Dependencies that do not specify a registry are resolved from the
+    /// registry mapped to their namespace, if there is one. If the namespace
+    /// is mapped to several registries, the first registry with a lock file
+    /// entry for the dependency is used; otherwise, the first registry with a
+    /// release satisfying its version requirement is used.
+    pub fn with_namespaces(mut self, namespaces: &'a HashMap<String, NamespaceRegistries>) -> Self {
+        self.namespaces = Some(namespaces);
+        self
+    }
+
+    /// Sets whether or not registry packages may resolve to pre-release
+    /// versions that their version requirements don't explicitly allow.
+    ///
+    /// By default, a pre-release version only satisfies a version requirement
+    /// with a pre-release of the same version (e.g. `0.3.0-rc2` satisfies
+    /// `^0.3.0-rc1`, but not `*` or `^0.2`), matching cargo.
+    pub fn with_pre_releases(mut self, pre_releases: bool) -> Self {
+        self.pre_releases = pre_releases;
+        self
+    }
+
+    /// Sets the exact versions to resolve registry packages to.
+    ///
+    /// A registry package with a precise version ignores its lock file entry
+    /// and fails to resolve if the version does not satisfy the dependency's
+    /// version requirement.
+    pub fn with_precise(mut self, precise: Option<&'a HashMap<PackageId, Version>>) -> Self {
+        self.precise = precise;
+        self
+    }
+
+    /// Sets the patches to apply to registry packages.
+    ///
+    /// A dependency on a patched registry package is resolved with the
+    /// patch instead, regardless of the version requirement of the dependency.
+    pub fn with_patches(mut self, patches: &'a HashMap<PackageId, Dependency>) -> Self {
+        self.patches = Some(patches);
+        self
+    }
+
     /// Add a dependency to the resolver.
     pub async fn add_dependency(
         &mut self,
         id: &'a PackageId,
         dependency: &'a Dependency,
     ) -> Result<()> {
+        // The interfaces selected by a dependency apply to its patch as well
+        let interfaces = match dependency {
+            Dependency::Package(package) => package.interfaces.as_slice(),
+            _ => &[],
+        };
+
+        // Replace a patched registry package with its patch
+        let dependency = match (dependency, self.patches) {
+            (Dependency::Package(package), Some(patches)) => {
+                let package_id = package.id.as_ref().unwrap_or(id);
+                match patches.get(package_id) {
+                    Some(patch) => {
+                        log::info!(
+                            "using patch for registry package `{package_id}` of dependency `{id}`"
+                        );
+                        patch
+                    }
+                    None => dependency,
+                }
+            }
+            _ => dependency,
+        };
+
         match dependency {
             Dependency::Package(package) => {
                 // Dependency comes from a registry, add a dependency to the resolver
-                let registry_name = package.registry.as_deref().unwrap_or(DEFAULT_REGISTRY_NAME);
                 let package_id = package.id.clone().unwrap_or_else(|| id.clone());
+                let registry_name = match package.registry.as_deref() {
+                    Some(registry) => registry,
+                    None => {
+                        self.select_namespace_registry(&package_id, &package.version)
+                            .await?
+                    }
+                };
+
+                let precise = self.precise.and_then(|p| p.get(&package_id)).cloned();
+                if let Some(version) = &precise {
+                    if !package.version.matches(version) {
+                        bail!(
+                            "version {version} of package `{package_id}` does not satisfy the version requirement `{req}` of dependency `{id}`",
+                            req = package.version
+                        );
+                    }
+                }
 
-                // Resolve the version from the lock file if there is one
-                let locked = match self.lock_file.as_ref().and_then(|resolver| {
-                    resolver
-                        .resolve(registry_name, &package_id, &package.version)
-                        .transpose()
-                }) {
+                // Resolve the version from the lock file if there is one (and no precise version)
+                let locked = match self
+                    .lock_file
+                    .as_ref()
+                    .filter(|_| precise.is_none())
+                    .and_then(|resolver| {
+                        resolver
+                            .resolve(registry_name, &package_id, &package.version)
+                            .transpose()
+                    }) {
                     Some(Ok(locked)) => Some(locked),
                     Some(Err(e)) => return Err(e),
                     _ => None,
                 };
 
-                let registry = match self.registries.entry(registry_name) {
-                    indexmap::map::Entry::Occupied(e) => e.into_mut(),
-                    indexmap::map::Entry::Vacant(e) => {
-                        let url = find_url(
-                            Some(registry_name),
-                            self.registry_urls,
-                            self.warg_config.default_url.as_deref(),
-                        )?;
-                        e.insert(Registry {
-                            client: Arc::new(create_client(self.warg_config, url, self.terminal)?),
-                            packages: HashMap::new(),
-                            dependencies: Vec::new(),
-                            upserts: HashSet::new(),
-                        })
+                // Use the vendored contents if the locked version was vendored
+                if let (Some(dir), Some(locked)) = (self.vendor_dir, locked) {
+                    let path = vendored_path(dir, &locked.digest);
+                    if path.is_file() {
+                        log::info!(
+                            "version {version} of registry package `{package_id}` from registry `{registry_name}` is vendored",
+                            version = locked.version
+                        );
+
+                        let res = DependencyResolution::Registry(RegistryResolution {
+                            id: id.clone(),
+                            package: package_id,
+                            registry: if registry_name == DEFAULT_REGISTRY_NAME {
+                                None
+                            } else {
+                                Some(registry_name.to_string())
+                            },
+                            requirement: package.version.clone(),
+                            version: locked.version.clone(),
+                            digest: locked.digest.clone(),
+                            path,
+                            interfaces: interfaces.to_vec(),
+                        });
+
+                        let prev = self.resolutions.insert(id.clone(), res);
+                        assert!(prev.is_none());
+                        return Ok(());
                     }
-                };
+                }
+
+                let url = find_url(
+                    Some(registry_name),
+                    self.registry_urls,
+                    self.warg_config.default_url.as_deref(),
+                )?;
+
+                if oci::is_oci_url(url) || static_registry::is_static_url(url) {
+                    // OCI and static registries are resolved separately as they have no package logs
+                    self.index_dependencies.push(IndexDependency {
+                        registry: registry_name,
+                        url,
+                        dependency: RegistryDependency {
+                            id,
+                            package: package_id,
+                            version: &package.version,
+                            interfaces,
+                            locked: locked.map(|l| (l.version.clone(), l.digest.clone())),
+                            precise,
+                            pre_releases: self.pre_releases,
+                            resolution: None,
+                        },
+                    });
+                    return Ok(());
+                }
 
-                registry
-                    .add_dependency(id, package_id, &package.version, registry_name, locked)
+                self.registry(registry_name, url)?
+                    .add_dependency(
+                        id,
+                        package_id,
+                        &package.version,
+                        interfaces,
+                        registry_name,
+                        locked,
+                        precise,
+                    )
                     .await?;
             }
             Dependency::Local(p) => {
@@ -484,6 +1092,40 @@ impl<'a> DependencyResolver<'a> {
                     path: p.clone(),
                 });
 
+                let prev = self.resolutions.insert(id.clone(), res);
+                assert!(prev.is_none());
+            }
+            Dependency::Git(dependency) => {
+                // A git dependency is checked out immediately, at the locked commit if there is one
+                let locked = self
+                    .lock_file
+                    .and_then(|r| r.resolve_git(&dependency.url, &dependency.reference));
+
+                let (commit, checkout) = git::checkout(
+                    &git::cache_dir(self.warg_config)?,
+                    &dependency.url,
+                    &dependency.reference,
+                    locked,
+                    self.network_allowed,
+                    self.terminal,
+                )?;
+
+                let path =
+                    git::find_wit(&checkout, dependency.path.as_deref()).with_context(|| {
+                        format!(
+                            "failed to find the WIT of dependency `{id}` in git repository `{url}`",
+                            url = dependency.url
+                        )
+                    })?;
+
+                let res = DependencyResolution::Git(GitResolution {
+                    id: id.clone(),
+                    url: dependency.url.clone(),
+                    reference: dependency.reference.clone(),
+                    commit,
+                    path,
+                });
+
                 let prev = self.resolutions.insert(id.clone(), res);
                 assert!(prev.is_none());
             }
@@ -492,6 +1134,110 @@ impl<'a> DependencyResolver<'a> {
         Ok(())
     }
 
+    /// Gets the warg registry with the given name, creating its client if needed.
+    fn registry(&mut self, name: &'a str, url: &str) -> Result<&mut Registry<'a>> {
+        Ok(match self.registries.entry(name) {
+            indexmap::map::Entry::Occupied(e) => e.into_mut(),
+            indexmap::map::Entry::Vacant(e) => e.insert(Registry {
+                client: Arc::new(create_client(self.warg_config, url, self.terminal)?),
+                packages: HashMap::new(),
+                dependencies: Vec::new(),
+                upserts: HashSet::new(),
+                pre_releases: self.pre_releases,
+            }),
+        })
+    }
+
+    /// Selects the registry of a dependency that doesn't specify one.
+    ///
+    /// Of the registries mapped to the namespace of the package, the first
+    /// with a lock file entry for the dependency is selected; otherwise, the
+    /// first with a release satisfying the version requirement is selected.
+    /// If none of them has such a release (or network access is disabled),
+    /// the first mapped registry is selected so that errors refer to it.
+    async fn select_namespace_registry(
+        &mut self,
+        id: &PackageId,
+        requirement: &VersionReq,
+    ) -> Result<&'a str> {
+        let names: &[String] = match self.namespaces {
+            Some(namespaces) => find_namespace_registries(namespaces, id),
+            None => &[],
+        };
+
+        let first = match names {
+            [] => return Ok(DEFAULT_REGISTRY_NAME),
+            [name] => return Ok(name.as_str()),
+            [first, ..] => first.as_str(),
+        };
+
+        if let Some(lock_file) = self.lock_file {
+            for name in names {
+                if lock_file.resolve(name, id, requirement)?.is_some() {
+                    return Ok(name.as_str());
+                }
+            }
+        }
+
+        if !self.network_allowed {
+            return Ok(first);
+        }
+
+        for name in names {
+            match self.has_release(name, id, requirement).await {
+                Ok(true) => {
+                    log::info!("resolving package `{id}` from registry `{name}`");
+                    return Ok(name.as_str());
+                }
+                Ok(false) => log::info!(
+                    "registry `{name}` has no release of package `{id}` matching `{requirement}`"
+                ),
+                Err(e) => log::info!("failed to find package `{id}` in registry `{name}`: {e:#}"),
+            }
+        }
+
+        Ok(first)
+    }
+
+    /// Determines if a registry has a release of a package satisfying a
+    /// version requirement.
+    async fn has_release(
+        &mut self,
+        name: &'a str,
+        id: &PackageId,
+        requirement: &VersionReq,
+    ) -> Result<bool> {
+        let url = find_url(
+            Some(name),
+            self.registry_urls,
+            self.warg_config.default_url.as_deref(),
+        )?;
+
+        let pre_releases = self.pre_releases;
+        if oci::is_oci_url(url) || static_registry::is_static_url(url) {
+            let client = IndexClient::new(url, self.warg_config)?;
+            return Ok(client
+                .list_versions(id)
+                .await?
+                .iter()
+                .any(|(v, yanked)| !yanked && matches_version(requirement, v, pre_releases)));
+        }
+
+        let client = self.registry(name, url)?.client.clone();
+        match client.upsert([id]).await {
+            Ok(()) => {}
+            Err(ClientError::PackageDoesNotExist { .. }) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(client
+            .registry()
+            .load_package(id)
+            .await?
+            .map(|p| find_latest_release(&p.state, requirement, pre_releases).is_some())
+            .unwrap_or(false))
+    }
+
     /// Resolve all dependencies.
     ///
     /// This will download all dependencies that are not already present in client storage.
@@ -500,8 +1246,10 @@ impl<'a> DependencyResolver<'a> {
     pub async fn resolve(self) -> Result<DependencyResolutionMap> {
         let Self {
             mut registries,
+            index_dependencies,
             mut resolutions,
             terminal,
+            warg_config,
             network_allowed,
             ..
         } = self;
@@ -518,9 +1266,199 @@ impl<'a> DependencyResolver<'a> {
             assert!(prev.is_none());
         }
 
+        for resolution in
+            Self::resolve_index(index_dependencies, warg_config, terminal, network_allowed).await?
+        {
+            let prev = resolutions.insert(resolution.id().clone(), resolution);
+            assert!(prev.is_none());
+        }
+
+        let mut ids: Vec<_> = resolutions.keys().collect();
+        ids.sort_by_key(|id| id.to_string());
+
+        // Verify the content of every registry dependency matches its digest,
+        // regardless of whether it was downloaded, cached, or vendored
+        for id in &ids {
+            if let DependencyResolution::Registry(res) = &resolutions[*id] {
+                verify_digest(&res.path, &res.digest).with_context(|| {
+                    format!(
+                        "failed to verify the content of component registry package `{name}` (v`{version}`)",
+                        name = res.package,
+                        version = res.version
+                    )
+                })?;
+            }
+        }
+
+        // Report the resolution of every dependency for machine-readable output
+        for id in ids {
+            terminal.event("dependency-resolved", resolutions[id].to_json())?;
+        }
+
         Ok(resolutions)
     }
 
+    /// Resolves the dependencies from registries that list the versions of
+    /// packages instead of keeping package logs (i.e. OCI and static registries).
+    async fn resolve_index(
+        dependencies: Vec<IndexDependency<'a>>,
+        config: &Config,
+        terminal: &Terminal,
+        network_allowed: bool,
+    ) -> Result<Vec<DependencyResolution>> {
+        if dependencies.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut clients: HashMap<&str, IndexClient> = HashMap::new();
+        for dependency in &dependencies {
+            if let hash_map::Entry::Vacant(e) = clients.entry(dependency.url) {
+                e.insert(IndexClient::new(dependency.url, config)?);
+            }
+        }
+
+        let clients = &clients;
+        stream::iter(dependencies)
+            .map(|dependency| async move {
+                Self::resolve_index_dependency(
+                    &clients[dependency.url],
+                    dependency,
+                    terminal,
+                    network_allowed,
+                )
+                .await
+            })
+            .buffer_unordered(MAX_CONCURRENT_DOWNLOADS)
+            .try_collect()
+            .await
+    }
+
+    /// Resolves a dependency from a registry that lists the versions of packages.
+    async fn resolve_index_dependency(
+        client: &IndexClient,
+        IndexDependency {
+            registry,
+            dependency,
+            ..
+        }: IndexDependency<'a>,
+        terminal: &Terminal,
+        network_allowed: bool,
+    ) -> Result<DependencyResolution> {
+        let resolution = |version: Version, digest: AnyHash, path: PathBuf| {
+            DependencyResolution::Registry(RegistryResolution {
+                id: dependency.id.clone(),
+                package: dependency.package.clone(),
+                registry: if registry == DEFAULT_REGISTRY_NAME {
+                    None
+                } else {
+                    Some(registry.to_string())
+                },
+                requirement: dependency.version.clone(),
+                version,
+                digest,
+                path,
+                interfaces: dependency.interfaces.to_vec(),
+            })
+        };
+
+        // The locked version is only used while it satisfies the requirement
+        let locked = dependency.locked.as_ref().filter(|(version, _)| {
+            matches_version(dependency.version, version, dependency.pre_releases)
+        });
+
+        // If the locked content is already cached, there is nothing to download
+        if let Some((version, digest)) = locked {
+            if let Some(path) = client.content_location(digest) {
+                log::info!(
+                    "version {version} of registry package `{name}` from registry `{registry}` is already cached",
+                    name = dependency.package,
+                );
+                return Ok(resolution(version.clone(), digest.clone(), path));
+            }
+        }
+
+        if !network_allowed {
+            bail!("a component package download is required but network access is disabled");
+        }
+
+        let versions = client
+            .list_versions(&dependency.package)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to list versions of package `{name}` from component registry `{registry}`",
+                    name = dependency.package
+                )
+            })?;
+
+        if versions.is_empty() {
+            bail!(
+                "package `{name}` was not found in component registry `{registry}`",
+                name = dependency.package
+            );
+        }
+
+        // Use the precise version if there is one; otherwise prefer the locked version and
+        // fallback to the latest matching release that isn't yanked if it is gone
+        let exists = |version: &Version| versions.iter().any(|(v, _)| v == version);
+        let version = match (&dependency.precise, locked) {
+            (Some(version), _) => {
+                if !exists(version) {
+                    bail!(
+                        "component registry package `{name}` has no release with version {version}",
+                        name = dependency.package
+                    );
+                }
+                version.clone()
+            }
+            (None, Some((version, _))) if exists(version) => version.clone(),
+            _ => versions
+                .iter()
+                .filter(|(v, yanked)| {
+                    !yanked && matches_version(dependency.version, v, dependency.pre_releases)
+                })
+                .map(|(v, _)| v)
+                .max()
+                .cloned()
+                .with_context(|| {
+                    format!(
+                        "component registry package `{name}` has no release matching version requirement `{version}`",
+                        name = dependency.package,
+                        version = dependency.version
+                    )
+                })?,
+        };
+
+        terminal.status(
+            "Downloading",
+            format!(
+                "package `{name}` (v{version}) from component registry `{registry}`",
+                name = dependency.package
+            ),
+        )?;
+
+        let (digest, path) = client
+            .download(&dependency.package, &version)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to download package `{name}` (v{version}) from component registry `{registry}`",
+                    name = dependency.package
+                )
+            })?;
+
+        if let Some((locked_version, locked_digest)) = &dependency.locked {
+            if *locked_version == version && *locked_digest != digest {
+                bail!(
+                    "component registry package `{name}` (v`{version}`) has digest `{digest}` but the lock file specifies digest `{locked_digest}`",
+                    name = dependency.package,
+                );
+            }
+        }
+
+        Ok(resolution(version, digest, path))
+    }
+
     async fn update_packages(
         registries: &mut IndexMap<&'a str, Registry<'a>>,
         terminal: &Terminal,
@@ -605,21 +1543,31 @@ impl<'a> DependencyResolver<'a> {
             let count = downloads.len();
             progress.tick_now(0, count, "")?;
 
-            let mut futures = FuturesUnordered::new();
-            for ((registry_name, package, version), deps) in downloads {
-                let registry_index = registries.get_index_of(registry_name).unwrap();
-                let (_, registry) = registries.get_index(registry_index).unwrap();
-
-                log::info!("downloading content for package `{package}` from component registry `{registry_name}`");
-
-                let client = registry.client.clone();
-                futures.push(tokio::spawn(async move {
-                    let res = client.download_exact(&package, &version).await;
-                    (registry_index, package, version, deps, res)
-                }))
-            }
-
-            assert_eq!(futures.len(), count);
+            let downloads: Vec<_> = downloads
+                .into_iter()
+                .map(|((registry_name, package, version), deps)| {
+                    let registry_index = registries.get_index_of(registry_name).unwrap();
+                    let (_, registry) = registries.get_index(registry_index).unwrap();
+                    (
+                        registry_index,
+                        registry.client.clone(),
+                        package,
+                        version,
+                        deps,
+                    )
+                })
+                .collect();
+
+            // Download at most a bounded number of packages at a time
+            let mut futures = stream::iter(downloads)
+                .map(|(registry_index, client, package, version, deps)| {
+                    log::info!("downloading content for package `{package}` (v{version})");
+                    tokio::spawn(async move {
+                        let res = client.download_exact(&package, &version).await;
+                        (registry_index, package, version, deps, res)
+                    })
+                })
+                .buffer_unordered(MAX_CONCURRENT_DOWNLOADS);
 
             let mut finished = 0;
             while let Some(res) = futures.next().await {
@@ -659,6 +1607,7 @@ impl<'a> DependencyResolver<'a> {
                         version: download.version.clone(),
                         digest: download.digest.clone(),
                         path: download.path.clone(),
+                        interfaces: dependency.interfaces.to_vec(),
                     });
                 }
             }
@@ -684,6 +1633,8 @@ struct Registry<'a> {
     packages: HashMap<PackageId, PackageInfo>,
     dependencies: Vec<RegistryDependency<'a>>,
     upserts: HashSet<PackageId>,
+    /// Whether or not dependencies may resolve to any pre-release version.
+    pre_releases: bool,
 }
 
 impl<'a> Registry<'a> {
@@ -692,14 +1643,19 @@ impl<'a> Registry<'a> {
         id: &'a PackageId,
         package: PackageId,
         version: &'a VersionReq,
+        interfaces: &'a [String],
         registry: &str,
         locked: Option<&LockedPackageVersion>,
+        precise: Option<Version>,
     ) -> Result<()> {
         let dep = RegistryDependency {
             id,
             package: package.clone(),
             version,
+            interfaces,
             locked: locked.map(|l| (l.version.clone(), l.digest.clone())),
+            precise,
+            pre_releases: self.pre_releases,
             resolution: None,
         };
 
@@ -754,18 +1710,49 @@ impl<'a> Registry<'a> {
                     )
                 })?;
 
-            let release = match &dependency.locked {
-                Some((version, digest)) => {
+            // A locked release that has since been yanked continues to be used
+            // if its content is still in client storage
+            if let (None, Some((version, digest))) = (&dependency.precise, &dependency.locked) {
+                let yanked = package
+                    .state
+                    .release(version)
+                    .map(|r| r.content().is_none())
+                    .unwrap_or(false);
+
+                if yanked {
+                    if let Some(path) = client.content().content_location(digest) {
+                        log::info!(
+                            "using yanked version {version} of registry package `{name}` from the lock file",
+                            name = dependency.package,
+                        );
+
+                        dependency.resolution = Some(RegistryResolution {
+                            id: dependency.id.clone(),
+                            package: dependency.package.clone(),
+                            registry: if registry == DEFAULT_REGISTRY_NAME {
+                                None
+                            } else {
+                                Some(registry.to_string())
+                            },
+                            requirement: dependency.version.clone(),
+                            version: version.clone(),
+                            digest: digest.clone(),
+                            path,
+                            interfaces: dependency.interfaces.to_vec(),
+                        });
+                        continue;
+                    }
+                }
+            }
+
+            let release = match (&dependency.precise, &dependency.locked) {
+                (Some(version), _) => {
+                    // The dependency has a precise version, so only an exact match is allowed
+                    package.state.find_latest_release(&exact_version_req(version))
+                }
+                (None, Some((version, digest))) => {
                     // The dependency had a lock file entry, so attempt to do an exact match first
-                    let exact_req = VersionReq {
-                        comparators: vec![Comparator {
-                            op: Op::Exact,
-                            major: version.major,
-                            minor: Some(version.minor),
-                            patch: Some(version.patch),
-                            pre: version.pre.clone(),
-                        }],
-                    };
+                    let exact_req = exact_version_req(version);
 
                     // If an exact match can't be found, fallback to the latest release to
                     // satisfy the version requirement; this can happen when packages are yanked
@@ -779,10 +1766,13 @@ impl<'a> Registry<'a> {
                             );
                         }
                         Ok(r)
-                    }).transpose()?.or_else(|| package.state.find_latest_release(dependency.version))
+                    }).transpose()?.or_else(|| find_latest_release(&package.state, dependency.version, dependency.pre_releases))
                 }
-                None => package.state.find_latest_release(dependency.version),
-            }.with_context(|| format!("component registry package `{name}` has no release matching version requirement `{version}`", name = dependency.package, version = dependency.version))?;
+                (None, None) => find_latest_release(&package.state, dependency.version, dependency.pre_releases),
+            }.with_context(|| match &dependency.precise {
+                Some(version) => format!("component registry package `{name}` has no release with version {version}", name = dependency.package),
+                None => format!("component registry package `{name}` has no release matching version requirement `{version}`", name = dependency.package, version = dependency.version),
+            })?;
 
             let digest = release.content().expect("release must have content");
             match client.content().content_location(digest) {
@@ -801,6 +1791,7 @@ impl<'a> Registry<'a> {
                         version: release.version.clone(),
                         digest: digest.clone(),
                         path,
+                        interfaces: dependency.interfaces.to_vec(),
                     });
 
                     log::info!(
@@ -853,17 +1844,243 @@ impl<'a> Registry<'a> {
 type DownloadMapKey<'a> = (&'a str, PackageId, Version);
 type DownloadMap<'a> = HashMap<DownloadMapKey<'a>, Vec<usize>>;
 
+struct IndexDependency<'a> {
+    /// The name of the registry containing the package.
+    registry: &'a str,
+    /// The URL of the OCI or static registry.
+    url: &'a str,
+    dependency: RegistryDependency<'a>,
+}
+
+/// A client for a registry that lists the versions of packages.
+enum IndexClient {
+    Oci(OciClient),
+    Static(StaticClient),
+}
+
+impl IndexClient {
+    fn new(url: &str, config: &Config) -> Result<Self> {
+        if static_registry::is_static_url(url) {
+            Ok(Self::Static(StaticClient::new(
+                url,
+                static_registry::cache_dir(config)?,
+            )?))
+        } else {
+            Ok(Self::Oci(OciClient::new(url, oci::cache_dir(config)?)?))
+        }
+    }
+
+    fn content_location(&self, digest: &AnyHash) -> Option<PathBuf> {
+        match self {
+            Self::Oci(client) => client.content_location(digest),
+            Self::Static(client) => client.content_location(digest),
+        }
+    }
+
+    /// Lists the versions of a package and whether they were yanked.
+    ///
+    /// OCI registries do not support yanking releases.
+    async fn list_versions(&self, id: &PackageId) -> Result<Vec<(Version, bool)>> {
+        match self {
+            Self::Oci(client) => Ok(client
+                .list_versions(id)
+                .await?
+                .into_iter()
+                .map(|v| (v, false))
+                .collect()),
+            Self::Static(client) => client.list_versions(id).await,
+        }
+    }
+
+    async fn download(&self, id: &PackageId, version: &Version) -> Result<(AnyHash, PathBuf)> {
+        match self {
+            Self::Oci(client) => client.download(id, version).await,
+            Self::Static(client) => client.download(id, version).await,
+        }
+    }
+}
+
 struct RegistryDependency<'a> {
     /// The package ID assigned in the configuration file.
     id: &'a PackageId,
     /// The package ID of the registry package.
     package: PackageId,
     version: &'a VersionReq,
+    /// The names of the interfaces and worlds of the package that are used.
+    interfaces: &'a [String],
     locked: Option<(Version, AnyHash)>,
+    /// The exact version to resolve the package to, if any.
+    precise: Option<Version>,
+    /// Whether or not the package may resolve to any pre-release version.
+    pre_releases: bool,
     resolution: Option<RegistryResolution>,
 }
 
+/// Determines if a version satisfies a version requirement.
+///
+/// Like cargo, a pre-release version only satisfies a requirement with a
+/// pre-release of the same version; if `pre_releases` is set, it also
+/// satisfies the requirements that its release version satisfies (e.g.
+/// `0.3.0-rc1` satisfies `*`), provided that its release version is above the
+/// lower bound of each comparator (e.g. `1.2.4-rc1` satisfies `^1.2.3` but
+/// `1.2.3-rc1` doesn't, as it precedes `1.2.3`).
+pub fn matches_version(requirement: &VersionReq, version: &Version, pre_releases: bool) -> bool {
+    if requirement.matches(version) {
+        return true;
+    }
+
+    if !pre_releases || version.pre.is_empty() {
+        return false;
+    }
+
+    let release = Version::new(version.major, version.minor, version.patch);
+    requirement.matches(&release)
+        && requirement.comparators.iter().all(|c| match c.op {
+            Op::Less | Op::LessEq => true,
+            _ => release > Version::new(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0)),
+        })
+}
+
+/// Finds the latest release of a package log satisfying a version
+/// requirement.
+fn find_latest_release<'b>(
+    state: &'b package::LogState,
+    requirement: &VersionReq,
+    pre_releases: bool,
+) -> Option<&'b package::Release> {
+    if !pre_releases {
+        return state.find_latest_release(requirement);
+    }
+
+    state
+        .releases()
+        .filter(|r| r.content().is_some() && matches_version(requirement, &r.version, true))
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Creates a version requirement matching exactly the given version.
+fn exact_version_req(version: &Version) -> VersionReq {
+    VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Exact,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: Some(version.patch),
+            pre: version.pre.clone(),
+        }],
+    }
+}
+
 /// Represents a map of dependency resolutions.
 ///
 /// The key to the map is the package ID of the dependency.
 pub type DependencyResolutionMap = HashMap<PackageId, DependencyResolution>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn namespaces(entries: &[(&str, &[&str])]) -> HashMap<String, NamespaceRegistries> {
+        entries
+            .iter()
+            .map(|(pattern, names)| {
+                let registries = match names {
+                    [name] => NamespaceRegistries::Single(name.to_string()),
+                    names => NamespaceRegistries::Fallbacks(
+                        names.iter().map(|n| n.to_string()).collect(),
+                    ),
+                };
+                (pattern.to_string(), registries)
+            })
+            .collect()
+    }
+
+    fn find<'a>(namespaces: &'a HashMap<String, NamespaceRegistries>, id: &str) -> &'a [String] {
+        find_namespace_registries(namespaces, &id.parse().unwrap())
+    }
+
+    #[test]
+    fn it_matches_pre_releases() {
+        let matches = |requirement: &str, version: &str, pre_releases| {
+            matches_version(
+                &requirement.parse().unwrap(),
+                &version.parse().unwrap(),
+                pre_releases,
+            )
+        };
+
+        // Without `--pre`, only requirements naming a pre-release match
+        assert!(matches("^0.3.0-rc1", "0.3.0-rc2", false));
+        assert!(!matches("*", "0.3.0-rc1", false));
+        assert!(!matches("^1.2.3", "1.2.4-rc1", false));
+
+        // With `--pre`, pre-releases above the lower bound match
+        assert!(matches("*", "0.3.0-rc1", true));
+        assert!(matches("^1.2.3", "1.2.4-rc1", true));
+        assert!(matches("~1.2", "1.2.1-rc1", true));
+        assert!(matches(">=1.2.3, <2.0.0", "1.3.0-rc1", true));
+
+        // Pre-releases of the lower bound itself precede it
+        assert!(!matches("^1.2.3", "1.2.3-rc1", true));
+        assert!(!matches(">=1.2.3", "1.2.3-rc1", true));
+        assert!(!matches("=1.2.3", "1.2.3-rc1", true));
+        assert!(!matches("1.*", "1.0.0-rc1", true));
+        assert!(!matches("^1.2.3", "2.0.0-rc1", true));
+
+        // Unless the requirement names that pre-release
+        assert!(matches("^1.2.3-rc1", "1.2.3-rc1", true));
+    }
+
+    #[test]
+    fn it_matches_patterns() {
+        assert!(matches_pattern("wasi", "wasi"));
+        assert!(!matches_pattern("wasi", "wasix"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("wasi:*", "wasi:http"));
+        assert!(!matches_pattern("wasi:*", "wasix:http"));
+        assert!(matches_pattern("my*-*", "mycorp-tools"));
+        assert!(!matches_pattern("my*-*", "mycorp"));
+        assert!(matches_pattern("*:cli*", "wasi:cli-base"));
+    }
+
+    #[test]
+    fn it_finds_namespace_registries() {
+        let namespaces = namespaces(&[
+            ("wasi:*", &["public"]),
+            ("wasi:http", &["mirror"]),
+            ("mycorp", &["internal"]),
+            ("mycorp*", &["mirror", "public"]),
+            ("*", &["fallback"]),
+        ]);
+
+        assert_eq!(find(&namespaces, "wasi:io"), ["public"]);
+        assert_eq!(find(&namespaces, "wasi:http"), ["mirror"]);
+        assert_eq!(find(&namespaces, "mycorp:logger"), ["internal"]);
+        assert_eq!(
+            find(&namespaces, "mycorp-tools:logger"),
+            ["mirror", "public"]
+        );
+        assert_eq!(find(&namespaces, "other:package"), ["fallback"]);
+        assert_eq!(
+            find_namespace_registry(&namespaces, &"mycorp-tools:logger".parse().unwrap()),
+            Some("mirror")
+        );
+
+        let namespaces = self::namespaces(&[("wasi:*", &["public"])]);
+        assert!(find(&namespaces, "other:package").is_empty());
+        assert_eq!(
+            find_namespace_registry(&namespaces, &"other:package".parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn it_deserializes_namespace_registries() {
+        let namespaces: HashMap<String, NamespaceRegistries> =
+            serde_json::from_str(r#"{ "mycorp": "internal", "wasi:*": ["mirror", "public"] }"#)
+                .unwrap();
+
+        assert_eq!(find(&namespaces, "mycorp:logger"), ["internal"]);
+        assert_eq!(find(&namespaces, "wasi:io"), ["mirror", "public"]);
+    }
+}
@@ -0,0 +1,788 @@
+//! Module for the providers of signing keys used when publishing packages.
+//!
+//! A signing key is either held by this process, or held by an external
+//! signer (a signing command, a PKCS#11 token, or AWS KMS) that is given the
+//! SHA-256 digest of the data to sign and returns an ECDSA P-256 signature of
+//! it, so the private key never leaves the signer.
+
+use crate::{
+    keyring::get_signing_key,
+    keystore::{default_keystore_path, passphrase_from_env, Keystore},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p256::pkcs8::DecodePublicKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+use warg_client::RegistryUrl;
+use warg_crypto::signing::{PrivateKey, PublicKey, Signature};
+
+/// The environment variable set to the registry URL for a signing command.
+pub const SIGNING_REGISTRY_ENV_VAR: &str = "SIGNING_KEY_REGISTRY";
+
+/// The environment variable set to the key name for a signing command.
+pub const SIGNING_KEY_NAME_ENV_VAR: &str = "SIGNING_KEY_NAME";
+
+/// The environment variable set to the operation requested of a signing
+/// command: `public-key` or `sign`.
+pub const SIGNING_OPERATION_ENV_VAR: &str = "SIGNING_OPERATION";
+
+/// The environment variable set to the hex-encoded SHA-256 digest to sign for
+/// the `sign` operation of a signing command.
+pub const SIGNING_DIGEST_ENV_VAR: &str = "SIGNING_DIGEST";
+
+/// The environment variable containing the user PIN of a PKCS#11 token.
+pub const PKCS11_PIN_ENV_VAR: &str = "SIGNING_PKCS11_PIN";
+
+/// The program used to access PKCS#11 tokens (from OpenSC).
+const PKCS11_TOOL: &str = "pkcs11-tool";
+
+/// The program used to access AWS KMS keys (version 2 of the AWS CLI).
+const AWS_CLI: &str = "aws";
+
+/// The name of the signing key used when none is specified.
+pub const DEFAULT_KEY_NAME: &str = "default";
+
+/// Represents the signing key configuration of a registry.
+///
+/// This is the value of an entry in the `signing-keys` table of a
+/// configuration, keyed by registry name (`default` for the default
+/// registry).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SigningKeyConfig {
+    /// The name of the signing key in the system keyring or keystore.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_name: Option<String>,
+    /// The path to a file containing the signing key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+    /// An external signing command holding the signing key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// The path to an encrypted keystore containing the signing key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<PathBuf>,
+    /// The PKCS#11 token (e.g. a hardware security module) holding the
+    /// signing key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pkcs11: Option<Pkcs11Key>,
+    /// The ID, ARN, or alias of the AWS KMS key used as the signing key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aws_kms_key: Option<String>,
+}
+
+/// Represents a signing key held by a PKCS#11 token.
+///
+/// The key must be an ECDSA P-256 key; `pkcs11-tool` reads the user PIN of
+/// the token from the `SIGNING_PKCS11_PIN` environment variable if it is set,
+/// or else prompts for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Pkcs11Key {
+    /// The path to the PKCS#11 module of the token.
+    pub module: PathBuf,
+    /// The hex-encoded ID of the key object in the token.
+    pub key_id: String,
+    /// The label of the token, if the module provides more than one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_label: Option<String>,
+}
+
+/// Represents the signing key options given on the command line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SigningKeyOptions<'a> {
+    /// The path to a file containing the signing key.
+    pub key_file: Option<&'a Path>,
+    /// An external signing command holding the signing key.
+    pub command: Option<&'a str>,
+    /// The path to an encrypted keystore containing the signing key.
+    pub keystore: Option<&'a Path>,
+    /// The name of the signing key in the system keyring or keystore.
+    pub key_name: Option<&'a str>,
+}
+
+/// Represents a provider of the signing key used to publish a package.
+///
+/// The signing command, PKCS#11, and AWS KMS providers are external signers:
+/// they only sign digests and never reveal the private key. As the warg
+/// registry client signs the records of a registry log in process, those
+/// providers cannot be used to publish to or yank from warg registries; they
+/// sign the signatures embedded in components.
+#[derive(Debug, Clone)]
+pub enum SigningKeyProvider {
+    /// The signing key is stored in the system keyring with the given name.
+    ///
+    /// If the keyring cannot provide the key and the default keystore exists,
+    /// the key is read from the default keystore instead.
+    Keyring(String),
+    /// The signing key is stored in an encrypted keystore.
+    Keystore {
+        /// The path to the keystore.
+        path: PathBuf,
+        /// The name of the key in the keystore.
+        key_name: String,
+    },
+    /// The signing key is the value of the given environment variable.
+    Environment(String),
+    /// The signing key is the contents of the given file.
+    File(PathBuf),
+    /// The signing key is held by an external signing command.
+    ///
+    /// The command is run with the `SIGNING_OPERATION` environment variable
+    /// set to `public-key` to print the public key of the signing key, or set
+    /// to `sign` to print the signature of the SHA-256 digest given in the
+    /// `SIGNING_DIGEST` environment variable (hex-encoded) and on stdin (raw
+    /// bytes). The public key is printed in the format of warg public keys
+    /// or as a PEM or base64-encoded DER `SubjectPublicKeyInfo`; the
+    /// signature is printed in the format of warg signatures or as a
+    /// base64-encoded DER ECDSA signature.
+    Command {
+        /// The command to run.
+        command: String,
+        /// The name of the key to request from the command.
+        key_name: String,
+    },
+    /// The signing key is held by a PKCS#11 token, used with `pkcs11-tool`.
+    Pkcs11(Pkcs11Key),
+    /// The signing key is an AWS KMS key, used with the AWS CLI.
+    AwsKms(String),
+}
+
+/// Represents a signer of the contents of published packages.
+pub enum Signer {
+    /// The signer holds the private key in process.
+    Key(PrivateKey),
+    /// The signer is external to the process and only signs digests.
+    External {
+        /// The provider of the external signer.
+        provider: SigningKeyProvider,
+        /// The registry the signing key is for.
+        registry_url: RegistryUrl,
+        /// The public key of the signing key.
+        public_key: PublicKey,
+    },
+}
+
+impl Signer {
+    /// Gets the public key of the signing key.
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            Self::Key(key) => key.public_key(),
+            Self::External { public_key, .. } => public_key.clone(),
+        }
+    }
+
+    /// Gets the private key of the signer.
+    ///
+    /// Fails for external signers, which never reveal the private key.
+    pub fn private_key(&self) -> Result<&PrivateKey> {
+        match self {
+            Self::Key(key) => Ok(key),
+            Self::External {
+                provider,
+                registry_url,
+                ..
+            } => Err(provider.external_key_error(registry_url)),
+        }
+    }
+
+    /// Signs the given bytes.
+    ///
+    /// Signatures made by an external signer are verified with its public
+    /// key before they are returned.
+    pub fn sign(&self, bytes: &[u8]) -> Result<Signature> {
+        match self {
+            Self::Key(key) => key
+                .sign(bytes)
+                .map_err(|e| anyhow!("failed to sign with signing key: {e}")),
+            Self::External {
+                provider,
+                registry_url,
+                public_key,
+            } => {
+                let digest = Sha256::digest(bytes);
+                let signature = provider.external_sign(registry_url, &digest)?;
+                public_key.verify(bytes, &signature).map_err(|_| {
+                    anyhow!(
+                        "signature returned by {signer} does not match its public key `{key}`",
+                        signer = provider.describe(),
+                        key = public_key.fingerprint()
+                    )
+                })?;
+                Ok(signature)
+            }
+        }
+    }
+}
+
+impl SigningKeyProvider {
+    /// Determines the signing key provider to use.
+    ///
+    /// An explicit signing command takes precedence, followed by a key set in
+    /// the given environment variable, and finally the system keyring.
+    pub fn new(command: Option<&str>, env_var: &str, key_name: &str) -> Self {
+        if let Some(command) = command {
+            Self::Command {
+                command: command.to_string(),
+                key_name: key_name.to_string(),
+            }
+        } else if std::env::var_os(env_var).is_some() {
+            Self::Environment(env_var.to_string())
+        } else {
+            Self::Keyring(key_name.to_string())
+        }
+    }
+
+    /// Selects the signing key provider to use for a registry.
+    ///
+    /// The options given on the command line take precedence, followed by the
+    /// signing key configuration of the registry, a key set in the given
+    /// environment variable, and finally the default key in the system
+    /// keyring.
+    ///
+    /// Within each, a key file takes precedence over a signing command, which
+    /// takes precedence over a keystore, which takes precedence over a
+    /// PKCS#11 token or AWS KMS key (which are only configured), which take
+    /// precedence over a key name.
+    pub fn select(
+        options: SigningKeyOptions,
+        config: Option<&SigningKeyConfig>,
+        env_var: &str,
+    ) -> Self {
+        let config_key_name = config.and_then(|c| c.key_name.as_deref());
+        let key_name = options
+            .key_name
+            .or(config_key_name)
+            .unwrap_or(DEFAULT_KEY_NAME);
+
+        if let Some(path) = options.key_file {
+            return Self::File(path.to_path_buf());
+        }
+
+        if let Some(command) = options.command {
+            return Self::Command {
+                command: command.to_string(),
+                key_name: key_name.to_string(),
+            };
+        }
+
+        if let Some(path) = options.keystore {
+            return Self::Keystore {
+                path: path.to_path_buf(),
+                key_name: key_name.to_string(),
+            };
+        }
+
+        if let Some(key_name) = options.key_name {
+            return Self::Keyring(key_name.to_string());
+        }
+
+        if let Some(config) = config {
+            if let Some(path) = &config.key_file {
+                return Self::File(path.clone());
+            }
+
+            if let Some(command) = &config.command {
+                return Self::Command {
+                    command: command.clone(),
+                    key_name: key_name.to_string(),
+                };
+            }
+
+            if let Some(path) = &config.keystore {
+                return Self::Keystore {
+                    path: path.clone(),
+                    key_name: key_name.to_string(),
+                };
+            }
+
+            if let Some(key) = &config.pkcs11 {
+                return Self::Pkcs11(key.clone());
+            }
+
+            if let Some(key) = &config.aws_kms_key {
+                return Self::AwsKms(key.clone());
+            }
+
+            if let Some(key_name) = config_key_name {
+                return Self::Keyring(key_name.to_string());
+            }
+        }
+
+        if std::env::var_os(env_var).is_some() {
+            Self::Environment(env_var.to_string())
+        } else {
+            Self::Keyring(DEFAULT_KEY_NAME.to_string())
+        }
+    }
+
+    /// Determines if the provider is an external signer.
+    pub fn is_external(&self) -> bool {
+        matches!(
+            self,
+            Self::Command { .. } | Self::Pkcs11(_) | Self::AwsKms(_)
+        )
+    }
+
+    /// Gets the signer for the given registry.
+    ///
+    /// The public key of an external signer is requested from it.
+    pub fn signer(&self, registry_url: &RegistryUrl) -> Result<Signer> {
+        if !self.is_external() {
+            return Ok(Signer::Key(self.signing_key(registry_url)?));
+        }
+
+        let public_key = self.external_public_key(registry_url)?;
+        log::debug!(
+            "using {signer} with public key `{key}`",
+            signer = self.describe(),
+            key = public_key.fingerprint()
+        );
+
+        Ok(Signer::External {
+            provider: self.clone(),
+            registry_url: registry_url.clone(),
+            public_key,
+        })
+    }
+
+    /// Gets the signing key for the given registry.
+    ///
+    /// Fails for external signers, which never reveal the private key.
+    pub fn signing_key(&self, registry_url: &RegistryUrl) -> Result<PrivateKey> {
+        match self {
+            Self::Keyring(key_name) => match get_signing_key(registry_url, key_name) {
+                Ok(key) => Ok(key),
+                Err(e) => match default_keystore_path().filter(|p| p.is_file()) {
+                    Some(path) => {
+                        log::debug!(
+                            "reading signing key from keystore `{path}` as the keyring failed: {e:#}",
+                            path = path.display()
+                        );
+                        Self::read_keystore(&path, key_name, registry_url)
+                    }
+                    None => Err(e),
+                },
+            },
+            Self::Keystore { path, key_name } => Self::read_keystore(path, key_name, registry_url),
+            Self::File(path) => {
+                let key = fs::read_to_string(path).with_context(|| {
+                    format!(
+                        "failed to read signing key file `{path}`",
+                        path = path.display()
+                    )
+                })?;
+
+                PrivateKey::decode(key.trim().to_string()).with_context(|| {
+                    format!(
+                        "failed to parse signing key from file `{path}`",
+                        path = path.display()
+                    )
+                })
+            }
+            Self::Environment(env_var) => {
+                let key = std::env::var(env_var).with_context(|| {
+                    format!("failed to read signing key from `{env_var}` environment variable")
+                })?;
+
+                PrivateKey::decode(key).with_context(|| {
+                    format!("failed to parse signing key from `{env_var}` environment variable")
+                })
+            }
+            Self::Command { .. } | Self::Pkcs11(_) | Self::AwsKms(_) => {
+                Err(self.external_key_error(registry_url))
+            }
+        }
+    }
+
+    /// Describes an external signer for messages.
+    fn describe(&self) -> String {
+        match self {
+            Self::Command { command, .. } => format!("signing command `{command}`"),
+            Self::Pkcs11(key) => format!("PKCS#11 key `{id}`", id = key.key_id),
+            Self::AwsKms(key) => format!("AWS KMS key `{key}`"),
+            _ => "signing key".to_string(),
+        }
+    }
+
+    /// Creates the error for using an external signer where the private key
+    /// is required.
+    fn external_key_error(&self, registry_url: &RegistryUrl) -> anyhow::Error {
+        anyhow!(
+            "the signing key for registry `{registry_url}` is held by {signer}, but warg registries require a local signing key as their records are signed in process; use a key from the keyring, a keystore, a key file, or an environment variable instead",
+            signer = self.describe()
+        )
+    }
+
+    /// Gets the public key of an external signer.
+    fn external_public_key(&self, registry_url: &RegistryUrl) -> Result<PublicKey> {
+        let output = match self {
+            Self::Command { command, key_name } => {
+                let mut cmd = Self::signing_command(command, key_name, registry_url)?;
+                cmd.env(SIGNING_OPERATION_ENV_VAR, "public-key");
+                run_signer(cmd, &self.describe(), Some(&[]))?
+            }
+            Self::Pkcs11(key) => {
+                let mut cmd = pkcs11_command(key);
+                cmd.args(["--read-object", "--type", "pubkey"]);
+                return public_key_from_der(&run_signer(cmd, &self.describe(), Some(&[]))?)
+                    .with_context(|| {
+                        format!("failed to parse the public key of {}", self.describe())
+                    });
+            }
+            Self::AwsKms(key) => {
+                let mut cmd = Command::new(AWS_CLI);
+                cmd.args(["kms", "get-public-key", "--key-id", key.as_str()])
+                    .args(["--query", "PublicKey", "--output", "text"]);
+                run_signer(cmd, &self.describe(), Some(&[]))?
+            }
+            _ => unreachable!("not an external signer"),
+        };
+
+        parse_public_key(&String::from_utf8_lossy(&output))
+            .with_context(|| format!("failed to parse the public key of {}", self.describe()))
+    }
+
+    /// Signs a SHA-256 digest with an external signer.
+    fn external_sign(&self, registry_url: &RegistryUrl, digest: &[u8]) -> Result<Signature> {
+        let output = match self {
+            Self::Command { command, key_name } => {
+                let mut cmd = Self::signing_command(command, key_name, registry_url)?;
+                cmd.env(SIGNING_OPERATION_ENV_VAR, "sign")
+                    .env(SIGNING_DIGEST_ENV_VAR, hex::encode(digest));
+                run_signer(cmd, &self.describe(), Some(digest))?
+            }
+            Self::Pkcs11(key) => {
+                // The digest is passed in a file so that the tool can prompt
+                // for the PIN when it isn't in the environment; the PIN itself
+                // is never passed as an argument
+                let mut input = tempfile::NamedTempFile::new()
+                    .context("failed to create a temporary file for the digest")?;
+                input
+                    .write_all(digest)
+                    .context("failed to write the digest to a temporary file")?;
+
+                let cmd = pkcs11_sign_command(
+                    key,
+                    input.path(),
+                    std::env::var_os(PKCS11_PIN_ENV_VAR).is_some(),
+                );
+                return signature_from_der(&run_signer(cmd, &self.describe(), None)?).with_context(
+                    || format!("failed to parse the signature of {}", self.describe()),
+                );
+            }
+            Self::AwsKms(key) => {
+                let mut cmd = Command::new(AWS_CLI);
+                cmd.args(["kms", "sign", "--key-id", key.as_str()])
+                    .args(["--message", STANDARD.encode(digest).as_str()])
+                    .args(["--message-type", "DIGEST"])
+                    .args(["--signing-algorithm", "ECDSA_SHA_256"])
+                    .args(["--query", "Signature", "--output", "text"]);
+                run_signer(cmd, &self.describe(), Some(&[]))?
+            }
+            _ => unreachable!("not an external signer"),
+        };
+
+        parse_signature(&String::from_utf8_lossy(&output))
+            .with_context(|| format!("failed to parse the signature of {}", self.describe()))
+    }
+
+    fn read_keystore(
+        path: &Path,
+        key_name: &str,
+        registry_url: &RegistryUrl,
+    ) -> Result<PrivateKey> {
+        Keystore::open(path)?
+            .get(registry_url, key_name, &passphrase_from_env(path)?)
+            .with_context(|| {
+                format!(
+                    "failed to read signing key from keystore `{path}`",
+                    path = path.display()
+                )
+            })
+    }
+
+    fn signing_command(
+        command: &str,
+        key_name: &str,
+        registry_url: &RegistryUrl,
+    ) -> Result<Command> {
+        let mut args = command.split_whitespace();
+        let program = args.next().context("signing command is empty")?;
+
+        log::debug!("running signing command `{command}` for registry `{registry_url}`");
+
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .env(SIGNING_REGISTRY_ENV_VAR, registry_url.to_string())
+            .env(SIGNING_KEY_NAME_ENV_VAR, key_name);
+        Ok(cmd)
+    }
+}
+
+/// Creates a `pkcs11-tool` command for the given key.
+fn pkcs11_command(key: &Pkcs11Key) -> Command {
+    let mut cmd = Command::new(PKCS11_TOOL);
+    cmd.arg("--module")
+        .arg(&key.module)
+        .args(["--id", key.key_id.as_str()]);
+    if let Some(label) = &key.token_label {
+        cmd.args(["--token-label", label.as_str()]);
+    }
+
+    cmd
+}
+
+/// Creates a `pkcs11-tool` command signing the digest in the given file.
+///
+/// If `pin_in_env` is set, the tool reads the PIN from the
+/// `SIGNING_PKCS11_PIN` environment variable; otherwise it prompts for it.
+fn pkcs11_sign_command(key: &Pkcs11Key, input: &Path, pin_in_env: bool) -> Command {
+    let mut cmd = pkcs11_command(key);
+    cmd.args(["--sign", "--mechanism", "ECDSA"])
+        .args(["--signature-format", "openssl"])
+        .arg("--input-file")
+        .arg(input)
+        .arg("--login");
+    if pin_in_env {
+        cmd.arg("--pin").arg(format!("env:{PKCS11_PIN_ENV_VAR}"));
+    }
+
+    cmd
+}
+
+/// Runs an external signer with the given input, returning its stdout.
+///
+/// If `input` is `None`, the signer inherits stdin so that it may prompt.
+fn run_signer(mut cmd: Command, signer: &str, input: Option<&[u8]>) -> Result<Vec<u8>> {
+    let mut child = cmd
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("failed to spawn {signer}"))?;
+
+    // The signer may not read its input
+    if let Some(input) = input {
+        let mut stdin = child.stdin.take().unwrap();
+        match stdin.write_all(input) {
+            Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+                return Err(e).with_context(|| format!("failed to write to {signer}"));
+            }
+            _ => drop(stdin),
+        }
+    }
+
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("failed to wait for {signer}"))?;
+
+    if !output.status.success() {
+        bail!(
+            "{signer} failed with exit status {status}",
+            status = output.status
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+/// Decodes base64 that may be wrapped across lines.
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    let text: String = text.split_whitespace().collect();
+    STANDARD
+        .decode(text)
+        .context("output is not in a known format")
+}
+
+/// Parses a public key in the format of warg public keys, or as a PEM or
+/// base64-encoded DER `SubjectPublicKeyInfo`.
+fn parse_public_key(text: &str) -> Result<PublicKey> {
+    let text = text.trim();
+    if text.starts_with("-----BEGIN") {
+        let base64: String = text.lines().filter(|l| !l.starts_with("-----")).collect();
+        return public_key_from_der(&decode_base64(&base64)?);
+    }
+
+    if text.contains(':') {
+        return text.parse().context("invalid public key");
+    }
+
+    public_key_from_der(&decode_base64(text)?)
+}
+
+/// Converts a DER-encoded ECDSA P-256 `SubjectPublicKeyInfo` to a public key.
+fn public_key_from_der(der: &[u8]) -> Result<PublicKey> {
+    let key = p256::ecdsa::VerifyingKey::from_public_key_der(der)
+        .map_err(|e| anyhow!("invalid ECDSA P-256 public key: {e}"))?;
+    format!(
+        "ecdsa-p256:{key}",
+        key = STANDARD.encode(key.to_encoded_point(true).as_bytes())
+    )
+    .parse()
+    .context("invalid public key")
+}
+
+/// Parses a signature in the format of warg signatures, or as a
+/// base64-encoded DER ECDSA signature.
+fn parse_signature(text: &str) -> Result<Signature> {
+    let text = text.trim();
+    if text.contains(':') {
+        return text.parse().context("invalid signature");
+    }
+
+    signature_from_der(&decode_base64(text)?)
+}
+
+/// Converts a DER-encoded ECDSA P-256 signature to a signature.
+fn signature_from_der(der: &[u8]) -> Result<Signature> {
+    let signature = p256::ecdsa::Signature::from_der(der)
+        .map_err(|e| anyhow!("invalid ECDSA P-256 signature: {e}"))?;
+    format!(
+        "ecdsa-p256:{signature}",
+        signature = STANDARD.encode(signature.to_der().as_bytes())
+    )
+    .parse()
+    .context("invalid signature")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use p256::ecdsa::{signature::Signer as _, SigningKey};
+
+    /// The DER prefix of an ECDSA P-256 `SubjectPublicKeyInfo`, followed by
+    /// the uncompressed point of the key.
+    const SPKI_PREFIX: &str = "3059301306072a8648ce3d020106082a8648ce3d030107034200";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[7; 32]).unwrap()
+    }
+
+    fn spki(key: &SigningKey) -> Vec<u8> {
+        let mut der = hex::decode(SPKI_PREFIX).unwrap();
+        der.extend_from_slice(key.verifying_key().to_encoded_point(false).as_bytes());
+        der
+    }
+
+    #[test]
+    fn it_parses_external_public_keys() -> Result<()> {
+        let key = signing_key();
+        let expected = PrivateKey::from(key.clone()).public_key();
+        let der = spki(&key);
+        let pem = format!(
+            "-----BEGIN PUBLIC KEY-----\n{base64}\n-----END PUBLIC KEY-----\n",
+            base64 = STANDARD.encode(&der)
+        );
+
+        for text in [expected.to_string(), STANDARD.encode(&der), pem] {
+            assert_eq!(parse_public_key(&text)?.to_string(), expected.to_string());
+        }
+
+        assert!(parse_public_key("not a key").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_external_signatures() -> Result<()> {
+        let key = signing_key();
+        let public_key = PrivateKey::from(key.clone()).public_key();
+        let signature: p256::ecdsa::Signature = key.sign(b"message");
+        let der = signature.to_der();
+
+        let parsed = parse_signature(&format!("{}\n", STANDARD.encode(der.as_bytes())))?;
+        assert!(public_key.verify(b"message", &parsed).is_ok());
+        assert!(public_key.verify(b"other", &parsed).is_err());
+        assert_eq!(
+            parse_signature(&parsed.to_string())?.to_string(),
+            parsed.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_passes_the_pkcs11_pin_through_the_environment() {
+        let key = Pkcs11Key {
+            module: "module.so".into(),
+            key_id: "01".to_string(),
+            token_label: None,
+        };
+
+        let args = |pin_in_env| {
+            pkcs11_sign_command(&key, Path::new("digest"), pin_in_env)
+                .get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let with_env = args(true);
+        assert!(with_env.ends_with(&[
+            "--login".to_string(),
+            "--pin".to_string(),
+            "env:SIGNING_PKCS11_PIN".to_string()
+        ]));
+
+        let prompting = args(false);
+        assert_eq!(prompting.last().map(String::as_str), Some("--login"));
+        assert!(!prompting.iter().any(|a| a == "--pin"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_signs_digests_with_a_signing_command() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let key = signing_key();
+        let public_key = PrivateKey::from(key.clone()).public_key();
+        let signature: p256::ecdsa::Signature = key.sign(b"message");
+
+        let dir = tempfile::tempdir()?;
+        let script = dir.path().join("signer.sh");
+        fs::write(dir.path().join("public"), STANDARD.encode(spki(&key)))?;
+        fs::write(
+            dir.path().join("signature"),
+            STANDARD.encode(signature.to_der().as_bytes()),
+        )?;
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\ncd {dir}\ncase \"$SIGNING_OPERATION\" in\n  public-key) cat public ;;\n  sign) test \"$SIGNING_DIGEST\" = {digest} && cat signature ;;\n  *) exit 1 ;;\nesac\n",
+                dir = dir.path().display(),
+                digest = hex::encode(Sha256::digest(b"message")),
+            ),
+        )?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+        let provider = SigningKeyProvider::Command {
+            command: script.display().to_string(),
+            key_name: DEFAULT_KEY_NAME.to_string(),
+        };
+        let url: RegistryUrl = "https://registry.example.com".parse()?;
+
+        let signer = provider.signer(&url)?;
+        assert_eq!(signer.public_key().to_string(), public_key.to_string());
+        let signed = signer.sign(b"message")?;
+        assert!(public_key.verify(b"message", &signed).is_ok());
+
+        // The command only signed the digest of `message`
+        assert!(signer.sign(b"other").is_err());
+
+        // The private key is never available
+        let err = provider.signing_key(&url).unwrap_err().to_string();
+        assert!(err.contains("require a local signing key"), "{err}");
+        assert!(signer.private_key().is_err());
+
+        Ok(())
+    }
+}
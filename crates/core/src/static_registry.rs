@@ -0,0 +1,237 @@
+//! Module for resolving packages from static HTTP registries.
+//!
+//! A static registry is a plain layout of files served over HTTP, such as a
+//! bucket of an object storage service, for hosting WIT and component
+//! packages without running a warg registry.
+//!
+//! A package `<namespace>:<name>` has an index of its versions at
+//! `<url>/pkgs/<namespace>/<name>/index.json`:
+//!
+//! ```json
+//! {
+//!   "versions": [
+//!     { "version": "1.0.0", "digest": "sha256:<hex>" },
+//!     { "version": "1.1.0", "digest": "sha256:<hex>", "yanked": true }
+//!   ]
+//! }
+//! ```
+//!
+//! The content of each version is at
+//! `<url>/pkgs/<namespace>/<name>/<version>.wasm`; downloaded content must
+//! match the digest in the index.
+//!
+//! Static registries are configured with a URL using the `static+https`
+//! scheme (e.g. `static+https://example.com/components`); the `static+http`
+//! scheme may be used for registries that are not served over TLS.
+//!
+//! Static registries are read-only: a package is published by uploading its
+//! content and adding its version to the index.
+//!
+//! A token set with `cargo component login` is sent as a bearer token with
+//! every request, so static registries may be served by private buckets.
+
+use crate::{cache, keyring};
+use anyhow::{bail, Context, Result};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use url::Url;
+use warg_crypto::hash::AnyHash;
+use warg_protocol::registry::PackageId;
+
+/// The URL scheme of static registries served over HTTPS.
+pub const STATIC_SCHEME: &str = "static+https";
+
+/// The URL scheme of static registries served over plain HTTP.
+pub const STATIC_HTTP_SCHEME: &str = "static+http";
+
+/// Determines if the given registry URL refers to a static registry.
+pub fn is_static_url(url: &str) -> bool {
+    url.starts_with("static+https://") || url.starts_with("static+http://")
+}
+
+/// Gets the directory used to cache content downloaded from static registries.
+///
+/// The cache is shared with the other registry clients.
+pub fn cache_dir(config: &warg_client::Config) -> Result<PathBuf> {
+    cache::content_dir(config)
+}
+
+/// Represents a version in the index of a static registry package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// The version of the package.
+    pub version: Version,
+    /// The digest of the content of the version.
+    pub digest: AnyHash,
+    /// Whether or not the version was yanked.
+    ///
+    /// Yanked versions are not selected when resolving dependencies, but
+    /// dependencies already locked to them continue to use them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+}
+
+/// Represents the index of a static registry package.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageIndex {
+    /// The versions of the package.
+    pub versions: Vec<IndexEntry>,
+}
+
+/// A client for a static registry.
+pub struct StaticClient {
+    client: Client,
+    url: String,
+    base: Url,
+    cache_dir: PathBuf,
+}
+
+impl StaticClient {
+    /// Creates a new static registry client for the given registry URL.
+    ///
+    /// Downloaded content is cached in the given directory.
+    pub fn new(url: &str, cache_dir: PathBuf) -> Result<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .with_context(|| format!("failed to parse static registry URL `{url}`"))?;
+
+        let scheme = match scheme {
+            STATIC_SCHEME => "https",
+            STATIC_HTTP_SCHEME => "http",
+            scheme => bail!("unsupported static registry URL scheme `{scheme}`"),
+        };
+
+        // Ensure the base URL ends with a slash so that paths are joined to it
+        let base: Url = format!("{scheme}://{rest}/", rest = rest.trim_end_matches('/'))
+            .parse()
+            .with_context(|| format!("failed to parse static registry URL `{url}`"))?;
+
+        Ok(Self {
+            client: Client::new(),
+            url: url.to_string(),
+            base,
+            cache_dir,
+        })
+    }
+
+    /// Gets the location of the given content in the cache, if present.
+    pub fn content_location(&self, digest: &AnyHash) -> Option<PathBuf> {
+        let path = self.content_path(digest)?;
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Fetches the index of the given package.
+    ///
+    /// Returns an empty index if the package does not exist.
+    pub async fn index(&self, id: &PackageId) -> Result<PackageIndex> {
+        let url = self.url(id, "index.json")?;
+        let res = self
+            .get(id, url.clone())
+            .send()
+            .await
+            .with_context(|| format!("failed to send request to `{url}`"))?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(PackageIndex::default());
+        }
+
+        let status = res.status();
+        if !status.is_success() {
+            bail!("failed to fetch the index of package `{id}`: registry returned status {status}");
+        }
+
+        res.json()
+            .await
+            .with_context(|| format!("failed to parse the index of package `{id}` from `{url}`"))
+    }
+
+    /// Lists the versions of the given package and whether they were yanked.
+    pub async fn list_versions(&self, id: &PackageId) -> Result<Vec<(Version, bool)>> {
+        Ok(self
+            .index(id)
+            .await?
+            .versions
+            .into_iter()
+            .map(|e| (e.version, e.yanked))
+            .collect())
+    }
+
+    /// Downloads the given version of a package.
+    ///
+    /// Returns the digest of the content and the path to it in the cache.
+    pub async fn download(&self, id: &PackageId, version: &Version) -> Result<(AnyHash, PathBuf)> {
+        let index = self.index(id).await?;
+        let entry = index
+            .versions
+            .iter()
+            .find(|e| e.version == *version)
+            .with_context(|| format!("package `{id}` has no release with version {version}"))?;
+
+        let path = self
+            .content_path(&entry.digest)
+            .context("unsupported content digest algorithm")?;
+        if path.is_file() {
+            return Ok((entry.digest.clone(), path));
+        }
+
+        let url = self.url(id, &format!("{version}.wasm"))?;
+        let res = self
+            .get(id, url.clone())
+            .send()
+            .await
+            .with_context(|| format!("failed to send request to `{url}`"))?;
+
+        let status = res.status();
+        if !status.is_success() {
+            bail!("failed to download `{url}`: registry returned status {status}");
+        }
+
+        let bytes = res
+            .bytes()
+            .await
+            .with_context(|| format!("failed to download `{url}`"))?;
+
+        let actual = format!("sha256:{hash}", hash = hex::encode(Sha256::digest(&bytes)));
+        if actual != entry.digest.to_string() {
+            bail!(
+                "content of `{url}` has digest `{actual}` but the index specifies digest `{expected}`",
+                expected = entry.digest
+            );
+        }
+
+        cache::write_content(&path, &bytes)?;
+        Ok((entry.digest.clone(), path))
+    }
+
+    /// Creates a request for the given URL of a package.
+    fn get(&self, id: &PackageId, url: Url) -> RequestBuilder {
+        let builder = self.client.get(url);
+        match keyring::find_registry_token(&self.url, Some(id.namespace())) {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    fn url(&self, id: &PackageId, file: &str) -> Result<Url> {
+        let path = format!(
+            "pkgs/{namespace}/{name}/{file}",
+            namespace = id.namespace(),
+            name = id.name()
+        );
+
+        self.base
+            .join(&path)
+            .with_context(|| format!("failed to create static registry URL for `{path}`"))
+    }
+
+    fn content_path(&self, digest: &AnyHash) -> Option<PathBuf> {
+        cache::content_path(&self.cache_dir, &digest.to_string())
+    }
+}
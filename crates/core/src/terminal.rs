@@ -49,6 +49,37 @@ impl fmt::Display for Color {
     }
 }
 
+/// The format of the messages printed by the terminal.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MessageFormat {
+    /// Human-readable messages printed to stderr.
+    #[default]
+    Human,
+    /// JSON messages printed to stdout, one per line.
+    Json,
+}
+
+impl FromStr for MessageFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            _ => bail!("argument for --message-format must be human or json, but found `{value}`"),
+        }
+    }
+}
+
+impl fmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Human => write!(f, "human"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
 /// The requested verbosity of output.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Verbosity {
@@ -63,6 +94,7 @@ pub enum Verbosity {
 pub(crate) struct TerminalState {
     pub(crate) output: Output,
     verbosity: Verbosity,
+    message_format: MessageFormat,
     pub(crate) needs_clear: bool,
 }
 
@@ -110,6 +142,7 @@ impl Terminal {
                 color,
             },
             verbosity,
+            message_format: MessageFormat::Human,
             needs_clear: false,
         }))
     }
@@ -119,16 +152,84 @@ impl Terminal {
         Self(RefCell::new(TerminalState {
             output: Output::Write(out),
             verbosity: Verbosity::Verbose,
+            message_format: MessageFormat::Human,
             needs_clear: false,
         }))
     }
 
+    /// Sets the format of the messages printed by the terminal.
+    ///
+    /// With the JSON format, every message is printed to stdout as a JSON
+    /// object with a `reason` field, like the JSON messages of `cargo`.
+    pub fn with_message_format(self, format: MessageFormat) -> Self {
+        self.0.borrow_mut().message_format = format;
+        self
+    }
+
+    /// Returns the format of the messages printed by the terminal.
+    pub fn message_format(&self) -> MessageFormat {
+        self.0.borrow().message_format
+    }
+
+    /// Prints a machine-readable event.
+    ///
+    /// Events are only printed with the JSON message format; the given fields
+    /// must be a JSON object and are printed along with the event's `reason`.
+    pub fn event(&self, reason: &str, fields: serde_json::Value) -> Result<()> {
+        if self.message_format() != MessageFormat::Json {
+            return Ok(());
+        }
+
+        let mut object = serde_json::Map::new();
+        object.insert("reason".to_string(), reason.into());
+        match fields {
+            serde_json::Value::Object(fields) => object.extend(fields),
+            serde_json::Value::Null => {}
+            _ => bail!("the fields of event `{reason}` must be a JSON object"),
+        }
+
+        self.0
+            .borrow_mut()
+            .output
+            .write_json(&serde_json::Value::Object(object))
+    }
+
+    /// Prints a message as a JSON event if the message format is JSON.
+    ///
+    /// Returns `true` if the message was printed.
+    fn print_json(
+        &self,
+        reason: &str,
+        status: Option<&dyn fmt::Display>,
+        message: &dyn fmt::Display,
+        always: bool,
+    ) -> Result<bool> {
+        if self.message_format() != MessageFormat::Json {
+            return Ok(false);
+        }
+
+        if always || self.verbosity() != Verbosity::Quiet {
+            let mut fields = serde_json::Map::new();
+            if let Some(status) = status {
+                fields.insert("status".to_string(), status.to_string().into());
+            }
+            fields.insert("message".to_string(), message.to_string().into());
+            self.event(reason, serde_json::Value::Object(fields))?;
+        }
+
+        Ok(true)
+    }
+
     /// Prints a green 'status' message.
     pub fn status<T, U>(&self, status: T, message: U) -> Result<()>
     where
         T: fmt::Display,
         U: fmt::Display,
     {
+        if self.print_json("status", Some(&status), &message, false)? {
+            return Ok(());
+        }
+
         let status_green = status.green();
 
         let status = if self.0.borrow().output.supports_color() {
@@ -151,6 +252,10 @@ impl Terminal {
         T: fmt::Display,
         U: fmt::Display,
     {
+        if self.print_json("status", Some(&status), &message, false)? {
+            return Ok(());
+        }
+
         let status_color = status.color(color);
 
         let status = if self.0.borrow().output.supports_color() {
@@ -164,6 +269,10 @@ impl Terminal {
 
     /// Prints a cyan 'note' message.
     pub fn note<T: fmt::Display>(&self, message: T) -> Result<()> {
+        if self.print_json("note", None, &message, false)? {
+            return Ok(());
+        }
+
         let status = "note";
         let status_cyan = status.cyan();
 
@@ -178,6 +287,10 @@ impl Terminal {
 
     /// Prints a yellow 'warning' message.
     pub fn warn<T: fmt::Display>(&self, message: T) -> Result<()> {
+        if self.print_json("warning", None, &message, false)? {
+            return Ok(());
+        }
+
         let status = "warning";
         let status_yellow = status.yellow();
 
@@ -192,6 +305,10 @@ impl Terminal {
 
     /// Prints a red 'error' message.
     pub fn error<T: fmt::Display>(&self, message: T) -> Result<()> {
+        if self.print_json("error", None, &message, true)? {
+            return Ok(());
+        }
+
         let status = "error";
         let status_red = status.red();
 
@@ -318,6 +435,17 @@ impl Output {
         Ok(())
     }
 
+    /// Writes a JSON value to stdout on a single line.
+    fn write_json(&mut self, value: &serde_json::Value) -> Result<()> {
+        let line = serde_json::to_string(value)?;
+        match self {
+            Self::Stream { .. } => writeln!(stdout(), "{line}")?,
+            Self::Write(w) => writeln!(w, "{line}")?,
+        }
+
+        Ok(())
+    }
+
     fn write_stdout(
         &mut self,
         fragment: impl fmt::Display,
@@ -2,6 +2,7 @@
 
 #![deny(missing_docs)]
 
+use cargo_component_core::bindings::{annotate_deprecations, remap_module};
 use heck::ToUpperCamelCase;
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
@@ -40,6 +41,20 @@ fn implementor_path_str(path: &syn::Path) -> String {
     s
 }
 
+/// Replaces the body of each remapped module with a glob re-export of the
+/// module it is remapped to.
+///
+/// This matches the remapping of the bindings emitted by
+/// `cargo component bindings`.
+fn remap_modules(source: &str, with: &[(Vec<String>, String)]) -> Option<String> {
+    let mut source = source.to_string();
+    for (module, path) in with {
+        source = remap_module(&source, module, path)?;
+    }
+
+    Some(source)
+}
+
 /// Used to generate bindings for a WebAssembly component.
 ///
 /// By default, all world exports are expected to be implemented
@@ -61,6 +76,28 @@ fn implementor_path_str(path: &syn::Path) -> String {
 /// - `resources`: A map of resource names to resource implementor types.
 /// - `ownership`: The ownership model to use for resources.
 /// - `additional_derives`: Additional derive macro attributes to add to generated types
+/// - `target`: The name of a target from `package.metadata.component.targets` to
+///   generate bindings for instead of the default target.
+///
+/// The defaults of the `ownership` and `additional_derives` options may be set
+/// with the same options of the `package.metadata.component.bindings` section
+/// in `Cargo.toml`; an `ownership` given to the macro takes precedence and
+/// `additional_derives` given to the macro are added to those of the manifest.
+///
+/// The conveniences configured for specific types in the
+/// `package.metadata.component.bindings.types` section (e.g. builders for
+/// records) are also included in the generated bindings.
+///
+/// WIT items with a doc comment line starting with `@deprecated` (optionally
+/// followed by a note) are annotated with `#[deprecated]` in the generated
+/// bindings.
+///
+/// When `package.metadata.component.bindings.no-std` is set, the generated
+/// bindings only depend on `core` and `alloc`, implementing
+/// `std::error::Error` only when the crate's `std` feature is enabled. The
+/// `panic-handler` and `global-allocator` settings of the section generate a
+/// `#[panic_handler]` that traps and register the given unit struct (e.g.
+/// `crate::Allocator`) as the `#[global_allocator]`, respectively.
 ///
 /// # Examples
 ///
@@ -95,6 +132,29 @@ fn implementor_path_str(path: &syn::Path) -> String {
 /// cargo_component_bindings::generate!({
 ///      ownership: "borrowing-duplicate-if-necessary"
 /// })
+/// ```
+///
+/// Generating bindings for a named target, only when the `plugin`
+/// cargo feature is enabled:
+///
+/// ```ignore
+/// #[cfg(feature = "plugin")]
+/// mod plugin {
+///     cargo_component_bindings::generate!({
+///         target: "plugin",
+///     });
+///
+///     struct Component;
+/// }
+/// ```
+///
+/// # Bindings crates
+///
+/// When a component package names this crate in its
+/// `package.metadata.component.bindings.crate` setting, the macro generates
+/// a public `bindings` module containing only the imports of the component's
+/// target world, so that other crates may depend on this crate to share them.
+/// The `implementor` and `resources` options do not apply to a bindings crate.
 #[proc_macro]
 pub fn generate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     syn::parse_macro_input!(input as Config)
@@ -103,17 +163,138 @@ pub fn generate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         .into()
 }
 
-fn target_path() -> Result<PathBuf> {
-    Ok(Path::new(env!("CARGO_TARGET_DIR"))
-        .join("bindings")
-        .join(
-            std::env::var("CARGO_PKG_NAME")
-                .expect("failed to get `CARGO_PKG_NAME` environment variable"),
-        )
-        .join("target.wasm"))
+fn target_path(target: Option<&str>) -> Result<PathBuf> {
+    let mut path = Path::new(env!("CARGO_TARGET_DIR")).join("bindings").join(
+        std::env::var("CARGO_PKG_NAME")
+            .expect("failed to get `CARGO_PKG_NAME` environment variable"),
+    );
+
+    if let Some(target) = target {
+        path = path.join("targets").join(target);
+    }
+
+    Ok(path.join("target.wasm"))
 }
 
-fn decode_resolve(path: &Path, span: Span) -> Result<(Resolve, WorldId)> {
+/// Represents the default options of the macro from the component's manifest.
+#[derive(Default)]
+struct ManifestOptions {
+    /// The path of the options file, if it exists.
+    path: Option<PathBuf>,
+    /// The default ownership model to use for resources.
+    ownership: Option<Ownership>,
+    /// The additional derive macro attributes to add to generated types.
+    additional_derives: Vec<String>,
+    /// The modules of the bindings to replace with a re-export of existing
+    /// modules, as `(module path, Rust path)` pairs.
+    with: Vec<(Vec<String>, String)>,
+    /// Whether or not to generate `no_std` bindings.
+    no_std: bool,
+    /// Whether or not to generate a `#[panic_handler]` for a `no_std` component.
+    panic_handler: bool,
+    /// The path of the `#[global_allocator]` to register for a `no_std` component.
+    global_allocator: Option<syn::Path>,
+}
+
+/// Reads the options file next to the given target file.
+///
+/// The file is written by `cargo-component` from the
+/// `package.metadata.component.bindings` section of the manifest.
+fn read_options(path: &Path, span: Span) -> Result<ManifestOptions> {
+    let path = path.with_file_name("options");
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(ManifestOptions::default()),
+    };
+
+    let mut options = ManifestOptions::default();
+    for line in contents.lines().filter(|l| !l.is_empty()) {
+        let (name, value) = line.split_once('=').ok_or_else(|| {
+            Error::new(
+                span,
+                format!(
+                    "invalid line `{line}` in options file `{path}`",
+                    path = path.display()
+                ),
+            )
+        })?;
+
+        match name {
+            "ownership" => {
+                options.ownership = Some(value.parse().map_err(|e| {
+                    Error::new(
+                        span,
+                        format!(
+                            "invalid ownership model in options file `{path}`: {e}",
+                            path = path.display()
+                        ),
+                    )
+                })?)
+            }
+            "additional-derive" => {
+                // Normalize the path so that it compares equal to derives given to the macro
+                let derive = syn::parse_str::<syn::Path>(value).map_err(|e| {
+                    Error::new(
+                        span,
+                        format!(
+                            "invalid derive `{value}` in options file `{path}`: {e}",
+                            path = path.display()
+                        ),
+                    )
+                })?;
+                options
+                    .additional_derives
+                    .push(derive.into_token_stream().to_string());
+            }
+            "with" => {
+                let (module, path) = value.split_once('=').ok_or_else(|| {
+                    Error::new(
+                        span,
+                        format!(
+                            "invalid remapped module `{value}` in options file `{path}`",
+                            path = path.display()
+                        ),
+                    )
+                })?;
+                options.with.push((
+                    module.split("::").map(ToString::to_string).collect(),
+                    path.to_string(),
+                ));
+            }
+            "no-std" => options.no_std = value == "true",
+            "panic-handler" => options.panic_handler = value == "true",
+            "global-allocator" => {
+                options.global_allocator = Some(syn::parse_str(value).map_err(|e| {
+                    Error::new(
+                        span,
+                        format!(
+                            "invalid global allocator `{value}` in options file `{path}`: {e}",
+                            path = path.display()
+                        ),
+                    )
+                })?)
+            }
+            _ => {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "unknown option `{name}` in options file `{path}`",
+                        path = path.display()
+                    ),
+                ))
+            }
+        }
+    }
+
+    options.path = Some(path);
+    Ok(options)
+}
+
+/// Decodes the target world from the given target file.
+///
+/// Returns the name of the component package the target belongs to if the
+/// current crate is the bindings crate of another package.
+fn decode_resolve(path: &Path, span: Span) -> Result<(Resolve, WorldId, Option<String>)> {
     let bytes = std::fs::read(path).map_err(|e| {
         Error::new(
             span,
@@ -146,12 +327,14 @@ fn decode_resolve(path: &Path, span: Span) -> Result<(Resolve, WorldId)> {
         )
     })?;
 
+    let component = fs::read_to_string(path.with_file_name("component")).ok();
+
     match decoded {
         DecodedWasm::WitPackage(resolve, pkg) => {
             let world = resolve
                 .select_world(pkg, if world.is_empty() { None } else { Some(&world) })
                 .map_err(|e| Error::new(span, format!("failed to select world for target: {e}")))?;
-            Ok((resolve, world))
+            Ok((resolve, world, component))
         }
         DecodedWasm::Component(_, _) => Err(Error::new(
             span,
@@ -168,6 +351,7 @@ mod kw {
     syn::custom_keyword!(resources);
     syn::custom_keyword!(ownership);
     syn::custom_keyword!(additional_derives);
+    syn::custom_keyword!(target);
 }
 
 #[derive(Clone)]
@@ -191,6 +375,7 @@ enum Opt {
     Ownership(Span, Ownership),
     // Parse as paths so we can take the concrete types/macro names rather than raw strings
     AdditionalDerives(Vec<syn::Path>),
+    Target(Span, syn::LitStr),
 }
 
 impl Parse for Opt {
@@ -230,6 +415,10 @@ impl Parse for Opt {
             syn::bracketed!(contents in input);
             let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
             Ok(Opt::AdditionalDerives(list.into_iter().collect()))
+        } else if l.peek(kw::target) {
+            let span = input.parse::<kw::target>()?.span;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::Target(span, input.parse()?))
         } else {
             Err(l.error())
         }
@@ -240,14 +429,21 @@ struct Config {
     input: PathBuf,
     resolve: Resolve,
     world: WorldId,
+    component: Option<String>,
     implementor: Option<syn::Path>,
     resources: HashMap<String, syn::Path>,
     ownership: Ownership,
     additional_derives: Vec<String>,
+    with: Vec<(Vec<String>, String)>,
+    no_std: bool,
+    panic_handler: bool,
+    global_allocator: Option<syn::Path>,
+    options: Option<PathBuf>,
+    conveniences: Option<PathBuf>,
 }
 
 impl Config {
-    fn expand(self) -> Result<TokenStream> {
+    fn expand(mut self) -> Result<TokenStream> {
         fn resource_implementor(
             key: &str,
             name: &str,
@@ -264,12 +460,20 @@ impl Config {
             }))
         }
 
+        // A bindings crate only contains the imports of the world as the
+        // exports are implemented by the component itself
+        if self.component.is_some() {
+            self.resolve.worlds[self.world].exports.clear();
+        }
+
         let implementor =
             implementor_path_str(&self.implementor.unwrap_or_else(|| parse_quote!(Component)));
 
         let world = &self.resolve.worlds[self.world];
         let mut exports = HashMap::new();
-        exports.insert(ExportKey::World, implementor.clone());
+        if self.component.is_none() {
+            exports.insert(ExportKey::World, implementor.clone());
+        }
 
         for (name, item) in &world.exports {
             let key = match name {
@@ -325,6 +529,7 @@ impl Config {
             runtime_path: Some("::cargo_component_bindings::rt".to_string()),
             bitflags_path: Some("::cargo_component_bindings::bitflags".to_string()),
             additional_derive_attributes: self.additional_derives,
+            std_feature: self.no_std,
             ..Default::default()
         };
 
@@ -350,14 +555,84 @@ impl Config {
             "expected exactly one source file to be generated"
         );
 
-        let source = sources[0].parse::<TokenStream>()?;
+        let source = remap_modules(sources[0], &self.with).ok_or_else(|| {
+            Error::new(
+                Span::call_site(),
+                "failed to find a remapped module in the generated bindings",
+            )
+        })?;
+        let source = annotate_deprecations(&source).parse::<TokenStream>()?;
         let input = self.input.display().to_string();
 
+        // Include the conveniences generated for the types of the bindings
+        let conveniences = match &self.conveniences {
+            Some(path) => {
+                let source = fs::read_to_string(path).map_err(|e| {
+                    Error::new(
+                        Span::call_site(),
+                        format!(
+                            "failed to read conveniences file `{path}`: {e}",
+                            path = path.display()
+                        ),
+                    )
+                })?;
+                let tokens = source.parse::<TokenStream>()?;
+                let path = path.display().to_string();
+                Some(quote!(
+                    #tokens
+                    const _: &[u8] = include_bytes!(#path);
+                ))
+            }
+            None => None,
+        };
+
+        // Depend on the options file so that a change to it regenerates the bindings
+        let options = self.options.map(|path| {
+            let path = path.display().to_string();
+            quote!(
+                const _: &[u8] = include_bytes!(#path);
+            )
+        });
+
+        // The shims of a `no_std` component; a bindings crate is a library
+        // and leaves them to the component
+        let panic_handler = (self.panic_handler && self.component.is_none()).then(|| {
+            quote!(
+                #[cfg(all(target_arch = "wasm32", not(test)))]
+                #[panic_handler]
+                fn panic(_: &::core::panic::PanicInfo<'_>) -> ! {
+                    ::core::arch::wasm32::unreachable()
+                }
+            )
+        });
+
+        let global_allocator = self
+            .global_allocator
+            .filter(|_| self.component.is_none())
+            .map(|path| {
+                quote!(
+                    #[global_allocator]
+                    static ALLOCATOR: #path = #path;
+                )
+            });
+
+        let visibility = if self.component.is_some() {
+            quote!(pub)
+        } else {
+            quote!(pub(crate))
+        };
+
+        // The bindings themselves may use their deprecated items
         Ok(quote! {
-            pub(crate) mod bindings {
+            #[allow(deprecated)]
+            #visibility mod bindings {
                 #source
 
                 const _: &[u8] = include_bytes!(#input);
+                #options
+                #conveniences
+                #panic_handler
+                #global_allocator
             }
         })
     }
@@ -369,6 +644,7 @@ impl Parse for Config {
         let mut resources: Option<Vec<Resource>> = None;
         let mut ownership: Option<Ownership> = None;
         let mut additional_derives = Vec::new();
+        let mut target: Option<syn::LitStr> = None;
 
         if input.peek(token::Brace) {
             let content;
@@ -421,23 +697,77 @@ impl Parse for Config {
                             .map(|p| p.into_token_stream().to_string())
                             .collect()
                     }
+                    Opt::Target(span, value) => {
+                        if target.is_some() {
+                            return Err(Error::new(span, "cannot specify `target` more than once"));
+                        }
+
+                        target = Some(value);
+                    }
                 }
             }
         }
 
-        let input = target_path()?;
-        let (resolve, world) = decode_resolve(&input, Span::call_site())?;
+        let input = target_path(target.as_ref().map(|t| t.value()).as_deref())?;
+        let (resolve, world, component) = decode_resolve(
+            &input,
+            target
+                .as_ref()
+                .map(|t| t.span())
+                .unwrap_or_else(Span::call_site),
+        )?;
+
+        let manifest = read_options(&input, Span::call_site())?;
+        for derive in manifest.additional_derives.into_iter().rev() {
+            if !additional_derives.contains(&derive) {
+                additional_derives.insert(0, derive);
+            }
+        }
+
+        // A bindings crate only has the conveniences of imported types
+        let conveniences = input.with_file_name(if component.is_some() {
+            "import-conveniences.rs"
+        } else {
+            "conveniences.rs"
+        });
+
+        if let Some(component) = &component {
+            if implementor.is_some() || resources.is_some() {
+                return Err(Error::new(
+                    Span::call_site(),
+                    format!("cannot specify `implementor` or `resources` for the bindings crate of package `{component}`"),
+                ));
+            }
+        }
+
+        // A bindings crate may be the crate that modules are remapped to
+        let with = manifest
+            .with
+            .into_iter()
+            .filter(|(_, path)| {
+                component.is_none()
+                    || std::env::var("CARGO_CRATE_NAME").ok().as_deref()
+                        != path.trim_start_matches("::").split("::").next()
+            })
+            .collect();
 
         Ok(Config {
             input,
             resolve,
             world,
+            component,
             implementor,
             resources: resources
                 .map(|r| r.into_iter().map(|r| (r.key.value(), r.value)).collect())
                 .unwrap_or_default(),
-            ownership: ownership.unwrap_or_default(),
+            ownership: ownership.or(manifest.ownership).unwrap_or_default(),
             additional_derives,
+            with,
+            no_std: manifest.no_std,
+            panic_handler: manifest.panic_handler,
+            global_allocator: manifest.global_allocator,
+            options: manifest.path,
+            conveniences: conveniences.is_file().then_some(conveniences),
         })
     }
 }
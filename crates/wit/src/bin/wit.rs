@@ -1,9 +1,13 @@
 use anyhow::Result;
-use cargo_component_core::terminal::{Color, Terminal, Verbosity};
+use cargo_component_core::{
+    command::detect_message_format,
+    terminal::{Color, Terminal, Verbosity},
+};
 use clap::Parser;
 use std::process::exit;
 use wit::commands::{
-    AddCommand, BuildCommand, InitCommand, KeyCommand, PublishCommand, UpdateCommand,
+    AddCommand, BuildCommand, DiffCommand, FmtCommand, InfoCommand, InitCommand, KeyCommand,
+    LintCommand, PublishCommand, SearchCommand, UpdateCommand, VerifyCommand, YankCommand,
 };
 
 fn version() -> &'static str {
@@ -29,9 +33,16 @@ pub enum Command {
     Init(InitCommand),
     Add(AddCommand),
     Build(BuildCommand),
+    Diff(DiffCommand),
+    Fmt(FmtCommand),
+    Info(InfoCommand),
+    Lint(LintCommand),
+    Search(SearchCommand),
     Publish(PublishCommand),
     Key(KeyCommand),
     Update(UpdateCommand),
+    Verify(VerifyCommand),
+    Yank(YankCommand),
 }
 
 #[tokio::main]
@@ -44,11 +55,19 @@ async fn main() -> Result<()> {
         Command::Init(cmd) => cmd.exec().await,
         Command::Add(cmd) => cmd.exec().await,
         Command::Build(cmd) => cmd.exec().await,
+        Command::Diff(cmd) => cmd.exec().await,
+        Command::Fmt(cmd) => cmd.exec().await,
+        Command::Info(cmd) => cmd.exec().await,
+        Command::Lint(cmd) => cmd.exec().await,
+        Command::Search(cmd) => cmd.exec().await,
         Command::Publish(cmd) => cmd.exec().await,
         Command::Key(cmd) => cmd.exec().await,
         Command::Update(cmd) => cmd.exec().await,
+        Command::Verify(cmd) => cmd.exec().await,
+        Command::Yank(cmd) => cmd.exec().await,
     } {
-        let terminal = Terminal::new(Verbosity::Normal, Color::Auto);
+        let terminal = Terminal::new(Verbosity::Normal, Color::Auto)
+            .with_message_format(detect_message_format(std::env::args()));
         terminal.error(format!("{e:?}"))?;
         exit(1);
     }
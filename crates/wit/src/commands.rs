@@ -2,14 +2,28 @@
 
 mod add;
 mod build;
+mod diff;
+mod fmt;
+mod info;
 mod init;
 mod key;
+mod lint;
 mod publish;
+mod search;
 mod update;
+mod verify;
+mod yank;
 
 pub use add::*;
 pub use build::*;
+pub use diff::*;
+pub use fmt::*;
+pub use info::*;
 pub use init::*;
 pub use key::*;
+pub use lint::*;
 pub use publish::*;
+pub use search::*;
 pub use update::*;
+pub use verify::*;
+pub use yank::*;
@@ -1,25 +1,49 @@
-use crate::config::{Config, CONFIG_FILE_NAME};
+use crate::{
+    config::{Config, CONFIG_FILE_NAME},
+    lock::{acquire_lock_file_ro, acquire_lock_file_rw},
+    read_lock_file,
+};
 use anyhow::{bail, Context, Result};
 use cargo_component_core::{
     command::CommonOptions,
-    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryPackage},
+    lock::{LockFile, LockFileResolver},
+    registry::{
+        Dependency, DependencyResolution, DependencyResolver, RegistryPackage, RegistryResolution,
+        DEFAULT_REGISTRY_NAME,
+    },
     terminal::Terminal,
     VersionedPackageId,
 };
 use clap::Args;
 use semver::VersionReq;
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use warg_protocol::registry::PackageId;
+use wit_component::DecodedWasm;
+use wit_parser::UnresolvedPackage;
 
+#[allow(clippy::too_many_arguments)]
 async fn resolve_version(
     config: &Config,
     warg_config: &warg_client::Config,
+    lock_file: Option<&LockFile>,
     package: &VersionedPackageId,
     registry: &Option<String>,
     terminal: &Terminal,
-) -> Result<String> {
-    let mut resolver =
-        DependencyResolver::new(warg_config, &config.registries, None, terminal, true)?;
+    network_allowed: bool,
+    pre_releases: bool,
+) -> Result<(String, RegistryResolution)> {
+    let mut resolver = DependencyResolver::new(
+        warg_config,
+        &config.registries,
+        lock_file.map(LockFileResolver::new),
+        terminal,
+        network_allowed,
+    )?
+    .with_namespaces(&config.namespaces)
+    .with_pre_releases(pre_releases);
     let dependency = Dependency::Package(RegistryPackage {
         id: Some(package.id.clone()),
         version: package
@@ -28,6 +52,7 @@ async fn resolve_version(
             .unwrap_or(&VersionReq::STAR)
             .clone(),
         registry: registry.clone(),
+        interfaces: Vec::new(),
     });
 
     resolver.add_dependency(&package.id, &dependency).await?;
@@ -35,16 +60,96 @@ async fn resolve_version(
     let dependencies = resolver.resolve().await?;
     assert_eq!(dependencies.len(), 1);
 
-    match dependencies.values().next().expect("expected a resolution") {
-        DependencyResolution::Registry(resolution) => Ok(package
-            .version
-            .as_ref()
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| resolution.version.to_string())),
+    match dependencies
+        .into_values()
+        .next()
+        .expect("expected a resolution")
+    {
+        DependencyResolution::Registry(resolution) => Ok((
+            package
+                .version
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| resolution.version.to_string()),
+            resolution,
+        )),
         _ => unreachable!(),
     }
 }
 
+/// Determines if the given resolution is already in the lock file with the
+/// given version requirement.
+fn is_locked(
+    lock_file: Option<&LockFile>,
+    resolution: &RegistryResolution,
+    requirement: &VersionReq,
+) -> Result<bool> {
+    let lock_file = match lock_file {
+        Some(lock_file) => lock_file,
+        None => return Ok(false),
+    };
+
+    Ok(LockFileResolver::new(lock_file)
+        .resolve(
+            resolution
+                .registry
+                .as_deref()
+                .unwrap_or(DEFAULT_REGISTRY_NAME),
+            &resolution.package,
+            requirement,
+        )?
+        .map(|locked| locked.digest == resolution.digest)
+        .unwrap_or(false))
+}
+
+/// Reads the id of the WIT package at the given path.
+///
+/// The path may be a directory of WIT files, a single WIT file, or a
+/// binary-encoded WIT package.
+fn read_package_id(path: &Path) -> Result<PackageId> {
+    let name = if path.is_dir() {
+        UnresolvedPackage::parse_dir(path)
+            .with_context(|| {
+                format!(
+                    "failed to parse WIT package from directory `{path}`",
+                    path = path.display()
+                )
+            })?
+            .name
+    } else {
+        let bytes = fs::read(path)
+            .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+        if bytes.starts_with(b"\0asm") {
+            match wit_component::decode(&bytes).with_context(|| {
+                format!(
+                    "failed to decode WIT package `{path}`",
+                    path = path.display()
+                )
+            })? {
+                DecodedWasm::WitPackage(resolve, package) => resolve.packages[package].name.clone(),
+                DecodedWasm::Component(..) => bail!(
+                    "`{path}` is a WebAssembly component and not a WIT package; specify the id of the package to add",
+                    path = path.display()
+                ),
+            }
+        } else {
+            UnresolvedPackage::parse_file(path)
+                .with_context(|| {
+                    format!(
+                        "failed to parse WIT package `{path}`",
+                        path = path.display()
+                    )
+                })?
+                .name
+        }
+    };
+
+    format!("{ns}:{name}", ns = name.namespace, name = name.name)
+        .parse()
+        .with_context(|| format!("invalid package id for WIT package `{name}`"))
+}
+
 /// Adds a reference to a WIT package from a registry.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -65,13 +170,41 @@ pub struct AddCommand {
     #[clap(long, value_name = "ID")]
     pub id: Option<PackageId>,
 
-    /// Add a package dependency to a file or directory.
-    #[clap(long = "path", value_name = "PATH")]
+    /// Add a package dependency to a WIT directory, WIT file, or binary WIT package.
+    ///
+    /// The package id is read from the package if it is not specified.
+    #[clap(long = "path", visible_alias = "file", value_name = "PATH")]
     pub path: Option<PathBuf>,
 
     /// The id of the package to add a dependency to.
-    #[clap(value_name = "PACKAGE")]
-    pub package: VersionedPackageId,
+    #[clap(value_name = "PACKAGE", required_unless_present = "path")]
+    pub package: Option<VersionedPackageId>,
+
+    /// Use only the given interfaces or worlds of the package.
+    ///
+    /// The interfaces that they use from the package are kept as well.
+    #[clap(
+        long = "interface",
+        value_name = "INTERFACE",
+        value_delimiter = ',',
+        conflicts_with = "path"
+    )]
+    pub interfaces: Vec<String>,
+
+    /// Allow selecting a pre-release version of the package.
+    ///
+    /// Without this, pre-release versions are only selected when the version
+    /// requirement includes a pre-release (e.g. `my:pkg@0.3.0-rc1`).
+    #[clap(long = "pre", conflicts_with = "path")]
+    pub pre: bool,
+
+    /// Require the dependency to already be in the lock file.
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Require the dependency to already be in the lock file and don't access the network.
+    #[clap(long = "frozen")]
+    pub frozen: bool,
 }
 
 impl AddCommand {
@@ -82,8 +215,14 @@ impl AddCommand {
         let (mut config, config_path) = Config::from_default_file()?
             .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
 
-        let id = self.id.as_ref().unwrap_or(&self.package.id);
-        if config.dependencies.contains_key(id) {
+        let id = match (&self.id, &self.package, self.path.as_deref()) {
+            (Some(id), _, _) => id.clone(),
+            (None, Some(package), _) => package.id.clone(),
+            (None, None, Some(path)) => read_package_id(path)?,
+            (None, None, None) => unreachable!("clap should require a package or path"),
+        };
+
+        if config.dependencies.contains_key(&id) {
             bail!("cannot add dependency `{id}` as it conflicts with an existing dependency");
         }
 
@@ -102,29 +241,65 @@ impl AddCommand {
                 )
             }
             None => {
-                let version = resolve_version(
+                let package = self
+                    .package
+                    .as_ref()
+                    .expect("clap should require a package without a path");
+                let file_lock = acquire_lock_file_ro(&terminal, &config_path)?;
+                let lock_file = read_lock_file(file_lock.as_ref())?;
+                let (version, resolution) = resolve_version(
                     &config,
                     &warg_config,
-                    &self.package,
+                    lock_file.as_ref(),
+                    package,
                     &self.registry,
                     &terminal,
+                    !self.frozen,
+                    self.pre,
                 )
                 .await?;
 
+                // Ensure the selected interfaces are present in the package
+                if !self.interfaces.is_empty() {
+                    DependencyResolution::Registry(RegistryResolution {
+                        interfaces: self.interfaces.clone(),
+                        ..resolution.clone()
+                    })
+                    .decode()?;
+                }
+
                 let package = RegistryPackage {
-                    id: self.id.is_some().then(|| self.package.id.clone()),
+                    id: self.id.is_some().then(|| package.id.clone()),
                     version: version.parse().expect("expected a valid version"),
                     registry: self.registry,
+                    interfaces: self.interfaces.clone(),
                 };
 
-                let id = self.id.as_ref().unwrap_or(&self.package.id);
+                if (self.locked || self.frozen)
+                    && !is_locked(lock_file.as_ref(), &resolution, &package.version)?
+                {
+                    acquire_lock_file_rw(&terminal, &config_path, false, self.locked)?;
+                }
 
                 config
                     .dependencies
                     .insert(id.clone(), Dependency::Package(package));
 
                 format!(
-                    "dependency `{id}` with version `{version}`{dry_run}",
+                    "dependency `{id}` with version `{version}`{interfaces}{dry_run}",
+                    interfaces = if self.interfaces.is_empty() {
+                        String::new()
+                    } else {
+                        format!(
+                            " (interfaces {list})",
+                            list = self
+                                .interfaces
+                                .iter()
+                                .map(|i| format!("`{i}`"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    },
                     dry_run = if self.dry_run { " (dry run)" } else { "" }
                 )
             }
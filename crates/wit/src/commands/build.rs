@@ -1,11 +1,16 @@
 use crate::{
-    build_wit_package,
-    config::{Config, CONFIG_FILE_NAME},
+    build_wit_package, build_workspace,
+    config::{Config, Workspace, CONFIG_FILE_NAME},
+    LockOptions,
 };
-use anyhow::{Context, Result};
-use cargo_component_core::command::CommonOptions;
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{command::CommonOptions, terminal::Terminal};
 use clap::Args;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use warg_protocol::registry::PackageId;
 
 /// Build a binary WIT package.
 #[derive(Args)]
@@ -18,6 +23,20 @@ pub struct BuildCommand {
     /// The output package path.
     #[clap(short, long, value_name = "PATH")]
     pub output: Option<PathBuf>,
+
+    /// Output the package in the WebAssembly text format.
+    ///
+    /// This is implied if the output path has a `.wat` extension.
+    #[clap(long)]
+    pub wat: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
 }
 
 impl BuildCommand {
@@ -25,17 +44,84 @@ impl BuildCommand {
     pub async fn exec(self) -> Result<()> {
         log::debug!("executing build command");
 
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let terminal = self.common.new_terminal();
+        let lock_options = LockOptions {
+            locked: self.locked,
+            frozen: self.frozen,
+        };
+
+        if let Some(workspace) = Workspace::from_default_file()? {
+            if workspace.current.is_none() && self.output.is_some() {
+                bail!("the `--output` option cannot be used when building every member of a workspace");
+            }
+
+            let cwd = std::env::current_dir()?;
+            for (config_path, id, bytes) in
+                build_workspace(&workspace, &warg_config, &terminal, lock_options).await?
+            {
+                // Packages of the entire workspace are written to the member directories
+                let dir = match workspace.current {
+                    Some(_) => None,
+                    None => config_path
+                        .parent()
+                        .map(|d| d.strip_prefix(&cwd).unwrap_or(d)),
+                };
+
+                self.write_package(&terminal, &id, bytes, dir)?;
+            }
+
+            return Ok(());
+        }
+
         let (config, config_path) = Config::from_default_file()?
             .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
 
-        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let (id, bytes) =
+            build_wit_package(&config, &config_path, &warg_config, &terminal, lock_options).await?;
 
-        let terminal = self.common.new_terminal();
-        let (id, bytes) = build_wit_package(&config, &config_path, &warg_config, &terminal).await?;
+        self.write_package(&terminal, &id, bytes, None)
+    }
+
+    /// Writes a built package to the output path.
+    ///
+    /// If no output path was given, the package is written to the given
+    /// directory (or the current directory) and named after the package.
+    fn write_package(
+        &self,
+        terminal: &Terminal,
+        id: &PackageId,
+        bytes: Vec<u8>,
+        dir: Option<&Path>,
+    ) -> Result<()> {
+        let wat = self.wat
+            || self
+                .output
+                .as_ref()
+                .and_then(|p| p.extension())
+                .map(|e| e == "wat")
+                .unwrap_or(false);
 
-        let output = self
-            .output
-            .unwrap_or_else(|| format!("{name}.wasm", name = id.name()).into());
+        let output = self.output.clone().unwrap_or_else(|| {
+            let name = format!(
+                "{name}.{ext}",
+                name = id.name(),
+                ext = if wat { "wat" } else { "wasm" }
+            );
+
+            match dir {
+                Some(dir) => dir.join(name),
+                None => name.into(),
+            }
+        });
+
+        let bytes = if wat {
+            wasmprinter::print_bytes(&bytes)
+                .context("failed to print WIT package as WebAssembly text")?
+                .into_bytes()
+        } else {
+            bytes
+        };
 
         fs::write(&output, bytes).with_context(|| {
             format!(
@@ -49,6 +135,14 @@ impl BuildCommand {
             format!("package `{output}`", output = output.display()),
         )?;
 
+        terminal.event(
+            "package-written",
+            serde_json::json!({
+                "package": id.to_string(),
+                "path": output,
+            }),
+        )?;
+
         Ok(())
     }
 }
@@ -0,0 +1,141 @@
+use crate::{
+    config::{Config, CONFIG_FILE_NAME},
+    diff::diff,
+    fetch_wit_package, load_wit_package, LockOptions,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use semver::Version;
+use std::path::PathBuf;
+
+/// Compare WIT packages and classify the changes.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct DiffCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Use the specified registry name when fetching the old version.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Fail if the version in the configuration file is not bumped enough
+    /// for the changes since the old version.
+    #[clap(long = "check")]
+    pub check: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// The published version of the package, or the path to a WIT package
+    /// directory or binary WIT package, to compare against.
+    #[clap(value_name = "OLD")]
+    pub old: String,
+
+    /// The path to the WIT package directory or binary WIT package to
+    /// compare; defaults to the current package.
+    #[clap(value_name = "NEW")]
+    pub new: Option<PathBuf>,
+}
+
+impl DiffCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing diff command");
+
+        let (config, config_path) = Config::from_default_file()?
+            .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
+
+        let terminal = self.common.new_terminal();
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let lock_options = LockOptions {
+            locked: self.locked,
+            frozen: self.frozen,
+        };
+
+        let old_version = Version::parse(&self.old).ok();
+        if self.check && (old_version.is_none() || self.new.is_some()) {
+            bail!("`--check` requires comparing the current package against a published version");
+        }
+
+        let (old, old_package) = match &old_version {
+            Some(version) => {
+                fetch_wit_package(
+                    &config,
+                    &config_path,
+                    &warg_config,
+                    &terminal,
+                    lock_options,
+                    version,
+                    self.registry.as_deref(),
+                )
+                .await?
+            }
+            None => {
+                load_wit_package(
+                    &config,
+                    &config_path,
+                    &warg_config,
+                    &terminal,
+                    lock_options,
+                    Some(self.old.as_ref()),
+                )
+                .await?
+            }
+        };
+
+        let (new, new_package) = load_wit_package(
+            &config,
+            &config_path,
+            &warg_config,
+            &terminal,
+            lock_options,
+            self.new.as_deref(),
+        )
+        .await?;
+
+        let changes = diff(&old, old_package, &new, new_package);
+        for change in &changes {
+            println!("{change}");
+        }
+
+        let bump = match changes.iter().map(|c| c.bump).min() {
+            Some(bump) => bump,
+            None => {
+                terminal.note("no changes found")?;
+                return Ok(());
+            }
+        };
+
+        let part = format!("{bump:?}").to_lowercase();
+        let version = match &old_version {
+            Some(version) => version,
+            None => {
+                terminal.note(format!("the changes require a {part} version bump"))?;
+                return Ok(());
+            }
+        };
+
+        let required = bump.apply(version);
+        terminal.note(format!(
+            "the changes require a {part} version bump from v{version} to at least v{required}"
+        ))?;
+
+        if self.check && config.version < required {
+            bail!(
+                "version v{current} in `{path}` is not compatible with the changes since v{version}; expected at least v{required}",
+                current = config.version,
+                path = config_path.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{
+    config::{Config, CONFIG_FILE_NAME},
+    fmt::format_package,
+    load_wit_package, LockOptions,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::{fs, path::Path};
+
+/// Format the WIT files of the package.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct FmtCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Check that the files are formatted without modifying them.
+    #[clap(long = "check")]
+    pub check: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+}
+
+impl FmtCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing fmt command");
+
+        let (config, config_path) = Config::from_default_file()?
+            .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
+
+        let terminal = self.common.new_terminal();
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+
+        let (resolve, package) = load_wit_package(
+            &config,
+            &config_path,
+            &warg_config,
+            &terminal,
+            LockOptions {
+                locked: self.locked,
+                frozen: self.frozen,
+            },
+            None,
+        )
+        .await?;
+
+        let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+        let files = format_package(&resolve, package, dir)?;
+
+        let mut unformatted = 0;
+        for file in files.iter().filter(|f| f.is_changed()) {
+            if self.check {
+                terminal.warn(format!(
+                    "file `{path}` is not formatted",
+                    path = file.path.display()
+                ))?;
+                unformatted += 1;
+                continue;
+            }
+
+            fs::write(&file.path, &file.formatted)
+                .with_context(|| format!("failed to write `{path}`", path = file.path.display()))?;
+
+            terminal.status("Formatted", format!("`{path}`", path = file.path.display()))?;
+        }
+
+        if unformatted > 0 {
+            bail!("{unformatted} WIT file(s) are not formatted; run `wit fmt` to format them");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::config::{Config, ConfigBuilder};
+use anyhow::Result;
+use cargo_component_core::{command::CommonOptions, info::package_details, VersionedPackageId};
+use clap::Args;
+
+/// Display information about a package in a registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct InfoCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The name of the registry to query.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Run without accessing the network.
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The package to display information about, with an optional version requirement (e.g. `wasi:http@0.2`).
+    #[clap(value_name = "PACKAGE")]
+    pub package: VersionedPackageId,
+}
+
+impl InfoCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing info command");
+
+        // The registries of the current WIT package are used, if there is one
+        let config = Config::from_default_file()?
+            .map(|(config, _)| config)
+            .unwrap_or_else(|| ConfigBuilder::new().build());
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let terminal = self.common.new_terminal();
+
+        let details = package_details(
+            &warg_config,
+            &config.registries,
+            &config.namespaces,
+            &self.package.id,
+            self.package.version.as_ref(),
+            self.registry.as_deref(),
+            &terminal,
+            !self.offline,
+        )
+        .await?;
+
+        print!("{details}");
+        Ok(())
+    }
+}
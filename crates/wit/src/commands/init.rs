@@ -1,9 +1,18 @@
 use crate::config::{ConfigBuilder, CONFIG_FILE_NAME};
-use anyhow::{bail, Result};
-use cargo_component_core::{command::CommonOptions, registry::DEFAULT_REGISTRY_NAME};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    registry::{Dependency, RegistryPackage, DEFAULT_REGISTRY_NAME},
+    terminal::Terminal,
+};
 use clap::Args;
-use std::path::PathBuf;
+use semver::VersionReq;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use url::Url;
+use wit_component::{DecodedWasm, WitPrinter};
 
 /// Initialize a new WIT package.
 #[derive(Args)]
@@ -17,6 +26,10 @@ pub struct InitCommand {
     #[clap(long = "registry", value_name = "REGISTRY")]
     pub registry: Option<Url>,
 
+    /// Initialize the package from the WIT of a binary WIT package or component.
+    #[clap(long = "from-wasm", value_name = "FILE")]
+    pub from_wasm: Option<PathBuf>,
+
     /// The path to initialize the package in.
     #[clap(value_name = "PATH", default_value = ".")]
     pub path: PathBuf,
@@ -42,6 +55,10 @@ impl InitCommand {
             builder = builder.with_registry(DEFAULT_REGISTRY_NAME, registry);
         }
 
+        if let Some(wasm) = &self.from_wasm {
+            builder = extract_wit_package(wasm, &self.path, builder, &terminal)?;
+        }
+
         let config = builder.build();
         config.write(&path)?;
 
@@ -53,3 +70,104 @@ impl InitCommand {
         Ok(())
     }
 }
+
+/// Extracts the WIT of a binary WIT package or component into the given directory.
+///
+/// Foreign packages with a version become registry dependencies; foreign
+/// packages without a version are extracted to the `deps` directory and
+/// become local dependencies.
+fn extract_wit_package(
+    wasm: &Path,
+    dir: &Path,
+    mut builder: ConfigBuilder,
+    terminal: &Terminal,
+) -> Result<ConfigBuilder> {
+    let bytes = fs::read(wasm)
+        .with_context(|| format!("failed to read `{path}`", path = wasm.display()))?;
+
+    let (resolve, package) = match wit_component::decode(&bytes).with_context(|| {
+        format!(
+            "failed to decode WIT package `{path}`",
+            path = wasm.display()
+        )
+    })? {
+        DecodedWasm::WitPackage(resolve, package) => (resolve, package),
+        DecodedWasm::Component(resolve, world) => {
+            let package = resolve.worlds[world].package.with_context(|| {
+                format!(
+                    "the world of component `{path}` is not in a package",
+                    path = wasm.display()
+                )
+            })?;
+            (resolve, package)
+        }
+    };
+
+    let write = |path: PathBuf, package: wit_parser::PackageId| -> Result<()> {
+        let source = WitPrinter::default().print(&resolve, package)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{path}`",
+                    path = parent.display()
+                )
+            })?;
+        }
+
+        fs::write(&path, source)
+            .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+
+        terminal.status(
+            "Extracted",
+            format!(
+                "package `{name}` to `{path}`",
+                name = resolve.packages[package].name,
+                path = path.display()
+            ),
+        )
+    };
+
+    let name = &resolve.packages[package].name;
+    if let Some(version) = &name.version {
+        builder = builder.with_version(version.clone());
+    }
+
+    write(dir.join(format!("{name}.wit", name = name.name)), package)?;
+
+    for (id, dep) in &resolve.packages {
+        if id == package {
+            continue;
+        }
+
+        let dep_id = format!("{ns}:{name}", ns = dep.name.namespace, name = dep.name.name)
+            .parse()
+            .with_context(|| {
+                format!(
+                    "invalid package id for WIT package `{name}`",
+                    name = dep.name
+                )
+            })?;
+
+        let dependency = match &dep.name.version {
+            Some(version) => Dependency::Package(RegistryPackage {
+                id: None,
+                version: VersionReq::parse(&version.to_string())?,
+                registry: None,
+                interfaces: Vec::new(),
+            }),
+            None => {
+                let file = format!(
+                    "deps/{ns}-{name}.wit",
+                    ns = dep.name.namespace,
+                    name = dep.name.name
+                );
+                write(dir.join(&file), id)?;
+                Dependency::Local(file.into())
+            }
+        };
+
+        builder = builder.with_dependency(dep_id, dependency);
+    }
+
+    Ok(builder)
+}
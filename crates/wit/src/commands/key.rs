@@ -2,12 +2,16 @@ use anyhow::{bail, Context, Result};
 use cargo_component_core::{
     command::CommonOptions,
     keyring::{self, delete_signing_key, get_signing_key, get_signing_key_entry, set_signing_key},
+    keystore::{passphrase_from_env, Keystore},
     terminal::{Colors, Terminal},
 };
 use clap::{Args, Subcommand};
 use p256::ecdsa::SigningKey;
 use rand_core::OsRng;
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
 use warg_client::RegistryUrl;
 use warg_crypto::signing::PrivateKey;
 
@@ -57,6 +61,9 @@ pub struct KeyIdCommand {
     /// The key name of the signing key.
     #[clap(long, short, value_name = "NAME", default_value = "default")]
     pub key_name: String,
+    /// Use the encrypted keystore at the given path instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
     /// The URL of the registry to print the Key ID for.
     #[clap(value_name = "URL")]
     pub url: RegistryUrl,
@@ -65,7 +72,12 @@ pub struct KeyIdCommand {
 impl KeyIdCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
-        let key = get_signing_key(&self.url, &self.key_name)?;
+        let key = match &self.keystore {
+            Some(path) => {
+                Keystore::open(path)?.get(&self.url, &self.key_name, &keystore_passphrase(path)?)?
+            }
+            None => get_signing_key(&self.url, &self.key_name)?,
+        };
         println!(
             "{fingerprint}",
             fingerprint = key.public_key().fingerprint()
@@ -81,6 +93,10 @@ pub struct KeyNewCommand {
     /// The key name to use for the signing key.
     #[clap(long, short, value_name = "NAME", default_value = "default")]
     pub key_name: String,
+    /// Create the signing key in the encrypted keystore at the given path
+    /// instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
     /// The URL of the registry to create a signing key for.
     #[clap(value_name = "URL")]
     pub url: RegistryUrl,
@@ -89,6 +105,33 @@ pub struct KeyNewCommand {
 impl KeyNewCommand {
     /// Executes the command.
     pub async fn exec(self, terminal: &Terminal) -> Result<()> {
+        if let Some(path) = &self.keystore {
+            let mut keystore = Keystore::open(path)?;
+            if keystore.contains(&self.url, &self.key_name) {
+                bail!(
+                    "signing key `{name}` already exists for registry `{url}` in keystore `{path}`",
+                    name = self.key_name,
+                    url = self.url,
+                    path = path.display()
+                );
+            }
+
+            let key = SigningKey::random(&mut OsRng).into();
+            keystore.set(&self.url, &self.key_name, &key, &keystore_passphrase(path)?)?;
+            keystore.save(path)?;
+
+            return terminal.status(
+                "Created",
+                format!(
+                    "signing key `{name}` ({fingerprint}) for registry `{url}` in keystore `{path}`",
+                    name = self.key_name,
+                    fingerprint = key.public_key().fingerprint(),
+                    url = self.url,
+                    path = path.display()
+                ),
+            );
+        }
+
         let entry = get_signing_key_entry(&self.url, &self.key_name)?;
 
         match entry.get_password() {
@@ -135,6 +178,10 @@ pub struct KeySetCommand {
     /// The key name to use for the signing key.
     #[clap(long, short, value_name = "NAME", default_value = "default")]
     pub key_name: String,
+    /// Set the signing key in the encrypted keystore at the given path
+    /// instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
     /// The URL of the registry to create a signing key for.
     #[clap(value_name = "URL")]
     pub url: RegistryUrl,
@@ -149,7 +196,14 @@ impl KeySetCommand {
         )
         .context("signing key is not in the correct format")?;
 
-        set_signing_key(&self.url, &self.key_name, &key)?;
+        match &self.keystore {
+            Some(path) => {
+                let mut keystore = Keystore::open(path)?;
+                keystore.set(&self.url, &self.key_name, &key, &keystore_passphrase(path)?)?;
+                keystore.save(path)?;
+            }
+            None => set_signing_key(&self.url, &self.key_name, &key)?,
+        }
 
         terminal.status(
             "Set",
@@ -222,3 +276,23 @@ impl KeyDeleteCommand {
         Ok(())
     }
 }
+
+/// Gets the passphrase of a keystore from the `SIGNING_KEYSTORE_PASSPHRASE`
+/// environment variable or, if it is not set, by prompting for it.
+fn keystore_passphrase(path: &Path) -> Result<String> {
+    if let Ok(passphrase) = passphrase_from_env(path) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = rpassword::prompt_password(format!(
+        "passphrase for keystore `{path}`: ",
+        path = path.display()
+    ))
+    .context("failed to read keystore passphrase")?;
+
+    if passphrase.is_empty() {
+        bail!("the keystore passphrase cannot be empty");
+    }
+
+    Ok(passphrase)
+}
@@ -0,0 +1,82 @@
+use crate::{
+    config::{Config, CONFIG_FILE_NAME},
+    lint::{lint_package, LintLevel},
+    load_wit_package, LockOptions,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+
+/// Lint the interfaces of the package.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct LintCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Treat warnings as errors.
+    #[clap(long = "deny-warnings")]
+    pub deny_warnings: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+}
+
+impl LintCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing lint command");
+
+        let (config, config_path) = Config::from_default_file()?
+            .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
+
+        let terminal = self.common.new_terminal();
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+
+        let (resolve, package) = load_wit_package(
+            &config,
+            &config_path,
+            &warg_config,
+            &terminal,
+            LockOptions {
+                locked: self.locked,
+                frozen: self.frozen,
+            },
+            None,
+        )
+        .await?;
+
+        let name = &resolve.packages[package].name;
+        let lints = lint_package(&resolve, package, &config.lint);
+
+        let mut errors = 0;
+        for lint in &lints {
+            if lint.level == LintLevel::Warn && !self.deny_warnings {
+                terminal.warn(lint)?;
+            } else {
+                terminal.error(lint)?;
+                errors += 1;
+            }
+        }
+
+        if errors > 0 {
+            bail!("package `{name}` has {errors} lint error(s)");
+        }
+
+        terminal.status(
+            "Linted",
+            match lints.len() {
+                0 => format!("package `{name}`"),
+                warnings => format!("package `{name}` with {warnings} warning(s)"),
+            },
+        )?;
+
+        Ok(())
+    }
+}
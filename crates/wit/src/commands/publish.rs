@@ -1,12 +1,21 @@
 use crate::{
-    config::{Config, CONFIG_FILE_NAME},
-    publish_wit_package, PublishOptions,
+    config::{Config, Workspace, CONFIG_FILE_NAME},
+    pending::PendingPublish,
+    publish_wit_package, resolve_workspace_dependencies, wit_package_id, LockOptions,
+    PublishOptions,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    oci::is_oci_url,
+    registry::{find_namespace_registry, find_url, DependencyResolutionMap},
+    signing::{SigningKeyOptions, SigningKeyProvider},
+    static_registry::is_static_url,
+    terminal::Terminal,
 };
-use anyhow::{Context, Result};
-use cargo_component_core::{command::CommonOptions, keyring::get_signing_key, registry::find_url};
 use clap::Args;
+use std::path::{Path, PathBuf};
 use warg_client::RegistryUrl;
-use warg_crypto::signing::PrivateKey;
 use warg_protocol::registry::PackageId;
 
 /// Publish a WIT package to a registry.
@@ -29,13 +38,68 @@ pub struct PublishCommand {
     #[clap(long = "registry", value_name = "REGISTRY")]
     pub registry: Option<String>,
 
-    /// The key name to use for the signing key.
-    #[clap(long, short, value_name = "KEY", default_value = "default")]
-    pub key_name: String,
+    /// The key name to use for the signing key; defaults to `default`.
+    #[clap(long, short, value_name = "KEY")]
+    pub key_name: Option<String>,
+
+    /// The path to a file containing the signing key to use.
+    #[clap(
+        long = "key-file",
+        value_name = "PATH",
+        conflicts_with = "signing_command"
+    )]
+    pub key_file: Option<PathBuf>,
+
+    /// An external signing command holding the signing key to use.
+    #[clap(long = "signing-command", value_name = "COMMAND")]
+    pub signing_command: Option<String>,
+
+    /// The path to an encrypted keystore containing the signing key to use.
+    ///
+    /// The passphrase of the keystore is read from the
+    /// `SIGNING_KEYSTORE_PASSPHRASE` environment variable.
+    #[clap(
+        long = "keystore",
+        value_name = "PATH",
+        conflicts_with_all = ["key_file", "signing_command"]
+    )]
+    pub keystore: Option<PathBuf>,
 
     /// Override the package name to publish.
     #[clap(long, value_name = "NAME")]
     pub package: Option<PackageId>,
+
+    /// Publish the next major, minor, or patch version after the latest
+    /// version of the package in the registry.
+    #[clap(long = "bump", value_name = "PART", value_parser = ["major", "minor", "patch"])]
+    pub bump: Option<String>,
+
+    /// Write the bumped version back to the configuration file.
+    #[clap(long = "write-version", requires = "bump")]
+    pub write_version: bool,
+
+    /// Finish a publish that did not complete (e.g. due to a network
+    /// failure) instead of publishing the package again.
+    #[clap(long = "resume", conflicts_with_all = ["dry_run", "bump"])]
+    pub resume: bool,
+
+    /// Publish every member of the workspace, even from the directory of a
+    /// member.
+    #[clap(long = "all")]
+    pub all: bool,
+
+    /// Only publish the packages whose content changed since their latest
+    /// release in the registry.
+    #[clap(long = "if-changed", conflicts_with = "resume")]
+    pub if_changed: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
 }
 
 impl PublishCommand {
@@ -43,43 +107,179 @@ impl PublishCommand {
     pub async fn exec(self) -> Result<()> {
         log::debug!("executing publish command");
 
+        let terminal = self.common.new_terminal();
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+
+        if let Some(workspace) = Workspace::from_default_file()? {
+            return self
+                .publish_workspace(&workspace, &warg_config, &terminal)
+                .await;
+        }
+
+        if self.all {
+            bail!("the `--all` option can only be used in a workspace");
+        }
+
         let (config, config_path) = Config::from_default_file()?
             .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
 
-        let terminal = self.common.new_terminal();
-        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        self.publish_package(&config, &config_path, None, &warg_config, &terminal)
+            .await
+    }
+
+    /// Publishes the members of a workspace in dependency order.
+    ///
+    /// If the workspace was found from one of its members, only that member
+    /// is published unless `--all` is specified.
+    async fn publish_workspace(
+        &self,
+        workspace: &Workspace,
+        warg_config: &warg_client::Config,
+        terminal: &Terminal,
+    ) -> Result<()> {
+        let current = workspace.current.filter(|_| !self.all);
+        if current.is_none() && self.package.is_some() {
+            bail!(
+                "the `--package` option cannot be used when publishing every member of a workspace"
+            );
+        }
+
+        let (maps, order) = resolve_workspace_dependencies(
+            workspace,
+            warg_config,
+            terminal,
+            self.lock_options(),
+            true,
+        )
+        .await?;
+
+        for index in order {
+            if current.map(|c| c != index).unwrap_or(false) {
+                continue;
+            }
+
+            // Only the members with a pending publish are resumed
+            let member = &workspace.members[index];
+            if self.resume
+                && current.is_none()
+                && !PendingPublish::path(&member.config_path).is_file()
+            {
+                continue;
+            }
+
+            self.publish_package(
+                &member.config,
+                &member.config_path,
+                Some(&maps[index]),
+                warg_config,
+                terminal,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn publish_package(
+        &self,
+        config: &Config,
+        config_path: &Path,
+        dependencies: Option<&DependencyResolutionMap>,
+        warg_config: &warg_client::Config,
+        terminal: &Terminal,
+    ) -> Result<()> {
+        // Use the registry mapped to the package's namespace if one wasn't specified
+        let registry = match self.registry.as_deref() {
+            Some(registry) => Some(registry),
+            None if !config.namespaces.is_empty() => {
+                let id = match &self.package {
+                    Some(id) => id.clone(),
+                    None => wit_package_id(config_path)?,
+                };
+
+                find_namespace_registry(&config.namespaces, &id)
+            }
+            None => None,
+        };
 
         let url = find_url(
-            self.registry.as_deref(),
+            registry,
             &config.registries,
             warg_config.default_url.as_deref(),
         )?;
 
-        let signing_key = if let Ok(key) = std::env::var("WIT_PUBLISH_KEY") {
-            PrivateKey::decode(key).context(
-                "failed to parse signing key from `WIT_PUBLISH_KEY` environment variable",
-            )?
+        if is_static_url(url) {
+            bail!("publishing to static registry `{url}` is not supported; upload the package and add it to the package's `index.json` instead", url = url);
+        }
+
+        // OCI registries do not use signing keys
+        let signing_key = if is_oci_url(url) {
+            None
         } else {
             let url: RegistryUrl = url
                 .parse()
                 .with_context(|| format!("failed to parse registry URL `{url}`"))?;
 
-            get_signing_key(&url, &self.key_name)?
+            let command = self
+                .signing_command
+                .clone()
+                .or_else(|| std::env::var("WIT_SIGNING_COMMAND").ok());
+
+            // Key files and keystores in the configuration are relative to the configuration file
+            let key_config = config
+                .signing_keys
+                .get(registry.unwrap_or("default"))
+                .map(|key| {
+                    let mut key = key.clone();
+                    if let Some(dir) = config_path.parent() {
+                        for path in [&mut key.key_file, &mut key.keystore].into_iter().flatten() {
+                            *path = dir.join(path.as_path());
+                        }
+                    }
+                    key
+                });
+
+            Some(
+                SigningKeyProvider::select(
+                    SigningKeyOptions {
+                        key_file: self.key_file.as_deref(),
+                        command: command.as_deref(),
+                        keystore: self.keystore.as_deref(),
+                        key_name: self.key_name.as_deref(),
+                    },
+                    key_config.as_ref(),
+                    "WIT_PUBLISH_KEY",
+                )
+                .signing_key(&url)?,
+            )
         };
 
         publish_wit_package(
             PublishOptions {
-                config: &config,
-                config_path: &config_path,
-                warg_config: &warg_config,
+                config,
+                dependencies,
+                config_path,
+                warg_config,
                 url,
-                signing_key: &signing_key,
+                signing_key: signing_key.as_ref(),
                 package: self.package.as_ref(),
                 init: self.init,
                 dry_run: self.dry_run,
+                lock_options: self.lock_options(),
+                bump: self.bump.as_deref().map(str::parse).transpose()?,
+                write_version: self.write_version,
+                resume: self.resume,
+                if_changed: self.if_changed,
             },
-            &terminal,
+            terminal,
         )
         .await
     }
+
+    fn lock_options(&self) -> LockOptions {
+        LockOptions {
+            locked: self.locked,
+            frozen: self.frozen,
+        }
+    }
 }
@@ -0,0 +1,63 @@
+use crate::config::{Config, ConfigBuilder};
+use anyhow::Result;
+use cargo_component_core::{command::CommonOptions, info::search, registry::find_url};
+use clap::Args;
+
+/// Search a registry for packages.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct SearchCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The name of the registry to search.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Run without accessing the network.
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The text to search package ids for.
+    #[clap(value_name = "QUERY")]
+    pub query: String,
+}
+
+impl SearchCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing search command");
+
+        // The registries of the current WIT package are used, if there is one
+        let config = Config::from_default_file()?
+            .map(|(config, _)| config)
+            .unwrap_or_else(|| ConfigBuilder::new().build());
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let terminal = self.common.new_terminal();
+
+        let url = find_url(
+            self.registry.as_deref(),
+            &config.registries,
+            warg_config.default_url.as_deref(),
+        )?;
+
+        let results = search(&warg_config, url, &self.query, &terminal, !self.offline).await?;
+        if results.is_empty() {
+            terminal.note(format!(
+                "no packages matching `{query}` were found",
+                query = self.query
+            ))?;
+            return Ok(());
+        }
+
+        for result in results {
+            match result.latest {
+                Some(version) => println!("{id} = \"{version}\"", id = result.id),
+                None => println!("{id} (no releases)", id = result.id),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
-use crate::config::{Config, CONFIG_FILE_NAME};
+use crate::{
+    config::{Config, Workspace, CONFIG_FILE_NAME},
+    update_workspace_lockfile, LockOptions, UpdateOptions,
+};
 use anyhow::{Context, Result};
 use cargo_component_core::command::CommonOptions;
 use clap::Args;
+use warg_protocol::registry::PackageId;
 
 /// Update dependencies as recorded in the lock file.
 #[derive(Args)]
@@ -14,6 +18,18 @@ pub struct UpdateCommand {
     /// Don't actually write the lockfile
     #[clap(long = "dry-run")]
     pub dry_run: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Registry package to update; all dependencies are updated if not specified
+    #[clap(long = "package", short = 'p', value_name = "PACKAGE")]
+    pub packages: Vec<PackageId>,
 }
 
 impl UpdateCommand {
@@ -21,12 +37,40 @@ impl UpdateCommand {
     pub async fn exec(self) -> Result<()> {
         log::debug!("executing update command");
 
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let terminal = self.common.new_terminal();
+        let lock_options = LockOptions {
+            locked: self.locked,
+            frozen: self.frozen,
+        };
+        let options = UpdateOptions {
+            packages: self.packages,
+            dry_run: self.dry_run,
+        };
+
+        // The members of a workspace share its lock file
+        if let Some(workspace) = Workspace::from_default_file()? {
+            return update_workspace_lockfile(
+                &workspace,
+                &warg_config,
+                &terminal,
+                lock_options,
+                &options,
+            )
+            .await;
+        }
+
         let (config, config_path) = Config::from_default_file()?
             .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
 
-        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
-
-        let terminal = self.common.new_terminal();
-        crate::update_lockfile(&config, &config_path, &warg_config, &terminal, self.dry_run).await
+        crate::update_lockfile(
+            &config,
+            &config_path,
+            &warg_config,
+            &terminal,
+            lock_options,
+            &options,
+        )
+        .await
     }
 }
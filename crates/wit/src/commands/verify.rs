@@ -0,0 +1,119 @@
+use crate::{
+    config::{Config, CONFIG_FILE_NAME},
+    diff::diff,
+    encode_wit_package, fetch_wit_package, parse_wit_package, resolve_dependencies, LockOptions,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use semver::Version;
+use std::path::Path;
+
+/// Verify that the package is valid and ready to be published.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct VerifyCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The published version of the package to check compatibility against.
+    #[clap(long = "against", value_name = "VERSION")]
+    pub against: Option<Version>,
+
+    /// Use the specified registry name when fetching the published version.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+}
+
+impl VerifyCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing verify command");
+
+        let (config, config_path) = Config::from_default_file()?
+            .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
+
+        let terminal = self.common.new_terminal();
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+        let lock_options = LockOptions {
+            locked: self.locked,
+            frozen: self.frozen,
+        };
+
+        // Every dependency must resolve; with `--locked`, to the locked versions
+        let dependencies = resolve_dependencies(
+            &config,
+            &config_path,
+            &warg_config,
+            &terminal,
+            lock_options,
+            true,
+        )
+        .await?;
+
+        // The package must parse and encode as it would when published
+        let (id, _) = encode_wit_package(&config_path, &dependencies)?;
+
+        if let Some(version) = &self.against {
+            if config.version <= *version {
+                bail!(
+                    "version v{current} in `{path}` must be greater than the published version v{version}",
+                    current = config.version,
+                    path = config_path.display()
+                );
+            }
+
+            let (old, old_package) = fetch_wit_package(
+                &config,
+                &config_path,
+                &warg_config,
+                &terminal,
+                lock_options,
+                version,
+                self.registry.as_deref(),
+            )
+            .await?;
+
+            let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+            let (new, new_package) = parse_wit_package(dir, &dependencies)?;
+
+            let changes = diff(&old, old_package, &new, new_package);
+            if let Some(bump) = changes.iter().map(|c| c.bump).min() {
+                let required = bump.apply(version);
+                if config.version < required {
+                    for change in &changes {
+                        terminal.error(change)?;
+                    }
+
+                    bail!(
+                        "version v{current} in `{path}` is not compatible with the changes since v{version}; expected at least v{required}",
+                        current = config.version,
+                        path = config_path.display()
+                    );
+                }
+            }
+        }
+
+        terminal.status(
+            "Verified",
+            match &self.against {
+                Some(version) => format!(
+                    "package `{id}` v{current} against v{version}",
+                    current = config.version
+                ),
+                None => format!("package `{id}` v{current}", current = config.version),
+            },
+        )?;
+
+        Ok(())
+    }
+}
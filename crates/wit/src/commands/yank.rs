@@ -0,0 +1,111 @@
+use crate::{
+    config::{Config, CONFIG_FILE_NAME},
+    wit_package_id,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    oci::is_oci_url,
+    registry::{find_namespace_registry, find_url, yank},
+    signing::SigningKeyProvider,
+    static_registry::is_static_url,
+};
+use clap::Args;
+use semver::Version;
+use warg_client::RegistryUrl;
+use warg_protocol::registry::PackageId;
+
+/// Yank a release of a WIT package from a registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct YankCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Use the specified registry name when yanking the release.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// The key name to use for the signing key.
+    #[clap(long, short, value_name = "KEY", default_value = "default")]
+    pub key_name: String,
+
+    /// An external signing command holding the signing key to use.
+    #[clap(long = "signing-command", value_name = "COMMAND")]
+    pub signing_command: Option<String>,
+
+    /// Override the name of the package to yank the release of.
+    #[clap(long, value_name = "NAME")]
+    pub package: Option<PackageId>,
+
+    /// Undo a previous yank of the release.
+    #[clap(long = "undo")]
+    pub undo: bool,
+
+    /// The version of the release to yank.
+    #[clap(value_name = "VERSION")]
+    pub version: Version,
+}
+
+impl YankCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing yank command");
+
+        let (config, config_path) = Config::from_default_file()?
+            .with_context(|| format!("failed to find configuration file `{CONFIG_FILE_NAME}`"))?;
+
+        let terminal = self.common.new_terminal();
+        let warg_config = warg_client::Config::from_default_file()?.unwrap_or_default();
+
+        let id = match &self.package {
+            Some(id) => id.clone(),
+            None => wit_package_id(&config_path)?,
+        };
+
+        if self.undo {
+            bail!("component registries do not support undoing the yank of a release; publish a new version of package `{id}` instead");
+        }
+
+        // Use the registry mapped to the package's namespace if one wasn't specified
+        let url = find_url(
+            self.registry
+                .as_deref()
+                .or_else(|| find_namespace_registry(&config.namespaces, &id)),
+            &config.registries,
+            warg_config.default_url.as_deref(),
+        )?;
+
+        if is_oci_url(url) {
+            bail!("yanking releases from OCI registry `{url}` is not supported");
+        }
+
+        if is_static_url(url) {
+            bail!("yanking releases from static registry `{url}` is not supported; mark the version as yanked in the package's `index.json` instead");
+        }
+
+        let registry_url: RegistryUrl = url
+            .parse()
+            .with_context(|| format!("failed to parse registry URL `{url}`"))?;
+
+        let command = self
+            .signing_command
+            .clone()
+            .or_else(|| std::env::var("WIT_SIGNING_COMMAND").ok());
+
+        let signing_key =
+            SigningKeyProvider::new(command.as_deref(), "WIT_PUBLISH_KEY", &self.key_name)
+                .signing_key(&registry_url)?;
+
+        yank(
+            &warg_config,
+            url,
+            &id,
+            &self.version,
+            &signing_key,
+            &terminal,
+        )
+        .await
+    }
+}
@@ -1,7 +1,11 @@
 //! Module for WIT package configuration.
 
-use anyhow::{Context, Result};
-use cargo_component_core::registry::Dependency;
+use crate::{lint::LintConfig, VersionBump};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    registry::{Dependency, NamespaceRegistries},
+    signing::SigningKeyConfig,
+};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -35,6 +39,7 @@ fn find_config(cwd: &Path) -> Option<PathBuf> {
 #[derive(Default)]
 pub struct ConfigBuilder {
     version: Option<Version>,
+    dependencies: HashMap<PackageId, Dependency>,
     registries: HashMap<String, Url>,
 }
 
@@ -50,6 +55,12 @@ impl ConfigBuilder {
         self
     }
 
+    /// Adds a dependency to the configuration.
+    pub fn with_dependency(mut self, id: PackageId, dependency: Dependency) -> Self {
+        self.dependencies.insert(id, dependency);
+        self
+    }
+
     /// Adds a registry to the configuration.
     pub fn with_registry(mut self, name: impl Into<String>, url: Url) -> Self {
         self.registries.insert(name.into(), url);
@@ -60,8 +71,11 @@ impl ConfigBuilder {
     pub fn build(self) -> Config {
         Config {
             version: self.version.unwrap_or_else(|| Version::new(0, 1, 0)),
-            dependencies: Default::default(),
+            dependencies: self.dependencies,
+            patch: Default::default(),
             registries: self.registries,
+            namespaces: Default::default(),
+            signing_keys: Default::default(),
             authors: Default::default(),
             categories: Default::default(),
             description: None,
@@ -69,6 +83,9 @@ impl ConfigBuilder {
             documentation: None,
             homepage: None,
             repository: None,
+            changelog: None,
+            lint: Default::default(),
+            publish: Default::default(),
         }
     }
 }
@@ -81,9 +98,22 @@ pub struct Config {
     /// The package dependencies.
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub dependencies: HashMap<PackageId, Dependency>,
+    /// The patches overriding registry packages.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub patch: HashMap<PackageId, Dependency>,
     /// The registries to use for sourcing packages.
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub registries: HashMap<String, Url>,
+    /// The names of the registries to use for packages in a namespace.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub namespaces: HashMap<String, NamespaceRegistries>,
+    /// The signing keys to use when publishing to a registry, by registry name.
+    #[serde(
+        default,
+        rename = "signing-keys",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub signing_keys: HashMap<String, SigningKeyConfig>,
     /// The authors of the package.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub authors: Vec<String>,
@@ -105,6 +135,34 @@ pub struct Config {
     /// The package repository URL.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub repository: Option<String>,
+    /// The package changelog URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<String>,
+    /// The lint rules of the package.
+    #[serde(default, skip_serializing_if = "LintConfig::is_default")]
+    pub lint: LintConfig,
+    /// The options for publishing the package.
+    #[serde(default, skip_serializing_if = "PublishConfig::is_default")]
+    pub publish: PublishConfig,
+}
+
+/// Represents the `publish` table of a WIT package configuration.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PublishConfig {
+    /// The part of the version to increment when publishing a package that
+    /// changed since its latest release.
+    ///
+    /// The `--bump` option of `wit publish` takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bump: Option<VersionBump>,
+}
+
+impl PublishConfig {
+    /// Determines if the publish options are the defaults.
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
 }
 
 impl Config {
@@ -118,6 +176,13 @@ impl Config {
     /// Returns `Ok(None)` if no configuration file was found.
     pub fn from_default_file() -> Result<Option<(Self, PathBuf)>> {
         if let Some(path) = find_config(&std::env::current_dir()?) {
+            if is_workspace_file(&path)? {
+                bail!(
+                    "configuration file `{path}` is for a workspace; run this command from the directory of a workspace member",
+                    path = path.display()
+                );
+            }
+
             return Ok(Some((Self::from_file(&path)?, path)));
         }
 
@@ -181,3 +246,170 @@ impl Config {
         Ok(())
     }
 }
+
+/// Determines if the given configuration file is for a workspace.
+fn is_workspace_file(path: &Path) -> Result<bool> {
+    let contents = fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read configuration file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let document: toml_edit::Document = contents.parse().with_context(|| {
+        format!(
+            "failed to parse configuration file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    Ok(document.contains_key("workspace"))
+}
+
+/// Represents the `workspace` table of a workspace configuration file.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceTable {
+    /// The directories of the member packages, relative to the workspace.
+    pub members: Vec<PathBuf>,
+}
+
+/// Represents a WIT workspace configuration file.
+///
+/// A workspace configuration file has only a `workspace` table listing the
+/// directories of its members.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    /// The workspace table.
+    pub workspace: WorkspaceTable,
+}
+
+/// Represents a member package of a WIT workspace.
+pub struct WorkspaceMember {
+    /// The configuration of the member.
+    pub config: Config,
+    /// The path to the configuration file of the member.
+    pub config_path: PathBuf,
+}
+
+impl WorkspaceMember {
+    /// Gets the directory of the member.
+    pub fn dir(&self) -> &Path {
+        self.config_path.parent().unwrap_or_else(|| Path::new("."))
+    }
+}
+
+/// Represents a WIT workspace of several packages sharing one lock file.
+pub struct Workspace {
+    /// The path to the workspace configuration file.
+    ///
+    /// The lock file of the workspace is next to it.
+    pub config_path: PathBuf,
+    /// The members of the workspace, in the order they are listed.
+    pub members: Vec<WorkspaceMember>,
+    /// The index of the member the workspace was found from.
+    ///
+    /// This is `None` if the workspace was found from its own directory.
+    pub current: Option<usize>,
+}
+
+impl Workspace {
+    /// Loads the WIT workspace of the current directory.
+    ///
+    /// The current directory is either the directory of the workspace (or one
+    /// of its subdirectories that is not a package) or the directory of one of
+    /// its members.
+    ///
+    /// Returns `Ok(None)` if the current directory is not part of a workspace.
+    pub fn from_default_file() -> Result<Option<Self>> {
+        let path = match find_config(&std::env::current_dir()?) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        if is_workspace_file(&path)? {
+            return Self::from_file(&path).map(Some);
+        }
+
+        // Search the parent directories for a workspace containing the package
+        let package_path = fs::canonicalize(&path).with_context(|| {
+            format!(
+                "failed to canonicalize path `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let mut parent = path.parent().and_then(Path::parent);
+        while let Some(root) = parent.and_then(find_config) {
+            if is_workspace_file(&root)? {
+                let mut workspace = Self::from_file(&root)?;
+                workspace.current = workspace.members.iter().position(|m| {
+                    fs::canonicalize(&m.config_path)
+                        .map(|p| p == package_path)
+                        .unwrap_or(false)
+                });
+
+                if workspace.current.is_some() {
+                    return Ok(Some(workspace));
+                }
+            }
+
+            parent = root.parent().and_then(Path::parent);
+        }
+
+        Ok(None)
+    }
+
+    /// Loads a WIT workspace from the given configuration file path.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).with_context(|| {
+            format!(
+                "failed to read configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let config: WorkspaceConfig = toml_edit::de::from_str(&contents).with_context(|| {
+            format!(
+                "failed to parse configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let members = config
+            .workspace
+            .members
+            .iter()
+            .map(|member| {
+                let config_path = dir.join(member).join(CONFIG_FILE_NAME);
+                if !config_path.is_file() {
+                    bail!(
+                        "workspace member `{member}` does not have a `{CONFIG_FILE_NAME}` file",
+                        member = member.display()
+                    );
+                }
+
+                if is_workspace_file(&config_path)? {
+                    bail!(
+                        "workspace member `{member}` cannot itself be a workspace",
+                        member = member.display()
+                    );
+                }
+
+                Ok(WorkspaceMember {
+                    config: Config::from_file(&config_path)?,
+                    config_path,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            config_path: path.to_path_buf(),
+            members,
+            current: None,
+        })
+    }
+}
@@ -0,0 +1,438 @@
+//! Module for comparing versions of a WIT package.
+
+use crate::VersionBump;
+use indexmap::IndexMap;
+use std::fmt::{self, Write};
+use wit_parser::{
+    Docs, Function, FunctionKind, Handle, InterfaceId, PackageId, Resolve, Results, Type,
+    TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem, WorldKey,
+};
+
+/// Represents a change between two versions of a WIT package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The version bump the change requires.
+    pub bump: VersionBump,
+    /// The description of the change.
+    pub description: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{bump}: {description}",
+            bump = match self.bump {
+                VersionBump::Major => "major",
+                VersionBump::Minor => "minor",
+                VersionBump::Patch => "patch",
+            },
+            description = self.description
+        )
+    }
+}
+
+/// Compares two versions of a WIT package.
+///
+/// Removing or changing an item requires a major version bump, as does
+/// adding an export to a world. Adding an interface, world, type, function,
+/// or world import requires a minor version bump. Changes to documentation
+/// only require a patch version bump.
+pub fn diff(
+    old: &Resolve,
+    old_package: PackageId,
+    new: &Resolve,
+    new_package: PackageId,
+) -> Vec<Change> {
+    let mut differ = Differ {
+        old,
+        new,
+        changes: Vec::new(),
+    };
+
+    let old_package = &old.packages[old_package];
+    let new_package = &new.packages[new_package];
+
+    differ.docs(&old_package.docs, &new_package.docs, "the package");
+
+    differ.items(
+        "interface",
+        &old_package.interfaces,
+        &new_package.interfaces,
+        VersionBump::Minor,
+        |differ, name, old, new| differ.interface(name, *old, *new),
+    );
+
+    differ.items(
+        "world",
+        &old_package.worlds,
+        &new_package.worlds,
+        VersionBump::Minor,
+        |differ, name, old, new| differ.world(name, *old, *new),
+    );
+
+    differ.changes
+}
+
+struct Differ<'a> {
+    old: &'a Resolve,
+    new: &'a Resolve,
+    changes: Vec<Change>,
+}
+
+impl Differ<'_> {
+    fn push(&mut self, bump: VersionBump, description: String) {
+        self.changes.push(Change { bump, description });
+    }
+
+    /// Compares two maps of named items, reporting removed and added items
+    /// before calling `compare` for the items present in both.
+    fn items<T>(
+        &mut self,
+        kind: &str,
+        old: &IndexMap<String, T>,
+        new: &IndexMap<String, T>,
+        added: VersionBump,
+        mut compare: impl FnMut(&mut Self, &str, &T, &T),
+    ) {
+        for (name, old_item) in old {
+            match new.get(name) {
+                Some(new_item) => compare(self, name, old_item, new_item),
+                None => self.push(VersionBump::Major, format!("removed {kind} `{name}`")),
+            }
+        }
+
+        for name in new.keys().filter(|name| !old.contains_key(*name)) {
+            self.push(added, format!("added {kind} `{name}`"));
+        }
+    }
+
+    fn docs(&mut self, old: &Docs, new: &Docs, item: &str) {
+        if old.contents != new.contents {
+            self.push(
+                VersionBump::Patch,
+                format!("changed the documentation of {item}"),
+            );
+        }
+    }
+
+    fn interface(&mut self, name: &str, old: InterfaceId, new: InterfaceId) {
+        let (old_resolve, new_resolve) = (self.old, self.new);
+        let old_interface = &old_resolve.interfaces[old];
+        let new_interface = &new_resolve.interfaces[new];
+
+        self.docs(
+            &old_interface.docs,
+            &new_interface.docs,
+            &format!("interface `{name}`"),
+        );
+
+        self.items(
+            "type",
+            &qualify(name, &old_interface.types),
+            &qualify(name, &new_interface.types),
+            VersionBump::Minor,
+            |differ, name, old, new| differ.ty(name, **old, **new),
+        );
+
+        self.items(
+            "function",
+            &qualify(name, &old_interface.functions),
+            &qualify(name, &new_interface.functions),
+            VersionBump::Minor,
+            |differ, name, old, new| differ.function(name, old, new),
+        );
+    }
+
+    fn ty(&mut self, name: &str, old: TypeId, new: TypeId) {
+        let (old_resolve, new_resolve) = (self.old, self.new);
+        let old_ty = &old_resolve.types[old];
+        let new_ty = &new_resolve.types[new];
+
+        if Printer(old_resolve).definition(&old_ty.kind)
+            != Printer(new_resolve).definition(&new_ty.kind)
+        {
+            self.push(
+                VersionBump::Major,
+                format!("changed the definition of type `{name}`"),
+            );
+        }
+
+        self.docs(&old_ty.docs, &new_ty.docs, &format!("type `{name}`"));
+    }
+
+    fn function(&mut self, name: &str, old: &Function, new: &Function) {
+        if Printer(self.old).signature(old) != Printer(self.new).signature(new) {
+            self.push(
+                VersionBump::Major,
+                format!("changed the signature of function `{name}`"),
+            );
+        }
+
+        self.docs(&old.docs, &new.docs, &format!("function `{name}`"));
+    }
+
+    fn world(&mut self, name: &str, old: WorldId, new: WorldId) {
+        let (old_resolve, new_resolve) = (self.old, self.new);
+        let old_world = &old_resolve.worlds[old];
+        let new_world = &new_resolve.worlds[new];
+
+        self.docs(&old_world.docs, &new_world.docs, &format!("world `{name}`"));
+
+        for (kind, old_items, new_items, added) in [
+            (
+                "import",
+                &old_world.imports,
+                &new_world.imports,
+                VersionBump::Minor,
+            ),
+            (
+                "export",
+                &old_world.exports,
+                &new_world.exports,
+                VersionBump::Major,
+            ),
+        ] {
+            let old_items = world_items(old_resolve, name, old_items);
+            let new_items = world_items(new_resolve, name, new_items);
+            self.items(
+                kind,
+                &old_items,
+                &new_items,
+                added,
+                |differ, name, old, new| differ.world_item(kind, name, old, new),
+            );
+        }
+    }
+
+    fn world_item(&mut self, kind: &str, name: &str, old: &WorldItem, new: &WorldItem) {
+        match (old, new) {
+            // Interfaces of the package are compared on their own; inline
+            // interfaces are compared as part of the world
+            (WorldItem::Interface(old), WorldItem::Interface(new)) => {
+                let old_interface = &self.old.interfaces[*old];
+                let new_interface = &self.new.interfaces[*new];
+                if old_interface.name.is_none() && new_interface.name.is_none() {
+                    self.interface(name, *old, *new);
+                }
+            }
+            (WorldItem::Function(old), WorldItem::Function(new)) => self.function(name, old, new),
+            (WorldItem::Type(old), WorldItem::Type(new)) => self.ty(name, *old, *new),
+            _ => self.push(
+                VersionBump::Major,
+                format!("changed the kind of {kind} `{name}`"),
+            ),
+        }
+    }
+}
+
+/// Qualifies the names of the items of an interface or world with its name.
+fn qualify<T>(name: &str, items: &IndexMap<String, T>) -> IndexMap<String, &T> {
+    items
+        .iter()
+        .map(|(item, value)| (format!("{name}.{item}"), value))
+        .collect()
+}
+
+/// Gets the items of a world keyed by their qualified names.
+fn world_items<'a>(
+    resolve: &Resolve,
+    world: &str,
+    items: &'a IndexMap<WorldKey, WorldItem>,
+) -> IndexMap<String, &'a WorldItem> {
+    items
+        .iter()
+        .map(|(key, item)| {
+            let name = match key {
+                WorldKey::Name(name) => format!("{world}.{name}"),
+                WorldKey::Interface(id) => interface_name(resolve, *id),
+            };
+            (name, item)
+        })
+        .collect()
+}
+
+/// Gets the name of an interface without the version of its package.
+///
+/// The version is omitted so that interfaces of the package itself and of
+/// foreign packages compare equal across versions.
+fn interface_name(resolve: &Resolve, id: InterfaceId) -> String {
+    let interface = &resolve.interfaces[id];
+    match (interface.package, &interface.name) {
+        (Some(package), Some(name)) => {
+            let package = &resolve.packages[package].name;
+            format!(
+                "{ns}:{pkg}/{name}",
+                ns = package.namespace,
+                pkg = package.name
+            )
+        }
+        (_, Some(name)) => name.clone(),
+        _ => "<unnamed>".to_string(),
+    }
+}
+
+/// Prints type definitions and function signatures in a form that can be
+/// compared between packages.
+struct Printer<'a>(&'a Resolve);
+
+impl Printer<'_> {
+    fn definition(&self, kind: &TypeDefKind) -> String {
+        let mut def = String::new();
+        match kind {
+            TypeDefKind::Record(record) => {
+                def.push_str("record {");
+                for field in &record.fields {
+                    write!(
+                        def,
+                        " {name}: {ty},",
+                        name = field.name,
+                        ty = self.name(&field.ty)
+                    )
+                    .unwrap();
+                }
+                def.push_str(" }");
+            }
+            TypeDefKind::Variant(variant) => {
+                def.push_str("variant {");
+                for case in &variant.cases {
+                    match &case.ty {
+                        Some(ty) => {
+                            write!(def, " {name}({ty}),", name = case.name, ty = self.name(ty))
+                        }
+                        None => write!(def, " {name},", name = case.name),
+                    }
+                    .unwrap();
+                }
+                def.push_str(" }");
+            }
+            TypeDefKind::Enum(e) => {
+                def.push_str("enum {");
+                for case in &e.cases {
+                    write!(def, " {name},", name = case.name).unwrap();
+                }
+                def.push_str(" }");
+            }
+            TypeDefKind::Flags(flags) => {
+                def.push_str("flags {");
+                for flag in &flags.flags {
+                    write!(def, " {name},", name = flag.name).unwrap();
+                }
+                def.push_str(" }");
+            }
+            kind => def.push_str(&self.kind_name(kind)),
+        }
+
+        def
+    }
+
+    fn signature(&self, func: &Function) -> String {
+        let mut sig = match func.kind {
+            FunctionKind::Freestanding => "func(".to_string(),
+            FunctionKind::Method(_) => "method(".to_string(),
+            FunctionKind::Static(_) => "static func(".to_string(),
+            FunctionKind::Constructor(_) => "constructor(".to_string(),
+        };
+
+        for (i, (name, ty)) in func.params.iter().enumerate() {
+            if i > 0 {
+                sig.push_str(", ");
+            }
+            write!(sig, "{name}: {ty}", ty = self.name(ty)).unwrap();
+        }
+        sig.push(')');
+
+        match &func.results {
+            Results::Named(results) => {
+                sig.push_str(" -> (");
+                for (i, (name, ty)) in results.iter().enumerate() {
+                    if i > 0 {
+                        sig.push_str(", ");
+                    }
+                    write!(sig, "{name}: {ty}", ty = self.name(ty)).unwrap();
+                }
+                sig.push(')');
+            }
+            Results::Anon(ty) => write!(sig, " -> {ty}", ty = self.name(ty)).unwrap(),
+        }
+
+        sig
+    }
+
+    fn name(&self, ty: &Type) -> String {
+        match ty {
+            Type::Bool => "bool".to_string(),
+            Type::U8 => "u8".to_string(),
+            Type::U16 => "u16".to_string(),
+            Type::U32 => "u32".to_string(),
+            Type::U64 => "u64".to_string(),
+            Type::S8 => "s8".to_string(),
+            Type::S16 => "s16".to_string(),
+            Type::S32 => "s32".to_string(),
+            Type::S64 => "s64".to_string(),
+            Type::Float32 => "float32".to_string(),
+            Type::Float64 => "float64".to_string(),
+            Type::Char => "char".to_string(),
+            Type::String => "string".to_string(),
+            Type::Id(id) => {
+                let ty = &self.0.types[*id];
+                match &ty.name {
+                    Some(name) => match ty.owner {
+                        TypeOwner::Interface(owner) => {
+                            format!("{owner}.{name}", owner = interface_name(self.0, owner))
+                        }
+                        _ => name.clone(),
+                    },
+                    None => self.kind_name(&ty.kind),
+                }
+            }
+        }
+    }
+
+    fn optional_name(&self, ty: Option<&Type>) -> String {
+        ty.map(|ty| self.name(ty))
+            .unwrap_or_else(|| "_".to_string())
+    }
+
+    fn kind_name(&self, kind: &TypeDefKind) -> String {
+        match kind {
+            TypeDefKind::List(ty) => format!("list<{ty}>", ty = self.name(ty)),
+            TypeDefKind::Option(ty) => format!("option<{ty}>", ty = self.name(ty)),
+            TypeDefKind::Result(r) => format!(
+                "result<{ok}, {err}>",
+                ok = self.optional_name(r.ok.as_ref()),
+                err = self.optional_name(r.err.as_ref())
+            ),
+            TypeDefKind::Tuple(t) => format!(
+                "tuple<{types}>",
+                types = t
+                    .types
+                    .iter()
+                    .map(|ty| self.name(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDefKind::Future(ty) => {
+                format!("future<{ty}>", ty = self.optional_name(ty.as_ref()))
+            }
+            TypeDefKind::Stream(stream) => format!(
+                "stream<{element}, {end}>",
+                element = self.optional_name(stream.element.as_ref()),
+                end = self.optional_name(stream.end.as_ref())
+            ),
+            TypeDefKind::Handle(Handle::Own(id)) => {
+                format!("own<{ty}>", ty = self.name(&Type::Id(*id)))
+            }
+            TypeDefKind::Handle(Handle::Borrow(id)) => {
+                format!("borrow<{ty}>", ty = self.name(&Type::Id(*id)))
+            }
+            TypeDefKind::Type(ty) => self.name(ty),
+            TypeDefKind::Record(_) => "record".to_string(),
+            TypeDefKind::Variant(_) => "variant".to_string(),
+            TypeDefKind::Enum(_) => "enum".to_string(),
+            TypeDefKind::Flags(_) => "flags".to_string(),
+            TypeDefKind::Resource => "resource".to_string(),
+            TypeDefKind::Unknown => "unknown".to_string(),
+        }
+    }
+}
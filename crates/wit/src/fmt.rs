@@ -0,0 +1,222 @@
+//! Module for formatting the WIT files of a package.
+//!
+//! Files are formatted by printing the parsed package with the WIT printer of
+//! `wit-component`, so the output is in the same canonical style regardless
+//! of how the files were written. Doc comments are preserved, but other
+//! comments are not.
+//!
+//! Each interface and world is printed to the file that originally declared
+//! it, in the original order.
+
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use wit_component::WitPrinter;
+use wit_parser::{PackageId, Resolve, UnresolvedPackage};
+
+/// Represents a formatted WIT file.
+pub struct FormattedFile {
+    /// The path to the WIT file.
+    pub path: PathBuf,
+    /// The original contents of the file.
+    pub original: String,
+    /// The formatted contents of the file.
+    pub formatted: String,
+}
+
+impl FormattedFile {
+    /// Determines if formatting changed the file.
+    pub fn is_changed(&self) -> bool {
+        self.original != self.formatted
+    }
+}
+
+/// The kind of a top-level WIT item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ItemKind {
+    Interface,
+    World,
+}
+
+/// Formats the WIT files of the given package in a directory.
+///
+/// The package must have been parsed from the same directory.
+pub fn format_package(
+    resolve: &Resolve,
+    package: PackageId,
+    dir: &Path,
+) -> Result<Vec<FormattedFile>> {
+    let unresolved = UnresolvedPackage::parse_dir(dir).with_context(|| {
+        format!(
+            "failed to parse package from directory `{dir}`",
+            dir = dir.display()
+        )
+    })?;
+
+    let printed = WitPrinter::default()
+        .print(resolve, package)
+        .context("failed to print WIT package")?;
+    let (header, mut blocks) = split_printed(&printed)?;
+
+    let mut files = Vec::new();
+    for path in unresolved.source_files() {
+        let original = fs::read_to_string(path)
+            .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+        let (has_package, items) = declarations(&original);
+
+        let mut parts = Vec::new();
+        if has_package || items.is_empty() {
+            parts.push(header.clone());
+        }
+
+        for (kind, name) in &items {
+            match blocks.remove(&(*kind, name.clone())) {
+                Some(block) => parts.push(block),
+                None => bail!(
+                    "failed to find `{name}` of `{path}` in the printed package",
+                    path = path.display()
+                ),
+            }
+        }
+
+        files.push(FormattedFile {
+            path: path.to_path_buf(),
+            original,
+            formatted: parts.join("\n\n") + "\n",
+        });
+    }
+
+    if !blocks.is_empty() {
+        bail!("not every item of the printed package was declared in a WIT file");
+    }
+
+    Ok(files)
+}
+
+/// Splits the printed package into its header (i.e. the `package`
+/// declaration) and the blocks of its interfaces and worlds.
+fn split_printed(printed: &str) -> Result<(String, HashMap<(ItemKind, String), String>)> {
+    let mut header = Vec::new();
+    let mut blocks = HashMap::new();
+    let mut current = Vec::new();
+    let mut key = None;
+
+    for line in printed.lines() {
+        if key.is_none() {
+            if let Some(item) = item_declaration(line) {
+                key = Some(item);
+            } else if current.is_empty() && !line.starts_with("///") {
+                if !line.trim().is_empty() {
+                    header.push(line);
+                }
+                continue;
+            }
+        }
+
+        current.push(line);
+
+        if line == "}" {
+            let key = key
+                .take()
+                .context("unexpected end of block in printed package")?;
+            blocks.insert(key, current.join("\n"));
+            current.clear();
+        }
+    }
+
+    if key.is_some() || !current.is_empty() {
+        bail!("unexpected end of printed package");
+    }
+
+    Ok((header.join("\n"), blocks))
+}
+
+/// Gets the item declared by a line of top-level WIT, if any.
+fn item_declaration(line: &str) -> Option<(ItemKind, String)> {
+    let (kind, rest) = if let Some(rest) = line.strip_prefix("interface ") {
+        (ItemKind::Interface, rest)
+    } else if let Some(rest) = line.strip_prefix("world ") {
+        (ItemKind::World, rest)
+    } else {
+        return None;
+    };
+
+    let name = rest
+        .split(|c: char| c.is_whitespace() || c == '{')
+        .next()
+        .unwrap_or_default();
+    Some((kind, name.trim_start_matches('%').to_string()))
+}
+
+/// Scans the source of a WIT file for its top-level declarations.
+///
+/// Returns whether the file declares the package and the interfaces and
+/// worlds it declares, in order.
+fn declarations(source: &str) -> (bool, Vec<(ItemKind, String)>) {
+    let mut has_package = false;
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut words = Vec::new();
+
+    let mut chars = source.chars().peekable();
+    let mut word = String::new();
+    while let Some(c) = chars.next() {
+        // Skip comments
+        if c == '/' && chars.peek() == Some(&'/') {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    break;
+                }
+            }
+        } else if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut prev = ' ';
+            for c in chars.by_ref() {
+                if prev == '*' && c == '/' {
+                    break;
+                }
+                prev = c;
+            }
+        } else if c.is_alphanumeric() || c == '-' || c == '%' || c == '_' {
+            word.push(c);
+            continue;
+        } else if c == '{' {
+            depth += 1;
+        } else if c == '}' {
+            depth = depth.saturating_sub(1);
+        }
+
+        if !word.is_empty() {
+            if depth == 0 || (depth == 1 && c == '{') {
+                words.push(std::mem::take(&mut word));
+            } else {
+                word.clear();
+            }
+        }
+
+        if c == '{' && depth == 1 {
+            // The item being opened is declared by the last two words
+            if let [.., kind, name] = words.as_slice() {
+                let kind = match kind.as_str() {
+                    "interface" => Some(ItemKind::Interface),
+                    "world" => Some(ItemKind::World),
+                    _ => None,
+                };
+
+                if let Some(kind) = kind {
+                    items.push((kind, name.trim_start_matches('%').to_string()));
+                }
+            }
+            words.clear();
+        } else if depth == 0 && words.first().map(String::as_str) == Some("package") {
+            has_package = true;
+            words.clear();
+        }
+    }
+
+    (has_package, items)
+}
@@ -5,35 +5,147 @@
 use anyhow::{bail, Context, Result};
 use bytes::Bytes;
 use cargo_component_core::{
-    lock::{LockFile, LockFileResolver, LockedPackage, LockedPackageVersion},
-    registry::{create_client, DecodedDependency, DependencyResolutionMap, DependencyResolver},
+    lock::{FileLock, LockFile, LockFileResolver, LockedPackage, LockedPackageVersion},
+    oci::{self, OciClient},
+    registry::{
+        content_digest, create_client, DecodedDependency, Dependency, DependencyResolution,
+        DependencyResolutionMap, DependencyResolver, RegistryPackage,
+    },
     terminal::{Colors, Terminal},
 };
-use config::Config;
+use config::{Config, Workspace};
 use indexmap::{IndexMap, IndexSet};
 use lock::{acquire_lock_file_ro, acquire_lock_file_rw, to_lock_file};
-use std::{collections::HashSet, path::Path, time::Duration};
-use warg_client::storage::{ContentStorage, PublishEntry, PublishInfo};
-use warg_crypto::signing::PrivateKey;
-use warg_protocol::registry::PackageId;
+use pending::PendingPublish;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use warg_client::{
+    storage::{ContentStorage, PublishEntry, PublishInfo},
+    ClientError, FileSystemClient,
+};
+use warg_crypto::{hash::AnyHash, signing::PrivateKey};
+use warg_protocol::registry::{PackageId, RecordId};
 use wasm_metadata::{Link, LinkType, RegistryMetadata};
 use wit_component::DecodedWasm;
 use wit_parser::{PackageName, Resolve, UnresolvedPackage};
 
 pub mod commands;
 pub mod config;
+pub mod diff;
+pub mod fmt;
+pub mod lint;
 mod lock;
+mod pending;
+
+/// The custom type of the registry metadata link to a package's changelog.
+pub const CHANGELOG_LINK_TYPE: &str = "changelog";
+
+/// Options controlling the use of the lock file when resolving dependencies.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LockOptions {
+    /// Require the lock file is up to date.
+    pub locked: bool,
+    /// Require the lock file and cache are up to date.
+    pub frozen: bool,
+}
+
+impl LockOptions {
+    /// Determines if network access is allowed.
+    pub fn network_allowed(&self) -> bool {
+        !self.frozen
+    }
+
+    /// Determines if an update to the lock file is allowed.
+    pub fn lock_update_allowed(&self) -> bool {
+        !self.frozen && !self.locked
+    }
+}
+
+/// The part of a semantic version to increment when publishing.
+///
+/// Bumps are ordered from the most to the least significant part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionBump {
+    /// Increment the major version.
+    Major,
+    /// Increment the minor version.
+    Minor,
+    /// Increment the patch version.
+    Patch,
+}
+
+impl VersionBump {
+    /// Applies the bump to the given version.
+    ///
+    /// Any pre-release or build metadata of the version is discarded.
+    pub fn apply(&self, version: &Version) -> Version {
+        match self {
+            Self::Major => Version::new(version.major + 1, 0, 0),
+            Self::Minor => Version::new(version.major, version.minor + 1, 0),
+            Self::Patch => Version::new(version.major, version.minor, version.patch + 1),
+        }
+    }
+}
+
+impl FromStr for VersionBump {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "major" => Ok(Self::Major),
+            "minor" => Ok(Self::Minor),
+            "patch" => Ok(Self::Patch),
+            _ => bail!("invalid version bump `{s}`: expected `major`, `minor`, or `patch`"),
+        }
+    }
+}
 
 async fn resolve_dependencies(
     config: &Config,
     config_path: &Path,
     warg_config: &warg_client::Config,
     terminal: &Terminal,
+    lock_options: LockOptions,
     update_lock_file: bool,
 ) -> Result<DependencyResolutionMap> {
     let file_lock = acquire_lock_file_ro(terminal, config_path)?;
-    let lock_file = file_lock
-        .as_ref()
+    let lock_file = read_lock_file(file_lock.as_ref())?;
+
+    let map = resolve_with_lock_file(
+        config,
+        &config.dependencies,
+        lock_file.as_ref(),
+        warg_config,
+        terminal,
+        lock_options.network_allowed(),
+    )
+    .await?;
+
+    // Update the lock file
+    if update_lock_file {
+        write_lock_file(
+            terminal,
+            config_path,
+            file_lock,
+            lock_file.as_ref(),
+            to_lock_file([&map]),
+            lock_options,
+        )?;
+    }
+
+    Ok(map)
+}
+
+pub(crate) fn read_lock_file(file_lock: Option<&FileLock>) -> Result<Option<LockFile>> {
+    file_lock
         .map(|f| {
             LockFile::read(f.file()).with_context(|| {
                 format!(
@@ -42,40 +154,235 @@ async fn resolve_dependencies(
                 )
             })
         })
-        .transpose()?;
+        .transpose()
+}
+
+/// Writes the given lock file next to the given configuration file if it
+/// differs from the current lock file.
+fn write_lock_file(
+    terminal: &Terminal,
+    config_path: &Path,
+    file_lock: Option<FileLock>,
+    current: Option<&LockFile>,
+    new_lock_file: LockFile,
+    lock_options: LockOptions,
+) -> Result<()> {
+    if Some(&new_lock_file) == current {
+        return Ok(());
+    }
+
+    drop(file_lock);
+    let file_lock = acquire_lock_file_rw(
+        terminal,
+        config_path,
+        lock_options.lock_update_allowed(),
+        lock_options.locked,
+    )?;
+    new_lock_file
+        .write(file_lock.file(), "wit")
+        .with_context(|| {
+            format!(
+                "failed to write lock file `{path}`",
+                path = file_lock.path().display()
+            )
+        })
+}
 
+async fn resolve_with_lock_file(
+    config: &Config,
+    dependencies: &HashMap<PackageId, Dependency>,
+    lock_file: Option<&LockFile>,
+    warg_config: &warg_client::Config,
+    terminal: &Terminal,
+    network_allowed: bool,
+) -> Result<DependencyResolutionMap> {
     let mut resolver = DependencyResolver::new(
         warg_config,
         &config.registries,
-        lock_file.as_ref().map(LockFileResolver::new),
+        lock_file.map(LockFileResolver::new),
         terminal,
-        true,
-    )?;
+        network_allowed,
+    )?
+    .with_namespaces(&config.namespaces)
+    .with_patches(&config.patch);
 
-    for (id, dep) in &config.dependencies {
+    for (id, dep) in dependencies {
         resolver.add_dependency(id, dep).await?;
     }
 
-    let map = resolver.resolve().await?;
+    resolver.resolve().await
+}
 
-    // Update the lock file
-    if update_lock_file {
-        let new_lock_file = to_lock_file(&map);
-        if Some(&new_lock_file) != lock_file.as_ref() {
-            drop(file_lock);
-            let file_lock = acquire_lock_file_rw(terminal, config_path)?;
-            new_lock_file
-                .write(file_lock.file(), "wit")
-                .with_context(|| {
-                    format!(
-                        "failed to write lock file `{path}`",
-                        path = file_lock.path().display()
-                    )
-                })?;
+/// Gets the dependencies of each member of a workspace and the order in
+/// which the members depend on each other.
+///
+/// Relative path dependencies of a member are relative to its directory. A
+/// member with a path dependency on another member also depends on the
+/// dependencies of that member, as they are needed to parse it.
+fn workspace_dependencies(
+    workspace: &Workspace,
+) -> Result<(Vec<HashMap<PackageId, Dependency>>, Vec<usize>)> {
+    let dirs = workspace
+        .members
+        .iter()
+        .map(|m| {
+            fs::canonicalize(m.dir()).with_context(|| {
+                format!(
+                    "failed to canonicalize path `{path}`",
+                    path = m.dir().display()
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut dependencies = Vec::with_capacity(workspace.members.len());
+    let mut member_dependencies = Vec::with_capacity(workspace.members.len());
+    for member in &workspace.members {
+        let mut deps = HashMap::new();
+        let mut members = Vec::new();
+        for (id, dep) in &member.config.dependencies {
+            let dep = match dep {
+                Dependency::Local(path) => {
+                    let path = member.dir().join(path);
+                    if let Some(index) = fs::canonicalize(&path)
+                        .ok()
+                        .and_then(|p| dirs.iter().position(|d| *d == p))
+                    {
+                        members.push(index);
+                    }
+
+                    Dependency::Local(path)
+                }
+                dep => dep.clone(),
+            };
+
+            deps.insert(id.clone(), dep);
         }
+
+        dependencies.push(deps);
+        member_dependencies.push(members);
     }
 
-    Ok(map)
+    let mut order = Vec::with_capacity(workspace.members.len());
+    let mut visiting = HashSet::new();
+    for index in 0..workspace.members.len() {
+        visit(
+            index,
+            &dirs,
+            &member_dependencies,
+            &mut order,
+            &mut visiting,
+        )?;
+    }
+
+    // Members are ordered after the members they depend on
+    for &index in &order {
+        for &dep in &member_dependencies[index] {
+            let inherited: Vec<_> = dependencies[dep]
+                .iter()
+                .map(|(id, dep)| (id.clone(), dep.clone()))
+                .collect();
+
+            for (id, dep) in inherited {
+                dependencies[index].entry(id).or_insert(dep);
+            }
+        }
+    }
+
+    return Ok((dependencies, order));
+
+    fn visit(
+        index: usize,
+        dirs: &[PathBuf],
+        member_dependencies: &[Vec<usize>],
+        order: &mut Vec<usize>,
+        visiting: &mut HashSet<usize>,
+    ) -> Result<()> {
+        if order.contains(&index) {
+            return Ok(());
+        }
+
+        if !visiting.insert(index) {
+            bail!(
+                "workspace member `{dir}` has a cyclic path dependency",
+                dir = dirs[index].display()
+            );
+        }
+
+        for &dep in &member_dependencies[index] {
+            visit(dep, dirs, member_dependencies, order, visiting)?;
+        }
+
+        visiting.remove(&index);
+        order.push(index);
+        Ok(())
+    }
+}
+
+/// Resolves the dependencies of every member of a workspace.
+///
+/// The members share the lock file of the workspace, which is updated with
+/// the dependencies of all members if `update_lock_file` is `true`.
+///
+/// Returns the resolved dependencies of each member and the order in which
+/// the members must be built.
+async fn resolve_workspace_dependencies(
+    workspace: &Workspace,
+    warg_config: &warg_client::Config,
+    terminal: &Terminal,
+    lock_options: LockOptions,
+    update_lock_file: bool,
+) -> Result<(Vec<DependencyResolutionMap>, Vec<usize>)> {
+    let (dependencies, order) = workspace_dependencies(workspace)?;
+
+    let file_lock = acquire_lock_file_ro(terminal, &workspace.config_path)?;
+    let lock_file = read_lock_file(file_lock.as_ref())?;
+
+    let mut maps = Vec::with_capacity(workspace.members.len());
+    for (member, dependencies) in workspace.members.iter().zip(&dependencies) {
+        maps.push(
+            resolve_with_lock_file(
+                &member.config,
+                dependencies,
+                lock_file.as_ref(),
+                warg_config,
+                terminal,
+                lock_options.network_allowed(),
+            )
+            .await?,
+        );
+    }
+
+    if update_lock_file {
+        write_lock_file(
+            terminal,
+            &workspace.config_path,
+            file_lock,
+            lock_file.as_ref(),
+            to_lock_file(&maps),
+            lock_options,
+        )?;
+    }
+
+    Ok((maps, order))
+}
+
+/// Gets the id of the WIT package in the directory of the given configuration file.
+fn wit_package_id(config_path: &Path) -> Result<PackageId> {
+    let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let package = UnresolvedPackage::parse_dir(dir).with_context(|| {
+        format!(
+            "failed to parse package from directory `{dir}`",
+            dir = dir.display()
+        )
+    })?;
+
+    format!(
+        "{ns}:{name}",
+        ns = package.name.namespace,
+        name = package.name.name
+    )
+    .parse()
 }
 
 fn parse_wit_package(
@@ -223,13 +530,30 @@ async fn build_wit_package(
     config_path: &Path,
     warg_config: &warg_client::Config,
     terminal: &Terminal,
+    lock_options: LockOptions,
 ) -> Result<(PackageId, Vec<u8>)> {
-    let dependencies =
-        resolve_dependencies(config, config_path, warg_config, terminal, true).await?;
+    let dependencies = resolve_dependencies(
+        config,
+        config_path,
+        warg_config,
+        terminal,
+        lock_options,
+        true,
+    )
+    .await?;
+
+    encode_wit_package(config_path, &dependencies)
+}
 
+/// Encodes the WIT package in the directory of the given configuration file
+/// with already resolved dependencies.
+fn encode_wit_package(
+    config_path: &Path,
+    dependencies: &DependencyResolutionMap,
+) -> Result<(PackageId, Vec<u8>)> {
     let dir = config_path.parent().unwrap_or_else(|| Path::new("."));
 
-    let (mut resolve, package) = parse_wit_package(dir, &dependencies)?;
+    let (mut resolve, package) = parse_wit_package(dir, dependencies)?;
 
     let pkg = &mut resolve.packages[package];
     let id = format!("{ns}:{name}", ns = pkg.name.namespace, name = pkg.name.name).parse()?;
@@ -250,15 +574,143 @@ async fn build_wit_package(
     Ok((id, bytes))
 }
 
+/// Builds the WIT packages of the members of a workspace.
+///
+/// If the workspace was found from one of its members, only that member is
+/// built; otherwise every member is built.
+///
+/// Returns the paths to the configuration files of the built members along
+/// with their package ids and contents, in dependency order.
+async fn build_workspace(
+    workspace: &Workspace,
+    warg_config: &warg_client::Config,
+    terminal: &Terminal,
+    lock_options: LockOptions,
+) -> Result<Vec<(PathBuf, PackageId, Vec<u8>)>> {
+    let (maps, order) =
+        resolve_workspace_dependencies(workspace, warg_config, terminal, lock_options, true)
+            .await?;
+
+    order
+        .into_iter()
+        .filter(|index| workspace.current.map(|c| c == *index).unwrap_or(true))
+        .map(|index| {
+            let config_path = &workspace.members[index].config_path;
+            let (id, bytes) = encode_wit_package(config_path, &maps[index])?;
+            Ok((config_path.clone(), id, bytes))
+        })
+        .collect()
+}
+
+/// Loads a WIT package for comparison with `diff::diff`.
+///
+/// If `path` is `None`, the package in the directory of the configuration
+/// file is loaded. Otherwise, `path` may be a directory containing a WIT
+/// package or a binary WIT package; directories are parsed with the
+/// dependencies of the configuration.
+async fn load_wit_package(
+    config: &Config,
+    config_path: &Path,
+    warg_config: &warg_client::Config,
+    terminal: &Terminal,
+    lock_options: LockOptions,
+    path: Option<&Path>,
+) -> Result<(Resolve, wit_parser::PackageId)> {
+    let dir = match path {
+        Some(path) if path.is_file() => {
+            let bytes = fs::read(path)
+                .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+            return match wit_component::decode(&bytes).with_context(|| {
+                format!(
+                    "failed to decode WIT package `{path}`",
+                    path = path.display()
+                )
+            })? {
+                DecodedWasm::WitPackage(resolve, package) => Ok((resolve, package)),
+                DecodedWasm::Component(..) => bail!(
+                    "`{path}` is a component, not a WIT package",
+                    path = path.display()
+                ),
+            };
+        }
+        Some(path) => path,
+        None => config_path.parent().unwrap_or_else(|| Path::new(".")),
+    };
+
+    let dependencies = resolve_dependencies(
+        config,
+        config_path,
+        warg_config,
+        terminal,
+        lock_options,
+        false,
+    )
+    .await?;
+
+    parse_wit_package(dir, &dependencies)
+}
+
+/// Fetches a published version of the WIT package of the configuration file
+/// for comparison with `diff::diff`.
+async fn fetch_wit_package(
+    config: &Config,
+    config_path: &Path,
+    warg_config: &warg_client::Config,
+    terminal: &Terminal,
+    lock_options: LockOptions,
+    version: &Version,
+    registry: Option<&str>,
+) -> Result<(Resolve, wit_parser::PackageId)> {
+    let id = wit_package_id(config_path)?;
+    let mut resolver = DependencyResolver::new(
+        warg_config,
+        &config.registries,
+        None,
+        terminal,
+        lock_options.network_allowed(),
+    )?
+    .with_namespaces(&config.namespaces);
+
+    resolver
+        .add_dependency(
+            &id,
+            &Dependency::Package(RegistryPackage {
+                id: None,
+                version: VersionReq::parse(&format!("={version}"))?,
+                registry: registry.map(ToString::to_string),
+                interfaces: Vec::new(),
+            }),
+        )
+        .await?;
+
+    let map = resolver.resolve().await?;
+    let resolution = map.values().next().expect("expected a resolution");
+    match resolution.decode()? {
+        DecodedDependency::Wasm {
+            decoded: DecodedWasm::WitPackage(resolve, package),
+            ..
+        } => Ok((resolve, package)),
+        _ => bail!("package `{id}` v{version} is not a binary WIT package"),
+    }
+}
+
 struct PublishOptions<'a> {
     config: &'a Config,
+    /// The already resolved dependencies of the package, if any.
+    dependencies: Option<&'a DependencyResolutionMap>,
     config_path: &'a Path,
     warg_config: &'a warg_client::Config,
     url: &'a str,
-    signing_key: &'a PrivateKey,
+    signing_key: Option<&'a PrivateKey>,
     package: Option<&'a PackageId>,
     init: bool,
     dry_run: bool,
+    lock_options: LockOptions,
+    bump: Option<VersionBump>,
+    write_version: bool,
+    resume: bool,
+    if_changed: bool,
 }
 
 fn add_registry_metadata(config: &Config, bytes: &[u8]) -> Result<Vec<u8>> {
@@ -302,6 +754,13 @@ fn add_registry_metadata(config: &Config, bytes: &[u8]) -> Result<Vec<u8>> {
         });
     }
 
+    if let Some(changelog) = &config.changelog {
+        links.push(Link {
+            ty: LinkType::Custom(CHANGELOG_LINK_TYPE.to_string()),
+            value: changelog.clone(),
+        });
+    }
+
     if !links.is_empty() {
         metadata.set_links(Some(links));
     }
@@ -312,96 +771,571 @@ fn add_registry_metadata(config: &Config, bytes: &[u8]) -> Result<Vec<u8>> {
 }
 
 async fn publish_wit_package(options: PublishOptions<'_>, terminal: &Terminal) -> Result<()> {
-    let (id, bytes) = build_wit_package(
-        options.config,
-        options.config_path,
-        options.warg_config,
-        terminal,
-    )
-    .await?;
+    let pending_path = PendingPublish::path(options.config_path);
+    if options.resume {
+        let pending = PendingPublish::load(options.config_path)?.with_context(|| {
+            format!(
+                "there is no pending publish to resume (`{path}` does not exist)",
+                path = pending_path.display()
+            )
+        })?;
+
+        return resume_publish(options, pending, terminal).await;
+    }
+
+    if let Some(pending) = PendingPublish::load(options.config_path)?.filter(|_| !options.dry_run) {
+        bail!(
+            "the publish of package `{id}` v{version} to `{url}` did not complete; run `wit publish --resume` to finish it or remove `{path}` to discard it",
+            id = pending.package,
+            version = pending.version,
+            url = pending.registry,
+            path = pending_path.display()
+        );
+    }
+
+    let (id, bytes) = match options.dependencies {
+        Some(dependencies) => encode_wit_package(options.config_path, dependencies)?,
+        None => {
+            build_wit_package(
+                options.config,
+                options.config_path,
+                options.warg_config,
+                terminal,
+                options.lock_options,
+            )
+            .await?
+        }
+    };
+
+    let id = options.package.unwrap_or(&id);
+    let bytes = add_registry_metadata(options.config, &bytes)?;
+
+    // The `--bump` option takes precedence over the configured policy
+    let bump = options.bump.or(options.config.publish.bump);
+    let latest = if bump.is_some() || options.if_changed {
+        latest_version(options.url, options.warg_config, id, terminal).await?
+    } else {
+        None
+    };
+
+    if let (true, Some(latest)) = (options.if_changed, &latest) {
+        let digest = release_digest(options.url, options.warg_config, id, latest, terminal).await?;
+        if digest.as_ref() == Some(&content_digest(&bytes)) {
+            terminal.status(
+                "Skipping",
+                format!("package `{id}` (unchanged since v{latest})"),
+            )?;
+            terminal.event(
+                "unchanged",
+                serde_json::json!({
+                    "package": id.to_string(),
+                    "version": latest.to_string(),
+                    "registry": options.url,
+                }),
+            )?;
+            return Ok(());
+        }
+
+        if bump.is_none() && options.config.version <= *latest {
+            bail!(
+                "package `{id}` changed since v{latest} but its configured version is v{version}; use `--bump` or set `bump` in the `publish` table of `{path}` to publish a new version",
+                version = options.config.version,
+                path = options.config_path.display()
+            );
+        }
+    }
+
+    let version = match bump {
+        Some(bump) => {
+            let version = match &latest {
+                Some(latest) => bump.apply(latest),
+                None => options.config.version.clone(),
+            };
+
+            terminal.status("Bumping", format!("package `{id}` to v{version}"))?;
+            version
+        }
+        None => options.config.version.clone(),
+    };
 
     if options.dry_run {
         terminal.warn("not publishing package to the registry due to the --dry-run option")?;
         return Ok(());
     }
 
-    let bytes = add_registry_metadata(options.config, &bytes)?;
-    let id = options.package.unwrap_or(&id);
-    let client = create_client(options.warg_config, options.url, terminal)?;
+    let record = if oci::is_oci_url(options.url) {
+        let client = OciClient::new(options.url, oci::cache_dir(options.warg_config)?)?;
 
-    let content = client
-        .content()
-        .store_content(
-            Box::pin(futures::stream::once(async { Ok(Bytes::from(bytes)) })),
-            None,
-        )
-        .await?;
+        terminal.status(
+            "Publishing",
+            format!("package `{id}` to `{url}`", url = options.url),
+        )?;
 
-    terminal.status("Publishing", format!("package `{id}` ({content})",))?;
+        client
+            .publish(id, &version, &bytes)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to publish package `{id}` to `{url}`",
+                    url = options.url
+                )
+            })?;
 
-    let mut info = PublishInfo {
-        id: id.clone(),
-        head: None,
-        entries: Default::default(),
+        None
+    } else {
+        let signing_key = options
+            .signing_key
+            .context("a signing key is required to publish to a warg registry")?;
+        let client = create_client(options.warg_config, options.url, terminal)?;
+
+        let content = client
+            .content()
+            .store_content(
+                Box::pin(futures::stream::once(async { Ok(Bytes::from(bytes)) })),
+                None,
+            )
+            .await?;
+
+        terminal.status("Publishing", format!("package `{id}` ({content})",))?;
+
+        let mut pending = PendingPublish {
+            package: id.clone(),
+            version: version.clone(),
+            registry: options.url.to_string(),
+            content,
+            init: options.init,
+            record: None,
+        };
+        pending.save(options.config_path)?;
+
+        complete_publish(
+            &client,
+            signing_key,
+            &mut pending,
+            options.config_path,
+            terminal,
+        )
+        .await?
     };
 
-    if options.init {
-        info.entries.push(PublishEntry::Init);
-    }
+    finish_publish(&options, id, version, record, terminal)
+}
 
-    info.entries.push(PublishEntry::Release {
-        version: options.config.version.clone(),
-        content,
-    });
+/// Resumes a publish to a warg registry that did not complete.
+async fn resume_publish(
+    options: PublishOptions<'_>,
+    mut pending: PendingPublish,
+    terminal: &Terminal,
+) -> Result<()> {
+    if pending.registry != options.url {
+        bail!(
+            "the pending publish of package `{id}` v{version} is to registry `{url}`, not `{current}`; use `--registry` to select the registry to resume it with",
+            id = pending.package,
+            version = pending.version,
+            url = pending.registry,
+            current = options.url
+        );
+    }
 
-    let record_id = client.publish_with_info(options.signing_key, info).await?;
-    client
-        .wait_for_publish(id, &record_id, Duration::from_secs(1))
-        .await?;
+    let signing_key = options
+        .signing_key
+        .context("a signing key is required to publish to a warg registry")?;
+    let client = create_client(options.warg_config, options.url, terminal)?;
 
     terminal.status(
-        "Published",
+        "Resuming",
         format!(
-            "package `{id}` v{version}",
-            version = options.config.version
+            "publish of package `{id}` v{version} ({content})",
+            id = pending.package,
+            version = pending.version,
+            content = pending.content
         ),
     )?;
 
+    let record = complete_publish(
+        &client,
+        signing_key,
+        &mut pending,
+        options.config_path,
+        terminal,
+    )
+    .await?;
+
+    finish_publish(
+        &options,
+        &pending.package,
+        pending.version,
+        record,
+        terminal,
+    )
+}
+
+/// Reports a completed publish and writes the published version back to the
+/// configuration file if requested.
+fn finish_publish(
+    options: &PublishOptions<'_>,
+    id: &PackageId,
+    version: Version,
+    record: Option<String>,
+    terminal: &Terminal,
+) -> Result<()> {
+    terminal.status("Published", format!("package `{id}` v{version}"))?;
+    terminal.event(
+        "published",
+        serde_json::json!({
+            "package": id.to_string(),
+            "version": version.to_string(),
+            "registry": options.url,
+            "record": record,
+        }),
+    )?;
+
+    if options.write_version && version != options.config.version {
+        let mut config = Config::from_file(options.config_path)?;
+        config.version = version;
+        config.write(options.config_path)?;
+    }
+
     Ok(())
 }
 
+/// The number of times a release is submitted to a registry before giving up.
+const MAX_PUBLISH_ATTEMPTS: u64 = 3;
+
+/// Represents the outcome of an attempt to submit a release.
+enum PublishAttempt {
+    /// The release was published, either by the given record or by an
+    /// earlier attempt whose record wasn't seen.
+    Published(Option<RecordId>),
+    /// The version was released with different content by another publish.
+    Conflict,
+}
+
+/// Submits a pending release to a warg registry and waits for it to be
+/// published.
+///
+/// Before every attempt, the package log is updated so that a release made by
+/// an earlier, interrupted attempt is detected rather than submitted again and
+/// so that the new record is based on the current head of the log (which a
+/// concurrent publish may have moved).
+///
+/// Returns the id of the record that published the release, if known.
+async fn complete_publish(
+    client: &FileSystemClient,
+    signing_key: &PrivateKey,
+    pending: &mut PendingPublish,
+    config_path: &Path,
+    terminal: &Terminal,
+) -> Result<Option<String>> {
+    let id = pending.package.clone();
+
+    // Wait on a record submitted by an earlier attempt before checking the log
+    if let Some(record) = &pending.record {
+        let record_id = RecordId::from(record.clone());
+        match client
+            .wait_for_publish(&id, &record_id, Duration::from_secs(1))
+            .await
+        {
+            Ok(()) => {
+                PendingPublish::remove(config_path)?;
+                return Ok(Some(record_id.to_string()));
+            }
+            Err(e) => log::debug!("record `{record_id}` was not published: {e}"),
+        }
+    }
+
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+
+        let result: Result<PublishAttempt> = async {
+            let state = match client.upsert([&id]).await {
+                Ok(()) => client.registry().load_package(&id).await?.map(|p| p.state),
+                Err(ClientError::PackageDoesNotExist { .. }) => None,
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("failed to update package log for `{id}`"))
+                }
+            };
+
+            if let Some(release) = state
+                .as_ref()
+                .and_then(|state| state.release(&pending.version))
+            {
+                if release.content() == Some(&pending.content) {
+                    return Ok(PublishAttempt::Published(None));
+                }
+
+                return Ok(PublishAttempt::Conflict);
+            }
+
+            let mut info = PublishInfo {
+                id: id.clone(),
+                head: state
+                    .as_ref()
+                    .and_then(|state| state.head().as_ref().map(|head| head.digest.clone())),
+                entries: Default::default(),
+            };
+
+            // The package log may have been initialized by an earlier attempt
+            if pending.init && state.is_none() {
+                info.entries.push(PublishEntry::Init);
+            }
+
+            info.entries.push(PublishEntry::Release {
+                version: pending.version.clone(),
+                content: pending.content.clone(),
+            });
+
+            let record_id = client.publish_with_info(signing_key, info).await?;
+            pending.record = Some(record_id.to_string().parse()?);
+            pending.save(config_path)?;
+
+            client
+                .wait_for_publish(&id, &record_id, Duration::from_secs(1))
+                .await?;
+
+            Ok(PublishAttempt::Published(Some(record_id)))
+        }
+        .await;
+
+        match result {
+            Ok(PublishAttempt::Published(record_id)) => {
+                PendingPublish::remove(config_path)?;
+                return Ok(record_id.map(|r| r.to_string()));
+            }
+            Ok(PublishAttempt::Conflict) => {
+                PendingPublish::remove(config_path)?;
+                bail!(
+                    "version {version} of package `{id}` was published with different content by another publish; bump the version and publish again",
+                    version = pending.version
+                );
+            }
+            Err(e) if attempt < MAX_PUBLISH_ATTEMPTS => {
+                terminal.warn(format!(
+                    "failed to publish package `{id}` (attempt {attempt} of {MAX_PUBLISH_ATTEMPTS}): {e:#}"
+                ))?;
+                tokio::time::sleep(Duration::from_secs(attempt)).await;
+            }
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "failed to publish package `{id}`; run `wit publish --resume` to try again"
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Gets the digest of the content of a published version of a package.
+///
+/// Returns `Ok(None)` if the version has no content (e.g. it was yanked).
+async fn release_digest(
+    url: &str,
+    warg_config: &warg_client::Config,
+    id: &PackageId,
+    version: &Version,
+    terminal: &Terminal,
+) -> Result<Option<AnyHash>> {
+    if oci::is_oci_url(url) {
+        let client = OciClient::new(url, oci::cache_dir(warg_config)?)?;
+        let (digest, _) = client.download(id, version).await.with_context(|| {
+            format!("failed to download package `{id}` v{version} from `{url}`")
+        })?;
+        return Ok(Some(digest));
+    }
+
+    // The package log was updated when its latest version was found
+    let client = create_client(warg_config, url, terminal)?;
+    Ok(client
+        .registry()
+        .load_package(id)
+        .await?
+        .and_then(|p| p.state.release(version).and_then(|r| r.content().cloned())))
+}
+
+/// Gets the latest published version of a package in the given registry.
+///
+/// Returns `Ok(None)` if the package has not been published.
+async fn latest_version(
+    url: &str,
+    warg_config: &warg_client::Config,
+    id: &PackageId,
+    terminal: &Terminal,
+) -> Result<Option<Version>> {
+    if oci::is_oci_url(url) {
+        let client = OciClient::new(url, oci::cache_dir(warg_config)?)?;
+        return Ok(client.list_versions(id).await?.into_iter().max());
+    }
+
+    let client = create_client(warg_config, url, terminal)?;
+    match client.upsert([id]).await {
+        Ok(()) => {}
+        Err(ClientError::PackageDoesNotExist { .. }) => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to update package log for `{id}`"))
+        }
+    }
+
+    Ok(client
+        .registry()
+        .load_package(id)
+        .await?
+        .and_then(|p| p.state.releases().map(|r| r.version.clone()).max()))
+}
+
+/// Options for updating the dependencies in a lock file.
+#[derive(Debug, Default, Clone)]
+pub struct UpdateOptions {
+    /// The registry packages to update.
+    ///
+    /// If empty, all dependencies are updated.
+    pub packages: Vec<PackageId>,
+    /// Don't actually write the lock file.
+    pub dry_run: bool,
+}
+
 /// Update the dependencies in the lock file.
 pub async fn update_lockfile(
     config: &Config,
     config_path: &Path,
     warg_config: &warg_client::Config,
     terminal: &Terminal,
-    dry_run: bool,
+    lock_options: LockOptions,
+    options: &UpdateOptions,
 ) -> Result<()> {
-    // Resolve all dependencies as if the lock file does not exist
-    let mut resolver =
-        DependencyResolver::new(warg_config, &config.registries, None, terminal, true)?;
-    for (id, dep) in &config.dependencies {
-        resolver.add_dependency(id, dep).await?;
+    let lock_file = locked_packages_to_keep(config_path, terminal, &options.packages)?;
+    let map = resolve_with_lock_file(
+        config,
+        &config.dependencies,
+        lock_file.as_ref(),
+        warg_config,
+        terminal,
+        lock_options.network_allowed(),
+    )
+    .await?;
+
+    update_lock_file_with(
+        config_path,
+        &[&map],
+        terminal,
+        lock_options,
+        options.dry_run,
+    )
+}
+
+/// Update the dependencies of all members of a workspace in its lock file.
+pub async fn update_workspace_lockfile(
+    workspace: &Workspace,
+    warg_config: &warg_client::Config,
+    terminal: &Terminal,
+    lock_options: LockOptions,
+    options: &UpdateOptions,
+) -> Result<()> {
+    let (dependencies, _) = workspace_dependencies(workspace)?;
+    let lock_file = locked_packages_to_keep(&workspace.config_path, terminal, &options.packages)?;
+
+    let mut maps = Vec::with_capacity(workspace.members.len());
+    for (member, dependencies) in workspace.members.iter().zip(&dependencies) {
+        maps.push(
+            resolve_with_lock_file(
+                &member.config,
+                dependencies,
+                lock_file.as_ref(),
+                warg_config,
+                terminal,
+                lock_options.network_allowed(),
+            )
+            .await?,
+        );
     }
 
-    let map = resolver.resolve().await?;
+    update_lock_file_with(
+        &workspace.config_path,
+        &maps.iter().collect::<Vec<_>>(),
+        terminal,
+        lock_options,
+        options.dry_run,
+    )
+}
+
+/// Gets the lock file to resolve dependencies with when updating the given
+/// packages; every other dependency is kept at its locked version.
+///
+/// Returns `None` to update all dependencies, as if the lock file does not
+/// exist, if no packages are given.
+fn locked_packages_to_keep(
+    config_path: &Path,
+    terminal: &Terminal,
+    packages: &[PackageId],
+) -> Result<Option<LockFile>> {
+    if packages.is_empty() {
+        return Ok(None);
+    }
 
     let file_lock = acquire_lock_file_ro(terminal, config_path)?;
-    let orig_lock_file = file_lock
-        .as_ref()
-        .map(|f| {
-            LockFile::read(f.file()).with_context(|| {
-                format!(
-                    "failed to read lock file `{path}`",
-                    path = f.path().display()
-                )
-            })
-        })
-        .transpose()?
-        .unwrap_or_default();
+    let mut lock_file = read_lock_file(file_lock.as_ref())?.unwrap_or_default();
+    for id in packages {
+        if !lock_file.packages.iter().any(|p| p.id == *id) {
+            bail!("package `{id}` is not a dependency in the lock file");
+        }
+    }
 
-    let new_lock_file = to_lock_file(&map);
+    lock_file.packages.retain(|p| !packages.contains(&p.id));
+    Ok(Some(lock_file))
+}
+
+/// Gets the changelog link of a resolved registry package version from its
+/// registry metadata, falling back to its repository link.
+fn changelog_link(
+    maps: &[&DependencyResolutionMap],
+    id: &PackageId,
+    version: &Version,
+) -> Option<String> {
+    let path = maps
+        .iter()
+        .flat_map(|m| m.values())
+        .find_map(|resolution| match resolution {
+            DependencyResolution::Registry(res)
+                if res.package == *id && res.version == *version =>
+            {
+                Some(&res.path)
+            }
+            _ => None,
+        })?;
+
+    let metadata = match fs::read(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| RegistryMetadata::from_wasm(&bytes))
+    {
+        Ok(metadata) => metadata?,
+        Err(e) => {
+            log::debug!("failed to read the registry metadata of `{id}` v{version}: {e}");
+            return None;
+        }
+    };
+
+    let links = metadata.get_links()?;
+    links
+        .iter()
+        .find(|link| matches!(&link.ty, LinkType::Custom(ty) if ty == CHANGELOG_LINK_TYPE))
+        .or_else(|| links.iter().find(|link| link.ty == LinkType::Repository))
+        .map(|link| link.value.clone())
+}
+
+/// Replaces the lock file next to the given configuration file, reporting the
+/// changes to the locked dependencies.
+fn update_lock_file_with(
+    config_path: &Path,
+    maps: &[&DependencyResolutionMap],
+    terminal: &Terminal,
+    lock_options: LockOptions,
+    dry_run: bool,
+) -> Result<()> {
+    let new_lock_file = to_lock_file(maps.iter().copied());
+    let file_lock = acquire_lock_file_ro(terminal, config_path)?;
+    let orig_lock_file = read_lock_file(file_lock.as_ref())?.unwrap_or_default();
 
     for old_pkg in &orig_lock_file.packages {
         let new_pkg = match new_lock_file
@@ -461,6 +1395,25 @@ pub async fn update_lockfile(
                     ),
                     Colors::Cyan,
                 )?;
+
+                let changelog = changelog_link(maps, &old_pkg.id, &new_ver.version);
+                if let Some(changelog) = &changelog {
+                    terminal.note(format!(
+                        "changes of `{id}` v{new}: {changelog}",
+                        id = old_pkg.id,
+                        new = new_ver.version
+                    ))?;
+                }
+
+                terminal.event(
+                    "dependency-updated",
+                    serde_json::json!({
+                        "package": old_pkg.id.to_string(),
+                        "old": old_ver.version.to_string(),
+                        "new": new_ver.version.to_string(),
+                        "changelog": changelog,
+                    }),
+                )?;
             }
         }
     }
@@ -510,22 +1463,43 @@ pub async fn update_lockfile(
         }
     }
 
+    for new_source in &new_lock_file.git {
+        let old_source = orig_lock_file
+            .git
+            .iter()
+            .find(|s| s.key() == new_source.key());
+
+        // The locked commit of the git repository has changed
+        if let Some(old_source) = old_source.filter(|s| s.commit != new_source.commit) {
+            terminal.status_with_color(
+                if dry_run { "Would update" } else { "Updating" },
+                format!(
+                    "git repository `{url}` ({reference}) {old} -> {new}",
+                    url = new_source.url,
+                    reference = new_source.reference,
+                    old = old_source.commit,
+                    new = new_source.commit
+                ),
+                Colors::Cyan,
+            )?;
+        }
+    }
+
+    if new_lock_file == orig_lock_file {
+        terminal.note("the locked dependencies are up to date")?;
+    }
+
     if dry_run {
         terminal.warn("not updating lock file due to --dry-run option")?;
     } else {
-        // Update the lock file
-        if new_lock_file != orig_lock_file {
-            drop(file_lock);
-            let file_lock = acquire_lock_file_rw(terminal, config_path)?;
-            new_lock_file
-                .write(file_lock.file(), "wit")
-                .with_context(|| {
-                    format!(
-                        "failed to write lock file `{path}`",
-                        path = file_lock.path().display()
-                    )
-                })?;
-        }
+        write_lock_file(
+            terminal,
+            config_path,
+            file_lock,
+            Some(&orig_lock_file),
+            new_lock_file,
+            lock_options,
+        )?;
     }
 
     Ok(())
@@ -0,0 +1,483 @@
+//! Module for linting the interfaces of a WIT package.
+//!
+//! Each lint belongs to a rule whose level (`allow`, `warn`, or `deny`) is
+//! configured in the `lint` table of `wit.toml`. The rules are:
+//!
+//! * `naming`: names should be lowercase kebab-case.
+//! * `abbreviations`: names should not use common abbreviations.
+//! * `missing-docs`: interfaces, worlds, types, and functions should be
+//!   documented.
+//! * `fallible-functions`: functions should report errors with a `result`
+//!   whose error type is not a string.
+//! * `deprecation`: deprecated items should explain what to use instead, and
+//!   items that are not deprecated should not use deprecated types.
+//!
+//! WIT has no deprecation attribute yet, so an item is deprecated when a line
+//! of its doc comment starts with `@deprecated`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wit_parser::{
+    Docs, Function, FunctionKind, Handle, InterfaceId, PackageId, Resolve, Results, Type,
+    TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem, WorldKey,
+};
+
+/// The abbreviations reported by the `abbreviations` rule.
+const ABBREVIATIONS: &[&str] = &[
+    "arg", "args", "attr", "attrs", "buf", "cfg", "cnt", "conf", "ctx", "dst", "elem", "err",
+    "hdr", "idx", "impl", "len", "mgr", "msg", "num", "obj", "param", "params", "pkg", "prev",
+    "ptr", "req", "resp", "src", "str", "tmp", "val", "var",
+];
+
+/// The names of function results that report errors without a `result`.
+const ERROR_RESULT_NAMES: &[&str] = &["err", "errno", "error", "error-code", "status"];
+
+/// The prefix of a doc comment line marking an item as deprecated.
+const DEPRECATED_PREFIX: &str = "@deprecated";
+
+/// Represents the level of a lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// The rule is not checked.
+    Allow,
+    /// Violations of the rule are reported as warnings.
+    Warn,
+    /// Violations of the rule are reported as errors.
+    Deny,
+}
+
+/// Represents a lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// Names should be lowercase kebab-case.
+    Naming,
+    /// Names should not use common abbreviations.
+    Abbreviations,
+    /// Items should be documented.
+    MissingDocs,
+    /// Functions should report errors with a `result`.
+    FallibleFunctions,
+    /// Deprecations should be explained and deprecated types not used.
+    Deprecation,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Naming => write!(f, "naming"),
+            Self::Abbreviations => write!(f, "abbreviations"),
+            Self::MissingDocs => write!(f, "missing-docs"),
+            Self::FallibleFunctions => write!(f, "fallible-functions"),
+            Self::Deprecation => write!(f, "deprecation"),
+        }
+    }
+}
+
+/// Represents the `lint` table of `wit.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LintConfig {
+    /// The level of the `naming` rule.
+    pub naming: LintLevel,
+    /// The level of the `abbreviations` rule.
+    pub abbreviations: LintLevel,
+    /// The level of the `missing-docs` rule.
+    pub missing_docs: LintLevel,
+    /// The level of the `fallible-functions` rule.
+    pub fallible_functions: LintLevel,
+    /// The level of the `deprecation` rule.
+    pub deprecation: LintLevel,
+    /// The abbreviations not reported by the `abbreviations` rule.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_abbreviations: Vec<String>,
+}
+
+impl LintConfig {
+    /// Gets the level of the given rule.
+    pub fn level(&self, rule: Rule) -> LintLevel {
+        match rule {
+            Rule::Naming => self.naming,
+            Rule::Abbreviations => self.abbreviations,
+            Rule::MissingDocs => self.missing_docs,
+            Rule::FallibleFunctions => self.fallible_functions,
+            Rule::Deprecation => self.deprecation,
+        }
+    }
+
+    /// Determines if the configuration is the default configuration.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            naming: LintLevel::Warn,
+            abbreviations: LintLevel::Warn,
+            missing_docs: LintLevel::Warn,
+            fallible_functions: LintLevel::Warn,
+            deprecation: LintLevel::Warn,
+            allowed_abbreviations: Vec::new(),
+        }
+    }
+}
+
+/// Represents a violation of a lint rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// The rule that was violated.
+    pub rule: Rule,
+    /// The level of the rule.
+    pub level: LintLevel,
+    /// The path of the item violating the rule (e.g. `interface.function`).
+    pub item: String,
+    /// The description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{item}`: {message} [{rule}]",
+            item = self.item,
+            message = self.message,
+            rule = self.rule
+        )
+    }
+}
+
+/// Lints the interfaces and worlds of a WIT package.
+///
+/// Rules with the `allow` level are not checked.
+pub fn lint_package(resolve: &Resolve, package: PackageId, config: &LintConfig) -> Vec<Lint> {
+    let mut linter = Linter {
+        resolve,
+        config,
+        lints: Vec::new(),
+    };
+
+    let package = &resolve.packages[package];
+    for (name, id) in &package.interfaces {
+        linter.interface(name, name, *id);
+    }
+
+    for (name, id) in &package.worlds {
+        linter.world(name, *id);
+    }
+
+    linter.lints
+}
+
+struct Linter<'a> {
+    resolve: &'a Resolve,
+    config: &'a LintConfig,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn push(&mut self, rule: Rule, item: &str, message: String) {
+        let level = self.config.level(rule);
+        if level == LintLevel::Allow {
+            return;
+        }
+
+        self.lints.push(Lint {
+            rule,
+            level,
+            item: item.to_string(),
+            message,
+        });
+    }
+
+    fn interface(&mut self, item: &str, name: &str, id: InterfaceId) {
+        let resolve = self.resolve;
+        let interface = &resolve.interfaces[id];
+        self.name(item, name);
+        self.docs(item, &interface.docs, "interface");
+        self.deprecation(item, &interface.docs);
+
+        for (name, ty) in &interface.types {
+            // Types brought into scope with `use` are linted where they are defined
+            if self.is_used_type(*ty) {
+                continue;
+            }
+
+            self.ty(&format!("{item}.{name}"), name, *ty);
+        }
+
+        for function in interface.functions.values() {
+            self.function(item, function);
+        }
+    }
+
+    fn world(&mut self, name: &str, id: WorldId) {
+        let resolve = self.resolve;
+        let world = &resolve.worlds[id];
+        self.name(name, name);
+        self.docs(name, &world.docs, "world");
+        self.deprecation(name, &world.docs);
+
+        // Interfaces referenced by the world are linted where they are defined
+        for (key, item) in world.imports.iter().chain(world.exports.iter()) {
+            let item_name = match key {
+                WorldKey::Name(name) => name,
+                WorldKey::Interface(_) => continue,
+            };
+
+            match item {
+                WorldItem::Interface(interface) => {
+                    self.interface(&format!("{name}.{item_name}"), item_name, *interface)
+                }
+                WorldItem::Function(function) => self.function(name, function),
+                WorldItem::Type(ty) => {
+                    if resolve.types[*ty].owner == TypeOwner::World(id) {
+                        self.ty(&format!("{name}.{item_name}"), item_name, *ty);
+                    }
+                }
+            }
+        }
+    }
+
+    fn ty(&mut self, item: &str, name: &str, id: TypeId) {
+        let resolve = self.resolve;
+        let ty = &resolve.types[id];
+        self.name(item, name);
+        self.docs(item, &ty.docs, "type");
+        self.deprecation(item, &ty.docs);
+
+        let deprecated = deprecation_note(&ty.docs).is_some();
+        let mut used = Vec::new();
+        match &ty.kind {
+            TypeDefKind::Record(record) => {
+                for field in &record.fields {
+                    self.name(&format!("{item}.{name}", name = field.name), &field.name);
+                    used.push(field.ty);
+                }
+            }
+            TypeDefKind::Flags(flags) => {
+                for flag in &flags.flags {
+                    self.name(&format!("{item}.{name}", name = flag.name), &flag.name);
+                }
+            }
+            TypeDefKind::Variant(variant) => {
+                for case in &variant.cases {
+                    self.name(&format!("{item}.{name}", name = case.name), &case.name);
+                    used.extend(case.ty);
+                }
+            }
+            TypeDefKind::Enum(e) => {
+                for case in &e.cases {
+                    self.name(&format!("{item}.{name}", name = case.name), &case.name);
+                }
+            }
+            TypeDefKind::Type(ty) => used.push(*ty),
+            _ => {}
+        }
+
+        if !deprecated {
+            for ty in used {
+                self.deprecated_use(item, &ty);
+            }
+        }
+    }
+
+    fn function(&mut self, owner: &str, function: &Function) {
+        // Methods, static functions, and constructors are named `[kind]resource.name`
+        let name = function
+            .name
+            .split_once(']')
+            .map(|(_, name)| name)
+            .unwrap_or(&function.name);
+        let item = format!("{owner}.{name}");
+
+        // The name of a constructor is the name of its resource, which is linted separately
+        if !matches!(function.kind, FunctionKind::Constructor(_)) {
+            self.name(&item, name.rsplit('.').next().unwrap_or(name));
+        }
+
+        self.docs(&item, &function.docs, "function");
+        self.deprecation(&item, &function.docs);
+
+        for (name, _) in &function.params {
+            self.name(&format!("{item}.{name}"), name);
+        }
+
+        let mut used: Vec<_> = function.params.iter().map(|(_, ty)| *ty).collect();
+        match &function.results {
+            Results::Named(results) => {
+                for (name, ty) in results {
+                    self.name(&format!("{item}.{name}"), name);
+                    used.push(*ty);
+
+                    if ERROR_RESULT_NAMES.contains(&name.as_str()) {
+                        self.push(
+                            Rule::FallibleFunctions,
+                            &item,
+                            format!("function reports errors with result `{name}`; return a `result` instead"),
+                        );
+                    }
+                }
+            }
+            Results::Anon(ty) => {
+                used.push(*ty);
+
+                if self.result_error(ty).map(|ty| self.is_string(&ty)) == Some(true) {
+                    self.push(
+                        Rule::FallibleFunctions,
+                        &item,
+                        "function uses `string` as its error type; use a dedicated error type (e.g. an `enum` or `variant`) instead".to_string(),
+                    );
+                }
+            }
+        }
+
+        if deprecation_note(&function.docs).is_none() {
+            for ty in used {
+                self.deprecated_use(&item, &ty);
+            }
+        }
+    }
+
+    fn name(&mut self, item: &str, name: &str) {
+        let name = name.trim_start_matches('%');
+        if name.chars().any(|c| c.is_ascii_uppercase()) {
+            self.push(
+                Rule::Naming,
+                item,
+                format!(
+                    "name `{name}` should be lowercase kebab-case (`{lower}`)",
+                    lower = name.to_ascii_lowercase()
+                ),
+            );
+        }
+
+        for word in name.split('-') {
+            let lower = word.to_ascii_lowercase();
+            if ABBREVIATIONS.contains(&lower.as_str())
+                && !self.config.allowed_abbreviations.contains(&lower)
+            {
+                self.push(
+                    Rule::Abbreviations,
+                    item,
+                    format!("name `{name}` uses the abbreviation `{word}`"),
+                );
+            }
+        }
+    }
+
+    fn docs(&mut self, item: &str, docs: &Docs, kind: &str) {
+        if docs
+            .contents
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .is_empty()
+        {
+            self.push(Rule::MissingDocs, item, format!("{kind} is not documented"));
+        }
+    }
+
+    fn deprecation(&mut self, item: &str, docs: &Docs) {
+        if deprecation_note(docs) == Some("") {
+            self.push(
+                Rule::Deprecation,
+                item,
+                "deprecation does not explain what to use instead".to_string(),
+            );
+        }
+    }
+
+    fn deprecated_use(&mut self, item: &str, ty: &Type) {
+        if let Some(name) = self.find_deprecated(ty) {
+            self.push(
+                Rule::Deprecation,
+                item,
+                format!("uses deprecated type `{name}`"),
+            );
+        }
+    }
+
+    /// Finds a deprecated type referenced by the given type.
+    fn find_deprecated(&self, ty: &Type) -> Option<String> {
+        let id = match ty {
+            Type::Id(id) => *id,
+            _ => return None,
+        };
+
+        let def = &self.resolve.types[id];
+        if let Some(name) = &def.name {
+            if deprecation_note(&def.docs).is_some() {
+                return Some(name.clone());
+            }
+
+            // Follow aliases (including types brought into scope with `use`)
+            return match &def.kind {
+                TypeDefKind::Type(ty) => self.find_deprecated(ty),
+                _ => None,
+            };
+        }
+
+        match &def.kind {
+            TypeDefKind::List(ty) | TypeDefKind::Option(ty) | TypeDefKind::Type(ty) => {
+                self.find_deprecated(ty)
+            }
+            TypeDefKind::Result(r) => {
+                r.ok.iter()
+                    .chain(r.err.iter())
+                    .find_map(|ty| self.find_deprecated(ty))
+            }
+            TypeDefKind::Tuple(t) => t.types.iter().find_map(|ty| self.find_deprecated(ty)),
+            TypeDefKind::Handle(Handle::Own(id) | Handle::Borrow(id)) => {
+                self.find_deprecated(&Type::Id(*id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the error type of a `result` type, following aliases.
+    fn result_error(&self, ty: &Type) -> Option<Type> {
+        match ty {
+            Type::Id(id) => match &self.resolve.types[*id].kind {
+                TypeDefKind::Result(r) => r.err,
+                TypeDefKind::Type(ty) => self.result_error(ty),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Determines if a type is a string, following aliases.
+    fn is_string(&self, ty: &Type) -> bool {
+        match ty {
+            Type::String => true,
+            Type::Id(id) => match &self.resolve.types[*id].kind {
+                TypeDefKind::Type(ty) => self.is_string(ty),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Determines if a type of an interface was brought into scope with `use`.
+    fn is_used_type(&self, id: TypeId) -> bool {
+        let def = &self.resolve.types[id];
+        match &def.kind {
+            TypeDefKind::Type(Type::Id(other)) => self.resolve.types[*other].owner != def.owner,
+            _ => false,
+        }
+    }
+}
+
+/// Gets the deprecation note of an item's documentation.
+///
+/// Returns `None` if the item is not deprecated.
+fn deprecation_note(docs: &Docs) -> Option<&str> {
+    docs.contents.as_deref()?.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix(DEPRECATED_PREFIX)
+            .map(|note| note.trim_start_matches(':').trim())
+    })
+}
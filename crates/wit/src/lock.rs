@@ -38,8 +38,21 @@ pub(crate) fn acquire_lock_file_ro(
     }
 }
 
-pub(crate) fn acquire_lock_file_rw(terminal: &Terminal, config_path: &Path) -> Result<FileLock> {
+pub(crate) fn acquire_lock_file_rw(
+    terminal: &Terminal,
+    config_path: &Path,
+    lock_update_allowed: bool,
+    locked: bool,
+) -> Result<FileLock> {
     let path = config_path.with_file_name(LOCK_FILE_NAME);
+    if !lock_update_allowed {
+        let flag = if locked { "--locked" } else { "--frozen" };
+        anyhow::bail!(
+            "the lock file {path} needs to be updated but {flag} was passed to prevent this",
+            path = path.display()
+        );
+    }
+
     log::info!("creating lock file `{path}`", path = path.display());
     match FileLock::try_open_rw(&path)? {
         Some(lock) => Ok(lock),
@@ -55,18 +68,22 @@ pub(crate) fn acquire_lock_file_rw(terminal: &Terminal, config_path: &Path) -> R
     }
 }
 
-/// Constructs a `LockFile` from a `DependencyResolutionMap`.
-pub fn to_lock_file(map: &DependencyResolutionMap) -> LockFile {
+/// Constructs a `LockFile` from the given `DependencyResolutionMap`s.
+///
+/// Multiple maps are merged into one lock file, such as for the members of a
+/// workspace.
+pub fn to_lock_file<'a>(maps: impl IntoIterator<Item = &'a DependencyResolutionMap>) -> LockFile {
     type PackageKey = (PackageId, Option<String>);
     type VersionsMap = HashMap<String, (Version, AnyHash)>;
     let mut packages: HashMap<PackageKey, VersionsMap> = HashMap::new();
+    let maps: Vec<_> = maps.into_iter().collect();
 
-    for resolution in map.values() {
+    for resolution in maps.iter().flat_map(|m| m.values()) {
         match resolution.key() {
             Some((id, registry)) => {
                 let pkg = match resolution {
                     DependencyResolution::Registry(pkg) => pkg,
-                    DependencyResolution::Local(_) => unreachable!(),
+                    DependencyResolution::Local(_) | DependencyResolution::Git(_) => unreachable!(),
                 };
 
                 let prev = packages
@@ -110,5 +127,5 @@ pub fn to_lock_file(map: &DependencyResolutionMap) -> LockFile {
 
     packages.sort_by(|a, b| a.key().cmp(&b.key()));
 
-    LockFile::new(packages)
+    LockFile::new(packages).with_git_sources(maps.iter().flat_map(|m| m.values()))
 }
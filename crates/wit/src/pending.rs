@@ -0,0 +1,96 @@
+//! Module for the records of publishes that have not completed.
+//!
+//! Before a release is submitted to a warg registry, a record of it is
+//! written next to the configuration file. The record is removed once the
+//! registry has accepted the release, so a record left behind means the
+//! publish was interrupted (e.g. by a network failure) and can be finished
+//! with `wit publish --resume`.
+
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::registry::PackageId;
+
+/// The name of the file of a pending publish.
+pub const PENDING_PUBLISH_FILE_NAME: &str = ".wit-publish.json";
+
+/// Represents a publish of a release that has not completed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PendingPublish {
+    /// The id of the package being published.
+    pub package: PackageId,
+    /// The version being released.
+    pub version: Version,
+    /// The URL of the registry being published to.
+    pub registry: String,
+    /// The digest of the content stored in the registry.
+    pub content: AnyHash,
+    /// Whether or not the package log is being initialized.
+    pub init: bool,
+    /// The id of the last record submitted to the registry, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<AnyHash>,
+}
+
+impl PendingPublish {
+    /// Gets the path of the pending publish for the given configuration file.
+    pub fn path(config_path: &Path) -> PathBuf {
+        config_path.with_file_name(PENDING_PUBLISH_FILE_NAME)
+    }
+
+    /// Loads the pending publish for the given configuration file.
+    ///
+    /// Returns `Ok(None)` if there is no pending publish.
+    pub fn load(config_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path(config_path);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "failed to read pending publish `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        serde_json::from_str(&contents).map(Some).with_context(|| {
+            format!(
+                "failed to parse pending publish `{path}`",
+                path = path.display()
+            )
+        })
+    }
+
+    /// Saves the pending publish for the given configuration file.
+    pub fn save(&self, config_path: &Path) -> Result<()> {
+        let path = Self::path(config_path);
+        fs::write(&path, serde_json::to_string_pretty(self)?).with_context(|| {
+            format!(
+                "failed to write pending publish `{path}`",
+                path = path.display()
+            )
+        })
+    }
+
+    /// Removes the pending publish for the given configuration file.
+    pub fn remove(config_path: &Path) -> Result<()> {
+        let path = Self::path(config_path);
+        if !path.is_file() {
+            return Ok(());
+        }
+
+        fs::remove_file(&path).with_context(|| {
+            format!(
+                "failed to remove pending publish `{path}`",
+                path = path.display()
+            )
+        })
+    }
+}
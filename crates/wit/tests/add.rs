@@ -170,3 +170,124 @@ fn validate_add_from_path() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_adds_a_wit_directory_without_a_package_id() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file("other/wit/bar.wit", "package foo:bar\n\ninterface bar {}\n")?;
+
+    project
+        .wit("add --path other/wit")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` from path `other/wit`"))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("wit.toml"))?;
+    assert!(contains(r#""foo:bar" = { path = "other/wit" }"#).eval(&manifest));
+
+    Ok(())
+}
+
+#[test]
+fn it_adds_a_binary_wit_package_without_a_package_id() -> Result<()> {
+    let root = create_root()?;
+    let dep = Project::with_root(&root, "dep", "")?;
+    dep.file("baz.wit", "package foo:baz\n\ninterface baz {}\n")?;
+    dep.wit("build -o baz.wasm")
+        .assert()
+        .stderr(contains("Created package `baz.wasm`"))
+        .success();
+
+    let project = Project::with_root(&root, "foo", "")?;
+    fs::create_dir_all(project.root().join("deps"))?;
+    fs::copy(
+        dep.root().join("baz.wasm"),
+        project.root().join("deps/baz.wasm"),
+    )?;
+
+    project
+        .wit("add --file deps/baz.wasm")
+        .assert()
+        .stderr(contains(
+            "Added dependency `foo:baz` from path `deps/baz.wasm`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("wit.toml"))?;
+    assert!(contains(r#""foo:baz" = { path = "deps/baz.wasm" }"#).eval(&manifest));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_adds_a_subset_of_interfaces() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file(
+        "foo.wit",
+        r#"package foo:bar
+
+interface types {
+    record point {
+        x: u32,
+        y: u32,
+    }
+}
+
+interface handler {
+    use types.{point}
+
+    handle: func(p: point)
+}
+
+interface other {}
+"#,
+    )?;
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `foo:bar` v0.1.0"))
+        .success();
+
+    let project = Project::with_root(&root, "bar", "")?;
+    project
+        .wit("add foo:bar --interface handler,unknown")
+        .assert()
+        .stderr(contains("has no interface or world named `unknown`"))
+        .failure();
+
+    project
+        .wit("add foo:bar --interface handler")
+        .assert()
+        .stderr(contains(
+            "Added dependency `foo:bar` with version `0.1.0` (interfaces `handler`)",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("wit.toml"))?;
+    assert!(
+        contains(r#""foo:bar" = { version = "0.1.0", interfaces = ["handler"] }"#).eval(&manifest)
+    );
+
+    project.file(
+        "bar.wit",
+        "package bar:baz\n\nworld example {\n    import foo:bar/handler\n}\n",
+    )?;
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Created package `baz.wasm`"))
+        .success();
+
+    project.file(
+        "bar.wit",
+        "package bar:baz\n\nworld example {\n    import foo:bar/other\n}\n",
+    )?;
+    project.wit("build").assert().failure();
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::support::*;
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
-use predicates::str::contains;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
 use std::fs;
 
 mod support;
@@ -97,3 +97,347 @@ fn it_adds_a_producers_field() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_builds_wat() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file("bar.wit", "package foo:bar\ninterface bar {}\n")?;
+
+    project
+        .wit("build --wat")
+        .assert()
+        .stderr(contains("Created package `bar.wat`"))
+        .success();
+
+    let contents = fs::read_to_string(project.root().join("bar.wat"))?;
+    assert!(contents.starts_with("(component"));
+
+    project
+        .wit("build -o out.wat")
+        .assert()
+        .stderr(contains("Created package `out.wat`"))
+        .success();
+
+    let contents = fs::read_to_string(project.root().join("out.wat"))?;
+    assert!(contents.starts_with("(component"));
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_with_locked_if_the_lock_file_needs_updating() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file("foo.wit", "package foo:bar\n")?;
+
+    project
+        .wit("build --locked")
+        .assert()
+        .stderr(contains(
+            "needs to be updated but --locked was passed to prevent this",
+        ))
+        .failure();
+
+    assert!(!project.root().join("wit.lock").exists());
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Created package `bar.wasm`"))
+        .success();
+
+    project
+        .wit("build --locked")
+        .assert()
+        .stderr(contains("Created package `bar.wasm`"))
+        .success();
+
+    project
+        .wit("build --frozen")
+        .assert()
+        .stderr(contains("Created package `bar.wasm`"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_a_patched_registry_package() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "wit.toml",
+        r#"version = "0.1.0"
+
+[dependencies]
+"foo:bar" = "1.0.0"
+
+[patch]
+"foo:bar" = { path = "patch/bar.wit" }
+"#,
+    )?;
+
+    // The patch is used instead of the (unpublished) registry package
+    fs::create_dir_all(project.root().join("patch"))?;
+    fs::write(
+        project.root().join("patch/bar.wit"),
+        "package foo:bar\ninterface baz {\n    f: func()\n}\n",
+    )?;
+    project.file(
+        "foo.wit",
+        "package foo:foo\nworld foo {\n    import foo:bar/baz\n}\n",
+    )?;
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Created package `foo.wasm`"))
+        .success();
+
+    validate_component(&project.root().join("foo.wasm"))?;
+
+    let contents = fs::read_to_string(project.root().join("wit.lock"))?;
+    assert!(!contents.contains("foo:bar"));
+
+    Ok(())
+}
+
+#[test]
+fn it_emits_json_messages() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "foo.wit",
+        r#"package foo:bar
+world foo {}
+"#,
+    )?;
+
+    project
+        .wit("build --message-format json")
+        .assert()
+        .stdout(contains(r#""reason":"status""#))
+        .stdout(contains(r#""status":"Created""#))
+        .stdout(contains(r#""reason":"package-written""#))
+        .stdout(contains(r#""package":"foo:bar""#))
+        .success();
+
+    validate_component(&project.root().join("bar.wasm"))?;
+
+    project
+        .wit("build --message-format yaml")
+        .assert()
+        .stderr(contains(
+            "--message-format must be human or json, but found `yaml`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_a_git_dependency() -> Result<()> {
+    let root = create_root()?;
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    let repo = root.join("repo");
+    let first = git_commit(
+        &repo,
+        &[(
+            "wit/bar.wit",
+            "package foo:bar\ninterface baz {\n    f: func()\n}\n",
+        )],
+    )?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file(
+        "wit.toml",
+        &format!(
+            r#"version = "0.1.0"
+
+[dependencies]
+"foo:bar" = {{ git = '{repo}' }}
+"#,
+            repo = repo.display()
+        ),
+    )?;
+    project.file(
+        "foo.wit",
+        "package foo:foo\nworld foo {\n    import foo:bar/baz\n}\n",
+    )?;
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Updating git repository"))
+        .stderr(contains("Created package `foo.wasm`"))
+        .success();
+
+    validate_component(&project.root().join("foo.wasm"))?;
+
+    let contents = fs::read_to_string(project.root().join("wit.lock"))?;
+    assert!(contents.contains("[[git]]"));
+    assert!(contents.contains(r#"reference = "HEAD""#));
+    assert!(contents.contains(&first));
+
+    // A new commit is not used until the dependency is updated
+    let second = git_commit(
+        &repo,
+        &[(
+            "wit/bar.wit",
+            "package foo:bar\ninterface baz {\n    f: func()\n    g: func()\n}\n",
+        )],
+    )?;
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Updating git repository").not())
+        .success();
+
+    project
+        .wit("update")
+        .assert()
+        .stderr(contains(format!("{first} -> {second}")))
+        .success();
+
+    let contents = fs::read_to_string(project.root().join("wit.lock"))?;
+    assert!(contents.contains(&second));
+    assert!(!contents.contains(&first));
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_git_dependencies() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "wit.toml",
+        r#"version = "0.1.0"
+
+[dependencies]
+"foo:bar" = { git = "https://example.com/bar.git", branch = "main", tag = "v1.0.0" }
+"#,
+    )?;
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains(
+            "only one of `branch`, `tag`, or `rev` may be specified in a git dependency entry",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_a_workspace_in_dependency_order() -> Result<()> {
+    let project = project()?
+        .file(
+            "wit.toml",
+            r#"[workspace]
+members = ["types", "api"]
+"#,
+        )?
+        .file(
+            "api/wit.toml",
+            r#"version = "0.1.0"
+
+[dependencies]
+"foo:types" = { path = "../types" }
+"#,
+        )?
+        .file(
+            "api/api.wit",
+            r#"package foo:api
+interface api {
+    use foo:types/types.{id}
+    get: func(id: id) -> string
+}
+"#,
+        )?
+        .file("types/wit.toml", "version = \"0.1.0\"\n")?
+        .file(
+            "types/types.wit",
+            r#"package foo:types
+interface types {
+    type id = u64
+}
+"#,
+        )?
+        .build();
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(
+            contains("Created package `types/types.wasm`")
+                .and(contains("Created package `api/api.wasm`")),
+        )
+        .success();
+
+    validate_component(&project.root().join("types/types.wasm"))?;
+    validate_component(&project.root().join("api/api.wasm"))?;
+
+    // The members share the lock file of the workspace
+    assert!(project.root().join("wit.lock").is_file());
+    assert!(!project.root().join("api/wit.lock").exists());
+
+    // Building from a member only builds that member
+    fs::remove_file(project.root().join("api/api.wasm"))?;
+    wit("build")
+        .current_dir(project.root().join("api"))
+        .assert()
+        .stderr(
+            contains("Created package `api.wasm`").and(contains("Created package `types").not()),
+        )
+        .success();
+
+    validate_component(&project.root().join("api/api.wasm"))?;
+
+    project
+        .wit("build -o api.wasm")
+        .assert()
+        .stderr(contains(
+            "the `--output` option cannot be used when building every member of a workspace",
+        ))
+        .failure();
+
+    project
+        .wit("lint")
+        .assert()
+        .stderr(contains(
+            "is for a workspace; run this command from the directory of a workspace member",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_cyclic_workspace_members() -> Result<()> {
+    let project = project()?
+        .file("wit.toml", "[workspace]\nmembers = [\"a\", \"b\"]\n")?
+        .file(
+            "a/wit.toml",
+            "version = \"0.1.0\"\n\n[dependencies]\n\"foo:b\" = { path = \"../b\" }\n",
+        )?
+        .file("a/a.wit", "package foo:a\n")?
+        .file(
+            "b/wit.toml",
+            "version = \"0.1.0\"\n\n[dependencies]\n\"foo:a\" = { path = \"../a\" }\n",
+        )?
+        .file("b/b.wit", "package foo:b\n")?
+        .build();
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("has a cyclic path dependency"))
+        .failure();
+
+    Ok(())
+}
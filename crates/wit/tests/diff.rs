@@ -0,0 +1,129 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use toml_edit::value;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help diff", "diff -h", "diff --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains("Compare WIT packages and classify the changes"))
+            .success();
+    }
+}
+
+#[test]
+fn it_classifies_changes() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "old/foo.wit",
+        r#"package foo:bar
+interface baz {
+    record point {
+        x: u32,
+    }
+    /// Gets a point.
+    get: func() -> point
+    remove: func()
+}
+world w {
+    import baz
+}
+"#,
+    )?;
+    project.file(
+        "foo.wit",
+        r#"package foo:bar
+interface baz {
+    record point {
+        x: u32,
+    }
+    /// Gets the point.
+    get: func() -> point
+    add: func(p: point)
+}
+interface qux {}
+world w {
+    import baz
+    export qux
+}
+"#,
+    )?;
+
+    project
+        .wit("diff old")
+        .assert()
+        .stdout(
+            contains("patch: changed the documentation of function `baz.get`")
+                .and(contains("major: removed function `baz.remove`"))
+                .and(contains("minor: added function `baz.add`"))
+                .and(contains("minor: added interface `qux`"))
+                .and(contains("major: added export `foo:bar/qux`")),
+        )
+        .stderr(contains("the changes require a major version bump"))
+        .success();
+
+    project
+        .wit("diff old old")
+        .assert()
+        .stderr(contains("no changes found"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_checks_the_version_against_a_published_version() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file(
+        "baz.wit",
+        "package baz:qux\ninterface a {\n    f: func()\n}\n",
+    )?;
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    project.file(
+        "baz.wit",
+        "package baz:qux\ninterface a {\n    f: func()\n    g: func()\n}\n",
+    )?;
+    project.update_manifest(|mut doc| {
+        doc["version"] = value("0.1.1");
+        Ok(doc)
+    })?;
+
+    project
+        .wit("diff 0.1.0 --check")
+        .assert()
+        .stdout(contains("minor: added function `a.g`"))
+        .stderr(contains(
+            "wit.toml` is not compatible with the changes since v0.1.0; expected at least v0.2.0",
+        ))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        doc["version"] = value("0.2.0");
+        Ok(doc)
+    })?;
+
+    project
+        .wit("diff 0.1.0 --check")
+        .assert()
+        .stderr(contains(
+            "the changes require a minor version bump from v0.1.0 to at least v0.2.0",
+        ))
+        .success();
+
+    Ok(())
+}
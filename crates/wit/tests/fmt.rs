@@ -0,0 +1,89 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help fmt", "fmt -h", "fmt --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains("Format the WIT files of the package"))
+            .success();
+    }
+}
+
+#[test]
+fn it_formats_wit_files() -> Result<()> {
+    let project = Project::new("foo")?;
+    let unformatted = r#"package foo:bar
+interface   baz {
+        /// Gets a point.
+  get: func(  ) -> u32
+}
+world w {   import baz }
+"#;
+    project.file("foo.wit", unformatted)?;
+
+    project
+        .wit("fmt --check")
+        .assert()
+        .stderr(contains("foo.wit` is not formatted"))
+        .stderr(contains("1 WIT file(s) are not formatted"))
+        .failure();
+
+    // Checking does not modify the file
+    assert_eq!(
+        fs::read_to_string(project.root().join("foo.wit"))?,
+        unformatted
+    );
+
+    project
+        .wit("fmt")
+        .assert()
+        .stderr(contains("Formatted").and(contains("foo.wit`")))
+        .success();
+
+    let formatted = fs::read_to_string(project.root().join("foo.wit"))?;
+    assert_ne!(formatted, unformatted);
+    assert!(formatted.starts_with("package foo:bar"));
+    assert!(formatted.contains("/// Gets a point."));
+
+    project.wit("fmt --check").assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_items_in_their_files() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "a.wit",
+        r#"package foo:bar
+interface a { f: func() }
+"#,
+    )?;
+    project.file(
+        "b.wit",
+        r#"world b { import a }
+"#,
+    )?;
+
+    project.wit("fmt").assert().success();
+
+    let a = fs::read_to_string(project.root().join("a.wit"))?;
+    let b = fs::read_to_string(project.root().join("b.wit"))?;
+    assert!(a.starts_with("package foo:bar"));
+    assert!(a.contains("interface a {"));
+    assert!(!a.contains("world b"));
+    assert!(!b.contains("package foo:bar"));
+    assert!(b.contains("world b {"));
+
+    project.wit("build").assert().success();
+    project.wit("fmt --check").assert().success();
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help info", "info -h", "info --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains(
+                "Display information about a package in a registry",
+            ))
+            .success();
+    }
+
+    for arg in ["help search", "search -h", "search --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains("Search a registry for packages"))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_displays_package_information() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file(
+        "baz.wit",
+        "package baz:qux\ninterface types {\n    type id = u32\n}\n",
+    )?;
+    project.update_manifest(|mut doc| {
+        doc["license"] = toml_edit::value("Apache-2.0");
+        Ok(doc)
+    })?;
+
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    project
+        .wit("info baz:qux")
+        .assert()
+        .stdout(contains("package: baz:qux"))
+        .stdout(contains("versions: 0.1.0"))
+        .stdout(contains("license: Apache-2.0"))
+        .stdout(contains("interface: types"))
+        .success();
+
+    project
+        .wit("search baz:qux")
+        .assert()
+        .stdout(contains("baz:qux = \"0.1.0\""))
+        .success();
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::support::*;
 use anyhow::Result;
 use assert_cmd::prelude::*;
-use predicates::str::contains;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
 use std::{fs, path::Path};
 
 mod support;
@@ -54,3 +54,65 @@ fn it_supports_registry_option() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_initializes_from_a_binary_package() -> Result<()> {
+    let root = create_root()?;
+
+    let dep = Project::with_root(&root, "dep", "")?;
+    dep.file(
+        "types.wit",
+        r#"package foo:dep
+interface types {
+    type t = u32
+}
+"#,
+    )?;
+
+    let project = Project::with_root(&root, "bar", "")?;
+    project.file(
+        "bar.wit",
+        r#"package foo:bar@1.2.3
+interface baz {
+    use foo:dep/types.{t}
+    f: func() -> t
+}
+"#,
+    )?;
+
+    project
+        .wit("add --path ../dep foo:dep")
+        .assert()
+        .stderr(contains("Added dependency `foo:dep` from path `../dep`"))
+        .success();
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Created package `bar.wasm`"))
+        .success();
+
+    wit("init --from-wasm bar/bar.wasm extracted")
+        .current_dir(&root)
+        .assert()
+        .stderr(
+            contains("Extracted package `foo:bar@1.2.3`")
+                .and(contains("Extracted package `foo:dep`")),
+        )
+        .success();
+
+    let extracted = root.join("extracted");
+    let config = fs::read_to_string(extracted.join("wit.toml"))?;
+    assert!(config.contains("version = \"1.2.3\""));
+    assert!(config.contains("deps/foo-dep.wit"));
+    assert!(fs::read_to_string(extracted.join("bar.wit"))?.contains("interface baz"));
+    assert!(extracted.join("deps/foo-dep.wit").is_file());
+
+    wit("build")
+        .current_dir(&extracted)
+        .assert()
+        .stderr(contains("Created package `bar.wasm`"))
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help lint", "lint -h", "lint --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains("Lint the interfaces of the package"))
+            .success();
+    }
+}
+
+#[test]
+fn it_accepts_a_well_designed_package() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "foo.wit",
+        r#"package foo:bar
+
+/// Reads files.
+interface reader {
+    /// An error reading a file.
+    enum error-code {
+        /// The file was not found.
+        not-found,
+    }
+
+    /// Reads the contents of a file.
+    read-file: func(path: string) -> result<list<u8>, error-code>
+}
+
+/// The world of a file reader.
+world files {
+    export reader
+}
+"#,
+    )?;
+
+    project
+        .wit("lint")
+        .assert()
+        .stderr(contains("Linted package `foo:bar`").and(contains("warning").not()))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_reports_lints() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "foo.wit",
+        r#"package foo:bar
+
+/// Reads files.
+interface reader {
+    /// @deprecated
+    type old-path = string
+
+    /// Reads a file.
+    read-file: func(path: old-path) -> result<list<u8>, string>
+
+    get-URL: func(idx: u32) -> (value: string, error: u32)
+}
+"#,
+    )?;
+
+    project
+        .wit("lint")
+        .assert()
+        .stderr(contains(
+            "`reader.get-URL`: name `get-URL` should be lowercase kebab-case (`get-url`) [naming]",
+        ))
+        .stderr(contains(
+            "`reader.get-URL.idx`: name `idx` uses the abbreviation `idx` [abbreviations]",
+        ))
+        .stderr(contains(
+            "`reader.get-URL`: function is not documented [missing-docs]",
+        ))
+        .stderr(contains(
+            "`reader.get-URL`: function reports errors with result `error`",
+        ))
+        .stderr(contains(
+            "`reader.read-file`: function uses `string` as its error type",
+        ))
+        .stderr(contains(
+            "`reader.old-path`: deprecation does not explain what to use instead [deprecation]",
+        ))
+        .stderr(contains(
+            "`reader.read-file`: uses deprecated type `old-path` [deprecation]",
+        ))
+        .stderr(contains("Linted package `foo:bar` with 7 warning(s)"))
+        .success();
+
+    project
+        .wit("lint --deny-warnings")
+        .assert()
+        .stderr(contains("package `foo:bar` has 7 lint error(s)"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_uses_the_configured_rule_levels() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file(
+        "wit.toml",
+        r#"version = "0.1.0"
+
+[lint]
+naming = "deny"
+missing-docs = "allow"
+allowed-abbreviations = ["idx"]
+"#,
+    )?;
+    project.file(
+        "foo.wit",
+        r#"package foo:bar
+
+interface reader {
+    get-URL: func(idx: u32) -> string
+}
+"#,
+    )?;
+
+    project
+        .wit("lint")
+        .assert()
+        .stderr(contains(
+            "error: `reader.get-URL`: name `get-URL` should be lowercase kebab-case",
+        ))
+        .stderr(contains("[abbreviations]").not())
+        .stderr(contains("[missing-docs]").not())
+        .stderr(contains("package `foo:bar` has 1 lint error(s)"))
+        .failure();
+
+    Ok(())
+}
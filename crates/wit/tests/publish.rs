@@ -3,10 +3,12 @@ use std::fs;
 use crate::support::*;
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component_core::keystore::Keystore;
 use predicates::str::contains;
 use semver::Version;
 use toml_edit::{value, Array};
 use warg_client::{Client, FileSystemClient};
+use warg_crypto::signing::PrivateKey;
 use warg_protocol::registry::PackageId;
 use wasm_metadata::LinkType;
 
@@ -183,3 +185,493 @@ async fn it_publishes_with_registry_metadata() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_requires_a_local_key_with_a_signing_command() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    // External signers only sign digests, but warg records are signed in process
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+    project
+        .wit("publish --init")
+        .env("WIT_SIGNING_COMMAND", "printenv TEST_SIGNING_KEY")
+        .env("TEST_SIGNING_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "is held by signing command `printenv TEST_SIGNING_KEY`, but warg registries require a local signing key",
+        ))
+        .failure();
+
+    project
+        .wit("publish --init --signing-command false")
+        .assert()
+        .stderr(contains("require a local signing key"))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_with_a_key_file() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+    project.file("publish.key", &format!("{key}\n", key = test_signing_key()))?;
+    project
+        .wit("publish --init --key-file publish.key")
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    project
+        .wit("publish --key-file missing.key")
+        .assert()
+        .stderr(contains("failed to read signing key file `missing.key`"))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_with_a_keystore() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+
+    let url = config
+        .default_url
+        .as_deref()
+        .context("expected a default URL")?;
+    let mut keystore = Keystore::new();
+    keystore.set(
+        &url.parse()?,
+        "default",
+        &PrivateKey::decode(test_signing_key().to_string())?,
+        "correct horse",
+    )?;
+    keystore.save(&project.root().join("keys/ci.json"))?;
+
+    project.update_manifest(|mut doc| {
+        let mut key = toml_edit::InlineTable::new();
+        key.insert("keystore", "keys/ci.json".into());
+        doc["signing-keys"]["default"] = value(key);
+        Ok(doc)
+    })?;
+
+    project
+        .wit("publish --init")
+        .env("SIGNING_KEYSTORE_PASSPHRASE", "battery staple")
+        .assert()
+        .stderr(contains(
+            "the passphrase is incorrect or the keystore was modified",
+        ))
+        .failure();
+
+    project
+        .wit("publish --init")
+        .env("SIGNING_KEYSTORE_PASSPHRASE", "correct horse")
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_uses_the_signing_key_configured_for_the_registry() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+    project.file("keys/publish.key", test_signing_key())?;
+    project.update_manifest(|mut doc| {
+        let mut key = toml_edit::InlineTable::new();
+        key.insert("key-file", "keys/publish.key".into());
+        doc["signing-keys"]["default"] = value(key);
+        Ok(doc)
+    })?;
+
+    // The configured key takes precedence over the environment variable
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", "not-a-key")
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_bumps_the_published_version() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+
+    // The configured version is used when the package has not been published
+    project
+        .wit("publish --init --bump minor")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    project
+        .wit("publish --bump minor --write-version")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Bumping package `baz:qux` to v0.2.0"))
+        .stderr(contains("Published package `baz:qux` v0.2.0"))
+        .success();
+
+    let contents = fs::read_to_string(project.root().join("wit.toml"))?;
+    assert!(contents.contains(r#"version = "0.2.0""#));
+
+    project
+        .wit("publish --bump patch")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.2.1"))
+        .success();
+
+    let contents = fs::read_to_string(project.root().join("wit.toml"))?;
+    assert!(contents.contains(r#"version = "0.2.0""#));
+
+    project
+        .wit("publish --bump major")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v1.0.0"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_uses_the_registry_mapped_to_the_namespace() -> Result<()> {
+    let root = create_root()?;
+    let (_public, config) = spawn_server(&root.join("public")).await?;
+    let (_private, private_config) = spawn_server(&root.join("private")).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let private_url = private_config.default_url.unwrap();
+    let map_namespace = |mut doc: toml_edit::Document| -> Result<toml_edit::Document> {
+        doc["registries"]["internal"] = value(private_url.as_str());
+        doc["namespaces"]["mycorp"] = value("internal");
+        Ok(doc)
+    };
+
+    // Publishing should use the registry mapped to the `mycorp` namespace
+    let project = Project::with_root(&root, "types", "")?;
+    project.file("types.wit", "package mycorp:types\ninterface types {}\n")?;
+    project.update_manifest(map_namespace)?;
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `mycorp:types` v0.1.0"))
+        .success();
+
+    let client = FileSystemClient::new_with_config(None, &config)?;
+    assert!(client
+        .download(&"mycorp:types".parse().unwrap(), &"0.1.0".parse().unwrap())
+        .await
+        .unwrap_err()
+        .to_string()
+        .contains("package `mycorp:types` does not exist"));
+
+    // Resolving a dependency should also use the mapped registry
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file(
+        "foo.wit",
+        "package foo:bar\nworld foo { import mycorp:types/types }\n",
+    )?;
+    project.update_manifest(|mut doc| {
+        doc["dependencies"]["mycorp:types"] = value("0.1.0");
+        map_namespace(doc)
+    })?;
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Created package `bar.wasm`"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_a_workspace_in_dependency_order() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let files = [
+        ("wit.toml", "[workspace]\nmembers = [\"api\", \"types\"]\n"),
+        (
+            "api/wit.toml",
+            "version = \"0.1.0\"\n\n[dependencies]\n\"foo:types\" = { path = \"../types\" }\n",
+        ),
+        (
+            "api/api.wit",
+            "package foo:api\ninterface api {\n    use foo:types/types.{id}\n    get: func(id: id) -> string\n}\n",
+        ),
+        ("types/wit.toml", "version = \"0.2.0\"\n"),
+        (
+            "types/types.wit",
+            "package foo:types\ninterface types {\n    type id = u64\n}\n",
+        ),
+    ];
+
+    for (path, contents) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+    }
+
+    let output = wit("publish --init")
+        .current_dir(&root)
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    let types = stderr
+        .find("Published package `foo:types` v0.2.0")
+        .context("expected `foo:types` to be published")?;
+    let api = stderr
+        .find("Published package `foo:api` v0.1.0")
+        .context("expected `foo:api` to be published")?;
+    assert!(types < api, "dependencies should be published first");
+
+    wit("publish --package foo:other")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains(
+            "the `--package` option cannot be used when publishing every member of a workspace",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_only_changed_workspace_members() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let files = [
+        ("wit.toml", "[workspace]\nmembers = [\"api\", \"types\"]\n"),
+        (
+            "api/wit.toml",
+            "version = \"0.1.0\"\n\n[dependencies]\n\"foo:types\" = { path = \"../types\" }\n",
+        ),
+        (
+            "api/api.wit",
+            "package foo:api\ninterface api {\n    use foo:types/types.{id}\n    get: func(id: id) -> string\n}\n",
+        ),
+        (
+            "types/wit.toml",
+            "version = \"0.2.0\"\n\n[publish]\nbump = \"minor\"\n",
+        ),
+        (
+            "types/types.wit",
+            "package foo:types\ninterface types {\n    type id = u64\n}\n",
+        ),
+    ];
+
+    for (path, contents) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+    }
+
+    wit("publish --init --if-changed")
+        .current_dir(&root)
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `foo:types` v0.2.0"))
+        .stderr(contains("Published package `foo:api` v0.1.0"))
+        .success();
+
+    wit("publish --if-changed")
+        .current_dir(&root)
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "Skipping package `foo:types` (unchanged since v0.2.0)",
+        ))
+        .stderr(contains(
+            "Skipping package `foo:api` (unchanged since v0.1.0)",
+        ))
+        .success();
+
+    // Changing `foo:types` only publishes it, with its version bumped by its policy
+    fs::write(
+        root.join("types/types.wit"),
+        "package foo:types\ninterface types {\n    type id = u64\n}\ninterface other {}\n",
+    )?;
+
+    wit("publish --all --if-changed")
+        .current_dir(root.join("api"))
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Bumping package `foo:types` to v0.3.0"))
+        .stderr(contains("Published package `foo:types` v0.3.0"))
+        .stderr(contains(
+            "Skipping package `foo:api` (unchanged since v0.1.0)",
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_resumes_a_pending_publish() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+    let url = config.default_url.clone().context("missing default URL")?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+
+    project
+        .wit("publish --resume")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("there is no pending publish to resume"))
+        .failure();
+
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+    assert!(!project.root().join(".wit-publish.json").exists());
+
+    let client = FileSystemClient::new_with_config(None, &config)?;
+    let download = client
+        .download(&"baz:qux".parse().unwrap(), &"0.1.0".parse().unwrap())
+        .await?
+        .context("missing download")?;
+
+    // Simulate a publish interrupted after the registry accepted the release
+    let pending = |content: &str| {
+        project.file(
+            ".wit-publish.json",
+            &serde_json::json!({
+                "package": "baz:qux",
+                "version": "0.1.0",
+                "registry": url,
+                "content": content,
+                "init": true,
+            })
+            .to_string(),
+        )
+    };
+    pending(&download.digest.to_string())?;
+
+    project
+        .wit("publish")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("the publish of package `baz:qux` v0.1.0 to"))
+        .stderr(contains("run `wit publish --resume` to finish it"))
+        .failure();
+
+    project
+        .wit("publish --resume")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Resuming publish of package `baz:qux` v0.1.0"))
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+    assert!(!project.root().join(".wit-publish.json").exists());
+
+    // A competing release of the same version with different content is detected
+    pending("sha256:0000000000000000000000000000000000000000000000000000000000000000")?;
+
+    project
+        .wit("publish --resume")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "version 0.1.0 of package `baz:qux` was published with different content by another publish",
+        ))
+        .failure();
+    assert!(!project.root().join(".wit-publish.json").exists());
+
+    Ok(())
+}
+
+#[test]
+fn it_publishes_to_an_oci_registry_with_bearer_auth() -> Result<()> {
+    let root = create_root()?;
+    let registry = spawn_oci_registry(OciAuth::Bearer, 1)?;
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("baz.wit", "package baz:qux\n")?;
+    project.update_manifest(|mut doc| {
+        doc["registries"]["oci"] = value(registry.url());
+        Ok(doc)
+    })?;
+
+    project
+        .wit("publish --registry oci")
+        .assert()
+        .stderr(contains("failed to authenticate with OCI registry"))
+        .failure();
+
+    project
+        .wit("publish --registry oci")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    // The latest version is found by listing the tags a page at a time
+    project
+        .wit("publish --registry oci --bump patch")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.1"))
+        .success();
+    assert_eq!(registry.tags("baz/qux"), ["0.1.0", "0.1.1"]);
+    assert!(registry.content("baz/qux", "0.1.1").is_some());
+
+    project
+        .wit("publish --registry oci --bump patch --if-changed")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .assert()
+        .stderr(contains(
+            "Skipping package `baz:qux` (unchanged since v0.1.1)",
+        ))
+        .success();
+
+    Ok(())
+}
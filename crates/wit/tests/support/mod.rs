@@ -16,6 +16,11 @@ use warg_crypto::signing::PrivateKey;
 use warg_server::{policy::content::WasmContentPolicy, Config, Server};
 use wasmparser::{Chunk, Encoding, Parser, Payload, Validator, WasmFeatures};
 
+#[path = "../../../../tests/support/oci.rs"]
+mod oci;
+
+pub use oci::*;
+
 pub fn test_operator_key() -> &'static str {
     "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk="
 }
@@ -225,6 +230,48 @@ impl Project {
     }
 }
 
+/// Runs a git command in a directory, returning its trimmed output.
+pub fn git(dir: &Path, args: &str) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args.split_whitespace())
+        .env("GIT_AUTHOR_NAME", "test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .output()
+        .context("failed to run `git`")?;
+
+    if !output.status.success() {
+        bail!(
+            "`git {args}` failed: {stderr}",
+            stderr = String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// Commits the given files to a git repository, creating it if needed.
+///
+/// Returns the hash of the new commit.
+pub fn git_commit(repo: &Path, files: &[(&str, &str)]) -> Result<String> {
+    if !repo.join(".git").is_dir() {
+        fs::create_dir_all(repo)?;
+        git(repo, "init --quiet")?;
+    }
+
+    for (path, contents) in files {
+        let path = repo.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+    }
+
+    git(repo, "add --all")?;
+    git(repo, "commit --quiet --message commit")?;
+    git(repo, "rev-parse HEAD")
+}
+
 pub fn validate_component(path: &Path) -> Result<()> {
     let bytes = fs::read(path)
         .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
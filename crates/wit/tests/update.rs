@@ -304,3 +304,100 @@ async fn update_with_changed_dependencies() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn update_selected_packages() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    for name in ["bar", "baz"] {
+        let project = Project::with_root(&root, name, "")?;
+        project.file(format!("{name}.wit"), &format!("package foo:{name}\n"))?;
+        project.file(
+            "wit.toml",
+            "version = \"1.0.0\"\nchangelog = \"https://example.com/changelog\"\n[dependencies]\n[registries]\n",
+        )?;
+
+        project
+            .wit("publish --init")
+            .env("WIT_PUBLISH_KEY", test_signing_key())
+            .assert()
+            .stderr(contains(format!("Published package `foo:{name}` v1.0.0")))
+            .success();
+    }
+
+    let project = Project::with_root(&root, "qux", "")?;
+    project.file("qux.wit", "package foo:qux\n")?;
+    project
+        .wit("add foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `1.0.0"))
+        .success();
+    project
+        .wit("add foo:baz")
+        .assert()
+        .stderr(contains("Added dependency `foo:baz` with version `1.0.0"))
+        .success();
+
+    project
+        .wit("build")
+        .assert()
+        .stderr(contains("Created package `qux.wasm`"))
+        .success();
+
+    for name in ["bar", "baz"] {
+        fs::write(
+            root.join(name).join("wit.toml"),
+            "version = \"1.1.0\"\nchangelog = \"https://example.com/changelog\"\n[dependencies]\n[registries]\n",
+        )?;
+
+        wit("publish")
+            .env("WIT_PUBLISH_KEY", test_signing_key())
+            .current_dir(root.join(name))
+            .assert()
+            .stderr(contains(format!("Published package `foo:{name}` v1.1.0")))
+            .success();
+    }
+
+    project
+        .wit("update --package foo:qux")
+        .assert()
+        .stderr(contains(
+            "package `foo:qux` is not a dependency in the lock file",
+        ))
+        .failure();
+
+    project
+        .wit("update -p foo:bar")
+        .assert()
+        .stderr(
+            contains("Updating dependency `foo:bar` v1.0.0 -> v1.1.0")
+                .and(contains(
+                    "changes of `foo:bar` v1.1.0: https://example.com/changelog",
+                ))
+                .and(contains("foo:baz").not()),
+        )
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("wit.lock"))?;
+    assert!(contains("version = \"1.0.0\"").eval(&lock_file));
+    assert!(contains("version = \"1.1.0\"").eval(&lock_file));
+
+    project
+        .wit("update")
+        .assert()
+        .stderr(
+            contains("Updating dependency `foo:baz` v1.0.0 -> v1.1.0")
+                .and(contains("foo:bar").not()),
+        )
+        .success();
+
+    project
+        .wit("update")
+        .assert()
+        .stderr(contains("the locked dependencies are up to date"))
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use toml_edit::value;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help verify", "verify -h", "verify --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains(
+                "Verify that the package is valid and ready to be published",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_verifies_a_valid_package() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.file("foo.wit", "package foo:bar\ninterface a {}\n")?;
+
+    project
+        .wit("verify")
+        .assert()
+        .stderr(contains("Verified package `foo:bar` v0.1.0"))
+        .success();
+
+    project.file(
+        "foo.wit",
+        "package foo:bar\ninterface a {\n    f: func() -> b\n}\n",
+    )?;
+
+    project
+        .wit("verify")
+        .assert()
+        .stderr(contains("does not exist"))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_verifies_against_a_published_version() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file(
+        "baz.wit",
+        "package baz:qux\ninterface a {\n    f: func()\n}\n",
+    )?;
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `baz:qux` v0.1.0"))
+        .success();
+
+    project
+        .wit("verify --against 0.1.0")
+        .assert()
+        .stderr(contains(
+            "must be greater than the published version v0.1.0",
+        ))
+        .failure();
+
+    project.file(
+        "baz.wit",
+        "package baz:qux\ninterface a {\n    f: func()\n    g: func()\n}\n",
+    )?;
+    project.update_manifest(|mut doc| {
+        doc["version"] = value("0.1.1");
+        Ok(doc)
+    })?;
+
+    project
+        .wit("verify --against 0.1.0")
+        .assert()
+        .stderr(contains("minor: added function `a.g`"))
+        .stderr(contains("expected at least v0.2.0"))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        doc["version"] = value("0.2.0");
+        Ok(doc)
+    })?;
+
+    project
+        .wit("verify --against 0.1.0 --locked")
+        .assert()
+        .stderr(contains("Verified package `baz:qux` v0.2.0 against v0.1.0"))
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help yank", "yank -h", "yank --help"] {
+        wit(arg)
+            .assert()
+            .stdout(contains("Yank a release of a WIT package from a registry"))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_yanks_a_release() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("foo.wit", "package foo:bar\n")?;
+    project
+        .wit("publish --init")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `foo:bar` v0.1.0"))
+        .success();
+
+    project
+        .wit("publish --bump minor")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `foo:bar` v0.2.0"))
+        .success();
+
+    // Lock a dependency on the release before it is yanked
+    let locked = Project::with_root(&root, "locked", "")?;
+    locked.file("locked.wit", "package locked:pkg\n")?;
+    locked
+        .wit("add foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `0.2.0`"))
+        .success();
+    locked.wit("build").assert().success();
+
+    project
+        .wit("yank 0.2.0")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Yanked package `foo:bar` v0.2.0"))
+        .success();
+
+    project
+        .wit("yank 0.2.0")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "version 0.2.0 of package `foo:bar` is already yanked",
+        ))
+        .failure();
+
+    // New dependencies do not select the yanked release
+    let project = Project::with_root(&root, "bar", "")?;
+    project
+        .wit("add foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `0.1.0`"))
+        .success();
+
+    // Locked dependencies continue to use the yanked release
+    locked.wit("build").assert().success();
+    let lock = fs::read_to_string(locked.root().join("wit.lock"))?;
+    assert!(lock.contains(r#"version = "0.2.0""#));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_fails_to_undo_a_yank() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.file("foo.wit", "package foo:bar\n")?;
+    project
+        .wit("yank --undo 0.1.0")
+        .env("WIT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "component registries do not support undoing the yank of a release",
+        ))
+        .failure();
+
+    Ok(())
+}
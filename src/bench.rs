@@ -0,0 +1,627 @@
+//! Module for benchmarking components.
+//!
+//! `cargo component bench` runs `cargo bench` with `cargo-component` as the
+//! target runner, as `cargo component test` does for tests. The options of
+//! the command are passed to the runner in the `CARGO_COMPONENT_BENCH`
+//! environment variable; the runner then runs each benchmark component a
+//! number of times with the runtime and records the timing of the runs in the
+//! results directory, which the command summarizes and compares with the
+//! results of the previous run.
+//!
+//! Each run spawns the runtime, so the time of a run includes starting the
+//! runtime process and instantiating the component as well as running it.
+//! When the runtime is `wasmtime`, components are compiled ahead of the timed
+//! runs so that compilation is not timed. Before the timed runs, a baseline
+//! component that exports `wasi:cli/run` like the benchmark but returns
+//! immediately is run the same number of times; the median of its runs is
+//! subtracted from the runs of the benchmark, so the results approximate the
+//! time spent running the benchmark itself.
+//!
+//! As timings vary between runs and machines, the fuel a run of a benchmark
+//! consumes is also measured when the runtime is `wasmtime`. The runtime
+//! can't report the fuel it consumed, so the benchmark is run with decreasing
+//! fuel limits to find the least amount it completes with.
+
+use crate::explain;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+use wit_component::DecodedWasm;
+
+/// The environment variable used to pass the benchmark options to the target runner.
+pub const BENCH_ENV_VAR: &str = "CARGO_COMPONENT_BENCH";
+
+/// The fuel a benchmark is first run with when searching for the fuel it
+/// consumes.
+const INITIAL_FUEL: u64 = 1 << 16;
+
+/// The fuel consumed by a benchmark is measured to within one part in this
+/// many.
+const FUEL_PRECISION: u64 = 10_000;
+
+/// Represents the options for running benchmark components.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BenchOptions {
+    /// The number of timed runs of each benchmark.
+    pub iterations: usize,
+    /// The number of untimed runs of each benchmark before the timed runs.
+    pub warm_up: usize,
+    /// The amount of fuel each run may consume before trapping.
+    pub fuel: Option<u64>,
+    /// The wall-clock time each run may take before it is interrupted.
+    pub timeout: Option<String>,
+    /// The directory to record the results in.
+    pub results_dir: PathBuf,
+}
+
+impl BenchOptions {
+    /// Gets the arguments that instrument the runtime (`wasmtime`) with the
+    /// fuel and timeout limits of the options.
+    ///
+    /// `wasmtime` interrupts runs that exceed the timeout using epochs.
+    pub fn runtime_args(&self) -> Vec<String> {
+        self.instrument_args(self.fuel)
+    }
+
+    /// Gets the arguments that instrument the runtime with the given fuel
+    /// limit and the timeout of the options.
+    fn instrument_args(&self, fuel: Option<u64>) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(fuel) = fuel {
+            args.push("-W".to_string());
+            args.push(format!("fuel={fuel}"));
+        }
+
+        if let Some(timeout) = &self.timeout {
+            args.push("-W".to_string());
+            args.push(format!("timeout={timeout}"));
+        }
+
+        args
+    }
+}
+
+/// Represents the timing of the runs of a benchmark.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BenchResult {
+    /// The name of the package of the benchmark.
+    pub package: String,
+    /// The name of the benchmark target.
+    pub name: String,
+    /// The number of timed runs.
+    pub iterations: usize,
+    /// The shortest run, in nanoseconds.
+    pub min_ns: u64,
+    /// The longest run, in nanoseconds.
+    pub max_ns: u64,
+    /// The mean of the runs, in nanoseconds.
+    pub mean_ns: u64,
+    /// The median of the runs, in nanoseconds.
+    pub median_ns: u64,
+    /// The standard deviation of the runs, in nanoseconds.
+    pub std_dev_ns: u64,
+    /// The median of the runs of the baseline component, in nanoseconds.
+    ///
+    /// It is subtracted from each run of the benchmark.
+    #[serde(default)]
+    pub baseline_ns: u64,
+    /// The fuel consumed by a run of the benchmark, if it was measured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// The change of the median from the previous run, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<f64>,
+    /// The change of the fuel consumed from the previous run, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel_change: Option<f64>,
+}
+
+impl BenchResult {
+    /// Creates a result from the durations of the timed runs of a benchmark
+    /// and the median of the runs of the baseline component.
+    fn new(package: &str, name: &str, samples: &[Duration], baseline_ns: u64) -> Self {
+        let mut nanos: Vec<u64> = samples
+            .iter()
+            .map(|d| (d.as_nanos() as u64).saturating_sub(baseline_ns))
+            .collect();
+        nanos.sort_unstable();
+
+        let len = nanos.len().max(1) as f64;
+        let mean = nanos.iter().sum::<u64>() as f64 / len;
+        let variance = nanos
+            .iter()
+            .map(|n| (*n as f64 - mean).powi(2))
+            .sum::<f64>()
+            / len;
+        let median = median(&nanos);
+
+        Self {
+            package: package.to_string(),
+            name: name.to_string(),
+            iterations: nanos.len(),
+            min_ns: nanos.first().copied().unwrap_or_default(),
+            max_ns: nanos.last().copied().unwrap_or_default(),
+            mean_ns: mean as u64,
+            median_ns: median,
+            std_dev_ns: variance.sqrt() as u64,
+            baseline_ns,
+            fuel: None,
+            change: None,
+            fuel_change: None,
+        }
+    }
+
+    /// Sets the changes of the median and of the fuel consumed from the
+    /// given previous result.
+    pub fn compare(&mut self, previous: &BenchResult) {
+        if previous.median_ns > 0 {
+            self.change = Some(
+                (self.median_ns as f64 - previous.median_ns as f64) / previous.median_ns as f64
+                    * 100.0,
+            );
+        }
+
+        if let (Some(fuel), Some(previous)) = (self.fuel, previous.fuel) {
+            if previous > 0 {
+                self.fuel_change = Some((fuel as f64 - previous as f64) / previous as f64 * 100.0);
+            }
+        }
+    }
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bench `{name}` of package `{package}`: median {median} (min {min}, mean {mean} ± {std_dev}, max {max}) over {iterations} iterations, excluding a baseline of {baseline}",
+            name = self.name,
+            package = self.package,
+            median = FormattedNanos(self.median_ns),
+            min = FormattedNanos(self.min_ns),
+            mean = FormattedNanos(self.mean_ns),
+            std_dev = FormattedNanos(self.std_dev_ns),
+            max = FormattedNanos(self.max_ns),
+            iterations = self.iterations,
+            baseline = FormattedNanos(self.baseline_ns),
+        )?;
+
+        if let Some(fuel) = self.fuel {
+            write!(f, ", consuming {fuel} fuel")?;
+        }
+
+        if let Some(change) = self.change {
+            write!(f, "; {change:+.2}% from the previous run")?;
+            if let Some(fuel_change) = self.fuel_change {
+                write!(f, " ({fuel_change:+.2}% fuel)")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a number of nanoseconds in the largest fitting unit.
+struct FormattedNanos(u64);
+
+impl fmt::Display for FormattedNanos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0 as f64;
+        if nanos >= 1e9 {
+            write!(f, "{:.3} s", nanos / 1e9)
+        } else if nanos >= 1e6 {
+            write!(f, "{:.3} ms", nanos / 1e6)
+        } else if nanos >= 1e3 {
+            write!(f, "{:.3} µs", nanos / 1e3)
+        } else {
+            write!(f, "{} ns", self.0)
+        }
+    }
+}
+
+/// Gets the median of sorted nanoseconds.
+fn median(nanos: &[u64]) -> u64 {
+    match nanos.len() {
+        0 => 0,
+        n if n % 2 == 0 => (nanos[n / 2 - 1] + nanos[n / 2]) / 2,
+        n => nanos[n / 2],
+    }
+}
+
+/// Runs a benchmark component with the given runtime command, recording the
+/// timing of its runs in the results directory.
+///
+/// The command is created for the path of the component to run and any
+/// additional runtime arguments, which include the instrumentation of the
+/// options. If `wasmtime` is the name or path of the `wasmtime` program, the
+/// components are compiled with it before they are run and the fuel consumed
+/// by the benchmark is measured.
+///
+/// The name of the benchmark is the name of its target, which cargo uses as
+/// the file name of its output followed by a hash.
+pub fn run(
+    options: &BenchOptions,
+    package: &str,
+    path: &Path,
+    wasmtime: Option<&str>,
+    mut command: impl FnMut(&Path, &[&str]) -> Command,
+) -> Result<BenchResult> {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .with_context(|| format!("invalid benchmark path `{path}`", path = path.display()))?;
+    let name = match stem.rsplit_once('-') {
+        Some((name, hash)) if hash.chars().all(|c| c.is_ascii_hexdigit()) => name,
+        _ => stem,
+    };
+
+    let dir = options.results_dir.join(package);
+    fs::create_dir_all(&dir).with_context(|| {
+        format!(
+            "failed to create benchmark results directory `{dir}`",
+            dir = dir.display()
+        )
+    })?;
+
+    let baseline = match run_export(path)? {
+        Some(export) => {
+            let baseline = dir.join(format!("{name}.baseline.wasm"));
+            fs::write(&baseline, baseline_component(&export)?).with_context(|| {
+                format!(
+                    "failed to write baseline component `{path}`",
+                    path = baseline.display()
+                )
+            })?;
+            Some(baseline)
+        }
+        None => {
+            log::debug!(
+                "benchmark `{name}` of package `{package}` does not export `wasi:cli/run`; not measuring a baseline"
+            );
+            None
+        }
+    };
+
+    // Compile the components ahead of the timed runs
+    let instrument = options.runtime_args();
+    let mut extra_args: Vec<&str> = instrument.iter().map(String::as_str).collect();
+    let (compiled, baseline) = match wasmtime {
+        Some(wasmtime) => {
+            extra_args.push("--allow-precompiled");
+            let compiled = precompile(
+                wasmtime,
+                &instrument,
+                path,
+                &dir.join(format!("{name}.cwasm")),
+            )?;
+            let baseline = baseline
+                .map(|b| {
+                    let output = b.with_extension("cwasm");
+                    precompile(wasmtime, &instrument, &b, &output)
+                })
+                .transpose()?;
+            (compiled, baseline)
+        }
+        None => (path.to_path_buf(), baseline),
+    };
+
+    let baseline_ns = match &baseline {
+        Some(baseline) => {
+            let mut nanos: Vec<u64> = time_runs(
+                options,
+                &format!("the baseline of benchmark `{name}` of package `{package}`"),
+                || command(baseline, &extra_args),
+            )?
+            .iter()
+            .map(|d| d.as_nanos() as u64)
+            .collect();
+            nanos.sort_unstable();
+            median(&nanos)
+        }
+        None => 0,
+    };
+
+    let desc = format!("benchmark `{name}` of package `{package}`");
+    let samples = time_runs(options, &desc, || command(&compiled, &extra_args))?;
+
+    let mut result = BenchResult::new(package, name, &samples, baseline_ns);
+    if let Some(wasmtime) = wasmtime {
+        // The timed runs are only instrumented for fuel with a fuel limit
+        let instrumented = match options.fuel {
+            Some(_) => compiled,
+            None => precompile(
+                wasmtime,
+                &options.instrument_args(Some(u64::MAX)),
+                path,
+                &dir.join(format!("{name}.fuel.cwasm")),
+            )?,
+        };
+
+        result.fuel = Some(find_fuel(options.fuel, |fuel| {
+            let args = options.instrument_args(Some(fuel));
+            let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+            args.push("--allow-precompiled");
+
+            let mut cmd = command(&instrumented, &args);
+            log::debug!("spawning fuel measurement command {cmd:?}");
+            let output = cmd
+                .output()
+                .with_context(|| format!("failed to spawn the runtime for {desc}"))?;
+            if output.status.success() {
+                return Ok(true);
+            }
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("all fuel consumed") {
+                return Ok(false);
+            }
+
+            bail!(
+                "{desc} failed ({status}) when measuring its fuel:\n{stdout}{stderr}",
+                status = output.status,
+                stdout = String::from_utf8_lossy(&output.stdout),
+            );
+        })?);
+    }
+
+    let path = dir.join(format!("{name}.json"));
+    fs::write(&path, serde_json::to_string_pretty(&result)?).with_context(|| {
+        format!(
+            "failed to write benchmark results `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    Ok(result)
+}
+
+/// Times the runs of a command, returning the durations of the runs after the
+/// warm-up runs.
+fn time_runs(
+    options: &BenchOptions,
+    desc: &str,
+    mut command: impl FnMut() -> Command,
+) -> Result<Vec<Duration>> {
+    let mut samples = Vec::with_capacity(options.iterations);
+    for i in 0..options.warm_up + options.iterations {
+        let mut cmd = command();
+        log::debug!("spawning benchmark command {cmd:?}");
+
+        let start = Instant::now();
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to spawn the runtime for {desc}"))?;
+        let elapsed = start.elapsed();
+
+        if !output.status.success() {
+            bail!(
+                "{desc} failed ({status}):\n{stdout}{stderr}",
+                status = output.status,
+                stdout = String::from_utf8_lossy(&output.stdout),
+                stderr = String::from_utf8_lossy(&output.stderr),
+            );
+        }
+
+        if i >= options.warm_up {
+            samples.push(elapsed);
+        }
+    }
+
+    Ok(samples)
+}
+
+/// Finds the least amount of fuel with which a run of a benchmark succeeds,
+/// to within one part in `FUEL_PRECISION`.
+///
+/// `limit` is an amount of fuel a run is known to succeed with, if any;
+/// otherwise the fuel is doubled from `INITIAL_FUEL` until a run succeeds.
+fn find_fuel(limit: Option<u64>, mut succeeds: impl FnMut(u64) -> Result<bool>) -> Result<u64> {
+    let mut low = 0;
+    let mut high = match limit {
+        Some(limit) => limit,
+        None => {
+            let mut fuel = INITIAL_FUEL;
+            while !succeeds(fuel)? {
+                low = fuel;
+                fuel = fuel
+                    .checked_mul(2)
+                    .context("benchmark did not complete with the maximum amount of fuel")?;
+            }
+            fuel
+        }
+    };
+
+    while high - low > (high / FUEL_PRECISION).max(1) {
+        let fuel = low + (high - low) / 2;
+        if succeeds(fuel)? {
+            high = fuel;
+        } else {
+            low = fuel;
+        }
+    }
+
+    Ok(high)
+}
+
+/// Compiles a component with `wasmtime compile`, using the given fuel and
+/// timeout instrumentation of the runs, as compiled code depends on it.
+fn precompile(
+    wasmtime: &str,
+    instrument: &[String],
+    path: &Path,
+    output: &Path,
+) -> Result<PathBuf> {
+    let mut cmd = Command::new(wasmtime);
+    cmd.arg("compile")
+        .args(["-W", "component-model"])
+        .args(instrument)
+        .arg(path)
+        .arg("-o")
+        .arg(output);
+    log::debug!("spawning compile command {cmd:?}");
+
+    let out = cmd
+        .output()
+        .with_context(|| format!("failed to spawn `{wasmtime}`"))?;
+    if !out.status.success() {
+        bail!(
+            "failed to compile `{path}` ({status}):\n{stderr}",
+            path = path.display(),
+            status = out.status,
+            stderr = String::from_utf8_lossy(&out.stderr),
+        );
+    }
+
+    Ok(output.to_path_buf())
+}
+
+/// Gets the name of the `wasi:cli/run` export of a component, including its
+/// version, if it has one.
+fn run_export(path: &Path) -> Result<Option<String>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+    let (resolve, world) = match wit_component::decode(&bytes)
+        .with_context(|| format!("failed to decode `{path}`", path = path.display()))?
+    {
+        DecodedWasm::Component(resolve, world) => (resolve, world),
+        DecodedWasm::WitPackage(..) => return Ok(None),
+    };
+
+    Ok(resolve.worlds[world]
+        .exports
+        .keys()
+        .map(|key| explain::key_name(&resolve, key))
+        .find(|name| name == "wasi:cli/run" || name.starts_with("wasi:cli/run@")))
+}
+
+/// Encodes the baseline component, which exports `wasi:cli/run` with the
+/// given name and returns from `run` immediately.
+fn baseline_component(export: &str) -> Result<Vec<u8>> {
+    wat::parse_str(format!(
+        r#"(component
+  (core module $m (func (export "run") (result i32) i32.const 0))
+  (core instance $i (instantiate $m))
+  (func $run (result (result)) (canon lift (core func $i "run")))
+  (instance $run-instance (export "run" (func $run)))
+  (export "{export}" (instance $run-instance))
+)"#
+    ))
+    .context("failed to encode the baseline component")
+}
+
+/// Reads the results recorded in the given results directory, keyed by
+/// package and benchmark name.
+pub fn read_results(dir: &Path) -> Result<HashMap<(String, String), BenchResult>> {
+    let mut results = HashMap::new();
+    if !dir.is_dir() {
+        return Ok(results);
+    }
+
+    for package in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+    {
+        let package = package?.path();
+        if !package.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(&package)
+            .with_context(|| format!("failed to read directory `{dir}`", dir = package.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let result: BenchResult = serde_json::from_str(
+                &fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read `{path}`", path = path.display()))?,
+            )
+            .with_context(|| {
+                format!(
+                    "failed to parse benchmark results `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            results.insert((result.package.clone(), result.name.clone()), result);
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_encodes_a_baseline_component() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for export in ["wasi:cli/run", "wasi:cli/run@0.2.0"] {
+            let path = dir.path().join("baseline.wasm");
+            fs::write(&path, baseline_component(export)?)?;
+            assert_eq!(run_export(&path)?.as_deref(), Some(export));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_finds_the_fuel_consumed() -> Result<()> {
+        let mut runs = 0;
+        let fuel = find_fuel(None, |fuel| {
+            runs += 1;
+            Ok(fuel >= 12_345)
+        })?;
+        assert_eq!(fuel, 12_345);
+        assert!(runs < 32);
+
+        // A known limit bounds the search
+        assert_eq!(find_fuel(Some(2_000), |fuel| Ok(fuel >= 1_500))?, 1_500);
+
+        // Large amounts are found to within the precision
+        let fuel = find_fuel(None, |fuel| Ok(fuel >= 1_000_000_007))?;
+        assert!((1_000_000_007..=1_000_000_007 + 1_000_000_007 / FUEL_PRECISION).contains(&fuel));
+
+        assert!(find_fuel(None, |_| bail!("trapped")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn it_compares_the_fuel_consumed() {
+        let samples = [Duration::from_nanos(100)];
+        let mut previous = BenchResult::new("foo", "bar", &samples, 0);
+        previous.fuel = Some(1_000);
+
+        let mut result = BenchResult::new("foo", "bar", &samples, 0);
+        result.fuel = Some(1_100);
+        result.compare(&previous);
+        assert_eq!(result.fuel_change, Some(10.0));
+        assert!(result
+            .to_string()
+            .ends_with(", consuming 1100 fuel; +0.00% from the previous run (+10.00% fuel)"));
+    }
+
+    #[test]
+    fn it_subtracts_the_baseline() {
+        let samples = [150, 110, 130].map(Duration::from_nanos);
+        let result = BenchResult::new("foo", "bar", &samples, 100);
+        assert_eq!(result.baseline_ns, 100);
+        assert_eq!(result.min_ns, 10);
+        assert_eq!(result.median_ns, 30);
+        assert_eq!(result.max_ns, 50);
+
+        // Runs faster than the baseline are not negative
+        let result = BenchResult::new("foo", "bar", &samples, 200);
+        assert_eq!(result.max_ns, 0);
+    }
+}
@@ -1,11 +1,21 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use cargo_component::{
-    commands::{AddCommand, KeyCommand, NewCommand, PublishCommand, UpdateCommand},
+    commands::{
+        AddCommand, AuditCommand, BenchCommand, BindgenCommand, BindingsCommand, CacheCommand,
+        CheckTargetCommand, ComposeCommand, ExplainCommand, InfoCommand, InspectCommand,
+        InstallCommand, KeyCommand, LoginCommand, LogoutCommand, MetadataCommand, MigrateCommand,
+        MockCommand, NewCommand, PublishCommand, SearchCommand, ServeCommand, StubCommand,
+        UpdateCommand, VendorCommand, VerifyCommand, WhyCommand, YankCommand,
+    },
     config::{CargoArguments, Config},
-    load_component_metadata, load_metadata, run_cargo_command,
+    load_component_metadata, load_metadata, run_cargo_command, run_component, RUNNER_ARG,
+};
+use cargo_component_core::{
+    command::detect_message_format,
+    terminal::{Color, Terminal, Verbosity},
 };
-use cargo_component_core::terminal::{Color, Terminal, Verbosity};
 use clap::{CommandFactory, Parser};
+use std::path::Path;
 
 fn version() -> &'static str {
     option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION"))
@@ -14,16 +24,38 @@ fn version() -> &'static str {
 /// The list of commands that are built-in to `cargo-component`.
 const BUILTIN_COMMANDS: &[&str] = &[
     "add",
+    "audit",
+    "bench",
+    "bindgen",
+    "bindings",
+    "cache",
+    "check-target",
+    "clean",
     "component", // for indirection via `cargo component`
+    "compose",
+    "explain",
     "help",
+    "info",
+    "inspect",
     "init",
+    "install",
     "key",
+    "login",
+    "logout",
+    "metadata",
+    "migrate",
+    "mock",
     "new",
     "publish",
     "remove",
     "rm",
+    "search",
+    "serve",
+    "stub",
     "update",
     "vendor",
+    "verify",
+    "why",
     "yank",
 ];
 
@@ -31,15 +63,7 @@ const BUILTIN_COMMANDS: &[&str] = &[
 ///
 /// These commands are intended to integrate with `crates.io` and have no
 /// analog in `cargo-component` currently.
-const UNSUPPORTED_COMMANDS: &[&str] = &[
-    "install",
-    "login",
-    "logout",
-    "owner",
-    "package",
-    "search",
-    "uninstall",
-];
+const UNSUPPORTED_COMMANDS: &[&str] = &["login", "logout", "owner", "package", "uninstall"];
 
 const AFTER_HELP: &str = "Unrecognized subcommands will be passed to cargo verbatim after\n\
      relevant component bindings are updated.\n\
@@ -67,14 +91,36 @@ enum CargoComponent {
 #[derive(Parser)]
 enum Command {
     Add(AddCommand),
+    Audit(AuditCommand),
+    Bench(BenchCommand),
+    Bindgen(BindgenCommand),
+    Bindings(BindingsCommand),
+    Cache(CacheCommand),
+    CheckTarget(CheckTargetCommand),
+    Clean(CleanCommand),
+    Compose(ComposeCommand),
+    Explain(ExplainCommand),
+    Info(InfoCommand),
+    Inspect(InspectCommand),
+    Install(InstallCommand),
     // TODO: Init(InitCommand),
     Key(KeyCommand),
+    Login(LoginCommand),
+    Logout(LogoutCommand),
+    Metadata(MetadataCommand),
+    Migrate(MigrateCommand),
+    Mock(MockCommand),
     New(NewCommand),
     // TODO: Remove(RemoveCommand),
     Update(UpdateCommand),
     Publish(PublishCommand),
-    // TODO: Yank(YankCommand),
-    // TODO: Vendor(VendorCommand),
+    Search(SearchCommand),
+    Serve(ServeCommand),
+    Stub(StubCommand),
+    Yank(YankCommand),
+    Vendor(VendorCommand),
+    Verify(VerifyCommand),
+    Why(WhyCommand),
 }
 
 fn detect_subcommand() -> Option<String> {
@@ -105,6 +151,22 @@ fn detect_subcommand() -> Option<String> {
 async fn main() -> Result<()> {
     pretty_env_logger::init_custom_env("CARGO_COMPONENT_LOG");
 
+    // Check if cargo is invoking `cargo-component` as a target runner
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() == Some(RUNNER_ARG) {
+        let config = Config::new(Terminal::new(Verbosity::Normal, Color::Auto))?;
+        let path = args
+            .next()
+            .context("expected a path to a WebAssembly module to run")?;
+        let args: Vec<_> = args.collect();
+        if let Err(e) = run_component(&config, Path::new(&path), &args) {
+            config.terminal().error(format!("{e:?}"))?;
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
     let subcommand = detect_subcommand();
     match subcommand.as_deref() {
         // Check for built-in command or no command (shows help)
@@ -112,13 +174,38 @@ async fn main() -> Result<()> {
             if let Err(e) = match CargoComponent::parse() {
                 CargoComponent::Component(cmd) | CargoComponent::Command(cmd) => match cmd {
                     Command::Add(cmd) => cmd.exec().await,
+                    Command::Audit(cmd) => cmd.exec().await,
+                    Command::Bench(cmd) => cmd.exec().await,
+                    Command::Bindgen(cmd) => cmd.exec().await,
+                    Command::Bindings(cmd) => cmd.exec().await,
+                    Command::Cache(cmd) => cmd.exec().await,
+                    Command::CheckTarget(cmd) => cmd.exec().await,
+                    Command::Clean(cmd) => cmd.exec().await,
+                    Command::Compose(cmd) => cmd.exec().await,
+                    Command::Explain(cmd) => cmd.exec().await,
+                    Command::Info(cmd) => cmd.exec().await,
+                    Command::Inspect(cmd) => cmd.exec().await,
+                    Command::Install(cmd) => cmd.exec().await,
                     Command::Key(cmd) => cmd.exec().await,
+                    Command::Login(cmd) => cmd.exec().await,
+                    Command::Logout(cmd) => cmd.exec().await,
+                    Command::Metadata(cmd) => cmd.exec().await,
+                    Command::Migrate(cmd) => cmd.exec().await,
+                    Command::Mock(cmd) => cmd.exec().await,
                     Command::New(cmd) => cmd.exec().await,
                     Command::Update(cmd) => cmd.exec().await,
                     Command::Publish(cmd) => cmd.exec().await,
+                    Command::Search(cmd) => cmd.exec().await,
+                    Command::Serve(cmd) => cmd.exec().await,
+                    Command::Stub(cmd) => cmd.exec().await,
+                    Command::Vendor(cmd) => cmd.exec().await,
+                    Command::Verify(cmd) => cmd.exec().await,
+                    Command::Why(cmd) => cmd.exec().await,
+                    Command::Yank(cmd) => cmd.exec().await,
                 },
             } {
-                let terminal = Terminal::new(Verbosity::Normal, Color::Auto);
+                let terminal = Terminal::new(Verbosity::Normal, Color::Auto)
+                    .with_message_format(detect_message_format(std::env::args()));
                 terminal.error(format!("{e:?}"))?;
                 std::process::exit(1);
             }
@@ -126,7 +213,8 @@ async fn main() -> Result<()> {
 
         // Check for explicitly unsupported commands (e.g. those that deal with crates.io)
         Some(cmd) if UNSUPPORTED_COMMANDS.contains(&cmd) => {
-            let terminal = Terminal::new(Verbosity::Normal, Color::Auto);
+            let terminal = Terminal::new(Verbosity::Normal, Color::Auto)
+                .with_message_format(detect_message_format(std::env::args()));
             terminal.error(format!(
                 "command `{cmd}` is not supported by `cargo component`\n\n\
                  use `cargo {cmd}` instead"
@@ -147,17 +235,20 @@ async fn main() -> Result<()> {
         _ => {
             // Not a built-in command, run the cargo command
             let cargo_args = CargoArguments::parse()?;
-            let config = Config::new(Terminal::new(
-                if cargo_args.quiet {
-                    Verbosity::Quiet
-                } else {
-                    match cargo_args.verbose {
-                        0 => Verbosity::Normal,
-                        _ => Verbosity::Verbose,
-                    }
-                },
-                cargo_args.color.unwrap_or_default(),
-            ))?;
+            let config = Config::new(
+                Terminal::new(
+                    if cargo_args.quiet {
+                        Verbosity::Quiet
+                    } else {
+                        match cargo_args.verbose {
+                            0 => Verbosity::Normal,
+                            _ => Verbosity::Verbose,
+                        }
+                    },
+                    cargo_args.color.unwrap_or_default(),
+                )
+                .with_message_format(detect_message_format(std::env::args())),
+            )?;
 
             let metadata = load_metadata(cargo_args.manifest_path.as_deref())?;
             let packages = load_component_metadata(
@@ -0,0 +1,116 @@
+//! Module for generating bindings for languages other than Rust.
+//!
+//! The bindings are generated with the respective `wit-bindgen` generators
+//! from either the resolved target world of a component package or the world
+//! of a built component.
+
+use anyhow::{bail, Context, Result};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use wit_bindgen_core::{Files, WorldGenerator};
+use wit_component::DecodedWasm;
+use wit_parser::{Resolve, WorldId};
+
+/// The languages bindings can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// Generate C bindings.
+    C,
+    /// Generate Go (TinyGo) bindings.
+    Go,
+}
+
+impl Language {
+    fn generator(&self) -> Box<dyn WorldGenerator> {
+        match self {
+            Self::C => wit_bindgen_c::Opts::default().build(),
+            Self::Go => wit_bindgen_go::Opts::default().build(),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "c" => Ok(Self::C),
+            "go" => Ok(Self::Go),
+            "python" | "py" => bail!(
+                "generating Python bindings is not supported; use `componentize-py bindings` with the WIT of the component instead"
+            ),
+            _ => bail!("argument for --lang must be c or go, but found `{value}`"),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::C => write!(f, "c"),
+            Self::Go => write!(f, "go"),
+        }
+    }
+}
+
+/// Generates bindings for the given world into the output directory.
+///
+/// Returns the paths of the generated files.
+pub fn generate(
+    resolve: &Resolve,
+    world: WorldId,
+    language: Language,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Files::default();
+    language.generator().generate(resolve, world, &mut files);
+
+    fs::create_dir_all(out_dir).with_context(|| {
+        format!(
+            "failed to create output directory `{path}`",
+            path = out_dir.display()
+        )
+    })?;
+
+    let mut paths = Vec::new();
+    for (name, contents) in files.iter() {
+        let path = out_dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{path}`",
+                    path = parent.display()
+                )
+            })?;
+        }
+
+        fs::write(&path, contents).with_context(|| {
+            format!(
+                "failed to write bindings file `{path}`",
+                path = path.display()
+            )
+        })?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Decodes the world of a built component.
+pub fn decode_component(path: &Path) -> Result<(Resolve, WorldId)> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+
+    match wit_component::decode(&bytes)
+        .with_context(|| format!("failed to decode component `{path}`", path = path.display()))?
+    {
+        DecodedWasm::Component(resolve, world) => Ok((resolve, world)),
+        DecodedWasm::WitPackage(..) => bail!(
+            "file `{path}` is a WIT package and not a component",
+            path = path.display()
+        ),
+    }
+}
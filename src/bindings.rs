@@ -1,15 +1,17 @@
 //! Module for bindings generation.
 
 use crate::{
-    last_modified_time,
+    instances, last_modified_time,
     metadata::{ComponentMetadata, Target},
     registry::PackageDependencyResolution,
+    sources, transitive,
 };
 use anyhow::{bail, Context, Result};
-use cargo_component_core::registry::DecodedDependency;
+use cargo_component_core::registry::{DecodedDependency, DependencyResolutionMap};
 use indexmap::{IndexMap, IndexSet};
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
     path::{Path, PathBuf},
     time::SystemTime,
 };
@@ -20,7 +22,38 @@ use wit_parser::{
     World, WorldId, WorldItem, WorldKey,
 };
 
-fn named_world_key<'a>(resolve: &'a Resolve, orig: &'a WorldKey, prefix: &str) -> WorldKey {
+/// Adds the files of a target or dependency path to the set of inputs.
+///
+/// Directories are walked recursively for WIT files; any other path is added
+/// as it is.
+fn add_inputs(path: &Path, inputs: &mut BTreeSet<PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        inputs.insert(path.to_path_buf());
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(path)
+        .with_context(|| format!("failed to read directory `{path}`", path = path.display()))?;
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("failed to read directory `{path}`", path = path.display()))?;
+        let path = entry.path();
+        if path.is_dir() {
+            add_inputs(&path, inputs)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("wit") {
+            inputs.insert(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn named_world_key<'a>(
+    resolve: &'a Resolve,
+    orig: &'a WorldKey,
+    prefix: &str,
+    renames: &'a HashMap<String, String>,
+) -> WorldKey {
     let name = match orig {
         WorldKey::Name(n) => n,
         WorldKey::Interface(id) => {
@@ -28,6 +61,7 @@ fn named_world_key<'a>(resolve: &'a Resolve, orig: &'a WorldKey, prefix: &str) -
             iface.name.as_ref().expect("unnamed interface")
         }
     };
+    let name = renames.get(name).unwrap_or(name);
 
     WorldKey::Name(format!("{prefix}-{name}"))
 }
@@ -39,29 +73,66 @@ fn named_world_key<'a>(resolve: &'a Resolve, orig: &'a WorldKey, prefix: &str) -
 /// will use for generating the bindings.
 pub struct BindingsEncoder<'a> {
     resolution: &'a PackageDependencyResolution<'a>,
+    target: &'a Target,
     resolve: Resolve,
     world: WorldId,
-    source_files: Vec<PathBuf>,
+    inputs: BTreeSet<PathBuf>,
 }
 
 impl<'a> BindingsEncoder<'a> {
-    /// Creates a new bindings encoder for the given bindings directory
-    /// and package dependency resolution.
-    pub fn new(resolution: &'a PackageDependencyResolution<'a>) -> Result<Self> {
+    /// Creates a new bindings encoder for the given package dependency resolution.
+    ///
+    /// If `name` is `None`, the package's default target is encoded; otherwise
+    /// the named target is encoded. Optional component dependencies are only
+    /// imported if enabled by the given features.
+    pub fn new(
+        resolution: &'a PackageDependencyResolution<'a>,
+        name: Option<&str>,
+        features: &HashSet<String>,
+    ) -> Result<Self> {
+        let target = resolution.metadata.target(name)?;
+        let target_resolutions = resolution
+            .target_resolutions(name)
+            .expect("target should have resolutions");
+
         let (resolve, world, source_files) =
-            Self::create_target_world(resolution).with_context(|| {
-                format!(
-                    "failed to create a target world for package `{name}` ({path})",
-                    name = resolution.metadata.name,
-                    path = resolution.metadata.manifest_path.display()
-                )
-            })?;
+            Self::create_target_world(resolution, target, target_resolutions, features)
+                .with_context(|| match name {
+                    Some(name) => format!(
+                        "failed to create target world `{name}` for package `{package}` ({path})",
+                        package = resolution.metadata.name,
+                        path = resolution.metadata.manifest_path.display()
+                    ),
+                    None => format!(
+                        "failed to create a target world for package `{name}` ({path})",
+                        name = resolution.metadata.name,
+                        path = resolution.metadata.manifest_path.display()
+                    ),
+                })?;
+
+        // The inputs are every file the target world may have been parsed
+        // from: the source files of the parsed packages and the transitive
+        // contents of local target and dependency directories (such as the
+        // `wit` directory of a sibling project and its `deps` directory)
+        let mut inputs: BTreeSet<_> = source_files.into_iter().collect();
+        if let Some(path) = resolution.metadata.local_target_path(target) {
+            add_inputs(&path, &mut inputs)?;
+        }
+
+        if let Some(dir) = Self::generated_dir(resolution.metadata, target) {
+            add_inputs(dir, &mut inputs)?;
+        }
+
+        for (_, dep) in resolution.all() {
+            add_inputs(dep.path(), &mut inputs)?;
+        }
 
         Ok(Self {
             resolution,
+            target,
             resolve,
             world,
-            source_files,
+            inputs,
         })
     }
 
@@ -70,6 +141,23 @@ impl<'a> BindingsEncoder<'a> {
         self.resolution.metadata
     }
 
+    /// Gets the resolve of the target world.
+    pub fn resolve(&self) -> &Resolve {
+        &self.resolve
+    }
+
+    /// Gets the id of the target world.
+    pub fn world(&self) -> WorldId {
+        self.world
+    }
+
+    /// Gets the paths of the files the target world is created from.
+    ///
+    /// Directories are not included, only the WIT files within them.
+    pub fn inputs(&self) -> &BTreeSet<PathBuf> {
+        &self.inputs
+    }
+
     /// Gets the reason for generating the bindings.
     ///
     /// If this returns `Ok(None)`, then the bindings are up-to-date and
@@ -77,7 +165,7 @@ impl<'a> BindingsEncoder<'a> {
     pub fn reason(&self, last_modified_output: SystemTime) -> Result<Option<&'static str>> {
         let metadata = self.metadata();
         let manifest_modified = metadata.modified_at > last_modified_output;
-        let target_modified = if let Some(path) = metadata.target_path() {
+        let target_modified = if let Some(path) = metadata.local_target_path(self.target) {
             last_modified_time(&path)? > last_modified_output
         } else {
             false
@@ -128,21 +216,11 @@ impl<'a> BindingsEncoder<'a> {
     }
 
     fn dependencies_are_newer(&self, last_modified_output: SystemTime) -> Result<bool> {
-        for dep in &self.source_files {
-            if last_modified_time(dep)? > last_modified_output {
+        for input in &self.inputs {
+            if last_modified_time(input)? > last_modified_output {
                 log::debug!(
-                    "target source file `{path}` has been modified",
-                    path = dep.display()
-                );
-                return Ok(true);
-            }
-        }
-
-        for (_, dep) in self.resolution.all() {
-            if last_modified_time(dep.path())? > last_modified_output {
-                log::debug!(
-                    "dependency `{path}` has been modified",
-                    path = dep.path().display()
+                    "target input file `{path}` has been modified",
+                    path = input.display()
                 );
                 return Ok(true);
             }
@@ -153,53 +231,94 @@ impl<'a> BindingsEncoder<'a> {
 
     fn create_target_world(
         resolution: &PackageDependencyResolution,
+        target: &Target,
+        target_resolutions: &DependencyResolutionMap,
+        features: &HashSet<String>,
     ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
-        let (mut merged, world_id, source_files) =
-            if let Target::Package { id, world, .. } = &resolution.metadata.section.target {
-                Self::target_package(resolution, id, world.as_deref())?
-            } else if let Some(path) = resolution.metadata.target_path() {
-                Self::target_local_path(
-                    resolution,
-                    &path,
-                    resolution.metadata.section.target.world(),
-                )?
-            } else {
-                let (merged, world) = Self::target_empty_world(resolution);
-                (merged, world, Vec::new())
-            };
+        // The WIT files of the `generate` command are merged into local targets
+        let mut include = target.include().to_vec();
+        if let Some(dir) = Self::generated_dir(resolution.metadata, target) {
+            include.push(dir.to_path_buf());
+        }
+
+        let (mut merged, world_id, source_files) = if let Target::Package { id, world, .. } = target
+        {
+            Self::target_package(target_resolutions, id, world.as_deref())?
+        } else if let Some(path) = resolution.metadata.local_target_path(target) {
+            Self::target_local_path(target_resolutions, Some(&path), &include, target.world())?
+        } else if !include.is_empty() {
+            Self::target_local_path(target_resolutions, None, &include, target.world())?
+        } else {
+            let (merged, world) = Self::target_empty_world(resolution);
+            (merged, world, Vec::new())
+        };
 
-        // Merge all component dependencies as interface imports
+        // Include the worlds enabled by the active features
+        for (feature, name) in target.included_worlds(features) {
+            Self::include_world(&mut merged, world_id, name).with_context(|| {
+                format!("failed to include world `{name}` enabled by feature `{feature}`")
+            })?;
+        }
+
+        // Merge all enabled component dependencies as interface imports
         for (id, dependency) in &resolution.resolutions {
+            if !resolution.metadata.is_dependency_enabled(id, features) {
+                log::debug!("skipping optional component dependency `{id}` as it is disabled");
+                continue;
+            }
+
             let (mut resolve, component_world_id) = dependency
                 .decode()?
                 .into_component_world()
                 .with_context(|| format!("failed to decode component dependency `{id}`"))?;
 
             // Set the world name as currently it defaults to "root"
-            // For now, set it to the name from the id
+            // For now, set it to the module name of the dependency
             let world = &mut resolve.worlds[component_world_id];
-            world.name = id.name().to_string();
+            world.name = resolution.metadata.dependency_module(id).to_string();
+
+            let renames = resolution
+                .metadata
+                .section
+                .dependency_names
+                .get(id)
+                .map(|n| n.interfaces.clone())
+                .unwrap_or_default();
 
             let source = merged
                 .merge(resolve)
                 .with_context(|| format!("failed to merge world of dependency `{id}`"))?
                 .worlds[component_world_id.index()];
-            Self::import_world(&mut merged, source, world_id)?;
+            Self::import_world(&mut merged, source, world_id, &renames)
+                .with_context(|| format!("failed to import component dependency `{id}`"))?;
         }
 
+        instances::apply(&mut merged, world_id, target.instances())?;
+        transitive::apply(&mut merged, world_id, target.transitive())?;
+
         Ok((merged, world_id, source_files))
     }
 
+    /// Gets the directory of the generated WIT files to merge into a target.
+    ///
+    /// Returns `None` for a target package or if no files were generated.
+    fn generated_dir<'b>(metadata: &'b ComponentMetadata, target: &Target) -> Option<&'b Path> {
+        match target {
+            Target::Local { .. } => metadata.generated_dir.as_deref().filter(|dir| dir.is_dir()),
+            Target::Package { .. } => None,
+        }
+    }
+
     fn target_package(
-        resolution: &PackageDependencyResolution,
+        target_resolutions: &DependencyResolutionMap,
         id: &PackageId,
         world: Option<&str>,
     ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
         // We must have resolved a target package dependency at this point
-        assert_eq!(resolution.target_resolutions.len(), 1);
+        assert_eq!(target_resolutions.len(), 1);
 
         // Decode the target package dependency
-        let dependency = resolution.target_resolutions.values().next().unwrap();
+        let dependency = target_resolutions.values().next().unwrap();
         let (resolve, pkg, source_files) = dependency.decode()?.resolve().with_context(|| {
             format!(
                 "failed to resolve target package `{id}`",
@@ -215,15 +334,16 @@ impl<'a> BindingsEncoder<'a> {
     }
 
     fn target_local_path(
-        resolution: &PackageDependencyResolution,
-        path: &Path,
+        target_resolutions: &DependencyResolutionMap,
+        path: Option<&Path>,
+        include: &[PathBuf],
         world: Option<&str>,
     ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
         let mut merged = Resolve::default();
 
         // Start by decoding all of the target dependencies
         let mut deps = IndexMap::new();
-        for (id, resolution) in &resolution.target_resolutions {
+        for (id, resolution) in target_resolutions {
             let decoded = resolution.decode()?;
             let name = decoded.package_name();
 
@@ -232,23 +352,31 @@ impl<'a> BindingsEncoder<'a> {
             }
         }
 
-        // Parse the target package itself
-        let root = if path.is_dir() {
-            UnresolvedPackage::parse_dir(path).with_context(|| {
-                format!(
-                    "failed to parse local target from directory `{}`",
-                    path.display()
-                )
-            })?
-        } else {
-            UnresolvedPackage::parse_file(path).with_context(|| {
+        // Parse the target package itself, merging the files of its includes
+        let root = match path {
+            _ if !include.is_empty() => sources::parse(&sources::collect(path, include)?)?,
+            Some(path) if path.is_dir() => {
+                UnresolvedPackage::parse_dir(path).with_context(|| {
+                    format!(
+                        "failed to parse local target from directory `{}`",
+                        path.display()
+                    )
+                })?
+            }
+            Some(path) => UnresolvedPackage::parse_file(path).with_context(|| {
                 format!(
                     "failed to parse local target `{path}`",
                     path = path.display()
                 )
-            })?
+            })?,
+            None => bail!("a local target must have a path or includes"),
         };
 
+        // A target without a path is described by its first include
+        let path = path
+            .or_else(|| include.first().map(PathBuf::as_path))
+            .expect("a local target must have a path or includes");
+
         let mut source_files: Vec<_> = root.source_files().map(Path::to_path_buf).collect();
 
         // Do a topological sort of the dependencies
@@ -393,11 +521,92 @@ impl<'a> BindingsEncoder<'a> {
         (resolve, world)
     }
 
+    // This function includes a world from the package of the target world in
+    // the target world, as if by an `include` statement in the target world.
+    //
+    // This is used for the parts of the target world enabled by cargo features.
+    fn include_world(resolve: &mut Resolve, target: WorldId, name: &str) -> Result<()> {
+        let package = resolve.worlds[target]
+            .package
+            .context("target world has no package")?;
+        let source = *resolve.packages[package]
+            .worlds
+            .get(name)
+            .with_context(|| {
+                format!(
+                    "package `{package}` does not contain a world named `{name}`",
+                    package = resolve.packages[package].name
+                )
+            })?;
+
+        if source == target {
+            bail!("world `{name}` cannot include itself");
+        }
+
+        let (imports, exports) = {
+            let source = &resolve.worlds[source];
+            (source.imports.clone(), source.exports.clone())
+        };
+
+        // Check for conflicting items before modifying the target world
+        for (existing, included, kind) in [
+            (&resolve.worlds[target].imports, &imports, "import"),
+            (&resolve.worlds[target].exports, &exports, "export"),
+        ] {
+            for (key, item) in included {
+                let conflicts = match (existing.get(key), item) {
+                    (None, _) => false,
+                    (Some(WorldItem::Interface(a)), WorldItem::Interface(b)) => a != b,
+                    (Some(WorldItem::Type(a)), WorldItem::Type(b)) => a != b,
+                    (Some(WorldItem::Function(a)), WorldItem::Function(b)) => a.name != b.name,
+                    (Some(_), _) => true,
+                };
+
+                if conflicts {
+                    let name = match key {
+                        WorldKey::Name(name) => name.clone(),
+                        WorldKey::Interface(id) => {
+                            let iface = &resolve.interfaces[*id];
+                            match (iface.package, &iface.name) {
+                                (Some(package), Some(name)) => {
+                                    resolve.packages[package].name.interface_id(name)
+                                }
+                                _ => "<unnamed>".to_string(),
+                            }
+                        }
+                    };
+                    bail!("{kind} `{name}` conflicts with an {kind} in the target world");
+                }
+            }
+        }
+
+        let target = &mut resolve.worlds[target];
+        for (key, item) in imports {
+            target.imports.entry(key).or_insert(item);
+        }
+
+        for (key, item) in exports {
+            target.exports.entry(key).or_insert(item);
+        }
+
+        target.includes.push(source);
+        target.include_names.push(Vec::new());
+        Ok(())
+    }
+
     // This function imports in the target world the exports of the source world.
     //
     // This is used for dependencies on other components so that their exports may
     // be imported by the component being built.
-    fn import_world(resolve: &mut Resolve, source: WorldId, target: WorldId) -> Result<()> {
+    //
+    // Exported interfaces are imported with the name of the source world as a
+    // prefix; `renames` maps exported interface names to the names to use instead.
+    fn import_world(
+        resolve: &mut Resolve,
+        source: WorldId,
+        target: WorldId,
+        renames: &HashMap<String, String>,
+    ) -> Result<()> {
         let mut types = IndexMap::default();
         let mut functions = IndexMap::default();
         let mut interfaces = IndexMap::new();
@@ -407,6 +616,22 @@ impl<'a> BindingsEncoder<'a> {
             let source = &resolve.worlds[source];
             name = source.name.clone();
 
+            for interface in renames.keys() {
+                let exported = source.exports.iter().any(|(key, item)| {
+                    matches!(item, WorldItem::Interface(_))
+                        && match key {
+                            WorldKey::Name(n) => n == interface,
+                            WorldKey::Interface(id) => {
+                                resolve.interfaces[*id].name.as_deref() == Some(interface)
+                            }
+                        }
+                });
+
+                if !exported {
+                    bail!("the dependency does not export an interface named `{interface}`");
+                }
+            }
+
             // Check for imported types, which must also import any owning interfaces
             for item in source.imports.values() {
                 if let WorldItem::Type(ty) = &item {
@@ -425,7 +650,7 @@ impl<'a> BindingsEncoder<'a> {
                         functions.insert(key.clone().unwrap_name(), f.clone());
                     }
                     WorldItem::Interface(i) => {
-                        interfaces.insert(named_world_key(resolve, key, &name), *i);
+                        interfaces.insert(named_world_key(resolve, key, &name, renames), *i);
                     }
                     WorldItem::Type(t) => {
                         types.insert(key.clone().unwrap_name(), *t);
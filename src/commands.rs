@@ -1,13 +1,61 @@
 //! Commands for the `cargo-component` CLI.
 
 mod add;
+mod audit;
+mod bench;
+mod bindgen;
+mod bindings;
+mod cache;
+mod check_target;
+mod clean;
+mod compose;
+mod explain;
+mod info;
+mod inspect;
+mod install;
 mod key;
+mod login;
+mod logout;
+mod metadata;
+mod migrate;
+mod mock;
 mod new;
 mod publish;
+mod search;
+mod serve;
+mod stub;
 mod update;
+mod vendor;
+mod verify;
+mod why;
+mod yank;
 
 pub use self::add::*;
+pub use self::audit::*;
+pub use self::bench::*;
+pub use self::bindgen::*;
+pub use self::bindings::*;
+pub use self::cache::*;
+pub use self::check_target::*;
+pub use self::clean::*;
+pub use self::compose::*;
+pub use self::explain::*;
+pub use self::info::*;
+pub use self::inspect::*;
+pub use self::install::*;
 pub use self::key::*;
+pub use self::login::*;
+pub use self::logout::*;
+pub use self::metadata::*;
+pub use self::migrate::*;
+pub use self::mock::*;
 pub use self::new::*;
 pub use self::publish::*;
+pub use self::search::*;
+pub use self::serve::*;
+pub use self::stub::*;
 pub use self::update::*;
+pub use self::vendor::*;
+pub use self::verify::*;
+pub use self::why::*;
+pub use self::yank::*;
@@ -1,16 +1,22 @@
+use super::new::escape_wit;
 use crate::{
     config::CargoPackageSpec,
-    load_component_metadata, load_metadata,
+    generate_bindings, load_component_metadata, load_metadata,
+    lock::{acquire_lock_file_ro, acquire_lock_file_rw},
     metadata::{ComponentMetadata, Target},
-    Config, PackageComponentMetadata,
+    BuildOptions, Config, PackageComponentMetadata,
 };
 use anyhow::{bail, Context, Result};
 use cargo_component_core::{
     command::CommonOptions,
-    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryPackage},
+    lock::{LockFile, LockFileResolver},
+    registry::{
+        DecodedDependency, Dependency, DependencyResolution, DependencyResolver, LocalResolution,
+        RegistryPackage, DEFAULT_REGISTRY_NAME,
+    },
     VersionedPackageId,
 };
-use cargo_metadata::Package;
+use cargo_metadata::{Metadata, Package};
 use clap::Args;
 use semver::VersionReq;
 use std::{
@@ -19,6 +25,8 @@ use std::{
 };
 use toml_edit::{value, Document, InlineTable, Item, Table, Value};
 use warg_protocol::registry::PackageId;
+use wit_component::DecodedWasm;
+use wit_parser::UnresolvedPackage;
 
 /// Add a dependency for a WebAssembly component
 #[derive(Args)]
@@ -52,30 +60,51 @@ pub struct AddCommand {
     #[clap(value_name = "PACKAGE")]
     pub package: VersionedPackageId,
 
-    /// Add the dependency to the list of target dependencies
+    /// Add the dependency to the list of target dependencies and merge its
+    /// interfaces into the target world
     #[clap(long = "target")]
     pub target: bool,
 
+    /// Export the merged interfaces from the target world instead of importing them
+    #[clap(long = "export", requires = "target")]
+    pub export: bool,
+
     /// Add a package dependency to a file or directory.
     #[clap(long = "path", value_name = "PATH")]
     pub path: Option<PathBuf>,
+
+    /// Allow selecting a pre-release version of the package
+    ///
+    /// Without this, pre-release versions are only selected when the version
+    /// requirement includes a pre-release (e.g. `my:pkg@0.3.0-rc1`).
+    #[clap(long = "pre", conflicts_with = "path")]
+    pub pre: bool,
+
+    /// Require the dependency to already be in the lock file
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Require the dependency to already be in the lock file and don't access the network
+    #[clap(long = "frozen")]
+    pub frozen: bool,
 }
 
 impl AddCommand {
     /// Executes the command
     pub async fn exec(self) -> Result<()> {
         let config = Config::new(self.common.new_terminal())?;
-        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let workspace = load_metadata(self.manifest_path.as_deref())?;
 
         let PackageComponentMetadata { package, metadata }: PackageComponentMetadata<'_> =
             match &self.spec {
                 Some(spec) => {
-                    let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                    let pkgs = load_component_metadata(&workspace, std::iter::once(spec), false)?;
                     assert!(pkgs.len() == 1, "one package should be present");
                     pkgs.into_iter().next().unwrap()
                 }
                 None => PackageComponentMetadata::new(
-                    metadata
+                    &workspace,
+                    workspace
                         .root_package()
                         .context("no root package found in metadata")?,
                 )?,
@@ -95,7 +124,7 @@ impl AddCommand {
 
         self.validate(&metadata, id)?;
 
-        if let Some(path) = self.path.as_ref() {
+        let resolution = if let Some(path) = self.path.as_ref() {
             self.add_from_path(package, path)?;
 
             config.terminal().status(
@@ -105,34 +134,228 @@ impl AddCommand {
                     path = path.to_str().unwrap()
                 ),
             )?;
+
+            DependencyResolution::Local(LocalResolution {
+                id: id.clone(),
+                path: package
+                    .manifest_path
+                    .parent()
+                    .unwrap()
+                    .as_std_path()
+                    .join(path),
+            })
         } else {
-            let version = self.resolve_version(&config, &metadata, id, true).await?;
+            let (version, resolution) = self
+                .resolve_version(&config, &workspace, &metadata, id, !self.frozen)
+                .await?;
             let version = version.trim_start_matches('^');
+            if self.locked || self.frozen {
+                self.check_locked(&config, &workspace, &resolution, version)?;
+            }
             self.add(package, version)?;
 
             config.terminal().status(
                 "Added",
                 format!("dependency `{id}` with version `{version}`"),
             )?;
+
+            resolution
+        };
+
+        if !self.target {
+            return Ok(());
+        }
+
+        let merged = self.merge_into_target(&config, &metadata, &resolution)?;
+
+        if merged && !self.dry_run {
+            generate_bindings(
+                &config,
+                &BuildOptions {
+                    manifest_path: Some(package.manifest_path.clone().into()),
+                    packages: vec![CargoPackageSpec {
+                        name: package.name.clone(),
+                        version: Some(package.version.clone()),
+                    }],
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+            config.terminal().status(
+                "Generated",
+                format!("bindings for package `{name}`", name = package.name),
+            )?;
         }
 
         Ok(())
     }
 
+    /// Merges the interfaces of a target dependency into the local target world.
+    ///
+    /// Returns `false` if there was nothing to merge.
+    fn merge_into_target(
+        &self,
+        config: &Config,
+        metadata: &ComponentMetadata,
+        resolution: &DependencyResolution,
+    ) -> Result<bool> {
+        let target = &metadata.section.target;
+        let path = match metadata.local_target_path(target) {
+            Some(path) => path,
+            None => return Ok(false),
+        };
+
+        let decoded = resolution.decode()?;
+        let name = decoded.package_name().clone();
+        let interfaces: Vec<_> = match &decoded {
+            DecodedDependency::Wit { package, .. } => package
+                .interfaces
+                .iter()
+                .filter_map(|(_, i)| i.name.clone())
+                .collect(),
+            DecodedDependency::Wasm {
+                decoded: DecodedWasm::WitPackage(resolve, package),
+                ..
+            } => resolve.packages[*package]
+                .interfaces
+                .keys()
+                .cloned()
+                .collect(),
+            // Components do not define interfaces of their own to merge
+            DecodedDependency::Wasm { .. } => Vec::new(),
+        };
+
+        if interfaces.is_empty() {
+            return Ok(false);
+        }
+
+        let root = if path.is_dir() {
+            UnresolvedPackage::parse_dir(&path)
+        } else {
+            UnresolvedPackage::parse_file(&path)
+        }
+        .with_context(|| {
+            format!(
+                "failed to parse local target `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let world = match target.world() {
+            Some(world) => world.to_string(),
+            None => {
+                let mut worlds = root.worlds.iter().map(|(_, w)| &w.name);
+                match (worlds.next(), worlds.next()) {
+                    (Some(world), None) => world.clone(),
+                    _ => bail!(
+                        "cannot merge dependency `{id}` as local target `{path}` does not have exactly one world; specify the world to target in the manifest",
+                        id = resolution.id(),
+                        path = path.display()
+                    ),
+                }
+            }
+        };
+
+        let kind = if self.export { "export" } else { "import" };
+        let statements: Vec<_> = interfaces
+            .iter()
+            .map(|interface| {
+                format!(
+                    "{kind} {ns}:{pkg}/{interface}{version}",
+                    ns = escape_wit(&name.namespace),
+                    pkg = escape_wit(&name.name),
+                    interface = escape_wit(interface),
+                    version = name
+                        .version
+                        .as_ref()
+                        .map(|v| format!("@{v}"))
+                        .unwrap_or_default()
+                )
+            })
+            .collect();
+
+        for file in root.source_files() {
+            let source = fs::read_to_string(file).with_context(|| {
+                format!("failed to read WIT file `{path}`", path = file.display())
+            })?;
+
+            let mut lines: Vec<_> = source.lines().collect();
+            let index = match lines.iter().position(|line| {
+                line.trim_start()
+                    .strip_prefix("world ")
+                    .map(|rest| {
+                        let rest = rest.trim_start();
+                        let rest = rest.strip_prefix('%').unwrap_or(rest);
+                        let end = rest
+                            .find(|c: char| c.is_whitespace() || c == '{')
+                            .unwrap_or(rest.len());
+                        rest[..end] == world && rest[end..].trim_start().starts_with('{')
+                    })
+                    .unwrap_or(false)
+            }) {
+                Some(index) => index,
+                None => continue,
+            };
+
+            let added: Vec<_> = statements
+                .iter()
+                .filter(|s| !lines.iter().any(|l| l.trim() == s.as_str()))
+                .map(|s| format!("    {s}"))
+                .collect();
+
+            for (i, statement) in added.iter().enumerate() {
+                lines.insert(index + 1 + i, statement);
+            }
+
+            let mut merged = lines.join("\n");
+            merged.push('\n');
+
+            if self.dry_run {
+                println!("{merged}");
+            } else {
+                fs::write(file, merged).with_context(|| {
+                    format!("failed to write WIT file `{path}`", path = file.display())
+                })?;
+            }
+
+            for statement in &added {
+                config.terminal().status(
+                    "Merged",
+                    format!(
+                        "`{statement}` into world `{world}`",
+                        statement = statement.trim()
+                    ),
+                )?;
+            }
+
+            return Ok(!added.is_empty());
+        }
+
+        bail!(
+            "failed to find the definition of world `{world}` in local target `{path}`",
+            path = path.display()
+        )
+    }
+
     async fn resolve_version(
         &self,
         config: &Config,
+        workspace: &Metadata,
         metadata: &ComponentMetadata,
         id: &PackageId,
         network_allowed: bool,
-    ) -> Result<String> {
+    ) -> Result<(String, DependencyResolution)> {
+        let lock_file = read_lock_file(config, workspace)?;
         let mut resolver = DependencyResolver::new(
             config.warg(),
             &metadata.section.registries,
-            None,
+            lock_file.as_ref().map(LockFileResolver::new),
             config.terminal(),
             network_allowed,
-        )?;
+        )?
+        .with_namespaces(&metadata.section.namespaces)
+        .with_pre_releases(self.pre);
         let dependency = Dependency::Package(RegistryPackage {
             id: Some(self.package.id.clone()),
             version: self
@@ -142,6 +365,7 @@ impl AddCommand {
                 .unwrap_or(&VersionReq::STAR)
                 .clone(),
             registry: self.registry.clone(),
+            interfaces: Vec::new(),
         });
 
         resolver.add_dependency(id, &dependency).await?;
@@ -149,17 +373,59 @@ impl AddCommand {
         let dependencies = resolver.resolve().await?;
         assert_eq!(dependencies.len(), 1);
 
-        match dependencies.values().next().expect("expected a resolution") {
-            DependencyResolution::Registry(resolution) => Ok(self
-                .package
-                .version
-                .as_ref()
-                .map(ToString::to_string)
-                .unwrap_or_else(|| resolution.version.to_string())),
+        let resolution = dependencies
+            .into_values()
+            .next()
+            .expect("expected a resolution");
+        match &resolution {
+            DependencyResolution::Registry(registry) => Ok((
+                self.package
+                    .version
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| registry.version.to_string()),
+                resolution,
+            )),
             _ => unreachable!(),
         }
     }
 
+    /// Ensures the lock file would not need to be updated for the added dependency.
+    fn check_locked(
+        &self,
+        config: &Config,
+        workspace: &Metadata,
+        resolution: &DependencyResolution,
+        version: &str,
+    ) -> Result<()> {
+        let resolution = match resolution {
+            DependencyResolution::Registry(resolution) => resolution,
+            _ => return Ok(()),
+        };
+
+        let requirement: VersionReq = version
+            .parse()
+            .with_context(|| format!("invalid version requirement `{version}`"))?;
+
+        let lock_file = read_lock_file(config, workspace)?;
+        let locked = match &lock_file {
+            Some(lock_file) => LockFileResolver::new(lock_file).resolve(
+                resolution
+                    .registry
+                    .as_deref()
+                    .unwrap_or(DEFAULT_REGISTRY_NAME),
+                &resolution.package,
+                &requirement,
+            )?,
+            None => None,
+        };
+
+        match locked {
+            Some(locked) if locked.digest == resolution.digest => Ok(()),
+            _ => acquire_lock_file_rw(config.terminal(), workspace, false, self.locked).map(|_| ()),
+        }
+    }
+
     fn with_dependencies<F>(&self, pkg: &Package, body: F) -> Result<()>
     where
         F: FnOnce(&mut Table) -> Result<()>,
@@ -217,17 +483,27 @@ impl AddCommand {
 
     fn add(&self, pkg: &Package, version: &str) -> Result<()> {
         self.with_dependencies(pkg, |dependencies| {
-            match self.id.as_ref() {
-                Some(id) => {
-                    dependencies[id.as_ref()] = value(InlineTable::from_iter([
-                        ("package", Value::from(self.package.id.to_string())),
-                        ("version", Value::from(version)),
-                    ]));
-                }
-                _ => {
-                    dependencies[self.package.id.as_ref()] = value(version);
-                }
+            let key = match self.id.as_ref() {
+                Some(id) => id.as_ref(),
+                None => self.package.id.as_ref(),
+            };
+
+            // The shorthand form can only be used for the default registry
+            if self.id.is_none() && self.registry.is_none() {
+                dependencies[key] = value(version);
+                return Ok(());
+            }
+
+            let mut entry = InlineTable::new();
+            if self.id.is_some() {
+                entry.insert("package", Value::from(self.package.id.to_string()));
             }
+            entry.insert("version", Value::from(version));
+            if let Some(registry) = &self.registry {
+                entry.insert("registry", Value::from(registry.as_str()));
+            }
+
+            dependencies[key] = value(entry);
             Ok(())
         })
     }
@@ -267,3 +543,17 @@ impl AddCommand {
         Ok(())
     }
 }
+
+/// Reads the lock file of the workspace, if there is one.
+fn read_lock_file(config: &Config, workspace: &Metadata) -> Result<Option<LockFile>> {
+    acquire_lock_file_ro(config.terminal(), workspace)?
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
+                )
+            })
+        })
+        .transpose()
+}
@@ -0,0 +1,95 @@
+use crate::{audit_dependencies, load_component_metadata, load_metadata, Config};
+use anyhow::{bail, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// The environment variable used to specify the advisory database.
+const ADVISORY_DB_ENV_VAR: &str = "CARGO_COMPONENT_ADVISORY_DB";
+
+/// Audit the locked registry dependencies for advisories and yanked releases
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct AuditCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// The path or git repository URL of the advisory database
+    #[clap(long = "db", value_name = "PATH_OR_URL")]
+    pub db: Option<String>,
+
+    /// Treat yanked and unpublished releases as errors
+    #[clap(long = "deny-warnings")]
+    pub deny_warnings: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+}
+
+impl AuditCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing audit command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(&metadata, [].iter(), true)?;
+
+        let db = self
+            .db
+            .clone()
+            .or_else(|| std::env::var(ADVISORY_DB_ENV_VAR).ok());
+        if db.is_none() {
+            config.terminal().note(format!(
+                "no advisory database was specified with `--db` or `{ADVISORY_DB_ENV_VAR}`; only checking for yanked releases"
+            ))?;
+        }
+
+        let findings =
+            match audit_dependencies(&config, &metadata, &packages, db.as_deref(), !self.offline)
+                .await?
+            {
+                Some(findings) => findings,
+                None => {
+                    config.terminal().note(
+                        "the workspace has no component lock file, so there is nothing to audit",
+                    )?;
+                    return Ok(());
+                }
+            };
+
+        let mut errors = 0;
+        for finding in &findings {
+            config
+                .terminal()
+                .event("audit-finding", finding.to_json())?;
+
+            if finding.is_error() || self.deny_warnings {
+                config.terminal().error(finding)?;
+                errors += 1;
+            } else {
+                config.terminal().warn(finding)?;
+            }
+        }
+
+        if errors > 0 {
+            bail!("found {errors} problem(s) with the locked component dependencies");
+        }
+
+        config.terminal().status(
+            "Audited",
+            match findings.len() {
+                0 => "locked component dependencies".to_string(),
+                warnings => format!("locked component dependencies with {warnings} warning(s)"),
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    bench::{read_results, BenchOptions, BENCH_ENV_VAR},
+    config::{CargoArguments, CargoPackageSpec, Config},
+    load_component_metadata, load_metadata, run_cargo_command,
+};
+use anyhow::{Context, Result};
+use cargo_component_core::{command::CommonOptions, terminal::MessageFormat};
+use clap::Args;
+use std::{fs, path::PathBuf};
+
+/// Build benchmark targets as components and time their runs
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct BenchCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Benchmark only the specified bench target
+    #[clap(long = "bench", value_name = "NAME")]
+    pub benches: Vec<String>,
+
+    /// The number of timed runs of each benchmark
+    #[clap(long = "iterations", value_name = "N", default_value = "10")]
+    pub iterations: usize,
+
+    /// The number of untimed runs of each benchmark before the timed runs
+    #[clap(long = "warm-up", value_name = "N", default_value = "1")]
+    pub warm_up: usize,
+
+    /// Trap runs that consume more than the given amount of fuel
+    #[clap(long = "fuel", value_name = "N")]
+    pub fuel: Option<u64>,
+
+    /// Interrupt runs that take longer than the given duration (e.g. `10s`)
+    #[clap(long = "timeout", value_name = "DURATION")]
+    pub timeout: Option<String>,
+
+    /// Package to benchmark (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub cargo_package: Option<CargoPackageSpec>,
+
+    /// Benchmark all packages in the workspace
+    #[clap(long = "workspace")]
+    pub workspace: bool,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Arguments for the benchmark binaries
+    #[clap(last = true, value_name = "ARGS")]
+    pub args: Vec<String>,
+}
+
+impl BenchCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing bench command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages =
+            load_component_metadata(&metadata, self.cargo_package.iter(), self.workspace)?;
+
+        let cargo_args = CargoArguments {
+            color: self.common.color,
+            verbose: self.common.verbose as usize,
+            quiet: self.common.quiet,
+            targets: Vec::new(),
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            release: false,
+            profile: Some("bench".to_string()),
+            offline: self.offline,
+            workspace: self.workspace,
+            packages: self.cargo_package.clone().into_iter().collect(),
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            bins: Vec::new(),
+            all_bins: false,
+        };
+
+        // The results of the previous run are the baseline of this run
+        let results_dir = metadata
+            .target_directory
+            .join("component-bench")
+            .into_std_path_buf();
+        let previous = read_results(&results_dir)?;
+        if results_dir.exists() {
+            fs::remove_dir_all(&results_dir).with_context(|| {
+                format!(
+                    "failed to remove benchmark results directory `{dir}`",
+                    dir = results_dir.display()
+                )
+            })?;
+        }
+
+        let options = BenchOptions {
+            iterations: self.iterations,
+            warm_up: self.warm_up,
+            fuel: self.fuel,
+            timeout: self.timeout.clone(),
+            results_dir: results_dir.clone(),
+        };
+
+        // The target runner spawned by cargo reads the options from the environment
+        std::env::set_var(BENCH_ENV_VAR, serde_json::to_string(&options)?);
+
+        run_cargo_command(
+            &config,
+            &metadata,
+            &packages,
+            Some("bench"),
+            &cargo_args,
+            &self.bench_args()?,
+        )
+        .await?;
+
+        let mut results: Vec<_> = read_results(&results_dir)?.into_values().collect();
+        results.sort_by(|a, b| (&a.package, &a.name).cmp(&(&b.package, &b.name)));
+        for result in &mut results {
+            if let Some(previous) = previous.get(&(result.package.clone(), result.name.clone())) {
+                result.compare(previous);
+            }
+        }
+
+        match config.terminal().message_format() {
+            MessageFormat::Json => println!("{}", serde_json::to_string(&results)?),
+            MessageFormat::Human => {
+                if results.is_empty() {
+                    config.terminal().warn("no benchmarks were run")?;
+                }
+
+                for result in &results {
+                    println!("{result}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bench_args(&self) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        args.push("bench".to_string());
+
+        if self.common.quiet {
+            args.push("-q".to_string());
+        }
+
+        args.extend(
+            std::iter::repeat("-v")
+                .take(self.common.verbose as usize)
+                .map(ToString::to_string),
+        );
+
+        if let Some(color) = self.common.color {
+            args.push("--color".to_string());
+            args.push(color.to_string());
+        }
+
+        for bench in &self.benches {
+            args.push("--bench".to_string());
+            args.push(bench.clone());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        if let Some(spec) = &self.cargo_package {
+            args.push("--package".to_string());
+            args.push(spec.to_string());
+        }
+
+        if self.workspace {
+            args.push("--workspace".to_string());
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(
+                manifest_path
+                    .as_os_str()
+                    .to_str()
+                    .with_context(|| {
+                        format!(
+                            "manifest path `{path}` is not valid UTF-8",
+                            path = manifest_path.display()
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        if self.offline {
+            args.push("--offline".to_string());
+        }
+
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+
+        if !self.args.is_empty() {
+            args.push("--".to_string());
+            args.extend(self.args.iter().cloned());
+        }
+
+        Ok(args)
+    }
+}
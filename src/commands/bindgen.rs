@@ -0,0 +1,139 @@
+use crate::{
+    config::{CargoArguments, CargoPackageSpec},
+    generate_component_language_bindings, generate_language_bindings, load_component_metadata,
+    load_metadata, Config, Language, PackageComponentMetadata,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Generate bindings for other languages from a component's target world
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct BindgenCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The language to generate bindings for (c or go)
+    #[clap(long = "lang", value_name = "LANG")]
+    pub language: Language,
+
+    /// The directory to write the generated bindings to
+    #[clap(long = "out-dir", value_name = "DIR")]
+    pub out_dir: PathBuf,
+
+    /// Generate bindings from the world of a built component instead of the
+    /// package's target world
+    #[clap(long = "component", value_name = "PATH", conflicts_with_all = ["spec", "target"])]
+    pub component: Option<PathBuf>,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to generate bindings for (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The name of the target to generate bindings for; defaults to the
+    /// package's target
+    #[clap(value_name = "TARGET")]
+    pub target: Option<String>,
+}
+
+impl BindgenCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing bindgen command");
+
+        let config = Config::new(self.common.new_terminal())?;
+
+        let paths = match &self.component {
+            Some(component) => {
+                if !component.is_file() {
+                    bail!(
+                        "component `{path}` does not exist",
+                        path = component.display()
+                    );
+                }
+
+                generate_component_language_bindings(component, self.language, &self.out_dir)?
+            }
+            None => {
+                let metadata = load_metadata(self.manifest_path.as_deref())?;
+                let package = match &self.spec {
+                    Some(spec) => {
+                        let pkgs =
+                            load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                        assert!(pkgs.len() == 1, "one package should be present");
+                        pkgs.into_iter().next().unwrap()
+                    }
+                    None => PackageComponentMetadata::new(
+                        &metadata,
+                        metadata
+                            .root_package()
+                            .context("no root package found in metadata")?,
+                    )?,
+                };
+
+                let cargo_args = CargoArguments {
+                    manifest_path: self.manifest_path.clone(),
+                    frozen: self.frozen,
+                    offline: self.offline,
+                    features: self.features.clone(),
+                    all_features: self.all_features,
+                    no_default_features: self.no_default_features,
+                    ..Default::default()
+                };
+
+                generate_language_bindings(
+                    &config,
+                    &metadata,
+                    &package,
+                    self.target.as_deref(),
+                    &cargo_args,
+                    self.language,
+                    &self.out_dir,
+                )
+                .await?
+            }
+        };
+
+        for path in &paths {
+            log::debug!("generated bindings file `{path}`", path = path.display());
+        }
+
+        config.terminal().status(
+            "Generated",
+            format!(
+                "{lang} bindings in `{path}`",
+                lang = self.language,
+                path = self.out_dir.display()
+            ),
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{
+    config::{CargoArguments, CargoPackageSpec},
+    emit, emit_bindings, emit_host_crate, load_component_metadata, load_metadata, Config,
+    PackageComponentMetadata,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::{fs, path::PathBuf};
+
+/// Emit the Rust bindings of a component's target world as a source file
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct BindingsCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The path of the source file to write the bindings to (e.g. `src/bindings.rs`)
+    #[clap(long = "emit", value_name = "PATH")]
+    pub emit: PathBuf,
+
+    /// The directory to also generate a host crate in, which uses `wasmtime`
+    /// to generate host bindings for the target world (e.g. `host`)
+    #[clap(long = "host", value_name = "DIR")]
+    pub host: Option<PathBuf>,
+
+    /// Check that the source file (and host crate) is up to date instead of writing it
+    #[clap(long = "check")]
+    pub check: bool,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to emit the bindings of (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The name of the target to emit the bindings of; defaults to the
+    /// package's target
+    #[clap(value_name = "TARGET")]
+    pub target: Option<String>,
+}
+
+impl BindingsCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing bindings command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let cargo_args = CargoArguments {
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            offline: self.offline,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            ..Default::default()
+        };
+
+        let command = self.regenerate_command();
+        let source = emit_bindings(
+            &config,
+            &metadata,
+            &package,
+            self.target.as_deref(),
+            &cargo_args,
+            &command,
+        )
+        .await?;
+
+        let mut files = vec![(self.emit.clone(), source)];
+        if let Some(dir) = &self.host {
+            let host = emit_host_crate(
+                &config,
+                &metadata,
+                &package,
+                self.target.as_deref(),
+                &cargo_args,
+                &command,
+            )
+            .await?;
+            files.extend(
+                host.into_iter()
+                    .map(|(path, source)| (dir.join(path), source)),
+            );
+        }
+
+        if self.check {
+            for (path, source) in &files {
+                let existing = match fs::read_to_string(path).ok() {
+                    Some(existing) => existing,
+                    None => bail!(
+                        "bindings file `{path}` does not exist; run `{command}` to generate it",
+                        path = path.display()
+                    ),
+                };
+
+                if let Some((line, expected, found)) = emit::first_difference(source, &existing) {
+                    config.terminal().error(format!(
+                        "bindings file `{path}` differs from the generated bindings at line {line}:\n\
+                         expected: {expected}\n\
+                         found:    {found}",
+                        path = path.display(),
+                        expected = expected.unwrap_or("<end of file>"),
+                        found = found.unwrap_or("<end of file>"),
+                    ))?;
+
+                    bail!(
+                        "bindings file `{path}` is out of date; run `{command}` to regenerate it",
+                        path = path.display()
+                    );
+                }
+            }
+
+            config.terminal().status(
+                "Checked",
+                format!(
+                    "bindings file `{path}` is up to date",
+                    path = self.emit.display()
+                ),
+            )?;
+
+            if let Some(dir) = &self.host {
+                config.terminal().status(
+                    "Checked",
+                    format!("host crate `{dir}` is up to date", dir = dir.display()),
+                )?;
+            }
+
+            return Ok(());
+        }
+
+        for (path, source) in &files {
+            // Only write the file if it changed so that the crate is not needlessly rebuilt
+            if fs::read_to_string(path).ok().as_deref() == Some(source.as_str()) {
+                continue;
+            }
+
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!(
+                        "failed to create directory `{path}`",
+                        path = parent.display()
+                    )
+                })?;
+            }
+
+            fs::write(path, source).with_context(|| {
+                format!(
+                    "failed to write bindings file `{path}`",
+                    path = path.display()
+                )
+            })?;
+        }
+
+        config.terminal().status(
+            "Generated",
+            format!("Rust bindings in `{path}`", path = self.emit.display()),
+        )?;
+
+        if let Some(dir) = &self.host {
+            config.terminal().status(
+                "Generated",
+                format!("host crate in `{dir}`", dir = dir.display()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the command that regenerates the bindings file.
+    fn regenerate_command(&self) -> String {
+        let mut command = "cargo component bindings".to_string();
+        if let Some(spec) = &self.spec {
+            command.push_str(&format!(" -p {spec}"));
+        }
+
+        if let Some(target) = &self.target {
+            command.push_str(&format!(" {target}"));
+        }
+
+        command.push_str(&format!(" --emit {path}", path = self.emit.display()));
+
+        if let Some(dir) = &self.host {
+            command.push_str(&format!(" --host {dir}", dir = dir.display()));
+        }
+
+        command
+    }
+}
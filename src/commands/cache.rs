@@ -0,0 +1,117 @@
+use crate::config::Config;
+use anyhow::Result;
+use cargo_component_core::{
+    cache::{cache_dirs, clean, gc, Usage},
+    command::CommonOptions,
+};
+use clap::{Args, Subcommand};
+use std::time::Duration;
+
+/// Manage the cache of downloaded packages shared by every project.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct CacheCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The subcommand to execute.
+    #[clap(subcommand)]
+    pub command: CacheSubcommand,
+}
+
+impl CacheCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing cache command");
+
+        let config = Config::new(self.common.new_terminal())?;
+
+        match self.command {
+            CacheSubcommand::Dir(cmd) => cmd.exec(&config).await,
+            CacheSubcommand::Clean(cmd) => cmd.exec(&config).await,
+            CacheSubcommand::Gc(cmd) => cmd.exec(&config).await,
+        }
+    }
+}
+
+/// The subcommand to execute.
+#[derive(Subcommand)]
+pub enum CacheSubcommand {
+    /// Prints the cache directories.
+    Dir(CacheDirCommand),
+    /// Removes everything from the cache.
+    Clean(CacheCleanCommand),
+    /// Removes the cache entries that were not downloaded recently.
+    Gc(CacheGcCommand),
+}
+
+/// Prints the cache directories.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct CacheDirCommand {}
+
+impl CacheDirCommand {
+    /// Executes the command.
+    pub async fn exec(self, config: &Config) -> Result<()> {
+        for dir in cache_dirs(config.warg())? {
+            println!("{name} {path}", name = dir.name, path = dir.path.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes everything from the cache.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct CacheCleanCommand {}
+
+impl CacheCleanCommand {
+    /// Executes the command.
+    pub async fn exec(self, config: &Config) -> Result<()> {
+        let mut removed = Usage::default();
+        for dir in cache_dirs(config.warg())? {
+            removed.add(clean(&dir)?);
+        }
+
+        config.terminal().status("Removed", describe(&removed))?;
+
+        Ok(())
+    }
+}
+
+/// Removes the cache entries that were not downloaded recently.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct CacheGcCommand {
+    /// Remove the entries not downloaded in this many days
+    #[clap(long = "max-age", value_name = "DAYS", default_value = "30")]
+    pub max_age: u64,
+}
+
+impl CacheGcCommand {
+    /// Executes the command.
+    pub async fn exec(self, config: &Config) -> Result<()> {
+        let max_age = Duration::from_secs(self.max_age * 24 * 60 * 60);
+
+        let mut removed = Usage::default();
+        for dir in cache_dirs(config.warg())? {
+            removed.add(gc(&dir, max_age)?);
+        }
+
+        config.terminal().status("Removed", describe(&removed))?;
+
+        Ok(())
+    }
+}
+
+/// Describes the usage of what was removed from the cache.
+fn describe(usage: &Usage) -> String {
+    format!(
+        "{files} file{s} ({bytes} bytes) from the cache",
+        files = usage.files,
+        s = if usage.files == 1 { "" } else { "s" },
+        bytes = usage.bytes
+    )
+}
@@ -0,0 +1,278 @@
+use super::info::registry_config;
+use crate::{
+    compat::{self, Externs},
+    config::CargoPackageSpec,
+    load_component_metadata, load_metadata, Config, PackageComponentMetadata,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    http,
+    registry::{Dependency, DependencyResolver, RegistryPackage},
+    VersionedPackageId,
+};
+use clap::Args;
+use semver::VersionReq;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use wit_component::DecodedWasm;
+use wit_parser::{Resolve, UnresolvedPackage, WorldId};
+
+/// Check that a component is compatible with the world of a host
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct CheckTargetCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The world of the host to check against: a registry world (e.g.
+    /// `wasi:cli/command@0.2.0`), a URL, or the path to a WIT directory,
+    /// WIT file, WIT package, or component
+    #[clap(long = "host", value_name = "HOST")]
+    pub host: String,
+
+    /// The name of the world to use if the host's package has more than one
+    #[clap(long = "world", value_name = "WORLD")]
+    pub world: Option<String>,
+
+    /// The name of the registry to download the host's world from
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Check the component built in release mode
+    #[clap(long = "release", short = 'r')]
+    pub release: bool,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to check the component of (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The path to the component to check; defaults to the package's built
+    /// component
+    #[clap(value_name = "COMPONENT")]
+    pub component: Option<PathBuf>,
+}
+
+impl CheckTargetCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing check-target command");
+
+        let config = Config::new(self.common.new_terminal())?;
+
+        let path = match &self.component {
+            Some(path) => path.clone(),
+            None => self.built_component()?,
+        };
+
+        let bytes = fs::read(&path)
+            .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+        let component = Externs::from_component(&bytes)
+            .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+
+        let (resolve, world) = self.host_world(&config).await?;
+        let incompatibilities = compat::check(&component, &Externs::from_world(&resolve, world));
+        if !incompatibilities.is_empty() {
+            for incompatibility in &incompatibilities {
+                config.terminal().error(incompatibility.to_string())?;
+            }
+
+            bail!(
+                "component `{path}` is not compatible with host `{host}`",
+                path = path.display(),
+                host = self.host
+            );
+        }
+
+        config.terminal().status(
+            "Compatible",
+            format!(
+                "component `{path}` is compatible with host `{host}`",
+                path = path.display(),
+                host = self.host
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Gets the path of the component built for the selected package.
+    fn built_component(&self) -> Result<PathBuf> {
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let name = package
+            .package
+            .targets
+            .iter()
+            .find(|t| t.kind.iter().any(|k| k == "cdylib"))
+            .map(|t| t.name.replace('-', "_"))
+            .with_context(|| {
+                format!(
+                    "package `{name}` does not have a `cdylib` library target",
+                    name = package.package.name
+                )
+            })?;
+
+        let path = metadata
+            .target_directory
+            .join("wasm32-wasi")
+            .join(if self.release { "release" } else { "debug" })
+            .join(format!("{name}.wasm"))
+            .into_std_path_buf();
+
+        if !path.is_file() {
+            bail!(
+                "component `{path}` does not exist; run `cargo component build{release}` first",
+                path = path.display(),
+                release = if self.release { " --release" } else { "" }
+            );
+        }
+
+        Ok(path)
+    }
+
+    /// Gets the world of the host.
+    async fn host_world(&self, config: &Config) -> Result<(Resolve, WorldId)> {
+        let host = self.host.as_str();
+        if http::is_http_url(host) {
+            if self.offline {
+                bail!("cannot fetch host `{host}` when running offline");
+            }
+
+            let bytes = http::fetch(host).await?;
+            return self.decode_world(Path::new(host), &bytes);
+        }
+
+        let path = Path::new(host);
+        if path.is_dir() {
+            let mut resolve = Resolve::new();
+            let (pkg, _) = resolve.push_dir(path).with_context(|| {
+                format!(
+                    "failed to parse WIT directory `{path}`",
+                    path = path.display()
+                )
+            })?;
+            let world = resolve.select_world(pkg, self.world.as_deref())?;
+            return Ok((resolve, world));
+        }
+
+        if path.is_file() {
+            let bytes = fs::read(path)
+                .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+            return self.decode_world(path, &bytes);
+        }
+
+        // Otherwise, the host is a world from a registry (e.g. `wasi:cli/command@0.2.0`)
+        let (package, world) = match host.split_once('/') {
+            Some((package, rest)) => match rest.split_once('@') {
+                Some((world, version)) => (format!("{package}@{version}"), world),
+                None => (package.to_string(), rest),
+            },
+            None => bail!(
+                "host `{host}` is not a URL, an existing path, or a registry world (e.g. `wasi:cli/command`)"
+            ),
+        };
+
+        let package: VersionedPackageId = package
+            .parse()
+            .with_context(|| format!("invalid host `{host}`"))?;
+        self.registry_world(config, &package, world).await
+    }
+
+    /// Resolves a world of a package from a registry.
+    async fn registry_world(
+        &self,
+        config: &Config,
+        package: &VersionedPackageId,
+        world: &str,
+    ) -> Result<(Resolve, WorldId)> {
+        let (registries, namespaces) = registry_config(self.manifest_path.as_deref());
+
+        let id = &package.id;
+        let dependency = Dependency::Package(RegistryPackage {
+            id: Some(id.clone()),
+            version: package.version.clone().unwrap_or(VersionReq::STAR),
+            registry: self.registry.clone(),
+            interfaces: Vec::new(),
+        });
+
+        let mut resolver = DependencyResolver::new(
+            config.warg(),
+            &registries,
+            None,
+            config.terminal(),
+            !self.offline,
+        )?
+        .with_namespaces(&namespaces);
+        resolver.add_dependency(id, &dependency).await?;
+
+        let resolution = resolver
+            .resolve()
+            .await?
+            .into_values()
+            .next()
+            .expect("expected a resolution");
+
+        let (resolve, pkg, _) = resolution
+            .decode()?
+            .resolve()
+            .with_context(|| format!("failed to decode package `{id}`"))?;
+        let world = resolve
+            .select_world(pkg, Some(world))
+            .with_context(|| format!("failed to find world `{world}` in package `{id}`"))?;
+        Ok((resolve, world))
+    }
+
+    /// Decodes the world of a host from a component, a WIT package, or WIT text.
+    fn decode_world(&self, path: &Path, bytes: &[u8]) -> Result<(Resolve, WorldId)> {
+        if bytes.starts_with(b"\0asm") {
+            return match wit_component::decode(bytes)
+                .with_context(|| format!("failed to decode host `{path}`", path = path.display()))?
+            {
+                DecodedWasm::WitPackage(resolve, pkg) => {
+                    let world = resolve.select_world(pkg, self.world.as_deref())?;
+                    Ok((resolve, world))
+                }
+                DecodedWasm::Component(resolve, world) => Ok((resolve, world)),
+            };
+        }
+
+        let contents = std::str::from_utf8(bytes).with_context(|| {
+            format!(
+                "host `{path}` is not a component or a WIT package",
+                path = path.display()
+            )
+        })?;
+
+        let mut resolve = Resolve::new();
+        let pkg = resolve
+            .push(UnresolvedPackage::parse(path, contents)?)
+            .with_context(|| format!("failed to parse WIT `{path}`", path = path.display()))?;
+        let world = resolve.select_world(pkg, self.world.as_deref())?;
+        Ok((resolve, world))
+    }
+}
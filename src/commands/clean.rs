@@ -0,0 +1,246 @@
+use crate::{
+    config::{CargoPackageSpec, Config},
+    load_metadata,
+};
+use anyhow::{Context, Result};
+use cargo_component_core::{
+    cache::{self, Usage},
+    command::CommonOptions,
+};
+use cargo_metadata::Package;
+use clap::Args;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Remove generated bindings, componentized outputs, and downloaded packages
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct CleanCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Only remove the generated bindings and encoded target worlds
+    #[clap(long = "bindings")]
+    pub bindings: bool,
+
+    /// Only remove the packages (including adapters) downloaded from registries
+    #[clap(long = "downloads")]
+    pub downloads: bool,
+
+    /// Only remove the componentized outputs of the release profile
+    #[clap(long = "release", short = 'r')]
+    pub release: bool,
+
+    /// Only remove the componentized outputs of the given profile
+    #[clap(long = "profile", value_name = "PROFILE-NAME")]
+    pub profile: Option<String>,
+
+    /// Package to clean (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub packages: Vec<CargoPackageSpec>,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+}
+
+impl CleanCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing clean command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+
+        let packages = if self.packages.is_empty() {
+            metadata.workspace_packages()
+        } else {
+            self.packages
+                .iter()
+                .map(|spec| {
+                    metadata
+                        .workspace_packages()
+                        .into_iter()
+                        .find(|p| {
+                            p.name == spec.name
+                                && spec
+                                    .version
+                                    .as_ref()
+                                    .map(|v| &p.version == v)
+                                    .unwrap_or(true)
+                        })
+                        .with_context(|| {
+                            format!("package ID specification `{spec}` did not match any packages")
+                        })
+                })
+                .collect::<Result<_>>()?
+        };
+
+        // Without a filter, everything is removed
+        let all = !self.bindings && !self.downloads;
+        let target_dir = metadata.target_directory.as_std_path();
+        let mut removed = Usage::default();
+
+        if all || self.bindings {
+            let bindings_dir = target_dir.join("bindings");
+            if self.packages.is_empty() {
+                removed.add(remove_dir(&bindings_dir)?);
+            } else {
+                for package in &packages {
+                    removed.add(remove_dir(&bindings_dir.join(package.name.as_str()))?);
+                }
+            }
+        }
+
+        if all {
+            let profile = match (&self.profile, self.release) {
+                (Some(profile), _) => Some(match profile.as_str() {
+                    "dev" | "test" => "debug",
+                    "bench" => "release",
+                    profile => profile,
+                }),
+                (None, true) => Some("release"),
+                (None, false) => None,
+            };
+
+            removed.add(remove_outputs(target_dir, &packages, profile)?);
+        }
+
+        if all || self.downloads {
+            for dir in cache::cache_dirs(config.warg())? {
+                removed.add(cache::clean(&dir)?);
+            }
+        }
+
+        config.terminal().status(
+            "Removed",
+            format!(
+                "{files} file{s} ({bytes} bytes)",
+                files = removed.files,
+                s = if removed.files == 1 { "" } else { "s" },
+                bytes = removed.bytes
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Removes a directory, returning the usage of what was removed.
+fn remove_dir(dir: &Path) -> Result<Usage> {
+    let usage = cache::usage(dir)?;
+    if dir.is_dir() {
+        fs::remove_dir_all(dir)
+            .with_context(|| format!("failed to remove `{path}`", path = dir.display()))?;
+    }
+
+    Ok(usage)
+}
+
+/// Removes the componentized outputs of the given packages.
+///
+/// The outputs are removed from every WebAssembly target directory, along
+/// with the files derived from them (e.g. `<name>.core.wasm`) and the copies
+/// in the `deps` directory that cargo would otherwise restore them from.
+fn remove_outputs(
+    target_dir: &Path,
+    packages: &[&Package],
+    profile: Option<&str>,
+) -> Result<Usage> {
+    let stems: Vec<_> = packages
+        .iter()
+        .flat_map(|p| &p.targets)
+        .filter_map(|t| {
+            if t.is_bin() {
+                Some(t.name.clone())
+            } else if t.kind.iter().any(|k| k == "cdylib") {
+                Some(t.name.replace('-', "_"))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut removed = Usage::default();
+    for triple in read_dir(target_dir)? {
+        if !triple
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with("wasm32-"))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        for profile_dir in read_dir(&triple)? {
+            if let Some(profile) = profile {
+                if profile_dir.file_name().and_then(|n| n.to_str()) != Some(profile) {
+                    continue;
+                }
+            }
+
+            let deps_dir = profile_dir.join("deps");
+            for (dir, deps) in [(&profile_dir, false), (&deps_dir, true)] {
+                for path in read_dir(dir)? {
+                    let name = match path.file_name().and_then(|n| n.to_str()) {
+                        Some(name) if path.is_file() => name,
+                        _ => continue,
+                    };
+
+                    if stems.iter().any(|stem| is_output(name, stem, deps)) {
+                        log::debug!("removing output `{path}`", path = path.display());
+                        removed.files += 1;
+                        removed.bytes += fs::metadata(&path).map(|m| m.len()).unwrap_or_default();
+                        fs::remove_file(&path).with_context(|| {
+                            format!("failed to remove `{path}`", path = path.display())
+                        })?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Determines if a file name is an output of a target with the given stem.
+fn is_output(name: &str, stem: &str, deps: bool) -> bool {
+    let rest = match name
+        .strip_suffix(".wasm")
+        .and_then(|n| n.strip_prefix(stem))
+    {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    if deps {
+        // Binaries in the `deps` directory have a metadata hash suffix
+        rest.is_empty()
+            || rest
+                .strip_prefix('-')
+                .map(|h| h.len() == 16 && h.chars().all(|c| c.is_ascii_hexdigit()))
+                .unwrap_or(false)
+    } else {
+        rest.is_empty() || (rest.starts_with('.') && !rest[1..].contains('-'))
+    }
+}
+
+/// Reads the subdirectories or files of a directory.
+///
+/// Returns an empty list if the directory does not exist.
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    fs::read_dir(dir)
+        .with_context(|| format!("failed to read `{path}`", path = dir.display()))?
+        .map(|e| {
+            e.map(|e| e.path())
+                .with_context(|| format!("failed to read `{path}`", path = dir.display()))
+        })
+        .collect()
+}
@@ -0,0 +1,247 @@
+use crate::{
+    compose,
+    config::{CargoArguments, CargoPackageSpec, Config},
+    is_wasm_target, load_metadata, run_cargo_command, PackageComponentMetadata,
+    COMPOSE_SCRIPT_FILE_NAME,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// The default name of the composition configuration file.
+const DEFAULT_CONFIG_FILE_NAME: &str = "compose.yml";
+
+/// Compose a component with its component dependencies
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct ComposeCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to the composition configuration file or WAC script (defaults to `compose.wac` or `compose.yml`)
+    #[clap(long = "config", short = 'c', value_name = "CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// The path to write the composed component to
+    #[clap(long = "output", short = 'o', value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Build for the target triple (defaults to `wasm32-wasi`)
+    #[clap(long = "target", value_name = "TRIPLE")]
+    pub target: Option<String>,
+
+    /// Build artifacts in release mode, with optimizations
+    #[clap(long = "release", short = 'r')]
+    pub release: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Cargo package to compose (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub cargo_package: Option<CargoPackageSpec>,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+}
+
+impl ComposeCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing compose command");
+
+        let config = Config::new(self.common.new_terminal())?;
+
+        if let Some(target) = &self.target {
+            if !is_wasm_target(target) {
+                bail!("target `{}` is not a WebAssembly target", target);
+            }
+        }
+
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = if let Some(spec) = &self.cargo_package {
+            metadata
+                .packages
+                .iter()
+                .find(|p| {
+                    p.name == spec.name
+                        && match spec.version.as_ref() {
+                            Some(v) => &p.version == v,
+                            None => true,
+                        }
+                })
+                .with_context(|| {
+                    format!("package ID specification `{spec}` did not match any packages")
+                })?
+        } else {
+            metadata
+                .root_package()
+                .context("no root package found in manifest")?
+        };
+
+        let packages = [PackageComponentMetadata::new(&metadata, package)?];
+        let cargo_build_args = CargoArguments {
+            color: self.common.color,
+            verbose: self.common.verbose as usize,
+            quiet: self.common.quiet,
+            targets: self.target.clone().into_iter().collect(),
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            release: self.release,
+            profile: None,
+            offline: self.offline,
+            workspace: false,
+            packages: self.cargo_package.clone().into_iter().collect(),
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            bins: Vec::new(),
+            all_bins: false,
+        };
+
+        let spawn_args = self.build_args()?;
+        let outputs = run_cargo_command(
+            &config,
+            &metadata,
+            &packages,
+            Some("build"),
+            &cargo_build_args,
+            &spawn_args,
+        )
+        .await?;
+        if outputs.len() != 1 {
+            bail!(
+                "expected one output from `cargo build`, got {len}",
+                len = outputs.len()
+            );
+        }
+
+        // Use the default composition script or configuration file if one is
+        // present in the package directory
+        let compose_config = self.config.clone().or_else(|| {
+            let dir = package.manifest_path.parent()?.as_std_path();
+            [COMPOSE_SCRIPT_FILE_NAME, DEFAULT_CONFIG_FILE_NAME]
+                .into_iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.is_file())
+        });
+
+        let output = self
+            .output
+            .clone()
+            .unwrap_or_else(|| outputs[0].with_extension("composed.wasm"));
+
+        compose(
+            &config,
+            &metadata,
+            &packages[0],
+            &outputs[0],
+            compose_config.as_deref(),
+            &output,
+            cargo_build_args.network_allowed(),
+        )
+        .await
+    }
+
+    fn build_args(&self) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        args.push("build".to_string());
+
+        if self.release {
+            args.push("--release".to_string());
+        }
+
+        if self.common.quiet {
+            args.push("-q".to_string());
+        }
+
+        args.extend(
+            std::iter::repeat("-v")
+                .take(self.common.verbose as usize)
+                .map(ToString::to_string),
+        );
+
+        if let Some(color) = self.common.color {
+            args.push("--color".to_string());
+            args.push(color.to_string());
+        }
+
+        if let Some(target) = &self.target {
+            args.push("--target".to_string());
+            args.push(target.clone());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        if let Some(spec) = &self.cargo_package {
+            args.push("--package".to_string());
+            args.push(spec.to_string());
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(
+                manifest_path
+                    .as_os_str()
+                    .to_str()
+                    .with_context(|| {
+                        format!(
+                            "manifest path `{path}` is not valid UTF-8",
+                            path = manifest_path.display()
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        if self.offline {
+            args.push("--offline".to_string());
+        }
+
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+
+        Ok(args)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    config::{CargoArguments, CargoPackageSpec},
+    explain_target, load_component_metadata, load_metadata, Config, PackageComponentMetadata,
+};
+use anyhow::{Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Explain the fully resolved target world of a component
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct ExplainCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to explain the target world of (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The name of the target to explain; defaults to the package's target
+    #[clap(value_name = "TARGET")]
+    pub target: Option<String>,
+}
+
+impl ExplainCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing explain command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let cargo_args = CargoArguments {
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            offline: self.offline,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            ..Default::default()
+        };
+
+        let explanation = explain_target(
+            &config,
+            &metadata,
+            &package,
+            self.target.as_deref(),
+            &cargo_args,
+        )
+        .await?;
+
+        print!("{explanation}");
+        Ok(())
+    }
+}
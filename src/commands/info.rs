@@ -0,0 +1,88 @@
+use crate::{load_metadata, metadata::ComponentSection, Config, PackageComponentMetadata};
+use anyhow::Result;
+use cargo_component_core::{
+    command::CommonOptions, info::package_details, registry::NamespaceRegistries,
+    VersionedPackageId,
+};
+use clap::Args;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+/// Display information about a package in a registry
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct InfoCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// The name of the registry to query.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The package to display information about, with an optional version requirement (e.g. `wasi:http@0.2`)
+    #[clap(value_name = "PACKAGE")]
+    pub package: VersionedPackageId,
+}
+
+impl InfoCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing info command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let (registries, namespaces) = registry_config(self.manifest_path.as_deref());
+
+        let details = package_details(
+            config.warg(),
+            &registries,
+            &namespaces,
+            &self.package.id,
+            self.package.version.as_ref(),
+            self.registry.as_deref(),
+            config.terminal(),
+            !self.offline,
+        )
+        .await?;
+
+        print!("{details}");
+        Ok(())
+    }
+}
+
+/// Gets the registries and namespace mappings of the root package of the
+/// current workspace.
+///
+/// Commands that query registries may run outside of a component package, in
+/// which case only the default registry is configured.
+pub(crate) fn registry_config(
+    manifest_path: Option<&Path>,
+) -> (HashMap<String, Url>, HashMap<String, NamespaceRegistries>) {
+    match component_section(manifest_path) {
+        Some(section) => (section.registries, section.namespaces),
+        None => Default::default(),
+    }
+}
+
+/// Gets the component metadata section of the root package of the current
+/// workspace, if there is one.
+pub(crate) fn component_section(manifest_path: Option<&Path>) -> Option<ComponentSection> {
+    load_metadata(manifest_path).ok().and_then(|metadata| {
+        metadata
+            .root_package()
+            .and_then(|package| PackageComponentMetadata::new(&metadata, package).ok())
+            .and_then(|package| package.metadata)
+            .map(|metadata| metadata.section)
+    })
+}
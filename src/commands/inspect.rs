@@ -0,0 +1,205 @@
+use super::info::registry_config;
+use crate::{
+    config::CargoPackageSpec, inspect::Inspection, load_component_metadata, load_metadata, Config,
+    PackageComponentMetadata,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryPackage},
+    terminal::MessageFormat,
+    VersionedPackageId,
+};
+use clap::Args;
+use semver::VersionReq;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Inspect the world, metadata, and structure of a component
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct InspectCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The name of the registry to download the package from
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Inspect the component built in release mode
+    #[clap(long = "release", short = 'r')]
+    pub release: bool,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to inspect the component of (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The path to a component or WIT package, or a registry package with an
+    /// optional version requirement (e.g. `wasi:http@0.2`); defaults to the
+    /// package's built component
+    #[clap(value_name = "FILE_OR_PACKAGE")]
+    pub target: Option<String>,
+}
+
+impl InspectCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing inspect command");
+
+        let config = Config::new(self.common.new_terminal())?;
+
+        let (source, bytes) = match self.target.as_deref() {
+            Some(target) if Path::new(target).is_file() => {
+                let bytes =
+                    fs::read(target).with_context(|| format!("failed to read `{target}`"))?;
+                (target.to_string(), bytes)
+            }
+            Some(target) if target.contains(':') => {
+                let package: VersionedPackageId = target
+                    .parse()
+                    .with_context(|| format!("invalid package `{target}`"))?;
+                self.download(&config, &package).await?
+            }
+            Some(target) => {
+                bail!("`{target}` is not an existing file or a registry package (e.g. `wasi:http`)")
+            }
+            None => {
+                let path = self.built_component()?;
+                let bytes = fs::read(&path).with_context(|| {
+                    format!("failed to read component `{path}`", path = path.display())
+                })?;
+                (path.display().to_string(), bytes)
+            }
+        };
+
+        let inspection = Inspection::new(source, &bytes)
+            .with_context(|| format!("failed to inspect `{source}`", source = self.source()))?;
+
+        match config.terminal().message_format() {
+            MessageFormat::Json => println!("{}", serde_json::to_string(&inspection)?),
+            MessageFormat::Human => print!("{inspection}"),
+        }
+
+        Ok(())
+    }
+
+    /// Describes what is being inspected for error messages.
+    fn source(&self) -> &str {
+        self.target.as_deref().unwrap_or("the built component")
+    }
+
+    /// Downloads the content of a registry package.
+    async fn download(
+        &self,
+        config: &Config,
+        package: &VersionedPackageId,
+    ) -> Result<(String, Vec<u8>)> {
+        let (registries, namespaces) = registry_config(self.manifest_path.as_deref());
+
+        let id = &package.id;
+        let dependency = Dependency::Package(RegistryPackage {
+            id: Some(id.clone()),
+            version: package.version.clone().unwrap_or(VersionReq::STAR),
+            registry: self.registry.clone(),
+            interfaces: Vec::new(),
+        });
+
+        let mut resolver = DependencyResolver::new(
+            config.warg(),
+            &registries,
+            None,
+            config.terminal(),
+            !self.offline,
+        )?
+        .with_namespaces(&namespaces);
+        resolver.add_dependency(id, &dependency).await?;
+
+        let resolution = resolver
+            .resolve()
+            .await?
+            .into_values()
+            .next()
+            .expect("expected a resolution");
+
+        let bytes = fs::read(resolution.path()).with_context(|| {
+            format!(
+                "failed to read content of package `{id}` at path `{path}`",
+                path = resolution.path().display()
+            )
+        })?;
+
+        let source = match &resolution {
+            DependencyResolution::Registry(res) => format!("{id}@{version}", version = res.version),
+            _ => id.to_string(),
+        };
+
+        Ok((source, bytes))
+    }
+
+    /// Gets the path of the component built for the selected package.
+    fn built_component(&self) -> Result<PathBuf> {
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let name = package
+            .package
+            .targets
+            .iter()
+            .find(|t| t.kind.iter().any(|k| k == "cdylib"))
+            .map(|t| t.name.replace('-', "_"))
+            .or_else(|| {
+                package
+                    .package
+                    .targets
+                    .iter()
+                    .find(|t| t.is_bin())
+                    .map(|t| t.name.clone())
+            })
+            .with_context(|| {
+                format!(
+                    "package `{name}` does not have a `cdylib` or binary target",
+                    name = package.package.name
+                )
+            })?;
+
+        let path = metadata
+            .target_directory
+            .join("wasm32-wasi")
+            .join(if self.release { "release" } else { "debug" })
+            .join(format!("{name}.wasm"))
+            .into_std_path_buf();
+
+        if !path.is_file() {
+            bail!(
+                "component `{path}` does not exist; run `cargo component build{release}` first",
+                path = path.display(),
+                release = if self.release { " --release" } else { "" }
+            );
+        }
+
+        Ok(path)
+    }
+}
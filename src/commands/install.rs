@@ -0,0 +1,452 @@
+use super::info::registry_config;
+use crate::{
+    config::CargoArguments, explain, load_metadata, run_cargo_command, Config,
+    PackageComponentMetadata, DEFAULT_RUNTIME, RUNTIME_ENV_VAR,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    git::{self, GitReference},
+    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryPackage},
+    VersionedPackageId,
+};
+use clap::Args;
+use semver::VersionReq;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use wit_component::DecodedWasm;
+
+/// The environment variable used to specify the installation root.
+const INSTALL_ROOT_ENV_VAR: &str = "CARGO_INSTALL_ROOT";
+
+/// Install a command component from a registry, git repository, or local path
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct InstallCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml used for registry configuration
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// The name of the registry to download the package from.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Build and install the command components of the package at the given path
+    #[clap(long = "path", value_name = "PATH", conflicts_with_all = ["package", "git"])]
+    pub path: Option<PathBuf>,
+
+    /// Build and install the command components of the package in the given git repository
+    #[clap(long = "git", value_name = "URL", conflicts_with = "package")]
+    pub git: Option<String>,
+
+    /// The branch of the git repository to install from
+    #[clap(long = "branch", value_name = "BRANCH", requires = "git", conflicts_with_all = ["tag", "rev"])]
+    pub branch: Option<String>,
+
+    /// The tag of the git repository to install from
+    #[clap(
+        long = "tag",
+        value_name = "TAG",
+        requires = "git",
+        conflicts_with = "rev"
+    )]
+    pub tag: Option<String>,
+
+    /// The revision of the git repository to install from
+    #[clap(long = "rev", value_name = "SHA", requires = "git")]
+    pub rev: Option<String>,
+
+    /// The name of the installed command; defaults to the package or binary name
+    #[clap(long = "name", value_name = "NAME")]
+    pub name: Option<String>,
+
+    /// Directory to install the command into (defaults to `$CARGO_INSTALL_ROOT` or `$CARGO_HOME`)
+    #[clap(long = "root", value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    /// Overwrite an existing command of the same name
+    #[clap(long = "force", short = 'f')]
+    pub force: bool,
+
+    /// Require the lock file of the package being built is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Require the lock file of the package being built is up to date and don't access the network
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The package to install, with an optional version requirement (e.g. `wasi:http@0.2`)
+    #[clap(value_name = "PACKAGE", required_unless_present_any = ["path", "git"])]
+    pub package: Option<VersionedPackageId>,
+}
+
+impl InstallCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing install command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let root = self.install_root()?;
+
+        let components = match (&self.package, &self.path, &self.git) {
+            (Some(package), _, _) => vec![self.download(&config, package).await?],
+            (None, Some(path), _) => self.build(&config, path).await?,
+            (None, None, Some(url)) => {
+                let reference = match (&self.branch, &self.tag, &self.rev) {
+                    (Some(branch), _, _) => GitReference::Branch(branch.clone()),
+                    (_, Some(tag), _) => GitReference::Tag(tag.clone()),
+                    (_, _, Some(rev)) => GitReference::Rev(rev.clone()),
+                    _ => GitReference::DefaultBranch,
+                };
+
+                let (_, dir) = git::checkout(
+                    &git::cache_dir(config.warg())?,
+                    url,
+                    &reference,
+                    None,
+                    self.network_allowed(),
+                    config.terminal(),
+                )
+                .with_context(|| format!("failed to fetch git repository `{url}`"))?;
+
+                self.build(&config, &dir).await?
+            }
+            (None, None, None) => unreachable!("a package source is required"),
+        };
+
+        if self.name.is_some() && components.len() > 1 {
+            bail!("`--name` cannot be used when installing multiple command components");
+        }
+
+        for (name, path) in components {
+            let name = self.name.clone().unwrap_or(name);
+            install(&config, &root, &name, &path, self.force)?;
+        }
+
+        Ok(())
+    }
+
+    fn network_allowed(&self) -> bool {
+        !self.offline && !self.frozen
+    }
+
+    fn install_root(&self) -> Result<PathBuf> {
+        if let Some(root) = &self.root {
+            return Ok(root.clone());
+        }
+
+        if let Some(root) = std::env::var_os(INSTALL_ROOT_ENV_VAR) {
+            return Ok(PathBuf::from(root));
+        }
+
+        match std::env::var_os("CARGO_HOME") {
+            Some(home) => Ok(PathBuf::from(home)),
+            None => Ok(dirs::home_dir()
+                .context("failed to determine the home directory")?
+                .join(".cargo")),
+        }
+    }
+
+    /// Downloads a command component from a registry.
+    ///
+    /// Returns the name of the command and the path to the component.
+    async fn download(
+        &self,
+        config: &Config,
+        package: &VersionedPackageId,
+    ) -> Result<(String, PathBuf)> {
+        let (registries, namespaces) = registry_config(self.manifest_path.as_deref());
+
+        let id = &package.id;
+        let dependency = Dependency::Package(RegistryPackage {
+            id: Some(id.clone()),
+            version: package.version.clone().unwrap_or(VersionReq::STAR),
+            registry: self.registry.clone(),
+            interfaces: Vec::new(),
+        });
+
+        let mut resolver = DependencyResolver::new(
+            config.warg(),
+            &registries,
+            None,
+            config.terminal(),
+            self.network_allowed(),
+        )?
+        .with_namespaces(&namespaces);
+        resolver.add_dependency(id, &dependency).await?;
+
+        let resolution = resolver
+            .resolve()
+            .await?
+            .into_values()
+            .next()
+            .expect("expected a resolution");
+
+        let version = match &resolution {
+            DependencyResolution::Registry(registry) => &registry.version,
+            _ => unreachable!(),
+        };
+
+        config
+            .terminal()
+            .status("Installing", format!("package `{id}` v{version}"))?;
+
+        let path = resolution.path();
+        check_command_component(path)
+            .with_context(|| format!("package `{id}` v{version} cannot be installed"))?;
+
+        Ok((id.name().to_string(), path.to_path_buf()))
+    }
+
+    /// Builds the command components of the package at the given path.
+    ///
+    /// Returns the names of the binaries and the paths to their components.
+    async fn build(&self, config: &Config, dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let manifest_path = if dir.is_file() {
+            dir.to_path_buf()
+        } else {
+            dir.join("Cargo.toml")
+        };
+
+        let metadata = load_metadata(Some(&manifest_path))?;
+        let package = PackageComponentMetadata::new(
+            &metadata,
+            metadata
+                .root_package()
+                .context("no root package found in metadata")?,
+        )?;
+
+        if package.metadata.is_none() {
+            bail!(
+                "package `{name}` is not a component package",
+                name = package.package.name
+            );
+        }
+
+        config.terminal().status(
+            "Installing",
+            format!(
+                "package `{name}` v{version} ({path})",
+                name = package.package.name,
+                version = package.package.version,
+                path = manifest_path.parent().unwrap().display()
+            ),
+        )?;
+
+        let cargo_args = CargoArguments {
+            manifest_path: Some(manifest_path.clone()),
+            release: true,
+            locked: self.locked,
+            frozen: self.frozen,
+            offline: self.offline,
+            all_bins: true,
+            ..Default::default()
+        };
+
+        let mut spawn_args = vec![
+            "build".to_string(),
+            "--release".to_string(),
+            "--bins".to_string(),
+            "--manifest-path".to_string(),
+            manifest_path.display().to_string(),
+        ];
+        if self.locked {
+            spawn_args.push("--locked".to_string());
+        }
+        if self.frozen {
+            spawn_args.push("--frozen".to_string());
+        }
+        if self.offline {
+            spawn_args.push("--offline".to_string());
+        }
+
+        let outputs = run_cargo_command(
+            config,
+            &metadata,
+            std::slice::from_ref(&package),
+            Some("build"),
+            &cargo_args,
+            &spawn_args,
+        )
+        .await?;
+
+        if outputs.is_empty() {
+            bail!(
+                "package `{name}` has no binary targets to install",
+                name = package.package.name
+            );
+        }
+
+        outputs
+            .into_iter()
+            .map(|path| {
+                check_command_component(&path)?;
+                let name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .context("output component has an invalid file name")?
+                    .to_string();
+                Ok((name, path))
+            })
+            .collect()
+    }
+}
+
+/// Checks that the component at the given path is a command component.
+///
+/// Command components export the `wasi:cli/run` interface.
+fn check_command_component(path: &Path) -> Result<()> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+    let (resolve, world) = match wit_component::decode(&bytes)
+        .with_context(|| format!("failed to decode `{path}`", path = path.display()))?
+    {
+        DecodedWasm::Component(resolve, world) => (resolve, world),
+        DecodedWasm::WitPackage(..) => bail!("package is a WIT package and not a component"),
+    };
+
+    let is_command = resolve.worlds[world]
+        .exports
+        .keys()
+        .any(|key| explain::key_name(&resolve, key).starts_with("wasi:cli/run"));
+
+    if !is_command {
+        bail!("component does not export `wasi:cli/run` and is not a command component");
+    }
+
+    Ok(())
+}
+
+/// Installs a command component and its launcher into the given root.
+///
+/// The component is copied to `<root>/components/<name>.wasm` and a launcher
+/// that runs it with the WebAssembly runtime is written to `<root>/bin`.
+fn install(config: &Config, root: &Path, name: &str, component: &Path, force: bool) -> Result<()> {
+    let bin_dir = root.join("bin");
+    let components_dir = root.join("components");
+    let launcher = bin_dir.join(if cfg!(windows) {
+        format!("{name}.cmd")
+    } else {
+        name.to_string()
+    });
+    let installed = components_dir.join(name).with_extension("wasm");
+
+    if launcher.exists() {
+        if !force {
+            bail!(
+                "command `{name}` is already installed at `{path}`; use `--force` to overwrite it",
+                path = launcher.display()
+            );
+        }
+
+        config
+            .terminal()
+            .status("Replacing", format!("`{path}`", path = launcher.display()))?;
+    }
+
+    for dir in [&bin_dir, &components_dir] {
+        fs::create_dir_all(dir).with_context(|| {
+            format!("failed to create directory `{path}`", path = dir.display())
+        })?;
+    }
+
+    fs::copy(component, &installed).with_context(|| {
+        format!(
+            "failed to copy `{src}` to `{dest}`",
+            src = component.display(),
+            dest = installed.display()
+        )
+    })?;
+
+    fs::write(&launcher, launcher_script(&installed)?).with_context(|| {
+        format!(
+            "failed to write launcher `{path}`",
+            path = launcher.display()
+        )
+    })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&launcher, fs::Permissions::from_mode(0o755)).with_context(|| {
+            format!(
+                "failed to set permissions of launcher `{path}`",
+                path = launcher.display()
+            )
+        })?;
+    }
+
+    config.terminal().status(
+        "Installed",
+        format!("command `{name}` to `{path}`", path = launcher.display()),
+    )?;
+
+    if let Some(paths) = std::env::var_os("PATH") {
+        if !std::env::split_paths(&paths).any(|p| p == bin_dir) {
+            config.terminal().warn(format!(
+                "be sure to add `{path}` to your PATH to be able to run the installed command",
+                path = bin_dir.display()
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates the launcher script for an installed component.
+///
+/// The launcher runs the component with the runtime specified by
+/// `CARGO_COMPONENT_RUNTIME` at the time it is run, defaulting to `wasmtime`.
+///
+/// The path of the component is quoted for the shell running the launcher;
+/// paths that cannot be quoted for it are refused.
+fn launcher_script(component: &Path) -> Result<String> {
+    let path = component.to_str().with_context(|| {
+        format!(
+            "installed component path `{path}` is not valid UTF-8",
+            path = component.display()
+        )
+    })?;
+
+    if path.contains(['\n', '\r']) {
+        bail!("installed component path `{path}` cannot contain a line break");
+    }
+
+    if cfg!(windows) {
+        // Batch files expand `%` even within quotes and cannot escape `"`
+        if path.contains('"') {
+            bail!("installed component path `{path}` cannot contain `\"`");
+        }
+
+        let component = path.replace('%', "%%");
+        Ok(format!(
+            "@echo off\r\n\
+             if defined {RUNTIME_ENV_VAR} (\r\n\
+             %{RUNTIME_ENV_VAR}% \"{component}\" %*\r\n\
+             ) else (\r\n\
+             {DEFAULT_RUNTIME} \"{component}\" %*\r\n\
+             )\r\n\
+             exit /b %ERRORLEVEL%\r\n"
+        ))
+    } else {
+        // Nothing is expanded within single quotes, which are closed and
+        // reopened around an escaped `'`
+        let component = format!("'{path}'", path = path.replace('\'', r"'\''"));
+        Ok(format!(
+            "#!/bin/sh\n\
+             exec ${{{RUNTIME_ENV_VAR}:-{DEFAULT_RUNTIME}}} {component} \"$@\"\n"
+        ))
+    }
+}
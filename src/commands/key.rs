@@ -2,13 +2,20 @@ use crate::config::Config;
 use anyhow::{bail, Context, Result};
 use cargo_component_core::{
     command::CommonOptions,
-    keyring::{self, delete_signing_key, get_signing_key, get_signing_key_entry, set_signing_key},
+    keyring::{
+        delete_signing_key, get_signing_key, list_signing_keys, set_signing_key, signing_key_exists,
+    },
+    keystore::{passphrase_from_env, Keystore},
     terminal::Colors,
 };
 use clap::{Args, Subcommand};
 use p256::ecdsa::SigningKey;
 use rand_core::OsRng;
-use std::io::{self, Write};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
 use warg_client::RegistryUrl;
 use warg_crypto::signing::PrivateKey;
 
@@ -35,7 +42,10 @@ impl KeyCommand {
         match self.command {
             KeySubcommand::Id(cmd) => cmd.exec().await,
             KeySubcommand::New(cmd) => cmd.exec(&config).await,
+            KeySubcommand::List(cmd) => cmd.exec(&config).await,
             KeySubcommand::Set(cmd) => cmd.exec(&config).await,
+            KeySubcommand::Import(cmd) => cmd.exec(&config).await,
+            KeySubcommand::Export(cmd) => cmd.exec(&config).await,
             KeySubcommand::Delete(cmd) => cmd.exec(&config).await,
         }
     }
@@ -48,8 +58,14 @@ pub enum KeySubcommand {
     Id(KeyIdCommand),
     /// Creates a new signing key for a registry in the local keyring.
     New(KeyNewCommand),
+    /// Lists the signing keys in the local keyring.
+    List(KeyListCommand),
     /// Sets the signing key for a registry in the local keyring.
     Set(KeySetCommand),
+    /// Imports a signing key for a registry from a file into the local keyring.
+    Import(KeyImportCommand),
+    /// Exports the signing key or public key for a registry from the local keyring.
+    Export(KeyExportCommand),
     /// Deletes the signing key for a registry from the local keyring.
     Delete(KeyDeleteCommand),
 }
@@ -60,6 +76,9 @@ pub struct KeyIdCommand {
     /// The key name of the signing key.
     #[clap(long, short, value_name = "NAME", default_value = "default")]
     pub key_name: String,
+    /// Use the encrypted keystore at the given path instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
     /// The URL of the registry to print the Key ID for.
     #[clap(value_name = "URL")]
     pub url: RegistryUrl,
@@ -68,7 +87,12 @@ pub struct KeyIdCommand {
 impl KeyIdCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
-        let key = get_signing_key(&self.url, &self.key_name)?;
+        let key = match &self.keystore {
+            Some(path) => {
+                Keystore::open(path)?.get(&self.url, &self.key_name, &keystore_passphrase(path)?)?
+            }
+            None => get_signing_key(&self.url, &self.key_name)?,
+        };
         println!(
             "{fingerprint}",
             fingerprint = key.public_key().fingerprint()
@@ -84,6 +108,10 @@ pub struct KeyNewCommand {
     /// The key name to use for the signing key.
     #[clap(long, short, value_name = "NAME", default_value = "default")]
     pub key_name: String,
+    /// Create the signing key in the encrypted keystore at the given path
+    /// instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
     /// The URL of the registry to create a signing key for.
     #[clap(value_name = "URL")]
     pub url: RegistryUrl,
@@ -92,30 +120,16 @@ pub struct KeyNewCommand {
 impl KeyNewCommand {
     /// Executes the command.
     pub async fn exec(self, config: &Config) -> Result<()> {
-        let entry = get_signing_key_entry(&self.url, &self.key_name)?;
-
-        match entry.get_password() {
-            Err(keyring::Error::NoEntry) => {
-                // no entry exists, so we can continue
-            }
-            Ok(_) | Err(keyring::Error::Ambiguous(_)) => {
-                bail!(
-                    "signing key `{name}` already exists for registry `{url}`",
-                    name = self.key_name,
-                    url = self.url
-                );
-            }
-            Err(e) => {
-                bail!(
-                    "failed to get signing key `{name}` for registry `{url}`: {e}",
-                    name = self.key_name,
-                    url = self.url
-                );
-            }
+        if key_exists(self.keystore.as_deref(), &self.url, &self.key_name)? {
+            bail!(
+                "signing key `{name}` already exists for registry `{url}`",
+                name = self.key_name,
+                url = self.url
+            );
         }
 
         let key = SigningKey::random(&mut OsRng).into();
-        set_signing_key(&self.url, &self.key_name, &key)?;
+        store_key(self.keystore.as_deref(), &self.url, &self.key_name, &key)?;
 
         config.terminal().status(
             "Created",
@@ -131,6 +145,60 @@ impl KeyNewCommand {
     }
 }
 
+/// Lists the signing keys in the local keyring.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct KeyListCommand {
+    /// Only list the signing keys for the registry with this URL.
+    #[clap(value_name = "URL")]
+    pub url: Option<RegistryUrl>,
+}
+
+impl KeyListCommand {
+    /// Executes the command.
+    pub async fn exec(self, config: &Config) -> Result<()> {
+        let filter = self.url.map(|url| url.to_string());
+        let entries: Vec<_> = list_signing_keys()?
+            .into_iter()
+            .filter(|e| filter.as_ref().map(|f| *f == e.registry).unwrap_or(true))
+            .collect();
+
+        if entries.is_empty() {
+            config
+                .terminal()
+                .note("no signing keys were found in the local keyring")?;
+            return Ok(());
+        }
+
+        for entry in entries {
+            let fingerprint = match entry
+                .registry
+                .parse::<RegistryUrl>()
+                .context("invalid registry URL in the key index")
+                .and_then(|url| get_signing_key(&url, &entry.name))
+            {
+                Ok(key) => key.public_key().fingerprint().to_string(),
+                Err(e) => {
+                    log::debug!(
+                        "failed to get signing key `{name}` for registry `{url}`: {e:?}",
+                        name = entry.name,
+                        url = entry.registry
+                    );
+                    "<missing from keyring>".to_string()
+                }
+            };
+
+            println!(
+                "{url} {name} {fingerprint}",
+                url = entry.registry,
+                name = entry.name
+            );
+        }
+
+        Ok(())
+    }
+}
+
 /// Sets the signing key for a registry in the local keyring.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -138,6 +206,10 @@ pub struct KeySetCommand {
     /// The key name to use for the signing key.
     #[clap(long, short, value_name = "NAME", default_value = "default")]
     pub key_name: String,
+    /// Set the signing key in the encrypted keystore at the given path
+    /// instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
     /// The URL of the registry to create a signing key for.
     #[clap(value_name = "URL")]
     pub url: RegistryUrl,
@@ -152,7 +224,7 @@ impl KeySetCommand {
         )
         .context("signing key is not in the correct format")?;
 
-        set_signing_key(&self.url, &self.key_name, &key)?;
+        store_key(self.keystore.as_deref(), &self.url, &self.key_name, &key)?;
 
         config.terminal().status(
             "Set",
@@ -168,6 +240,114 @@ impl KeySetCommand {
     }
 }
 
+/// Imports a signing key for a registry from a file into the local keyring.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct KeyImportCommand {
+    /// The key name to use for the signing key.
+    #[clap(long, short, value_name = "NAME", default_value = "default")]
+    pub key_name: String,
+    /// Replace an existing signing key with the same name.
+    #[clap(long = "force")]
+    pub force: bool,
+    /// Import the signing key into the encrypted keystore at the given path
+    /// instead of the local keyring.
+    #[clap(long = "keystore", value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
+    /// The URL of the registry to import the signing key for.
+    #[clap(value_name = "URL")]
+    pub url: RegistryUrl,
+    /// The file containing the signing key in the `<alg>:<base64>` format (`-` for stdin).
+    #[clap(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+impl KeyImportCommand {
+    /// Executes the command.
+    pub async fn exec(self, config: &Config) -> Result<()> {
+        let contents = if self.file.as_os_str() == "-" {
+            let mut contents = String::new();
+            io::stdin()
+                .read_to_string(&mut contents)
+                .context("failed to read signing key from stdin")?;
+            contents
+        } else {
+            fs::read_to_string(&self.file).with_context(|| {
+                format!(
+                    "failed to read signing key file `{path}`",
+                    path = self.file.display()
+                )
+            })?
+        };
+
+        let key = PrivateKey::decode(contents.trim().to_string())
+            .context("signing key is not in the correct format")?;
+
+        if !self.force && key_exists(self.keystore.as_deref(), &self.url, &self.key_name)? {
+            bail!(
+                "signing key `{name}` already exists for registry `{url}`; use `--force` to replace it",
+                name = self.key_name,
+                url = self.url
+            );
+        }
+
+        store_key(self.keystore.as_deref(), &self.url, &self.key_name, &key)?;
+
+        config.terminal().status(
+            "Imported",
+            format!(
+                "signing key `{name}` ({fingerprint}) for registry `{url}`",
+                name = self.key_name,
+                fingerprint = key.public_key().fingerprint(),
+                url = self.url,
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Exports the signing key or public key for a registry from the local keyring.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct KeyExportCommand {
+    /// The key name of the signing key.
+    #[clap(long, short, value_name = "NAME", default_value = "default")]
+    pub key_name: String,
+    /// Export the public key and its fingerprint instead of the signing key.
+    #[clap(long = "public")]
+    pub public: bool,
+    /// The URL of the registry to export the signing key for.
+    #[clap(value_name = "URL")]
+    pub url: RegistryUrl,
+}
+
+impl KeyExportCommand {
+    /// Executes the command.
+    pub async fn exec(self, config: &Config) -> Result<()> {
+        let key = get_signing_key(&self.url, &self.key_name)?;
+
+        if self.public {
+            let public = key.public_key();
+            println!("{public}");
+            config.terminal().note(format!(
+                "the fingerprint of the public key is `{fingerprint}`",
+                fingerprint = public.fingerprint()
+            ))?;
+            return Ok(());
+        }
+
+        config.terminal().warn(format!(
+            "the signing key `{name}` for registry `{url}` is being printed; keep it secret",
+            name = self.key_name,
+            url = self.url
+        ))?;
+
+        println!("{key}", key = &*key.encode());
+        Ok(())
+    }
+}
+
 /// Deletes the signing key for a registry from the local keyring.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -225,3 +405,50 @@ impl KeyDeleteCommand {
         Ok(())
     }
 }
+
+/// Determines if a signing key exists in the given keystore or, if there is
+/// none, in the local keyring.
+fn key_exists(keystore: Option<&Path>, url: &RegistryUrl, key_name: &str) -> Result<bool> {
+    match keystore {
+        Some(path) => Ok(Keystore::open(path)?.contains(url, key_name)),
+        None => signing_key_exists(url, key_name),
+    }
+}
+
+/// Stores a signing key in the given keystore or, if there is none, in the
+/// local keyring.
+fn store_key(
+    keystore: Option<&Path>,
+    url: &RegistryUrl,
+    key_name: &str,
+    key: &PrivateKey,
+) -> Result<()> {
+    match keystore {
+        Some(path) => {
+            let mut keystore = Keystore::open(path)?;
+            keystore.set(url, key_name, key, &keystore_passphrase(path)?)?;
+            keystore.save(path)
+        }
+        None => set_signing_key(url, key_name, key),
+    }
+}
+
+/// Gets the passphrase of a keystore from the `SIGNING_KEYSTORE_PASSPHRASE`
+/// environment variable or, if it is not set, by prompting for it.
+fn keystore_passphrase(path: &Path) -> Result<String> {
+    if let Ok(passphrase) = passphrase_from_env(path) {
+        return Ok(passphrase);
+    }
+
+    let passphrase = rpassword::prompt_password(format!(
+        "passphrase for keystore `{path}`: ",
+        path = path.display()
+    ))
+    .context("failed to read keystore passphrase")?;
+
+    if passphrase.is_empty() {
+        bail!("the keystore passphrase cannot be empty");
+    }
+
+    Ok(passphrase)
+}
@@ -0,0 +1,87 @@
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions, keyring::set_registry_token, oci::is_oci_url,
+    static_registry::is_static_url,
+};
+use clap::Args;
+use std::io::{self, Read};
+
+/// Store an authentication token for a registry in the local keyring
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct LoginCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Only use the token for packages in the given namespace
+    #[clap(long = "namespace", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Read the token from stdin instead of prompting for it
+    #[clap(long = "token-stdin")]
+    pub token_stdin: bool,
+
+    /// The URL of the registry to log in to
+    #[clap(value_name = "URL")]
+    pub url: String,
+}
+
+impl LoginCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing login command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        check_token_registry(&self.url)?;
+
+        let token = if self.token_stdin {
+            let mut token = String::new();
+            io::stdin()
+                .read_to_string(&mut token)
+                .context("failed to read token from stdin")?;
+            token
+        } else {
+            rpassword::prompt_password(format!("token for registry `{url}`: ", url = self.url))
+                .context("failed to read token")?
+        };
+
+        let token = token.trim();
+        if token.is_empty() {
+            bail!("authentication token cannot be empty");
+        }
+
+        set_registry_token(&self.url, self.namespace.as_deref(), token)?;
+
+        config.terminal().status(
+            "Login",
+            match &self.namespace {
+                Some(namespace) => format!(
+                    "token for namespace `{namespace}` of registry `{url}` saved to the local keyring",
+                    url = self.url
+                ),
+                None => format!(
+                    "token for registry `{url}` saved to the local keyring",
+                    url = self.url
+                ),
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Checks that the given registry supports authentication tokens.
+///
+/// Tokens are sent to OCI and static registries; the warg client does not
+/// yet support authenticating requests.
+pub(crate) fn check_token_registry(url: &str) -> Result<()> {
+    if !is_oci_url(url) && !is_static_url(url) {
+        bail!(
+            "registry `{url}` does not support authentication tokens; only OCI (`oci://`) and static (`static+https://`) registries do (use `cargo component key` to manage the signing keys of warg registries)"
+        );
+    }
+
+    Ok(())
+}
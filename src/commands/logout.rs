@@ -0,0 +1,50 @@
+use super::login::check_token_registry;
+use crate::config::Config;
+use anyhow::Result;
+use cargo_component_core::{command::CommonOptions, keyring::delete_registry_token};
+use clap::Args;
+
+/// Remove the authentication token for a registry from the local keyring
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct LogoutCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Remove the token for the given namespace instead of the whole registry
+    #[clap(long = "namespace", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// The URL of the registry to log out of
+    #[clap(value_name = "URL")]
+    pub url: String,
+}
+
+impl LogoutCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing logout command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        check_token_registry(&self.url)?;
+
+        if !delete_registry_token(&self.url, self.namespace.as_deref())? {
+            config.terminal().warn(format!(
+                "no token for registry `{url}` was found in the local keyring",
+                url = self.url
+            ))?;
+            return Ok(());
+        }
+
+        config.terminal().status(
+            "Logout",
+            format!(
+                "token for registry `{url}` removed from the local keyring",
+                url = self.url
+            ),
+        )?;
+
+        Ok(())
+    }
+}
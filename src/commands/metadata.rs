@@ -0,0 +1,79 @@
+use crate::{
+    config::{CargoArguments, CargoPackageSpec},
+    describe_components, load_component_metadata, load_metadata, Config,
+};
+use anyhow::Result;
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Output the resolved component graph of the workspace as JSON
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct MetadataCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to output the metadata of (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub packages: Vec<CargoPackageSpec>,
+
+    /// Format version of the output
+    #[clap(long = "format-version", value_name = "VERSION", value_parser = ["1"])]
+    pub format_version: Option<String>,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+}
+
+impl MetadataCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing metadata command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(&metadata, self.packages.iter(), false)?;
+
+        let cargo_args = CargoArguments {
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            offline: self.offline,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            ..Default::default()
+        };
+
+        let description = describe_components(&config, &metadata, &packages, &cargo_args).await?;
+        println!("{}", serde_json::to_string(&description)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    config::CargoPackageSpec, load_component_metadata, load_metadata, migrate, Config,
+    PackageComponentMetadata,
+};
+use anyhow::{Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Migrate a `wit-bindgen` project to a `cargo-component` project
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct MigrateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to migrate (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+}
+
+impl MigrateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing migrate command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let migration = migrate::migrate(package.package)?;
+
+        for warning in &migration.warnings {
+            config.terminal().warn(warning)?;
+        }
+
+        if let Some(path) = &migration.wit {
+            config.terminal().status(
+                "Created",
+                format!(
+                    "WIT file `{path}` from the inline WIT of the bindings macro",
+                    path = path.display()
+                ),
+            )?;
+        }
+
+        for path in &migration.sources {
+            config.terminal().status(
+                "Updated",
+                format!("source file `{path}`", path = path.display()),
+            )?;
+        }
+
+        if migration.gitignore {
+            config
+                .terminal()
+                .status("Updated", "`.gitignore` to ignore the target directory")?;
+        }
+
+        config.terminal().status(
+            "Migrated",
+            format!(
+                "package `{name}` to `cargo-component`",
+                name = package.package.name
+            ),
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    config::{CargoArguments, CargoPackageSpec},
+    generate_mock, load_component_metadata, load_metadata, Config, PackageComponentMetadata,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{value, Document, InlineTable, Item, Table, Value};
+
+/// Generate a mock component for the imported interfaces of a component's target world
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct MockCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to generate a mock for (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The imported interface to mock (e.g. `wasi:http/outgoing-handler`);
+    /// defaults to every imported non-WASI interface with functions
+    #[clap(long = "interface", value_name = "INTERFACE")]
+    pub interfaces: Vec<String>,
+
+    /// The name of the mock package; defaults to `<package>-mock`
+    #[clap(long = "name", value_name = "NAME")]
+    pub name: Option<String>,
+
+    /// The directory to create the mock package in; defaults to
+    /// `mocks/<name>` in the package directory
+    #[clap(long = "path", value_name = "PATH")]
+    pub path: Option<PathBuf>,
+}
+
+impl MockCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing mock command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => format!(
+                "{name}-mock",
+                name = package.package.name.to_lowercase().replace('_', "-")
+            ),
+        };
+
+        let id = format!("component:{name}");
+        if let Some(component_metadata) = &package.metadata {
+            if component_metadata
+                .section
+                .dev_dependencies
+                .keys()
+                .any(|dependency| dependency.to_string() == id)
+            {
+                bail!(
+                    "package `{name}` already has a dev-dependency named `{id}`",
+                    name = package.package.name
+                );
+            }
+        }
+
+        let manifest_dir = package
+            .package
+            .manifest_path
+            .parent()
+            .context("manifest path has no parent directory")?
+            .as_std_path();
+        let dir = match &self.path {
+            Some(path) => std::env::current_dir()
+                .context("failed to determine the current directory")?
+                .join(path),
+            None => manifest_dir.join("mocks").join(&name),
+        };
+
+        let cargo_args = CargoArguments {
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            offline: self.offline,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            ..Default::default()
+        };
+
+        let mock = generate_mock(
+            &config,
+            &metadata,
+            &package,
+            &dir,
+            &name,
+            &self.interfaces,
+            &cargo_args,
+        )
+        .await?;
+
+        config.terminal().status(
+            "Created",
+            format!(
+                "mock component `{name}` at `{path}`",
+                path = mock.dir.display()
+            ),
+        )?;
+
+        for interface in &mock.interfaces {
+            config
+                .terminal()
+                .status("Stubbed", format!("interface `{interface}`"))?;
+        }
+
+        add_dev_dependency(&package, &mock.id, &mock.dependency_path)?;
+
+        config.terminal().status(
+            "Added",
+            format!(
+                "dev-dependency `{id}` to package `{name}`",
+                id = mock.id,
+                name = package.package.name
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Adds a dev-dependency on the mock to the manifest of the mocked package.
+fn add_dev_dependency(package: &PackageComponentMetadata, id: &str, path: &Path) -> Result<()> {
+    let manifest_path = &package.package.manifest_path;
+    let manifest = fs::read_to_string(manifest_path).with_context(|| {
+        format!(
+            "failed to read manifest file `{path}`",
+            path = manifest_path
+        )
+    })?;
+
+    let mut document: Document = manifest.parse().with_context(|| {
+        format!(
+            "failed to parse manifest file `{path}`",
+            path = manifest_path
+        )
+    })?;
+
+    let dependencies = document["package"]["metadata"]["component"]["dev-dependencies"]
+        .or_insert(Item::Table(Table::new()))
+        .as_table_mut()
+        .with_context(|| {
+            format!(
+                "failed to find component metadata in manifest file `{path}`",
+                path = manifest_path
+            )
+        })?;
+
+    dependencies[id] = value(InlineTable::from_iter([(
+        "path",
+        Value::from(path.display().to_string()),
+    )]));
+
+    fs::write(manifest_path, document.to_string()).with_context(|| {
+        format!(
+            "failed to write manifest file `{path}`",
+            path = manifest_path
+        )
+    })
+}
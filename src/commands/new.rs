@@ -1,8 +1,18 @@
-use crate::{config::Config, generator::SourceGenerator, metadata, metadata::DEFAULT_WIT_DIR};
+use crate::{
+    config::Config,
+    generator::SourceGenerator,
+    metadata,
+    metadata::DEFAULT_WIT_DIR,
+    template::{self, Template},
+};
 use anyhow::{bail, Context, Result};
 use cargo_component_core::{
     command::CommonOptions,
-    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryResolution},
+    git::{self, GitReference},
+    registry::{
+        Dependency, DependencyResolution, DependencyResolver, RegistryPackage, RegistryResolution,
+    },
+    VersionedPackageId,
 };
 use clap::Args;
 use heck::ToKebabCase;
@@ -19,7 +29,7 @@ use url::Url;
 
 const BINDINGS_CRATE_NAME: &str = "cargo-component-bindings";
 
-fn escape_wit(s: &str) -> Cow<str> {
+pub(crate) fn escape_wit(s: &str) -> Cow<str> {
     match s {
         "use" | "type" | "func" | "u8" | "u16" | "u32" | "u64" | "s8" | "s16" | "s32" | "s64"
         | "float32" | "float64" | "char" | "record" | "flags" | "variant" | "enum" | "union"
@@ -74,12 +84,14 @@ pub struct NewCommand {
     #[clap(long = "editor", value_name = "EDITOR", value_parser = ["emacs", "vscode", "none"])]
     pub editor: Option<String>,
 
-    /// Use the specified target world from a WIT package.
+    /// Use the specified target world from a WIT package (e.g.
+    /// `wasi:http/proxy`), generating a stub implementation of its exports.
+    /// Implies `--reactor`.
     #[clap(
         long = "target",
         short = 't',
         value_name = "TARGET",
-        requires = "reactor"
+        conflicts_with("command")
     )]
     pub target: Option<String>,
 
@@ -87,6 +99,12 @@ pub struct NewCommand {
     #[clap(long = "registry", value_name = "REGISTRY")]
     pub registry: Option<String>,
 
+    /// Apply a project template to the generated package: a local directory,
+    /// a git repository URL, or a template package in a registry (e.g.
+    /// `my-org:templates@1.0`).
+    #[clap(long = "template", value_name = "TEMPLATE")]
+    pub template: Option<String>,
+
     /// Disable the use of `rustfmt` when generating source code.
     #[clap(long = "no-rustfmt")]
     pub no_rustfmt: bool,
@@ -163,6 +181,10 @@ impl NewCommand {
             .resolve_target(&config, &registries, target, true)
             .await?;
         let source = self.generate_source(&target)?;
+        let template = match self.template.as_deref() {
+            Some(template) => Some(self.load_template(&config, &registries, template).await?),
+            None => None,
+        };
 
         let mut command = self.new_command();
         match command.status() {
@@ -181,6 +203,10 @@ impl NewCommand {
         self.create_targets_file(&name, &out_dir)?;
         self.create_editor_settings_file(&out_dir)?;
 
+        if let Some(template) = &template {
+            self.apply_template(&config, template, &name, &out_dir, &target)?;
+        }
+
         Ok(())
     }
 
@@ -306,7 +332,7 @@ impl NewCommand {
     }
 
     fn is_command(&self) -> bool {
-        self.command || !self.reactor
+        self.command || (!self.reactor && self.target.is_none())
     }
 
     fn generate_source(
@@ -488,7 +514,9 @@ world example {{
         network_allowed: bool,
     ) -> Result<Option<(RegistryResolution, Option<String>)>> {
         match target {
-            Some(metadata::Target::Package { id, package, world }) => {
+            Some(metadata::Target::Package {
+                id, package, world, ..
+            }) => {
                 let mut resolver = DependencyResolver::new(
                     config.warg(),
                     registries,
@@ -516,6 +544,131 @@ world example {{
         }
     }
 
+    async fn load_template(
+        &self,
+        config: &Config,
+        registries: &HashMap<String, Url>,
+        template: &str,
+    ) -> Result<Template> {
+        let path = Path::new(template);
+        if path.is_dir() {
+            return Template::from_dir(path);
+        }
+
+        if let Some(url) = template::git_url(template) {
+            let (_, dir) = git::checkout(
+                &git::cache_dir(config.warg())?,
+                url,
+                &GitReference::DefaultBranch,
+                None,
+                true,
+                config.terminal(),
+            )
+            .with_context(|| format!("failed to fetch template repository `{url}`"))?;
+
+            return Template::from_dir(&dir);
+        }
+
+        let package: VersionedPackageId = template.parse().with_context(|| {
+            format!(
+                "template `{template}` is not a directory, a git repository URL, or a registry package"
+            )
+        })?;
+
+        let mut resolver =
+            DependencyResolver::new(config.warg(), registries, None, config.terminal(), true)?;
+        let dependency = Dependency::Package(RegistryPackage {
+            id: Some(package.id.clone()),
+            version: package.version.clone().unwrap_or(VersionReq::STAR),
+            registry: None,
+            interfaces: Vec::new(),
+        });
+
+        resolver.add_dependency(&package.id, &dependency).await?;
+
+        let resolution = resolver
+            .resolve()
+            .await?
+            .into_values()
+            .next()
+            .expect("expected a template resolution");
+        let path = resolution.path();
+        let bytes = fs::read(path).with_context(|| {
+            format!(
+                "failed to read template package `{id}` from `{path}`",
+                id = package.id,
+                path = path.display()
+            )
+        })?;
+
+        Template::from_package(&bytes)
+            .with_context(|| format!("package `{id}` is not a template", id = package.id))
+    }
+
+    fn apply_template(
+        &self,
+        config: &Config,
+        template: &Template,
+        name: &PackageName,
+        out_dir: &Path,
+        target: &Option<(RegistryResolution, Option<String>)>,
+    ) -> Result<()> {
+        let target = match target {
+            Some((resolution, Some(world))) => format!(
+                "{id}/{world}@{version}",
+                id = resolution.id,
+                version = resolution.version
+            ),
+            Some((resolution, None)) => format!(
+                "{id}@{version}",
+                id = resolution.id,
+                version = resolution.version
+            ),
+            None if !self.is_command() => {
+                format!("{ns}:{name}/example", ns = name.namespace, name = name.name)
+            }
+            None => String::new(),
+        };
+
+        let vars = HashMap::from([
+            ("name", name.name.clone()),
+            ("namespace", name.namespace.clone()),
+            ("target", target),
+        ]);
+
+        let files = template.render(&vars)?;
+        for (path, contents) in &files {
+            let path = out_dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!(
+                        "failed to create directory `{parent}`",
+                        parent = parent.display()
+                    )
+                })?;
+            }
+
+            fs::write(&path, contents).with_context(|| {
+                format!(
+                    "failed to write template file `{path}`",
+                    path = path.display()
+                )
+            })?;
+        }
+
+        config.terminal().status(
+            "Applied",
+            format!(
+                "template `{template}` ({count} file{s})",
+                template = self.template.as_deref().unwrap_or_default(),
+                count = files.len(),
+                s = if files.len() == 1 { "" } else { "s" }
+            ),
+        )?;
+
+        Ok(())
+    }
+
     fn registries(&self) -> Result<HashMap<String, Url>> {
         let mut registries = HashMap::new();
 
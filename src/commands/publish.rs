@@ -1,14 +1,30 @@
 use crate::{
     config::{CargoArguments, CargoPackageSpec, Config},
-    is_wasm_target, load_metadata, publish, run_cargo_command, PackageComponentMetadata,
-    PublishOptions,
+    generate_sbom, is_wasm_target, load_metadata,
+    metadata::{ComponentMetadata, Target},
+    publish, run_cargo_command, PackageComponentMetadata, PublishOptions,
 };
 use anyhow::{bail, Context, Result};
-use cargo_component_core::{command::CommonOptions, keyring::get_signing_key, registry::find_url};
+use cargo_component_core::{
+    command::CommonOptions,
+    git::{self, GitProvenance},
+    oci::is_oci_url,
+    registry::{find_namespace_registry, find_url, Dependency},
+    signing::{SigningKeyOptions, SigningKeyProvider},
+    static_registry::is_static_url,
+};
+use cargo_metadata::{Metadata, Package};
 use clap::Args;
-use std::path::PathBuf;
+use semver::Version;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use toml_edit::{value, Document, Item};
 use warg_client::RegistryUrl;
-use warg_crypto::signing::PrivateKey;
+use warg_protocol::registry::PackageId;
 
 /// Publish a package to a registry.
 #[derive(Args)]
@@ -38,6 +54,10 @@ pub struct PublishCommand {
     #[clap(long = "package", short = 'p', value_name = "SPEC")]
     pub cargo_package: Option<CargoPackageSpec>,
 
+    /// Publish all component packages in the workspace in dependency order
+    #[clap(long = "workspace", conflicts_with = "cargo_package")]
+    pub workspace: bool,
+
     /// Path to Cargo.toml
     #[clap(long = "manifest-path", value_name = "PATH")]
     pub manifest_path: Option<PathBuf>,
@@ -66,9 +86,32 @@ pub struct PublishCommand {
     #[clap(long = "dry-run")]
     pub dry_run: bool,
 
-    /// The key name to use for the signing key.
-    #[clap(long, short, value_name = "KEY", default_value = "default")]
-    pub key_name: String,
+    /// The key name to use for the signing key; defaults to `default`.
+    #[clap(long, short, value_name = "KEY")]
+    pub key_name: Option<String>,
+
+    /// The path to a file containing the signing key to use.
+    #[clap(
+        long = "key-file",
+        value_name = "PATH",
+        conflicts_with = "signing_command"
+    )]
+    pub key_file: Option<PathBuf>,
+
+    /// An external signing command holding the signing key to use.
+    #[clap(long = "signing-command", value_name = "COMMAND")]
+    pub signing_command: Option<String>,
+
+    /// The path to an encrypted keystore containing the signing key to use.
+    ///
+    /// The passphrase of the keystore is read from the
+    /// `SIGNING_KEYSTORE_PASSPHRASE` environment variable.
+    #[clap(
+        long = "keystore",
+        value_name = "PATH",
+        conflicts_with_all = ["key_file", "signing_command"]
+    )]
+    pub keystore: Option<PathBuf>,
 
     /// The registry to publish to.
     #[clap(long = "registry", value_name = "REGISTRY")]
@@ -77,6 +120,30 @@ pub struct PublishCommand {
     /// Initialize a new package in the registry.
     #[clap(long = "init")]
     pub init: bool,
+
+    /// Also write the bill of materials embedded in the component to a file
+    #[clap(long = "sbom", value_name = "PATH", conflicts_with = "workspace")]
+    pub sbom: Option<PathBuf>,
+
+    /// Build the component reproducibly
+    #[clap(long = "reproducible")]
+    pub reproducible: bool,
+
+    /// Build the component twice and fail if the builds are not identical (implies `--reproducible`)
+    #[clap(long = "verify-reproducible")]
+    pub verify_reproducible: bool,
+
+    /// Embed a signature of the component made with the signing key
+    #[clap(long = "embed-signature")]
+    pub embed_signature: bool,
+
+    /// Embed the git commit and repository of the package in the component
+    #[clap(long = "provenance-git")]
+    pub provenance_git: bool,
+
+    /// Allow publishing with uncommitted changes (requires `--provenance-git`)
+    #[clap(long = "allow-dirty", requires = "provenance_git")]
+    pub allow_dirty: bool,
 }
 
 impl PublishCommand {
@@ -93,29 +160,99 @@ impl PublishCommand {
         }
 
         let metadata = load_metadata(self.manifest_path.as_deref())?;
-        let packages = [PackageComponentMetadata::new(
-            if let Some(spec) = &self.cargo_package {
-                metadata
-                    .packages
-                    .iter()
-                    .find(|p| {
-                        p.name == spec.name
-                            && match spec.version.as_ref() {
-                                Some(v) => &p.version == v,
-                                None => true,
-                            }
-                    })
-                    .with_context(|| {
-                        format!("package ID specification `{spec}` did not match any packages")
-                    })?
-            } else {
-                metadata
-                    .root_package()
-                    .context("no root package found in manifest")?
-            },
-        )?];
-
-        let package = packages[0].package;
+        if self.workspace {
+            return self.publish_workspace(&config, metadata).await;
+        }
+
+        let package = if let Some(spec) = &self.cargo_package {
+            metadata
+                .packages
+                .iter()
+                .find(|p| {
+                    p.name == spec.name
+                        && match spec.version.as_ref() {
+                            Some(v) => &p.version == v,
+                            None => true,
+                        }
+                })
+                .with_context(|| {
+                    format!("package ID specification `{spec}` did not match any packages")
+                })?
+        } else {
+            metadata
+                .root_package()
+                .context("no root package found in manifest")?
+        };
+
+        self.publish_package(&config, &metadata, package, self.cargo_package.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Publishes the component packages of the workspace in dependency order.
+    ///
+    /// After each package is published, the version requirements of workspace
+    /// members depending on it are updated to the published version.
+    async fn publish_workspace(&self, config: &Config, mut metadata: Metadata) -> Result<()> {
+        let order = publish_order(&metadata)?;
+        if order.is_empty() {
+            bail!("no component packages with a `package.metadata.component.package` setting were found in the workspace");
+        }
+
+        for (index, name) in order.iter().enumerate() {
+            // Reload the metadata as previous publishes may have updated the manifests
+            if index > 0 {
+                metadata = load_metadata(self.manifest_path.as_deref())?;
+            }
+
+            let package = metadata
+                .workspace_packages()
+                .into_iter()
+                .find(|p| &p.name == name)
+                .with_context(|| format!("workspace member `{name}` was not found"))?;
+
+            let spec = CargoPackageSpec::new(name.clone())?;
+            let (id, version) = self
+                .publish_package(config, &metadata, package, Some(&spec))
+                .await?;
+
+            if self.dry_run {
+                continue;
+            }
+
+            for dependent in metadata
+                .workspace_packages()
+                .into_iter()
+                .filter(|p| order[index + 1..].contains(&p.name))
+            {
+                let path = dependent.manifest_path.as_std_path();
+                if update_dependency_versions(path, &id, &version)? {
+                    config.terminal().status(
+                        "Updating",
+                        format!(
+                            "dependency `{id}` of package `{name}` to v{version}",
+                            name = dependent.name
+                        ),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds and publishes a single package.
+    ///
+    /// Returns the id and version of the published package.
+    async fn publish_package(
+        &self,
+        config: &Config,
+        metadata: &Metadata,
+        package: &Package,
+        spec: Option<&CargoPackageSpec>,
+    ) -> Result<(PackageId, Version)> {
+        let packages = [PackageComponentMetadata::new(metadata, package)?];
         let component_metadata = packages[0].metadata.as_ref().with_context(|| {
             format!(
                 "package `{name}` is missing component metadata in manifest `{path}`",
@@ -132,20 +269,58 @@ impl PublishCommand {
             )
         })?;
 
+        let registry = self
+            .registry
+            .as_deref()
+            .or_else(|| find_namespace_registry(&component_metadata.section.namespaces, id));
         let registry_url = find_url(
-            self.registry.as_deref(),
+            registry,
             &component_metadata.section.registries,
             config.warg().default_url.as_deref(),
         )?;
 
-        let signing_key = if let Ok(key) = std::env::var("CARGO_COMPONENT_PUBLISH_KEY") {
-            PrivateKey::decode(key).context("failed to parse signing key from `CARGO_COMPONENT_PUBLISH_KEY` environment variable")?
+        if is_static_url(registry_url) {
+            bail!("publishing to static registry `{url}` is not supported; upload the package and add it to the package's `index.json` instead", url = registry_url);
+        }
+
+        // OCI registries do not use signing keys unless a signature is embedded
+        let signer = if is_oci_url(registry_url) && !self.embed_signature {
+            None
         } else {
+            // Signing keys for OCI registries are stored for the HTTPS URL of the registry
             let url: RegistryUrl = registry_url
+                .replacen("oci+http://", "http://", 1)
+                .replacen("oci://", "https://", 1)
                 .parse()
                 .with_context(|| format!("failed to parse registry URL `{registry_url}`"))?;
 
-            get_signing_key(&url, &self.key_name)?
+            let command = self
+                .signing_command
+                .clone()
+                .or_else(|| std::env::var("CARGO_COMPONENT_SIGNING_COMMAND").ok());
+
+            Some(
+                SigningKeyProvider::select(
+                    SigningKeyOptions {
+                        key_file: self.key_file.as_deref(),
+                        command: command.as_deref(),
+                        keystore: self.keystore.as_deref(),
+                        key_name: self.key_name.as_deref(),
+                    },
+                    component_metadata
+                        .section
+                        .signing_keys
+                        .get(registry.unwrap_or("default")),
+                    "CARGO_COMPONENT_PUBLISH_KEY",
+                )
+                .signer(&url)?,
+            )
+        };
+
+        let provenance = if self.provenance_git {
+            Some(self.git_provenance(config, package)?)
+        } else {
+            None
         };
 
         let cargo_build_args = CargoArguments {
@@ -157,15 +332,21 @@ impl PublishCommand {
             frozen: self.frozen,
             locked: self.locked,
             release: true,
+            profile: None,
             offline: self.offline,
             workspace: false,
-            packages: self.cargo_package.clone().into_iter().collect(),
+            packages: spec.cloned().into_iter().collect(),
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            bins: Vec::new(),
+            all_bins: false,
         };
 
-        let spawn_args = self.build_args()?;
+        let spawn_args = self.build_args(spec)?;
         let outputs = run_cargo_command(
-            &config,
-            &metadata,
+            config,
+            metadata,
             &packages,
             Some("build"),
             &cargo_build_args,
@@ -179,6 +360,34 @@ impl PublishCommand {
             );
         }
 
+        if self.verify_reproducible {
+            self.verify_reproducible_build(
+                config,
+                metadata,
+                &packages,
+                package,
+                &cargo_build_args,
+                &spawn_args,
+                &outputs[0],
+            )
+            .await?;
+        }
+
+        let sbom = generate_sbom(config, metadata, &packages[0], &cargo_build_args).await?;
+        if let Some(path) = &self.sbom {
+            fs::write(path, &sbom).with_context(|| {
+                format!(
+                    "failed to write bill of materials `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            config.terminal().status(
+                "Created",
+                format!("bill of materials `{path}`", path = path.display()),
+            )?;
+        }
+
         let options = PublishOptions {
             package,
             registry_url,
@@ -186,14 +395,162 @@ impl PublishCommand {
             id,
             version: &component_metadata.version,
             path: &outputs[0],
-            signing_key: &signing_key,
+            signer: signer.as_ref(),
+            embed_signature: self.embed_signature,
+            sbom: Some(&sbom),
+            provenance: provenance.as_ref(),
             dry_run: self.dry_run,
         };
 
-        publish(&config, &options).await
+        publish(config, &options).await?;
+
+        Ok((id.clone(), component_metadata.version.clone()))
     }
 
-    fn build_args(&self) -> Result<Vec<String>> {
+    /// Gets the version control state of the given package.
+    ///
+    /// Like `cargo publish`, packages with uncommitted changes are refused
+    /// unless `--allow-dirty` is given.
+    fn git_provenance(&self, config: &Config, package: &Package) -> Result<GitProvenance> {
+        let dir = package
+            .manifest_path
+            .parent()
+            .expect("manifest path should have a parent")
+            .as_std_path();
+
+        let provenance = git::provenance(dir)?.with_context(|| {
+            format!(
+                "package `{name}` is not in a git repository, which `--provenance-git` requires",
+                name = package.name
+            )
+        })?;
+
+        if !provenance.dirty.is_empty() {
+            if !self.allow_dirty {
+                bail!(
+                    "{count} files in the working directory of package `{name}` contain changes that were not yet committed into git:\n\n{files}\n\nto proceed despite this and record the commit as dirty, pass the `--allow-dirty` flag",
+                    count = provenance.dirty.len(),
+                    name = package.name,
+                    files = provenance.dirty.join("\n")
+                );
+            }
+
+            config.terminal().warn(format!(
+                "publishing package `{name}` with uncommitted changes to commit {commit}",
+                name = package.name,
+                commit = provenance.commit
+            ))?;
+        }
+
+        Ok(provenance)
+    }
+
+    /// Rebuilds the package from a clean state and verifies that the
+    /// component is identical to the one previously built.
+    #[allow(clippy::too_many_arguments)]
+    async fn verify_reproducible_build(
+        &self,
+        config: &Config,
+        metadata: &Metadata,
+        packages: &[PackageComponentMetadata<'_>],
+        package: &Package,
+        cargo_build_args: &CargoArguments,
+        spawn_args: &[String],
+        output: &Path,
+    ) -> Result<()> {
+        let first = fs::read(output).with_context(|| {
+            format!("failed to read component `{path}`", path = output.display())
+        })?;
+
+        config.terminal().status(
+            "Verifying",
+            format!(
+                "that package `{name}` builds reproducibly",
+                name = package.name
+            ),
+        )?;
+
+        self.clean_package(package)?;
+
+        run_cargo_command(
+            config,
+            metadata,
+            packages,
+            Some("build"),
+            cargo_build_args,
+            spawn_args,
+        )
+        .await?;
+
+        let second = fs::read(output).with_context(|| {
+            format!("failed to read component `{path}`", path = output.display())
+        })?;
+
+        if first != second {
+            let offset = first
+                .iter()
+                .zip(&second)
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| first.len().min(second.len()));
+
+            bail!(
+                "package `{name}` does not build reproducibly: component `{path}` differs between builds at offset {offset} ({first} bytes vs. {second} bytes)",
+                name = package.name,
+                path = output.display(),
+                first = first.len(),
+                second = second.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Removes the release build artifacts of the given package so that the
+    /// next build compiles it again.
+    fn clean_package(&self, package: &Package) -> Result<()> {
+        let cargo = std::env::var("CARGO")
+            .map(PathBuf::from)
+            .ok()
+            .unwrap_or_else(|| PathBuf::from("cargo"));
+
+        let mut cmd = Command::new(&cargo);
+        cmd.arg("clean")
+            .arg("--release")
+            .arg("--package")
+            .arg(&package.name)
+            .arg("--manifest-path")
+            .arg(package.manifest_path.as_std_path())
+            .arg("--target")
+            .arg(self.target.as_deref().unwrap_or("wasm32-wasi"));
+
+        if let Some(target_dir) = &self.target_dir {
+            cmd.arg("--target-dir").arg(target_dir);
+        }
+
+        if self.common.quiet {
+            cmd.arg("-q");
+        }
+
+        log::debug!(
+            "spawning cargo `{cargo}` to clean package `{name}`",
+            cargo = cargo.display(),
+            name = package.name
+        );
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to spawn `{cargo}`", cargo = cargo.display()))?;
+        if !status.success() {
+            bail!(
+                "failed to clean the build artifacts of package `{name}`",
+                name = package.name
+            );
+        }
+
+        Ok(())
+    }
+
+    fn build_args(&self, spec: Option<&CargoPackageSpec>) -> Result<Vec<String>> {
         let mut args = Vec::new();
         args.push("build".to_string());
         args.push("--release".to_string());
@@ -242,7 +599,7 @@ impl PublishCommand {
             args.push("--locked".to_string());
         }
 
-        if let Some(spec) = &self.cargo_package {
+        if let Some(spec) = spec {
             args.push("--package".to_string());
             args.push(spec.to_string());
         }
@@ -285,6 +642,179 @@ impl PublishCommand {
             args.push(jobs.to_string());
         }
 
+        if self.reproducible || self.verify_reproducible {
+            args.push("--reproducible".to_string());
+        }
+
         Ok(args)
     }
 }
+
+/// Gets the names of the workspace component packages to publish in dependency order.
+fn publish_order(metadata: &Metadata) -> Result<Vec<String>> {
+    // Map the registry package ids of the members to the member names
+    let mut members = HashMap::new();
+    for package in metadata.workspace_packages() {
+        let metadata = match PackageComponentMetadata::new(metadata, package)?.metadata {
+            Some(metadata) => metadata,
+            None => continue,
+        };
+
+        if let Some(id) = metadata.section.package.clone() {
+            members.insert(id, (package.name.clone(), metadata));
+        }
+    }
+
+    let mut order = Vec::with_capacity(members.len());
+    let mut visited = HashSet::new();
+    let mut visiting = HashSet::new();
+
+    fn visit<'a>(
+        id: &'a PackageId,
+        members: &'a HashMap<PackageId, (String, ComponentMetadata)>,
+        visited: &mut HashSet<&'a PackageId>,
+        visiting: &mut HashSet<&'a PackageId>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if visited.contains(id) {
+            return Ok(());
+        }
+
+        if !visiting.insert(id) {
+            bail!("workspace component package `{id}` has a cyclic dependency");
+        }
+
+        let (name, metadata) = &members[id];
+        let dependencies = metadata.section.dependencies.iter().chain(
+            match &metadata.section.target {
+                Target::Local { dependencies, .. } => Some(dependencies),
+                _ => None,
+            }
+            .into_iter()
+            .flatten(),
+        );
+
+        for (key, dependency) in dependencies {
+            if let Dependency::Package(package) = dependency {
+                let dep_id = package.id.as_ref().unwrap_or(key);
+                if let Some((dep_id, _)) = members.get_key_value(dep_id) {
+                    visit(dep_id, members, visited, visiting, order)?;
+                }
+            }
+        }
+
+        if let Target::Package { id: target, .. } = &metadata.section.target {
+            if let Some((target, _)) = members.get_key_value(target) {
+                visit(target, members, visited, visiting, order)?;
+            }
+        }
+
+        visiting.remove(id);
+        visited.insert(id);
+        order.push(name.clone());
+        Ok(())
+    }
+
+    // Visit the members in a stable order
+    let mut ids: Vec<_> = members.keys().collect();
+    ids.sort_by_key(|id| &members[*id].0);
+
+    for id in ids {
+        visit(id, &members, &mut visited, &mut visiting, &mut order)?;
+    }
+
+    Ok(order)
+}
+
+/// Updates the registry dependencies on the given package in a manifest to the given version.
+///
+/// Returns `true` if the manifest was modified.
+fn update_dependency_versions(
+    manifest_path: &Path,
+    id: &PackageId,
+    version: &Version,
+) -> Result<bool> {
+    let contents = fs::read_to_string(manifest_path).with_context(|| {
+        format!(
+            "failed to read manifest file `{path}`",
+            path = manifest_path.display()
+        )
+    })?;
+
+    let mut document: Document = contents.parse().with_context(|| {
+        format!(
+            "failed to parse manifest file `{path}`",
+            path = manifest_path.display()
+        )
+    })?;
+
+    let component = match document
+        .get_mut("package")
+        .and_then(|p| p.get_mut("metadata"))
+        .and_then(|m| m.get_mut("component"))
+    {
+        Some(component) => component,
+        None => return Ok(false),
+    };
+
+    let id = id.to_string();
+    let version = version.to_string();
+    let mut changed = false;
+
+    if let Some(dependencies) = component.get_mut("dependencies") {
+        changed |= update_dependency_table(dependencies, &id, &version);
+    }
+
+    if let Some(dependencies) = component
+        .get_mut("target")
+        .and_then(|t| t.get_mut("dependencies"))
+    {
+        changed |= update_dependency_table(dependencies, &id, &version);
+    }
+
+    if changed {
+        fs::write(manifest_path, document.to_string()).with_context(|| {
+            format!(
+                "failed to write manifest file `{path}`",
+                path = manifest_path.display()
+            )
+        })?;
+    }
+
+    Ok(changed)
+}
+
+fn update_dependency_table(dependencies: &mut Item, id: &str, version: &str) -> bool {
+    let dependencies = match dependencies.as_table_like_mut() {
+        Some(dependencies) => dependencies,
+        None => return false,
+    };
+
+    let mut changed = false;
+    for (key, item) in dependencies.iter_mut() {
+        // The shorthand form of `<id> = "<version>"`
+        if let Some(current) = item.as_str() {
+            if key.get() == id && current != version {
+                *item = value(version);
+                changed = true;
+            }
+            continue;
+        }
+
+        if let Some(table) = item.as_table_like_mut() {
+            let matches = table
+                .get("package")
+                .and_then(Item::as_str)
+                .unwrap_or(key.get())
+                == id
+                && !table.contains_key("path");
+
+            if matches && table.get("version").and_then(Item::as_str) != Some(version) {
+                table.insert("version", value(version));
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
@@ -0,0 +1,72 @@
+use super::info::registry_config;
+use crate::Config;
+use anyhow::Result;
+use cargo_component_core::{command::CommonOptions, info::search, registry::find_url};
+use clap::Args;
+use std::path::PathBuf;
+
+/// Search a registry for packages
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct SearchCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// The name of the registry to search.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The text to search package ids for
+    #[clap(value_name = "QUERY")]
+    pub query: String,
+}
+
+impl SearchCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing search command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let (registries, _) = registry_config(self.manifest_path.as_deref());
+        let url = find_url(
+            self.registry.as_deref(),
+            &registries,
+            config.warg().default_url.as_deref(),
+        )?;
+
+        let results = search(
+            config.warg(),
+            url,
+            &self.query,
+            config.terminal(),
+            !self.offline,
+        )
+        .await?;
+
+        if results.is_empty() {
+            config.terminal().note(format!(
+                "no packages matching `{query}` were found",
+                query = self.query
+            ))?;
+            return Ok(());
+        }
+
+        for result in results {
+            match result.latest {
+                Some(version) => println!("{id} = \"{version}\"", id = result.id),
+                None => println!("{id} (no releases)", id = result.id),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    compat::Externs,
+    config::{CargoArguments, CargoPackageSpec, Config},
+    load_metadata, run_cargo_command, watch, PackageComponentMetadata,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The environment variable used to specify the command that serves components.
+const SERVE_RUNTIME_ENV_VAR: &str = "CARGO_COMPONENT_SERVE_RUNTIME";
+
+/// The default command used to serve components.
+const DEFAULT_SERVE_RUNTIME: &str = "wasmtime serve";
+
+/// The name of the interface exported by components targeting `wasi:http/proxy`.
+const INCOMING_HANDLER_INTERFACE: &str = "wasi:http/incoming-handler";
+
+/// Build a `wasi:http/proxy` component and serve it over HTTP
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct ServeCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The socket address to serve the component on
+    #[clap(long = "addr", value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    pub addr: String,
+
+    /// Rebuild and restart the server whenever the package's inputs change
+    #[clap(long = "watch")]
+    pub watch: bool,
+
+    /// Build artifacts in release mode, with optimizations
+    #[clap(long = "release", short = 'r')]
+    pub release: bool,
+
+    /// Cargo package to serve (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub cargo_package: Option<CargoPackageSpec>,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+}
+
+impl ServeCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing serve command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = if let Some(spec) = &self.cargo_package {
+            metadata
+                .packages
+                .iter()
+                .find(|p| {
+                    p.name == spec.name
+                        && match spec.version.as_ref() {
+                            Some(v) => &p.version == v,
+                            None => true,
+                        }
+                })
+                .with_context(|| {
+                    format!("package ID specification `{spec}` did not match any packages")
+                })?
+        } else {
+            metadata
+                .root_package()
+                .context("no root package found in manifest")?
+        };
+
+        let packages = [PackageComponentMetadata::new(&metadata, package)?];
+        let cargo_build_args = CargoArguments {
+            color: self.common.color,
+            verbose: self.common.verbose as usize,
+            quiet: self.common.quiet,
+            targets: Vec::new(),
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            release: self.release,
+            profile: None,
+            offline: self.offline,
+            workspace: false,
+            packages: self.cargo_package.clone().into_iter().collect(),
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            bins: Vec::new(),
+            all_bins: false,
+        };
+
+        let runtime = std::env::var(SERVE_RUNTIME_ENV_VAR)
+            .unwrap_or_else(|_| DEFAULT_SERVE_RUNTIME.to_string());
+        let spawn_args = self.build_args()?;
+
+        // The server is restarted with the rebuilt component after every
+        // successful build
+        if self.watch {
+            let name = package
+                .targets
+                .iter()
+                .find(|t| t.kind.iter().any(|k| k == "cdylib"))
+                .map(|t| t.name.replace('-', "_"))
+                .with_context(|| {
+                    format!(
+                        "package `{name}` does not have a `cdylib` library target",
+                        name = package.name
+                    )
+                })?;
+
+            let path = metadata
+                .target_directory
+                .join("wasm32-wasi")
+                .join(cargo_build_args.profile_dir())
+                .join(format!("{name}.wasm"))
+                .into_std_path_buf();
+
+            let exec = format!(
+                "{runtime} --addr {addr} {path}",
+                addr = self.addr,
+                path = shell_quote(&path)?
+            );
+
+            return watch::watch(
+                &config,
+                Some("build"),
+                &cargo_build_args,
+                &spawn_args,
+                Some(&exec),
+            )
+            .await;
+        }
+
+        let outputs = run_cargo_command(
+            &config,
+            &metadata,
+            &packages,
+            Some("build"),
+            &cargo_build_args,
+            &spawn_args,
+        )
+        .await?;
+        if outputs.len() != 1 {
+            bail!(
+                "expected one output from `cargo build`, got {len}",
+                len = outputs.len()
+            );
+        }
+
+        let path = &outputs[0];
+        check_proxy(path)?;
+
+        let mut args = runtime.split_whitespace();
+        let program = args
+            .next()
+            .with_context(|| format!("environment variable `{SERVE_RUNTIME_ENV_VAR}` is empty"))?;
+
+        config.terminal().status(
+            "Serving",
+            format!(
+                "component `{path}` on http://{addr}",
+                path = path.display(),
+                addr = self.addr
+            ),
+        )?;
+
+        let mut cmd = Command::new(program);
+        cmd.args(args).arg("--addr").arg(&self.addr).arg(path);
+
+        log::debug!("spawning command {cmd:?}");
+
+        let status = cmd.status().with_context(|| {
+            format!("failed to spawn `{program}`; set `{SERVE_RUNTIME_ENV_VAR}` to the command that serves components")
+        })?;
+
+        if !status.success() {
+            std::process::exit(status.code().unwrap_or(1));
+        }
+
+        Ok(())
+    }
+
+    fn build_args(&self) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        args.push("build".to_string());
+
+        if self.release {
+            args.push("--release".to_string());
+        }
+
+        if self.common.quiet {
+            args.push("-q".to_string());
+        }
+
+        args.extend(
+            std::iter::repeat("-v")
+                .take(self.common.verbose as usize)
+                .map(ToString::to_string),
+        );
+
+        if let Some(color) = self.common.color {
+            args.push("--color".to_string());
+            args.push(color.to_string());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        if let Some(spec) = &self.cargo_package {
+            args.push("--package".to_string());
+            args.push(spec.to_string());
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(
+                manifest_path
+                    .as_os_str()
+                    .to_str()
+                    .with_context(|| {
+                        format!(
+                            "manifest path `{path}` is not valid UTF-8",
+                            path = manifest_path.display()
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        if self.offline {
+            args.push("--offline".to_string());
+        }
+
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+
+        Ok(args)
+    }
+}
+
+/// Checks that a component exports the incoming handler of `wasi:http/proxy`.
+fn check_proxy(path: &Path) -> Result<()> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+    let externs = Externs::from_component(&bytes)
+        .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+
+    if !externs.exports.iter().any(|name| {
+        name == INCOMING_HANDLER_INTERFACE
+            || name.starts_with(&format!("{INCOMING_HANDLER_INTERFACE}@"))
+    }) {
+        bail!(
+            "component `{path}` does not export `{INCOMING_HANDLER_INTERFACE}`; only components targeting the `wasi:http/proxy` world can be served",
+            path = path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Quotes a path for use in a command run by the system shell.
+fn shell_quote(path: &Path) -> Result<String> {
+    let path = path.to_str().with_context(|| {
+        format!(
+            "component path `{path}` is not valid UTF-8",
+            path = path.display()
+        )
+    })?;
+
+    if cfg!(windows) {
+        Ok(format!("\"{path}\""))
+    } else {
+        Ok(format!("'{path}'", path = path.replace('\'', r"'\''")))
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    config::{CargoArguments, CargoPackageSpec},
+    load_component_metadata, load_metadata, stub, stub_exports, Config, PackageComponentMetadata,
+};
+use anyhow::{Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Generate stub implementations for the unimplemented exports of a component's target world
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct StubCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Print the stubs instead of adding them to the crate's sources
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to generate stubs for (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The name of the target to generate stubs for; defaults to the
+    /// package's target
+    #[clap(value_name = "TARGET")]
+    pub target: Option<String>,
+}
+
+impl StubCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing stub command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let package = match &self.spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                &metadata,
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+            )?,
+        };
+
+        let cargo_args = CargoArguments {
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            offline: self.offline,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            ..Default::default()
+        };
+
+        let stubs = stub_exports(
+            &config,
+            &metadata,
+            &package,
+            self.target.as_deref(),
+            &cargo_args,
+        )
+        .await?;
+
+        if stubs.is_empty() {
+            config
+                .terminal()
+                .note("every export of the target world is already implemented")?;
+            return Ok(());
+        }
+
+        if self.dry_run {
+            for stub in &stubs {
+                println!(
+                    "// {description} in `{path}`{source}",
+                    description = stub.description,
+                    path = stub.path.display(),
+                    source = stub.source
+                );
+            }
+
+            return Ok(());
+        }
+
+        stub::apply(&stubs)?;
+
+        for stub in &stubs {
+            config.terminal().status(
+                "Stubbed",
+                format!(
+                    "{description} in `{path}`",
+                    description = stub.description,
+                    path = stub.path.display()
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
-use crate::{load_component_metadata, load_metadata, Config};
-use anyhow::Result;
+use crate::{load_component_metadata, load_metadata, Config, UpdateOptions};
+use anyhow::{bail, Result};
 use cargo_component_core::command::CommonOptions;
 use clap::Args;
+use semver::Version;
 use std::path::PathBuf;
+use warg_protocol::registry::PackageId;
 
 /// Update dependencies as recorded in the component lock file
 #[derive(Args)]
@@ -31,12 +33,25 @@ pub struct UpdateCommand {
     /// Run without accessing the network
     #[clap(long = "offline")]
     pub offline: bool,
+
+    /// Registry package to update; all dependencies are updated if not specified
+    #[clap(long = "package", short = 'p', value_name = "PACKAGE")]
+    pub packages: Vec<PackageId>,
+
+    /// Update a single package to exactly this version
+    #[clap(long = "precise", value_name = "VERSION", requires = "packages")]
+    pub precise: Option<Version>,
 }
 
 impl UpdateCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
         log::debug!("executing update command");
+
+        if self.precise.is_some() && self.packages.len() != 1 {
+            bail!("`--precise` requires exactly one package to be specified with `--package`");
+        }
+
         let config = Config::new(self.common.new_terminal())?;
         let metadata = load_metadata(self.manifest_path.as_deref())?;
         let packages = load_component_metadata(&metadata, [].iter(), true)?;
@@ -50,7 +65,11 @@ impl UpdateCommand {
             network_allowed,
             lock_update_allowed,
             self.locked,
-            self.dry_run,
+            &UpdateOptions {
+                packages: self.packages,
+                precise: self.precise,
+                dry_run: self.dry_run,
+            },
         )
         .await
     }
@@ -0,0 +1,63 @@
+use crate::{load_component_metadata, load_metadata, Config};
+use anyhow::Result;
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Vendor all registry dependencies for offline builds
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct VendorCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The directory to vendor dependencies into, relative to the workspace root
+    #[clap(value_name = "PATH", default_value = "vendor/components")]
+    pub path: PathBuf,
+}
+
+impl VendorCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing vendor command");
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(&metadata, [].iter(), true)?;
+
+        let network_allowed = !self.frozen && !self.offline;
+        let lock_update_allowed = !self.frozen && !self.locked;
+        crate::vendor_dependencies(
+            &config,
+            &metadata,
+            &packages,
+            &self.path,
+            network_allowed,
+            lock_update_allowed,
+            self.locked,
+        )
+        .await?;
+
+        config.terminal().note(
+            "build with `--offline` to use the vendored dependencies without accessing the network",
+        )?;
+
+        Ok(())
+    }
+}
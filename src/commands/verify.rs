@@ -0,0 +1,119 @@
+use super::info::component_section;
+use crate::{signature, Config};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryPackage},
+    VersionedPackageId,
+};
+use clap::Args;
+use semver::VersionReq;
+use std::{fs, path::PathBuf};
+use warg_crypto::signing::PublicKey;
+
+/// Verify the signature embedded in a component package from a registry
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct VerifyCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// The name of the registry to download the package from.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// A public key trusted to sign the package, in addition to the configured trusted keys (see `cargo component key export --public`)
+    #[clap(long = "key", value_name = "PUBLIC_KEY")]
+    pub keys: Vec<String>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// The package to verify, with an optional version requirement (e.g. `wasi:http@0.2`)
+    #[clap(value_name = "PACKAGE")]
+    pub package: VersionedPackageId,
+}
+
+impl VerifyCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing verify command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let section = component_section(self.manifest_path.as_deref()).unwrap_or_default();
+
+        let id = &self.package.id;
+        let mut keys = self
+            .keys
+            .iter()
+            .map(|k| {
+                k.parse::<PublicKey>()
+                    .with_context(|| format!("failed to parse public key `{k}`"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        keys.extend(section.trusted_keys(id)?);
+
+        if keys.is_empty() {
+            bail!(
+                "no trusted keys were given for package `{id}`: pass them with `--key` or configure them in `[package.metadata.component.trusted-keys]`"
+            );
+        }
+
+        let dependency = Dependency::Package(RegistryPackage {
+            id: Some(id.clone()),
+            version: self.package.version.clone().unwrap_or(VersionReq::STAR),
+            registry: self.registry.clone(),
+            interfaces: Vec::new(),
+        });
+
+        let mut resolver = DependencyResolver::new(
+            config.warg(),
+            &section.registries,
+            None,
+            config.terminal(),
+            !self.offline,
+        )?
+        .with_namespaces(&section.namespaces);
+        resolver.add_dependency(id, &dependency).await?;
+
+        let resolution = resolver
+            .resolve()
+            .await?
+            .into_values()
+            .next()
+            .expect("expected a resolution");
+
+        let version = match &resolution {
+            DependencyResolution::Registry(registry) => &registry.version,
+            _ => unreachable!(),
+        };
+
+        let path = resolution.path();
+        let bytes = fs::read(path).with_context(|| {
+            format!(
+                "failed to read the contents of package `{id}` from `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let embedded = signature::verify(&bytes, &keys).with_context(|| {
+            format!("failed to verify the signature of package `{id}` v{version}")
+        })?;
+
+        config.terminal().status(
+            "Verified",
+            format!(
+                "package `{id}` v{version} was signed by key `{key}`",
+                key = embedded.key_id
+            ),
+        )?;
+
+        Ok(())
+    }
+}
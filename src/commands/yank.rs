@@ -0,0 +1,135 @@
+use crate::{load_metadata, Config, PackageComponentMetadata};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    oci::is_oci_url,
+    registry::{find_namespace_registry, find_url, yank},
+    signing::SigningKeyProvider,
+    static_registry::is_static_url,
+};
+use clap::Args;
+use semver::Version;
+use std::path::PathBuf;
+use warg_client::RegistryUrl;
+
+/// Yank a release of a package from a registry
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct YankCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package whose release should be yanked (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub cargo_package: Option<String>,
+
+    /// The registry to yank the release from.
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<String>,
+
+    /// The key name to use for the signing key.
+    #[clap(long, short, value_name = "KEY", default_value = "default")]
+    pub key_name: String,
+
+    /// An external signing command holding the signing key to use.
+    #[clap(long = "signing-command", value_name = "COMMAND")]
+    pub signing_command: Option<String>,
+
+    /// Undo a previous yank of the release
+    #[clap(long = "undo")]
+    pub undo: bool,
+
+    /// The version of the release to yank
+    #[clap(value_name = "VERSION")]
+    pub version: Version,
+}
+
+impl YankCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing yank command");
+
+        let config = Config::new(self.common.new_terminal())?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+
+        let package = match &self.cargo_package {
+            Some(name) => metadata
+                .workspace_packages()
+                .into_iter()
+                .find(|p| &p.name == name)
+                .with_context(|| format!("package `{name}` was not found in the workspace"))?,
+            None => metadata
+                .root_package()
+                .context("no root package found in manifest")?,
+        };
+
+        let component_metadata = PackageComponentMetadata::new(&metadata, package)?
+            .metadata
+            .with_context(|| {
+                format!(
+                    "package `{name}` is missing component metadata in manifest `{path}`",
+                    name = package.name,
+                    path = package.manifest_path
+                )
+            })?;
+
+        let id = component_metadata.section.package.as_ref().with_context(|| {
+            format!(
+                "package `{name}` is missing a `package.metadata.component.package` setting in manifest `{path}`",
+                name = package.name,
+                path = package.manifest_path
+            )
+        })?;
+
+        if self.undo {
+            bail!("component registries do not support undoing the yank of a release; publish a new version of package `{id}` instead");
+        }
+
+        let url = find_url(
+            self.registry
+                .as_deref()
+                .or_else(|| find_namespace_registry(&component_metadata.section.namespaces, id)),
+            &component_metadata.section.registries,
+            config.warg().default_url.as_deref(),
+        )?;
+
+        if is_oci_url(url) {
+            bail!("yanking releases from OCI registry `{url}` is not supported");
+        }
+
+        if is_static_url(url) {
+            bail!("yanking releases from static registry `{url}` is not supported; mark the version as yanked in the package's `index.json` instead");
+        }
+
+        let registry_url: RegistryUrl = url
+            .parse()
+            .with_context(|| format!("failed to parse registry URL `{url}`"))?;
+
+        let command = self
+            .signing_command
+            .clone()
+            .or_else(|| std::env::var("CARGO_COMPONENT_SIGNING_COMMAND").ok());
+
+        let signing_key = SigningKeyProvider::new(
+            command.as_deref(),
+            "CARGO_COMPONENT_PUBLISH_KEY",
+            &self.key_name,
+        )
+        .signing_key(&registry_url)?;
+
+        yank(
+            config.warg(),
+            url,
+            id,
+            &self.version,
+            &signing_key,
+            config.terminal(),
+        )
+        .await
+    }
+}
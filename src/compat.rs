@@ -0,0 +1,196 @@
+//! Module for checking a component against the world of a host.
+//!
+//! A host is described by a world: its imports are what the host provides to
+//! components and its exports are what the host expects components to
+//! export (e.g. `wasi:cli/run` for the `wasi:cli/command` world).
+//!
+//! Imports and exports are matched by name. An interface at a different
+//! version satisfies a requirement if the versions are semver compatible and
+//! the provided version is not older than the required one; the types of the
+//! matched items are not compared.
+
+use anyhow::{Context, Result};
+use semver::Version;
+use std::collections::BTreeSet;
+use wasmparser::{Parser, Payload};
+use wit_parser::{Resolve, WorldId, WorldKey};
+
+/// The names of the imports and exports of a world or component.
+#[derive(Debug, Default)]
+pub struct Externs {
+    /// The names of the imports.
+    pub imports: BTreeSet<String>,
+    /// The names of the exports.
+    pub exports: BTreeSet<String>,
+}
+
+impl Externs {
+    /// Gets the imports and exports of a world.
+    pub fn from_world(resolve: &Resolve, world: WorldId) -> Self {
+        let name = |key: &WorldKey| match key {
+            WorldKey::Name(name) => name.clone(),
+            WorldKey::Interface(id) => resolve.id_of(*id).expect("interface should have a name"),
+        };
+
+        let world = &resolve.worlds[world];
+        Self {
+            imports: world.imports.keys().map(name).collect(),
+            exports: world.exports.keys().map(name).collect(),
+        }
+    }
+
+    /// Gets the top-level imports and exports of a component.
+    pub fn from_component(bytes: &[u8]) -> Result<Self> {
+        let mut externs = Self::default();
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(bytes) {
+            match payload.context("failed to parse component")? {
+                Payload::Version { .. } => depth += 1,
+                Payload::End(_) => depth -= 1,
+                Payload::ComponentImportSection(reader) if depth == 1 => {
+                    for import in reader {
+                        let import = import.context("failed to parse component import")?;
+                        externs.imports.insert(import.name.0.to_string());
+                    }
+                }
+                Payload::ComponentExportSection(reader) if depth == 1 => {
+                    for export in reader {
+                        let export = export.context("failed to parse component export")?;
+                        externs.exports.insert(export.name.0.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(externs)
+    }
+}
+
+/// Represents a reason a component is not compatible with a host.
+#[derive(Debug)]
+pub enum Incompatibility {
+    /// The component imports something the host doesn't provide.
+    Import {
+        /// The name of the import.
+        name: String,
+        /// The names of other versions of the import the host provides.
+        provided: Vec<String>,
+    },
+    /// The host expects an export the component doesn't have.
+    Export {
+        /// The name of the export.
+        name: String,
+        /// The names of other versions of the export the component has.
+        provided: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (message, provided) = match self {
+            Self::Import { name, provided } => (
+                format!("the host does not provide import `{name}`"),
+                provided,
+            ),
+            Self::Export { name, provided } => (
+                format!("the host expects export `{name}`, which the component does not have"),
+                provided,
+            ),
+        };
+
+        write!(f, "{message}")?;
+        if !provided.is_empty() {
+            write!(
+                f,
+                " (found incompatible {list})",
+                list = provided
+                    .iter()
+                    .map(|p| format!("`{p}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks the imports and exports of a component against a host's world.
+///
+/// Returns the reasons the component is incompatible with the host, if any.
+pub fn check(component: &Externs, host: &Externs) -> Vec<Incompatibility> {
+    let mut incompatibilities = Vec::new();
+
+    for name in &component.imports {
+        if let Err(provided) = find(&host.imports, name) {
+            incompatibilities.push(Incompatibility::Import {
+                name: name.clone(),
+                provided,
+            });
+        }
+    }
+
+    for name in &host.exports {
+        if let Err(provided) = find(&component.exports, name) {
+            incompatibilities.push(Incompatibility::Export {
+                name: name.clone(),
+                provided,
+            });
+        }
+    }
+
+    incompatibilities
+}
+
+/// Finds a name in a set that satisfies the required name.
+///
+/// On failure, returns the names of the other versions in the set.
+fn find(set: &BTreeSet<String>, required: &str) -> Result<(), Vec<String>> {
+    if set.contains(required) {
+        return Ok(());
+    }
+
+    let (base, version) = split_version(required);
+    let others: Vec<_> = set
+        .iter()
+        .filter(|name| split_version(name).0 == base)
+        .collect();
+
+    let compatible = |provided: &str| match (&version, split_version(provided).1) {
+        (Some(required), Some(provided)) => is_compatible(required, &provided),
+        _ => false,
+    };
+
+    if others.iter().any(|name| compatible(name)) {
+        return Ok(());
+    }
+
+    Err(others.into_iter().cloned().collect())
+}
+
+/// Splits a name into its unversioned name and its version, if any.
+fn split_version(name: &str) -> (&str, Option<Version>) {
+    match name.split_once('@') {
+        Some((base, version)) => (base, version.parse().ok()),
+        None => (name, None),
+    }
+}
+
+/// Determines if a provided version satisfies a required version.
+///
+/// Versions are compatible if they have the same major version (or the same
+/// minor version for `0.x` versions); pre-release versions are only
+/// compatible with themselves.
+fn is_compatible(required: &Version, provided: &Version) -> bool {
+    if !required.pre.is_empty() || !provided.pre.is_empty() {
+        return required == provided;
+    }
+
+    let same_series = match required.major {
+        0 => provided.major == 0 && provided.minor == required.minor,
+        major => provided.major == major,
+    };
+
+    same_series && provided >= required
+}
@@ -20,11 +20,16 @@
 
 use anyhow::{anyhow, bail, Context, Result};
 use cargo_component_core::terminal::{Color, Terminal};
+use cargo_metadata::Package;
 use parse_arg::{iter_short, match_arg};
 use semver::Version;
 use std::fmt;
 use std::str::FromStr;
-use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    path::PathBuf,
+};
 
 /// Represents a cargo package specifier.
 ///
@@ -360,12 +365,24 @@ pub struct CargoArguments {
     pub locked: bool,
     /// The --release argument.
     pub release: bool,
+    /// The --profile argument.
+    pub profile: Option<String>,
     /// The --offline argument.
     pub offline: bool,
     /// The --workspace argument.
     pub workspace: bool,
     /// The --package argument.
     pub packages: Vec<CargoPackageSpec>,
+    /// The --features argument.
+    pub features: Vec<String>,
+    /// The --all-features argument.
+    pub all_features: bool,
+    /// The --no-default-features argument.
+    pub no_default_features: bool,
+    /// The --bin argument.
+    pub bins: Vec<String>,
+    /// The --bins argument.
+    pub all_bins: bool,
 }
 
 impl CargoArguments {
@@ -379,6 +396,38 @@ impl CargoArguments {
         !self.frozen && !self.locked
     }
 
+    /// Determines if the binary target with the given name is selected.
+    ///
+    /// All binaries are selected unless specific ones were requested with
+    /// `--bin`.
+    pub fn bin_selected(&self, name: &str) -> bool {
+        self.all_bins || self.bins.is_empty() || self.bins.iter().any(|b| b == name)
+    }
+
+    /// Determines if the library target is selected.
+    ///
+    /// Selecting specific binaries with `--bin` or `--bins` excludes the
+    /// library target.
+    pub fn lib_selected(&self) -> bool {
+        !self.all_bins && self.bins.is_empty()
+    }
+
+    /// Gets the name of the build profile.
+    pub fn profile(&self) -> &str {
+        self.profile
+            .as_deref()
+            .unwrap_or(if self.release { "release" } else { "dev" })
+    }
+
+    /// Gets the name of the output directory of the build profile.
+    pub fn profile_dir(&self) -> &str {
+        match self.profile() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            profile => profile,
+        }
+    }
+
     /// Parses the arguments from the environment.
     pub fn parse() -> Result<Self> {
         Self::parse_from(std::env::args().skip(1))
@@ -395,11 +444,17 @@ impl CargoArguments {
             .multiple("--package", "SPEC", Some('p'))
             .multiple("--target", "TRIPLE", None)
             .flag("--release", Some('r'))
+            .single("--profile", "PROFILE-NAME", None)
             .flag("--frozen", None)
             .flag("--locked", None)
             .flag("--offline", None)
             .flag("--all", None)
             .flag("--workspace", None)
+            .multiple("--features", "FEATURES", Some('F'))
+            .flag("--all-features", None)
+            .flag("--no-default-features", None)
+            .multiple("--bin", "NAME", None)
+            .flag("--bins", None)
             .counting("--verbose", Some('v'))
             .flag("--quiet", Some('q'));
 
@@ -443,6 +498,7 @@ impl CargoArguments {
             locked: args.get("--locked").unwrap().count() > 0,
             offline: args.get("--offline").unwrap().count() > 0,
             release: args.get("--release").unwrap().count() > 0,
+            profile: args.get_mut("--profile").unwrap().take_single(),
             workspace: args.get("--workspace").unwrap().count() > 0
                 || args.get("--all").unwrap().count() > 0,
             packages: args
@@ -452,8 +508,340 @@ impl CargoArguments {
                 .into_iter()
                 .map(CargoPackageSpec::new)
                 .collect::<Result<_>>()?,
+            features: args
+                .get_mut("--features")
+                .unwrap()
+                .take_multiple()
+                .iter()
+                .flat_map(|f| f.split([',', ' ']))
+                .filter(|f| !f.is_empty())
+                .map(str::to_string)
+                .collect(),
+            all_features: args.get("--all-features").unwrap().count() > 0,
+            no_default_features: args.get("--no-default-features").unwrap().count() > 0,
+            bins: args.get_mut("--bin").unwrap().take_multiple(),
+            all_bins: args.get("--bins").unwrap().count() > 0,
         })
     }
+
+    /// Gets the set of features enabled for the given package.
+    ///
+    /// Features enabled by other features are included in the set.
+    pub fn enabled_features(&self, package: &Package) -> HashSet<String> {
+        if self.all_features {
+            return package.features.keys().cloned().collect();
+        }
+
+        let mut pending: Vec<&str> = self
+            .features
+            .iter()
+            .flat_map(|f| f.split([',', ' ']))
+            .filter(|f| !f.is_empty())
+            .filter_map(|f| match f.split_once('/') {
+                Some((name, feature)) if name == package.name => Some(feature),
+                Some(_) => None,
+                None => Some(f),
+            })
+            .collect();
+
+        if !self.no_default_features {
+            pending.push("default");
+        }
+
+        let mut enabled = HashSet::new();
+        while let Some(feature) = pending.pop() {
+            let Some(implied) = package.features.get(feature) else {
+                continue;
+            };
+
+            if enabled.insert(feature.to_string()) {
+                // Only follow features of this package (e.g. not `dep:` or `crate/feature`)
+                pending.extend(
+                    implied
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|f| !f.starts_with("dep:") && !f.contains('/')),
+                );
+            }
+        }
+
+        enabled
+    }
+}
+
+/// Represents the arguments `cargo-component` accepts for running components.
+///
+/// These arguments are specific to `cargo-component` and are removed from
+/// the arguments passed to cargo for the `run` and `test` commands.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct RunArguments {
+    /// The --dir arguments, of the form `HOST_DIR[::GUEST_DIR]`.
+    pub dirs: Vec<String>,
+    /// The --env arguments, of the form `NAME[=VALUE]`.
+    pub envs: Vec<String>,
+    /// The --inherit-network argument.
+    pub inherit_network: bool,
+    /// The --config-var arguments, of the form `KEY=VALUE`.
+    ///
+    /// These override the configuration values of the component and are not
+    /// part of the runtime arguments.
+    pub config_vars: Vec<String>,
+}
+
+impl RunArguments {
+    /// Extracts the run arguments from the given arguments.
+    ///
+    /// Returns the run arguments and the remaining arguments to pass to cargo.
+    pub fn extract<T>(iter: impl Iterator<Item = T>) -> Result<(Self, Vec<String>)>
+    where
+        T: Into<String>,
+    {
+        let mut run_args = Self::default();
+        let mut remaining = Vec::new();
+        let mut iter = iter.map(Into::into);
+
+        while let Some(arg) = iter.next() {
+            // Stop processing at the first `--`
+            if arg == "--" {
+                remaining.push(arg);
+                break;
+            }
+
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+
+            let values = match name {
+                "--dir" => &mut run_args.dirs,
+                "--env" => &mut run_args.envs,
+                "--config-var" => &mut run_args.config_vars,
+                "--inherit-network" => {
+                    if value.is_some() {
+                        bail!("argument `--inherit-network` does not take a value");
+                    }
+
+                    run_args.inherit_network = true;
+                    continue;
+                }
+                _ => {
+                    remaining.push(arg);
+                    continue;
+                }
+            };
+
+            match value.or_else(|| iter.next()) {
+                Some(value) => values.push(value),
+                None => bail!("argument `{name}` expects a value"),
+            }
+        }
+
+        for var in &run_args.config_vars {
+            if !matches!(var.split_once('='), Some((key, _)) if !key.is_empty()) {
+                bail!("argument `--config-var` expects a value of the form `KEY=VALUE`, but found `{var}`");
+            }
+        }
+
+        remaining.extend(iter);
+        Ok((run_args, remaining))
+    }
+
+    /// Gets the configuration values specified with `--config-var`.
+    pub fn config_vars(&self) -> BTreeMap<String, String> {
+        self.config_vars
+            .iter()
+            .filter_map(|var| var.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Gets the arguments to pass to the runtime for the run arguments.
+    pub fn runtime_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for dir in &self.dirs {
+            args.push("--dir".to_string());
+            args.push(dir.clone());
+        }
+
+        for env in &self.envs {
+            args.push("--env".to_string());
+            args.push(env.clone());
+        }
+
+        if self.inherit_network {
+            args.push("-S".to_string());
+            args.push("inherit-network".to_string());
+        }
+
+        args
+    }
+}
+
+/// Represents the arguments `cargo-component` accepts for building components.
+///
+/// These arguments are specific to `cargo-component` and are removed from
+/// the arguments passed to cargo for the `build` command.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct BuildArguments {
+    /// Whether `--emit core-wasm` was specified.
+    ///
+    /// When set, the core module of each component is written alongside the
+    /// component as `<name>.core.wasm`, along with the adapter used to
+    /// componentize it as `<name>.adapter.wasm`.
+    pub emit_core_wasm: bool,
+    /// Whether `--watch` was specified.
+    ///
+    /// When set, the packages are rebuilt whenever their sources, target
+    /// worlds, or local dependencies change.
+    pub watch: bool,
+    /// The command to run after each successful build in watch mode.
+    pub exec: Option<String>,
+    /// Whether `--reproducible` was specified.
+    ///
+    /// When set, the paths of the build machine are remapped in the
+    /// compiler output and the metadata added to each component does not
+    /// depend on the build environment.
+    pub reproducible: bool,
+    /// Whether `--minimize-imports` was specified.
+    ///
+    /// When set, the function imports that the core module of each
+    /// component never references are removed before it is componentized.
+    pub minimize_imports: bool,
+    /// The format of the size report specified with `--size-report`, if any.
+    ///
+    /// When set, a breakdown of the size of each component by section is
+    /// printed after it is created.
+    pub size_report: Option<SizeReportFormat>,
+    /// What to do with the DWARF debug information specified with
+    /// `--debug-info`, if any.
+    ///
+    /// When set, this takes precedence over the `debug-info` setting of the
+    /// profile or binary target in `Cargo.toml`.
+    pub debug_info: Option<DebugInfo>,
+    /// The path specified with `--manifest-out`, if any.
+    ///
+    /// When set, a deployment manifest describing the capabilities required
+    /// by each built component is written to the path.
+    pub manifest_out: Option<PathBuf>,
+}
+
+/// What to do with the DWARF debug information of a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugInfo {
+    /// The debug information is kept in the component.
+    Keep,
+    /// The debug information is removed from the component.
+    Strip,
+    /// The debug information is moved to a separate debug file.
+    Split,
+}
+
+/// The format of a component size report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeReportFormat {
+    /// A human-readable table.
+    Text,
+    /// A JSON object per component.
+    Json,
+}
+
+impl BuildArguments {
+    /// Extracts the build arguments from the given arguments.
+    ///
+    /// Returns the build arguments and the remaining arguments to pass to cargo.
+    pub fn extract<T>(iter: impl Iterator<Item = T>) -> Result<(Self, Vec<String>)>
+    where
+        T: Into<String>,
+    {
+        let mut build_args = Self::default();
+        let mut remaining = Vec::new();
+        let mut iter = iter.map(Into::into);
+
+        while let Some(arg) = iter.next() {
+            // Stop processing at the first `--`
+            if arg == "--" {
+                remaining.push(arg);
+                break;
+            }
+
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+
+            match name {
+                "--emit" | "--exec" | "--debug-info" | "--manifest-out" => {}
+                "--size-report" => {
+                    // The format is optional, so it must be given with `=`
+                    build_args.size_report = Some(match value.as_deref() {
+                        None | Some("text") => SizeReportFormat::Text,
+                        Some("json") => SizeReportFormat::Json,
+                        Some(format) => bail!(
+                            "unsupported `--size-report` format `{format}`; expected `text` or `json`"
+                        ),
+                    });
+                    continue;
+                }
+                "--watch" | "--reproducible" | "--minimize-imports" => {
+                    if value.is_some() {
+                        bail!("argument `{name}` does not take a value");
+                    }
+
+                    match name {
+                        "--watch" => build_args.watch = true,
+                        "--reproducible" => build_args.reproducible = true,
+                        _ => build_args.minimize_imports = true,
+                    }
+                    continue;
+                }
+                _ => {
+                    remaining.push(arg);
+                    continue;
+                }
+            }
+
+            let value = match value.or_else(|| iter.next()) {
+                Some(value) => value,
+                None => bail!("argument `{name}` expects a value"),
+            };
+
+            if name == "--exec" {
+                build_args.exec = Some(value);
+                continue;
+            }
+
+            if name == "--manifest-out" {
+                build_args.manifest_out = Some(value.into());
+                continue;
+            }
+
+            if name == "--debug-info" {
+                build_args.debug_info = Some(match value.as_str() {
+                    "keep" => DebugInfo::Keep,
+                    "strip" => DebugInfo::Strip,
+                    "split" => DebugInfo::Split,
+                    _ => bail!(
+                        "unsupported `--debug-info` value `{value}`; expected `keep`, `strip`, or `split`"
+                    ),
+                });
+                continue;
+            }
+
+            for kind in value.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+                match kind {
+                    "core-wasm" => build_args.emit_core_wasm = true,
+                    _ => bail!("unsupported `--emit` kind `{kind}`; expected `core-wasm`"),
+                }
+            }
+        }
+
+        if build_args.exec.is_some() && !build_args.watch {
+            bail!("argument `--exec` requires `--watch`");
+        }
+
+        remaining.extend(iter);
+        Ok((build_args, remaining))
+    }
 }
 
 /// Configuration information for cargo-component.
@@ -757,11 +1145,17 @@ mod test {
                 targets: Vec::new(),
                 manifest_path: None,
                 release: false,
+                profile: None,
                 frozen: false,
                 locked: false,
                 offline: false,
                 workspace: true,
                 packages: Vec::new(),
+                features: Vec::new(),
+                all_features: false,
+                no_default_features: false,
+                bins: Vec::new(),
+                all_bins: false,
             }
         );
 
@@ -774,6 +1168,7 @@ mod test {
                 "--manifest-path",
                 "Cargo.toml",
                 "--release",
+                "--profile=custom",
                 "--package",
                 "package1",
                 "-p=package2@1.1.1",
@@ -785,6 +1180,15 @@ mod test {
                 "--locked",
                 "--offline",
                 "--all",
+                "--features",
+                "foo,bar",
+                "-F=baz",
+                "--all-features",
+                "--no-default-features",
+                "--bin",
+                "foo",
+                "--bin=bar",
+                "--bins",
                 "--not-an-option",
             ]
             .into_iter(),
@@ -799,6 +1203,7 @@ mod test {
                 targets: vec!["foo".to_string(), "bar".to_string()],
                 manifest_path: Some("Cargo.toml".into()),
                 release: true,
+                profile: Some("custom".to_string()),
                 frozen: true,
                 locked: true,
                 offline: true,
@@ -813,7 +1218,158 @@ mod test {
                         version: Some(Version::parse("1.1.1").unwrap())
                     }
                 ],
+                features: vec!["foo".to_string(), "bar".to_string(), "baz".to_string()],
+                all_features: true,
+                no_default_features: true,
+                bins: vec!["foo".to_string(), "bar".to_string()],
+                all_bins: true,
+            }
+        );
+        assert_eq!(args.profile(), "custom");
+        assert_eq!(args.profile_dir(), "custom");
+    }
+
+    #[test]
+    fn it_extracts_run_arguments() {
+        let (args, remaining) = RunArguments::extract(
+            [
+                "component",
+                "run",
+                "--dir",
+                "foo::/bar",
+                "--release",
+                "--env=FOO=bar",
+                "--inherit-network",
+                "--env",
+                "BAZ",
+                "--config-var",
+                "greeting=hello=world",
+                "--",
+                "--dir",
+                "baz",
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            args,
+            RunArguments {
+                dirs: vec!["foo::/bar".to_string()],
+                envs: vec!["FOO=bar".to_string(), "BAZ".to_string()],
+                inherit_network: true,
+                config_vars: vec!["greeting=hello=world".to_string()],
             }
         );
+
+        assert_eq!(
+            args.config_vars(),
+            BTreeMap::from([("greeting".to_string(), "hello=world".to_string())])
+        );
+
+        assert_eq!(
+            remaining,
+            ["component", "run", "--release", "--", "--dir", "baz"]
+        );
+
+        assert_eq!(
+            args.runtime_args(),
+            [
+                "--dir",
+                "foo::/bar",
+                "--env",
+                "FOO=bar",
+                "--env",
+                "BAZ",
+                "-S",
+                "inherit-network"
+            ]
+        );
+
+        assert!(RunArguments::extract(["run", "--dir"].into_iter()).is_err());
+        assert!(RunArguments::extract(["run", "--config-var", "foo"].into_iter()).is_err());
+        assert!(RunArguments::extract(["run", "--config-var=bar"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn it_extracts_build_arguments() {
+        let (args, remaining) = BuildArguments::extract(
+            ["component", "build", "--emit", "core-wasm", "--release"].into_iter(),
+        )
+        .unwrap();
+
+        assert!(args.emit_core_wasm);
+        assert_eq!(remaining, ["component", "build", "--release"]);
+
+        let (args, remaining) =
+            BuildArguments::extract(["build", "--", "--emit=core-wasm"].into_iter()).unwrap();
+
+        assert!(!args.emit_core_wasm);
+        assert_eq!(remaining, ["build", "--", "--emit=core-wasm"]);
+
+        assert!(BuildArguments::extract(["build", "--emit"].into_iter()).is_err());
+        assert!(BuildArguments::extract(["build", "--emit=asm"].into_iter()).is_err());
+
+        let (args, remaining) = BuildArguments::extract(
+            ["build", "--watch", "--exec", "wasmtime serve", "-p", "foo"].into_iter(),
+        )
+        .unwrap();
+
+        assert!(args.watch);
+        assert_eq!(args.exec.as_deref(), Some("wasmtime serve"));
+        assert_eq!(remaining, ["build", "-p", "foo"]);
+
+        assert!(BuildArguments::extract(["build", "--watch=yes"].into_iter()).is_err());
+        assert!(BuildArguments::extract(["build", "--exec=true"].into_iter()).is_err());
+
+        let (args, remaining) =
+            BuildArguments::extract(["build", "--reproducible", "--release"].into_iter()).unwrap();
+
+        assert!(args.reproducible);
+        assert_eq!(remaining, ["build", "--release"]);
+
+        assert!(BuildArguments::extract(["build", "--reproducible=1"].into_iter()).is_err());
+
+        let (args, remaining) =
+            BuildArguments::extract(["build", "--minimize-imports", "-p", "foo"].into_iter())
+                .unwrap();
+
+        assert!(args.minimize_imports);
+        assert_eq!(remaining, ["build", "-p", "foo"]);
+
+        let (args, remaining) =
+            BuildArguments::extract(["build", "--size-report", "--release"].into_iter()).unwrap();
+
+        assert_eq!(args.size_report, Some(SizeReportFormat::Text));
+        assert_eq!(remaining, ["build", "--release"]);
+
+        let (args, _) =
+            BuildArguments::extract(["build", "--size-report=json"].into_iter()).unwrap();
+
+        assert_eq!(args.size_report, Some(SizeReportFormat::Json));
+        assert!(BuildArguments::extract(["build", "--size-report=xml"].into_iter()).is_err());
+
+        let (args, remaining) =
+            BuildArguments::extract(["build", "--debug-info", "split", "--release"].into_iter())
+                .unwrap();
+
+        assert_eq!(args.debug_info, Some(DebugInfo::Split));
+        assert_eq!(remaining, ["build", "--release"]);
+
+        let (args, _) =
+            BuildArguments::extract(["build", "--debug-info=strip"].into_iter()).unwrap();
+
+        assert_eq!(args.debug_info, Some(DebugInfo::Strip));
+        assert!(BuildArguments::extract(["build", "--debug-info"].into_iter()).is_err());
+        assert!(BuildArguments::extract(["build", "--debug-info=full"].into_iter()).is_err());
+
+        let (args, remaining) = BuildArguments::extract(
+            ["build", "--manifest-out", "deploy.yaml", "--release"].into_iter(),
+        )
+        .unwrap();
+
+        assert_eq!(args.manifest_out, Some(PathBuf::from("deploy.yaml")));
+        assert_eq!(remaining, ["build", "--release"]);
+        assert!(BuildArguments::extract(["build", "--manifest-out"].into_iter()).is_err());
     }
 }
@@ -0,0 +1,1033 @@
+//! Module for generating conveniences for the types of the Rust bindings.
+//!
+//! The conveniences are configured per type in the
+//! `package.metadata.component.bindings.types` section of `Cargo.toml`. The
+//! generated source is written to the bindings directory of the package,
+//! where the `generate!` macro includes it in the `bindings` module, and is
+//! appended to the output of `cargo component bindings`.
+
+use crate::metadata::{Bindings, Ownership, TypeOptions};
+use anyhow::{bail, Context, Result};
+use heck::{ToShoutySnakeCase, ToUpperCamelCase};
+use semver::Version;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+};
+use wit_bindgen_rust::to_rust_ident;
+use wit_parser::{
+    Handle, InterfaceId, Resolve, Type, TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem,
+    WorldKey,
+};
+
+/// The name of the file in the bindings directory with the conveniences of
+/// every type of the target world.
+pub const CONVENIENCES_FILE: &str = "conveniences.rs";
+
+/// The name of the file in the bindings directory with only the conveniences
+/// of imported types, for use by a bindings crate.
+pub const IMPORT_CONVENIENCES_FILE: &str = "import-conveniences.rs";
+
+/// Generates the conveniences configured for the types of the given world.
+///
+/// If `exports` is false, conveniences are only generated for imported types.
+///
+/// Returns an empty string if no conveniences are configured.
+pub fn generate(
+    resolve: &Resolve,
+    world: WorldId,
+    bindings: &Bindings,
+    exports: bool,
+) -> Result<String> {
+    let types: Vec<_> = bindings
+        .types
+        .iter()
+        .filter(|(_, options)| !options.is_empty())
+        .collect();
+
+    if types.is_empty() {
+        return Ok(String::new());
+    }
+
+    if let Some(ownership) = bindings.ownership {
+        if ownership != Ownership::Owning {
+            bail!(
+                "the options of `package.metadata.component.bindings.types` require the `owning` ownership model, but `{ownership}` is used"
+            );
+        }
+    }
+
+    let mut generator = Generator::new(resolve, world, exports, bindings.no_std.is_some());
+    for (key, options) in types {
+        generator
+            .generate(key, options)
+            .with_context(|| format!("failed to generate the bindings options of type `{key}`"))?;
+    }
+
+    Ok(generator.finish())
+}
+
+/// Represents a location of a WIT type in the generated bindings.
+///
+/// The types of an interface that is both imported and exported are
+/// generated at two locations.
+struct Location {
+    /// The interface the location is exported from, if any.
+    export: Option<InterfaceId>,
+    /// The module path of the type relative to the `bindings` module.
+    module: Vec<String>,
+    /// The Rust name of the type.
+    name: String,
+}
+
+impl Location {
+    /// Gets the path to the type from a module with the given path prefix.
+    fn path(&self, prefix: &str) -> String {
+        let mut path = prefix.to_string();
+        for segment in &self.module {
+            write!(path, "::{segment}").unwrap();
+        }
+        write!(path, "::{name}", name = self.name).unwrap();
+        path
+    }
+}
+
+/// A tree of the builder modules, mirroring the modules of the bindings.
+#[derive(Default)]
+struct BuilderModule {
+    children: BTreeMap<String, BuilderModule>,
+    source: String,
+}
+
+impl BuilderModule {
+    fn print(&self, source: &mut String) {
+        source.push_str(&self.source);
+        for (name, child) in &self.children {
+            writeln!(source, "pub mod {name} {{").unwrap();
+            child.print(source);
+            source.push_str("}\n");
+        }
+    }
+}
+
+struct Generator<'a> {
+    resolve: &'a Resolve,
+    world: WorldId,
+    exports: bool,
+    imported: HashSet<InterfaceId>,
+    exported: HashSet<InterfaceId>,
+    keys: HashMap<String, TypeId>,
+    errors: HashSet<TypeId>,
+    conversions: HashSet<(String, String)>,
+    builders: BuilderModule,
+    source: String,
+    no_std: bool,
+}
+
+impl<'a> Generator<'a> {
+    fn new(resolve: &'a Resolve, world: WorldId, exports: bool, no_std: bool) -> Self {
+        let w = &resolve.worlds[world];
+        let interfaces = |items: &indexmap::IndexMap<WorldKey, WorldItem>| {
+            items
+                .values()
+                .filter_map(|item| match item {
+                    WorldItem::Interface(id) => Some(*id),
+                    _ => None,
+                })
+                .collect::<HashSet<_>>()
+        };
+
+        let imported = interfaces(&w.imports);
+        let exported = interfaces(&w.exports);
+
+        // Map the names used in the manifest to the types of the world
+        let mut keys = HashMap::new();
+        for id in imported.iter().chain(exported.iter()) {
+            let interface = &resolve.interfaces[*id];
+            let prefix = match (interface.package, &interface.name) {
+                (Some(package), Some(name)) => resolve.packages[package].name.interface_id(name),
+                _ => match Self::world_key_name(&w.imports, *id)
+                    .or_else(|| Self::world_key_name(&w.exports, *id))
+                {
+                    Some(name) => name.to_string(),
+                    None => continue,
+                },
+            };
+
+            for (name, ty) in &interface.types {
+                keys.insert(format!("{prefix}/{name}"), *ty);
+            }
+        }
+
+        for (key, item) in &w.imports {
+            if let (WorldKey::Name(name), WorldItem::Type(ty)) = (key, item) {
+                keys.insert(name.clone(), *ty);
+            }
+        }
+
+        // The bindings generator already implements `Display` and `Error` for
+        // types used as the error of a function result
+        let mut errors = HashSet::new();
+        let functions = w
+            .imports
+            .values()
+            .chain(w.exports.values())
+            .flat_map(|item| match item {
+                WorldItem::Function(f) => vec![f],
+                WorldItem::Interface(id) => resolve.interfaces[*id].functions.values().collect(),
+                WorldItem::Type(_) => Vec::new(),
+            });
+
+        for func in functions {
+            for ty in func.results.iter_types() {
+                if let Type::Id(id) = ty {
+                    if let TypeDefKind::Result(r) = &resolve.types[*id].kind {
+                        if let Some(Type::Id(err)) = r.err {
+                            errors.insert(err);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            resolve,
+            world,
+            exports,
+            imported,
+            exported,
+            keys,
+            errors,
+            conversions: HashSet::new(),
+            builders: BuilderModule::default(),
+            source: String::new(),
+            no_std,
+        }
+    }
+
+    fn world_key_name(
+        items: &indexmap::IndexMap<WorldKey, WorldItem>,
+        id: InterfaceId,
+    ) -> Option<&str> {
+        items.iter().find_map(|(key, item)| match (key, item) {
+            (WorldKey::Name(name), WorldItem::Interface(i)) if *i == id => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Gets the attribute gating implementations of `std::error::Error`.
+    ///
+    /// Like the bindings, `no_std` bindings only implement it when the
+    /// crate's `std` feature is enabled.
+    fn std_cfg(&self) -> &'static str {
+        if self.no_std {
+            "#[cfg(feature = \"std\")]\n"
+        } else {
+            ""
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut source = String::from(
+            "// Conveniences generated from `package.metadata.component.bindings.types`.\n",
+        );
+        source.push_str(&self.source);
+
+        if !self.builders.children.is_empty() || !self.builders.source.is_empty() {
+            source.push_str(
+                "pub mod builders {\n\
+                 /// The error returned when building a record without setting one of its required fields.\n\
+                 #[derive(Debug, Clone, Copy, PartialEq, Eq)]\n\
+                 pub struct MissingField(pub &'static str);\n\
+                 impl ::core::fmt::Display for MissingField {\n\
+                 fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {\n\
+                 write!(f, \"missing required field `{}`\", self.0)\n\
+                 }\n\
+                 }\n",
+            );
+            source.push_str(self.std_cfg());
+            source.push_str("impl ::std::error::Error for MissingField {}\n");
+            self.builders.print(&mut source);
+            source.push_str("}\n");
+        }
+
+        source
+    }
+
+    fn generate(&mut self, key: &str, options: &TypeOptions) -> Result<()> {
+        let id = *self
+            .keys
+            .get(key)
+            .with_context(|| format!("type `{key}` was not found in the target world"))?;
+
+        let locations = self.locations(id);
+        if locations.is_empty() {
+            // The type only exists in the exports, which are not generated
+            return Ok(());
+        }
+
+        for location in &locations {
+            if options.default {
+                self.default(key, id, location)?;
+            }
+
+            if options.error {
+                self.error(key, id, location)?;
+            }
+
+            if options.builder {
+                self.builder(key, id, location)?;
+            }
+        }
+
+        for version in &options.versions {
+            let other = self.other_version(id, version)?;
+            for from in &locations {
+                for to in self.locations(other) {
+                    let forward = self.convert(id, from, other, &to)?;
+                    let backward = self.convert(other, &to, id, from)?;
+                    if !forward && !backward {
+                        bail!(
+                            "records `{from}` and `{to}` each have required fields the other is missing and cannot be converted",
+                            from = location_path(from),
+                            to = location_path(&to)
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the locations of the given type in the generated bindings.
+    fn locations(&self, id: TypeId) -> Vec<Location> {
+        let ty = &self.resolve.types[id];
+        let name = ty.name.as_deref().unwrap_or_default().to_upper_camel_case();
+        match ty.owner {
+            TypeOwner::World(_) => vec![Location {
+                export: None,
+                module: Vec::new(),
+                name,
+            }],
+            TypeOwner::Interface(interface) => {
+                let mut locations = Vec::new();
+                if self.imported.contains(&interface) {
+                    locations.push(Location {
+                        export: None,
+                        module: self.interface_module(interface, false),
+                        name: name.clone(),
+                    });
+                }
+
+                if self.exports && self.exported.contains(&interface) {
+                    locations.push(Location {
+                        export: Some(interface),
+                        module: self.interface_module(interface, true),
+                        name,
+                    });
+                }
+
+                locations
+            }
+            TypeOwner::None => Vec::new(),
+        }
+    }
+
+    fn interface_module(&self, id: InterfaceId, exported: bool) -> Vec<String> {
+        let interface = &self.resolve.interfaces[id];
+        let mut module = Vec::new();
+        if exported {
+            module.push("exports".to_string());
+        }
+
+        match (interface.package, &interface.name) {
+            (Some(package), Some(name)) => {
+                let package = &self.resolve.packages[package].name;
+                module.push(to_rust_ident(&package.namespace));
+                module.push(to_rust_ident(&package.name));
+                module.push(to_rust_ident(name));
+            }
+            _ => {
+                let world = &self.resolve.worlds[self.world];
+                let items = if exported {
+                    &world.exports
+                } else {
+                    &world.imports
+                };
+                let name = Self::world_key_name(items, id).expect("interface should be in world");
+                module.push(to_rust_ident(name));
+            }
+        }
+
+        module
+    }
+
+    fn default(&mut self, key: &str, id: TypeId, location: &Location) -> Result<()> {
+        let body = match &self.resolve.types[id].kind {
+            TypeDefKind::Record(record) => {
+                let mut body = "Self {\n".to_string();
+                for field in &record.fields {
+                    writeln!(
+                        body,
+                        "{name}: ::core::default::Default::default(),",
+                        name = to_rust_ident(&field.name)
+                    )
+                    .unwrap();
+                }
+                body.push('}');
+                body
+            }
+            TypeDefKind::Enum(e) => format!(
+                "Self::{case}",
+                case = e
+                    .cases
+                    .first()
+                    .context("enum has no cases")?
+                    .name
+                    .to_upper_camel_case()
+            ),
+            TypeDefKind::Variant(v) => {
+                let case = v.cases.first().context("variant has no cases")?;
+                match case.ty {
+                    Some(_) => format!(
+                        "Self::{case}(::core::default::Default::default())",
+                        case = case.name.to_upper_camel_case()
+                    ),
+                    None => format!("Self::{case}", case = case.name.to_upper_camel_case()),
+                }
+            }
+            TypeDefKind::Flags(_) => "Self::empty()".to_string(),
+            kind => bail!(
+                "`default` is only supported for records, variants, enums, and flags, but type `{key}` is a {kind}",
+                kind = kind_name(kind)
+            ),
+        };
+
+        writeln!(
+            self.source,
+            "impl ::core::default::Default for {path} {{\n\
+             fn default() -> Self {{\n\
+             {body}\n\
+             }}\n\
+             }}",
+            path = location.path("self")
+        )
+        .unwrap();
+
+        Ok(())
+    }
+
+    fn error(&mut self, key: &str, id: TypeId, location: &Location) -> Result<()> {
+        let body = match &self.resolve.types[id].kind {
+            // The bindings generator already implements the traits for these types
+            TypeDefKind::Variant(_) | TypeDefKind::Enum(_) if self.errors.contains(&id) => {
+                return Ok(());
+            }
+            TypeDefKind::Record(_) => "write!(f, \"{:?}\", self)".to_string(),
+            TypeDefKind::Enum(e) => {
+                let mut body = "match self {\n".to_string();
+                for case in &e.cases {
+                    writeln!(
+                        body,
+                        "Self::{name} => f.write_str({message:?}),",
+                        name = case.name.to_upper_camel_case(),
+                        message = message(&case.name, case.docs.contents.as_deref())
+                    )
+                    .unwrap();
+                }
+                body.push('}');
+                body
+            }
+            TypeDefKind::Variant(v) => {
+                let mut body = "match self {\n".to_string();
+                for case in &v.cases {
+                    let name = case.name.to_upper_camel_case();
+                    let message = message(&case.name, case.docs.contents.as_deref());
+                    match case.ty {
+                        Some(_) => writeln!(
+                            body,
+                            "Self::{name}(e) => write!(f, \"{{}}: {{:?}}\", {message:?}, e),"
+                        ),
+                        None => writeln!(body, "Self::{name} => f.write_str({message:?}),"),
+                    }
+                    .unwrap();
+                }
+                body.push('}');
+                body
+            }
+            kind => bail!(
+                "`error` is only supported for records, variants, and enums, but type `{key}` is a {kind}",
+                kind = kind_name(kind)
+            ),
+        };
+
+        let path = location.path("self");
+        writeln!(
+            self.source,
+            "impl ::core::fmt::Display for {path} {{\n\
+             fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{\n\
+             {body}\n\
+             }}\n\
+             }}\n\
+             {cfg}impl ::std::error::Error for {path} {{}}",
+            cfg = self.std_cfg()
+        )
+        .unwrap();
+
+        Ok(())
+    }
+
+    fn builder(&mut self, key: &str, id: TypeId, location: &Location) -> Result<()> {
+        let record = match &self.resolve.types[id].kind {
+            TypeDefKind::Record(record) => record,
+            kind => bail!(
+                "`builder` is only supported for records, but type `{key}` is a {kind}",
+                kind = kind_name(kind)
+            ),
+        };
+
+        // The builder is in a module of `builders` that mirrors the location of the record
+        let mut module = &mut self.builders;
+        for segment in &location.module {
+            module = module.children.entry(segment.clone()).or_default();
+        }
+
+        let prefix = vec!["super"; location.module.len() + 1].join("::");
+        let builder = format!("{name}Builder", name = location.name);
+
+        let mut fields = String::new();
+        let mut setters = String::new();
+        let mut build = String::new();
+        for field in &record.fields {
+            let name = to_rust_ident(&field.name);
+            let (ty, required) = match field.ty {
+                Type::Id(ty) => match &self.resolve.types[ty].kind {
+                    TypeDefKind::Option(inner) if self.resolve.types[ty].name.is_none() => (
+                        print_type(
+                            self.resolve,
+                            &self.imported,
+                            self.no_std,
+                            inner,
+                            location,
+                            &prefix,
+                        )?,
+                        false,
+                    ),
+                    _ => (
+                        print_type(
+                            self.resolve,
+                            &self.imported,
+                            self.no_std,
+                            &field.ty,
+                            location,
+                            &prefix,
+                        )?,
+                        true,
+                    ),
+                },
+                _ => (
+                    print_type(
+                        self.resolve,
+                        &self.imported,
+                        self.no_std,
+                        &field.ty,
+                        location,
+                        &prefix,
+                    )?,
+                    true,
+                ),
+            };
+
+            writeln!(fields, "{name}: ::core::option::Option<{ty}>,").unwrap();
+            writeln!(
+                setters,
+                "/// Sets the `{field}` field of the record.\n\
+                 pub fn {name}(mut self, value: impl ::core::convert::Into<{ty}>) -> Self {{\n\
+                 self.{name} = ::core::option::Option::Some(value.into());\n\
+                 self\n\
+                 }}",
+                field = field.name
+            )
+            .unwrap();
+
+            if required {
+                writeln!(
+                    build,
+                    "{name}: self.{name}.ok_or({prefix}::builders::MissingField({field:?}))?,",
+                    field = field.name
+                )
+                .unwrap();
+            } else {
+                writeln!(build, "{name}: self.{name},").unwrap();
+            }
+        }
+
+        let record_path = location.path(&prefix);
+        writeln!(
+            module.source,
+            "/// A builder for the `{key}` record.\n\
+             #[derive(Default)]\n\
+             pub struct {builder} {{\n\
+             {fields}\
+             }}\n\
+             impl {builder} {{\n\
+             {setters}\
+             /// Builds the record.\n\
+             ///\n\
+             /// Fails if a field that is not an `option` was not set.\n\
+             pub fn build(self) -> ::core::result::Result<{record_path}, {prefix}::builders::MissingField> {{\n\
+             ::core::result::Result::Ok({record_path} {{\n\
+             {build}\
+             }})\n\
+             }}\n\
+             }}"
+        )
+        .unwrap();
+
+        let mut builder_path = "self::builders".to_string();
+        for segment in &location.module {
+            write!(builder_path, "::{segment}").unwrap();
+        }
+        writeln!(
+            self.source,
+            "impl {path} {{\n\
+             /// Creates a builder for the record.\n\
+             pub fn builder() -> {builder_path}::{builder} {{\n\
+             ::core::default::Default::default()\n\
+             }}\n\
+             }}",
+            path = location.path("self")
+        )
+        .unwrap();
+
+        Ok(())
+    }
+
+    /// Finds the same type in another version of its interface.
+    fn other_version(&self, id: TypeId, version: &str) -> Result<TypeId> {
+        let version: Version = version
+            .parse()
+            .with_context(|| format!("invalid version `{version}`"))?;
+
+        let ty = &self.resolve.types[id];
+        let interface = match ty.owner {
+            TypeOwner::Interface(interface) => &self.resolve.interfaces[interface],
+            _ => bail!("`versions` is only supported for types of interfaces"),
+        };
+
+        let (package, name) = match (interface.package, &interface.name) {
+            (Some(package), Some(name)) => (&self.resolve.packages[package].name, name),
+            _ => bail!("`versions` is only supported for types of interfaces in a package"),
+        };
+
+        let current = package
+            .version
+            .as_ref()
+            .with_context(|| format!("package `{package}` is not versioned"))?;
+
+        let compatible = current.major == version.major
+            && (current.major > 0
+                || (current.minor == version.minor
+                    && (current.minor > 0 || current.patch == version.patch)));
+        if !compatible {
+            bail!("version {version} is not semver-compatible with version {current} of package `{package}`");
+        }
+
+        let other = self
+            .imported
+            .iter()
+            .chain(self.exported.iter())
+            .map(|id| &self.resolve.interfaces[*id])
+            .find(|i| {
+                i.name.as_ref() == Some(name)
+                    && i.package
+                        .map(|p| {
+                            let other = &self.resolve.packages[p].name;
+                            other.namespace == package.namespace
+                                && other.name == package.name
+                                && other.version.as_ref() == Some(&version)
+                        })
+                        .unwrap_or(false)
+            })
+            .with_context(|| {
+                format!(
+                    "version {version} of interface `{namespace}:{package}/{name}` is not in the target world",
+                    namespace = package.namespace,
+                    package = package.name,
+                )
+            })?;
+
+        other
+            .types
+            .get(ty.name.as_deref().unwrap_or_default())
+            .copied()
+            .with_context(|| {
+                format!(
+                    "version {version} of interface `{namespace}:{package}/{name}` has no type named `{ty}`",
+                    namespace = package.namespace,
+                    package = package.name,
+                    ty = ty.name.as_deref().unwrap_or_default()
+                )
+            })
+    }
+
+    /// Generates a conversion from one location of a type to another.
+    ///
+    /// A `From` implementation is generated if every value of the source type
+    /// can be represented by the destination type; otherwise, a `TryFrom`
+    /// implementation that returns the original value on failure is generated.
+    ///
+    /// Returns `false` if the source is a record that is missing a required
+    /// field of the destination record.
+    fn convert(
+        &mut self,
+        from_id: TypeId,
+        from: &Location,
+        to_id: TypeId,
+        to: &Location,
+    ) -> Result<bool> {
+        let from_path = location_path(from);
+        let to_path = location_path(to);
+        if from_path == to_path {
+            if from_id != to_id {
+                bail!(
+                    "types of different versions of an interface are generated at the same path `{from_path}`; either version must be exported to convert between them"
+                );
+            }
+
+            return Ok(true);
+        }
+
+        if !self
+            .conversions
+            .insert((from_path.clone(), to_path.clone()))
+        {
+            return Ok(true);
+        }
+
+        let from_ty = &self.resolve.types[from_id];
+        let to_ty = &self.resolve.types[to_id];
+        let src = from.path("self");
+        let dst = to.path("self");
+
+        let (body, fallible) = match (&from_ty.kind, &to_ty.kind) {
+            (TypeDefKind::Record(a), TypeDefKind::Record(b)) => {
+                let mut body = "Self {\n".to_string();
+                for field in &b.fields {
+                    let name = to_rust_ident(&field.name);
+                    match a.fields.iter().find(|f| f.name == field.name) {
+                        Some(source) => writeln!(
+                            body,
+                            "{name}: {expr},",
+                            expr = convert_expr(self.resolve, &source.ty, &format!("value.{name}"))
+                        )
+                        .unwrap(),
+                        None if is_option(self.resolve, &field.ty) => {
+                            writeln!(body, "{name}: ::core::option::Option::None,").unwrap()
+                        }
+                        None => return Ok(false),
+                    }
+                }
+                body.push('}');
+                (body, false)
+            }
+            (TypeDefKind::Enum(a), TypeDefKind::Enum(b)) => {
+                let mut body = "match value {\n".to_string();
+                let mut fallible = false;
+                for case in &a.cases {
+                    let name = case.name.to_upper_camel_case();
+                    if b.cases.iter().any(|c| c.name == case.name) {
+                        writeln!(body, "{src}::{name} => Self::{name},").unwrap();
+                    } else {
+                        fallible = true;
+                        writeln!(body, "{src}::{name} => return ::core::result::Result::Err(value),")
+                            .unwrap();
+                    }
+                }
+                body.push('}');
+                (body, fallible)
+            }
+            (TypeDefKind::Variant(a), TypeDefKind::Variant(b)) => {
+                let mut body = "match value {\n".to_string();
+                let mut fallible = false;
+                for case in &a.cases {
+                    let name = case.name.to_upper_camel_case();
+                    match (b.cases.iter().find(|c| c.name == case.name), &case.ty) {
+                        (Some(other), Some(ty)) if other.ty.is_some() => writeln!(
+                            body,
+                            "{src}::{name}(e) => Self::{name}({expr}),",
+                            expr = convert_expr(self.resolve, ty, "e")
+                        )
+                        .unwrap(),
+                        (Some(other), None) if other.ty.is_none() => {
+                            writeln!(body, "{src}::{name} => Self::{name},").unwrap()
+                        }
+                        (_, ty) => {
+                            fallible = true;
+                            writeln!(
+                                body,
+                                "other @ {src}::{name}{payload} => return ::core::result::Result::Err(other),",
+                                payload = if ty.is_some() { "(_)" } else { "" }
+                            )
+                            .unwrap();
+                        }
+                    }
+                }
+                body.push('}');
+                (body, fallible)
+            }
+            (TypeDefKind::Flags(a), TypeDefKind::Flags(b)) => {
+                let mut body = String::new();
+                let missing: Vec<_> = a
+                    .flags
+                    .iter()
+                    .filter(|f| !b.flags.iter().any(|o| o.name == f.name))
+                    .map(|f| format!("{src}::{name}", name = f.name.to_shouty_snake_case()))
+                    .collect();
+                if !missing.is_empty() {
+                    writeln!(
+                        body,
+                        "if value.intersects({missing}) {{\n\
+                         return ::core::result::Result::Err(value);\n\
+                         }}",
+                        missing = missing.join(" | ")
+                    )
+                    .unwrap();
+                }
+
+                body.push_str("let mut flags = Self::empty();\n");
+                for flag in a.flags.iter().filter(|f| b.flags.iter().any(|o| o.name == f.name)) {
+                    writeln!(
+                        body,
+                        "if value.contains({src}::{name}) {{\n\
+                         flags |= Self::{name};\n\
+                         }}",
+                        name = flag.name.to_shouty_snake_case()
+                    )
+                    .unwrap();
+                }
+                body.push_str("flags");
+                (body, !missing.is_empty())
+            }
+            (a, b) => bail!(
+                "`versions` is only supported for records, variants, enums, and flags of the same kind, but `{from_path}` is a {a} and `{to_path}` is a {b}",
+                a = kind_name(a),
+                b = kind_name(b)
+            ),
+        };
+
+        if fallible {
+            writeln!(
+                self.source,
+                "impl ::core::convert::TryFrom<{src}> for {dst} {{\n\
+                 type Error = {src};\n\
+                 #[allow(clippy::useless_conversion, unreachable_code)]\n\
+                 fn try_from(value: {src}) -> ::core::result::Result<Self, Self::Error> {{\n\
+                 ::core::result::Result::Ok({{\n\
+                 {body}\n\
+                 }})\n\
+                 }}\n\
+                 }}"
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                self.source,
+                "impl ::core::convert::From<{src}> for {dst} {{\n\
+                 #[allow(clippy::useless_conversion)]\n\
+                 fn from(value: {src}) -> Self {{\n\
+                 {body}\n\
+                 }}\n\
+                 }}"
+            )
+            .unwrap();
+        }
+
+        Ok(true)
+    }
+}
+
+fn location_path(location: &Location) -> String {
+    location.path("bindings")
+}
+
+fn is_option(resolve: &Resolve, ty: &Type) -> bool {
+    match ty {
+        Type::Id(id) => match &resolve.types[*id].kind {
+            TypeDefKind::Option(_) => true,
+            TypeDefKind::Type(ty) => is_option(resolve, ty),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Gets the message displayed for a case of an error type.
+///
+/// The first line of the case's documentation is used if present.
+fn message(name: &str, docs: Option<&str>) -> String {
+    docs.and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// Gets an expression that converts a value of the given type with `Into`.
+///
+/// Lists, options, and results are converted element-wise.
+fn convert_expr(resolve: &Resolve, ty: &Type, expr: &str) -> String {
+    let inner = |ty: &Type, name: &str| convert_expr(resolve, ty, name);
+    match ty {
+        Type::Id(id) if resolve.types[*id].name.is_none() => match &resolve.types[*id].kind {
+            TypeDefKind::List(ty) => format!(
+                "{expr}.into_iter().map(|v| {v}).collect()",
+                v = inner(ty, "v")
+            ),
+            TypeDefKind::Option(ty) => format!("{expr}.map(|v| {v})", v = inner(ty, "v")),
+            TypeDefKind::Result(r) => {
+                let mut expr = expr.to_string();
+                if let Some(ok) = &r.ok {
+                    write!(expr, ".map(|v| {v})", v = inner(ok, "v")).unwrap();
+                }
+                if let Some(err) = &r.err {
+                    write!(expr, ".map_err(|e| {e})", e = inner(err, "e")).unwrap();
+                }
+                expr
+            }
+            TypeDefKind::Type(ty) => inner(ty, expr),
+            _ => format!("{expr}.into()"),
+        },
+        _ => format!("{expr}.into()"),
+    }
+}
+
+/// Prints the Rust type of a field of a type at the given location.
+///
+/// Types of the interface the location is exported from refer to the
+/// exported types; other types refer to the imported types.
+fn print_type(
+    resolve: &Resolve,
+    imported: &HashSet<InterfaceId>,
+    no_std: bool,
+    ty: &Type,
+    location: &Location,
+    prefix: &str,
+) -> Result<String> {
+    let print = |ty: &Type| print_type(resolve, imported, no_std, ty, location, prefix);
+    Ok(match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "i8".to_string(),
+        Type::S16 => "i16".to_string(),
+        Type::S32 => "i32".to_string(),
+        Type::S64 => "i64".to_string(),
+        Type::Float32 => "f32".to_string(),
+        Type::Float64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String if no_std => "::cargo_component_bindings::rt::string::String".to_string(),
+        Type::String => "String".to_string(),
+        Type::Id(id) => {
+            let def = &resolve.types[*id];
+            if let TypeDefKind::Handle(_) | TypeDefKind::Resource = def.kind {
+                bail!("builders are not supported for records with resource fields");
+            }
+
+            if let Some(name) = &def.name {
+                let mut path = prefix.to_string();
+                match def.owner {
+                    TypeOwner::Interface(interface) => {
+                        let exported = location.export == Some(interface);
+                        if !exported && !imported.contains(&interface) {
+                            bail!("type `{name}` is not imported by the target world");
+                        }
+
+                        let interface = &resolve.interfaces[interface];
+                        if exported {
+                            path.push_str("::exports");
+                        }
+
+                        match (interface.package, &interface.name) {
+                            (Some(package), Some(iface)) => {
+                                let package = &resolve.packages[package].name;
+                                write!(
+                                    path,
+                                    "::{namespace}::{package}::{iface}",
+                                    namespace = to_rust_ident(&package.namespace),
+                                    package = to_rust_ident(&package.name),
+                                    iface = to_rust_ident(iface)
+                                )
+                                .unwrap();
+                            }
+                            _ => bail!("builders are not supported for records using types of unnamed interfaces"),
+                        }
+                    }
+                    TypeOwner::World(_) => {}
+                    TypeOwner::None => bail!("type `{name}` has no owner"),
+                }
+
+                write!(path, "::{name}", name = name.to_upper_camel_case()).unwrap();
+                return Ok(path);
+            }
+
+            match &def.kind {
+                TypeDefKind::List(ty) if no_std => format!(
+                    "::cargo_component_bindings::rt::vec::Vec<{ty}>",
+                    ty = print(ty)?
+                ),
+                TypeDefKind::List(ty) => format!("Vec<{ty}>", ty = print(ty)?),
+                TypeDefKind::Option(ty) => format!("Option<{ty}>", ty = print(ty)?),
+                TypeDefKind::Result(r) => format!(
+                    "Result<{ok}, {err}>",
+                    ok =
+                        r.ok.as_ref()
+                            .map(print)
+                            .transpose()?
+                            .unwrap_or_else(|| "()".to_string()),
+                    err = r
+                        .err
+                        .as_ref()
+                        .map(print)
+                        .transpose()?
+                        .unwrap_or_else(|| "()".to_string())
+                ),
+                TypeDefKind::Tuple(t) => format!(
+                    "({types},)",
+                    types = t
+                        .types
+                        .iter()
+                        .map(print)
+                        .collect::<Result<Vec<_>>>()?
+                        .join(", ")
+                ),
+                TypeDefKind::Type(ty) => print(ty)?,
+                kind => bail!(
+                    "builders are not supported for records with fields of anonymous {kind} types",
+                    kind = kind_name(kind)
+                ),
+            }
+        }
+    })
+}
+
+fn kind_name(kind: &TypeDefKind) -> &'static str {
+    match kind {
+        TypeDefKind::Record(_) => "record",
+        TypeDefKind::Resource => "resource",
+        TypeDefKind::Handle(Handle::Own(_)) => "owned handle",
+        TypeDefKind::Handle(Handle::Borrow(_)) => "borrowed handle",
+        TypeDefKind::Flags(_) => "flags",
+        TypeDefKind::Tuple(_) => "tuple",
+        TypeDefKind::Variant(_) => "variant",
+        TypeDefKind::Enum(_) => "enum",
+        TypeDefKind::Option(_) => "option",
+        TypeDefKind::Result(_) => "result",
+        TypeDefKind::List(_) => "list",
+        TypeDefKind::Future(_) => "future",
+        TypeDefKind::Stream(_) => "stream",
+        TypeDefKind::Type(_) => "type alias",
+        TypeDefKind::Unknown => "unknown",
+    }
+}
@@ -0,0 +1,288 @@
+//! Module for generating deployment manifests of components.
+//!
+//! A deployment manifest describes what a built component requires of the
+//! platform it is deployed to: the WASI capabilities and custom host
+//! interfaces it imports, the worlds its exports allow it to be run as, the
+//! configuration values it expects, and hints for its resource limits. The
+//! manifest is written as JSON, or as YAML if its path has a `.yaml` or `.yml`
+//! extension.
+
+use crate::{bindgen, metadata::ComponentMetadata};
+use anyhow::{Context, Result};
+use cargo_metadata::Package;
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeSet, fmt::Write, fs, path::Path};
+use wasmparser::{Parser, Payload};
+use wit_parser::{WorldItem, WorldKey};
+
+/// The size of a page of linear memory, in bytes.
+const PAGE_SIZE: u64 = 65536;
+
+/// The worlds a component may be run as and the export each one requires.
+const KNOWN_WORLDS: &[(&str, &str)] = &[
+    ("wasi:cli/command", "wasi:cli/run"),
+    ("wasi:http/proxy", "wasi:http/incoming-handler"),
+];
+
+/// Represents the deployment manifest of the components of a build.
+#[derive(Debug, Default, Serialize)]
+pub struct DeploymentManifest {
+    /// The manifests of the built components.
+    pub components: Vec<ComponentManifest>,
+}
+
+/// Represents the deployment manifest of a component.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ComponentManifest {
+    /// The path of the component.
+    pub path: String,
+    /// The name of the package the component was built from.
+    pub package: String,
+    /// The version of the package.
+    pub version: String,
+    /// The name of the binary target the component was built from, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bin: Option<String>,
+    /// The WASI packages the component imports (e.g. `wasi:filesystem`).
+    pub capabilities: BTreeSet<String>,
+    /// The imports of the component.
+    pub imports: Vec<Import>,
+    /// The names of the exports of the component.
+    pub exports: Vec<String>,
+    /// The known worlds the exports of the component satisfy.
+    pub worlds: Vec<String>,
+    /// The names of the configuration values of the package.
+    pub config: Vec<String>,
+    /// The resource limit hints of the component.
+    pub limits: Limits,
+}
+
+/// Represents an import of a component.
+#[derive(Debug, Serialize)]
+pub struct Import {
+    /// The name of the import.
+    pub name: String,
+    /// The kind of the import.
+    pub kind: ImportKind,
+}
+
+/// Represents the kind of an import of a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportKind {
+    /// A WASI interface.
+    Wasi,
+    /// A custom interface that the host must provide.
+    Host,
+    /// A function that the host must provide.
+    Function,
+}
+
+/// Represents the resource limit hints of a component.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Limits {
+    /// The size of the component, in bytes.
+    pub size_bytes: u64,
+    /// The total initial size of the linear memories of the component, in
+    /// bytes.
+    pub memory_initial_bytes: u64,
+    /// The total maximum size of the linear memories of the component, in
+    /// bytes; `None` if any memory is unbounded.
+    pub memory_maximum_bytes: Option<u64>,
+}
+
+impl ComponentManifest {
+    /// Creates the deployment manifest of a built component.
+    pub fn new(
+        path: &Path,
+        package: &Package,
+        bin: Option<&str>,
+        metadata: &ComponentMetadata,
+    ) -> Result<Self> {
+        let (resolve, world) = bindgen::decode_component(path)?;
+        let world = &resolve.worlds[world];
+
+        let mut capabilities = BTreeSet::new();
+        let mut imports = Vec::new();
+        for (key, item) in &world.imports {
+            let kind = match (key, item) {
+                (_, WorldItem::Type(_)) => continue,
+                (WorldKey::Interface(id), _) => {
+                    match resolve.interfaces[*id]
+                        .package
+                        .map(|p| &resolve.packages[p].name)
+                    {
+                        Some(name) if name.namespace == "wasi" => {
+                            capabilities.insert(format!(
+                                "{ns}:{name}",
+                                ns = name.namespace,
+                                name = name.name
+                            ));
+                            ImportKind::Wasi
+                        }
+                        _ => ImportKind::Host,
+                    }
+                }
+                (WorldKey::Name(_), WorldItem::Function(_)) => ImportKind::Function,
+                (WorldKey::Name(_), WorldItem::Interface(_)) => ImportKind::Host,
+            };
+
+            imports.push(Import {
+                name: resolve.name_world_key(key),
+                kind,
+            });
+        }
+
+        let exports: Vec<_> = world
+            .exports
+            .iter()
+            .filter(|(_, item)| !matches!(item, WorldItem::Type(_)))
+            .map(|(key, _)| resolve.name_world_key(key))
+            .collect();
+
+        let worlds = KNOWN_WORLDS
+            .iter()
+            .filter_map(|(world, required)| {
+                exports.iter().find_map(|export| {
+                    let (name, version) = match export.split_once('@') {
+                        Some((name, version)) => (name, Some(version)),
+                        None => (export.as_str(), None),
+                    };
+
+                    (name == *required).then(|| match version {
+                        Some(version) => format!("{world}@{version}"),
+                        None => world.to_string(),
+                    })
+                })
+            })
+            .collect();
+
+        let bytes = fs::read(path)
+            .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+
+        Ok(Self {
+            path: path.display().to_string(),
+            package: package.name.clone(),
+            version: package.version.to_string(),
+            bin: bin.map(ToString::to_string),
+            capabilities,
+            imports,
+            exports,
+            worlds,
+            config: metadata.section.config.keys().cloned().collect(),
+            limits: limits(&bytes)?,
+        })
+    }
+}
+
+/// Gets the resource limit hints of a component from its core modules.
+fn limits(component: &[u8]) -> Result<Limits> {
+    let mut limits = Limits {
+        size_bytes: component.len() as u64,
+        memory_initial_bytes: 0,
+        memory_maximum_bytes: Some(0),
+    };
+
+    for payload in Parser::new(0).parse_all(component) {
+        if let Payload::MemorySection(reader) = payload.context("failed to parse component")? {
+            for memory in reader {
+                let memory = memory.context("failed to parse memory type")?;
+                limits.memory_initial_bytes += memory.initial * PAGE_SIZE;
+                limits.memory_maximum_bytes = limits
+                    .memory_maximum_bytes
+                    .zip(memory.maximum)
+                    .map(|(total, maximum)| total + maximum * PAGE_SIZE);
+            }
+        }
+    }
+
+    Ok(limits)
+}
+
+impl DeploymentManifest {
+    /// Writes the deployment manifest to the given path.
+    ///
+    /// The manifest is written as YAML if the path has a `.yaml` or `.yml`
+    /// extension and as JSON otherwise.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let value = serde_json::to_value(self)?;
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => {
+                let mut yaml = String::new();
+                write_yaml(&mut yaml, &value, 0);
+                yaml
+            }
+            _ => serde_json::to_string_pretty(&value)? + "\n",
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{path}`",
+                    path = parent.display()
+                )
+            })?;
+        }
+
+        fs::write(path, contents).with_context(|| {
+            format!(
+                "failed to write deployment manifest `{path}`",
+                path = path.display()
+            )
+        })
+    }
+}
+
+/// Writes a JSON value as YAML.
+///
+/// Strings are written as double-quoted (JSON) scalars, so that no value is
+/// mistaken for another type.
+fn write_yaml(out: &mut String, value: &Value, indent: usize) {
+    fn is_scalar(value: &Value) -> bool {
+        match value {
+            Value::Array(items) => items.is_empty(),
+            Value::Object(fields) => fields.is_empty(),
+            _ => true,
+        }
+    }
+
+    fn scalar(value: &Value) -> String {
+        match value {
+            Value::Array(_) => "[]".to_string(),
+            Value::Object(_) => "{}".to_string(),
+            value => value.to_string(),
+        }
+    }
+
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, value) in fields {
+                if is_scalar(value) {
+                    writeln!(out, "{pad}{key}: {value}", value = scalar(value)).unwrap();
+                } else {
+                    writeln!(out, "{pad}{key}:").unwrap();
+                    write_yaml(out, value, indent + 2);
+                }
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                if is_scalar(item) {
+                    writeln!(out, "{pad}- {item}", item = scalar(item)).unwrap();
+                } else {
+                    // The first line of a nested item follows the dash
+                    let mut nested = String::new();
+                    write_yaml(&mut nested, item, indent + 2);
+                    out.push_str(&pad);
+                    out.push_str("- ");
+                    out.push_str(&nested[indent + 2..]);
+                }
+            }
+        }
+        value => writeln!(out, "{pad}{value}", value = scalar(value)).unwrap(),
+    }
+}
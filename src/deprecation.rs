@@ -0,0 +1,75 @@
+//! Module for the deprecation of WIT items.
+//!
+//! A WIT item is deprecated by a line of its doc comment that starts with
+//! `@deprecated`, optionally followed by a note:
+//!
+//! ```wit
+//! /// Gets the value.
+//! ///
+//! /// @deprecated use `get-value` instead
+//! get: func() -> u32
+//! ```
+//!
+//! The generated Rust bindings of a deprecated item are annotated with
+//! `#[deprecated]`, so that using a deprecated import (or implementing a
+//! deprecated exported interface) produces a warning from the compiler.
+//! As implementing a deprecated exported function does not, the exports of
+//! the target world are also checked when a component is built.
+
+use cargo_component_core::bindings::DEPRECATED_MARKER;
+use std::fmt::Write;
+use wit_parser::{Docs, Resolve, WorldId, WorldItem};
+
+/// Gets the deprecation note of an item from its documentation.
+///
+/// Returns `None` if the item is not deprecated; the note may be empty.
+pub fn deprecation(docs: &Docs) -> Option<&str> {
+    docs.contents
+        .as_deref()?
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(DEPRECATED_MARKER))
+        .map(str::trim)
+}
+
+/// Describes the deprecated items exported by the given world.
+pub fn exported_deprecations(resolve: &Resolve, world: WorldId) -> Vec<String> {
+    fn describe(kind: &str, name: &str, note: &str) -> String {
+        let mut description = format!("{kind} `{name}` is deprecated");
+        if !note.is_empty() {
+            write!(description, ": {note}").unwrap();
+        }
+        description
+    }
+
+    let mut deprecations = Vec::new();
+    for (key, item) in &resolve.worlds[world].exports {
+        match item {
+            WorldItem::Function(func) => {
+                if let Some(note) = deprecation(&func.docs) {
+                    deprecations.push(describe("exported function", &func.name, note));
+                }
+            }
+            WorldItem::Interface(id) => {
+                let interface = &resolve.interfaces[*id];
+                let name = resolve.name_world_key(key);
+
+                if let Some(note) = deprecation(&interface.docs) {
+                    deprecations.push(describe("exported interface", &name, note));
+                }
+
+                for func in interface.functions.values() {
+                    if let Some(note) = deprecation(&func.docs) {
+                        deprecations.push(describe(
+                            "exported function",
+                            &format!("{name}#{func}", func = func.name),
+                            note,
+                        ));
+                    }
+                }
+            }
+            WorldItem::Type(_) => {}
+        }
+    }
+
+    deprecations
+}
@@ -0,0 +1,413 @@
+//! Module for generating HTML documentation of the targeted worlds of
+//! component packages.
+
+use heck::ToSnakeCase;
+use std::fmt::Write;
+use wit_parser::{
+    Docs, Function, FunctionKind, Handle, Interface, InterfaceId, Resolve, Results, Type,
+    TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem, WorldKey,
+};
+
+const STYLE: &str = r#"body { font-family: sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; }
+code, pre { font-family: monospace; }
+pre { background: #f5f5f5; padding: 0.5em; overflow-x: auto; }
+section { margin-left: 1em; }
+.docs { margin-left: 1em; }
+"#;
+
+/// Generates HTML documentation for a world.
+///
+/// Links to the generated bindings are relative to the rustdoc output
+/// directory of the crate.
+pub struct DocGenerator<'a> {
+    resolve: &'a Resolve,
+    world: WorldId,
+    crate_name: &'a str,
+}
+
+impl<'a> DocGenerator<'a> {
+    /// Creates a new documentation generator for the given world.
+    pub fn new(resolve: &'a Resolve, world: WorldId, crate_name: &'a str) -> Self {
+        Self {
+            resolve,
+            world,
+            crate_name,
+        }
+    }
+
+    /// Generates the HTML documentation.
+    pub fn generate(&self) -> String {
+        let world = &self.resolve.worlds[self.world];
+        let name = match world.package {
+            Some(pkg) => format!(
+                "{pkg}/{name}",
+                pkg = self.resolve.packages[pkg].name,
+                name = world.name
+            ),
+            None => world.name.clone(),
+        };
+
+        let mut html = String::new();
+        write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{name} - WIT documentation</title>\n<style>\n{STYLE}</style>\n\
+             </head>\n<body>\n<h1>World <code>{name}</code></h1>\n\
+             <p>Rust bindings: <a href=\"bindings/index.html\"><code>{krate}::bindings</code></a></p>\n",
+            name = escape(&name),
+            krate = escape(self.crate_name),
+        )
+        .unwrap();
+        Self::print_docs(&world.docs, &mut html);
+
+        for (heading, items, export) in [
+            ("Imports", &world.imports, false),
+            ("Exports", &world.exports, true),
+        ] {
+            if items.is_empty() {
+                continue;
+            }
+
+            writeln!(html, "<h2>{heading}</h2>").unwrap();
+            for (key, item) in items {
+                self.print_item(key, item, export, &mut html);
+            }
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn print_item(&self, key: &WorldKey, item: &WorldItem, export: bool, html: &mut String) {
+        match item {
+            WorldItem::Interface(id) => self.print_interface(key, *id, export, html),
+            WorldItem::Function(func) => {
+                html.push_str("<section>\n");
+                self.print_func(func, html);
+                html.push_str("</section>\n");
+            }
+            WorldItem::Type(id) => {
+                html.push_str("<section>\n");
+                self.print_typedef(*id, &[], html);
+                html.push_str("</section>\n");
+            }
+        }
+    }
+
+    fn print_interface(&self, key: &WorldKey, id: InterfaceId, export: bool, html: &mut String) {
+        let interface = &self.resolve.interfaces[id];
+        let name = self.resolve.name_world_key(key);
+
+        write!(
+            html,
+            "<section>\n<h3 id=\"{anchor}\">Interface <code>{name}</code></h3>\n\
+             <p><a href=\"{link}\">Rust bindings</a></p>\n",
+            anchor = escape(&name),
+            name = escape(&name),
+            link = self.module_link(key, interface, export),
+        )
+        .unwrap();
+        Self::print_docs(&interface.docs, html);
+
+        // Resource methods are documented with their resource
+        let methods: Vec<_> = interface
+            .functions
+            .values()
+            .filter(|f| !matches!(f.kind, FunctionKind::Freestanding))
+            .collect();
+
+        for id in interface.types.values() {
+            self.print_typedef(*id, &methods, html);
+        }
+
+        for func in interface
+            .functions
+            .values()
+            .filter(|f| matches!(f.kind, FunctionKind::Freestanding))
+        {
+            self.print_func(func, html);
+        }
+
+        html.push_str("</section>\n");
+    }
+
+    fn module_link(&self, key: &WorldKey, interface: &Interface, export: bool) -> String {
+        let mut segments = vec!["bindings".to_string()];
+        if export {
+            segments.push("exports".to_string());
+        }
+
+        match key {
+            WorldKey::Name(name) => segments.push(name.to_snake_case()),
+            WorldKey::Interface(_) => {
+                if let Some(pkg) = interface.package {
+                    let name = &self.resolve.packages[pkg].name;
+                    segments.push(name.namespace.to_snake_case());
+                    segments.push(name.name.to_snake_case());
+                }
+
+                if let Some(name) = &interface.name {
+                    segments.push(name.to_snake_case());
+                }
+            }
+        }
+
+        segments.push("index.html".to_string());
+        segments.join("/")
+    }
+
+    fn print_typedef(&self, id: TypeId, methods: &[&Function], html: &mut String) {
+        let ty = &self.resolve.types[id];
+        let name = match &ty.name {
+            Some(name) => name,
+            None => return,
+        };
+
+        let mut def = String::new();
+        let mut members = Vec::new();
+        match &ty.kind {
+            TypeDefKind::Record(record) => {
+                write!(def, "record {name}").unwrap();
+                for field in &record.fields {
+                    members.push((
+                        format!(
+                            "{name}: {ty}",
+                            name = field.name,
+                            ty = self.type_name(&field.ty)
+                        ),
+                        &field.docs,
+                    ));
+                }
+            }
+            TypeDefKind::Variant(variant) => {
+                write!(def, "variant {name}").unwrap();
+                for case in &variant.cases {
+                    members.push((
+                        match &case.ty {
+                            Some(ty) => {
+                                format!("{name}({ty})", name = case.name, ty = self.type_name(ty))
+                            }
+                            None => case.name.clone(),
+                        },
+                        &case.docs,
+                    ));
+                }
+            }
+            TypeDefKind::Enum(e) => {
+                write!(def, "enum {name}").unwrap();
+                for case in &e.cases {
+                    members.push((case.name.clone(), &case.docs));
+                }
+            }
+            TypeDefKind::Flags(flags) => {
+                write!(def, "flags {name}").unwrap();
+                for flag in &flags.flags {
+                    members.push((flag.name.clone(), &flag.docs));
+                }
+            }
+            TypeDefKind::Resource => write!(def, "resource {name}").unwrap(),
+            _ => write!(
+                def,
+                "type {name} = {ty}",
+                ty = self.type_name_of_kind(&ty.kind)
+            )
+            .unwrap(),
+        }
+
+        write!(
+            html,
+            "<h4 id=\"{name}\"><code>{def}</code></h4>\n",
+            name = escape(name),
+            def = escape(&def)
+        )
+        .unwrap();
+
+        let mut docs = String::new();
+        Self::print_docs(&ty.docs, &mut docs);
+
+        if !members.is_empty() {
+            docs.push_str("<ul>\n");
+            for (member, member_docs) in members {
+                write!(docs, "<li><code>{member}</code>", member = escape(&member)).unwrap();
+                Self::print_docs(member_docs, &mut docs);
+                docs.push_str("</li>\n");
+            }
+            docs.push_str("</ul>\n");
+        }
+
+        for method in methods.iter().filter(|f| match f.kind {
+            FunctionKind::Method(r) | FunctionKind::Static(r) | FunctionKind::Constructor(r) => {
+                r == id
+            }
+            FunctionKind::Freestanding => false,
+        }) {
+            self.print_func(method, &mut docs);
+        }
+
+        write!(html, "<div class=\"docs\">\n{docs}</div>\n").unwrap();
+    }
+
+    fn print_func(&self, func: &Function, html: &mut String) {
+        let name = match func.kind {
+            FunctionKind::Freestanding => func.name.as_str(),
+            FunctionKind::Constructor(_) => "constructor",
+            FunctionKind::Method(_) | FunctionKind::Static(_) => func
+                .name
+                .split_once('.')
+                .map(|(_, name)| name)
+                .unwrap_or(&func.name),
+        };
+
+        let mut sig = String::new();
+        match func.kind {
+            FunctionKind::Constructor(_) => sig.push_str("constructor("),
+            FunctionKind::Static(_) => write!(sig, "{name}: static func(").unwrap(),
+            _ => write!(sig, "{name}: func(").unwrap(),
+        }
+
+        // Methods have an implicit `self` parameter that isn't written in WIT
+        let skip = matches!(func.kind, FunctionKind::Method(_)) as usize;
+        for (i, (name, ty)) in func.params.iter().skip(skip).enumerate() {
+            if i > 0 {
+                sig.push_str(", ");
+            }
+            write!(sig, "{name}: {ty}", ty = self.type_name(ty)).unwrap();
+        }
+        sig.push(')');
+
+        if !matches!(func.kind, FunctionKind::Constructor(_)) {
+            match &func.results {
+                Results::Named(results) if results.is_empty() => {}
+                Results::Named(results) => {
+                    sig.push_str(" -> (");
+                    for (i, (name, ty)) in results.iter().enumerate() {
+                        if i > 0 {
+                            sig.push_str(", ");
+                        }
+                        write!(sig, "{name}: {ty}", ty = self.type_name(ty)).unwrap();
+                    }
+                    sig.push(')');
+                }
+                Results::Anon(ty) => write!(sig, " -> {ty}", ty = self.type_name(ty)).unwrap(),
+            }
+        }
+
+        write!(html, "<pre><code>{sig}</code></pre>\n", sig = escape(&sig)).unwrap();
+
+        let mut docs = String::new();
+        Self::print_docs(&func.docs, &mut docs);
+        if !docs.is_empty() {
+            write!(html, "<div class=\"docs\">\n{docs}</div>\n").unwrap();
+        }
+    }
+
+    fn type_name(&self, ty: &Type) -> String {
+        match ty {
+            Type::Bool => "bool".to_string(),
+            Type::U8 => "u8".to_string(),
+            Type::U16 => "u16".to_string(),
+            Type::U32 => "u32".to_string(),
+            Type::U64 => "u64".to_string(),
+            Type::S8 => "s8".to_string(),
+            Type::S16 => "s16".to_string(),
+            Type::S32 => "s32".to_string(),
+            Type::S64 => "s64".to_string(),
+            Type::Float32 => "float32".to_string(),
+            Type::Float64 => "float64".to_string(),
+            Type::Char => "char".to_string(),
+            Type::String => "string".to_string(),
+            Type::Id(id) => {
+                let ty = &self.resolve.types[*id];
+                match &ty.name {
+                    Some(name) => match ty.owner {
+                        // Qualify types from other interfaces
+                        TypeOwner::Interface(owner) => match &self.resolve.interfaces[owner].name {
+                            Some(interface) => format!("{interface}.{name}"),
+                            None => name.clone(),
+                        },
+                        _ => name.clone(),
+                    },
+                    None => self.type_name_of_kind(&ty.kind),
+                }
+            }
+        }
+    }
+
+    fn optional_type_name(&self, ty: Option<&Type>) -> String {
+        ty.map(|ty| self.type_name(ty))
+            .unwrap_or_else(|| "_".to_string())
+    }
+
+    fn type_name_of_kind(&self, kind: &TypeDefKind) -> String {
+        match kind {
+            TypeDefKind::List(ty) => format!("list<{ty}>", ty = self.type_name(ty)),
+            TypeDefKind::Option(ty) => format!("option<{ty}>", ty = self.type_name(ty)),
+            TypeDefKind::Result(r) => match (&r.ok, &r.err) {
+                (None, None) => "result".to_string(),
+                (Some(ok), None) => format!("result<{ok}>", ok = self.type_name(ok)),
+                (ok, Some(err)) => format!(
+                    "result<{ok}, {err}>",
+                    ok = self.optional_type_name(ok.as_ref()),
+                    err = self.type_name(err)
+                ),
+            },
+            TypeDefKind::Tuple(t) => format!(
+                "tuple<{types}>",
+                types = t
+                    .types
+                    .iter()
+                    .map(|ty| self.type_name(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDefKind::Future(ty) => match ty {
+                Some(ty) => format!("future<{ty}>", ty = self.type_name(ty)),
+                None => "future".to_string(),
+            },
+            TypeDefKind::Stream(stream) => format!(
+                "stream<{element}, {end}>",
+                element = self.optional_type_name(stream.element.as_ref()),
+                end = self.optional_type_name(stream.end.as_ref())
+            ),
+            TypeDefKind::Handle(Handle::Own(id)) => self.type_name(&Type::Id(*id)),
+            TypeDefKind::Handle(Handle::Borrow(id)) => {
+                format!("borrow<{ty}>", ty = self.type_name(&Type::Id(*id)))
+            }
+            TypeDefKind::Type(ty) => self.type_name(ty),
+            TypeDefKind::Record(_) => "record".to_string(),
+            TypeDefKind::Variant(_) => "variant".to_string(),
+            TypeDefKind::Enum(_) => "enum".to_string(),
+            TypeDefKind::Flags(_) => "flags".to_string(),
+            TypeDefKind::Resource => "resource".to_string(),
+            TypeDefKind::Unknown => unreachable!(),
+        }
+    }
+
+    fn print_docs(docs: &Docs, html: &mut String) {
+        let contents = match docs.contents.as_deref() {
+            Some(contents) => contents,
+            None => return,
+        };
+
+        for paragraph in contents.split("\n\n") {
+            let paragraph = paragraph.trim();
+            if !paragraph.is_empty() {
+                writeln!(html, "<p>{p}</p>", p = escape(paragraph)).unwrap();
+            }
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
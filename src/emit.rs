@@ -0,0 +1,173 @@
+//! Module for emitting the Rust bindings of a target world as a source file.
+//!
+//! The emitted source is the same as what the `generate!` macro of
+//! `cargo-component-bindings` expands to with the options of the
+//! `package.metadata.component.bindings` section and no others (including the
+//! conveniences configured for its types), so that it may be checked into a
+//! repository and used with `mod bindings;` instead of generating the bindings
+//! at build time.
+
+use crate::{
+    conveniences,
+    metadata::{Bindings, NoStd},
+    remap,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use cargo_component_core::bindings::annotate_deprecations;
+use heck::ToUpperCamelCase;
+use std::collections::HashMap;
+use wit_bindgen_core::Files;
+use wit_bindgen_rust::{ExportKey, Opts};
+use wit_parser::{Resolve, TypeDefKind, WorldId, WorldItem, WorldKey};
+
+/// The name of the type world exports are implemented on.
+const IMPLEMENTOR: &str = "super::Component";
+
+/// Generates the Rust bindings source for the given world.
+///
+/// The returned source starts with a header that marks it as generated and
+/// records the command used to regenerate it.
+pub fn generate(
+    resolve: &Resolve,
+    world: WorldId,
+    package: &str,
+    bindings: &Bindings,
+    command: &str,
+) -> Result<String> {
+    let mut exports = HashMap::new();
+    exports.insert(ExportKey::World, IMPLEMENTOR.to_string());
+
+    for (key, item) in &resolve.worlds[world].exports {
+        let name = match key {
+            WorldKey::Name(name) => name.clone(),
+            WorldKey::Interface(id) => {
+                let interface = &resolve.interfaces[*id];
+                let package = &resolve.packages[interface
+                    .package
+                    .context("exported interface must have a package")?];
+                package.name.interface_id(
+                    interface
+                        .name
+                        .as_deref()
+                        .context("exported interface must have a name")?,
+                )
+            }
+        };
+
+        let implementor = match item {
+            WorldItem::Interface(id) => {
+                for (resource, ty) in &resolve.interfaces[*id].types {
+                    if let TypeDefKind::Resource = resolve.types[*ty].kind {
+                        exports.insert(
+                            ExportKey::Name(format!("{name}/{resource}")),
+                            format!("super::{ty}", ty = resource.to_upper_camel_case()),
+                        );
+                    }
+                }
+
+                IMPLEMENTOR.to_string()
+            }
+            WorldItem::Type(id) => match resolve.types[*id].kind {
+                TypeDefKind::Resource => format!("super::{ty}", ty = name.to_upper_camel_case()),
+                _ => continue,
+            },
+            WorldItem::Function(_) => IMPLEMENTOR.to_string(),
+        };
+
+        exports.insert(ExportKey::Name(name), implementor);
+    }
+
+    let ownership = match bindings.ownership {
+        Some(ownership) => ownership
+            .to_string()
+            .parse()
+            .map_err(|e| anyhow!("invalid ownership model `{ownership}`: {e}"))?,
+        None => Default::default(),
+    };
+
+    let opts = Opts {
+        exports,
+        ownership,
+        additional_derive_attributes: bindings.additional_derives.clone(),
+        runtime_path: Some("::cargo_component_bindings::rt".to_string()),
+        bitflags_path: Some("::cargo_component_bindings::bitflags".to_string()),
+        std_feature: bindings.no_std.is_some(),
+        ..Default::default()
+    };
+
+    let mut files = Files::default();
+    opts.build()
+        .generate(resolve, world, &mut files)
+        .context("failed to generate Rust bindings")?;
+
+    let sources: Vec<_> = files.iter().map(|(_, s)| s).collect();
+    if sources.len() != 1 {
+        bail!("expected exactly one source file to be generated");
+    }
+
+    let source = annotate_deprecations(&remap::remap(
+        std::str::from_utf8(sources[0]).context("generated bindings are not UTF-8")?,
+        &remap::remapped_modules(resolve, world, bindings)?,
+    )?);
+    let conveniences = conveniences::generate(resolve, world, bindings, true)?;
+    let shims = bindings
+        .no_std
+        .as_ref()
+        .map(no_std_shims)
+        .unwrap_or_default();
+
+    // The bindings themselves may use their deprecated items
+    let allow = if source.contains("#[deprecated") {
+        "#![allow(deprecated)]\n\n"
+    } else {
+        ""
+    };
+
+    Ok(format!(
+        "// Generated by `cargo-component` from the target world of package `{package}`.\n\
+         // DO NOT EDIT! Regenerate with `{command}`.\n\
+         \n\
+         {allow}{source}{conveniences}{shims}"
+    ))
+}
+
+/// Generates the shims of a `no_std` component, as the `generate!` macro does.
+fn no_std_shims(no_std: &NoStd) -> String {
+    let mut shims = String::new();
+    if no_std.panic_handler {
+        shims.push_str(
+            "\n#[cfg(all(target_arch = \"wasm32\", not(test)))]\n\
+             #[panic_handler]\n\
+             fn panic(_: &::core::panic::PanicInfo<'_>) -> ! {\n    \
+                 ::core::arch::wasm32::unreachable()\n\
+             }\n",
+        );
+    }
+
+    if let Some(allocator) = &no_std.global_allocator {
+        shims.push_str(&format!(
+            "\n#[global_allocator]\nstatic ALLOCATOR: {allocator} = {allocator};\n"
+        ));
+    }
+
+    shims
+}
+
+/// Finds the first line that differs between two sources.
+///
+/// Returns the line number (starting at 1) and the differing lines, if any.
+pub fn first_difference<'a>(
+    expected: &'a str,
+    found: &'a str,
+) -> Option<(usize, Option<&'a str>, Option<&'a str>)> {
+    let mut expected_lines = expected.lines();
+    let mut found_lines = found.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), found_lines.next()) {
+            (None, None) => return None,
+            (e, f) if e != f => return Some((line, e, f)),
+            _ => line += 1,
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Module for explaining the fully resolved target world of a component
+//! package.
+
+use crate::{bindings::BindingsEncoder, metadata::Target, registry::PackageDependencyResolution};
+use anyhow::Result;
+use cargo_component_core::registry::DependencyResolution;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+use wit_parser::{
+    InterfaceId, PackageId, PackageName, Resolve, Type, TypeDefKind, TypeOwner, WorldItem, WorldKey,
+};
+
+/// Explains where each import and export of a target world comes from.
+///
+/// The world is resolved exactly as it is for bindings generation, so the
+/// explanation reflects includes, `use` statements, registry resolution, and
+/// the imports added for component dependencies.
+pub struct WorldExplainer<'a> {
+    encoder: BindingsEncoder<'a>,
+    target: &'a Target,
+    /// The descriptions of the sources of the packages in the world.
+    packages: HashMap<PackageName, String>,
+    /// The name prefixes of the imports of component dependencies and their descriptions.
+    components: Vec<(String, String)>,
+}
+
+impl<'a> WorldExplainer<'a> {
+    /// Creates a new world explainer for the given package dependency resolution.
+    ///
+    /// If `name` is `None`, the package's default target is explained;
+    /// otherwise the named target is explained.
+    pub fn new(
+        resolution: &'a PackageDependencyResolution<'a>,
+        name: Option<&str>,
+        features: &HashSet<String>,
+    ) -> Result<Self> {
+        let encoder = BindingsEncoder::new(resolution, name, features)?;
+        let target = resolution.metadata.target(name)?;
+
+        let mut packages = HashMap::new();
+        for dependency in resolution
+            .target_resolutions(name)
+            .expect("target should have resolutions")
+            .values()
+        {
+            let name = dependency.decode()?.package_name().clone();
+            packages.insert(name, describe_dependency(dependency));
+        }
+
+        let components = resolution
+            .resolutions
+            .iter()
+            .filter(|(id, _)| resolution.metadata.is_dependency_enabled(id, features))
+            .map(|(id, dependency)| {
+                (
+                    resolution.metadata.dependency_module(id).to_string(),
+                    format!(
+                        "component dependency `{id}` ({source})",
+                        source = describe_dependency(dependency)
+                    ),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            encoder,
+            target,
+            packages,
+            components,
+        })
+    }
+
+    /// Explains the world, returning a human-readable description.
+    pub fn explain(&self) -> String {
+        let resolve = self.encoder.resolve();
+        let world_id = self.encoder.world();
+        let world = &resolve.worlds[world_id];
+
+        let mut output = String::new();
+        match world.package {
+            Some(package) => writeln!(
+                output,
+                "world `{package}/{name}` ({source})",
+                package = resolve.packages[package].name,
+                name = world.name,
+                source = self.describe_target()
+            ),
+            None => writeln!(output, "world `{name}`", name = world.name),
+        }
+        .unwrap();
+
+        for (kind, items) in [("imports", &world.imports), ("exports", &world.exports)] {
+            writeln!(output).unwrap();
+            if items.is_empty() {
+                writeln!(output, "no {kind}").unwrap();
+                continue;
+            }
+
+            writeln!(output, "{kind}:").unwrap();
+            for (key, item) in items {
+                let kind = match item {
+                    WorldItem::Interface(_) => "interface",
+                    WorldItem::Function(_) => "function",
+                    WorldItem::Type(_) => "type",
+                };
+
+                writeln!(output, "  {kind} `{name}`", name = key_name(resolve, key)).unwrap();
+                writeln!(
+                    output,
+                    "    from {source}",
+                    source = self.describe_item(key, item)
+                )
+                .unwrap();
+
+                for included in &world.includes {
+                    let included = &resolve.worlds[*included];
+                    if included.imports.contains_key(key) || included.exports.contains_key(key) {
+                        writeln!(
+                            output,
+                            "    included from world `{name}`",
+                            name = world_name(resolve, included.package, &included.name)
+                        )
+                        .unwrap();
+                    }
+                }
+
+                if let WorldItem::Interface(id) = item {
+                    for user in self.users(*id) {
+                        writeln!(output, "    used by `{user}`").unwrap();
+                    }
+                }
+            }
+        }
+
+        output
+    }
+
+    fn describe_target(&self) -> String {
+        match self.target {
+            Target::Package { id, .. } => format!("target package `{id}`"),
+            Target::Local { .. } => match self.encoder.metadata().local_target_path(self.target) {
+                Some(path) => format!("local target `{path}`", path = path.display()),
+                None => "empty target world".to_string(),
+            },
+        }
+    }
+
+    fn describe_item(&self, key: &WorldKey, item: &WorldItem) -> String {
+        if let WorldKey::Name(name) = key {
+            for (prefix, description) in &self.components {
+                if name == prefix || name.starts_with(&format!("{prefix}-")) {
+                    return description.clone();
+                }
+            }
+        }
+
+        let resolve = self.encoder.resolve();
+        let world = &resolve.worlds[self.encoder.world()];
+        let package = match item {
+            WorldItem::Interface(id) => resolve.interfaces[*id].package,
+            WorldItem::Function(_) | WorldItem::Type(_) => world.package,
+        };
+
+        match package {
+            Some(package) if Some(package) == world.package => {
+                if matches!(self.target, Target::Package { .. }) {
+                    self.describe_target()
+                } else {
+                    "the target world".to_string()
+                }
+            }
+            Some(package) => self.describe_package(resolve, package),
+            None => "the target world".to_string(),
+        }
+    }
+
+    fn describe_package(&self, resolve: &Resolve, package: PackageId) -> String {
+        let name = &resolve.packages[package].name;
+        match self.packages.get(name) {
+            Some(description) => format!("package `{name}` ({description})"),
+            None => format!("package `{name}` (a dependency of another package)"),
+        }
+    }
+
+    /// Gets the other interfaces of the world, or the world itself, that use
+    /// types from the given interface.
+    fn users(&self, interface: InterfaceId) -> Vec<String> {
+        let resolve = self.encoder.resolve();
+        let world = &resolve.worlds[self.encoder.world()];
+        let uses = |ty: &wit_parser::TypeId| match resolve.types[*ty].kind {
+            TypeDefKind::Type(Type::Id(used)) => {
+                resolve.types[used].owner == TypeOwner::Interface(interface)
+            }
+            _ => false,
+        };
+
+        let mut users = Vec::new();
+        for (key, item) in world.imports.iter().chain(world.exports.iter()) {
+            match item {
+                WorldItem::Interface(id) if *id != interface => {
+                    if resolve.interfaces[*id].types.values().any(&uses) {
+                        users.push(key_name(resolve, key));
+                    }
+                }
+                WorldItem::Type(ty) if uses(ty) => users.push(format!(
+                    "{name} (in the target world)",
+                    name = key_name(resolve, key)
+                )),
+                _ => {}
+            }
+        }
+
+        users
+    }
+}
+
+pub(crate) fn describe_dependency(dependency: &DependencyResolution) -> String {
+    match dependency {
+        DependencyResolution::Registry(resolution) => match &resolution.registry {
+            Some(registry) => format!(
+                "registry package `{package}@{version}` from registry `{registry}`",
+                package = resolution.package,
+                version = resolution.version
+            ),
+            None => format!(
+                "registry package `{package}@{version}`",
+                package = resolution.package,
+                version = resolution.version
+            ),
+        },
+        DependencyResolution::Local(resolution) => format!(
+            "local dependency `{path}`",
+            path = resolution.path.display()
+        ),
+        DependencyResolution::Git(resolution) => format!(
+            "git dependency `{url}` at commit `{commit}`",
+            url = resolution.url,
+            commit = resolution.commit
+        ),
+    }
+}
+
+pub(crate) fn key_name(resolve: &Resolve, key: &WorldKey) -> String {
+    match key {
+        WorldKey::Name(name) => name.clone(),
+        WorldKey::Interface(id) => {
+            let iface = &resolve.interfaces[*id];
+            match (iface.package, &iface.name) {
+                (Some(package), Some(name)) => resolve.packages[package].name.interface_id(name),
+                (_, Some(name)) => name.clone(),
+                _ => "<unnamed>".to_string(),
+            }
+        }
+    }
+}
+
+pub(crate) fn world_name(resolve: &Resolve, package: Option<PackageId>, name: &str) -> String {
+    match package {
+        Some(package) => format!("{package}/{name}", package = resolve.packages[package].name),
+        None => name.to_string(),
+    }
+}
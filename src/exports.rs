@@ -0,0 +1,80 @@
+//! Module for exporting the interfaces of a component at additional versions.
+//!
+//! A component implements the exported interfaces of its target world at the
+//! versions of the target world. Hosts that pin a different (compatible)
+//! version of an interface can instead find it under an additional export of
+//! the same instance, named with that version.
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use semver::Version;
+use std::collections::BTreeMap;
+use wasm_encoder::{ComponentExportKind, ComponentExportSection, ComponentSection, Encode};
+use wasmparser::{ComponentExternalKind, Parser, Payload};
+
+/// Adds exports of the given interfaces at additional versions to a component.
+///
+/// Each interface is named without a version (e.g.
+/// `wasi:http/incoming-handler`) and must be exported by the component at
+/// some version; the instance it is exported as is exported again for each
+/// additional version.
+///
+/// Returns `Ok(None)` if the component already has every additional export.
+pub fn add_export_versions(
+    component: &[u8],
+    versions: &BTreeMap<String, Vec<Version>>,
+) -> Result<Option<Vec<u8>>> {
+    let mut exports = IndexMap::new();
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(component) {
+        match payload.context("failed to parse component")? {
+            Payload::Version { .. } => depth += 1,
+            Payload::End(_) => depth -= 1,
+            Payload::ComponentExportSection(reader) if depth == 1 => {
+                for export in reader {
+                    let export = export.context("failed to parse component export")?;
+                    exports.insert(export.name.0.to_string(), (export.kind, export.index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut section = ComponentExportSection::new();
+    for (interface, versions) in versions {
+        // The first export of the interface is the one the component implements
+        let (name, (kind, index)) = exports
+            .iter()
+            .find(|(name, _)| {
+                name.split_once('@')
+                    .map(|(name, _)| name == interface)
+                    .unwrap_or(false)
+            })
+            .with_context(|| {
+                format!("component does not export a version of interface `{interface}`")
+            })?;
+
+        if *kind != ComponentExternalKind::Instance {
+            bail!("component export `{name}` is not an instance");
+        }
+
+        for version in versions {
+            let name = format!("{interface}@{version}");
+            if exports.contains_key(&name) {
+                continue;
+            }
+
+            log::debug!("exporting instance {index} as `{name}`");
+            section.export(&name, ComponentExportKind::Instance, *index, None);
+        }
+    }
+
+    if section.is_empty() {
+        return Ok(None);
+    }
+
+    let mut component = component.to_vec();
+    component.push(section.id());
+    section.encode(&mut component);
+    Ok(Some(component))
+}
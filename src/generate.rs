@@ -0,0 +1,264 @@
+//! Module for running the command that generates WIT files for a package.
+//!
+//! The `generate` command of a package runs before the bindings of the
+//! package are generated, with `OUT_DIR` set as cargo sets it for a build
+//! script, so that a generator written for a build script (e.g. one creating
+//! interfaces from an IDL) can be used unchanged. A build script cannot feed
+//! the target world itself, as cargo runs it after the bindings are generated.
+//!
+//! The command writes to a staging directory; the WIT files it writes are
+//! copied to the output directory only when their contents change, so that
+//! running it again does not cause the target world to be encoded again.
+
+use crate::{config::Config, last_modified_time, metadata::ComponentMetadata};
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::HashSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::SystemTime,
+};
+
+/// The name of the directory of the generated WIT files of a package.
+const GENERATED_DIR: &str = "generated-wit";
+
+/// The name of the file recording when the command was last run.
+const STAMP_FILE: &str = "generated-wit.stamp";
+
+/// Gets the directory of the generated WIT files given the bindings directory
+/// of a package.
+pub fn output_dir(package_bindings_dir: &Path) -> PathBuf {
+    package_bindings_dir.join(GENERATED_DIR)
+}
+
+/// Runs the `generate` command of a package if its inputs were modified since
+/// it last ran.
+pub fn run(config: &Config, metadata: &ComponentMetadata) -> Result<()> {
+    let (generate, out_dir) = match (&metadata.section.generate, &metadata.generated_dir) {
+        (Some(generate), Some(out_dir)) => (generate, out_dir),
+        _ => return Ok(()),
+    };
+
+    let stamp = out_dir.with_file_name(STAMP_FILE);
+    if let Some(reason) = reason(metadata, &generate.inputs, out_dir, &stamp)? {
+        log::debug!(
+            "running the `generate` command of package `{name}` because {reason}",
+            name = metadata.name
+        );
+    } else {
+        log::debug!(
+            "generated WIT files of package `{name}` are up-to-date",
+            name = metadata.name
+        );
+        return Ok(());
+    }
+
+    let staging = out_dir.with_extension("tmp");
+    if staging.exists() {
+        fs::remove_dir_all(&staging).with_context(|| {
+            format!(
+                "failed to remove directory `{path}`",
+                path = staging.display()
+            )
+        })?;
+    }
+
+    fs::create_dir_all(&staging).with_context(|| {
+        format!(
+            "failed to create directory `{path}`",
+            path = staging.display()
+        )
+    })?;
+
+    config.terminal().status(
+        "Generating",
+        format!("WIT files for package `{name}`", name = metadata.name),
+    )?;
+
+    let command = generate.command.join(" ");
+    let manifest_dir = metadata.manifest_path.parent().unwrap();
+    let (program, args) = generate
+        .command
+        .split_first()
+        .context("`generate` command is empty")?;
+
+    log::debug!(
+        "running `generate` command `{command}` with `OUT_DIR` set to `{path}`",
+        path = staging.display()
+    );
+
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(manifest_dir)
+        .env("OUT_DIR", &staging)
+        .env("CARGO_MANIFEST_DIR", manifest_dir)
+        .env("CARGO_PKG_NAME", &metadata.name)
+        .env("CARGO_PKG_VERSION", metadata.version.to_string())
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("failed to spawn `generate` command `{program}`"))?;
+
+    // The output of the command is shown as diagnostics; stdout may be used
+    // for machine-readable messages
+    std::io::stderr().write_all(&output.stdout)?;
+
+    if !output.status.success() {
+        bail!(
+            "`generate` command `{command}` of package `{name}` failed with exit status {status}",
+            name = metadata.name,
+            status = output.status
+        );
+    }
+
+    let count = sync(&staging, out_dir)?;
+    if count == 0 {
+        config.terminal().warn(format!(
+            "the `generate` command of package `{name}` did not write any WIT files to `OUT_DIR`",
+            name = metadata.name
+        ))?;
+    }
+
+    fs::remove_dir_all(&staging).with_context(|| {
+        format!(
+            "failed to remove directory `{path}`",
+            path = staging.display()
+        )
+    })?;
+
+    fs::write(&stamp, "").with_context(|| {
+        format!(
+            "failed to write generated WIT stamp file `{path}`",
+            path = stamp.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Gets the reason for running the `generate` command.
+///
+/// Returns `Ok(None)` if the generated WIT files are up-to-date.
+fn reason(
+    metadata: &ComponentMetadata,
+    inputs: &[PathBuf],
+    out_dir: &Path,
+    stamp: &Path,
+) -> Result<Option<&'static str>> {
+    if !out_dir.is_dir() || !stamp.is_file() {
+        return Ok(Some("it has not run before"));
+    }
+
+    if inputs.is_empty() {
+        return Ok(Some("it has no inputs"));
+    }
+
+    let last_run = last_modified_time(stamp)?;
+    if metadata.modified_at > last_run {
+        return Ok(Some("the manifest was modified"));
+    }
+
+    for input in inputs {
+        if !input.exists() {
+            bail!(
+                "input `{path}` of the `generate` command of package `{name}` does not exist",
+                path = input.display(),
+                name = metadata.name
+            );
+        }
+
+        if is_newer(input, last_run)? {
+            return Ok(Some("an input was modified"));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Determines if a file, or any file within a directory, was modified after
+/// the given time.
+fn is_newer(path: &Path, time: SystemTime) -> Result<bool> {
+    if last_modified_time(path)? > time {
+        return Ok(true);
+    }
+
+    if path.is_dir() {
+        for entry in fs::read_dir(path)
+            .with_context(|| format!("failed to read directory `{path}`", path = path.display()))?
+        {
+            let entry = entry.with_context(|| {
+                format!("failed to read directory `{path}`", path = path.display())
+            })?;
+            if is_newer(&entry.path(), time)? {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Copies the WIT files written directly to the staging directory to the
+/// output directory, removing the files that were not written again.
+///
+/// Files with unchanged contents are left as they are. Returns the number of
+/// WIT files written.
+fn sync(staging: &Path, out_dir: &Path) -> Result<usize> {
+    fs::create_dir_all(out_dir).with_context(|| {
+        format!(
+            "failed to create directory `{path}`",
+            path = out_dir.display()
+        )
+    })?;
+
+    let mut written = HashSet::new();
+    for path in wit_files(staging)? {
+        let name = path.file_name().unwrap().to_owned();
+        let contents = fs::read(&path)
+            .with_context(|| format!("failed to read WIT file `{path}`", path = path.display()))?;
+
+        let dest = out_dir.join(&name);
+        if fs::read(&dest).ok().as_deref() != Some(contents.as_slice()) {
+            fs::write(&dest, contents).with_context(|| {
+                format!(
+                    "failed to write generated WIT file `{path}`",
+                    path = dest.display()
+                )
+            })?;
+        }
+
+        written.insert(name);
+    }
+
+    for path in wit_files(out_dir)? {
+        if !written.contains(path.file_name().unwrap()) {
+            fs::remove_file(&path).with_context(|| {
+                format!(
+                    "failed to remove generated WIT file `{path}`",
+                    path = path.display()
+                )
+            })?;
+        }
+    }
+
+    Ok(written.len())
+}
+
+/// Gets the WIT files directly in a directory.
+fn wit_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+    {
+        let path = entry
+            .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+            .path();
+        if path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("wit") {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
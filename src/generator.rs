@@ -1,12 +1,12 @@
 //! A module for implementing the Rust source generator used for
-//! the `--target` option of the `new` command.
+//! the `--target` option of the `new` command and the `stub` command.
 
 use anyhow::{bail, Context, Result};
 use heck::{AsSnakeCase, ToSnakeCase, ToUpperCamelCase};
 use indexmap::IndexSet;
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     fmt::{self, Write},
     fs,
     io::Read,
@@ -17,8 +17,8 @@ use warg_protocol::registry::PackageId;
 use wit_bindgen_rust::to_rust_ident;
 use wit_component::DecodedWasm;
 use wit_parser::{
-    Function, Handle, Interface, Resolve, Type, TypeDef, TypeDefKind, TypeId, TypeOwner, WorldId,
-    WorldItem, WorldKey,
+    Function, FunctionKind, Handle, InterfaceId, Resolve, Type, TypeDef, TypeDefKind, TypeId,
+    TypeOwner, WorldId, WorldItem, WorldKey,
 };
 
 /// Represents a node in a "use" trie.
@@ -78,9 +78,31 @@ struct UseTrie {
     segments: IndexSet<String>,
     /// The set of all known type names.
     types: IndexSet<String>,
+    /// Whether or not fully qualified type paths are printed instead of
+    /// recording uses.
+    qualified: bool,
+    /// The set of interfaces exported by the world; used to distinguish
+    /// exported from imported interface types in qualified paths.
+    exports: HashSet<InterfaceId>,
 }
 
 impl UseTrie {
+    /// Creates a trie that prints fully qualified type paths for the given world.
+    fn qualified(resolve: &Resolve, world: WorldId) -> Self {
+        Self {
+            qualified: true,
+            exports: resolve.worlds[world]
+                .exports
+                .values()
+                .filter_map(|item| match item {
+                    WorldItem::Interface(id) => Some(*id),
+                    _ => None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
     /// Gets the used types at a given path.
     fn get<'a>(&self, path: impl Iterator<Item = &'a str>) -> Option<impl Iterator<Item = &str>> {
         let mut node = &self.root;
@@ -101,6 +123,15 @@ impl UseTrie {
         I: IntoIterator<Item = &'a str>,
         I::IntoIter: Clone,
     {
+        if self.qualified {
+            let mut qualified = path.into_iter().fold(String::new(), |mut s, p| {
+                write!(s, "{p}::", p = AsSnakeCase(p)).unwrap();
+                s
+            });
+            qualified.push_str(&ty.to_upper_camel_case());
+            return qualified.into();
+        }
+
         let (type_index, inserted) = self.types.insert_full(ty.to_upper_camel_case());
         let ty: &String = &self.types[type_index];
         if !inserted {
@@ -147,35 +178,27 @@ impl UseTrie {
     }
 
     /// Inserts a type from a WIT interface.
-    fn insert_interface_type(
-        &mut self,
-        resolve: &Resolve,
-        interface: &Interface,
-        ty: &str,
-    ) -> Cow<str> {
+    fn insert_interface_type(&mut self, resolve: &Resolve, id: InterfaceId, ty: &str) -> Cow<str> {
+        let interface = &resolve.interfaces[id];
         let pkg = &resolve.packages[interface.package.expect("interface should have a package")];
         let name = interface.name.as_deref().expect("unnamed interface");
 
-        self.insert(
-            [
-                "bindings",
-                "exports",
-                pkg.name.namespace.as_str(),
-                pkg.name.name.as_str(),
-                name,
-            ],
-            ty,
-        )
+        // Types of imported interfaces are not in the `exports` module
+        let exports = !self.qualified || self.exports.contains(&id);
+        let path = ["bindings"]
+            .into_iter()
+            .chain(exports.then_some("exports"))
+            .chain([pkg.name.namespace.as_str(), pkg.name.name.as_str(), name])
+            .collect::<Vec<_>>();
+
+        self.insert(path, ty)
     }
 
     /// Inserts an export trait for the given world key.
     fn insert_export_trait(&mut self, resolve: &Resolve, key: &WorldKey) -> Cow<str> {
         match key {
             WorldKey::Name(name) => self.insert(["bindings", "exports", name.as_str()], "Guest"),
-            WorldKey::Interface(id) => {
-                let iface = &resolve.interfaces[*id];
-                self.insert_interface_type(resolve, iface, "Guest")
-            }
+            WorldKey::Interface(id) => self.insert_interface_type(resolve, *id, "Guest"),
         }
     }
 
@@ -353,11 +376,38 @@ impl<'a> SourceGenerator<'a> {
         func: &Function,
         source: &mut String,
         trie: &mut UseTrie,
+    ) -> Result<()> {
+        Self::print_func(resolve, func, "unimplemented!()", source, trie)
+    }
+
+    fn print_func(
+        resolve: &Resolve,
+        func: &Function,
+        body: &str,
+        source: &mut String,
+        trie: &mut UseTrie,
     ) -> Result<()> {
         // TODO: it would be nice to share the printing of the signature of the function
         // with wit-bindgen, but right now it's tightly coupled with interface generation.
-        write!(source, "    fn {name}(", name = to_rust_ident(&func.name)).unwrap();
-        for (i, (name, param)) in func.params.iter().enumerate() {
+        let name = match func.kind {
+            FunctionKind::Freestanding => to_rust_ident(&func.name),
+            FunctionKind::Constructor(_) => "new".to_string(),
+            FunctionKind::Method(_) | FunctionKind::Static(_) => to_rust_ident(func.item_name()),
+        };
+
+        write!(source, "    fn {name}(").unwrap();
+
+        // The first parameter of a method is the resource itself
+        let mut params = func.params.iter().peekable();
+        if let FunctionKind::Method(_) = func.kind {
+            params.next();
+            source.push_str("&self");
+            if params.peek().is_some() {
+                source.push_str(", ");
+            }
+        }
+
+        for (i, (name, param)) in params.enumerate() {
             if i > 0 {
                 source.push_str(", ");
             }
@@ -367,6 +417,7 @@ impl<'a> SourceGenerator<'a> {
         }
         source.push(')');
         match func.results.len() {
+            _ if matches!(func.kind, FunctionKind::Constructor(_)) => source.push_str(" -> Self"),
             0 => {}
             1 => {
                 source.push_str(" -> ");
@@ -388,7 +439,7 @@ impl<'a> SourceGenerator<'a> {
                 source.push(')');
             }
         }
-        source.push_str(" {\n        unimplemented!()\n    }\n");
+        writeln!(source, " {{\n        {body}\n    }}").unwrap();
         Ok(())
     }
 
@@ -502,13 +553,11 @@ impl<'a> SourceGenerator<'a> {
 
     fn print_type_path(resolve: &Resolve, ty: &TypeDef, source: &mut String, trie: &mut UseTrie) {
         if let TypeOwner::Interface(id) = ty.owner {
-            let interface = &resolve.interfaces[id];
-            if interface.package.is_some() {
+            if resolve.interfaces[id].package.is_some() {
                 write!(
                     source,
                     "{name}",
-                    name =
-                        trie.insert_interface_type(resolve, interface, ty.name.as_deref().unwrap())
+                    name = trie.insert_interface_type(resolve, id, ty.name.as_deref().unwrap())
                 )
                 .unwrap();
                 return;
@@ -537,3 +586,18 @@ impl<'a> SourceGenerator<'a> {
         Ok(())
     }
 }
+
+/// Prints a `todo!()` implementation of an exported function of the given world.
+///
+/// Unlike the source generated for the `new` command, the types in the
+/// signature are printed with fully qualified paths so that the function
+/// can be inserted into existing source without adding any uses.
+pub(crate) fn print_stub_func(
+    resolve: &Resolve,
+    world: WorldId,
+    func: &Function,
+    source: &mut String,
+) -> Result<()> {
+    let mut trie = UseTrie::qualified(resolve, world);
+    SourceGenerator::print_func(resolve, func, "todo!()", source, &mut trie)
+}
@@ -0,0 +1,294 @@
+//! Module for guiding the implementation of a target world whose exports
+//! changed.
+//!
+//! When a target is encoded, the export traits of its world (and the
+//! signatures of their functions, as `cargo component stub` would write them)
+//! are recorded in the bindings directory. The record of the last successful
+//! build is kept alongside it; if the two differ, the traits and functions
+//! that must be added, changed, or removed are reported before the crate is
+//! compiled, rather than leaving the compiler to report missing trait items
+//! from deep inside the generated bindings.
+
+use crate::{generator::print_stub_func, stub};
+use anyhow::{Context, Result};
+use cargo_component_core::terminal::Terminal;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+use wit_parser::{Resolve, WorldId};
+
+/// The file name of the exports of the last encoded target world.
+const EXPORTS_FILE: &str = "exports.json";
+
+/// The file name of the exports of the last successfully built target world.
+const BUILT_EXPORTS_FILE: &str = "built-exports.json";
+
+/// Represents the export traits of a target world.
+///
+/// Traits are keyed by their path in the bindings and map the names of their
+/// functions to the signatures of the functions.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exports(BTreeMap<String, BTreeMap<String, String>>);
+
+impl Exports {
+    /// Gets the export traits of the given world.
+    pub fn new(resolve: &Resolve, world: WorldId) -> Result<Self> {
+        let mut traits = BTreeMap::new();
+        for export in stub::export_traits(resolve, world) {
+            let mut functions = BTreeMap::new();
+            for func in &export.functions {
+                let mut source = String::new();
+                print_stub_func(resolve, world, func, &mut source)?;
+                let signature = source
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .trim_end_matches('{')
+                    .trim_end()
+                    .to_string();
+                functions.insert(stub::function_name(func), signature);
+            }
+
+            traits.insert(export.path.join("::"), functions);
+        }
+
+        Ok(Self(traits))
+    }
+
+    /// Gets the changes to implement the traits of these exports given an
+    /// implementation of the previous exports.
+    pub fn changes(&self, previous: &Self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for (path, functions) in &previous.0 {
+            if !self.0.contains_key(path) {
+                changes.push(Change::RemoveTrait {
+                    path: path.clone(),
+                    functions: functions.len(),
+                });
+            }
+        }
+
+        for (path, functions) in &self.0 {
+            let old = match previous.0.get(path) {
+                Some(old) => old,
+                None => {
+                    changes.push(Change::AddTrait {
+                        path: path.clone(),
+                        signatures: functions.values().cloned().collect(),
+                    });
+                    continue;
+                }
+            };
+
+            for (name, signature) in old {
+                if !functions.contains_key(name) {
+                    changes.push(Change::RemoveFunction {
+                        path: path.clone(),
+                        signature: signature.clone(),
+                    });
+                }
+            }
+
+            for (name, signature) in functions {
+                match old.get(name) {
+                    None => changes.push(Change::AddFunction {
+                        path: path.clone(),
+                        signature: signature.clone(),
+                    }),
+                    Some(old) if old != signature => changes.push(Change::ChangeFunction {
+                        path: path.clone(),
+                        from: old.clone(),
+                        to: signature.clone(),
+                    }),
+                    Some(_) => {}
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+/// Represents a change to make to the implementation of a target world.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// An export trait must be implemented.
+    AddTrait {
+        /// The path of the trait.
+        path: String,
+        /// The signatures of the functions of the trait.
+        signatures: Vec<String>,
+    },
+    /// An implementation of an export trait must be removed.
+    RemoveTrait {
+        /// The path of the trait.
+        path: String,
+        /// The number of functions the trait had.
+        functions: usize,
+    },
+    /// A function must be added to the implementation of a trait.
+    AddFunction {
+        /// The path of the trait.
+        path: String,
+        /// The signature of the function.
+        signature: String,
+    },
+    /// A function must be removed from the implementation of a trait.
+    RemoveFunction {
+        /// The path of the trait.
+        path: String,
+        /// The signature of the function.
+        signature: String,
+    },
+    /// The signature of a function of the implementation of a trait must change.
+    ChangeFunction {
+        /// The path of the trait.
+        path: String,
+        /// The previous signature of the function.
+        from: String,
+        /// The new signature of the function.
+        to: String,
+    },
+}
+
+impl Change {
+    /// Gets the path of the trait the change is to.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::AddTrait { path, .. }
+            | Self::RemoveTrait { path, .. }
+            | Self::AddFunction { path, .. }
+            | Self::RemoveFunction { path, .. }
+            | Self::ChangeFunction { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddTrait { path, signatures } => {
+                write!(f, "implement `{path}`")?;
+                if !signatures.is_empty() {
+                    write!(f, " with:")?;
+                    for signature in signatures {
+                        write!(f, "\n    {signature}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::RemoveTrait { path, functions } => write!(
+                f,
+                "remove the implementation of `{path}` ({functions} function{s})",
+                s = if *functions == 1 { "" } else { "s" }
+            ),
+            Self::AddFunction { path, signature } => {
+                write!(f, "add to the implementation of `{path}`:\n    {signature}")
+            }
+            Self::RemoveFunction { path, signature } => write!(
+                f,
+                "remove from the implementation of `{path}`:\n    {signature}"
+            ),
+            Self::ChangeFunction { path, from, to } => write!(
+                f,
+                "change in the implementation of `{path}`:\n    {from}\n  to:\n    {to}"
+            ),
+        }
+    }
+}
+
+/// Records the exports of an encoded target world in its output directory and
+/// reports the changes to its implementation since the last successful build.
+///
+/// Nothing is reported if the target has not been built successfully before.
+pub fn check(
+    terminal: &Terminal,
+    package: &str,
+    output_dir: &Path,
+    exports: &Exports,
+) -> Result<()> {
+    let path = output_dir.join(EXPORTS_FILE);
+    let contents = serde_json::to_string_pretty(exports)?;
+    if fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+        fs::create_dir_all(output_dir).with_context(|| {
+            format!(
+                "failed to create output directory `{path}`",
+                path = output_dir.display()
+            )
+        })?;
+
+        fs::write(&path, contents).with_context(|| {
+            format!(
+                "failed to write target exports file `{path}`",
+                path = path.display()
+            )
+        })?;
+    }
+
+    let built = match read(&output_dir.join(BUILT_EXPORTS_FILE))? {
+        Some(built) => built,
+        None => return Ok(()),
+    };
+
+    let changes = exports.changes(&built);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    terminal.warn(format!(
+        "the exports of the target world of package `{package}` changed since the last successful build; to implement them:\n{changes}",
+        changes = changes
+            .iter()
+            .map(|c| format!("  {c}", c = c.to_string().replace('\n', "\n  ")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))?;
+
+    terminal.event(
+        "exports-changed",
+        serde_json::json!({
+            "package": package,
+            "changes": changes.iter().map(|c| serde_json::json!({
+                "trait": c.path(),
+                "message": c.to_string(),
+            })).collect::<Vec<_>>(),
+        }),
+    )?;
+
+    Ok(())
+}
+
+/// Marks the last encoded exports of a target world as successfully built.
+pub fn record_built(output_dir: &Path) -> Result<()> {
+    let path = output_dir.join(EXPORTS_FILE);
+    if !path.is_file() {
+        return Ok(());
+    }
+
+    let built = output_dir.join(BUILT_EXPORTS_FILE);
+    fs::copy(&path, &built).with_context(|| {
+        format!(
+            "failed to write target exports file `{path}`",
+            path = built.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Reads the exports recorded in the given file, if it exists.
+fn read(path: &Path) -> Result<Option<Exports>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read target exports file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    // A record in an outdated format is treated as missing
+    Ok(serde_json::from_str(&contents).ok())
+}
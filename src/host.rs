@@ -0,0 +1,102 @@
+//! Module for generating a host crate for the target world of a component.
+//!
+//! The host crate contains the fully resolved WIT of the target world (the
+//! target package and every package it depends on) and uses the `bindgen!`
+//! macro of `wasmtime` to generate the traits and linker functions that a
+//! native embedder implements and calls to run the component.
+//!
+//! As the WIT is written from the same resolution as the guest bindings, the
+//! host and the guest can't drift apart as long as the host crate is
+//! regenerated along with the guest bindings.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use wit_component::WitPrinter;
+use wit_parser::{Resolve, WorldId};
+
+/// The version requirement of `wasmtime` used by generated host crates.
+const WASMTIME_VERSION: &str = "14.0.0";
+
+/// Generates the files of a host crate for the given world.
+///
+/// The paths of the returned files are relative to the directory of the
+/// host crate.
+pub fn generate(
+    resolve: &Resolve,
+    world: WorldId,
+    package: &str,
+    version: &str,
+    command: &str,
+) -> Result<Vec<(PathBuf, String)>> {
+    let world_package = resolve.worlds[world]
+        .package
+        .context("target world must have a package")?;
+
+    let header = format!(
+        "Generated by `cargo-component` from the target world of package `{package}`.\n\
+         DO NOT EDIT! Regenerate with `{command}`."
+    );
+    let comment = |prefix: &str| {
+        header
+            .lines()
+            .map(|l| format!("{prefix} {l}\n"))
+            .collect::<String>()
+    };
+
+    let mut files = Vec::new();
+    files.push((
+        PathBuf::from("Cargo.toml"),
+        format!(
+            "{comment}\n\
+             [package]\n\
+             name = \"{package}-host\"\n\
+             version = \"{version}\"\n\
+             edition = \"2021\"\n\
+             publish = false\n\
+             \n\
+             [dependencies]\n\
+             wasmtime = {{ version = \"{WASMTIME_VERSION}\", features = [\"component-model\"] }}\n\
+             \n\
+             [workspace]\n",
+            comment = comment("#")
+        ),
+    ));
+
+    files.push((
+        PathBuf::from("src/lib.rs"),
+        format!(
+            "{comment}\n\
+             wasmtime::component::bindgen!({{\n    \
+                 world: \"{world}\",\n    \
+                 path: \"wit\",\n\
+             }});\n",
+            comment = comment("//"),
+            world = resolve.worlds[world].name
+        ),
+    ));
+
+    // The target package is the root package and the others are its dependencies
+    let mut printer = WitPrinter::default();
+    let mut packages: Vec<_> = resolve.packages.iter().collect();
+    packages.sort_by_key(|(_, p)| p.name.to_string());
+    for (id, pkg) in packages {
+        let path = if id == world_package {
+            PathBuf::from("wit/world.wit")
+        } else {
+            let mut dir = format!("{ns}-{name}", ns = pkg.name.namespace, name = pkg.name.name);
+            if let Some(version) = &pkg.name.version {
+                dir.push_str(&format!("-{version}"));
+            }
+
+            PathBuf::from("wit/deps").join(dir).join("package.wit")
+        };
+
+        let wit = printer
+            .print(resolve, id)
+            .with_context(|| format!("failed to print WIT package `{name}`", name = pkg.name))?;
+
+        files.push((path, format!("{comment}\n{wit}", comment = comment("//"))));
+    }
+
+    Ok(files)
+}
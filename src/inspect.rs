@@ -0,0 +1,219 @@
+//! Module for inspecting binary components and WIT packages.
+//!
+//! An inspection describes the world of a component (its imports and
+//! exports), its embedded registry metadata, and the structure of its nested
+//! core modules and components along with their producers sections.
+
+use anyhow::{Context, Result};
+use cargo_component_core::info::{describe_item, write_registry_metadata};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::fmt::{self, Write};
+use wasm_metadata::RegistryMetadata;
+use wasmparser::{Encoding, Parser, Payload, ProducersSectionReader};
+use wit_component::DecodedWasm;
+
+/// The name of the custom section describing the producers of a module or
+/// component.
+const PRODUCERS_SECTION_NAME: &str = "producers";
+
+/// Represents the inspection of a component or WIT package.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Inspection {
+    /// Where the inspected file came from (a path or registry package).
+    pub source: String,
+    /// The kind of the file: `component` or `wit-package`.
+    pub kind: &'static str,
+    /// The imports of the component.
+    pub imports: Vec<String>,
+    /// The exports of the component.
+    pub exports: Vec<String>,
+    /// The worlds and interfaces of the WIT package.
+    pub definitions: Vec<String>,
+    /// The embedded registry metadata, if any.
+    pub registry_metadata: Option<RegistryMetadata>,
+    /// The structure of the component and its nested modules and components.
+    pub structure: Node,
+}
+
+/// Represents a component or core module within an inspected file.
+#[derive(Debug, Serialize)]
+pub struct Node {
+    /// The kind of the node: `component` or `module`.
+    pub kind: &'static str,
+    /// The size of the node, in bytes.
+    pub size: usize,
+    /// The fields of the producers section of the node, if any (e.g.
+    /// `processed-by`), each mapping a name to a version.
+    pub producers: IndexMap<String, IndexMap<String, String>>,
+    /// The nested core modules and components of a component.
+    pub children: Vec<Node>,
+}
+
+impl Inspection {
+    /// Inspects the given component or WIT package.
+    pub fn new(source: String, bytes: &[u8]) -> Result<Self> {
+        let (kind, imports, exports, definitions) =
+            match wit_component::decode(bytes).context("failed to decode component")? {
+                DecodedWasm::Component(resolve, world) => {
+                    let world = &resolve.worlds[world];
+                    (
+                        "component",
+                        world
+                            .imports
+                            .iter()
+                            .map(|(key, item)| describe_item(&resolve, key, item))
+                            .collect(),
+                        world
+                            .exports
+                            .iter()
+                            .map(|(key, item)| describe_item(&resolve, key, item))
+                            .collect(),
+                        Vec::new(),
+                    )
+                }
+                DecodedWasm::WitPackage(resolve, package) => {
+                    let package = &resolve.packages[package];
+                    (
+                        "wit-package",
+                        Vec::new(),
+                        Vec::new(),
+                        package
+                            .worlds
+                            .keys()
+                            .map(|name| format!("world `{name}`"))
+                            .chain(
+                                package
+                                    .interfaces
+                                    .keys()
+                                    .map(|name| format!("interface `{name}`")),
+                            )
+                            .collect(),
+                    )
+                }
+            };
+
+        Ok(Self {
+            source,
+            kind,
+            imports,
+            exports,
+            definitions,
+            registry_metadata: RegistryMetadata::from_wasm(bytes)
+                .context("failed to parse registry metadata")?,
+            structure: structure(bytes)?,
+        })
+    }
+}
+
+/// Gets the structure of a component and its nested modules and components.
+fn structure(bytes: &[u8]) -> Result<Node> {
+    let mut stack: Vec<Node> = Vec::new();
+    let mut sizes = vec![bytes.len()];
+    let mut root = None;
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload.context("failed to parse component")? {
+            Payload::Version { encoding, .. } => stack.push(Node {
+                kind: match encoding {
+                    Encoding::Module => "module",
+                    Encoding::Component => "component",
+                },
+                size: sizes.pop().unwrap_or_default(),
+                producers: IndexMap::new(),
+                children: Vec::new(),
+            }),
+            Payload::ModuleSection {
+                unchecked_range, ..
+            }
+            | Payload::ComponentSection {
+                unchecked_range, ..
+            } => {
+                sizes.push(unchecked_range.end - unchecked_range.start);
+            }
+            Payload::CustomSection(reader) if reader.name() == PRODUCERS_SECTION_NAME => {
+                let node = stack.last_mut().expect("node should be present");
+                let fields = ProducersSectionReader::new(reader.data(), reader.data_offset())
+                    .context("failed to parse producers section")?;
+                for field in fields {
+                    let field = field.context("failed to parse producers field")?;
+                    let values = node.producers.entry(field.name.to_string()).or_default();
+                    for value in field.values {
+                        let value = value.context("failed to parse producers field value")?;
+                        values.insert(value.name.to_string(), value.version.to_string());
+                    }
+                }
+            }
+            Payload::End(_) => {
+                let node = stack.pop().expect("node should be present");
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => root = Some(node),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    root.context("failed to parse component")
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "source: {source}", source = self.source)?;
+        writeln!(f, "kind: {kind}", kind = self.kind)?;
+
+        for import in &self.imports {
+            writeln!(f, "import: {import}")?;
+        }
+
+        for export in &self.exports {
+            writeln!(f, "export: {export}")?;
+        }
+
+        for definition in &self.definitions {
+            writeln!(f, "definition: {definition}")?;
+        }
+
+        if let Some(metadata) = &self.registry_metadata {
+            write_registry_metadata(f, metadata)?;
+        }
+
+        writeln!(f, "structure:")?;
+        write_node(f, &self.structure, 1)
+    }
+}
+
+/// Writes a node of the structure of a component and its children.
+fn write_node(f: &mut impl Write, node: &Node, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    writeln!(
+        f,
+        "{indent}{kind} ({size} bytes)",
+        kind = node.kind,
+        size = node.size
+    )?;
+
+    for (field, values) in &node.producers {
+        writeln!(
+            f,
+            "{indent}  {field}: {values}",
+            values = values
+                .iter()
+                .map(|(name, version)| if version.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{name} {version}")
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+    }
+
+    for child in &node.children {
+        write_node(f, child, depth + 1)?;
+    }
+
+    Ok(())
+}
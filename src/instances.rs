@@ -0,0 +1,82 @@
+//! Module for exporting additional named instances of interfaces.
+//!
+//! The component model allows a component to export the same interface more
+//! than once under distinct names (e.g. an `admin` and a `public` instance of
+//! `wasi:http/incoming-handler`). The `instances` table of a target entry maps
+//! the name of each instance to the interface it implements; the instances
+//! are added to the exports of the target world, so the generated bindings
+//! have a separate export trait for each (e.g. `bindings::exports::admin`).
+
+use crate::{remap::names, transitive::dependencies};
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use std::collections::HashMap;
+use wit_parser::{Resolve, WorldId, WorldItem, WorldKey};
+
+/// Adds the named instances of the `instances` table of a target entry to
+/// the exports of its target world.
+pub fn apply(
+    resolve: &mut Resolve,
+    world: WorldId,
+    instances: &HashMap<String, String>,
+) -> Result<()> {
+    let mut entries: Vec<_> = instances.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (instance, interface) in entries {
+        if wit_parser::validate_id(instance).is_err() {
+            bail!(
+                "invalid instance name `{instance}` in `package.metadata.component.target.instances`; instance names must be in kebab case (e.g. `admin-handler`)"
+            );
+        }
+
+        let matches: Vec<_> = resolve
+            .interfaces
+            .iter()
+            .filter(|(id, _)| names(resolve, *id).contains(interface))
+            .map(|(id, _)| id)
+            .collect();
+
+        let id = match matches[..] {
+            [id] => id,
+            [] => bail!(
+                "interface `{interface}` of instance `{instance}` in `package.metadata.component.target.instances` was not found in the target world or its dependencies"
+            ),
+            _ => bail!(
+                "interface `{interface}` of instance `{instance}` in `package.metadata.component.target.instances` is ambiguous; specify its version (e.g. `{interface}@1.0.0`)"
+            ),
+        };
+
+        let key = WorldKey::Name(instance.clone());
+        let target = &resolve.worlds[world];
+        if target.exports.contains_key(&key) || target.imports.contains_key(&key) {
+            bail!(
+                "instance `{instance}` in `package.metadata.component.target.instances` conflicts with an item of the same name in the target world"
+            );
+        }
+
+        // The interfaces whose types the instance uses are imported
+        let mut used = IndexSet::new();
+        let mut pending: Vec<_> = dependencies(resolve, id).into_iter().collect();
+        while let Some(dep) = pending.pop() {
+            if used.insert(dep) {
+                pending.extend(dependencies(resolve, dep));
+            }
+        }
+
+        let target = &mut resolve.worlds[world];
+        for dep in used {
+            let dep_key = WorldKey::Interface(dep);
+            if !target.exports.contains_key(&dep_key) {
+                target
+                    .imports
+                    .entry(dep_key)
+                    .or_insert(WorldItem::Interface(dep));
+            }
+        }
+
+        target.exports.insert(key, WorldItem::Interface(id));
+    }
+
+    Ok(())
+}
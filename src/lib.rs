@@ -2,46 +2,100 @@
 
 #![deny(missing_docs)]
 
-use crate::target::install_wasm32_wasi;
+use crate::target::install_wasm_target;
 use anyhow::{bail, Context, Result};
+pub use bindgen::Language;
 use bindings::BindingsEncoder;
 use bytes::Bytes;
 use cargo_component_core::{
+    audit,
+    git::{self, GitProvenance},
     lock::{LockFile, LockFileResolver, LockedPackage, LockedPackageVersion},
-    registry::create_client,
-    terminal::Colors,
+    oci::{self, OciClient},
+    registry::{create_client, vendored_path, DependencyResolution, DependencyResolutionMap},
+    signing::Signer,
+    terminal::{Colors, Verbosity},
 };
-use cargo_metadata::{Metadata, MetadataCommand, Package};
-use config::{CargoArguments, CargoPackageSpec, Config};
+use cargo_metadata::{CargoOpt, Metadata, MetadataCommand, Package};
+use config::{
+    BuildArguments, CargoArguments, CargoPackageSpec, Config, DebugInfo, RunArguments,
+    SizeReportFormat,
+};
+use deployment::{ComponentManifest, DeploymentManifest};
+use indexmap::IndexMap;
 use lock::{acquire_lock_file_ro, acquire_lock_file_rw};
-use metadata::ComponentMetadata;
+use metadata::{
+    artifact_dependencies, Adapter, ComponentMetadata, OptLevel, Raw, SectionPolicies,
+    SectionPolicy, ARTIFACTS_KEY,
+};
 use registry::{PackageDependencyResolution, PackageResolutionMap};
 use semver::Version;
+use sha2::{Digest, Sha256};
 use std::{
     borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
     fs::{self, File},
     io::Read,
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, ExitStatus},
     time::{Duration, SystemTime},
 };
 use warg_client::storage::{ContentStorage, PublishEntry, PublishInfo};
-use warg_crypto::signing::PrivateKey;
 use warg_protocol::registry::PackageId;
-use wasm_metadata::{Link, LinkType, RegistryMetadata};
+use wasm_encoder::{Encode, Section};
+use wasm_metadata::{Link, LinkType, Producers, RegistryMetadata};
 use wit_component::ComponentEncoder;
+use wit_parser::{WorldItem, WorldKey};
 
+mod bench;
+mod bindgen;
 mod bindings;
 pub mod commands;
+mod compat;
 pub mod config;
+mod conveniences;
+mod deployment;
+mod deprecation;
+mod doc;
+mod emit;
+mod explain;
+mod exports;
+mod generate;
 mod generator;
+mod guidance;
+mod host;
+mod inspect;
+mod instances;
 mod lock;
 mod metadata;
+mod migrate;
+mod minimize;
+mod mock;
 mod registry;
+mod remap;
+mod rustflags;
+mod sbom;
+pub mod signature;
+mod size;
+mod sources;
+mod strip;
+mod stub;
 mod target;
+mod template;
+mod transitive;
+mod wac;
+mod watch;
+mod why;
 
 fn is_wasm_target(target: &str) -> bool {
-    target == "wasm32-wasi" || target == "wasm32-unknown-unknown"
+    target == "wasm32-wasi" || target == "wasm32-wasip2" || target == "wasm32-unknown-unknown"
+}
+
+/// Determines if the toolchain produces components for the given target
+/// itself, in which case no adapter is used to componentize its outputs.
+fn is_native_component_target(target: &str) -> bool {
+    target == "wasm32-wasip2"
 }
 
 /// Represents a cargo package paired with its component metadata.
@@ -55,20 +109,52 @@ pub struct PackageComponentMetadata<'a> {
 }
 
 impl<'a> PackageComponentMetadata<'a> {
-    /// Creates a new package metadata from the given package.
-    pub fn new(package: &'a Package) -> Result<Self> {
+    /// Creates a new package metadata from the given package of the workspace.
+    pub fn new(metadata: &Metadata, package: &'a Package) -> Result<Self> {
+        let mut component = ComponentMetadata::from_package(package)?;
+        if let Some(component) = &mut component {
+            component.generated_dir = component.section.generate.as_ref().map(|_| {
+                generate::output_dir(
+                    metadata
+                        .target_directory
+                        .join("bindings")
+                        .join(&package.name)
+                        .as_std_path(),
+                )
+            });
+        }
+
         Ok(Self {
             package,
-            metadata: ComponentMetadata::from_package(package)?,
+            metadata: component,
         })
     }
 }
 
+/// The error returned when a spawned cargo command exits with a non-zero status.
+#[derive(Debug, Clone, Copy)]
+pub struct CargoCommandError {
+    /// The exit status of the cargo command.
+    pub status: ExitStatus,
+}
+
+impl fmt::Display for CargoCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cargo command failed: {status}", status = self.status)
+    }
+}
+
+impl std::error::Error for CargoCommandError {}
+
 /// Runs the cargo command as specified in the configuration.
 ///
 /// Note: if the command returns a non-zero status, this
 /// function will exit the process.
 ///
+/// If `--watch` was specified for a build, this function rebuilds
+/// whenever the inputs of the packages change and does not return
+/// unless an error occurs.
+///
 /// Returns any relevant output components.
 pub async fn run_cargo_command(
     config: &Config,
@@ -78,13 +164,76 @@ pub async fn run_cargo_command(
     cargo_args: &CargoArguments,
     spawn_args: &[String],
 ) -> Result<Vec<PathBuf>> {
-    encode_targets(config, metadata, packages, cargo_args).await?;
+    if matches!(subcommand, Some("b") | Some("build") | Some("rustc")) {
+        let (build_args, _) = BuildArguments::extract(spawn_args.iter().cloned())?;
+        if build_args.watch {
+            watch::watch(
+                config,
+                subcommand,
+                cargo_args,
+                spawn_args,
+                build_args.exec.as_deref(),
+            )
+            .await?;
+            return Ok(Vec::new());
+        }
+    }
+
+    match execute_cargo_command(
+        config, metadata, packages, subcommand, cargo_args, spawn_args,
+    )
+    .await
+    {
+        Ok(outputs) => Ok(outputs),
+        Err(e) => match e.downcast_ref::<CargoCommandError>() {
+            Some(e) => std::process::exit(e.status.code().unwrap_or(1)),
+            None => Err(e),
+        },
+    }
+}
+
+/// Runs the cargo command as specified in the configuration.
+///
+/// Unlike `run_cargo_command`, a non-zero exit status of cargo is
+/// returned as a `CargoCommandError` rather than exiting the process.
+async fn execute_cargo_command(
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    subcommand: Option<&str>,
+    cargo_args: &CargoArguments,
+    spawn_args: &[String],
+) -> Result<Vec<PathBuf>> {
+    let (adapters, mut dev_dependencies) =
+        encode_targets(config, metadata, packages, cargo_args).await?;
 
     let cargo = std::env::var("CARGO")
         .map(PathBuf::from)
         .ok()
         .unwrap_or_else(|| PathBuf::from("cargo"));
 
+    let is_build = matches!(subcommand, Some("b") | Some("build") | Some("rustc"));
+    let is_run = matches!(
+        subcommand,
+        Some("r") | Some("run") | Some("t") | Some("test") | Some("bench")
+    );
+    let is_check = matches!(subcommand, Some("c") | Some("check"));
+    let is_doc = matches!(subcommand, Some("d") | Some("doc"));
+
+    // Remove the arguments for running components as cargo doesn't know about them
+    let (run_args, spawn_args) = if is_run {
+        RunArguments::extract(spawn_args.iter().cloned())?
+    } else {
+        (RunArguments::default(), spawn_args.to_vec())
+    };
+
+    // Remove the arguments for building components as cargo doesn't know about them
+    let (build_args, spawn_args) = if is_build {
+        BuildArguments::extract(spawn_args.into_iter())?
+    } else {
+        (BuildArguments::default(), spawn_args)
+    };
+
     let mut args = spawn_args.iter().peekable();
     if let Some(arg) = args.peek() {
         if *arg == "component" {
@@ -102,22 +251,91 @@ pub async fn run_cargo_command(
     let mut cmd = Command::new(&cargo);
     cmd.args(args);
 
-    let is_build = matches!(subcommand, Some("b") | Some("build") | Some("rustc"));
+    // Handle the target for build, run, check, and doc commands
+    // Checking and documenting use the same target as building so that the
+    // results match, but the outputs are never componentized
+    if is_build || is_run || is_check || is_doc {
+        // The components the packages depend on are built first, as they may
+        // be embedded when the packages are compiled
+        for (var, path) in build_artifact_dependencies(config, metadata, packages, cargo_args)? {
+            cmd.env(var, path);
+        }
 
-    // Handle the target for build commands
-    if is_build {
-        install_wasm32_wasi(config)?;
+        // Hosts that only depend on components are built for the host target
+        let hosts_only = packages
+            .iter()
+            .all(|p| p.metadata.is_none() && p.package.metadata.get(ARTIFACTS_KEY).is_some());
+        if hosts_only && !packages.is_empty() {
+            log::debug!("building host packages without a WebAssembly target");
+        } else {
+            add_wasm_target(config, cargo_args, &mut cmd)?;
+        }
+    }
+
+    if build_args.reproducible {
+        configure_reproducible_build(&mut cmd, metadata)?;
+    }
 
-        // Add an implicit wasm32-wasi target if there isn't a wasm target present
-        if !cargo_args.targets.iter().any(|t| is_wasm_target(t)) {
-            cmd.arg("--target").arg("wasm32-wasi");
+    // Keeping the debug information of a component requires the compiler to
+    // emit it, which the release profile does not by default
+    if matches!(
+        build_args.debug_info,
+        Some(DebugInfo::Keep) | Some(DebugInfo::Split)
+    ) {
+        add_rustflags(&mut cmd, ["-Cdebuginfo=2".to_string()])?;
+    }
+
+    // Keep the raw exports of the packages when linking the core modules;
+    // every package is linked with the same flags, so missing symbols are
+    // ignored by the linker and checked when componentizing instead
+    let raw_exports: BTreeSet<&str> = packages
+        .iter()
+        .filter_map(|p| p.metadata.as_ref())
+        .flat_map(|m| m.section.raw.exports.iter().map(String::as_str))
+        .collect();
+    if (is_build || is_run) && !raw_exports.is_empty() {
+        add_rustflags(
+            &mut cmd,
+            raw_exports
+                .iter()
+                .map(|name| format!("-Clink-arg=--export-if-defined={name}")),
+        )?;
+    }
+
+    // Run the outputs as components using `cargo-component` as the target runner
+    // Components produced natively for `wasm32-wasip2` are run the same way
+    let runners: Vec<_> = [RUNNER_ENV_VAR, WASIP2_RUNNER_ENV_VAR]
+        .into_iter()
+        .filter(|var| std::env::var_os(var).is_none())
+        .collect();
+    if is_run && !runners.is_empty() {
+        build_dev_dependencies(config, cargo_args, &mut dev_dependencies)?;
+
+        let exe = std::env::current_exe()
+            .context("failed to determine the path to the `cargo-component` executable")?;
+        for var in runners {
+            cmd.env(var, format!("{exe} {RUNNER_ARG}", exe = exe.display()));
         }
+        cmd.env(
+            RUNTIME_ARGS_ENV_VAR,
+            serde_json::to_string(&run_args.runtime_args())?,
+        );
+        cmd.env(PROFILE_ENV_VAR, cargo_args.profile());
+        cmd.env(ADAPTERS_ENV_VAR, serde_json::to_string(&adapters)?);
+        cmd.env(
+            DEV_DEPENDENCIES_ENV_VAR,
+            serde_json::to_string(&dev_dependencies)?,
+        );
+        cmd.env(
+            CONFIG_ENV_VAR,
+            serde_json::to_string(&run_args.config_vars())?,
+        );
     }
 
     match cmd.status() {
         Ok(status) => {
             if !status.success() {
-                std::process::exit(status.code().unwrap_or(1));
+                return Err(CargoCommandError { status }.into());
             }
         }
         Err(e) => {
@@ -125,6 +343,15 @@ pub async fn run_cargo_command(
         }
     }
 
+    // The packages compiled, so their implementations match their target worlds
+    if is_build || is_run || is_check {
+        record_built_exports(metadata, packages)?;
+    }
+
+    if is_doc {
+        document_targets(config, metadata, packages, cargo_args)?;
+    }
+
     let mut outputs = Vec::new();
     if is_build {
         log::debug!("searching for WebAssembly modules to componentize");
@@ -135,547 +362,3588 @@ pub async fn run_cargo_command(
             .filter(|t| is_wasm_target(t))
             .chain(cargo_args.targets.is_empty().then_some("wasm32-wasi"));
 
+        let bindings_dir = metadata.target_directory.join("bindings");
+        let cargo_metadata = metadata;
+        let mut deployment_manifest = DeploymentManifest::default();
         for target in targets {
+            let native = is_native_component_target(target);
             let out_dir = metadata
                 .target_directory
                 .join(target)
-                .join(if cargo_args.release {
-                    "release"
-                } else {
-                    "debug"
-                });
+                .join(cargo_args.profile_dir());
 
-            for PackageComponentMetadata { package, metadata } in packages {
+            for package_metadata in packages {
+                let PackageComponentMetadata { package, metadata } = package_metadata;
                 let metadata = match metadata {
                     Some(metadata) => metadata,
                     None => continue,
                 };
 
-                let is_bin = package.targets.iter().any(|t| t.is_bin());
+                // Each binary target and the `cdylib` library target are
+                // componentized separately; only the binaries selected with
+                // `--bin` are componentized if any were specified
+                let mut found = false;
+                for target in &package.targets {
+                    let (file_name, bin) = if target.is_bin() {
+                        if !cargo_args.bin_selected(&target.name) {
+                            continue;
+                        }
+
+                        (target.name.clone(), Some(target.name.as_str()))
+                    } else if target.kind.iter().any(|k| k == "cdylib") {
+                        if !cargo_args.lib_selected() {
+                            continue;
+                        }
 
-                // First try for <name>.wasm
-                let path = out_dir.join(&package.name).with_extension("wasm");
-                if path.exists() {
-                    create_component(config, metadata, path.as_std_path(), is_bin)?;
-                    outputs.push(path.to_path_buf().into_std_path_buf());
-                    continue;
-                }
+                        (target.name.replace('-', "_"), None)
+                    } else {
+                        continue;
+                    };
+
+                    let path = out_dir.join(file_name).with_extension("wasm");
+                    if !path.exists() {
+                        log::debug!(
+                            "no output found for target `{target}` of package `{name}`",
+                            target = target.name,
+                            name = package.name
+                        );
+                        continue;
+                    }
+
+                    // The `--debug-info` argument takes precedence over the
+                    // setting of the profile or binary target
+                    let debug_info = build_args
+                        .debug_info
+                        .map(SectionPolicy::from)
+                        .or_else(|| metadata.debug_info(cargo_args.profile(), bin));
+                    let mut policies = metadata.section_policies(cargo_args.profile(), bin);
+                    if let Some(debug_info) = debug_info {
+                        policies.debug_info = debug_info;
+                    }
+
+                    if native {
+                        finish_native_component(
+                            config,
+                            Some(metadata),
+                            bindings_dir.as_std_path(),
+                            cargo_args.profile(),
+                            bin,
+                            path.as_std_path(),
+                            &build_args,
+                        )?;
+                    } else {
+                        let adapter = resolve_adapter(
+                            metadata.adapter(cargo_args.profile(), bin),
+                            adapters.get(&package.name),
+                        )?;
+
+                        create_component(
+                            config,
+                            adapter.as_deref(),
+                            Some(&metadata.section.raw),
+                            metadata.optimize(cargo_args.profile(), bin),
+                            matches!(debug_info, Some(SectionPolicy::Keep | SectionPolicy::Split)),
+                            path.as_std_path(),
+                            bin.is_some(),
+                            &build_args,
+                        )?;
+                    }
+                    add_export_versions(metadata, path.as_std_path())?;
+                    validate_config(config, metadata, path.as_std_path())?;
+                    strip_component(config, &policies, path.as_std_path())?;
+
+                    // Compose the library component with its dependencies if
+                    // the package has a composition script
+                    let script = package
+                        .manifest_path
+                        .with_file_name(COMPOSE_SCRIPT_FILE_NAME)
+                        .into_std_path_buf();
+                    let composed = bin.is_none() && script.is_file();
+                    if composed {
+                        compose(
+                            config,
+                            cargo_metadata,
+                            package_metadata,
+                            path.as_std_path(),
+                            Some(&script),
+                            path.with_extension("composed.wasm").as_std_path(),
+                            cargo_args.network_allowed(),
+                        )
+                        .await?;
+                    }
+
+                    // The deployment manifest describes the final component
+                    if build_args.manifest_out.is_some() {
+                        let final_path = if composed {
+                            path.with_extension("composed.wasm")
+                        } else {
+                            path.clone()
+                        };
+                        deployment_manifest.components.push(ComponentManifest::new(
+                            final_path.as_std_path(),
+                            package,
+                            bin,
+                            metadata,
+                        )?);
+                    }
 
-                // Next, try replacing `-` with `_`
-                let path = out_dir
-                    .join(package.name.replace('-', "_"))
-                    .with_extension("wasm");
-                if path.exists() {
-                    create_component(config, metadata, path.as_std_path(), is_bin)?;
                     outputs.push(path.to_path_buf().into_std_path_buf());
-                    continue;
+                    found = true;
                 }
 
-                log::debug!("no output found for package `{name}`", name = package.name);
+                if !found {
+                    log::debug!("no output found for package `{name}`", name = package.name);
+                }
             }
         }
+
+        if let Some(manifest_out) = &build_args.manifest_out {
+            deployment_manifest.write(manifest_out)?;
+            config.terminal().status(
+                "Created",
+                format!(
+                    "deployment manifest `{path}`",
+                    path = manifest_out.display()
+                ),
+            )?;
+        }
     }
 
     Ok(outputs)
 }
 
-fn last_modified_time(path: &Path) -> Result<SystemTime> {
-    path.metadata()
-        .with_context(|| {
-            format!(
-                "failed to read file metadata for `{path}`",
-                path = path.display()
-            )
-        })?
-        .modified()
-        .with_context(|| {
-            format!(
-                "failed to retrieve last modified time for `{path}`",
-                path = path.display()
-            )
-        })
+/// Marks the exports of the target worlds of the given packages as
+/// successfully built.
+fn record_built_exports(
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+) -> Result<()> {
+    let bindings_dir = metadata.target_directory.join("bindings");
+    for metadata in packages.iter().filter_map(|p| p.metadata.as_ref()) {
+        let dir = bindings_dir.join(&metadata.name).into_std_path_buf();
+        guidance::record_built(&dir)?;
+        for name in metadata.section.targets.keys() {
+            guidance::record_built(&dir.join("targets").join(name))?;
+        }
+    }
+
+    Ok(())
 }
 
-/// Loads the workspace metadata based on the given manifest path.
-pub fn load_metadata(manifest_path: Option<&Path>) -> Result<Metadata> {
-    let mut command = MetadataCommand::new();
-    command.no_deps();
+/// Configures the environment of a cargo command for a reproducible build.
+///
+/// The workspace root and cargo home directory are remapped in the compiler
+/// output so that the build does not depend on where it happens, and
+/// `SOURCE_DATE_EPOCH` is set for build scripts that embed timestamps.
+fn configure_reproducible_build(cmd: &mut Command, metadata: &Metadata) -> Result<()> {
+    let mut flags = Vec::new();
+    let mut remap = |from: &Path, to: &str| -> Result<()> {
+        let from = from
+            .to_str()
+            .with_context(|| format!("path `{path}` is not valid UTF-8", path = from.display()))?;
+        flags.push(format!("--remap-path-prefix={from}={to}"));
+        Ok(())
+    };
 
-    if let Some(path) = manifest_path {
-        log::debug!(
-            "loading metadata from manifest `{path}`",
-            path = path.display()
-        );
-        command.manifest_path(path);
-    } else {
-        log::debug!("loading metadata from current directory");
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|dir| dir.join(".cargo")));
+    if let Some(cargo_home) = cargo_home {
+        remap(&cargo_home, "/cargo")?;
     }
 
-    command.exec().context("failed to load cargo metadata")
+    // The workspace is remapped last so that it takes precedence for
+    // workspaces nested in the cargo home directory
+    remap(metadata.workspace_root.as_std_path(), ".")?;
+
+    log::debug!("building reproducibly with additional rustflags `{flags:?}`");
+    add_rustflags(cmd, flags)?;
+
+    if std::env::var_os("SOURCE_DATE_EPOCH").is_none() {
+        cmd.env("SOURCE_DATE_EPOCH", "0");
+    }
+
+    Ok(())
 }
 
-/// Loads the component metadata for the given package specs.
+/// Adds flags to the rustflags of a cargo command.
 ///
-/// If `workspace` is true, all workspace packages are loaded.
-pub fn load_component_metadata<'a>(
-    metadata: &'a Metadata,
-    specs: impl ExactSizeIterator<Item = &'a CargoPackageSpec>,
-    workspace: bool,
-) -> Result<Vec<PackageComponentMetadata<'a>>> {
-    let pkgs = if workspace {
-        metadata.workspace_packages()
-    } else if specs.len() > 0 {
-        let mut pkgs = Vec::with_capacity(specs.len());
-        for spec in specs {
-            let pkg = metadata
-                .packages
-                .iter()
-                .find(|p| {
-                    p.name == spec.name
-                        && match spec.version.as_ref() {
-                            Some(v) => &p.version == v,
-                            None => true,
-                        }
-                })
-                .with_context(|| {
-                    format!("package ID specification `{spec}` did not match any packages")
-                })?;
-            pkgs.push(pkg);
-        }
+/// Flags already added to the command are preserved, as are the flags cargo
+/// would otherwise use from the environment or its configuration files, which
+/// setting `CARGO_ENCODED_RUSTFLAGS` overrides; the encoded form is used as
+/// paths may contain spaces.
+fn add_rustflags(cmd: &mut Command, additional: impl IntoIterator<Item = String>) -> Result<()> {
+    let current = cmd
+        .get_envs()
+        .find(|(key, _)| *key == "CARGO_ENCODED_RUSTFLAGS")
+        .and_then(|(_, value)| value)
+        .map(|value| value.to_string_lossy().into_owned());
 
-        pkgs
-    } else {
-        // TODO: this should be the default members, or default to all members
-        // However, `cargo-metadata` doesn't return the workspace default members yet
-        // See: https://github.com/oli-obk/cargo_metadata/issues/215
-        metadata.workspace_packages()
+    let mut flags: Vec<String> = match current {
+        Some(flags) if flags.is_empty() => Vec::new(),
+        Some(flags) => flags.split('\x1f').map(ToString::to_string).collect(),
+        None => {
+            // The configured flags depend on the targets of the build
+            let mut targets = Vec::new();
+            let mut args = cmd.get_args().map(|a| a.to_string_lossy());
+            while let Some(arg) = args.next() {
+                if arg == "--target" {
+                    targets.extend(args.next().map(|t| t.into_owned()));
+                } else if let Some(target) = arg.strip_prefix("--target=") {
+                    targets.push(target.to_string());
+                }
+            }
+
+            rustflags::configured(&targets)?
+        }
     };
 
-    pkgs.into_iter()
-        .map(PackageComponentMetadata::new)
-        .collect::<Result<_>>()
+    flags.extend(additional);
+    cmd.env("CARGO_ENCODED_RUSTFLAGS", flags.join("\x1f"));
+    cmd.env_remove("RUSTFLAGS");
+    Ok(())
 }
 
-async fn encode_targets(
+/// Generates HTML documentation of the target worlds of the given packages.
+///
+/// The documentation is written alongside the rustdoc output of each package
+/// as `wit.html` for the default target and `wit-<name>.html` for named targets.
+fn document_targets(
     config: &Config,
     metadata: &Metadata,
     packages: &[PackageComponentMetadata<'_>],
     cargo_args: &CargoArguments,
 ) -> Result<()> {
     let bindings_dir = metadata.target_directory.join("bindings");
-    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
-    let lock_file = file_lock
-        .as_ref()
-        .map(|f| {
-            LockFile::read(f.file()).with_context(|| {
-                format!(
-                    "failed to read lock file `{path}`",
-                    path = f.path().display()
-                )
-            })
-        })
-        .transpose()?;
+    let targets = cargo_args
+        .targets
+        .iter()
+        .map(String::as_str)
+        .filter(|t| is_wasm_target(t))
+        .chain(cargo_args.targets.is_empty().then_some("wasm32-wasi"));
 
-    let resolver = lock_file.as_ref().map(LockFileResolver::new);
-    let map =
-        create_resolution_map(config, packages, resolver, cargo_args.network_allowed()).await?;
-    for PackageComponentMetadata { package, .. } in packages {
-        let resolution = match map.get(&package.id) {
-            Some(resolution) => resolution,
-            None => continue,
-        };
+    for target in targets {
+        let doc_dir = metadata.target_directory.join(target).join("doc");
 
-        encode_target_world(config, resolution, bindings_dir.as_std_path()).await?;
-    }
+        for PackageComponentMetadata { package, metadata } in packages {
+            let metadata = match metadata {
+                Some(metadata) => metadata,
+                None => continue,
+            };
 
-    // Update the lock file if it exists or if the new lock file is non-empty
-    let new_lock_file = map.to_lock_file();
-    if (lock_file.is_some() || !new_lock_file.packages.is_empty())
-        && Some(&new_lock_file) != lock_file.as_ref()
-    {
-        drop(file_lock);
-        let file_lock = acquire_lock_file_rw(
-            config.terminal(),
-            metadata,
-            cargo_args.lock_update_allowed(),
-            cargo_args.locked,
-        )?;
-        new_lock_file
-            .write(file_lock.file(), "cargo-component")
-            .with_context(|| {
-                format!(
-                    "failed to write lock file `{path}`",
-                    path = file_lock.path().display()
-                )
-            })?;
-    }
+            let crate_name = package.name.replace('-', "_");
+            let crate_dir = doc_dir.join(&crate_name);
+            if !crate_dir.is_dir() {
+                log::debug!(
+                    "no documentation found for package `{name}`",
+                    name = package.name
+                );
+                continue;
+            }
 
-    Ok(())
-}
+            let names = std::iter::once(None).chain(metadata.section.targets.keys().map(Some));
+            for name in names {
+                let encoded_dir = match name {
+                    Some(name) => bindings_dir.join(&metadata.name).join("targets").join(name),
+                    None => bindings_dir.join(&metadata.name),
+                };
 
-async fn create_resolution_map<'a>(
-    config: &Config,
-    packages: &'a [PackageComponentMetadata<'_>],
-    lock_file: Option<LockFileResolver<'_>>,
-    network_allowed: bool,
-) -> Result<PackageResolutionMap<'a>> {
-    let mut map = PackageResolutionMap::default();
+                let (resolve, world) = decode_target_world(encoded_dir.as_std_path())?;
+                let html = doc::DocGenerator::new(&resolve, world, &crate_name).generate();
 
-    for PackageComponentMetadata { package, metadata } in packages {
-        match metadata {
-            Some(metadata) => {
-                let resolution =
-                    PackageDependencyResolution::new(config, metadata, lock_file, network_allowed)
-                        .await?;
-                map.insert(package.id.clone(), resolution);
+                let path = crate_dir.join(match name {
+                    Some(name) => format!("wit-{name}.html"),
+                    None => "wit.html".to_string(),
+                });
+
+                fs::write(&path, html)
+                    .with_context(|| format!("failed to write WIT documentation `{path}`"))?;
+
+                config.terminal().status(
+                    "Documented",
+                    format!("target world of `{name}` ({path})", name = package.name),
+                )?;
             }
-            None => continue,
         }
     }
 
-    Ok(map)
+    Ok(())
 }
 
-async fn encode_target_world(
-    config: &Config,
-    resolution: &PackageDependencyResolution<'_>,
-    bindings_dir: &Path,
-) -> Result<()> {
-    let output_dir = bindings_dir.join(&resolution.metadata.name);
-    let target_path = output_dir.join("target.wasm");
-    let world_path = output_dir.join("world");
+/// Decodes an encoded target world from the given bindings directory.
+fn decode_target_world(dir: &Path) -> Result<(wit_parser::Resolve, wit_parser::WorldId)> {
+    let target_path = dir.join("target.wasm");
+    let bytes = fs::read(&target_path).with_context(|| {
+        format!(
+            "failed to read target file `{path}`",
+            path = target_path.display()
+        )
+    })?;
 
-    let last_modified_output = target_path
-        .is_file()
-        .then(|| last_modified_time(&target_path))
-        .transpose()?
-        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let world_path = dir.join("world");
+    let world = fs::read_to_string(&world_path).with_context(|| {
+        format!(
+            "failed to read world name `{path}`",
+            path = world_path.display()
+        )
+    })?;
 
-    let encoder = BindingsEncoder::new(resolution)?;
-    match encoder.reason(last_modified_output)? {
-        Some(reason) => {
-            ::log::debug!(
-                "encoding target for package `{name}` at `{path}` because {reason}",
-                name = resolution.metadata.name,
-                path = target_path.display(),
-            );
+    match wit_component::decode(&bytes).with_context(|| {
+        format!(
+            "failed to decode target file `{path}`",
+            path = target_path.display()
+        )
+    })? {
+        wit_component::DecodedWasm::WitPackage(resolve, package) => {
+            let world =
+                resolve.select_world(package, (!world.is_empty()).then_some(world.as_str()))?;
+            Ok((resolve, world))
+        }
+        wit_component::DecodedWasm::Component(..) => bail!(
+            "target file `{path}` is not a WIT package",
+            path = target_path.display()
+        ),
+    }
+}
+
+/// The environment variable cargo uses for the `wasm32-wasi` target runner.
+const RUNNER_ENV_VAR: &str = "CARGO_TARGET_WASM32_WASI_RUNNER";
+
+/// The environment variable cargo uses for the `wasm32-wasip2` target runner.
+const WASIP2_RUNNER_ENV_VAR: &str = "CARGO_TARGET_WASM32_WASIP2_RUNNER";
+
+/// The argument passed to `cargo-component` when cargo invokes it as a target runner.
+pub const RUNNER_ARG: &str = "--component-runner";
+
+/// The environment variable used to specify the WebAssembly runtime command.
+pub const RUNTIME_ENV_VAR: &str = "CARGO_COMPONENT_RUNTIME";
+
+/// The environment variable used to pass additional arguments to the runtime.
+const RUNTIME_ARGS_ENV_VAR: &str = "CARGO_COMPONENT_RUNTIME_ARGS";
+
+/// The environment variable used to specify the `wasm-opt` program.
+const WASM_OPT_ENV_VAR: &str = "CARGO_COMPONENT_WASM_OPT";
+
+/// The environment variable used to pass the build profile to the target runner.
+const PROFILE_ENV_VAR: &str = "CARGO_COMPONENT_PROFILE";
+
+/// The environment variable used to pass the paths of the adapters resolved
+/// from registries to the target runner.
+const ADAPTERS_ENV_VAR: &str = "CARGO_COMPONENT_ADAPTERS";
+
+/// The environment variable used to pass the configuration values specified
+/// on the command line to the target runner.
+const CONFIG_ENV_VAR: &str = "CARGO_COMPONENT_CONFIG";
+
+/// The environment variable used to pass the paths of the resolved component
+/// dev-dependencies to the target runner.
+const DEV_DEPENDENCIES_ENV_VAR: &str = "CARGO_COMPONENT_DEV_DEPENDENCIES";
+
+/// The file extension of WAC composition scripts.
+const WAC_EXTENSION: &str = "wac";
+
+/// The name of the composition script that is composed on every build.
+pub const COMPOSE_SCRIPT_FILE_NAME: &str = "compose.wac";
+
+/// The field of the producers section recording the commit a component was
+/// published from.
+const VCS_PRODUCERS_FIELD: &str = "vcs";
+
+/// The type of the registry metadata link to the commit a component was
+/// published from.
+const VCS_LINK_TYPE: &str = "vcs";
+
+/// The paths of the adapter modules resolved from registries, by package name.
+type AdapterPaths = HashMap<String, HashMap<PackageId, PathBuf>>;
+
+/// The paths of the resolved component dev-dependencies, by package name.
+type DevDependencyPaths = HashMap<String, HashMap<PackageId, PathBuf>>;
+
+/// The default WebAssembly runtime command used to run components.
+pub(crate) const DEFAULT_RUNTIME: &str = "wasmtime run -W component-model --dir .";
+
+/// Runs a WebAssembly module produced by cargo as a component.
+///
+/// This is invoked when `cargo-component` is used as the target runner; the
+/// module is componentized in place with the command adapter and then run
+/// with the runtime specified by `CARGO_COMPONENT_RUNTIME` (defaults to
+/// `wasmtime`).
+///
+/// Note: this function exits the process with the runtime's exit code.
+pub fn run_component(config: &Config, path: &Path, args: &[String]) -> Result<()> {
+    // Use the adapter of the package being run, if it has component metadata
+    let (metadata, bindings_dir) = match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => {
+            let metadata = load_metadata(Some(&Path::new(&dir).join("Cargo.toml")))?;
+            let name = std::env::var("CARGO_PKG_NAME").unwrap_or_default();
+            let bindings_dir = metadata
+                .target_directory
+                .join("bindings")
+                .into_std_path_buf();
+            match metadata.packages.iter().find(|p| p.name == name) {
+                Some(package) => (
+                    ComponentMetadata::from_package(package)?,
+                    Some(bindings_dir),
+                ),
+                None => (None, Some(bindings_dir)),
+            }
+        }
+        None => (None, None),
+    };
+
+    // The profile is passed from the cargo command; the binary is named after its target
+    let profile = std::env::var(PROFILE_ENV_VAR).unwrap_or_else(|_| "dev".to_string());
+    let bin = path.file_stem().and_then(|s| s.to_str());
+
+    let adapters: AdapterPaths = match std::env::var(ADAPTERS_ENV_VAR) {
+        Ok(adapters) => serde_json::from_str(&adapters).with_context(|| {
+            format!("failed to parse environment variable `{ADAPTERS_ENV_VAR}`")
+        })?,
+        Err(_) => AdapterPaths::new(),
+    };
+
+    let adapter = match &metadata {
+        Some(metadata) => resolve_adapter(
+            metadata.adapter(&profile, bin),
+            adapters.get(&metadata.name),
+        )?,
+        None => None,
+    };
+
+    let optimize = match &metadata {
+        Some(metadata) => metadata.optimize(&profile, bin),
+        None => (OptLevel::None, false),
+    };
+
+    // Outputs of targets that produce components natively are only checked
+    if is_wasm_module(path)? {
+        create_component(
+            config,
+            adapter.as_deref(),
+            metadata.as_ref().map(|m| &m.section.raw),
+            optimize,
+            false,
+            path,
+            true,
+            &BuildArguments::default(),
+        )?;
+    } else if let Some(bindings_dir) = &bindings_dir {
+        finish_native_component(
+            config,
+            metadata.as_ref(),
+            bindings_dir,
+            &profile,
+            bin,
+            path,
+            &BuildArguments::default(),
+        )?;
+    }
+
+    if let Some(metadata) = &metadata {
+        add_export_versions(metadata, path)?;
+        validate_config(config, metadata, path)?;
+    }
+
+    // Satisfy the imports exported by the dev-dependencies of the package
+    let dev_dependencies: DevDependencyPaths = match std::env::var(DEV_DEPENDENCIES_ENV_VAR) {
+        Ok(paths) => serde_json::from_str(&paths).with_context(|| {
+            format!("failed to parse environment variable `{DEV_DEPENDENCIES_ENV_VAR}`")
+        })?,
+        Err(_) => DevDependencyPaths::new(),
+    };
+
+    let composed = match metadata
+        .as_ref()
+        .and_then(|m| dev_dependencies.get(&m.name))
+        .filter(|d| !d.is_empty())
+    {
+        Some(dependencies) => compose_dev_dependencies(path, dependencies)?,
+        None => None,
+    };
+    let path = composed.as_deref().unwrap_or(path);
+
+    let runtime = std::env::var(RUNTIME_ENV_VAR).unwrap_or_else(|_| DEFAULT_RUNTIME.to_string());
+    let mut runtime_args = runtime.split_whitespace();
+    let program = runtime_args
+        .next()
+        .with_context(|| format!("environment variable `{RUNTIME_ENV_VAR}` is empty"))?;
+
+    log::debug!(
+        "running component `{path}` with runtime `{runtime}`",
+        path = path.display()
+    );
+
+    // Add any arguments for capabilities requested on the command line
+    let capability_args: Vec<String> = match std::env::var(RUNTIME_ARGS_ENV_VAR) {
+        Ok(args) => serde_json::from_str(&args).with_context(|| {
+            format!("failed to parse environment variable `{RUNTIME_ARGS_ENV_VAR}`")
+        })?,
+        Err(_) => Vec::new(),
+    };
+
+    // Provide the configuration values through `wasi:config`; values given
+    // on the command line override those of the manifest
+    let mut config_vars = metadata
+        .as_ref()
+        .map(|m| m.section.config.clone())
+        .unwrap_or_default();
+    if let Ok(vars) = std::env::var(CONFIG_ENV_VAR) {
+        config_vars.extend(
+            serde_json::from_str::<BTreeMap<String, String>>(&vars).with_context(|| {
+                format!("failed to parse environment variable `{CONFIG_ENV_VAR}`")
+            })?,
+        );
+    }
+
+    let mut config_args = Vec::new();
+    if !config_vars.is_empty() {
+        config_args.push("-S".to_string());
+        config_args.push("config".to_string());
+        for (key, value) in &config_vars {
+            config_args.push("-S".to_string());
+            config_args.push(format!("config-var={key}={value}"));
+        }
+    }
+
+    // Benchmarks are run repeatedly and timed rather than run once
+    if let Ok(options) = std::env::var(bench::BENCH_ENV_VAR) {
+        let options: bench::BenchOptions = serde_json::from_str(&options).with_context(|| {
+            format!(
+                "failed to parse environment variable `{var}`",
+                var = bench::BENCH_ENV_VAR
+            )
+        })?;
+        let package = std::env::var("CARGO_PKG_NAME").unwrap_or_default();
+
+        config.terminal().status(
+            "Benchmarking",
+            format!(
+                "`{path}` ({iterations} iterations)",
+                path = path.display(),
+                iterations = options.iterations
+            ),
+        )?;
+
+        // Components run with `wasmtime run` are compiled ahead of the timed runs
+        let wasmtime = (Path::new(program).file_stem() == Some("wasmtime".as_ref())
+            && runtime_args.clone().next() == Some("run"))
+        .then_some(program);
+
+        bench::run(&options, &package, path, wasmtime, |component, extra| {
+            let mut cmd = Command::new(program);
+            cmd.args(runtime_args.clone())
+                .args(extra)
+                .args(&capability_args)
+                .args(&config_args)
+                .arg(component)
+                .args(args);
+            cmd
+        })?;
+
+        std::process::exit(0);
+    }
+
+    let mut cmd = Command::new(program);
+    cmd.args(runtime_args)
+        .args(capability_args)
+        .args(config_args)
+        .arg(path)
+        .args(args);
+
+    match cmd.status() {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => bail!(
+            "failed to spawn `{program}`: {e}\n\n\
+             ensure the runtime is installed or set the `{RUNTIME_ENV_VAR}` environment variable"
+        ),
+    }
+}
+
+/// Composes a component with the dev-dependencies that export its imports.
+///
+/// Returns the path to the composed component, or `None` if no
+/// dev-dependency exports any of the component's imports.
+fn compose_dev_dependencies(
+    component: &Path,
+    dependencies: &HashMap<PackageId, PathBuf>,
+) -> Result<Option<PathBuf>> {
+    let decode_world = |path: &Path| -> Result<(wit_parser::Resolve, wit_parser::WorldId)> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+        match wit_component::decode(&bytes)
+            .with_context(|| format!("failed to decode `{path}`", path = path.display()))?
+        {
+            wit_component::DecodedWasm::Component(resolve, world) => Ok((resolve, world)),
+            wit_component::DecodedWasm::WitPackage(..) => {
+                bail!(
+                    "`{path}` is not a WebAssembly component",
+                    path = path.display()
+                )
+            }
+        }
+    };
+
+    let (resolve, world) = decode_world(component)?;
+    let imports: HashSet<_> = resolve.worlds[world]
+        .imports
+        .keys()
+        .map(|key| explain::key_name(&resolve, key))
+        .collect();
+
+    let mut ids: Vec<_> = dependencies.keys().collect();
+    ids.sort();
+
+    let mut compose_config = wasm_compose::config::Config {
+        dir: component
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    let mut providers = HashMap::new();
+    for id in ids {
+        let path = &dependencies[id];
+        let (resolve, world) = decode_world(path)
+            .with_context(|| format!("failed to decode dev-dependency `{id}`"))?;
+
+        for (key, item) in &resolve.worlds[world].exports {
+            let name = explain::key_name(&resolve, key);
+            if !matches!(item, WorldItem::Interface(_)) || !imports.contains(&name) {
+                continue;
+            }
+
+            if let Some(other) = providers.insert(name.clone(), id) {
+                bail!("dev-dependencies `{other}` and `{id}` both export `{name}`");
+            }
+
+            log::debug!(
+                "satisfying import `{name}` with dev-dependency `{id}` ({path})",
+                path = path.display()
+            );
+
+            compose_config.dependencies.insert(
+                name,
+                wasm_compose::config::Dependency { path: path.clone() },
+            );
+        }
+    }
+
+    if compose_config.dependencies.is_empty() {
+        return Ok(None);
+    }
+
+    let bytes = wasm_compose::composer::ComponentComposer::new(component, &compose_config)
+        .compose()
+        .with_context(|| {
+            format!(
+                "failed to compose component `{path}` with its dev-dependencies",
+                path = component.display()
+            )
+        })?;
+
+    let output = component.with_extension("composed.wasm");
+    fs::write(&output, bytes).with_context(|| {
+        format!(
+            "failed to write composed component `{path}`",
+            path = output.display()
+        )
+    })?;
+
+    Ok(Some(output))
+}
+
+/// Installs the WebAssembly targets of a cargo command, adding an implicit
+/// `wasm32-wasi` target if there isn't a WebAssembly target present.
+fn add_wasm_target(config: &Config, cargo_args: &CargoArguments, cmd: &mut Command) -> Result<()> {
+    let mut installed = false;
+    for target in cargo_args
+        .targets
+        .iter()
+        .filter(|t| *t == "wasm32-wasi" || is_native_component_target(t))
+    {
+        install_wasm_target(config, target)?;
+        installed = true;
+    }
+
+    if !installed {
+        install_wasm_target(config, "wasm32-wasi")?;
+    }
+
+    if !cargo_args.targets.iter().any(|t| is_wasm_target(t)) {
+        cmd.arg("--target").arg("wasm32-wasi");
+    }
+
+    Ok(())
+}
+
+/// Builds a component package with `cargo component build` using the same
+/// profile and lock file options as the current command.
+///
+/// If a package name is given, only that package of the manifest's workspace
+/// is built.
+fn build_component_package(
+    config: &Config,
+    cargo_args: &CargoArguments,
+    manifest_path: &Path,
+    package: Option<&str>,
+    description: &str,
+) -> Result<()> {
+    let exe = std::env::current_exe()
+        .context("failed to determine the path to the `cargo-component` executable")?;
+
+    config.terminal().status("Building", description)?;
+
+    let mut cmd = Command::new(&exe);
+    cmd.args(["component", "build", "--manifest-path"])
+        .arg(manifest_path)
+        .args(["--profile", cargo_args.profile()]);
+
+    if let Some(package) = package {
+        cmd.args(["--package", package]);
+    }
+
+    for (flag, enabled) in [
+        ("--offline", cargo_args.offline),
+        ("--locked", cargo_args.locked),
+        ("--frozen", cargo_args.frozen),
+    ] {
+        if enabled {
+            cmd.arg(flag);
+        }
+    }
+
+    log::debug!(
+        "building {description} from manifest `{path}`",
+        path = manifest_path.display()
+    );
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to spawn `{exe}`", exe = exe.display()))?;
+    if !status.success() {
+        bail!("failed to build {description}");
+    }
+
+    Ok(())
+}
+
+/// Gets the path of the component built for a package with the given profile.
+///
+/// The component of the `cdylib` library target is used if the package has
+/// one; otherwise, the component of its first binary target is used.
+fn component_output_path(
+    metadata: &Metadata,
+    package: &Package,
+    cargo_args: &CargoArguments,
+) -> Option<PathBuf> {
+    let name = package
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| k == "cdylib"))
+        .map(|t| t.name.replace('-', "_"))
+        .or_else(|| {
+            package
+                .targets
+                .iter()
+                .find(|t| t.is_bin())
+                .map(|t| t.name.clone())
+        })?;
+
+    Some(
+        metadata
+            .target_directory
+            .join("wasm32-wasi")
+            .join(cargo_args.profile_dir())
+            .join(format!("{name}.wasm"))
+            .into_std_path_buf(),
+    )
+}
+
+/// Builds the component dev-dependencies that are paths to cargo packages
+/// (e.g. those generated with `cargo component mock`).
+///
+/// The paths of the built dev-dependencies are replaced with the paths of
+/// their components.
+fn build_dev_dependencies(
+    config: &Config,
+    cargo_args: &CargoArguments,
+    dev_dependencies: &mut DevDependencyPaths,
+) -> Result<()> {
+    for (id, path) in dev_dependencies.values_mut().flat_map(|d| d.iter_mut()) {
+        let manifest_path = path.join("Cargo.toml");
+        if !manifest_path.is_file() {
+            continue;
+        }
+
+        build_component_package(
+            config,
+            cargo_args,
+            &manifest_path,
+            None,
+            &format!("dev-dependency `{id}`"),
+        )?;
+
+        let metadata = load_metadata(Some(&manifest_path))?;
+        *path = metadata
+            .root_package()
+            .filter(|p| {
+                p.targets
+                    .iter()
+                    .any(|t| t.kind.iter().any(|k| k == "cdylib"))
+            })
+            .and_then(|p| component_output_path(&metadata, p, cargo_args))
+            .with_context(|| {
+                format!(
+                    "dev-dependency `{id}` at `{path}` does not have a `cdylib` library target",
+                    path = manifest_path.display()
+                )
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Builds the components of the workspace members that the given packages
+/// depend on as artifacts (see [`ARTIFACTS_KEY`]).
+///
+/// Returns the environment variables exposing the path of each built
+/// component to the packages, `CARGO_COMPONENT_ARTIFACT_<NAME>`, where
+/// `<NAME>` is the package name of the member in uppercase with dashes
+/// replaced by underscores.
+fn build_artifact_dependencies(
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+) -> Result<Vec<(String, PathBuf)>> {
+    let mut names = BTreeSet::new();
+    for package in packages {
+        for name in artifact_dependencies(package.package)? {
+            check_artifact_cycle(metadata, &name, &mut vec![package.package.name.clone()])?;
+            names.insert(name);
+        }
+    }
+
+    let mut vars = Vec::with_capacity(names.len());
+    for name in names {
+        let package = metadata
+            .workspace_packages()
+            .into_iter()
+            .find(|p| p.name == name)
+            .with_context(|| {
+                format!("artifact dependency `{name}` is not a member of the workspace")
+            })?;
+
+        if package.metadata.get("component").is_none() {
+            bail!("artifact dependency `{name}` is not a component package");
+        }
+
+        let path = component_output_path(metadata, package, cargo_args).with_context(|| {
+            format!("artifact dependency `{name}` does not have a `cdylib` or binary target")
+        })?;
+
+        build_component_package(
+            config,
+            cargo_args,
+            package.manifest_path.as_std_path(),
+            Some(&name),
+            &format!("artifact dependency `{name}`"),
+        )?;
+
+        vars.push((
+            format!(
+                "CARGO_COMPONENT_ARTIFACT_{name}",
+                name = name.to_uppercase().replace('-', "_")
+            ),
+            path,
+        ));
+    }
+
+    Ok(vars)
+}
+
+/// Checks that the artifact dependencies of a workspace member do not
+/// depend on the member itself, as they could never be built.
+fn check_artifact_cycle(metadata: &Metadata, name: &str, stack: &mut Vec<String>) -> Result<()> {
+    if stack.iter().any(|n| n == name) {
+        stack.push(name.to_string());
+        bail!(
+            "cyclic artifact dependency: {cycle}",
+            cycle = stack
+                .iter()
+                .map(|n| format!("`{n}`"))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        );
+    }
+
+    let package = match metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == name)
+    {
+        Some(package) => package,
+        None => return Ok(()),
+    };
+
+    stack.push(name.to_string());
+    for dependency in artifact_dependencies(package)? {
+        check_artifact_cycle(metadata, &dependency, stack)?;
+    }
+    stack.pop();
+
+    Ok(())
+}
+
+fn last_modified_time(path: &Path) -> Result<SystemTime> {
+    path.metadata()
+        .with_context(|| {
+            format!(
+                "failed to read file metadata for `{path}`",
+                path = path.display()
+            )
+        })?
+        .modified()
+        .with_context(|| {
+            format!(
+                "failed to retrieve last modified time for `{path}`",
+                path = path.display()
+            )
+        })
+}
+
+/// Loads the workspace metadata based on the given manifest path.
+pub fn load_metadata(manifest_path: Option<&Path>) -> Result<Metadata> {
+    let mut command = MetadataCommand::new();
+    command.no_deps();
+
+    if let Some(path) = manifest_path {
+        log::debug!(
+            "loading metadata from manifest `{path}`",
+            path = path.display()
+        );
+        command.manifest_path(path);
+    } else {
+        log::debug!("loading metadata from current directory");
+    }
+
+    command.exec().context("failed to load cargo metadata")
+}
+
+/// Loads the component metadata for the given package specs.
+///
+/// If `workspace` is true, all workspace packages are loaded.
+pub fn load_component_metadata<'a>(
+    metadata: &'a Metadata,
+    specs: impl ExactSizeIterator<Item = &'a CargoPackageSpec>,
+    workspace: bool,
+) -> Result<Vec<PackageComponentMetadata<'a>>> {
+    let pkgs = if workspace {
+        metadata.workspace_packages()
+    } else if specs.len() > 0 {
+        let mut pkgs = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let pkg = metadata
+                .packages
+                .iter()
+                .find(|p| {
+                    p.name == spec.name
+                        && match spec.version.as_ref() {
+                            Some(v) => &p.version == v,
+                            None => true,
+                        }
+                })
+                .with_context(|| {
+                    format!("package ID specification `{spec}` did not match any packages")
+                })?;
+            pkgs.push(pkg);
+        }
+
+        pkgs
+    } else {
+        // TODO: this should be the default members, or default to all members
+        // However, `cargo-metadata` doesn't return the workspace default members yet
+        // See: https://github.com/oli-obk/cargo_metadata/issues/215
+        metadata.workspace_packages()
+    };
+
+    pkgs.into_iter()
+        .map(|p| PackageComponentMetadata::new(metadata, p))
+        .collect::<Result<_>>()
+}
+
+async fn encode_targets(
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+) -> Result<(AdapterPaths, DevDependencyPaths)> {
+    let bindings_dir = metadata.target_directory.join("bindings");
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
+                )
+            })
+        })
+        .transpose()?;
+
+    for metadata in packages.iter().filter_map(|p| p.metadata.as_ref()) {
+        generate::run(config, metadata)?;
+    }
+
+    let resolver = lock_file.as_ref().map(LockFileResolver::new);
+    let vendor_dir = vendor_dir(metadata, lock_file.as_ref());
+    let map = create_resolution_map(
+        config,
+        packages,
+        resolver,
+        vendor_dir.as_deref(),
+        None,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    let mut adapters = AdapterPaths::new();
+    let mut dev_dependencies = DevDependencyPaths::new();
+    let mut bindings_crates = HashMap::new();
+    for PackageComponentMetadata { package, .. } in packages {
+        let resolution = match map.get(&package.id) {
+            Some(resolution) => resolution,
+            None => continue,
+        };
+
+        adapters.insert(package.name.clone(), resolution.adapter_paths());
+        dev_dependencies.insert(package.name.clone(), resolution.dev_dependency_paths());
+
+        let features = cargo_args.enabled_features(package);
+        encode_target_world(
+            config,
+            resolution,
+            bindings_dir.as_std_path(),
+            None,
+            &features,
+        )
+        .await?;
+
+        if let Some(crate_name) = &resolution.metadata.section.bindings.crate_name {
+            if let Some(other) = bindings_crates.insert(crate_name.as_str(), package.name.as_str())
+            {
+                bail!(
+                    "packages `{other}` and `{name}` cannot both generate bindings into crate `{crate_name}`",
+                    name = package.name
+                );
+            }
+
+            write_bindings_crate_target(
+                metadata,
+                bindings_dir.as_std_path(),
+                &package.name,
+                crate_name,
+            )?;
+        }
+
+        for name in resolution.metadata.section.targets.keys() {
+            encode_target_world(
+                config,
+                resolution,
+                bindings_dir.as_std_path(),
+                Some(name),
+                &features,
+            )
+            .await?;
+        }
+    }
+
+    // Update the lock file if it exists or if the new lock file is non-empty
+    let mut new_lock_file = map.to_lock_file();
+    new_lock_file.vendor = lock_file.as_ref().and_then(|l| l.vendor.clone());
+    if (lock_file.is_some() || !new_lock_file.packages.is_empty() || !new_lock_file.git.is_empty())
+        && Some(&new_lock_file) != lock_file.as_ref()
+    {
+        drop(file_lock);
+        let file_lock = acquire_lock_file_rw(
+            config.terminal(),
+            metadata,
+            cargo_args.lock_update_allowed(),
+            cargo_args.locked,
+        )?;
+        new_lock_file
+            .write(file_lock.file(), "cargo-component")
+            .with_context(|| {
+                format!(
+                    "failed to write lock file `{path}`",
+                    path = file_lock.path().display()
+                )
+            })?;
+    }
+
+    Ok((adapters, dev_dependencies))
+}
+
+/// Gets the directory of vendored dependencies recorded in the lock file.
+fn vendor_dir(metadata: &Metadata, lock_file: Option<&LockFile>) -> Option<PathBuf> {
+    lock_file
+        .and_then(|l| l.vendor.as_ref())
+        .map(|dir| metadata.workspace_root.as_std_path().join(dir))
+}
+
+async fn create_resolution_map<'a>(
+    config: &Config,
+    packages: &'a [PackageComponentMetadata<'_>],
+    lock_file: Option<LockFileResolver<'_>>,
+    vendor_dir: Option<&Path>,
+    precise: Option<&HashMap<PackageId, Version>>,
+    network_allowed: bool,
+) -> Result<PackageResolutionMap<'a>> {
+    let mut map = PackageResolutionMap::default();
+
+    for PackageComponentMetadata { package, metadata } in packages {
+        match metadata {
+            Some(metadata) => {
+                let resolution = PackageDependencyResolution::new(
+                    config,
+                    metadata,
+                    lock_file,
+                    vendor_dir,
+                    precise,
+                    network_allowed,
+                )
+                .await?;
+                map.insert(package.id.clone(), resolution);
+            }
+            None => continue,
+        }
+    }
+
+    Ok(map)
+}
+
+async fn encode_target_world(
+    config: &Config,
+    resolution: &PackageDependencyResolution<'_>,
+    bindings_dir: &Path,
+    name: Option<&str>,
+    features: &HashSet<String>,
+) -> Result<()> {
+    // Named targets are encoded to a `targets/<name>` subdirectory
+    let output_dir = match name {
+        Some(name) => bindings_dir
+            .join(&resolution.metadata.name)
+            .join("targets")
+            .join(name),
+        None => bindings_dir.join(&resolution.metadata.name),
+    };
+    let target_path = output_dir.join("target.wasm");
+    let world_path = output_dir.join("world");
+    let hash_path = output_dir.join("target.sha256");
+    let features_path = output_dir.join("features");
+    let options_path = output_dir.join("options");
+    let inputs_path = output_dir.join("inputs");
+
+    // The hash file is touched whenever the encoding is checked, so prefer
+    // its modification time over that of the target file
+    let last_modified_output = [&hash_path, &target_path]
+        .into_iter()
+        .find(|p| p.is_file())
+        .map(|p| last_modified_time(p))
+        .transpose()?
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    // Record the enabled optional dependencies and target features so that a
+    // change to the enabled features causes the target to be encoded again
+    let mut enabled: Vec<_> = resolution
+        .metadata
+        .section
+        .optional_dependencies
+        .keys()
+        .filter(|id| resolution.metadata.is_dependency_enabled(id, features))
+        .map(ToString::to_string)
+        .chain(
+            resolution
+                .metadata
+                .target(name)?
+                .features()
+                .keys()
+                .filter(|f| features.contains(*f))
+                .map(|f| format!("feature:{f}")),
+        )
+        .collect();
+    enabled.sort();
+    let enabled = enabled.join("\n");
+    let features_changed = fs::read_to_string(&features_path).unwrap_or_default() != enabled;
+
+    let encoder = BindingsEncoder::new(resolution, name, features)?;
+
+    for deprecation in deprecation::exported_deprecations(encoder.resolve(), encoder.world()) {
+        config.terminal().warn(format!(
+            "{deprecation} (exported by package `{name}`)",
+            name = resolution.metadata.name
+        ))?;
+    }
+
+    // Report the changes to the implementation of the exports of the world
+    // since the last successful build
+    guidance::check(
+        config.terminal(),
+        &resolution.metadata.name,
+        &output_dir,
+        &guidance::Exports::new(encoder.resolve(), encoder.world())?,
+    )?;
+
+    // Generate the conveniences before the encoder is consumed; a bindings
+    // crate only has the imports of the world, so it gets its own copy
+    let bindings = &resolution.metadata.section.bindings;
+    let remapped = remap::remapped_modules(encoder.resolve(), encoder.world(), bindings)?;
+    let conveniences = [
+        (
+            conveniences::CONVENIENCES_FILE,
+            conveniences::generate(encoder.resolve(), encoder.world(), bindings, true)?,
+        ),
+        (
+            conveniences::IMPORT_CONVENIENCES_FILE,
+            conveniences::generate(encoder.resolve(), encoder.world(), bindings, false)?,
+        ),
+    ];
+
+    // Record the files the target world was created from so that adding,
+    // removing, or renaming one also causes the target to be encoded again
+    let inputs = encoder
+        .inputs()
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let inputs_changed = fs::read_to_string(&inputs_path).ok().as_deref() != Some(inputs.as_str());
+
+    let reason = if features_changed {
+        Some("the enabled features changed")
+    } else if inputs_changed {
+        Some("the set of target input files changed")
+    } else {
+        encoder.reason(last_modified_output)?
+    };
+
+    match reason {
+        Some(reason) => {
+            ::log::debug!(
+                "encoding target for package `{name}` at `{path}` because {reason}",
+                name = resolution.metadata.name,
+                path = target_path.display(),
+            );
+
+            let encoded = encoder.encode()?;
+            let world = resolution.metadata.target(name)?.world().unwrap_or("");
+            let hash = target_hash(&encoded, world);
+
+            fs::create_dir_all(&output_dir).with_context(|| {
+                format!(
+                    "failed to create output directory `{path}`",
+                    path = output_dir.display()
+                )
+            })?;
+
+            // Only write the target and world files if their content changed;
+            // this prevents the bindings from being regenerated and the crate
+            // from being needlessly rebuilt
+            let unchanged = target_path.is_file()
+                && world_path.is_file()
+                && fs::read_to_string(&hash_path)
+                    .map(|h| h.trim() == hash)
+                    .unwrap_or(false);
+
+            if unchanged {
+                ::log::debug!(
+                    "content of target encoding for package `{name}` at `{path}` is unchanged",
+                    name = resolution.metadata.name,
+                    path = target_path.display(),
+                );
+            } else {
+                config.terminal().status(
+                    "Encoding",
+                    format!(
+                        "target for {name} ({path})",
+                        name = resolution.metadata.name,
+                        path = target_path.display()
+                    ),
+                )?;
+
+                fs::write(&target_path, encoded).with_context(|| {
+                    format!(
+                        "failed to write target file `{path}`",
+                        path = target_path.display()
+                    )
+                })?;
+
+                fs::write(&world_path, world).with_context(|| {
+                    format!(
+                        "failed to write world name `{path}`",
+                        path = world_path.display()
+                    )
+                })?;
+
+                config.terminal().event(
+                    "target-encoded",
+                    serde_json::json!({
+                        "package": resolution.metadata.name,
+                        "target": name,
+                        "path": target_path,
+                        "sha256": hash,
+                    }),
+                )?;
+            }
+
+            fs::write(&hash_path, hash).with_context(|| {
+                format!(
+                    "failed to write target hash file `{path}`",
+                    path = hash_path.display()
+                )
+            })?;
+
+            fs::write(&features_path, enabled).with_context(|| {
+                format!(
+                    "failed to write enabled features file `{path}`",
+                    path = features_path.display()
+                )
+            })?;
+
+            fs::write(&inputs_path, inputs).with_context(|| {
+                format!(
+                    "failed to write target inputs file `{path}`",
+                    path = inputs_path.display()
+                )
+            })?;
+        }
+        None => {
+            ::log::debug!(
+                "existing target encoding for package `{name}` at `{path}` is up-to-date",
+                name = resolution.metadata.name,
+                path = target_path.display(),
+            );
+        }
+    }
+
+    // The `generate!` macro reads its default options from the options file;
+    // like the target file, only write it if its content changed
+    let options = bindings.options_file(&remapped);
+    if fs::read_to_string(&options_path).ok().as_deref() != Some(options.as_str()) {
+        fs::write(&options_path, options).with_context(|| {
+            format!(
+                "failed to write bindings options file `{path}`",
+                path = options_path.display()
+            )
+        })?;
+    }
+
+    for (file, source) in conveniences {
+        let path = output_dir.join(file);
+        if fs::read_to_string(&path).ok().as_deref() != Some(source.as_str()) {
+            fs::write(&path, source).with_context(|| {
+                format!(
+                    "failed to write bindings conveniences file `{path}`",
+                    path = path.display()
+                )
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the target world of a component package for its bindings crate.
+///
+/// The `generate!` macro in the bindings crate reads the target from the
+/// bindings directory of that crate; the `component` file marks it as a
+/// bindings crate, causing only the imports of the world to be generated.
+fn write_bindings_crate_target(
+    metadata: &Metadata,
+    bindings_dir: &Path,
+    package_name: &str,
+    crate_name: &str,
+) -> Result<()> {
+    if crate_name == package_name {
+        bail!("package `{package_name}` cannot generate bindings into its own crate");
+    }
+
+    if !metadata
+        .workspace_packages()
+        .iter()
+        .any(|p| p.name == crate_name)
+    {
+        bail!(
+            "bindings crate `{crate_name}` of package `{package_name}` is not a member of the workspace"
+        );
+    }
+
+    let source_dir = bindings_dir.join(package_name);
+    let output_dir = bindings_dir.join(crate_name);
+    fs::create_dir_all(&output_dir).with_context(|| {
+        format!(
+            "failed to create output directory `{path}`",
+            path = output_dir.display()
+        )
+    })?;
+
+    for (file, contents) in [
+        ("target.wasm", None),
+        ("world", None),
+        ("options", None),
+        (conveniences::IMPORT_CONVENIENCES_FILE, None),
+        ("component", Some(package_name.as_bytes().to_vec())),
+    ] {
+        let contents = match contents {
+            Some(contents) => contents,
+            None => {
+                let path = source_dir.join(file);
+                fs::read(&path)
+                    .with_context(|| format!("failed to read `{path}`", path = path.display()))?
+            }
+        };
+
+        // Only write files whose content changed so that the bindings crate
+        // is not needlessly rebuilt
+        let path = output_dir.join(file);
+        if fs::read(&path).map(|c| c == contents).unwrap_or(false) {
+            continue;
+        }
+
+        ::log::debug!(
+            "writing `{path}` for bindings crate `{crate_name}`",
+            path = path.display()
+        );
+
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Computes the content hash of an encoded target world.
+fn target_hash(encoded: &[u8], world: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(encoded);
+    hasher.update([0]);
+    hasher.update(world.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn is_wasm_module(path: impl AsRef<Path>) -> Result<bool> {
+    let path = path.as_ref();
+
+    let mut file = File::open(path)
+        .with_context(|| format!("failed to open `{path}` for read", path = path.display()))?;
+
+    let mut bytes = [0u8; 8];
+    file.read(&mut bytes).with_context(|| {
+        format!(
+            "failed to read file header for `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    if bytes[0..4] != [0x0, b'a', b's', b'm'] {
+        bail!(
+            "expected `{path}` to be a WebAssembly module",
+            path = path.display()
+        );
+    }
+
+    // Check for the module header version
+    Ok(bytes[4..] == [0x01, 0x00, 0x00, 0x00])
+}
+
+/// Resolves an adapter from a registry package to the path of its module.
+fn resolve_adapter<'a>(
+    adapter: Option<&'a Adapter>,
+    paths: Option<&HashMap<PackageId, PathBuf>>,
+) -> Result<Option<Cow<'a, Adapter>>> {
+    match adapter {
+        Some(Adapter::Package { id, .. }) => {
+            let path = paths
+                .and_then(|p| p.get(id))
+                .with_context(|| format!("adapter package `{id}` has not been resolved"))?;
+            Ok(Some(Cow::Owned(Adapter::Path(path.clone()))))
+        }
+        adapter => Ok(adapter.map(Cow::Borrowed)),
+    }
+}
+
+fn adapter_bytes(adapter: Option<&Adapter>, binary: bool) -> Result<Cow<[u8]>> {
+    let binary = match adapter {
+        Some(Adapter::Path(path)) => {
+            return Ok(fs::read(path)
+                .with_context(|| {
+                    format!(
+                        "failed to read module adapter `{path}`",
+                        path = path.display()
+                    )
+                })?
+                .into());
+        }
+        Some(Adapter::Package { id, .. }) => {
+            bail!("adapter package `{id}` has not been resolved")
+        }
+        Some(Adapter::Command) => true,
+        Some(Adapter::Reactor) => false,
+        None => binary,
+    };
+
+    if binary {
+        Ok(Cow::Borrowed(include_bytes!(concat!(
+            "../adapters/",
+            env!("WASI_ADAPTER_VERSION"),
+            "/wasi_snapshot_preview1.command.wasm"
+        ))))
+    } else {
+        Ok(Cow::Borrowed(include_bytes!(concat!(
+            "../adapters/",
+            env!("WASI_ADAPTER_VERSION"),
+            "/wasi_snapshot_preview1.reactor.wasm"
+        ))))
+    }
+}
+
+/// Checks that a core module exports the given raw exports.
+fn check_raw_exports(module: &[u8], exports: &[String], path: &Path) -> Result<()> {
+    if exports.is_empty() {
+        return Ok(());
+    }
+
+    let mut names = HashSet::new();
+    for payload in wasmparser::Parser::new(0).parse_all(module) {
+        if let wasmparser::Payload::ExportSection(reader) =
+            payload.context("failed to parse core module")?
+        {
+            for export in reader {
+                names.insert(export.context("failed to parse export")?.name);
+            }
+        }
+    }
+
+    for name in exports {
+        if !names.contains(name.as_str()) {
+            bail!(
+                "raw export `{name}` is not exported by module `{path}`; it must be defined by the package (e.g. with `#[no_mangle]`)",
+                path = path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the given raw custom sections to a component.
+fn add_custom_sections(
+    mut component: Vec<u8>,
+    sections: &BTreeMap<String, PathBuf>,
+) -> Result<Vec<u8>> {
+    for (name, path) in sections {
+        let data = fs::read(path).with_context(|| {
+            format!(
+                "failed to read the contents of custom section `{name}` from `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let section = wasm_encoder::CustomSection {
+            name: name.into(),
+            data: data.into(),
+        };
+
+        component.push(section.id());
+        section.encode(&mut component);
+    }
+
+    Ok(component)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_component(
+    config: &Config,
+    adapter: Option<&Adapter>,
+    raw: Option<&Raw>,
+    (opt_level, opt_explicit): (OptLevel, bool),
+    preserve_debug_info: bool,
+    path: &Path,
+    binary: bool,
+    build_args: &BuildArguments,
+) -> Result<()> {
+    // If the compilation output is not a WebAssembly module, then do nothing
+    // Note: due to the way cargo currently works on macOS, it will overwrite
+    // a previously generated component on an up-to-date build.
+    //
+    // As a result, users on macOS will see a "creating component" message
+    // even if the build is up-to-date.
+    //
+    // See: https://github.com/rust-lang/cargo/blob/99ad42deb4b0be0cdb062d333d5e63460a94c33c/crates/cargo-util/src/paths.rs#L542-L550
+    if !is_wasm_module(path)? {
+        ::log::debug!(
+            "output file `{path}` is already a WebAssembly component",
+            path = path.display()
+        );
+
+        if let Some(format) = build_args.size_report {
+            let component = fs::read(path).with_context(|| {
+                format!(
+                    "failed to read output component `{path}`",
+                    path = path.display()
+                )
+            })?;
+            print_size_report(path, &component, format)?;
+        }
+
+        return Ok(());
+    }
+
+    ::log::debug!(
+        "componentizing WebAssembly module `{path}` as a {kind} component",
+        path = path.display(),
+        kind = if binary { "command" } else { "reactor" },
+    );
+
+    let module = fs::read(path).with_context(|| {
+        format!(
+            "failed to read output module `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    // Optimize the core module before it is componentized; running `wasm-opt`
+    // on the component itself is not supported
+    let module = optimize_module(
+        config,
+        module,
+        path,
+        (opt_level, opt_explicit),
+        preserve_debug_info,
+    )?;
+
+    // Remove the imports the module never references, as each one would
+    // otherwise become an import of the component
+    let module = if build_args.minimize_imports {
+        let minimized = minimize::minimize_imports(&module).with_context(|| {
+            format!(
+                "failed to minimize the imports of module `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        for (module, name) in &minimized.removed {
+            ::log::debug!("removed unused import `{module}::{name}`");
+        }
+
+        if !minimized.removed.is_empty() {
+            config.terminal().status(
+                "Minimized",
+                format!(
+                    "imports of {path} ({len} unused removed)",
+                    path = path.display(),
+                    len = minimized.removed.len()
+                ),
+            )?;
+        }
+
+        minimized.module
+    } else {
+        module
+    };
+
+    if let Some(raw) = raw {
+        check_raw_exports(&module, &raw.exports, path)?;
+    }
+
+    config.terminal().status(
+        "Creating",
+        format!("component {path}", path = path.display()),
+    )?;
+
+    let adapter_module = adapter_bytes(adapter, binary)?;
+
+    // Keep the core module and its adapter for debugging or re-componentizing
+    if build_args.emit_core_wasm {
+        for (extension, bytes) in [
+            ("core.wasm", &module[..]),
+            ("adapter.wasm", &*adapter_module),
+        ] {
+            let path = path.with_extension(extension);
+            fs::write(&path, bytes)
+                .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+        }
+    }
+
+    let mut encoder = ComponentEncoder::default()
+        .module(&module)?
+        .adapter("wasi_snapshot_preview1", &adapter_module)
+        .with_context(|| {
+            format!(
+                "failed to load adapter module `{path}`",
+                path = match adapter {
+                    Some(Adapter::Path(path)) => path.as_path(),
+                    _ => Path::new("<built-in>"),
+                }
+                .display()
+            )
+        })?
+        .validate(true);
+
+    // Satisfy the raw imports of the module with their adapters
+    for (name, adapter) in raw.map(|r| &r.adapters).into_iter().flatten() {
+        let bytes = fs::read(adapter).with_context(|| {
+            format!(
+                "failed to read adapter module `{path}`",
+                path = adapter.display()
+            )
+        })?;
+
+        encoder = encoder.adapter(name, &bytes).with_context(|| {
+            format!(
+                "failed to load adapter module `{path}` for raw imports of module `{name}`",
+                path = adapter.display()
+            )
+        })?;
+    }
+
+    // The version info includes the commit and date of the `cargo-component`
+    // build, so reproducible builds only record the package version
+    let mut producers = wasm_metadata::Producers::empty();
+    producers.add(
+        "processed-by",
+        env!("CARGO_PKG_NAME"),
+        if build_args.reproducible {
+            env!("CARGO_PKG_VERSION")
+        } else {
+            option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION"))
+        },
+    );
+
+    let component = producers.add_to_wasm(&encoder.encode()?).with_context(|| {
+        format!(
+            "failed to add metadata to output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let component = match raw {
+        Some(raw) => add_custom_sections(component, &raw.custom_sections)?,
+        None => component,
+    };
+
+    fs::write(path, &component).with_context(|| {
+        format!(
+            "failed to write output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    if let Some(format) = build_args.size_report {
+        print_size_report(path, &component, format)?;
+    }
+
+    config.terminal().event(
+        "component-written",
+        serde_json::json!({
+            "path": path,
+            "kind": if binary { "command" } else { "reactor" },
+            "sha256": hex::encode(Sha256::digest(&component)),
+        }),
+    )
+}
+
+/// Finishes a component produced natively by the toolchain (e.g. for the
+/// `wasm32-wasip2` target).
+///
+/// No adapter is used for such components; instead, the component is checked
+/// to export the exports of its target world and to only import what the
+/// target world imports (or WASI interfaces), and the `cargo-component`
+/// producers metadata is added to it.
+fn finish_native_component(
+    config: &Config,
+    metadata: Option<&ComponentMetadata>,
+    bindings_dir: &Path,
+    profile: &str,
+    bin: Option<&str>,
+    path: &Path,
+    build_args: &BuildArguments,
+) -> Result<()> {
+    if let Some(metadata) = metadata {
+        if metadata.adapter(profile, bin).is_some() {
+            config.terminal().warn(format!(
+                "the WASI adapter of package `{name}` is not used as component `{path}` was produced natively",
+                name = metadata.name,
+                path = path.display()
+            ))?;
+        }
+
+        if metadata.optimize(profile, bin).1 {
+            config.terminal().warn(format!(
+                "component `{path}` was produced natively and cannot be optimized",
+                path = path.display()
+            ))?;
+        }
+    }
+
+    if build_args.emit_core_wasm {
+        config.terminal().warn(format!(
+            "no core module is emitted for component `{path}` as it was produced natively",
+            path = path.display()
+        ))?;
+    }
+
+    let bytes = fs::read(path).with_context(|| {
+        format!(
+            "failed to read output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let (resolve, world) = match wit_component::decode(&bytes).with_context(|| {
+        format!(
+            "failed to decode output component `{path}`",
+            path = path.display()
+        )
+    })? {
+        wit_component::DecodedWasm::Component(resolve, world) => (resolve, world),
+        wit_component::DecodedWasm::WitPackage(..) => bail!(
+            "output `{path}` is a WIT package and not a component",
+            path = path.display()
+        ),
+    };
+
+    config.terminal().status(
+        "Validating",
+        format!("component {path}", path = path.display()),
+    )?;
+
+    // Check the component against its target world, if it was encoded
+    if let Some(metadata) = metadata {
+        let target_dir = bindings_dir.join(&metadata.name);
+        if target_dir.join("target.wasm").is_file() {
+            let (target_resolve, target_world) = decode_target_world(&target_dir)?;
+            let target = &target_resolve.worlds[target_world];
+            let component = &resolve.worlds[world];
+
+            let names = |resolve: &wit_parser::Resolve, items: &IndexMap<WorldKey, WorldItem>| {
+                items
+                    .keys()
+                    .map(|k| explain::key_name(resolve, k))
+                    .collect::<HashSet<_>>()
+            };
+
+            let exports = names(&resolve, &component.exports);
+            for name in names(&target_resolve, &target.exports) {
+                if !exports.contains(&name) {
+                    bail!(
+                        "component `{path}` does not export `{name}` of its target world",
+                        path = path.display()
+                    );
+                }
+            }
+
+            let imports = names(&target_resolve, &target.imports);
+            for (key, name) in component
+                .imports
+                .keys()
+                .map(|k| (k, explain::key_name(&resolve, k)))
+            {
+                let is_wasi = match key {
+                    WorldKey::Interface(id) => resolve.interfaces[*id]
+                        .package
+                        .map(|p| resolve.packages[p].name.namespace == "wasi")
+                        .unwrap_or(false),
+                    WorldKey::Name(_) => false,
+                };
+
+                if !is_wasi && !imports.contains(&name) {
+                    bail!(
+                        "component `{path}` imports `{name}`, which is not imported by its target world",
+                        path = path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    let mut producers = wasm_metadata::Producers::empty();
+    producers.add(
+        "processed-by",
+        env!("CARGO_PKG_NAME"),
+        if build_args.reproducible {
+            env!("CARGO_PKG_VERSION")
+        } else {
+            option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION"))
+        },
+    );
+
+    let component = producers.add_to_wasm(&bytes).with_context(|| {
+        format!(
+            "failed to add metadata to output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    fs::write(path, &component).with_context(|| {
+        format!(
+            "failed to write output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    if let Some(format) = build_args.size_report {
+        print_size_report(path, &component, format)?;
+    }
+
+    config.terminal().event(
+        "component-written",
+        serde_json::json!({
+            "path": path,
+            "kind": if bin.is_some() { "command" } else { "reactor" },
+            "sha256": hex::encode(Sha256::digest(&component)),
+        }),
+    )
+}
+
+/// Exports the interfaces of a component at the additional versions
+/// specified in the component metadata.
+fn add_export_versions(metadata: &ComponentMetadata, path: &Path) -> Result<()> {
+    if metadata.section.export_versions.is_empty() {
+        return Ok(());
+    }
+
+    let component = fs::read(path).with_context(|| {
+        format!(
+            "failed to read output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let component =
+        match exports::add_export_versions(&component, &metadata.section.export_versions)
+            .with_context(|| {
+                format!(
+                    "failed to export additional interface versions from component `{path}`",
+                    path = path.display()
+                )
+            })? {
+            Some(component) => component,
+            None => return Ok(()),
+        };
+
+    fs::write(path, component).with_context(|| {
+        format!(
+            "failed to write output component `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+/// Prints the size report of a component to stdout.
+fn print_size_report(path: &Path, component: &[u8], format: SizeReportFormat) -> Result<()> {
+    let report = size::SizeReport::new(component).with_context(|| {
+        format!(
+            "failed to create a size report for component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    print!("{report}", report = report.format(path, format)?);
+    Ok(())
+}
+
+/// Strips the custom sections of a component according to the given
+/// policies, writing the separate debug file if any sections are split.
+fn strip_component(config: &Config, policies: &SectionPolicies, path: &Path) -> Result<()> {
+    if *policies == SectionPolicies::default() {
+        return Ok(());
+    }
+
+    let component = fs::read(path).with_context(|| {
+        format!(
+            "failed to read output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let mut stripped = strip::strip_sections(&component, &|name| {
+        strip::section_policy(policies, name) != SectionPolicy::Keep
+    })
+    .with_context(|| format!("failed to strip component `{path}`", path = path.display()))?;
+
+    // Nothing is removed from a component that was already stripped (e.g.
+    // on an up-to-date build), so its debug file is left as is
+    if stripped.len() == component.len() {
+        return Ok(());
+    }
+
+    let split = [
+        policies.debug_info,
+        policies.name_section,
+        policies.producers,
+    ]
+    .contains(&SectionPolicy::Split);
+
+    if split {
+        let debug_path = path.with_extension("debug.wasm");
+        fs::write(&debug_path, &component).with_context(|| {
+            format!(
+                "failed to write debug file `{path}`",
+                path = debug_path.display()
+            )
+        })?;
+
+        stripped = strip::add_external_debug_info(
+            &stripped,
+            &debug_path.file_name().unwrap().to_string_lossy(),
+        );
+    }
+
+    fs::write(path, &stripped).with_context(|| {
+        format!(
+            "failed to write output component `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    config.terminal().status(
+        "Stripped",
+        format!(
+            "component {path} ({before} to {after} bytes)",
+            path = path.display(),
+            before = component.len(),
+            after = stripped.len()
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Validates the configuration values of a package against a component.
+///
+/// Configuration values are provided to a component through `wasi:config`,
+/// so a warning is printed if they are declared for a component that does
+/// not import it.
+fn validate_config(config: &Config, metadata: &ComponentMetadata, path: &Path) -> Result<()> {
+    if metadata.section.config.is_empty() {
+        return Ok(());
+    }
+
+    let (resolve, world) = bindgen::decode_component(path)?;
+    let imports_config = resolve.worlds[world].imports.keys().any(|key| match key {
+        WorldKey::Interface(id) => resolve.interfaces[*id]
+            .package
+            .map(|p| {
+                let name = &resolve.packages[p].name;
+                name.namespace == "wasi" && name.name == "config"
+            })
+            .unwrap_or(false),
+        WorldKey::Name(_) => false,
+    });
+
+    if !imports_config {
+        config.terminal().warn(format!(
+            "package `{name}` declares configuration values, but component `{path}` does not import `wasi:config`",
+            name = metadata.name,
+            path = path.display()
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Runs `wasm-opt` on a core module with the given optimization level.
+///
+/// The program may be specified with the `CARGO_COMPONENT_WASM_OPT` environment
+/// variable. If the level was not explicitly specified and `wasm-opt` is not
+/// installed, the module is returned unoptimized.
+///
+/// `wasm-opt` removes the DWARF debug information and name section of the
+/// module unless `preserve_debug_info` is set, in which case it updates the
+/// debug information for the code it rewrites.
+fn optimize_module(
+    config: &Config,
+    module: Vec<u8>,
+    path: &Path,
+    (level, explicit): (OptLevel, bool),
+    preserve_debug_info: bool,
+) -> Result<Vec<u8>> {
+    let flag = match level.flag() {
+        Some(flag) => flag,
+        None => return Ok(module),
+    };
+
+    let program = std::env::var(WASM_OPT_ENV_VAR).unwrap_or_else(|_| "wasm-opt".to_string());
+    let input = path.with_extension("pre-opt.wasm");
+    let output = path.with_extension("opt.wasm");
+
+    fs::write(&input, &module)
+        .with_context(|| format!("failed to write `{path}`", path = input.display()))?;
+
+    log::debug!(
+        "optimizing module `{path}` with `{program} {flag}`",
+        path = path.display()
+    );
+
+    // Custom sections (such as the component type information of the bindings)
+    // are preserved by `wasm-opt`, and features are detected from the module
+    let mut cmd = Command::new(&program);
+    if preserve_debug_info {
+        cmd.arg("-g");
+    }
+
+    let result = cmd.arg(flag).arg(&input).arg("-o").arg(&output).output();
+
+    fs::remove_file(&input).ok();
+
+    let result = match result {
+        Ok(result) => result,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => {
+            log::debug!("`{program}` was not found; skipping optimization of the module");
+            return Ok(module);
+        }
+        Err(e) => {
+            return Err(e).with_context(|| {
+                format!(
+                    "failed to run `{program}` to optimize module `{path}`; install binaryen or set the `{WASM_OPT_ENV_VAR}` environment variable",
+                    path = path.display()
+                )
+            })
+        }
+    };
+
+    if !result.status.success() {
+        fs::remove_file(&output).ok();
+        bail!(
+            "failed to optimize module `{path}` with `{program} {flag}`: {stderr}",
+            path = path.display(),
+            stderr = String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    config.terminal().status(
+        "Optimized",
+        format!("module {path} ({flag})", path = path.display()),
+    )?;
+
+    let optimized = fs::read(&output)
+        .with_context(|| format!("failed to read `{path}`", path = output.display()))?;
+    fs::remove_file(&output).ok();
+
+    Ok(optimized)
+}
+
+/// Represents options for building components.
+///
+/// This is the programmatic equivalent of `cargo component build`.
+///
+/// New options may be added in future releases, so the options are created
+/// with [`BuildOptions::new`] and then set.
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct BuildOptions {
+    /// The path to the `Cargo.toml` of the package or workspace.
+    ///
+    /// If `None`, the manifest is searched for from the current directory.
+    pub manifest_path: Option<PathBuf>,
+    /// The packages to build.
+    ///
+    /// If empty, all workspace packages are built.
+    pub packages: Vec<CargoPackageSpec>,
+    /// Build all packages in the workspace.
+    pub workspace: bool,
+    /// Build with the `release` profile.
+    pub release: bool,
+    /// The name of the profile to build with.
+    pub profile: Option<String>,
+    /// The targets to build for.
+    ///
+    /// If empty, `wasm32-wasi` is used.
+    pub targets: Vec<String>,
+    /// The features to activate.
+    pub features: Vec<String>,
+    /// Activate all available features.
+    pub all_features: bool,
+    /// Do not activate the `default` feature.
+    pub no_default_features: bool,
+    /// Require the lock files to be up to date.
+    pub locked: bool,
+    /// Require the lock files and cache to be up to date.
+    pub frozen: bool,
+    /// Run without accessing the network.
+    pub offline: bool,
+    /// Keep the core module and adapter of each built component.
+    pub emit_core_wasm: bool,
+    /// Build the components reproducibly.
+    pub reproducible: bool,
+    /// The binary targets to build.
+    ///
+    /// If empty and `all_bins` is not set, all targets are built.
+    pub bins: Vec<String>,
+    /// Build all binary targets (and not the library target).
+    pub all_bins: bool,
+    /// The format of the size report to print for each built component, if any.
+    pub size_report: Option<SizeReportFormat>,
+    /// Remove the function imports that the core module of each component
+    /// never references.
+    pub minimize_imports: bool,
+    /// What to do with the debug information of the components.
+    ///
+    /// If `None`, the `debug-info` setting in `Cargo.toml` is used.
+    pub debug_info: Option<DebugInfo>,
+    /// The path to write a deployment manifest of the built components to, if any.
+    pub manifest_out: Option<PathBuf>,
+}
+
+impl BuildOptions {
+    /// Creates the default options, which build all workspace packages with
+    /// the `dev` profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn cargo_arguments(&self, config: &Config) -> CargoArguments {
+        CargoArguments {
+            color: None,
+            verbose: 0,
+            quiet: config.terminal().verbosity() == Verbosity::Quiet,
+            targets: self.targets.clone(),
+            manifest_path: self.manifest_path.clone(),
+            frozen: self.frozen,
+            locked: self.locked,
+            release: self.release,
+            profile: self.profile.clone(),
+            offline: self.offline,
+            workspace: self.workspace,
+            packages: self.packages.clone(),
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            bins: self.bins.clone(),
+            all_bins: self.all_bins,
+        }
+    }
+
+    fn spawn_args(&self, cargo_args: &CargoArguments) -> Result<Vec<String>> {
+        let mut args = vec!["build".to_string()];
+
+        if cargo_args.quiet {
+            args.push("-q".to_string());
+        }
+
+        if let Some(path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(
+                path.to_str()
+                    .with_context(|| {
+                        format!(
+                            "manifest path `{path}` is not valid UTF-8",
+                            path = path.display()
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        for bin in &self.bins {
+            args.push("--bin".to_string());
+            args.push(bin.clone());
+        }
+
+        if self.all_bins {
+            args.push("--bins".to_string());
+        }
+
+        for spec in &self.packages {
+            args.push("--package".to_string());
+            args.push(spec.to_string());
+        }
+
+        if self.workspace {
+            args.push("--workspace".to_string());
+        }
+
+        if self.release {
+            args.push("--release".to_string());
+        }
+
+        if let Some(profile) = &self.profile {
+            args.push("--profile".to_string());
+            args.push(profile.clone());
+        }
+
+        for target in &self.targets {
+            args.push("--target".to_string());
+            args.push(target.clone());
+        }
+
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.offline {
+            args.push("--offline".to_string());
+        }
+
+        if self.emit_core_wasm {
+            args.push("--emit".to_string());
+            args.push("core-wasm".to_string());
+        }
+
+        if self.reproducible {
+            args.push("--reproducible".to_string());
+        }
+
+        if let Some(format) = self.size_report {
+            args.push(match format {
+                SizeReportFormat::Text => "--size-report=text".to_string(),
+                SizeReportFormat::Json => "--size-report=json".to_string(),
+            });
+        }
+
+        if self.minimize_imports {
+            args.push("--minimize-imports".to_string());
+        }
+
+        if let Some(debug_info) = self.debug_info {
+            args.push("--debug-info".to_string());
+            args.push(
+                match debug_info {
+                    DebugInfo::Keep => "keep",
+                    DebugInfo::Strip => "strip",
+                    DebugInfo::Split => "split",
+                }
+                .to_string(),
+            );
+        }
+
+        if let Some(path) = &self.manifest_out {
+            args.push("--manifest-out".to_string());
+            args.push(
+                path.to_str()
+                    .with_context(|| {
+                        format!(
+                            "deployment manifest path `{path}` is not valid UTF-8",
+                            path = path.display()
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        Ok(args)
+    }
+}
+
+/// Builds the components of the packages specified by the given options.
+///
+/// Unlike `run_cargo_command`, this never exits the process; if cargo fails,
+/// a `CargoCommandError` is returned.
+///
+/// Returns the paths of the built components.
+pub async fn build_component(config: &Config, options: &BuildOptions) -> Result<Vec<PathBuf>> {
+    let metadata = load_metadata(options.manifest_path.as_deref())?;
+    let cargo_args = options.cargo_arguments(config);
+    let packages =
+        load_component_metadata(&metadata, cargo_args.packages.iter(), cargo_args.workspace)?;
+    let spawn_args = options.spawn_args(&cargo_args)?;
+
+    execute_cargo_command(
+        config,
+        &metadata,
+        &packages,
+        Some("build"),
+        &cargo_args,
+        &spawn_args,
+    )
+    .await
+}
+
+/// Generates the bindings of the packages specified by the given options
+/// without building them.
+///
+/// The bindings are generated in the `bindings` directory of the target
+/// directory of the workspace.
+pub async fn generate_bindings(config: &Config, options: &BuildOptions) -> Result<()> {
+    let metadata = load_metadata(options.manifest_path.as_deref())?;
+    let cargo_args = options.cargo_arguments(config);
+    let packages =
+        load_component_metadata(&metadata, cargo_args.packages.iter(), cargo_args.workspace)?;
+
+    encode_targets(config, &metadata, &packages, &cargo_args).await?;
+    Ok(())
+}
+
+/// Represents options for a publish operation.
+pub struct PublishOptions<'a> {
+    /// The package to publish.
+    pub package: &'a Package,
+    /// The registry URL to publish to.
+    pub registry_url: &'a str,
+    /// Whether to initialize the package or not.
+    pub init: bool,
+    /// The id of the package being published.
+    pub id: &'a PackageId,
+    /// The version of the package being published.
+    pub version: &'a Version,
+    /// The path to the package being published.
+    pub path: &'a Path,
+    /// The signer to use for the publish operation.
+    ///
+    /// A signer is not required when publishing to an OCI registry, and
+    /// publishing to a warg registry requires a signer holding the private key.
+    pub signer: Option<&'a Signer>,
+    /// Whether to embed a signature of the component made with the signing key.
+    pub embed_signature: bool,
+    /// The bill of materials to embed in the component, if any.
+    pub sbom: Option<&'a [u8]>,
+    /// The version control state to embed in the component, if any.
+    pub provenance: Option<&'a GitProvenance>,
+    /// Whether to perform a dry run or not.
+    pub dry_run: bool,
+}
+
+fn add_registry_metadata(
+    package: &Package,
+    bytes: &[u8],
+    path: &Path,
+    provenance: Option<&GitProvenance>,
+) -> Result<Vec<u8>> {
+    let mut metadata = RegistryMetadata::default();
+    if !package.authors.is_empty() {
+        metadata.set_authors(Some(package.authors.clone()));
+    }
+
+    if !package.categories.is_empty() {
+        metadata.set_categories(Some(package.categories.clone()));
+    }
+
+    metadata.set_description(package.description.clone());
+
+    // TODO: registry metadata should have keywords
+    // if !package.keywords.is_empty() {
+    //     metadata.set_keywords(Some(package.keywords.clone()));
+    // }
+
+    metadata.set_license(package.license.clone());
+
+    let mut links = Vec::new();
+    if let Some(docs) = &package.documentation {
+        links.push(Link {
+            ty: LinkType::Documentation,
+            value: docs.clone(),
+        });
+    }
+
+    if let Some(homepage) = &package.homepage {
+        links.push(Link {
+            ty: LinkType::Homepage,
+            value: homepage.clone(),
+        });
+    }
+
+    let repository = package
+        .repository
+        .clone()
+        .or_else(|| provenance.and_then(|p| p.repository.clone()));
+    if let Some(repo) = &repository {
+        links.push(Link {
+            ty: LinkType::Repository,
+            value: repo.clone(),
+        });
+    }
+
+    // The source link locates the published commit in the repository
+    if let Some((repo, provenance)) = repository.as_ref().zip(provenance) {
+        links.push(Link {
+            ty: LinkType::Custom(VCS_LINK_TYPE.to_string()),
+            value: format!("git+{repo}#{version}", version = provenance.version()),
+        });
+    }
+
+    if !links.is_empty() {
+        metadata.set_links(Some(links));
+    }
+
+    metadata.add_to_wasm(bytes).with_context(|| {
+        format!(
+            "failed to add registry metadata to component `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+/// Adds the commit of a package's repository to the producers section of a
+/// component.
+fn add_provenance(bytes: &[u8], provenance: &GitProvenance, path: &Path) -> Result<Vec<u8>> {
+    let mut producers = Producers::empty();
+    producers.add(VCS_PRODUCERS_FIELD, "git", &provenance.version());
+    producers.add_to_wasm(bytes).with_context(|| {
+        format!(
+            "failed to add version control information to component `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+/// Publish a component for the given workspace and publish options.
+pub async fn publish(config: &Config, options: &PublishOptions<'_>) -> Result<()> {
+    if options.dry_run {
+        config
+            .terminal()
+            .warn("not publishing component to the registry due to the --dry-run option")?;
+        return Ok(());
+    }
 
-            config.terminal().status(
-                "Encoding",
-                format!(
-                    "target for {name} ({path})",
-                    name = resolution.metadata.name,
-                    path = target_path.display()
-                ),
-            )?;
+    let bytes = fs::read(options.path).with_context(|| {
+        format!(
+            "failed to read component `{path}`",
+            path = options.path.display()
+        )
+    })?;
 
-            let encoded = encoder.encode()?;
-            fs::create_dir_all(&output_dir).with_context(|| {
-                format!(
-                    "failed to create output directory `{path}`",
-                    path = output_dir.display()
-                )
-            })?;
+    let bytes = match options.sbom {
+        Some(sbom) => sbom::add_to_wasm(&bytes, sbom),
+        None => bytes,
+    };
+
+    let bytes = add_registry_metadata(options.package, &bytes, options.path, options.provenance)?;
+
+    let bytes = match options.provenance {
+        Some(provenance) => add_provenance(&bytes, provenance, options.path)?,
+        None => bytes,
+    };
+
+    // The signature must be embedded last as it covers the rest of the component
+    let bytes = if options.embed_signature {
+        let signer = options
+            .signer
+            .context("a signing key is required to embed a signature in the component")?;
+
+        config.terminal().status(
+            "Signing",
+            format!(
+                "component {path} with key `{id}`",
+                path = options.path.display(),
+                id = signer.public_key().fingerprint()
+            ),
+        )?;
+
+        signature::sign(&bytes, signer)?
+    } else {
+        bytes
+    };
+
+    let record = if oci::is_oci_url(options.registry_url) {
+        let client = OciClient::new(options.registry_url, oci::cache_dir(config.warg())?)?;
+
+        config.terminal().status(
+            "Publishing",
+            format!(
+                "component {path} to `{url}`",
+                path = options.path.display(),
+                url = options.registry_url
+            ),
+        )?;
 
-            fs::write(&target_path, encoded).with_context(|| {
+        client
+            .publish(options.id, options.version, &bytes)
+            .await
+            .with_context(|| {
                 format!(
-                    "failed to write target file `{path}`",
-                    path = target_path.display()
+                    "failed to publish package `{id}` to `{url}`",
+                    id = options.id,
+                    url = options.registry_url
                 )
             })?;
 
-            let world = resolution.metadata.section.target.world().unwrap_or("");
+        None
+    } else {
+        let signing_key = options
+            .signer
+            .context("a signing key is required to publish to a warg registry")?
+            .private_key()?;
+        let client = create_client(config.warg(), options.registry_url, config.terminal())?;
+
+        let content = client
+            .content()
+            .store_content(
+                Box::pin(futures::stream::once(async { Ok(Bytes::from(bytes)) })),
+                None,
+            )
+            .await?;
+
+        config.terminal().status(
+            "Publishing",
+            format!(
+                "component {path} ({content})",
+                path = options.path.display()
+            ),
+        )?;
+
+        let mut info = PublishInfo {
+            id: options.id.clone(),
+            head: None,
+            entries: Default::default(),
+        };
+
+        if options.init {
+            info.entries.push(PublishEntry::Init);
+        }
+
+        info.entries.push(PublishEntry::Release {
+            version: options.version.clone(),
+            content,
+        });
+
+        let record_id = client.publish_with_info(signing_key, info).await?;
+        client
+            .wait_for_publish(options.id, &record_id, Duration::from_secs(1))
+            .await?;
+
+        Some(record_id.to_string())
+    };
+
+    config.terminal().status(
+        "Published",
+        format!(
+            "package `{id}` v{version}",
+            id = options.id,
+            version = options.version
+        ),
+    )?;
+
+    config.terminal().event(
+        "published",
+        serde_json::json!({
+            "package": options.id.to_string(),
+            "version": options.version.to_string(),
+            "registry": options.registry_url,
+            "record": record,
+        }),
+    )?;
+
+    Ok(())
+}
 
-            fs::write(&world_path, world).with_context(|| {
+/// Resolves the dependencies of a component package using the lock file.
+async fn resolve_package_dependencies<'a>(
+    config: &Config,
+    metadata: &Metadata,
+    component_metadata: &'a ComponentMetadata,
+    network_allowed: bool,
+) -> Result<PackageDependencyResolution<'a>> {
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
                 format!(
-                    "failed to write world name `{path}`",
-                    path = world_path.display()
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
                 )
-            })?;
-        }
-        None => {
-            ::log::debug!(
-                "existing target encoding for package `{name}` at `{path}` is up-to-date",
-                name = resolution.metadata.name,
-                path = target_path.display(),
-            );
-        }
+            })
+        })
+        .transpose()?;
+
+    // The generated WIT files are part of the target world
+    generate::run(config, component_metadata)?;
+
+    let vendor_dir = vendor_dir(metadata, lock_file.as_ref());
+    PackageDependencyResolution::new(
+        config,
+        component_metadata,
+        lock_file.as_ref().map(LockFileResolver::new),
+        vendor_dir.as_deref(),
+        None,
+        network_allowed,
+    )
+    .await
+}
+
+/// Generates a CycloneDX software bill of materials for a component package.
+///
+/// The bill of materials covers the Rust crate graph of the package and its
+/// component and WIT registry dependencies.
+pub async fn generate_sbom(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    cargo_args: &CargoArguments,
+) -> Result<Vec<u8>> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
+        format!(
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
+        )
+    })?;
+
+    let id = component_metadata.section.package.as_ref().with_context(|| {
+        format!(
+            "package `{name}` is missing a `package.metadata.component.package` setting in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
+        )
+    })?;
+
+    // The workspace metadata does not include dependencies, so load the crate graph
+    let mut command = MetadataCommand::new();
+    command.manifest_path(&package.package.manifest_path);
+    if cargo_args.all_features {
+        command.features(CargoOpt::AllFeatures);
+    }
+    if cargo_args.no_default_features {
+        command.features(CargoOpt::NoDefaultFeatures);
     }
+    if !cargo_args.all_features && !cargo_args.features.is_empty() {
+        command.features(CargoOpt::SomeFeatures(cargo_args.features.clone()));
+    }
+    command.other_options(
+        [
+            (cargo_args.frozen, "--frozen"),
+            (cargo_args.locked, "--locked"),
+            (cargo_args.offline, "--offline"),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, flag)| flag.to_string())
+        .collect::<Vec<_>>(),
+    );
 
-    Ok(())
+    let graph = command
+        .exec()
+        .context("failed to load cargo metadata with dependencies")?;
+
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    sbom::generate(
+        &graph,
+        package.package,
+        id,
+        &component_metadata.version,
+        &resolution,
+    )
 }
 
-fn is_wasm_module(path: impl AsRef<Path>) -> Result<bool> {
-    let path = path.as_ref();
+/// Explains the fully resolved target world of a component package.
+///
+/// If `name` is `None`, the package's default target is explained; otherwise
+/// the named target is explained.
+///
+/// Returns a description of every import and export of the world along with
+/// where it came from.
+pub async fn explain_target(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    name: Option<&str>,
+    cargo_args: &CargoArguments,
+) -> Result<String> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
+        format!(
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
+        )
+    })?;
 
-    let mut file = File::open(path)
-        .with_context(|| format!("failed to open `{path}` for read", path = path.display()))?;
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
 
-    let mut bytes = [0u8; 8];
-    file.read(&mut bytes).with_context(|| {
+    let features = cargo_args.enabled_features(package.package);
+    Ok(explain::WorldExplainer::new(&resolution, name, &features)?.explain())
+}
+
+/// Explains why packages matching the given name are in the resolved
+/// dependency graph of the target world of a component package.
+///
+/// If `target` is `None`, the package's default target is used; otherwise
+/// the named target is used.
+///
+/// Returns a description of the dependencies, world includes, and interface
+/// `use` statements that pulled in each matching package.
+pub async fn explain_package(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    name: &str,
+    target: Option<&str>,
+    cargo_args: &CargoArguments,
+) -> Result<String> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
         format!(
-            "failed to read file header for `{path}`",
-            path = path.display()
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
         )
     })?;
 
-    if bytes[0..4] != [0x0, b'a', b's', b'm'] {
-        bail!(
-            "expected `{path}` to be a WebAssembly module",
-            path = path.display()
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    let features = cargo_args.enabled_features(package.package);
+    why::PackageExplainer::new(&resolution, target, &features)?.explain(name)
+}
+
+/// Audits the registry dependencies locked in the component lock file.
+///
+/// The locked versions are checked against the advisory database at the given
+/// path or git repository URL, if any, and against the releases of their
+/// registries.
+///
+/// Returns `Ok(None)` if the workspace has no component lock file.
+pub async fn audit_dependencies(
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    db: Option<&str>,
+    network_allowed: bool,
+) -> Result<Option<Vec<audit::Finding>>> {
+    let file_lock = match acquire_lock_file_ro(config.terminal(), metadata)? {
+        Some(file_lock) => file_lock,
+        None => return Ok(None),
+    };
+
+    let lock_file = LockFile::read(file_lock.file()).with_context(|| {
+        format!(
+            "failed to read lock file `{path}`",
+            path = file_lock.path().display()
+        )
+    })?;
+
+    let db = db
+        .map(|source| {
+            audit::AdvisoryDatabase::open(
+                source,
+                &git::cache_dir(config.warg())?,
+                network_allowed,
+                config.terminal(),
+            )
+        })
+        .transpose()?;
+
+    // Every package in the workspace shares the lock file, so use all of their registries
+    let mut registries = HashMap::new();
+    for package in packages.iter().filter_map(|p| p.metadata.as_ref()) {
+        registries.extend(
+            package
+                .section
+                .registries
+                .iter()
+                .map(|(name, url)| (name.clone(), url.clone())),
         );
     }
 
-    // Check for the module header version
-    Ok(bytes[4..] == [0x01, 0x00, 0x00, 0x00])
+    audit::audit(
+        config.warg(),
+        &registries,
+        &lock_file,
+        db.as_ref(),
+        config.terminal(),
+        network_allowed,
+    )
+    .await
+    .map(Some)
 }
 
-fn adapter_bytes(metadata: &ComponentMetadata, binary: bool) -> Result<Cow<[u8]>> {
-    if let Some(adapter) = &metadata.section.adapter {
-        return Ok(fs::read(adapter)
-            .with_context(|| {
+/// Describes the resolved component graph of the given packages.
+///
+/// Returns a JSON object describing the resolved target worlds of each
+/// component package, its target, component, and adapter dependencies, and
+/// the location of its generated bindings. Packages without component
+/// metadata are included with a `null` component.
+pub async fn describe_components(
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+) -> Result<serde_json::Value> {
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
                 format!(
-                    "failed to read module adapter `{path}`",
-                    path = adapter.display()
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
                 )
-            })?
-            .into());
+            })
+        })
+        .transpose()?;
+
+    for metadata in packages.iter().filter_map(|p| p.metadata.as_ref()) {
+        generate::run(config, metadata)?;
     }
 
-    if binary {
-        Ok(Cow::Borrowed(include_bytes!(concat!(
-            "../adapters/",
-            env!("WASI_ADAPTER_VERSION"),
-            "/wasi_snapshot_preview1.command.wasm"
-        ))))
-    } else {
-        Ok(Cow::Borrowed(include_bytes!(concat!(
-            "../adapters/",
-            env!("WASI_ADAPTER_VERSION"),
-            "/wasi_snapshot_preview1.reactor.wasm"
-        ))))
+    let vendor_dir = vendor_dir(metadata, lock_file.as_ref());
+    let map = create_resolution_map(
+        config,
+        packages,
+        lock_file.as_ref().map(LockFileResolver::new),
+        vendor_dir.as_deref(),
+        None,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    let bindings_dir = metadata.target_directory.join("bindings");
+    let describe_all = |resolutions: &DependencyResolutionMap| {
+        let mut ids: Vec<_> = resolutions.keys().collect();
+        ids.sort_by_key(|id| id.to_string());
+        ids.into_iter()
+            .map(|id| resolutions[id].to_json())
+            .collect::<Vec<_>>()
+    };
+
+    let mut described = Vec::with_capacity(packages.len());
+    for PackageComponentMetadata { package, .. } in packages {
+        let component = match map.get(&package.id) {
+            Some(resolution) => {
+                let features = cargo_args.enabled_features(package);
+
+                let mut names: Vec<_> = resolution.metadata.section.targets.keys().collect();
+                names.sort();
+
+                let mut targets = Vec::with_capacity(names.len() + 1);
+                for name in std::iter::once(None).chain(names.into_iter().map(|n| Some(n.as_str())))
+                {
+                    let encoder = BindingsEncoder::new(resolution, name, &features)?;
+                    let resolve = encoder.resolve();
+                    let world = &resolve.worlds[encoder.world()];
+                    let dir = match name {
+                        Some(name) => bindings_dir.join(&package.name).join("targets").join(name),
+                        None => bindings_dir.join(&package.name),
+                    };
+
+                    targets.push(serde_json::json!({
+                        "name": name,
+                        "world": explain::world_name(resolve, world.package, &world.name),
+                        "imports": world
+                            .imports
+                            .keys()
+                            .map(|k| explain::key_name(resolve, k))
+                            .collect::<Vec<_>>(),
+                        "exports": world
+                            .exports
+                            .keys()
+                            .map(|k| explain::key_name(resolve, k))
+                            .collect::<Vec<_>>(),
+                        "dependencies": describe_all(
+                            resolution
+                                .target_resolutions(name)
+                                .expect("target should have resolutions")
+                        ),
+                        "bindings": dir,
+                    }));
+                }
+
+                let mut dependencies: Vec<_> = resolution
+                    .resolutions
+                    .iter()
+                    .filter(|(id, _)| resolution.metadata.is_dependency_enabled(id, &features))
+                    .map(|(id, dependency)| (id.to_string(), dependency.to_json()))
+                    .collect();
+                dependencies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                serde_json::json!({
+                    "package": resolution.metadata.section.package.as_ref().map(ToString::to_string),
+                    "targets": targets,
+                    "dependencies": dependencies.into_iter().map(|(_, d)| d).collect::<Vec<_>>(),
+                    "dev-dependencies": describe_all(&resolution.dev_resolutions),
+                    "adapters": describe_all(&resolution.adapter_resolutions),
+                })
+            }
+            None => serde_json::Value::Null,
+        };
+
+        described.push(serde_json::json!({
+            "name": package.name,
+            "version": package.version.to_string(),
+            "id": package.id.repr,
+            "manifest_path": package.manifest_path,
+            "component": component,
+        }));
     }
+
+    Ok(serde_json::json!({
+        "version": 1,
+        "workspace_root": metadata.workspace_root,
+        "target_directory": metadata.target_directory,
+        "packages": described,
+    }))
 }
 
-fn create_component(
+/// Generates the Rust bindings of the fully resolved target world of a
+/// component package as a source file.
+///
+/// If `name` is `None`, bindings are generated for the package's default
+/// target; otherwise they are generated for the named target. The given
+/// command is recorded in the header of the source as the way to regenerate it.
+pub async fn emit_bindings(
     config: &Config,
-    metadata: &ComponentMetadata,
-    path: &Path,
-    binary: bool,
-) -> Result<()> {
-    // If the compilation output is not a WebAssembly module, then do nothing
-    // Note: due to the way cargo currently works on macOS, it will overwrite
-    // a previously generated component on an up-to-date build.
-    //
-    // As a result, users on macOS will see a "creating component" message
-    // even if the build is up-to-date.
-    //
-    // See: https://github.com/rust-lang/cargo/blob/99ad42deb4b0be0cdb062d333d5e63460a94c33c/crates/cargo-util/src/paths.rs#L542-L550
-    if !is_wasm_module(path)? {
-        ::log::debug!(
-            "output file `{path}` is already a WebAssembly component",
-            path = path.display()
-        );
-        return Ok(());
-    }
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    name: Option<&str>,
+    cargo_args: &CargoArguments,
+    command: &str,
+) -> Result<String> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
+        format!(
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
+        )
+    })?;
 
-    ::log::debug!(
-        "componentizing WebAssembly module `{path}` as a {kind} component",
-        path = path.display(),
-        kind = if binary { "command" } else { "reactor" },
-    );
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
 
-    let module = fs::read(path).with_context(|| {
+    let features = cargo_args.enabled_features(package.package);
+    let encoder = BindingsEncoder::new(&resolution, name, &features)?;
+    emit::generate(
+        encoder.resolve(),
+        encoder.world(),
+        &package.package.name,
+        &component_metadata.section.bindings,
+        command,
+    )
+}
+
+/// Generates the files of a host crate for the fully resolved target world of
+/// a component package.
+///
+/// If `name` is `None`, the host crate is for the package's default target;
+/// otherwise it is for the named target.
+///
+/// The paths of the returned files are relative to the directory of the host
+/// crate.
+pub async fn emit_host_crate(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    name: Option<&str>,
+    cargo_args: &CargoArguments,
+    command: &str,
+) -> Result<Vec<(PathBuf, String)>> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
+        format!(
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
+        )
+    })?;
+
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    let features = cargo_args.enabled_features(package.package);
+    let encoder = BindingsEncoder::new(&resolution, name, &features)?;
+    host::generate(
+        encoder.resolve(),
+        encoder.world(),
+        &package.package.name,
+        &package.package.version.to_string(),
+        command,
+    )
+}
+
+/// Generates `todo!()` stubs for the exports of the fully resolved target
+/// world of a component package that the package's sources don't implement.
+///
+/// If `name` is `None`, the stubs are for the package's default target;
+/// otherwise they are for the named target.
+pub async fn stub_exports(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    name: Option<&str>,
+    cargo_args: &CargoArguments,
+) -> Result<Vec<stub::Stub>> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
         format!(
-            "failed to read output module `{path}`",
-            path = path.display()
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
         )
     })?;
 
-    config.terminal().status(
-        "Creating",
-        format!("component {path}", path = path.display()),
-    )?;
-
-    let encoder = ComponentEncoder::default()
-        .module(&module)?
-        .adapter("wasi_snapshot_preview1", &adapter_bytes(metadata, binary)?)
+    // Prefer the library target as the crate root, as that is what becomes the component
+    let root = package
+        .package
+        .targets
+        .iter()
+        .find(|t| t.kind.iter().any(|k| k == "cdylib" || k == "lib"))
+        .or_else(|| package.package.targets.iter().find(|t| t.is_bin()))
         .with_context(|| {
             format!(
-                "failed to load adapter module `{path}`",
-                path = if let Some(path) = &metadata.section.adapter {
-                    path.as_path()
-                } else {
-                    Path::new("<built-in>")
-                }
-                .display()
+                "package `{name}` has no library or binary target",
+                name = package.package.name
             )
-        })?
-        .validate(true);
+        })?;
 
-    let mut producers = wasm_metadata::Producers::empty();
-    producers.add(
-        "processed-by",
-        env!("CARGO_PKG_NAME"),
-        option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION")),
-    );
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
 
-    let component = producers.add_to_wasm(&encoder.encode()?).with_context(|| {
+    let features = cargo_args.enabled_features(package.package);
+    let encoder = BindingsEncoder::new(&resolution, name, &features)?;
+    stub::generate(
+        encoder.resolve(),
+        encoder.world(),
+        root.src_path.as_std_path(),
+    )
+}
+
+/// Generates a mock component package for the imported interfaces of the
+/// target world of a component package.
+///
+/// If `interfaces` is empty, every imported non-WASI interface with functions
+/// is mocked.
+///
+/// The mock package is created at `dir` and exports the mocked interfaces
+/// with `todo!()` implementations.
+pub async fn generate_mock(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    dir: &Path,
+    name: &str,
+    interfaces: &[String],
+    cargo_args: &CargoArguments,
+) -> Result<mock::Mock> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
         format!(
-            "failed to add metadata to output component `{path}`",
-            path = path.display()
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
         )
     })?;
 
-    fs::write(path, component).with_context(|| {
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    let features = cargo_args.enabled_features(package.package);
+    let encoder = BindingsEncoder::new(&resolution, None, &features)?;
+    mock::generate(
+        encoder.resolve(),
+        encoder.world(),
+        component_metadata,
+        dir,
+        name,
+        interfaces,
+    )
+}
+
+/// Generates bindings for another language from the fully resolved target
+/// world of a component package.
+///
+/// If `name` is `None`, bindings are generated for the package's default
+/// target; otherwise they are generated for the named target.
+///
+/// Returns the paths of the generated files.
+pub async fn generate_language_bindings(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    name: Option<&str>,
+    cargo_args: &CargoArguments,
+    language: Language,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
         format!(
-            "failed to write output component `{path}`",
-            path = path.display()
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
         )
-    })
+    })?;
+
+    let resolution = resolve_package_dependencies(
+        config,
+        metadata,
+        component_metadata,
+        cargo_args.network_allowed(),
+    )
+    .await?;
+
+    let features = cargo_args.enabled_features(package.package);
+    let encoder = BindingsEncoder::new(&resolution, name, &features)?;
+    bindgen::generate(encoder.resolve(), encoder.world(), language, out_dir)
 }
 
-/// Represents options for a publish operation.
-pub struct PublishOptions<'a> {
-    /// The package to publish.
-    pub package: &'a Package,
-    /// The registry URL to publish to.
-    pub registry_url: &'a str,
-    /// Whether to initialize the package or not.
-    pub init: bool,
-    /// The id of the package being published.
-    pub id: &'a PackageId,
-    /// The version of the package being published.
-    pub version: &'a Version,
-    /// The path to the package being published.
-    pub path: &'a Path,
-    /// The signing key to use for the publish operation.
-    pub signing_key: &'a PrivateKey,
-    /// Whether to perform a dry run or not.
-    pub dry_run: bool,
+/// Generates bindings for another language from the world of a built
+/// component.
+///
+/// Returns the paths of the generated files.
+pub fn generate_component_language_bindings(
+    component: &Path,
+    language: Language,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let (resolve, world) = bindgen::decode_component(component)?;
+    bindgen::generate(&resolve, world, language, out_dir)
 }
 
-fn add_registry_metadata(package: &Package, bytes: &[u8], path: &Path) -> Result<Vec<u8>> {
-    let mut metadata = RegistryMetadata::default();
-    if !package.authors.is_empty() {
-        metadata.set_authors(Some(package.authors.clone()));
-    }
+/// Composes a built component with its component dependencies.
+///
+/// The composition is described by an optional `wasm-compose` configuration
+/// file or WAC composition script (with a `.wac` extension). The component
+/// dependencies of the package are made available to the composition as
+/// dependencies named after their package names (e.g. `bar` for `foo:bar`)
+/// unless the configuration already defines a dependency of that name.
+pub async fn compose(
+    config: &Config,
+    metadata: &Metadata,
+    package: &PackageComponentMetadata<'_>,
+    component: &Path,
+    compose_config: Option<&Path>,
+    output: &Path,
+    network_allowed: bool,
+) -> Result<()> {
+    let component_metadata = package.metadata.as_ref().with_context(|| {
+        format!(
+            "package `{name}` is missing component metadata in manifest `{path}`",
+            name = package.package.name,
+            path = package.package.manifest_path
+        )
+    })?;
 
-    if !package.categories.is_empty() {
-        metadata.set_categories(Some(package.categories.clone()));
-    }
+    let resolution =
+        resolve_package_dependencies(config, metadata, component_metadata, network_allowed).await?;
 
-    metadata.set_description(package.description.clone());
+    let mut name = None;
+    let (root, mut compose_config) = match compose_config {
+        Some(path) if path.extension().and_then(|e| e.to_str()) == Some(WAC_EXTENSION) => {
+            let composition: wac::Composition = fs::read_to_string(path)
+                .with_context(|| {
+                    format!(
+                        "failed to read composition script `{path}`",
+                        path = path.display()
+                    )
+                })?
+                .parse()
+                .with_context(|| {
+                    format!(
+                        "failed to parse composition script `{path}`",
+                        path = path.display()
+                    )
+                })?;
 
-    // TODO: registry metadata should have keywords
-    // if !package.keywords.is_empty() {
-    //     metadata.set_keywords(Some(package.keywords.clone()));
-    // }
+            // The package itself is referenced by its id
+            let id: PackageId = match &component_metadata.section.package {
+                Some(id) => id.clone(),
+                None => format!("component:{name}", name = component_metadata.name).parse()?,
+            };
 
-    metadata.set_license(package.license.clone());
+            name = composition.package.clone();
+            composition
+                .to_config(
+                    path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    |package, version| {
+                        if *package == id {
+                            return Ok(component.to_path_buf());
+                        }
 
-    let mut links = Vec::new();
-    if let Some(docs) = &package.documentation {
-        links.push(Link {
-            ty: LinkType::Documentation,
-            value: docs.clone(),
-        });
-    }
+                        let dependency = resolution.resolutions.get(package).with_context(|| {
+                            format!(
+                                "package `{package}` referenced by composition script `{path}` is not a component dependency",
+                                path = path.display()
+                            )
+                        })?;
 
-    if let Some(homepage) = &package.homepage {
-        links.push(Link {
-            ty: LinkType::Homepage,
-            value: homepage.clone(),
-        });
-    }
+                        if let (Some(version), DependencyResolution::Registry(res)) =
+                            (version, dependency)
+                        {
+                            if res.version != *version {
+                                bail!(
+                                    "composition script `{path}` references version {version} of package `{package}`, but version {locked} is locked",
+                                    path = path.display(),
+                                    locked = res.version
+                                );
+                            }
+                        }
 
-    if let Some(repo) = &package.repository {
-        links.push(Link {
-            ty: LinkType::Repository,
-            value: repo.clone(),
-        });
-    }
+                        Ok(dependency.path().to_path_buf())
+                    },
+                )
+                .with_context(|| {
+                    format!(
+                        "failed to resolve composition script `{path}`",
+                        path = path.display()
+                    )
+                })?
+        }
+        Some(path) => (
+            component.to_path_buf(),
+            wasm_compose::config::Config::from_file(path).with_context(|| {
+                format!(
+                    "failed to read composition configuration `{path}`",
+                    path = path.display()
+                )
+            })?,
+        ),
+        None => (
+            component.to_path_buf(),
+            wasm_compose::config::Config {
+                dir: component_metadata
+                    .manifest_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
+        ),
+    };
 
-    if !links.is_empty() {
-        metadata.set_links(Some(links));
+    for (id, dependency) in &resolution.resolutions {
+        let name = id.name();
+        if compose_config.dependencies.contains_key(name) {
+            continue;
+        }
+
+        log::debug!(
+            "adding component dependency `{id}` to the composition as `{name}` ({path})",
+            path = dependency.path().display()
+        );
+
+        compose_config.dependencies.insert(
+            name.to_string(),
+            wasm_compose::config::Dependency {
+                path: dependency.path().to_path_buf(),
+            },
+        );
     }
 
-    metadata.add_to_wasm(bytes).with_context(|| {
+    let bytes = wasm_compose::composer::ComponentComposer::new(&root, &compose_config)
+        .compose()
+        .with_context(|| {
+            format!(
+                "failed to compose component `{path}`",
+                path = root.display()
+            )
+        })?;
+
+    fs::write(output, bytes).with_context(|| {
         format!(
-            "failed to add registry metadata to component `{path}`",
-            path = path.display()
+            "failed to write composed component `{path}`",
+            path = output.display()
         )
-    })
+    })?;
+
+    config.terminal().status(
+        "Composed",
+        match name {
+            Some(name) => format!("`{name}` to `{path}`", path = output.display()),
+            None => format!("component `{path}`", path = output.display()),
+        },
+    )?;
+
+    Ok(())
 }
 
-/// Publish a component for the given workspace and publish options.
-pub async fn publish(config: &Config, options: &PublishOptions<'_>) -> Result<()> {
-    if options.dry_run {
-        config
-            .terminal()
-            .warn("not publishing component to the registry due to the --dry-run option")?;
-        return Ok(());
-    }
+/// Vendors the registry dependencies of the given packages into a directory.
+///
+/// The vendor directory is recorded in the lock file so that subsequent
+/// builds resolve the vendored contents without accessing the network.
+pub async fn vendor_dependencies(
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    dir: &Path,
+    network_allowed: bool,
+    lock_update_allowed: bool,
+    locked: bool,
+) -> Result<()> {
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
+                )
+            })
+        })
+        .transpose()?;
 
-    let client = create_client(config.warg(), options.registry_url, config.terminal())?;
+    // Resolve from the registries (not an existing vendor directory) to get the contents
+    let resolver = lock_file.as_ref().map(LockFileResolver::new);
+    let map =
+        create_resolution_map(config, packages, resolver, None, None, network_allowed).await?;
 
-    let bytes = fs::read(options.path).with_context(|| {
+    let workspace_root = metadata.workspace_root.as_std_path();
+    let vendor_dir = workspace_root.join(dir);
+    fs::create_dir_all(&vendor_dir).with_context(|| {
         format!(
-            "failed to read component `{path}`",
-            path = options.path.display()
+            "failed to create vendor directory `{path}`",
+            path = vendor_dir.display()
         )
     })?;
 
-    let bytes = add_registry_metadata(options.package, &bytes, options.path)?;
-
-    let content = client
-        .content()
-        .store_content(
-            Box::pin(futures::stream::once(async { Ok(Bytes::from(bytes)) })),
-            None,
+    // Remove any previously vendored contents as they may no longer be dependencies
+    for entry in fs::read_dir(&vendor_dir).with_context(|| {
+        format!(
+            "failed to read vendor directory `{path}`",
+            path = vendor_dir.display()
         )
-        .await?;
+    })? {
+        let path = entry?.path();
+        let is_vendored = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with("sha256-") && n.ends_with(".wasm"))
+            .unwrap_or(false);
 
-    config.terminal().status(
-        "Publishing",
-        format!(
-            "component {path} ({content})",
-            path = options.path.display()
-        ),
-    )?;
+        if is_vendored {
+            fs::remove_file(&path).with_context(|| {
+                format!(
+                    "failed to remove vendored file `{path}`",
+                    path = path.display()
+                )
+            })?;
+        }
+    }
 
-    let mut info = PublishInfo {
-        id: options.id.clone(),
-        head: None,
-        entries: Default::default(),
-    };
+    let mut vendored = HashSet::new();
+    for PackageComponentMetadata { package, .. } in packages {
+        let resolution = match map.get(&package.id) {
+            Some(resolution) => resolution,
+            None => continue,
+        };
+
+        for (_, dependency) in resolution.all() {
+            let resolution = match dependency {
+                DependencyResolution::Registry(resolution) => resolution,
+                DependencyResolution::Local(_) | DependencyResolution::Git(_) => continue,
+            };
+
+            let path = vendored_path(&vendor_dir, &resolution.digest);
+            if !vendored.insert(path.clone()) {
+                continue;
+            }
+
+            config.terminal().status(
+                "Vendoring",
+                format!(
+                    "dependency `{id}` v{version}",
+                    id = resolution.package,
+                    version = resolution.version
+                ),
+            )?;
 
-    if options.init {
-        info.entries.push(PublishEntry::Init);
+            fs::copy(&resolution.path, &path).with_context(|| {
+                format!(
+                    "failed to copy `{src}` to `{dest}`",
+                    src = resolution.path.display(),
+                    dest = path.display()
+                )
+            })?;
+        }
     }
 
-    info.entries.push(PublishEntry::Release {
-        version: options.version.clone(),
-        content,
-    });
+    let mut new_lock_file = map.to_lock_file();
+    new_lock_file.vendor = Some(
+        vendor_dir
+            .strip_prefix(workspace_root)
+            .unwrap_or(&vendor_dir)
+            .to_path_buf(),
+    );
 
-    let record_id = client.publish_with_info(options.signing_key, info).await?;
-    client
-        .wait_for_publish(options.id, &record_id, Duration::from_secs(1))
-        .await?;
+    if Some(&new_lock_file) != lock_file.as_ref() {
+        drop(file_lock);
+        let file_lock =
+            acquire_lock_file_rw(config.terminal(), metadata, lock_update_allowed, locked)?;
+        new_lock_file
+            .write(file_lock.file(), "cargo-component")
+            .with_context(|| {
+                format!(
+                    "failed to write lock file `{path}`",
+                    path = file_lock.path().display()
+                )
+            })?;
+    }
 
     config.terminal().status(
-        "Published",
+        "Vendored",
         format!(
-            "package `{id}` v{version}",
-            id = options.id,
-            version = options.version
+            "{count} dependencies to `{path}`",
+            count = vendored.len(),
+            path = vendor_dir.display()
         ),
     )?;
 
     Ok(())
 }
 
+/// Represents options for updating the component lock file.
+#[derive(Debug, Default, Clone)]
+pub struct UpdateOptions {
+    /// The registry packages to update.
+    ///
+    /// If empty, all dependencies are updated.
+    pub packages: Vec<PackageId>,
+    /// The exact version to update the packages to.
+    pub precise: Option<Version>,
+    /// Don't actually write the lock file.
+    pub dry_run: bool,
+}
+
 /// Update the dependencies in the lock file.
 ///
 /// This updates only `Cargo-component.lock`.
@@ -686,11 +3954,11 @@ pub async fn update_lockfile(
     network_allowed: bool,
     lock_update_allowed: bool,
     locked: bool,
-    dry_run: bool,
+    options: &UpdateOptions,
 ) -> Result<()> {
-    // Read the current lock file and generate a new one
-    let map = create_resolution_map(config, packages, None, network_allowed).await?;
+    let dry_run = options.dry_run;
 
+    // Read the current lock file and generate a new one
     let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
     let orig_lock_file = file_lock
         .as_ref()
@@ -705,7 +3973,43 @@ pub async fn update_lockfile(
         .transpose()?
         .unwrap_or_default();
 
-    let new_lock_file = map.to_lock_file();
+    // When updating specific packages, keep every other dependency locked
+    let lock_file = if options.packages.is_empty() {
+        None
+    } else {
+        for id in &options.packages {
+            if !orig_lock_file.packages.iter().any(|p| p.id == *id) {
+                bail!("package `{id}` is not a dependency in the component lock file");
+            }
+        }
+
+        let mut lock_file = orig_lock_file.clone();
+        lock_file
+            .packages
+            .retain(|p| !options.packages.contains(&p.id));
+        Some(lock_file)
+    };
+
+    let precise = options.precise.as_ref().map(|version| {
+        options
+            .packages
+            .iter()
+            .map(|id| (id.clone(), version.clone()))
+            .collect::<HashMap<_, _>>()
+    });
+
+    let map = create_resolution_map(
+        config,
+        packages,
+        lock_file.as_ref().map(LockFileResolver::new),
+        None,
+        precise.as_ref(),
+        network_allowed,
+    )
+    .await?;
+
+    let mut new_lock_file = map.to_lock_file();
+    new_lock_file.vendor = orig_lock_file.vendor.clone();
 
     for old_pkg in &orig_lock_file.packages {
         let new_pkg = match new_lock_file
@@ -814,6 +4118,28 @@ pub async fn update_lockfile(
         }
     }
 
+    for new_source in &new_lock_file.git {
+        let old_source = orig_lock_file
+            .git
+            .iter()
+            .find(|s| s.key() == new_source.key());
+
+        // The locked commit of the git repository has changed
+        if let Some(old_source) = old_source.filter(|s| s.commit != new_source.commit) {
+            config.terminal().status_with_color(
+                if dry_run { "Would update" } else { "Updating" },
+                format!(
+                    "git repository `{url}` ({reference}) {old} -> {new}",
+                    url = new_source.url,
+                    reference = new_source.reference,
+                    old = old_source.commit,
+                    new = new_source.commit
+                ),
+                Colors::Cyan,
+            )?;
+        }
+    }
+
     if dry_run {
         config
             .terminal()
@@ -1,27 +1,53 @@
 //! Module for component metadata representation in `Cargo.toml`.
 
+use crate::{config::DebugInfo, remap::Remapped};
 use anyhow::{bail, Context, Result};
-use cargo_component_core::registry::{Dependency, RegistryPackage};
+use cargo_component_core::{
+    registry::{Dependency, NamespaceRegistries, RegistryPackage},
+    signing::SigningKeyConfig,
+};
 use cargo_metadata::Package;
 use semver::{Version, VersionReq};
 use serde::{
     de::{self, value::MapAccessDeserializer},
     Deserialize,
 };
-use serde_json::from_value;
+use serde_json::{from_value, Value};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
     str::FromStr,
     time::SystemTime,
 };
 use url::Url;
+use warg_crypto::signing::PublicKey;
 use warg_protocol::registry::PackageId;
 
 /// The default directory to look for a target WIT file.
 pub const DEFAULT_WIT_DIR: &str = "wit";
 
+/// The key of the `package.metadata` list of the workspace members whose
+/// components a package depends on.
+///
+/// Unlike the `package.metadata.component` section, it may be specified by
+/// packages that are not components (e.g. hosts embedding a component).
+pub const ARTIFACTS_KEY: &str = "component-artifacts";
+
+/// Gets the names of the workspace members whose components the given
+/// package depends on as artifacts.
+pub fn artifact_dependencies(package: &Package) -> Result<Vec<String>> {
+    match package.metadata.get(ARTIFACTS_KEY) {
+        Some(value) => from_value(value.clone()).with_context(|| {
+            format!(
+                "failed to deserialize `package.metadata.{ARTIFACTS_KEY}` from `{path}`",
+                path = package.manifest_path
+            )
+        }),
+        None => Ok(Vec::new()),
+    }
+}
+
 /// The target of a component.
 ///
 /// The target defines the world of the component being developed.
@@ -40,6 +66,14 @@ pub enum Target {
         ///
         /// [select-world]: https://docs.rs/wit-parser/latest/wit_parser/struct.Resolve.html#method.select_world
         world: Option<String>,
+        /// The parts of the target world enabled by cargo features.
+        features: HashMap<String, TargetFeature>,
+        /// How the interfaces used by the exports of the target world are
+        /// provided, by interface name.
+        transitive: HashMap<String, TransitiveInterface>,
+        /// The additional named instances of interfaces to export, mapping
+        /// instance names to interface names.
+        instances: HashMap<String, String>,
     },
     /// The target is a world from a local wit document.
     Local {
@@ -47,6 +81,10 @@ pub enum Target {
         ///
         /// Defaults to the `wit` directory.
         path: Option<PathBuf>,
+        /// Additional WIT files, directories, or glob patterns (e.g.
+        /// `wit/**/*.wit` or `../common-wit`) whose WIT files are merged into
+        /// the target package.
+        include: Vec<PathBuf>,
         /// The name of the world being targeted.
         ///
         /// [Resolve::select_world][select-world] will be used
@@ -56,6 +94,14 @@ pub enum Target {
         world: Option<String>,
         /// The dependencies of the wit document being targeted.
         dependencies: HashMap<PackageId, Dependency>,
+        /// The parts of the target world enabled by cargo features.
+        features: HashMap<String, TargetFeature>,
+        /// How the interfaces used by the exports of the target world are
+        /// provided, by interface name.
+        transitive: HashMap<String, TransitiveInterface>,
+        /// The additional named instances of interfaces to export, mapping
+        /// instance names to interface names.
+        instances: HashMap<String, String>,
     },
 }
 
@@ -77,14 +123,96 @@ impl Target {
             Self::Package { world, .. } | Self::Local { world, .. } => world.as_deref(),
         }
     }
+
+    /// Gets the parts of the target world enabled by cargo features.
+    pub fn features(&self) -> &HashMap<String, TargetFeature> {
+        match self {
+            Self::Package { features, .. } | Self::Local { features, .. } => features,
+        }
+    }
+
+    /// Gets how the interfaces used by the exports of the target world are
+    /// provided, by interface name.
+    pub fn transitive(&self) -> &HashMap<String, TransitiveInterface> {
+        match self {
+            Self::Package { transitive, .. } | Self::Local { transitive, .. } => transitive,
+        }
+    }
+
+    /// Gets the additional WIT sources merged into a local target package.
+    pub fn include(&self) -> &[PathBuf] {
+        match self {
+            Self::Package { .. } => &[],
+            Self::Local { include, .. } => include,
+        }
+    }
+
+    /// Gets the additional named instances of interfaces to export, mapping
+    /// instance names to interface names.
+    pub fn instances(&self) -> &HashMap<String, String> {
+        match self {
+            Self::Package { instances, .. } | Self::Local { instances, .. } => instances,
+        }
+    }
+
+    /// Gets the names of the worlds to include in the target world for the
+    /// given set of enabled features.
+    ///
+    /// The worlds are returned in the order of their feature names.
+    pub fn included_worlds<'a>(&'a self, enabled: &HashSet<String>) -> Vec<(&'a str, &'a str)> {
+        let mut features: Vec<_> = self
+            .features()
+            .iter()
+            .filter(|(name, _)| enabled.contains(*name))
+            .collect();
+        features.sort_by(|a, b| a.0.cmp(b.0));
+
+        features
+            .into_iter()
+            .flat_map(|(name, feature)| {
+                feature
+                    .include
+                    .iter()
+                    .map(move |world| (name.as_str(), world.as_str()))
+            })
+            .collect()
+    }
+}
+
+/// Represents the parts of a target world enabled by a cargo feature.
+///
+/// These are specified in a `features.<name>` table of a target entry.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TargetFeature {
+    /// The names of the worlds in the target package to include in the
+    /// target world when the feature is enabled.
+    pub include: Vec<String>,
+}
+
+/// Represents how an interface used by the exports of a target world is
+/// provided.
+///
+/// These are specified in the `transitive` table of a target entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitiveInterface {
+    /// The interface is imported and referenced by its versioned name.
+    Use,
+    /// The interface is also exported by the component.
+    Export,
 }
 
 impl Default for Target {
     fn default() -> Self {
         Self::Local {
             path: None,
+            include: Vec::new(),
             world: None,
             dependencies: HashMap::new(),
+            features: HashMap::new(),
+            transitive: HashMap::new(),
+            instances: HashMap::new(),
         }
     }
 }
@@ -118,8 +246,12 @@ impl FromStr for Target {
                 id: None,
                 version,
                 registry: None,
+                interfaces: Vec::new(),
             },
             world,
+            features: HashMap::new(),
+            transitive: HashMap::new(),
+            instances: HashMap::new(),
         })
     }
 }
@@ -157,14 +289,21 @@ impl<'de> Deserialize<'de> for Target {
                     world: Option<String>,
                     registry: Option<String>,
                     path: Option<PathBuf>,
+                    include: Vec<PathBuf>,
                     dependencies: HashMap<PackageId, Dependency>,
+                    features: HashMap<String, TargetFeature>,
+                    transitive: HashMap<String, TransitiveInterface>,
+                    instances: HashMap<String, String>,
                 }
 
                 let entry = Entry::deserialize(MapAccessDeserializer::new(map))?;
 
                 match (entry.path, entry.package) {
                     (None, Some(package)) => {
-                        for (present, name) in [(!entry.dependencies.is_empty(), "dependencies")] {
+                        for (present, name) in [
+                            (!entry.dependencies.is_empty(), "dependencies"),
+                            (!entry.include.is_empty(), "include"),
+                        ] {
                             if present {
                                 return Err(de::Error::custom(
                                     format!("cannot specify both `{name}` and `package` fields in a target entry"),
@@ -180,8 +319,12 @@ impl<'de> Deserialize<'de> for Target {
                                     .version
                                     .ok_or_else(|| de::Error::missing_field("version"))?,
                                 registry: entry.registry,
+                                interfaces: Vec::new(),
                             },
                             world: entry.world,
+                            features: entry.features,
+                            transitive: entry.transitive,
+                            instances: entry.instances,
                         })
                     }
                     (path, None) => {
@@ -197,8 +340,12 @@ impl<'de> Deserialize<'de> for Target {
                         }
                         Ok(Target::Local {
                             path,
+                            include: entry.include,
                             world: entry.world,
                             dependencies: entry.dependencies,
+                            features: entry.features,
+                            transitive: entry.transitive,
+                            instances: entry.instances,
                         })
                     }
                     (Some(_), Some(_)) => Err(de::Error::custom(
@@ -212,6 +359,494 @@ impl<'de> Deserialize<'de> for Target {
     }
 }
 
+/// The WASI adapter used to componentize a core module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Adapter {
+    /// The built-in reactor adapter.
+    Reactor,
+    /// The built-in command adapter.
+    Command,
+    /// The adapter module at the given path.
+    Path(PathBuf),
+    /// The adapter module from a registry package.
+    ///
+    /// The resolved version and digest of the package are recorded in the
+    /// component lock file, so the adapter only changes when the version
+    /// requirement is changed or the lock file is updated.
+    Package {
+        /// The id of the adapter package.
+        id: PackageId,
+        /// The registry package of the adapter.
+        package: RegistryPackage,
+    },
+}
+
+impl FromStr for Adapter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "reactor" => Ok(Self::Reactor),
+            "command" => Ok(Self::Command),
+            "proxy" => bail!(
+                "there is no built-in `proxy` adapter; specify the path to a proxy adapter module instead"
+            ),
+            _ => Ok(Self::Path(s.into())),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Adapter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Adapter;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "a string or a table")
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Adapter::from_str(s).map_err(de::Error::custom)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                #[derive(Deserialize)]
+                #[serde(deny_unknown_fields)]
+                struct Entry {
+                    package: String,
+                    version: VersionReq,
+                    registry: Option<String>,
+                }
+
+                let entry = Entry::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(Adapter::Package {
+                    id: entry.package.parse().map_err(de::Error::custom)?,
+                    package: RegistryPackage {
+                        id: None,
+                        version: entry.version,
+                        registry: entry.registry,
+                        interfaces: Vec::new(),
+                    },
+                })
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// The optimization level of the `wasm-opt` pass run on a core module
+/// before it is componentized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptLevel {
+    /// The module is not optimized.
+    None,
+    /// Optimize with `-O1`.
+    O1,
+    /// Optimize with `-O2`.
+    O2,
+    /// Optimize with `-O3`.
+    O3,
+    /// Optimize with `-O4`.
+    O4,
+    /// Optimize for size with `-Os`.
+    Os,
+    /// Optimize aggressively for size with `-Oz`.
+    Oz,
+}
+
+impl OptLevel {
+    /// Gets the `wasm-opt` flag of the optimization level.
+    ///
+    /// Returns `None` if the module is not optimized.
+    pub fn flag(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::O1 => Some("-O1"),
+            Self::O2 => Some("-O2"),
+            Self::O3 => Some("-O3"),
+            Self::O4 => Some("-O4"),
+            Self::Os => Some("-Os"),
+            Self::Oz => Some("-Oz"),
+        }
+    }
+}
+
+impl FromStr for OptLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "0" | "none" => Ok(Self::None),
+            "1" => Ok(Self::O1),
+            "2" => Ok(Self::O2),
+            "3" => Ok(Self::O3),
+            "4" => Ok(Self::O4),
+            "s" => Ok(Self::Os),
+            "z" => Ok(Self::Oz),
+            _ => bail!(
+                "invalid optimization level `{s}`: expected a boolean or one of `0`, `1`, `2`, `3`, `4`, `s`, or `z`"
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OptLevel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = OptLevel;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "a boolean, an integer, or a string")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(if v { OptLevel::O2 } else { OptLevel::None })
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                OptLevel::from_str(&v.to_string()).map_err(de::Error::custom)
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                OptLevel::from_str(&v.to_string()).map_err(de::Error::custom)
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                OptLevel::from_str(s).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Represents component settings overridden for a build profile or binary target.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComponentOverrides {
+    /// The WASI adapter to use.
+    pub adapter: Option<Adapter>,
+    /// The optimization level of the core module.
+    ///
+    /// This is either a boolean or one of `0`, `1`, `2`, `3`, `4`, `s`, or
+    /// `z`; `true` is the same as `2`.
+    pub optimize: Option<OptLevel>,
+    /// What to do with the DWARF debug information of the component.
+    #[serde(rename = "debug-info")]
+    pub debug_info: Option<SectionPolicy>,
+    /// What to do with the name sections of the component.
+    #[serde(rename = "name-section")]
+    pub name_section: Option<SectionPolicy>,
+    /// What to do with the producers sections of the component.
+    pub producers: Option<SectionPolicy>,
+}
+
+/// Represents what to do with a kind of custom section of a component.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SectionPolicy {
+    /// The sections are kept in the component.
+    #[default]
+    Keep,
+    /// The sections are removed from the component.
+    Strip,
+    /// The sections are removed from the component, which references a
+    /// separate debug file that keeps them.
+    Split,
+}
+
+impl From<DebugInfo> for SectionPolicy {
+    fn from(debug_info: DebugInfo) -> Self {
+        match debug_info {
+            DebugInfo::Keep => Self::Keep,
+            DebugInfo::Strip => Self::Strip,
+            DebugInfo::Split => Self::Split,
+        }
+    }
+}
+
+/// Represents what to do with each kind of custom section of a component.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SectionPolicies {
+    /// The policy for DWARF debug information (`.debug_*` sections).
+    pub debug_info: SectionPolicy,
+    /// The policy for name sections (`name` and `component-name`).
+    pub name_section: SectionPolicy,
+    /// The policy for producers sections.
+    pub producers: SectionPolicy,
+}
+
+/// Represents the `package.metadata.component.raw` section in `Cargo.toml`.
+///
+/// These settings describe parts of the core module that are outside of the
+/// target world, for integrating with host-specific ABIs.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Raw {
+    /// The names of additional functions to export from the core module.
+    ///
+    /// Each function must be defined by the package (e.g. with
+    /// `#[no_mangle]`); it is exported by the core module embedded in the
+    /// component rather than by the component itself.
+    pub exports: Vec<String>,
+    /// The adapter modules that satisfy the imports of the core module from
+    /// other modules, by the name of the imported module.
+    pub adapters: BTreeMap<String, PathBuf>,
+    /// The custom sections to add to the component, by section name, with
+    /// the path to the contents of each.
+    #[serde(rename = "custom-sections")]
+    pub custom_sections: BTreeMap<String, PathBuf>,
+}
+
+/// Represents the `package.metadata.component.bindings` section in `Cargo.toml`.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bindings {
+    /// The name of a workspace crate to also generate the bindings of the
+    /// target world's imports into.
+    ///
+    /// Other crates of the workspace may depend on this crate to share one set
+    /// of bindings for the imports.
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    /// The ownership model to use for resources in the generated bindings.
+    ///
+    /// This is the default for the `ownership` option of the `generate!` macro.
+    pub ownership: Option<Ownership>,
+    /// Additional derive macros to add to the generated types.
+    ///
+    /// These are added to any given with the `additional_derives` option of
+    /// the `generate!` macro.
+    #[serde(rename = "additional-derives")]
+    pub additional_derives: Vec<String>,
+    /// Additional conveniences to generate for specific types, keyed by the
+    /// name of the type (e.g. `my:pkg/iface@1.0.0/point`, or `point` for a
+    /// type defined by the world itself).
+    pub types: BTreeMap<String, TypeOptions>,
+    /// The Rust modules to use for imported interfaces instead of generating
+    /// their bindings, keyed by the name of the interface (e.g.
+    /// `my:types/shapes`).
+    pub with: BTreeMap<String, String>,
+    /// Whether or not to generate `no_std` bindings, and the shims to
+    /// generate with them.
+    ///
+    /// This is either a boolean or a table of the shims.
+    #[serde(rename = "no-std", deserialize_with = "deserialize_no_std")]
+    pub no_std: Option<NoStd>,
+}
+
+impl Bindings {
+    /// Gets the contents of the options file that the `generate!` macro reads
+    /// its default options from.
+    ///
+    /// Each line of the file is a `<name>=<value>` pair; the given modules
+    /// are the remapped modules of the `with` section.
+    pub fn options_file(&self, remapped: &[Remapped]) -> String {
+        let mut contents = String::new();
+        if let Some(ownership) = self.ownership {
+            contents.push_str(&format!("ownership={ownership}\n"));
+        }
+
+        for derive in &self.additional_derives {
+            contents.push_str(&format!("additional-derive={derive}\n"));
+        }
+
+        for Remapped { module, path } in remapped {
+            contents.push_str(&format!(
+                "with={module}={path}\n",
+                module = module.join("::")
+            ));
+        }
+
+        if let Some(no_std) = &self.no_std {
+            contents.push_str("no-std=true\n");
+
+            if no_std.panic_handler {
+                contents.push_str("panic-handler=true\n");
+            }
+
+            if let Some(allocator) = &no_std.global_allocator {
+                contents.push_str(&format!("global-allocator={allocator}\n"));
+            }
+        }
+
+        contents
+    }
+}
+
+/// Represents the `package.metadata.component.generate` section in `Cargo.toml`.
+///
+/// The command generates WIT files (e.g. from an IDL) before the bindings of
+/// the component are generated; the files it writes to the directory in the
+/// `OUT_DIR` environment variable are merged into the local targets.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Generate {
+    /// The program to run followed by its arguments.
+    ///
+    /// This is either an array of the program and arguments or a string that
+    /// is split on whitespace. The command is run in the directory of the
+    /// manifest.
+    #[serde(deserialize_with = "deserialize_command")]
+    pub command: Vec<String>,
+    /// The files and directories the generated WIT files are created from.
+    ///
+    /// The command is only run again when one of these is modified; if there
+    /// are none, the command is run every time the bindings are generated.
+    #[serde(default)]
+    pub inputs: Vec<PathBuf>,
+}
+
+/// Deserializes a command given as a string or as an array of arguments.
+fn deserialize_command<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "a string or an array of strings")
+        }
+
+        fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(s.split_whitespace().map(ToString::to_string).collect())
+        }
+
+        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::SeqAccess<'de>,
+        {
+            Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+/// Represents the shims to generate with `no_std` bindings.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NoStd {
+    /// Whether or not to generate a `#[panic_handler]` that traps.
+    #[serde(rename = "panic-handler")]
+    pub panic_handler: bool,
+    /// The path of a unit struct implementing `GlobalAlloc` to register as
+    /// the `#[global_allocator]` (e.g. `crate::Allocator`).
+    #[serde(rename = "global-allocator")]
+    pub global_allocator: Option<String>,
+}
+
+fn deserialize_no_std<'de, D>(deserializer: D) -> Result<Option<NoStd>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Option<NoStd>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "a boolean or a table")
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(v.then(NoStd::default))
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            NoStd::deserialize(MapAccessDeserializer::new(map)).map(Some)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+/// Represents the conveniences to generate for a type in the bindings.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeOptions {
+    /// Whether or not to generate a builder for a record type.
+    pub builder: bool,
+    /// Whether or not to implement `Default` for the type.
+    pub default: bool,
+    /// Whether or not to implement `Display` and `std::error::Error` for the type.
+    pub error: bool,
+    /// Other versions of the type's interface to generate `From` or `TryFrom`
+    /// conversions to and from.
+    pub versions: Vec<String>,
+}
+
+impl TypeOptions {
+    /// Determines if any conveniences are enabled for the type.
+    pub fn is_empty(&self) -> bool {
+        !self.builder && !self.default && !self.error && self.versions.is_empty()
+    }
+}
+
+/// Represents the ownership model of resources in generated bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ownership {
+    /// Generated types own their contents, even for function parameters.
+    Owning,
+    /// Function parameters borrow their contents where possible.
+    Borrowing,
+    /// Like `borrowing`, but generates separate owned types where a type
+    /// would otherwise both borrow and own its contents.
+    BorrowingDuplicateIfNecessary,
+}
+
+impl std::fmt::Display for Ownership {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Owning => write!(f, "owning"),
+            Self::Borrowing => write!(f, "borrowing"),
+            Self::BorrowingDuplicateIfNecessary => write!(f, "borrowing-duplicate-if-necessary"),
+        }
+    }
+}
+
 /// Represents the `package.metadata.component` section in `Cargo.toml`.
 #[derive(Default, Debug, Clone, Deserialize)]
 #[serde(default, deny_unknown_fields)]
@@ -220,12 +855,150 @@ pub struct ComponentSection {
     pub package: Option<PackageId>,
     /// The world targeted by the component.
     pub target: Target,
-    /// The path to the WASI adapter to use.
-    pub adapter: Option<PathBuf>,
+    /// Additional named worlds targeted by the component.
+    ///
+    /// Bindings for a named target are generated with
+    /// `generate!({ target: "<name>" })`.
+    pub targets: HashMap<String, Target>,
+    /// The WASI adapter to use.
+    ///
+    /// This is either `reactor`, `command`, the path to an adapter module, or
+    /// a table specifying a registry package.
+    pub adapter: Option<Adapter>,
+    /// The settings for generating bindings.
+    pub bindings: Bindings,
+    /// The command generating WIT files to merge into the local targets.
+    pub generate: Option<Generate>,
+    /// The settings for specific build profiles (e.g. `release`).
+    pub profile: HashMap<String, ComponentOverrides>,
+    /// The settings for specific binary targets.
+    pub bin: HashMap<String, ComponentOverrides>,
     /// The dependencies of the component.
     pub dependencies: HashMap<PackageId, Dependency>,
+    /// The component dependencies used only when running tests or binaries.
+    ///
+    /// Unlike other dependencies, these do not add imports to the target
+    /// world; instead, the imports of the component that they export are
+    /// satisfied by composing them with the component before it is run.
+    #[serde(rename = "dev-dependencies")]
+    pub dev_dependencies: HashMap<PackageId, Dependency>,
+    /// The cargo features that enable the optional dependencies of the component.
+    ///
+    /// Dependencies not in this map are always enabled.
+    #[serde(skip)]
+    pub optional_dependencies: HashMap<PackageId, String>,
+    /// The names used for the component dependencies in the generated bindings.
+    ///
+    /// Dependencies not in this map are named after their package.
+    #[serde(skip)]
+    pub dependency_names: HashMap<PackageId, DependencyNames>,
+    /// The patches overriding registry packages.
+    ///
+    /// Every dependency on a patched registry package, including the target
+    /// package and adapter packages, is resolved with the patch instead.
+    pub patch: HashMap<PackageId, Dependency>,
     /// The registries to use for the component.
     pub registries: HashMap<String, Url>,
+    /// The names of the registries to use for packages in a namespace.
+    ///
+    /// Dependencies that specify a registry are not affected.
+    pub namespaces: HashMap<String, NamespaceRegistries>,
+    /// The additional versions to export the exported interfaces of the
+    /// target world at, by interface name without a version.
+    ///
+    /// The instance implementing each interface is also exported under the
+    /// name of each additional version.
+    #[serde(rename = "export-versions")]
+    pub export_versions: BTreeMap<String, Vec<Version>>,
+    /// The signing keys to use when publishing to a registry, by registry name.
+    ///
+    /// The default registry is named `default`.
+    #[serde(rename = "signing-keys")]
+    pub signing_keys: HashMap<String, SigningKeyConfig>,
+    /// The public keys trusted to sign registry packages, by package id or
+    /// namespace.
+    ///
+    /// Registry dependencies with trusted keys must have a signature embedded
+    /// by one of the keys; the keys of a package id take precedence over
+    /// those of its namespace.
+    #[serde(rename = "trusted-keys")]
+    pub trusted_keys: HashMap<String, Vec<String>>,
+    /// The default configuration values of the component.
+    ///
+    /// These are provided to the component through `wasi:config` when it is
+    /// run; values may be strings, numbers, or booleans.
+    #[serde(deserialize_with = "deserialize_config")]
+    pub config: BTreeMap<String, String>,
+    /// The raw core module exports, adapters, and custom sections of the component.
+    pub raw: Raw,
+}
+
+impl ComponentSection {
+    /// Gets the public keys trusted to sign the given registry package.
+    ///
+    /// Returns an empty list if no keys are trusted for the package.
+    pub fn trusted_keys(&self, id: &PackageId) -> Result<Vec<PublicKey>> {
+        let name: &str = id.as_ref();
+        let keys = match self
+            .trusted_keys
+            .get(name)
+            .or_else(|| self.trusted_keys.get(id.namespace()))
+        {
+            Some(keys) => keys,
+            None => return Ok(Vec::new()),
+        };
+
+        keys.iter()
+            .map(|k| {
+                k.parse()
+                    .with_context(|| format!("trusted key `{k}` for package `{id}` is invalid"))
+            })
+            .collect()
+    }
+}
+
+/// Deserializes the configuration values of a component as strings.
+fn deserialize_config<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let values = BTreeMap::<String, Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .map(|(key, value)| {
+            if key.is_empty() || key.contains('=') {
+                return Err(de::Error::custom(format!(
+                    "invalid configuration key `{key}`: keys must be non-empty and cannot contain `=`"
+                )));
+            }
+
+            let value = match value {
+                Value::String(s) => s,
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => {
+                    return Err(de::Error::custom(format!(
+                        "configuration value `{key}` must be a string, number, or boolean"
+                    )))
+                }
+            };
+
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Represents the names used for a component dependency in the generated bindings.
+///
+/// These are specified with the `module` and `interfaces` fields of a
+/// dependency entry so that the `bindings` module layout does not change
+/// when the package id of the dependency does.
+#[derive(Default, Debug, Clone)]
+pub struct DependencyNames {
+    /// The name to import the dependency as, in place of the package name.
+    pub module: Option<String>,
+    /// The names to import the exported interfaces of the dependency as.
+    pub interfaces: HashMap<String, String>,
 }
 
 /// Represents cargo metadata for a WebAssembly component.
@@ -241,6 +1014,11 @@ pub struct ComponentMetadata {
     pub modified_at: SystemTime,
     /// The component section in `Cargo.toml`.
     pub section: ComponentSection,
+    /// The directory the `generate` command writes WIT files to.
+    ///
+    /// This is `None` if the package has no `generate` command or if the
+    /// metadata was not loaded with the target directory of the workspace.
+    pub generated_dir: Option<PathBuf>,
 }
 
 impl ComponentMetadata {
@@ -254,12 +1032,34 @@ impl ComponentMetadata {
         );
 
         let mut section: ComponentSection = match package.metadata.get("component").cloned() {
-            Some(component) => from_value(component).with_context(|| {
-                format!(
-                    "failed to deserialize component metadata from `{path}`",
-                    path = package.manifest_path
-                )
-            })?,
+            Some(mut component) => {
+                let optional_dependencies = take_optional_dependencies(&mut component)
+                    .with_context(|| {
+                        format!(
+                            "failed to deserialize component metadata from `{path}`",
+                            path = package.manifest_path
+                        )
+                    })?;
+
+                let dependency_names =
+                    take_dependency_names(&mut component).with_context(|| {
+                        format!(
+                            "failed to deserialize component metadata from `{path}`",
+                            path = package.manifest_path
+                        )
+                    })?;
+
+                let mut section: ComponentSection = from_value(component).with_context(|| {
+                    format!(
+                        "failed to deserialize component metadata from `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
+
+                section.optional_dependencies = optional_dependencies;
+                section.dependency_names = dependency_names;
+                section
+            }
             None => {
                 log::debug!(
                     "manifest `{path}` has no component metadata",
@@ -281,30 +1081,171 @@ impl ComponentMetadata {
             })?;
         let modified_at = crate::last_modified_time(package.manifest_path.as_std_path())?;
 
-        // Make all paths stored in the metadata relative to the manifest directory.
-        if let Target::Local {
-            path, dependencies, ..
-        } = &mut section.target
+        for name in section.targets.keys() {
+            wit_parser::validate_id(name).with_context(|| {
+                format!(
+                    "invalid target name `{name}` in manifest `{path}`",
+                    path = package.manifest_path
+                )
+            })?;
+        }
+
+        for (id, feature) in &section.optional_dependencies {
+            if !package.features.contains_key(feature) {
+                bail!(
+                    "optional dependency `{id}` is enabled by feature `{feature}`, which is not \
+                     defined in the `[features]` table of manifest `{path}`",
+                    path = package.manifest_path
+                );
+            }
+        }
+
+        for (name, target) in std::iter::once((None, &section.target))
+            .chain(section.targets.iter().map(|(n, t)| (Some(n), t)))
         {
-            if let Some(path) = path {
-                *path = manifest_dir.join(path.as_path());
+            for (feature, gated) in target.features() {
+                if !package.features.contains_key(feature) {
+                    bail!(
+                        "{target} has parts enabled by feature `{feature}`, which is not defined \
+                         in the `[features]` table of manifest `{path}`",
+                        target = match name {
+                            Some(name) => format!("target `{name}`"),
+                            None => "the target".to_string(),
+                        },
+                        path = package.manifest_path
+                    );
+                }
+
+                for world in &gated.include {
+                    wit_parser::validate_id(world).with_context(|| {
+                        format!(
+                            "invalid world name `{world}` for feature `{feature}` in manifest `{path}`",
+                            path = package.manifest_path
+                        )
+                    })?;
+                }
             }
+        }
 
-            for dependency in dependencies.values_mut() {
-                if let Dependency::Local(path) = dependency {
+        // Make all paths stored in the metadata relative to the manifest directory.
+        for target in std::iter::once(&mut section.target).chain(section.targets.values_mut()) {
+            if let Target::Local {
+                path,
+                include,
+                dependencies,
+                ..
+            } = target
+            {
+                if let Some(path) = path {
+                    *path = manifest_dir.join(path.as_path());
+                }
+
+                for path in include {
                     *path = manifest_dir.join(path.as_path());
                 }
+
+                for dependency in dependencies.values_mut() {
+                    if let Dependency::Local(path) = dependency {
+                        *path = manifest_dir.join(path.as_path());
+                    }
+                }
+            }
+        }
+
+        for id in section.dev_dependencies.keys() {
+            if section.dependencies.contains_key(id) {
+                bail!(
+                    "dependency `{id}` cannot be both a dependency and a dev-dependency in manifest `{path}`",
+                    path = package.manifest_path
+                );
             }
         }
 
-        for dependency in section.dependencies.values_mut() {
+        for dependency in section
+            .dependencies
+            .values_mut()
+            .chain(section.dev_dependencies.values_mut())
+            .chain(section.patch.values_mut())
+        {
             if let Dependency::Local(path) = dependency {
                 *path = manifest_dir.join(path.as_path());
             }
         }
 
-        if let Some(adapter) = section.adapter.as_mut() {
-            *adapter = manifest_dir.join(adapter.as_path());
+        for interface in section.export_versions.keys() {
+            let valid = match interface.split_once('/') {
+                Some((package, name)) => {
+                    package.contains(':') && !name.is_empty() && !interface.contains('@')
+                }
+                None => false,
+            };
+
+            if !valid {
+                bail!(
+                    "invalid interface name `{interface}` in `export-versions` of manifest `{path}`: expected `namespace:package/interface` without a version",
+                    path = package.manifest_path
+                );
+            }
+        }
+
+        if let Some(generate) = &mut section.generate {
+            if generate.command.first().map_or(true, |p| p.is_empty()) {
+                bail!(
+                    "the `generate` command in manifest `{path}` is empty",
+                    path = package.manifest_path
+                );
+            }
+
+            if !std::iter::once(&section.target)
+                .chain(section.targets.values())
+                .any(|t| matches!(t, Target::Local { .. }))
+            {
+                bail!(
+                    "the WIT files of the `generate` command in manifest `{path}` can only be merged into a local target",
+                    path = package.manifest_path
+                );
+            }
+
+            for path in &mut generate.inputs {
+                *path = manifest_dir.join(path.as_path());
+            }
+        }
+
+        for key in section.signing_keys.values_mut() {
+            for path in [&mut key.key_file, &mut key.keystore].into_iter().flatten() {
+                *path = manifest_dir.join(path.as_path());
+            }
+        }
+
+        for path in section
+            .raw
+            .adapters
+            .values_mut()
+            .chain(section.raw.custom_sections.values_mut())
+        {
+            *path = manifest_dir.join(path.as_path());
+        }
+
+        for name in section.bin.keys() {
+            if !package
+                .targets
+                .iter()
+                .any(|t| t.is_bin() && t.name == *name)
+            {
+                bail!(
+                    "binary target `{name}` in the component metadata of manifest `{path}` does not exist",
+                    path = package.manifest_path
+                );
+            }
+        }
+
+        for adapter in std::iter::once(&mut section.adapter)
+            .chain(section.profile.values_mut().map(|o| &mut o.adapter))
+            .chain(section.bin.values_mut().map(|o| &mut o.adapter))
+        {
+            if let Some(Adapter::Path(path)) = adapter {
+                *path = manifest_dir.join(path.as_path());
+            }
         }
 
         Ok(Some(Self {
@@ -313,15 +1254,149 @@ impl ComponentMetadata {
             manifest_path: package.manifest_path.clone().into(),
             modified_at,
             section,
+            generated_dir: None,
         }))
     }
 
-    /// Gets the path to a local target.
+    /// Determines if the given component dependency is enabled.
+    ///
+    /// Optional dependencies are only enabled when their feature is
+    /// in the given set of enabled features.
+    pub fn is_dependency_enabled(&self, id: &PackageId, features: &HashSet<String>) -> bool {
+        self.section
+            .optional_dependencies
+            .get(id)
+            .map(|feature| features.contains(feature))
+            .unwrap_or(true)
+    }
+
+    /// Gets the name the given component dependency is imported as in the
+    /// generated bindings.
+    ///
+    /// This is the `module` field of the dependency, if present; otherwise it
+    /// is the name of the dependency's package.
+    pub fn dependency_module<'b>(&'b self, id: &'b PackageId) -> &'b str {
+        self.section
+            .dependency_names
+            .get(id)
+            .and_then(|n| n.module.as_deref())
+            .unwrap_or_else(|| id.name())
+    }
+
+    /// Gets the WASI adapter to use for the given build profile and binary target.
+    ///
+    /// The adapter of a binary target takes precedence over the adapter of a
+    /// profile, which takes precedence over the adapter of the package.
+    ///
+    /// Returns `None` if the adapter should be inferred from the output.
+    pub fn adapter(&self, profile: &str, bin: Option<&str>) -> Option<&Adapter> {
+        bin.and_then(|name| self.section.bin.get(name))
+            .and_then(|o| o.adapter.as_ref())
+            .or_else(|| {
+                self.section
+                    .profile
+                    .get(profile)
+                    .and_then(|o| o.adapter.as_ref())
+            })
+            .or(self.section.adapter.as_ref())
+    }
+
+    /// Gets the optimization level of the core module for the given build
+    /// profile and binary target.
+    ///
+    /// The level of a binary target takes precedence over the level of a
+    /// profile. Returns the level and whether it was explicitly specified;
+    /// the `release` profile defaults to [`OptLevel::O2`] and other profiles
+    /// are not optimized by default.
+    pub fn optimize(&self, profile: &str, bin: Option<&str>) -> (OptLevel, bool) {
+        match bin
+            .and_then(|name| self.section.bin.get(name))
+            .and_then(|o| o.optimize)
+            .or_else(|| self.section.profile.get(profile).and_then(|o| o.optimize))
+        {
+            Some(level) => (level, true),
+            None if profile == "release" => (OptLevel::O2, false),
+            None => (OptLevel::None, false),
+        }
+    }
+
+    /// Gets what to do with the DWARF debug information of the component for
+    /// the given build profile and binary target.
+    ///
+    /// Returns `None` if it is not explicitly specified, in which case the
+    /// debug information is not preserved when the core module is optimized.
+    pub fn debug_info(&self, profile: &str, bin: Option<&str>) -> Option<SectionPolicy> {
+        bin.and_then(|name| self.section.bin.get(name))
+            .and_then(|o| o.debug_info)
+            .or_else(|| self.section.profile.get(profile).and_then(|o| o.debug_info))
+    }
+
+    /// Gets what to do with the custom sections of the component for the
+    /// given build profile and binary target.
+    ///
+    /// The settings of a binary target take precedence over the settings of
+    /// a profile; every section is kept by default.
+    pub fn section_policies(&self, profile: &str, bin: Option<&str>) -> SectionPolicies {
+        let overrides = [
+            bin.and_then(|name| self.section.bin.get(name)),
+            self.section.profile.get(profile),
+        ];
+        let policy = |f: fn(&ComponentOverrides) -> Option<SectionPolicy>| {
+            overrides
+                .iter()
+                .flatten()
+                .find_map(|o| f(o))
+                .unwrap_or_default()
+        };
+
+        SectionPolicies {
+            debug_info: self.debug_info(profile, bin).unwrap_or_default(),
+            name_section: policy(|o| o.name_section),
+            producers: policy(|o| o.producers),
+        }
+    }
+
+    /// Gets the registry packages of the adapters of the package, profiles,
+    /// and binary targets.
+    pub fn adapter_packages(&self) -> impl Iterator<Item = (&PackageId, &RegistryPackage)> {
+        std::iter::once(&self.section.adapter)
+            .chain(self.section.profile.values().map(|o| &o.adapter))
+            .chain(self.section.bin.values().map(|o| &o.adapter))
+            .filter_map(|adapter| match adapter {
+                Some(Adapter::Package { id, package }) => Some((id, package)),
+                _ => None,
+            })
+    }
+
+    /// Gets the target with the given name.
+    ///
+    /// If `name` is `None`, the default target is returned.
+    pub fn target(&self, name: Option<&str>) -> Result<&Target> {
+        match name {
+            Some(name) => self.section.targets.get(name).with_context(|| {
+                format!(
+                    "package `{package}` does not have a target named `{name}`",
+                    package = self.name
+                )
+            }),
+            None => Ok(&self.section.target),
+        }
+    }
+
+    /// Gets the path to the default local target.
     ///
     /// Returns `None` if the target is a registry package or
     /// if a path is not specified and the default path does not exist.
     pub fn target_path(&self) -> Option<Cow<Path>> {
-        match &self.section.target {
+        self.local_target_path(&self.section.target)
+    }
+
+    /// Gets the path to the given local target.
+    ///
+    /// Returns `None` if the target is a registry package or
+    /// if a path is not specified and the default path does not exist.
+    pub fn local_target_path<'a>(&self, target: &'a Target) -> Option<Cow<'a, Path>> {
+        match target {
             Target::Local {
                 path: Some(path), ..
             } => Some(path.into()),
@@ -338,3 +1413,113 @@ impl ComponentMetadata {
         }
     }
 }
+
+/// Removes the `optional` and `feature` fields from the component dependency
+/// entries, returning the cargo feature that enables each optional dependency.
+///
+/// The feature of an optional dependency defaults to the name of the package.
+fn take_optional_dependencies(component: &mut Value) -> Result<HashMap<PackageId, String>> {
+    let mut optional = HashMap::new();
+    let Some(dependencies) = component
+        .get_mut("dependencies")
+        .and_then(Value::as_object_mut)
+    else {
+        return Ok(optional);
+    };
+
+    for (name, dependency) in dependencies.iter_mut() {
+        let Some(entry) = dependency.as_object_mut() else {
+            continue;
+        };
+
+        let is_optional = match entry.remove("optional") {
+            Some(Value::Bool(optional)) => optional,
+            Some(_) => bail!("the `optional` field of dependency `{name}` must be a boolean"),
+            None => false,
+        };
+
+        let feature = match entry.remove("feature") {
+            Some(Value::String(feature)) => Some(feature),
+            Some(_) => bail!("the `feature` field of dependency `{name}` must be a string"),
+            None => None,
+        };
+
+        if !is_optional {
+            if feature.is_some() {
+                bail!("dependency `{name}` specifies a `feature` field but is not optional");
+            }
+
+            continue;
+        }
+
+        let id: PackageId = name
+            .parse()
+            .with_context(|| format!("invalid dependency name `{name}`"))?;
+        let feature = feature.unwrap_or_else(|| id.name().to_string());
+        optional.insert(id, feature);
+    }
+
+    Ok(optional)
+}
+
+/// Removes the `module` and `interfaces` fields from the component dependency
+/// entries, returning the names to use for each dependency in the generated
+/// bindings.
+fn take_dependency_names(component: &mut Value) -> Result<HashMap<PackageId, DependencyNames>> {
+    let mut names = HashMap::new();
+    let Some(dependencies) = component
+        .get_mut("dependencies")
+        .and_then(Value::as_object_mut)
+    else {
+        return Ok(names);
+    };
+
+    for (name, dependency) in dependencies.iter_mut() {
+        let Some(entry) = dependency.as_object_mut() else {
+            continue;
+        };
+
+        let module = match entry.remove("module") {
+            Some(Value::String(module)) => {
+                wit_parser::validate_id(&module).with_context(|| {
+                    format!("invalid module name `{module}` for dependency `{name}`")
+                })?;
+                Some(module)
+            }
+            Some(_) => bail!("the `module` field of dependency `{name}` must be a string"),
+            None => None,
+        };
+
+        let interfaces = match entry.remove("interfaces") {
+            Some(Value::Object(map)) => map
+                .into_iter()
+                .map(|(interface, value)| match value {
+                    Value::String(renamed) => {
+                        wit_parser::validate_id(&renamed).with_context(|| {
+                            format!(
+                                "invalid name `{renamed}` for interface `{interface}` of dependency `{name}`"
+                            )
+                        })?;
+                        Ok((interface, renamed))
+                    }
+                    _ => bail!(
+                        "the name for interface `{interface}` of dependency `{name}` must be a string"
+                    ),
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => bail!("the `interfaces` field of dependency `{name}` must be a table"),
+            None => HashMap::new(),
+        };
+
+        if module.is_none() && interfaces.is_empty() {
+            continue;
+        }
+
+        let id: PackageId = name
+            .parse()
+            .with_context(|| format!("invalid dependency name `{name}`"))?;
+        names.insert(id, DependencyNames { module, interfaces });
+    }
+
+    Ok(names)
+}
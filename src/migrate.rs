@@ -0,0 +1,714 @@
+//! Module for migrating `wit-bindgen` projects to `cargo-component`.
+//!
+//! The `wit_bindgen::generate!` invocations of a crate are rewritten to the
+//! `cargo_component_bindings::generate!` macro and the options of the
+//! invocation (the target world and its WIT location) are moved to the
+//! component metadata of the package's manifest.
+//!
+//! The macro invocations are found textually rather than with a full parse;
+//! invocations that are expanded from other macros are not rewritten.
+
+use crate::{commands::escape_wit, stub::find_sources};
+use anyhow::{bail, Context, Result};
+use cargo_metadata::Package;
+use heck::ToKebabCase;
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{table, value, Array, Document, InlineTable, Item, Table, Value};
+use wit_parser::UnresolvedPackage;
+
+/// The name of the bindings crate dependency.
+const BINDINGS_CRATE_NAME: &str = "cargo-component-bindings";
+
+/// The name of the `wit-bindgen` crate dependency.
+const WIT_BINDGEN_CRATE_NAME: &str = "wit-bindgen";
+
+/// The path of the `wit-bindgen` macro.
+const WIT_BINDGEN_MACRO: &str = "wit_bindgen::generate!";
+
+/// The default directory of the WIT of a `wit-bindgen` project.
+const DEFAULT_WIT_DIR: &str = "wit";
+
+/// Represents the result of migrating a package.
+#[derive(Default)]
+pub struct Migration {
+    /// The source files that were rewritten.
+    pub sources: Vec<PathBuf>,
+    /// The WIT file created from an inline WIT document, if any.
+    pub wit: Option<PathBuf>,
+    /// Whether the `.gitignore` file of the package was updated.
+    pub gitignore: bool,
+    /// The warnings about options that could not be migrated.
+    pub warnings: Vec<String>,
+}
+
+/// A lexical token of a macro invocation.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+    Group(char, Vec<Token>),
+}
+
+/// The options of a `wit_bindgen::generate!` invocation.
+#[derive(Default)]
+struct Options {
+    world: Option<String>,
+    path: Option<String>,
+    inline: Option<String>,
+    implementor: Option<String>,
+    resources: Vec<(String, String)>,
+    ownership: Option<&'static str>,
+    additional_derives: Vec<String>,
+}
+
+/// A `wit_bindgen::generate!` invocation found in a source file.
+struct Invocation {
+    path: PathBuf,
+    start: usize,
+    end: usize,
+    options: Options,
+}
+
+/// Migrates the given package from `wit-bindgen` to `cargo-component`.
+pub fn migrate(package: &Package) -> Result<Migration> {
+    if package.metadata.get("component").is_some() {
+        bail!(
+            "package `{name}` already has component metadata in manifest `{path}`",
+            name = package.name,
+            path = package.manifest_path
+        );
+    }
+
+    let manifest_dir = package
+        .manifest_path
+        .parent()
+        .context("manifest path has no parent directory")?
+        .as_std_path();
+
+    let mut migration = Migration::default();
+    let invocations = find_invocations(package, &mut migration.warnings)?;
+    if invocations.len() > 1 {
+        bail!(
+            "package `{name}` invokes `{WIT_BINDGEN_MACRO}` more than once; only packages with a single invocation can be migrated",
+            name = package.name
+        );
+    }
+
+    let options = invocations
+        .first()
+        .map(|i| &i.options)
+        .unwrap_or(&Options::DEFAULT);
+
+    // Inline WIT is moved to the default WIT directory
+    if let Some(inline) = &options.inline {
+        if options.path.is_some() {
+            bail!("the `inline` and `path` options of `{WIT_BINDGEN_MACRO}` cannot be migrated together");
+        }
+
+        let path = manifest_dir.join(DEFAULT_WIT_DIR).join("world.wit");
+        if path.exists() {
+            bail!(
+                "cannot move the inline WIT of `{WIT_BINDGEN_MACRO}` to `{path}` as it already exists",
+                path = path.display()
+            );
+        }
+
+        fs::create_dir_all(path.parent().unwrap()).with_context(|| {
+            format!(
+                "failed to create directory `{path}`",
+                path = path.parent().unwrap().display()
+            )
+        })?;
+        fs::write(&path, format!("{inline}\n"))
+            .with_context(|| format!("failed to write WIT file `{path}`", path = path.display()))?;
+        migration.wit = Some(path);
+    }
+
+    update_manifest(package, manifest_dir, options, !invocations.is_empty())?;
+
+    for invocation in &invocations {
+        let source = fs::read_to_string(&invocation.path).with_context(|| {
+            format!(
+                "failed to read source file `{path}`",
+                path = invocation.path.display()
+            )
+        })?;
+
+        let mut rewritten = String::with_capacity(source.len());
+        rewritten.push_str(&source[..invocation.start]);
+        rewritten.push_str(&generate_invocation(&invocation.options));
+        rewritten.push_str(&source[invocation.end..]);
+
+        fs::write(&invocation.path, rewritten).with_context(|| {
+            format!(
+                "failed to write source file `{path}`",
+                path = invocation.path.display()
+            )
+        })?;
+
+        migration.sources.push(invocation.path.clone());
+    }
+
+    migration.gitignore = update_gitignore(manifest_dir)?;
+    Ok(migration)
+}
+
+impl Options {
+    const DEFAULT: Self = Self {
+        world: None,
+        path: None,
+        inline: None,
+        implementor: None,
+        resources: Vec::new(),
+        ownership: None,
+        additional_derives: Vec::new(),
+    };
+}
+
+/// Finds the `wit_bindgen::generate!` invocations in the sources of a package.
+fn find_invocations(package: &Package, warnings: &mut Vec<String>) -> Result<Vec<Invocation>> {
+    let mut paths = Vec::new();
+    let mut dirs: Vec<_> = package
+        .targets
+        .iter()
+        .filter_map(|t| t.src_path.parent().map(|p| p.as_std_path().to_path_buf()))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        if dir.is_dir() {
+            find_sources(&dir, &mut paths)?;
+        }
+    }
+    paths.sort();
+    paths.dedup();
+
+    let mut invocations = Vec::new();
+    for path in paths {
+        let source = fs::read_to_string(&path).with_context(|| {
+            format!("failed to read source file `{path}`", path = path.display())
+        })?;
+
+        let mut offset = 0;
+        while let Some(start) = source[offset..].find(WIT_BINDGEN_MACRO) {
+            let start = offset + start;
+            let (tokens, end) = parse_invocation(&source, start + WIT_BINDGEN_MACRO.len())
+                .with_context(|| {
+                    format!(
+                        "failed to parse `{WIT_BINDGEN_MACRO}` invocation in `{path}`",
+                        path = path.display()
+                    )
+                })?;
+
+            let options = parse_options(&tokens, warnings).with_context(|| {
+                format!(
+                    "failed to parse the options of `{WIT_BINDGEN_MACRO}` in `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            invocations.push(Invocation {
+                path: path.clone(),
+                start,
+                end,
+                options,
+            });
+            offset = end;
+        }
+    }
+
+    Ok(invocations)
+}
+
+/// Parses the tokens of a macro invocation starting at the given offset,
+/// returning the tokens and the end offset of the invocation.
+///
+/// The end offset includes a trailing semicolon, if any.
+fn parse_invocation(source: &str, offset: usize) -> Result<(Vec<Token>, usize)> {
+    let mut lexer = Lexer {
+        source,
+        pos: offset,
+    };
+    lexer.skip_trivia();
+    let open = lexer
+        .next_char()
+        .context("unexpected end of macro invocation")?;
+    let close = match open {
+        '(' => ')',
+        '{' => '}',
+        '[' => ']',
+        c => bail!("expected a delimiter for the macro invocation, found `{c}`"),
+    };
+
+    let tokens = lexer.tokens(Some(close))?;
+
+    // Include the semicolon terminating the statement, if there is one
+    let end = lexer.pos;
+    lexer.skip_trivia();
+    if lexer.peek_char() == Some(';') {
+        lexer.next_char();
+        return Ok((tokens, lexer.pos));
+    }
+
+    Ok((tokens, end))
+}
+
+/// A lexer for the tokens of a macro invocation.
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Lexer<'_> {
+    fn peek_char(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = &self.source[self.pos..];
+            if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                self.pos += rest.find("*/").map(|i| i + 2).unwrap_or(rest.len());
+            } else if let Some(c) = self.peek_char().filter(|c| c.is_whitespace()) {
+                self.pos += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn tokens(&mut self, close: Option<char>) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_trivia();
+            let c = match self.next_char() {
+                Some(c) => c,
+                None if close.is_none() => return Ok(tokens),
+                None => bail!("unexpected end of input: expected `{}`", close.unwrap()),
+            };
+
+            match c {
+                c if Some(c) == close => return Ok(tokens),
+                '(' | '{' | '[' => {
+                    let close = match c {
+                        '(' => ')',
+                        '{' => '}',
+                        _ => ']',
+                    };
+                    tokens.push(Token::Group(c, self.tokens(Some(close))?));
+                }
+                ')' | '}' | ']' => bail!("unexpected `{c}`"),
+                '"' => tokens.push(Token::Str(self.string()?)),
+                'r' if matches!(self.peek_char(), Some('"') | Some('#')) => {
+                    tokens.push(Token::Str(self.raw_string()?))
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let start = self.pos - c.len_utf8();
+                    while let Some(c) = self.peek_char() {
+                        if !c.is_alphanumeric() && c != '_' {
+                            break;
+                        }
+                        self.pos += c.len_utf8();
+                    }
+                    tokens.push(Token::Ident(self.source[start..self.pos].to_string()));
+                }
+                c => tokens.push(Token::Punct(c)),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let mut s = String::new();
+        loop {
+            match self.next_char().context("unterminated string literal")? {
+                '"' => return Ok(s),
+                '\\' => match self.next_char().context("unterminated string literal")? {
+                    'n' => s.push('\n'),
+                    't' => s.push('\t'),
+                    'r' => s.push('\r'),
+                    '0' => s.push('\0'),
+                    '\\' => s.push('\\'),
+                    '"' => s.push('"'),
+                    '\'' => s.push('\''),
+                    '\n' => self.skip_trivia(),
+                    c => bail!("unsupported escape sequence `\\{c}` in string literal"),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn raw_string(&mut self) -> Result<String> {
+        let mut hashes = 0;
+        while self.peek_char() == Some('#') {
+            self.next_char();
+            hashes += 1;
+        }
+
+        if self.next_char() != Some('"') {
+            bail!("invalid raw string literal");
+        }
+
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .find(&terminator)
+            .context("unterminated raw string literal")?;
+        self.pos += len + terminator.len();
+        Ok(rest[..len].to_string())
+    }
+}
+
+/// Parses the options of a `wit_bindgen::generate!` invocation.
+fn parse_options(tokens: &[Token], warnings: &mut Vec<String>) -> Result<Options> {
+    let mut options = Options::default();
+    let entries = match tokens {
+        [] => return Ok(options),
+        [Token::Str(world)] => {
+            options.world = Some(world.clone());
+            return Ok(options);
+        }
+        [Token::Group('{', tokens)] => tokens,
+        _ => bail!("unsupported macro input"),
+    };
+
+    for entry in entries.split(|t| *t == Token::Punct(',')) {
+        let (key, value) = match entry {
+            [] => continue,
+            [Token::Ident(key), Token::Punct(':'), value @ ..] => (key.as_str(), value),
+            _ => bail!("expected an option of the form `<name>: <value>`"),
+        };
+
+        match (key, value) {
+            ("world", [Token::Str(world)]) => options.world = Some(world.clone()),
+            ("path", [Token::Str(path)]) => options.path = Some(path.clone()),
+            ("inline", [Token::Str(inline)]) => options.inline = Some(inline.clone()),
+            ("exports", [Token::Group('{', exports)]) => {
+                for export in exports.split(|t| *t == Token::Punct(',')) {
+                    let (name, ty) = match export {
+                        [] => continue,
+                        [Token::Ident(name), Token::Punct(':'), ty @ ..] if name == "world" => {
+                            (None, render(ty))
+                        }
+                        [Token::Str(name), Token::Punct(':'), ty @ ..] => {
+                            (Some(name.clone()), render(ty))
+                        }
+                        _ => bail!("expected an export of the form `<name>: <type>`"),
+                    };
+
+                    match name {
+                        // Resources are named `<interface>/<resource>`
+                        Some(name) if name.matches('/').count() > 1 => {
+                            options.resources.push((name, ty))
+                        }
+                        _ => match &options.implementor {
+                            Some(implementor) if *implementor != ty => warnings.push(format!(
+                                "exports implemented by `{ty}` must be implemented by `{implementor}` instead, as `cargo-component` uses one implementor for the exports of a world"
+                            )),
+                            Some(_) => {}
+                            None => options.implementor = Some(ty),
+                        },
+                    }
+                }
+            }
+            ("ownership", [Token::Ident(ownership)]) if ownership == "Owning" => {
+                options.ownership = Some("owning")
+            }
+            ("ownership", [Token::Ident(ownership), rest @ ..]) if ownership == "Borrowing" => {
+                let duplicate = matches!(
+                    rest,
+                    [Token::Group('{', fields)] if render(fields).replace(' ', "") == "duplicate_if_necessary:true"
+                );
+                options.ownership = Some(if duplicate {
+                    "borrowing-duplicate-if-necessary"
+                } else {
+                    "borrowing"
+                });
+            }
+            ("additional_derives", [Token::Group('[', derives)]) => {
+                options.additional_derives.extend(
+                    derives
+                        .split(|t| *t == Token::Punct(','))
+                        .filter(|d| !d.is_empty())
+                        .map(render),
+                );
+            }
+            (key, _) => warnings.push(format!(
+                "option `{key}` of `{WIT_BINDGEN_MACRO}` is not supported by `cargo-component` and was removed"
+            )),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Renders tokens back to source.
+fn render(tokens: &[Token]) -> String {
+    let mut s = String::new();
+    for token in tokens {
+        match token {
+            Token::Ident(ident) => {
+                if s.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    s.push(' ');
+                }
+                s.push_str(ident);
+            }
+            Token::Str(string) => write!(s, "{string:?}").unwrap(),
+            Token::Punct(c) => s.push(*c),
+            Token::Group(open, tokens) => {
+                let close = match open {
+                    '(' => ')',
+                    '{' => '}',
+                    _ => ']',
+                };
+                write!(s, "{open}{inner}{close}", inner = render(tokens)).unwrap();
+            }
+        }
+    }
+    s
+}
+
+/// Generates the `cargo_component_bindings::generate!` invocation for the
+/// given options.
+///
+/// The bindings of `wit-bindgen` are generated in place whereas those of
+/// `cargo-component` are generated into a `bindings` module, so the module
+/// is glob-imported to keep existing paths working.
+fn generate_invocation(options: &Options) -> String {
+    let mut fields = Vec::new();
+    if let Some(implementor) = &options.implementor {
+        fields.push(format!("    implementor: {implementor},\n"));
+    }
+
+    if !options.resources.is_empty() {
+        let mut resources = String::from("    resources: {\n");
+        for (name, ty) in &options.resources {
+            writeln!(resources, "        {name:?}: {ty},").unwrap();
+        }
+        resources.push_str("    },\n");
+        fields.push(resources);
+    }
+
+    if let Some(ownership) = options.ownership {
+        fields.push(format!("    ownership: {ownership:?},\n"));
+    }
+
+    if !options.additional_derives.is_empty() {
+        fields.push(format!(
+            "    additional_derives: [{derives}],\n",
+            derives = options.additional_derives.join(", ")
+        ));
+    }
+
+    let invocation = if fields.is_empty() {
+        "cargo_component_bindings::generate!();".to_string()
+    } else {
+        format!(
+            "cargo_component_bindings::generate!({{\n{fields}}});",
+            fields = fields.concat()
+        )
+    };
+
+    format!("{invocation}\n\nuse bindings::*;")
+}
+
+/// Adds the component metadata to the manifest of a package.
+fn update_manifest(
+    package: &Package,
+    manifest_dir: &Path,
+    options: &Options,
+    has_invocation: bool,
+) -> Result<()> {
+    let manifest_path = &package.manifest_path;
+    let manifest = fs::read_to_string(manifest_path)
+        .with_context(|| format!("failed to read manifest file `{manifest_path}`"))?;
+    let mut doc: Document = manifest
+        .parse()
+        .with_context(|| format!("failed to parse manifest file `{manifest_path}`"))?;
+
+    // Libraries are componentized from their `cdylib`
+    if package
+        .targets
+        .iter()
+        .any(|t| t.kind.iter().any(|k| k == "lib" || k == "cdylib"))
+    {
+        let crate_types = doc["lib"]["crate-type"].or_insert(value(Array::new()));
+        let crate_types = crate_types
+            .as_array_mut()
+            .context("`lib.crate-type` is not an array")?;
+        if !crate_types.iter().any(|t| t.as_str() == Some("cdylib")) {
+            crate_types.push("cdylib");
+        }
+    }
+
+    let wit_path = options.path.as_deref().unwrap_or(DEFAULT_WIT_DIR);
+    let wit = manifest_dir.join(wit_path);
+    let wit_package = if wit.is_dir() {
+        Some(UnresolvedPackage::parse_dir(&wit))
+    } else if wit.is_file() {
+        Some(UnresolvedPackage::parse_file(&wit))
+    } else if has_invocation {
+        bail!(
+            "WIT path `{path}` used by `{WIT_BINDGEN_MACRO}` does not exist",
+            path = wit.display()
+        );
+    } else {
+        None
+    }
+    .transpose()
+    .with_context(|| format!("failed to parse WIT `{path}`", path = wit.display()))?;
+
+    let mut component = Table::new();
+    component.set_implicit(true);
+    component["package"] = value(match &wit_package {
+        Some(wit) => format!("{ns}:{name}", ns = wit.name.namespace, name = wit.name.name),
+        None => format!(
+            "component:{name}",
+            name = escape_wit(&package.name.to_kebab_case())
+        ),
+    });
+
+    if let Some(wit_package) = &wit_package {
+        let mut target = Table::new();
+        if wit_path != DEFAULT_WIT_DIR {
+            target["path"] = value(wit_path);
+        }
+
+        if let Some(world) = &options.world {
+            // A world qualified with the target package is named without it
+            let prefix = format!(
+                "{ns}:{name}/",
+                ns = wit_package.name.namespace,
+                name = wit_package.name.name
+            );
+            let world = world.strip_prefix(&prefix).unwrap_or(world);
+            target["world"] = value(world.split('@').next().unwrap_or(world));
+        }
+
+        // The packages in the `deps` directory become target dependencies
+        let deps_dir = wit.join("deps");
+        if deps_dir.is_dir() {
+            let mut entries = fs::read_dir(&deps_dir)
+                .and_then(|entries| {
+                    entries
+                        .map(|e| e.map(|e| e.path()))
+                        .collect::<std::io::Result<Vec<_>>>()
+                })
+                .with_context(|| {
+                    format!(
+                        "failed to read WIT dependencies directory `{path}`",
+                        path = deps_dir.display()
+                    )
+                })?;
+            entries.sort();
+
+            let mut dependencies = Table::new();
+            for entry in entries {
+                let dependency = if entry.is_dir() {
+                    UnresolvedPackage::parse_dir(&entry)
+                } else if entry.extension().and_then(|e| e.to_str()) == Some("wit") {
+                    UnresolvedPackage::parse_file(&entry)
+                } else {
+                    continue;
+                }
+                .with_context(|| {
+                    format!(
+                        "failed to parse WIT dependency `{path}`",
+                        path = entry.display()
+                    )
+                })?;
+
+                let path = Path::new(wit_path)
+                    .join("deps")
+                    .join(entry.file_name().unwrap());
+                let key = format!(
+                    "{ns}:{name}",
+                    ns = dependency.name.namespace,
+                    name = dependency.name.name
+                );
+                dependencies[key.as_str()] = value(InlineTable::from_iter([(
+                    "path",
+                    Value::from(path.display().to_string()),
+                )]));
+            }
+
+            if !dependencies.is_empty() {
+                target["dependencies"] = Item::Table(dependencies);
+            }
+        }
+
+        if !target.is_empty() {
+            component["target"] = Item::Table(target);
+        }
+    }
+
+    component["dependencies"] = Item::Table(Table::new());
+
+    let mut metadata = Table::new();
+    metadata.set_implicit(true);
+    metadata.set_position(doc.len());
+    metadata["component"] = Item::Table(component);
+    doc["package"]["metadata"] = Item::Table(metadata);
+
+    if doc.get("dependencies").is_none() {
+        doc["dependencies"] = table();
+    }
+
+    let dependencies = doc["dependencies"]
+        .as_table_like_mut()
+        .context("`dependencies` is not a table")?;
+    if has_invocation {
+        dependencies.remove(WIT_BINDGEN_CRATE_NAME);
+    }
+    if dependencies.get(BINDINGS_CRATE_NAME).is_none() {
+        dependencies.insert(BINDINGS_CRATE_NAME, value(env!("CARGO_PKG_VERSION")));
+    }
+
+    fs::write(manifest_path, doc.to_string())
+        .with_context(|| format!("failed to write manifest file `{manifest_path}`"))
+}
+
+/// Ensures the `.gitignore` file of a package ignores the target directory.
+///
+/// Returns `true` if the file was updated.
+fn update_gitignore(dir: &Path) -> Result<bool> {
+    let path = dir.join(".gitignore");
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("failed to read `{path}`", path = path.display()))
+        }
+    };
+
+    if contents
+        .lines()
+        .any(|l| matches!(l.trim(), "target" | "/target" | "target/" | "/target/"))
+    {
+        return Ok(false);
+    }
+
+    let mut contents = contents;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str("/target\n");
+
+    fs::write(&path, contents)
+        .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+    Ok(true)
+}
@@ -0,0 +1,254 @@
+//! Module for removing unused function imports from core modules.
+//!
+//! The imports of a component are derived from the imports of its core
+//! module (and adapter), so a function import that the module never
+//! references still causes the component to import its interface. This pass
+//! removes such imports from the core module before it is componentized.
+//!
+//! An imported function is unused if it is never called, referenced with
+//! `ref.func`, placed in a table by an element segment, exported, or used as
+//! the start function. Removing it shifts the indices of the functions after
+//! it; every reference is rewritten in place with an index encoded to the same
+//! width, so that code offsets (and therefore any DWARF debug information)
+//! are unchanged. The `name` section, which would otherwise name the wrong
+//! functions, is removed.
+
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use wasmparser::{ElementItems, ExternalKind, Operator, OperatorsReader, Parser, Payload, TypeRef};
+
+/// The id of the import section.
+const IMPORT_SECTION_ID: u8 = 2;
+
+/// Represents the result of removing unused imports from a module.
+pub struct Minimized {
+    /// The rewritten module.
+    pub module: Vec<u8>,
+    /// The `module` and `name` of every removed import.
+    pub removed: Vec<(String, String)>,
+}
+
+/// Removes the unused function imports of a core module.
+///
+/// If no import is unused, the module is returned unchanged.
+pub fn minimize_imports(module: &[u8]) -> Result<Minimized> {
+    let mut imports = Vec::new();
+    let mut funcs = Vec::new();
+    let mut references = Vec::new();
+    let mut import_section_end = 0;
+    let mut name_section = None;
+
+    for payload in Parser::new(0).parse_all(module) {
+        match payload.context("failed to parse core module")? {
+            Payload::Version { encoding, .. } => {
+                if encoding != wasmparser::Encoding::Module {
+                    bail!("input is not a core WebAssembly module");
+                }
+            }
+            Payload::ImportSection(reader) => {
+                import_section_end = reader.range().end;
+                for item in reader.into_iter_with_offsets() {
+                    let (offset, import) = item.context("failed to parse import")?;
+                    if let TypeRef::Func(_) = import.ty {
+                        funcs.push(imports.len());
+                    }
+                    imports.push((offset, import.module.to_string(), import.name.to_string()));
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for item in reader.into_iter_with_offsets() {
+                    let (offset, export) = item.context("failed to parse export")?;
+                    if export.kind == ExternalKind::Func {
+                        // The index follows the name and the kind byte
+                        let offset =
+                            offset + leb_len(export.name.len() as u32) + export.name.len() + 1;
+                        references.push((offset, export.index));
+                    }
+                }
+            }
+            Payload::StartSection { func, range } => references.push((range.start, func)),
+            Payload::ElementSection(reader) => {
+                for element in reader {
+                    match element.context("failed to parse element segment")?.items {
+                        ElementItems::Functions(reader) => {
+                            for item in reader.into_iter_with_offsets() {
+                                let (offset, index) = item.context("failed to parse element")?;
+                                references.push((offset, index));
+                            }
+                        }
+                        ElementItems::Expressions(reader) => {
+                            for expr in reader {
+                                let expr = expr.context("failed to parse element")?;
+                                add_references(expr.get_operators_reader(), &mut references)?;
+                            }
+                        }
+                    }
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    let global = global.context("failed to parse global")?;
+                    add_references(global.init_expr.get_operators_reader(), &mut references)?;
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                add_references(body.get_operators_reader()?, &mut references)?;
+            }
+            Payload::CustomSection(reader) if reader.name() == "name" => {
+                name_section = Some(reader.range());
+            }
+            _ => {}
+        }
+    }
+
+    // Determine the imports that are never referenced
+    let referenced: BTreeSet<u32> = references.iter().map(|(_, index)| *index).collect();
+    let removed: BTreeSet<u32> = (0..funcs.len() as u32)
+        .filter(|index| !referenced.contains(index))
+        .collect();
+
+    if removed.is_empty() {
+        return Ok(Minimized {
+            module: module.to_vec(),
+            removed: Vec::new(),
+        });
+    }
+
+    let removed_imports: BTreeSet<usize> = removed.iter().map(|i| funcs[*i as usize]).collect();
+
+    // Rewrite every reference to a function after a removed import
+    let mut patches = BTreeMap::new();
+    for (offset, index) in references {
+        let shift = removed.range(..index).count() as u32;
+        if shift > 0 {
+            patches.insert(offset, index - shift);
+        }
+    }
+
+    let mut output = module[..8].to_vec();
+    let mut offset = 8;
+    while offset < module.len() {
+        let start = offset;
+        let id = module[offset];
+        offset += 1;
+        let size = read_u32(module, &mut offset)? as usize;
+        let end = offset + size;
+
+        if name_section
+            .as_ref()
+            .map(|r| r.start >= offset && r.end <= end)
+            == Some(true)
+        {
+            offset = end;
+            continue;
+        }
+
+        if id == IMPORT_SECTION_ID {
+            let mut data = Vec::new();
+            write_u32(&mut data, (imports.len() - removed_imports.len()) as u32);
+            for (i, (start, ..)) in imports.iter().enumerate() {
+                if removed_imports.contains(&i) {
+                    continue;
+                }
+
+                let end = imports
+                    .get(i + 1)
+                    .map(|(s, ..)| *s)
+                    .unwrap_or(import_section_end);
+                data.extend_from_slice(&module[*start..end]);
+            }
+
+            output.push(id);
+            write_u32(&mut output, data.len() as u32);
+            output.extend_from_slice(&data);
+            offset = end;
+            continue;
+        }
+
+        // Patch the references within the section in place
+        let mut pos = start;
+        for (patch, index) in patches.range(offset..end) {
+            output.extend_from_slice(&module[pos..*patch]);
+            let mut cursor = *patch;
+            read_u32(module, &mut cursor)?;
+            write_padded_u32(&mut output, *index, cursor - patch);
+            pos = cursor;
+        }
+
+        output.extend_from_slice(&module[pos..end]);
+        offset = end;
+    }
+
+    Ok(Minimized {
+        module: output,
+        removed: removed_imports
+            .into_iter()
+            .map(|i| (imports[i].1.clone(), imports[i].2.clone()))
+            .collect(),
+    })
+}
+
+/// Adds the function references of the given operators.
+fn add_references(mut reader: OperatorsReader, references: &mut Vec<(usize, u32)>) -> Result<()> {
+    while !reader.eof() {
+        let (op, offset) = reader
+            .read_with_offset()
+            .context("failed to parse instruction")?;
+
+        // Each of these instructions is a single-byte opcode followed by the index
+        match op {
+            Operator::Call { function_index }
+            | Operator::ReturnCall { function_index }
+            | Operator::RefFunc { function_index } => {
+                references.push((offset + 1, function_index));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the length of the LEB128 encoding of an integer.
+fn leb_len(mut value: u32) -> usize {
+    let mut len = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        len += 1;
+    }
+    len
+}
+
+/// Reads an unsigned LEB128 encoded 32-bit integer.
+pub(crate) fn read_u32(bytes: &[u8], offset: &mut usize) -> Result<u32> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes
+            .get(*offset)
+            .context("WebAssembly binary has a truncated section")?;
+        *offset += 1;
+        result |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+
+    bail!("WebAssembly binary has a malformed integer")
+}
+
+/// Writes an unsigned LEB128 encoded 32-bit integer.
+pub(crate) fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    write_padded_u32(bytes, value, leb_len(value));
+}
+
+/// Writes an unsigned LEB128 encoded 32-bit integer padded to the given width.
+fn write_padded_u32(bytes: &mut Vec<u8>, mut value: u32, width: usize) {
+    for i in 0..width {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if i + 1 < width {
+            byte |= 0x80;
+        }
+        bytes.push(byte);
+    }
+}
@@ -0,0 +1,376 @@
+//! Module for generating mock components for the imported interfaces of a
+//! component's target world.
+//!
+//! A mock is a separate reactor component package that exports the mocked
+//! interfaces with `todo!()` implementations to fill in. It is added as a
+//! dev-dependency of the package being mocked, so that the imports it exports
+//! are satisfied by it when the package is tested or run.
+
+use crate::{
+    commands::escape_wit,
+    explain::key_name,
+    metadata::{ComponentMetadata, Target},
+    stub,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    fmt::Write,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use toml_edit::{table, value, Document, InlineTable, Item, Table, Value};
+use wit_parser::{InterfaceId, Resolve, UnresolvedPackage, WorldId, WorldItem};
+
+/// The name of the bindings crate dependency.
+const BINDINGS_CRATE_NAME: &str = "cargo-component-bindings";
+
+/// The name of the world exported by a mock component.
+const MOCK_WORLD_NAME: &str = "mock";
+
+/// Represents a generated mock component package.
+pub struct Mock {
+    /// The id of the WIT package of the mock (e.g. `component:foo-mock`).
+    pub id: String,
+    /// The directory of the mock package.
+    pub dir: PathBuf,
+    /// The names of the mocked interfaces.
+    pub interfaces: Vec<String>,
+    /// The path of the dev-dependency on the mock, relative to the manifest
+    /// of the mocked package if possible.
+    pub dependency_path: PathBuf,
+}
+
+/// Generates a mock component package for the imported interfaces of the
+/// given world.
+///
+/// If `interfaces` is empty, every imported interface with functions that is
+/// not from the `wasi` namespace is mocked; otherwise only the named
+/// interfaces are (e.g. `wasi:http/outgoing-handler`, optionally with a
+/// version).
+pub fn generate(
+    resolve: &Resolve,
+    world: WorldId,
+    metadata: &ComponentMetadata,
+    dir: &Path,
+    name: &str,
+    interfaces: &[String],
+) -> Result<Mock> {
+    let selected = select_interfaces(resolve, world, interfaces)?;
+
+    if dir.exists() {
+        bail!("destination `{path}` already exists", path = dir.display());
+    }
+
+    wit_parser::validate_id(name)
+        .with_context(|| format!("mock name `{name}` is not a legal WIT identifier"))?;
+
+    let manifest_dir = metadata
+        .manifest_path
+        .parent()
+        .context("manifest path has no parent directory")?;
+    let relative = relative_path(dir, manifest_dir);
+    let base = match &relative {
+        Some(relative) => relative.components().map(|_| "..").collect(),
+        None => manifest_dir.to_path_buf(),
+    };
+
+    let id = format!("component:{name}");
+    let mut source = format!("package component:{name}\n\nworld {MOCK_WORLD_NAME} {{\n");
+    for (_, interface) in &selected {
+        let iface = &resolve.interfaces[*interface];
+        let package = &resolve.packages[iface.package.expect("interface should have a package")];
+        writeln!(
+            source,
+            "    export {ns}:{pkg}/{name}{version}",
+            ns = escape_wit(&package.name.namespace),
+            pkg = escape_wit(&package.name.name),
+            name = escape_wit(iface.name.as_deref().expect("interface should be named")),
+            version = package
+                .name
+                .version
+                .as_ref()
+                .map(|v| format!("@{v}"))
+                .unwrap_or_default()
+        )?;
+    }
+    source.push_str("}\n");
+
+    let manifest = manifest(resolve, world, metadata, &selected, name, &id, &base)?;
+
+    let wit_dir = dir.join("wit");
+    let src_dir = dir.join("src");
+    for dir in [&wit_dir, &src_dir] {
+        fs::create_dir_all(dir).with_context(|| {
+            format!("failed to create directory `{path}`", path = dir.display())
+        })?;
+    }
+
+    let manifest_path = dir.join("Cargo.toml");
+    fs::write(&manifest_path, manifest.to_string()).with_context(|| {
+        format!(
+            "failed to write manifest file `{path}`",
+            path = manifest_path.display()
+        )
+    })?;
+
+    let wit_path = wit_dir.join("world.wit");
+    fs::write(&wit_path, &source).with_context(|| {
+        format!(
+            "failed to write WIT file `{path}`",
+            path = wit_path.display()
+        )
+    })?;
+
+    let lib_path = src_dir.join("lib.rs");
+    fs::write(&lib_path, "cargo_component_bindings::generate!();\n").with_context(|| {
+        format!(
+            "failed to write source file `{path}`",
+            path = lib_path.display()
+        )
+    })?;
+
+    // Resolve the mock world against the packages of the mocked world so
+    // that the stubs use the same types
+    let mut resolve = resolve.clone();
+    let package = resolve.push(UnresolvedPackage::parse(&wit_path, &source)?)?;
+    let mock_world = resolve.packages[package].worlds[MOCK_WORLD_NAME];
+    stub::apply(&stub::generate(&resolve, mock_world, &lib_path)?)?;
+
+    Ok(Mock {
+        id,
+        dir: dir.to_path_buf(),
+        interfaces: selected.into_iter().map(|(name, _)| name).collect(),
+        dependency_path: relative.unwrap_or_else(|| dir.to_path_buf()),
+    })
+}
+
+/// Selects the imported interfaces of the world to mock.
+fn select_interfaces(
+    resolve: &Resolve,
+    world: WorldId,
+    names: &[String],
+) -> Result<Vec<(String, InterfaceId)>> {
+    let imports: Vec<_> = resolve.worlds[world]
+        .imports
+        .iter()
+        .filter_map(|(key, item)| match item {
+            WorldItem::Interface(id) if resolve.interfaces[*id].package.is_some() => {
+                Some((key_name(resolve, key), *id))
+            }
+            _ => None,
+        })
+        .collect();
+
+    if names.is_empty() {
+        let selected: Vec<_> = imports
+            .into_iter()
+            .filter(|(_, id)| {
+                let interface = &resolve.interfaces[*id];
+                let package = &resolve.packages[interface.package.unwrap()];
+                package.name.namespace != "wasi" && !interface.functions.is_empty()
+            })
+            .collect();
+
+        if selected.is_empty() {
+            bail!(
+                "the target world does not import any non-WASI interfaces with functions; use `--interface` to mock specific interfaces"
+            );
+        }
+
+        return Ok(selected);
+    }
+
+    names
+        .iter()
+        .map(|name| {
+            imports
+                .iter()
+                .find(|(import, _)| {
+                    import == name
+                        || import
+                            .strip_prefix(name.as_str())
+                            .map(|rest| rest.starts_with('@'))
+                            .unwrap_or(false)
+                })
+                .cloned()
+                .with_context(|| format!("the target world does not import interface `{name}`"))
+        })
+        .collect()
+}
+
+/// Creates the manifest of a mock package.
+///
+/// The mock targets its own world, with the dependencies of the mocked
+/// package's target so that the mocked interfaces resolve the same way.
+fn manifest(
+    resolve: &Resolve,
+    world: WorldId,
+    metadata: &ComponentMetadata,
+    selected: &[(String, InterfaceId)],
+    name: &str,
+    id: &str,
+    base: &Path,
+) -> Result<Document> {
+    let original = fs::read_to_string(&metadata.manifest_path).with_context(|| {
+        format!(
+            "failed to read manifest file `{path}`",
+            path = metadata.manifest_path.display()
+        )
+    })?;
+    let original: Document = original.parse().with_context(|| {
+        format!(
+            "failed to parse manifest file `{path}`",
+            path = metadata.manifest_path.display()
+        )
+    })?;
+    let component = original
+        .get("package")
+        .and_then(|p| p.get("metadata"))
+        .and_then(|m| m.get("component"));
+
+    let mut dependencies = Table::new();
+    match &metadata.section.target {
+        Target::Package { id, package, .. } => {
+            let mut dependency = InlineTable::new();
+            if let Some(package_id) = &package.id {
+                dependency.insert("package", Value::from(package_id.to_string()));
+            }
+            dependency.insert("version", Value::from(package.version.to_string()));
+            if let Some(registry) = &package.registry {
+                dependency.insert("registry", Value::from(registry.as_str()));
+            }
+            dependencies[&id.to_string()] = value(dependency);
+        }
+        Target::Local { .. } => {
+            if let Some(existing) = component
+                .and_then(|c| c.get("target"))
+                .and_then(|t| t.get("dependencies"))
+                .and_then(Item::as_table_like)
+            {
+                for (key, item) in existing.iter() {
+                    let mut item = item.clone();
+                    rebase_paths(&mut item, base);
+                    dependencies[key] = item;
+                }
+            }
+
+            // Interfaces defined by the local target itself need it as a dependency
+            let local = resolve.worlds[world].package;
+            if selected
+                .iter()
+                .any(|(_, id)| resolve.interfaces[*id].package == local)
+            {
+                if let (Some(local), Some(path)) =
+                    (local, metadata.local_target_path(&metadata.section.target))
+                {
+                    let package = &resolve.packages[local].name;
+                    let key = format!("{ns}:{name}", ns = package.namespace, name = package.name);
+                    dependencies[&key] = value(InlineTable::from_iter([(
+                        "path",
+                        Value::from(path.display().to_string()),
+                    )]));
+                }
+            }
+        }
+    }
+
+    let mut target = Table::new();
+    target["path"] = value("wit");
+    target["world"] = value(MOCK_WORLD_NAME);
+    target["dependencies"] = Item::Table(dependencies);
+
+    let mut mock = Table::new();
+    mock.set_implicit(true);
+    mock["package"] = value(id);
+    mock["target"] = Item::Table(target);
+    for key in ["registries", "namespaces", "patch"] {
+        if let Some(item) = component.and_then(|c| c.get(key)) {
+            let mut item = item.clone();
+            rebase_paths(&mut item, base);
+            mock[key] = item;
+        }
+    }
+
+    let mut doc = Document::new();
+    doc["package"] = table();
+    doc["package"]["name"] = value(name);
+    doc["package"]["version"] = value("0.1.0");
+    doc["package"]["edition"] = value("2021");
+    doc["package"]["publish"] = value(false);
+
+    doc["lib"] = table();
+    doc["lib"]["crate-type"] = value(Value::from_iter(["cdylib"]));
+
+    doc["dependencies"] = table();
+    doc["dependencies"][BINDINGS_CRATE_NAME] = match original
+        .get("dependencies")
+        .and_then(|d| d.get(BINDINGS_CRATE_NAME))
+    {
+        Some(item) => {
+            let mut item = item.clone();
+            rebase_paths(&mut item, base);
+            item
+        }
+        None => value(env!("CARGO_PKG_VERSION")),
+    };
+
+    let mut package_metadata = Table::new();
+    package_metadata.set_implicit(true);
+    package_metadata["component"] = Item::Table(mock);
+    doc["package"]["metadata"] = Item::Table(package_metadata);
+
+    // The mock is its own workspace so it may be nested in the mocked package
+    doc["workspace"] = table();
+
+    Ok(doc)
+}
+
+/// Gets the path of `path` relative to `base`, if `path` is within `base`.
+fn relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(base).ok()?;
+    if relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        Some(relative.to_path_buf())
+    } else {
+        None
+    }
+}
+
+/// Rebases the relative `path` fields of the given TOML item onto `base`.
+fn rebase_paths(item: &mut Item, base: &Path) {
+    match item {
+        Item::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                rebase_paths(item, base);
+            }
+
+            if let Some(path) = table.get_mut("path").and_then(Item::as_value_mut) {
+                rebase_path(path, base);
+            }
+        }
+        Item::Value(value) => rebase_value_paths(value, base),
+        _ => {}
+    }
+}
+
+fn rebase_value_paths(value: &mut Value, base: &Path) {
+    if let Value::InlineTable(table) = value {
+        for (_, value) in table.iter_mut() {
+            rebase_value_paths(value, base);
+        }
+
+        if let Some(path) = table.get_mut("path") {
+            rebase_path(path, base);
+        }
+    }
+}
+
+fn rebase_path(value: &mut Value, base: &Path) {
+    if let Some(path) = value.as_str() {
+        if !Path::new(path).is_absolute() {
+            *value = Value::from(base.join(path).display().to_string());
+        }
+    }
+}
@@ -1,13 +1,21 @@
 //! Module for interacting with component registries.
 
-use crate::{config::Config, metadata::ComponentMetadata};
-use anyhow::Result;
+use crate::{
+    config::Config,
+    metadata::{ComponentMetadata, Target},
+    signature,
+};
+use anyhow::{Context, Result};
 use cargo_component_core::{
     lock::{LockFile, LockFileResolver, LockedPackage, LockedPackageVersion},
-    registry::{DependencyResolution, DependencyResolutionMap, DependencyResolver},
+    registry::{Dependency, DependencyResolution, DependencyResolutionMap, DependencyResolver},
 };
 use semver::Version;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
 use warg_crypto::hash::AnyHash;
 use warg_protocol::registry::PackageId;
 
@@ -18,47 +26,185 @@ pub struct PackageDependencyResolution<'a> {
     pub metadata: &'a ComponentMetadata,
     /// Resolutions for the package's target dependencies.
     pub target_resolutions: DependencyResolutionMap,
+    /// Resolutions for the dependencies of the package's named targets.
+    pub named_target_resolutions: HashMap<String, DependencyResolutionMap>,
     /// Resolutions for the package's component dependencies.
     pub resolutions: DependencyResolutionMap,
+    /// Resolutions for the package's component dev-dependencies.
+    pub dev_resolutions: DependencyResolutionMap,
+    /// Resolutions for the package's adapters from registries.
+    pub adapter_resolutions: DependencyResolutionMap,
 }
 
 impl<'a> PackageDependencyResolution<'a> {
     /// Creates a new package dependency resolution for the given package.
     ///
     /// Returns `Ok(None)` if the package is not a component package.
+    ///
+    /// Registry packages in `precise` are resolved to exactly the given version.
     pub async fn new(
         config: &Config,
         metadata: &'a ComponentMetadata,
         lock_file: Option<LockFileResolver<'_>>,
+        vendor_dir: Option<&Path>,
+        precise: Option<&HashMap<PackageId, Version>>,
         network_allowed: bool,
     ) -> Result<PackageDependencyResolution<'a>> {
-        Ok(Self {
+        let mut named_target_resolutions = HashMap::new();
+        for (name, target) in &metadata.section.targets {
+            named_target_resolutions.insert(
+                name.clone(),
+                Self::resolve_target_deps(
+                    config,
+                    metadata,
+                    target,
+                    lock_file,
+                    vendor_dir,
+                    precise,
+                    network_allowed,
+                )
+                .await?,
+            );
+        }
+
+        let resolution = Self {
             metadata,
             target_resolutions: Self::resolve_target_deps(
                 config,
                 metadata,
+                &metadata.section.target,
+                lock_file,
+                vendor_dir,
+                precise,
+                network_allowed,
+            )
+            .await?,
+            named_target_resolutions,
+            resolutions: Self::resolve_deps(
+                config,
+                metadata,
+                &metadata.section.dependencies,
                 lock_file,
+                vendor_dir,
+                precise,
                 network_allowed,
             )
             .await?,
-            resolutions: Self::resolve_deps(config, metadata, lock_file, network_allowed).await?,
-        })
+            dev_resolutions: Self::resolve_deps(
+                config,
+                metadata,
+                &metadata.section.dev_dependencies,
+                lock_file,
+                vendor_dir,
+                precise,
+                network_allowed,
+            )
+            .await?,
+            adapter_resolutions: Self::resolve_adapters(
+                config,
+                metadata,
+                lock_file,
+                vendor_dir,
+                precise,
+                network_allowed,
+            )
+            .await?,
+        };
+
+        resolution.verify_signatures()?;
+        Ok(resolution)
+    }
+
+    /// Verifies the embedded signatures of the registry dependencies that
+    /// have trusted keys.
+    fn verify_signatures(&self) -> Result<()> {
+        for (_, resolution) in self.all() {
+            let DependencyResolution::Registry(registry) = resolution else {
+                continue;
+            };
+
+            let keys = self.metadata.section.trusted_keys(&registry.package)?;
+            if keys.is_empty() {
+                continue;
+            }
+
+            let bytes = fs::read(&registry.path).with_context(|| {
+                format!(
+                    "failed to read the contents of package `{id}` from `{path}`",
+                    id = registry.package,
+                    path = registry.path.display()
+                )
+            })?;
+
+            let embedded = signature::verify(&bytes, &keys).with_context(|| {
+                format!(
+                    "failed to verify the signature of package `{id}` v{version}",
+                    id = registry.package,
+                    version = registry.version
+                )
+            })?;
+
+            log::debug!(
+                "package `{id}` v{version} was signed by trusted key `{key}`",
+                id = registry.package,
+                version = registry.version,
+                key = embedded.key_id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Gets the target dependency resolutions for the given target name.
+    ///
+    /// If `name` is `None`, the resolutions of the default target are returned.
+    pub fn target_resolutions(&self, name: Option<&str>) -> Option<&DependencyResolutionMap> {
+        match name {
+            Some(name) => self.named_target_resolutions.get(name),
+            None => Some(&self.target_resolutions),
+        }
     }
 
     /// Iterates over all dependency resolutions of the package.
     pub fn all(&self) -> impl Iterator<Item = (&PackageId, &DependencyResolution)> {
         self.target_resolutions
             .iter()
+            .chain(
+                self.named_target_resolutions
+                    .values()
+                    .flat_map(|r| r.iter()),
+            )
             .chain(self.resolutions.iter())
+            .chain(self.dev_resolutions.iter())
+            .chain(self.adapter_resolutions.iter())
+    }
+
+    /// Gets the paths of the adapter modules resolved from registries.
+    pub fn adapter_paths(&self) -> HashMap<PackageId, PathBuf> {
+        self.adapter_resolutions
+            .iter()
+            .map(|(id, resolution)| (id.clone(), resolution.path().to_path_buf()))
+            .collect()
+    }
+
+    /// Gets the paths of the component dev-dependencies.
+    pub fn dev_dependency_paths(&self) -> HashMap<PackageId, PathBuf> {
+        self.dev_resolutions
+            .iter()
+            .map(|(id, resolution)| (id.clone(), resolution.path().to_path_buf()))
+            .collect()
     }
 
     async fn resolve_target_deps(
         config: &Config,
         metadata: &ComponentMetadata,
+        target: &Target,
         lock_file: Option<LockFileResolver<'_>>,
+        vendor_dir: Option<&Path>,
+        precise: Option<&HashMap<PackageId, Version>>,
         network_allowed: bool,
     ) -> Result<DependencyResolutionMap> {
-        let target_deps = metadata.section.target.dependencies();
+        let target_deps = target.dependencies();
 
         let mut resolver = DependencyResolver::new(
             config.warg(),
@@ -66,7 +212,11 @@ impl<'a> PackageDependencyResolution<'a> {
             lock_file,
             config.terminal(),
             network_allowed,
-        )?;
+        )?
+        .with_vendor_dir(vendor_dir)
+        .with_namespaces(&metadata.section.namespaces)
+        .with_patches(&metadata.section.patch)
+        .with_precise(precise);
 
         for (name, dependency) in target_deps.iter() {
             resolver.add_dependency(name, dependency).await?;
@@ -78,7 +228,10 @@ impl<'a> PackageDependencyResolution<'a> {
     async fn resolve_deps(
         config: &Config,
         metadata: &ComponentMetadata,
+        dependencies: &HashMap<PackageId, Dependency>,
         lock_file: Option<LockFileResolver<'_>>,
+        vendor_dir: Option<&Path>,
+        precise: Option<&HashMap<PackageId, Version>>,
         network_allowed: bool,
     ) -> Result<DependencyResolutionMap> {
         let mut resolver = DependencyResolver::new(
@@ -87,14 +240,47 @@ impl<'a> PackageDependencyResolution<'a> {
             lock_file,
             config.terminal(),
             network_allowed,
-        )?;
+        )?
+        .with_vendor_dir(vendor_dir)
+        .with_namespaces(&metadata.section.namespaces)
+        .with_patches(&metadata.section.patch)
+        .with_precise(precise);
 
-        for (name, dependency) in &metadata.section.dependencies {
+        for (name, dependency) in dependencies {
             resolver.add_dependency(name, dependency).await?;
         }
 
         resolver.resolve().await
     }
+
+    async fn resolve_adapters(
+        config: &Config,
+        metadata: &ComponentMetadata,
+        lock_file: Option<LockFileResolver<'_>>,
+        vendor_dir: Option<&Path>,
+        precise: Option<&HashMap<PackageId, Version>>,
+        network_allowed: bool,
+    ) -> Result<DependencyResolutionMap> {
+        let mut resolver = DependencyResolver::new(
+            config.warg(),
+            &metadata.section.registries,
+            lock_file,
+            config.terminal(),
+            network_allowed,
+        )?
+        .with_vendor_dir(vendor_dir)
+        .with_namespaces(&metadata.section.namespaces)
+        .with_patches(&metadata.section.patch)
+        .with_precise(precise);
+
+        for (id, package) in metadata.adapter_packages() {
+            resolver
+                .add_dependency(id, &Dependency::Package(package.clone()))
+                .await?;
+        }
+
+        resolver.resolve().await
+    }
 }
 
 /// Represents a mapping between all component packages and their dependency resolutions.
@@ -137,7 +323,9 @@ impl<'a> PackageResolutionMap<'a> {
                     Some((id, registry)) => {
                         let pkg = match dep {
                             DependencyResolution::Registry(pkg) => pkg,
-                            DependencyResolution::Local(_) => unreachable!(),
+                            DependencyResolution::Local(_) | DependencyResolution::Git(_) => {
+                                unreachable!()
+                            }
                         };
 
                         let prev = packages
@@ -182,6 +370,10 @@ impl<'a> PackageResolutionMap<'a> {
 
         packages.sort_by(|a, b| a.key().cmp(&b.key()));
 
-        LockFile::new(packages)
+        LockFile::new(packages).with_git_sources(
+            self.0
+                .values()
+                .flat_map(|resolution| resolution.all().map(|(_, dep)| dep)),
+        )
     }
 }
@@ -0,0 +1,136 @@
+//! Module for remapping imported interfaces to existing Rust modules.
+//!
+//! The `package.metadata.component.bindings.with` section maps the name of an
+//! imported interface (e.g. `my:types/shapes`) to the path of a Rust module
+//! that already has its bindings (e.g. the `bindings` module of a published
+//! bindings crate, `my_types::bindings::my::types::shapes`).
+//!
+//! The module generated for a remapped interface is replaced by a glob
+//! re-export of the given module, so its types are shared with the crate the
+//! module is from rather than defined again by every component. The given
+//! module must be generated by `cargo-component-bindings` from the same
+//! interface, as the rest of the bindings use its types as they generate them.
+
+use crate::metadata::Bindings;
+use anyhow::{bail, Result};
+use cargo_component_core::bindings::remap_module;
+use wit_bindgen_rust::to_rust_ident;
+use wit_parser::{InterfaceId, Resolve, WorldId, WorldItem, WorldKey};
+
+/// Represents a module of the generated bindings that is remapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remapped {
+    /// The path of the module relative to the `bindings` module.
+    pub module: Vec<String>,
+    /// The Rust path of the module to re-export instead.
+    pub path: String,
+}
+
+/// Gets the modules of the generated bindings of a world that are remapped
+/// by the `with` section of the given bindings settings.
+pub fn remapped_modules(
+    resolve: &Resolve,
+    world: WorldId,
+    bindings: &Bindings,
+) -> Result<Vec<Remapped>> {
+    let mut remapped = Vec::new();
+    for (name, path) in &bindings.with {
+        let relative = path.strip_prefix("::").unwrap_or(path);
+        if relative.is_empty()
+            || !relative.split("::").all(|segment| {
+                !segment.is_empty()
+                    && segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            })
+        {
+            bail!("invalid Rust module path `{path}` for interface `{name}` in `package.metadata.component.bindings.with`");
+        }
+
+        let interface = resolve.worlds[world]
+            .imports
+            .iter()
+            .find_map(|(key, item)| match (key, item) {
+                (WorldKey::Interface(id), WorldItem::Interface(_))
+                    if names(resolve, *id).contains(name) =>
+                {
+                    Some(*id)
+                }
+                _ => None,
+            });
+
+        let interface = match interface {
+            Some(interface) => interface,
+            None => bail!(
+                "interface `{name}` in `package.metadata.component.bindings.with` is not imported by the target world"
+            ),
+        };
+
+        // The conveniences of a type are implemented on the type, which a
+        // remapped interface does not define
+        let names = names(resolve, interface);
+        if let Some(ty) = bindings.types.keys().find(|key| {
+            key.rsplit_once('/')
+                .map(|(interface, _)| names.iter().any(|n| n == interface))
+                .unwrap_or(false)
+        }) {
+            bail!(
+                "the options of type `{ty}` in `package.metadata.component.bindings.types` cannot be used as interface `{name}` is remapped with `package.metadata.component.bindings.with`"
+            );
+        }
+
+        let interface = &resolve.interfaces[interface];
+
+        let package = &resolve.packages[interface.package.unwrap()].name;
+        remapped.push(Remapped {
+            module: vec![
+                to_rust_ident(&package.namespace),
+                to_rust_ident(&package.name),
+                to_rust_ident(interface.name.as_deref().unwrap()),
+            ],
+            path: path.clone(),
+        });
+    }
+
+    Ok(remapped)
+}
+
+/// Gets the names an interface may be referred to by: its id without and
+/// with its package's version.
+pub fn names(resolve: &Resolve, id: InterfaceId) -> Vec<String> {
+    let interface = &resolve.interfaces[id];
+    let (package, name) = match (interface.package, &interface.name) {
+        (Some(package), Some(name)) => (&resolve.packages[package].name, name),
+        _ => return Vec::new(),
+    };
+
+    let mut names = vec![format!(
+        "{ns}:{pkg}/{name}",
+        ns = package.namespace,
+        pkg = package.name
+    )];
+    if let Some(version) = &package.version {
+        names.push(format!("{unversioned}@{version}", unversioned = names[0]));
+    }
+
+    names
+}
+
+/// Replaces the remapped modules of generated Rust bindings.
+///
+/// The body of each remapped module is replaced with a glob re-export of the
+/// module it is remapped to.
+pub fn remap(source: &str, remapped: &[Remapped]) -> Result<String> {
+    let mut source = source.to_string();
+    for Remapped { module, path } in remapped {
+        source = match remap_module(&source, module, path) {
+            Some(source) => source,
+            None => bail!(
+                "failed to find module `{module}` in the generated bindings",
+                module = module.join("::")
+            ),
+        };
+    }
+
+    Ok(source)
+}
@@ -0,0 +1,468 @@
+//! Module for determining the rustflags cargo uses for a build.
+//!
+//! Setting `CARGO_ENCODED_RUSTFLAGS` for a cargo command replaces the
+//! rustflags of the `build` and `target` tables of the cargo configuration,
+//! so flags added to a command start from the flags cargo would otherwise use.
+//! These follow the precedence cargo documents: `CARGO_ENCODED_RUSTFLAGS`,
+//! then `RUSTFLAGS`, then the `target.<triple>.rustflags` and
+//! `target.<cfg>.rustflags` entries matching the target, and finally
+//! `build.rustflags`.
+
+use anyhow::{bail, Context, Result};
+use serde::{de, Deserialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Represents the parts of a cargo configuration file that specify rustflags.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    build: Flags,
+    target: BTreeMap<String, Flags>,
+}
+
+/// Represents a table of a cargo configuration file with a `rustflags` key.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Flags {
+    #[serde(deserialize_with = "deserialize_flags")]
+    rustflags: Option<Vec<String>>,
+}
+
+/// Deserializes rustflags given as a string or as an array of strings.
+fn deserialize_flags<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Option<Vec<String>>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "a string or an array of strings")
+        }
+
+        fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(Some(split(s)))
+        }
+
+        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::SeqAccess<'de>,
+        {
+            Vec::<String>::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(Some)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+/// Gets the rustflags cargo would use when building for the given targets
+/// from the current directory.
+///
+/// If no targets are given, the flags for the host are returned.
+pub fn configured(targets: &[String]) -> Result<Vec<String>> {
+    if let Ok(flags) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+        return Ok(if flags.is_empty() {
+            Vec::new()
+        } else {
+            flags.split('\x1f').map(ToString::to_string).collect()
+        });
+    }
+
+    if let Ok(flags) = env::var("RUSTFLAGS") {
+        return Ok(split(&flags));
+    }
+
+    let files = config_files(&env::current_dir().context("failed to get the current directory")?)?;
+
+    let host;
+    let targets = if targets.is_empty() {
+        host = [host_target()?];
+        &host[..]
+    } else {
+        targets
+    };
+
+    let mut configured: Option<Vec<String>> = None;
+    for target in targets {
+        let flags = target_flags(&files, target)?;
+        match &configured {
+            Some(other) if *other != flags => bail!(
+                "cannot add rustflags to a build for targets with different configured rustflags"
+            ),
+            Some(_) => {}
+            None => configured = Some(flags),
+        }
+    }
+
+    Ok(configured.unwrap_or_default())
+}
+
+/// Gets the configured rustflags for a target.
+///
+/// The files are ordered from the lowest precedence to the highest.
+fn target_flags(files: &[ConfigFile], target: &str) -> Result<Vec<String>> {
+    let mut cfgs = None;
+    let mut flags = Vec::new();
+    let mut found = false;
+    for file in files {
+        for (key, table) in &file.target {
+            let rustflags = match &table.rustflags {
+                Some(rustflags) => rustflags,
+                None => continue,
+            };
+
+            let matches = if key.starts_with("cfg(") {
+                if cfgs.is_none() {
+                    cfgs = Some(target_cfgs(target)?);
+                }
+
+                Cfg::parse(key)?.matches(cfgs.as_deref().unwrap())
+            } else {
+                key == target
+            };
+
+            if matches {
+                flags.extend(rustflags.iter().cloned());
+                found = true;
+            }
+        }
+    }
+
+    let var = format!(
+        "CARGO_TARGET_{target}_RUSTFLAGS",
+        target = target.to_uppercase().replace(|c| c == '-' || c == '.', "_")
+    );
+    if let Ok(value) = env::var(var) {
+        flags.extend(split(&value));
+        found = true;
+    }
+
+    if found {
+        return Ok(flags);
+    }
+
+    for file in files {
+        if let Some(rustflags) = &file.build.rustflags {
+            flags.extend(rustflags.iter().cloned());
+        }
+    }
+
+    if let Ok(value) = env::var("CARGO_BUILD_RUSTFLAGS") {
+        flags.extend(split(&value));
+    }
+
+    Ok(flags)
+}
+
+/// Reads the cargo configuration files that apply to the given directory.
+///
+/// The files are returned from the lowest precedence to the highest: the
+/// file in the cargo home directory, then the files of the directory's
+/// ancestors from the root down.
+fn config_files(dir: &Path) -> Result<Vec<ConfigFile>> {
+    let mut dirs: Vec<PathBuf> = dir.ancestors().map(|d| d.join(".cargo")).collect();
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|dir| dir.join(".cargo")));
+    if let Some(cargo_home) = cargo_home {
+        dirs.push(cargo_home);
+    }
+
+    let mut seen = Vec::new();
+    let mut files = Vec::new();
+    for dir in dirs {
+        let path = ["config.toml", "config"]
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|p| p.is_file());
+        let path = match path {
+            Some(path) => path,
+            None => continue,
+        };
+
+        // The cargo home directory may also be the `.cargo` of an ancestor
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+
+        let contents = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "failed to read cargo configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+        let file: ConfigFile = toml_edit::de::from_str(&contents).with_context(|| {
+            format!(
+                "failed to parse cargo configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+        files.push(file);
+    }
+
+    files.reverse();
+    Ok(files)
+}
+
+/// Splits rustflags given as a string.
+fn split(flags: &str) -> Vec<String> {
+    flags.split_whitespace().map(ToString::to_string).collect()
+}
+
+fn rustc() -> PathBuf {
+    env::var_os("RUSTC")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("rustc"))
+}
+
+/// Gets the target triple of the host from rustc.
+fn host_target() -> Result<String> {
+    let output = Command::new(rustc())
+        .arg("-vV")
+        .output()
+        .context("failed to run `rustc -vV`")?;
+    if !output.status.success() {
+        bail!(
+            "`rustc -vV` failed with exit status {status}",
+            status = output.status
+        );
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(|host| host.trim().to_string())
+        .context("`rustc -vV` did not print the host target")
+}
+
+/// Gets the cfg values of a target from rustc (e.g. `target_arch="wasm32"`).
+fn target_cfgs(target: &str) -> Result<Vec<String>> {
+    let output = Command::new(rustc())
+        .args(["--print", "cfg", "--target", target])
+        .output()
+        .with_context(|| format!("failed to get the cfg values of target `{target}`"))?;
+    if !output.status.success() {
+        bail!(
+            "failed to get the cfg values of target `{target}`: rustc exited with status {status}",
+            status = output.status
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// Represents a `cfg(...)` expression of a `target` table key.
+#[derive(Debug, PartialEq, Eq)]
+enum Cfg {
+    /// A name or `name="value"` pair, as printed by `rustc --print cfg`.
+    Value(String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
+
+impl Cfg {
+    /// Parses a `cfg(...)` key.
+    fn parse(key: &str) -> Result<Self> {
+        let inner = key
+            .strip_prefix("cfg(")
+            .and_then(|s| s.strip_suffix(')'))
+            .with_context(|| format!("invalid `cfg` expression `{key}`"))?;
+
+        let tokens =
+            tokenize(inner).with_context(|| format!("invalid `cfg` expression `{key}`"))?;
+        let mut pos = 0;
+        let cfg = Self::parse_predicate(&tokens, &mut pos)
+            .with_context(|| format!("invalid `cfg` expression `{key}`"))?;
+        if pos != tokens.len() {
+            bail!("invalid `cfg` expression `{key}`: unexpected trailing input");
+        }
+
+        Ok(cfg)
+    }
+
+    fn parse_predicate(tokens: &[Token], pos: &mut usize) -> Result<Self> {
+        let name = match tokens.get(*pos) {
+            Some(Token::Ident(name)) => name,
+            _ => bail!("expected a name"),
+        };
+        *pos += 1;
+
+        match tokens.get(*pos) {
+            Some(Token::Equals) => {
+                *pos += 1;
+                match tokens.get(*pos) {
+                    Some(Token::String(value)) => {
+                        *pos += 1;
+                        Ok(Self::Value(format!("{name}=\"{value}\"")))
+                    }
+                    _ => bail!("expected a string after `{name} =`"),
+                }
+            }
+            Some(Token::Open) if matches!(name.as_str(), "all" | "any" | "not") => {
+                *pos += 1;
+                let mut list = Vec::new();
+                while tokens.get(*pos) != Some(&Token::Close) {
+                    list.push(Self::parse_predicate(tokens, pos)?);
+                    match tokens.get(*pos) {
+                        Some(Token::Comma) => *pos += 1,
+                        Some(Token::Close) => {}
+                        _ => bail!("expected `,` or `)`"),
+                    }
+                }
+                *pos += 1;
+
+                match name.as_str() {
+                    "all" => Ok(Self::All(list)),
+                    "any" => Ok(Self::Any(list)),
+                    _ => match <[Cfg; 1]>::try_from(list) {
+                        Ok([cfg]) => Ok(Self::Not(Box::new(cfg))),
+                        Err(_) => bail!("`not` expects exactly one predicate"),
+                    },
+                }
+            }
+            _ => Ok(Self::Value(name.clone())),
+        }
+    }
+
+    /// Determines if the expression matches the given cfg values.
+    fn matches(&self, cfgs: &[String]) -> bool {
+        match self {
+            Self::Value(value) => cfgs.contains(value),
+            Self::All(list) => list.iter().all(|c| c.matches(cfgs)),
+            Self::Any(list) => list.iter().any(|c| c.matches(cfgs)),
+            Self::Not(cfg) => !cfg.matches(cfgs),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Equals,
+    Comma,
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '=' => tokens.push(Token::Equals),
+            ',' => tokens.push(Token::Comma),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => bail!("unterminated string"),
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                tokens.push(Token::Ident(name));
+            }
+            c => bail!("unexpected character `{c}`"),
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cfgs() -> Vec<String> {
+        [
+            "target_arch=\"wasm32\"",
+            "target_os=\"wasi\"",
+            "panic=\"abort\"",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+    }
+
+    #[test]
+    fn it_matches_cfg_expressions() {
+        for (key, expected) in [
+            ("cfg(target_arch = \"wasm32\")", true),
+            ("cfg(target_arch = \"x86_64\")", false),
+            ("cfg(unix)", false),
+            ("cfg(not(unix))", true),
+            (
+                "cfg(all(target_arch = \"wasm32\", target_os = \"wasi\"))",
+                true,
+            ),
+            ("cfg(all(target_arch = \"wasm32\", unix))", false),
+            ("cfg(any(unix, target_os = \"wasi\",))", true),
+        ] {
+            assert_eq!(Cfg::parse(key).unwrap().matches(&cfgs()), expected, "{key}");
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_cfg_expressions() {
+        for key in [
+            "cfg(target_arch = )",
+            "cfg(not(unix, windows))",
+            "cfg(all(unix)",
+            "cfg(unix windows)",
+        ] {
+            assert!(Cfg::parse(key).is_err(), "{key}");
+        }
+    }
+
+    #[test]
+    fn it_prefers_target_rustflags() {
+        let build: ConfigFile =
+            toml_edit::de::from_str("[build]\nrustflags = [\"--cfg\", \"build\"]\n").unwrap();
+        let target: ConfigFile =
+            toml_edit::de::from_str("[target.wasm32-wasi]\nrustflags = \"--cfg wasm\"\n").unwrap();
+
+        let files = [build];
+        assert_eq!(
+            target_flags(&files, "wasm32-wasi").unwrap(),
+            ["--cfg", "build"]
+        );
+
+        let files = [files.into_iter().next().unwrap(), target];
+        assert_eq!(
+            target_flags(&files, "wasm32-wasi").unwrap(),
+            ["--cfg", "wasm"]
+        );
+        assert_eq!(
+            target_flags(&files, "wasm32-unknown-unknown").unwrap(),
+            ["--cfg", "build"]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Module for generating software bills of materials for components.
+//!
+//! The bill of materials is in the [CycloneDX](https://cyclonedx.org/) JSON
+//! format and covers both the Rust crate graph of the package and its
+//! component and WIT dependencies from registries.
+
+use crate::registry::PackageDependencyResolution;
+use anyhow::{Context, Result};
+use cargo_component_core::registry::DependencyResolution;
+use cargo_metadata::{DependencyKind, Metadata, Package};
+use semver::Version;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use warg_protocol::registry::PackageId;
+use wasm_encoder::{CustomSection, Encode, Section};
+
+/// The name of the custom section the bill of materials is embedded in.
+pub const SBOM_SECTION_NAME: &str = "sbom";
+
+/// The CycloneDX specification version of the bill of materials.
+const SPEC_VERSION: &str = "1.5";
+
+/// Generates a CycloneDX bill of materials for a component package.
+///
+/// The metadata must include the dependency graph of the package (i.e. it must
+/// not have been loaded with `--no-deps`).
+pub fn generate(
+    metadata: &Metadata,
+    package: &Package,
+    id: &PackageId,
+    version: &Version,
+    resolution: &PackageDependencyResolution,
+) -> Result<Vec<u8>> {
+    let resolve = metadata
+        .resolve
+        .as_ref()
+        .context("cargo metadata does not contain a dependency graph")?;
+    let packages: HashMap<_, _> = metadata.packages.iter().map(|p| (&p.id, p)).collect();
+    let nodes: HashMap<_, _> = resolve.nodes.iter().map(|n| (&n.id, n)).collect();
+
+    let root_ref = format!("{id}@{version}");
+    let mut components = BTreeMap::new();
+    let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    // Walk the crate graph from the package, ignoring dev-dependencies
+    let mut stack = vec![(&package.id, root_ref.clone())];
+    let mut visited = BTreeSet::new();
+    while let Some((pkg_id, bom_ref)) = stack.pop() {
+        if !visited.insert(bom_ref.clone()) {
+            continue;
+        }
+
+        let node = match nodes.get(pkg_id) {
+            Some(node) => node,
+            None => continue,
+        };
+
+        let depends_on = dependencies.entry(bom_ref).or_default();
+        for dep in &node.deps {
+            if dep
+                .dep_kinds
+                .iter()
+                .all(|k| k.kind == DependencyKind::Development)
+            {
+                continue;
+            }
+
+            let dep_package = packages.get(&dep.pkg).with_context(|| {
+                format!("package `{pkg}` is not in cargo metadata", pkg = dep.pkg)
+            })?;
+            let dep_ref = purl(dep_package);
+            depends_on.insert(dep_ref.clone());
+
+            components
+                .entry(dep_ref.clone())
+                .or_insert_with(|| crate_component(dep_package, &dep_ref));
+            stack.push((&dep.pkg, dep_ref));
+        }
+    }
+
+    // Add the component and WIT dependencies of the package
+    let depends_on = dependencies.entry(root_ref.clone()).or_default();
+    for (_, dependency) in resolution.all() {
+        let (bom_ref, component) = match dependency {
+            DependencyResolution::Registry(resolution) => {
+                let bom_ref = format!(
+                    "{package}@{version}",
+                    package = resolution.package,
+                    version = resolution.version
+                );
+
+                let digest = resolution.digest.to_string();
+                let (alg, content) = digest.split_once(':').unwrap_or(("sha256", &digest));
+
+                let component = json!({
+                    "type": "library",
+                    "bom-ref": bom_ref,
+                    "name": resolution.package.to_string(),
+                    "version": resolution.version.to_string(),
+                    "hashes": [{
+                        "alg": hash_algorithm(alg),
+                        "content": content,
+                    }],
+                    "properties": [
+                        {
+                            "name": "cargo-component:source",
+                            "value": "registry",
+                        },
+                        {
+                            "name": "cargo-component:registry",
+                            "value": resolution.registry.as_deref().unwrap_or("default"),
+                        },
+                    ],
+                });
+
+                (bom_ref, component)
+            }
+            DependencyResolution::Local(resolution) => {
+                let bom_ref = resolution.id.to_string();
+                let component = json!({
+                    "type": "library",
+                    "bom-ref": bom_ref,
+                    "name": resolution.id.to_string(),
+                    "properties": [{
+                        "name": "cargo-component:source",
+                        "value": "local",
+                    }],
+                });
+
+                (bom_ref, component)
+            }
+            DependencyResolution::Git(resolution) => {
+                let bom_ref = format!(
+                    "{id}@{commit}",
+                    id = resolution.id,
+                    commit = resolution.commit
+                );
+                let component = json!({
+                    "type": "library",
+                    "bom-ref": bom_ref,
+                    "name": resolution.id.to_string(),
+                    "version": resolution.commit,
+                    "externalReferences": [{
+                        "type": "vcs",
+                        "url": resolution.url,
+                    }],
+                    "properties": [
+                        {
+                            "name": "cargo-component:source",
+                            "value": "git",
+                        },
+                        {
+                            "name": "cargo-component:git-reference",
+                            "value": resolution.reference.to_string(),
+                        },
+                    ],
+                });
+
+                (bom_ref, component)
+            }
+        };
+
+        depends_on.insert(bom_ref.clone());
+        components.entry(bom_ref).or_insert(component);
+    }
+
+    let sbom = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "version": 1,
+        "metadata": {
+            "tools": [{
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            }],
+            "component": {
+                "type": "application",
+                "bom-ref": root_ref,
+                "name": id.to_string(),
+                "version": version.to_string(),
+                "purl": purl(package),
+            },
+        },
+        "components": components.into_values().collect::<Vec<_>>(),
+        "dependencies": dependencies
+            .into_iter()
+            .map(|(r, deps)| json!({ "ref": r, "dependsOn": deps }))
+            .collect::<Vec<_>>(),
+    });
+
+    serde_json::to_vec_pretty(&sbom).context("failed to serialize bill of materials")
+}
+
+/// Embeds the given bill of materials in a custom section of a component.
+pub fn add_to_wasm(bytes: &[u8], sbom: &[u8]) -> Vec<u8> {
+    let section = CustomSection {
+        name: SBOM_SECTION_NAME.into(),
+        data: sbom.into(),
+    };
+
+    let mut output = bytes.to_vec();
+    output.push(section.id());
+    section.encode(&mut output);
+    output
+}
+
+fn purl(package: &Package) -> String {
+    format!(
+        "pkg:cargo/{name}@{version}",
+        name = package.name,
+        version = package.version
+    )
+}
+
+fn crate_component(package: &Package, bom_ref: &str) -> Value {
+    let mut component = json!({
+        "type": "library",
+        "bom-ref": bom_ref,
+        "name": package.name,
+        "version": package.version.to_string(),
+        "purl": purl(package),
+    });
+
+    if let Some(license) = &package.license {
+        component["licenses"] = json!([{ "expression": license }]);
+    }
+
+    if let Some(source) = &package.source {
+        component["properties"] = json!([{
+            "name": "cargo-component:source",
+            "value": source.repr,
+        }]);
+    }
+
+    component
+}
+
+fn hash_algorithm(alg: &str) -> &str {
+    match alg {
+        "sha256" => "SHA-256",
+        "sha384" => "SHA-384",
+        "sha512" => "SHA-512",
+        alg => alg,
+    }
+}
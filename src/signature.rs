@@ -0,0 +1,200 @@
+//! Module for signatures embedded in published components.
+//!
+//! Unlike the signatures of registry log records, an embedded signature
+//! travels with the component itself, so its contents can be verified
+//! regardless of how the component was transferred.
+//!
+//! The signature is stored as JSON in the last custom section of the
+//! component and covers every byte of the component except that section.
+//! Components are signed with the same kind of key used to publish to a
+//! warg registry, which may be held by an external signer.
+
+use crate::minimize::read_u32;
+use anyhow::{bail, Context, Result};
+use cargo_component_core::signing::Signer;
+use serde::{Deserialize, Serialize};
+use warg_crypto::signing::{PublicKey, Signature};
+use wasm_encoder::{CustomSection, Encode, Section};
+
+/// The name of the custom section the signature is embedded in.
+pub const SIGNATURE_SECTION_NAME: &str = "component-signature";
+
+/// Represents a signature embedded in a component.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EmbeddedSignature {
+    /// The fingerprint of the public key.
+    pub key_id: String,
+    /// The public key that verifies the signature.
+    pub public_key: String,
+    /// The signature of the component.
+    pub signature: String,
+}
+
+impl EmbeddedSignature {
+    /// Gets the public key of the signature.
+    pub fn public_key(&self) -> Result<PublicKey> {
+        self.public_key.parse().with_context(|| {
+            format!(
+                "embedded signature has an invalid public key `{key}`",
+                key = self.public_key
+            )
+        })
+    }
+}
+
+/// Signs a component and embeds the signature in it.
+///
+/// Any signature already embedded in the component is replaced.
+pub fn sign(bytes: &[u8], signer: &Signer) -> Result<Vec<u8>> {
+    let mut output = strip(bytes)?;
+    let signature = signer.sign(&output).context("failed to sign component")?;
+
+    let public_key = signer.public_key();
+    let embedded = EmbeddedSignature {
+        key_id: public_key.fingerprint().to_string(),
+        public_key: public_key.to_string(),
+        signature: signature.to_string(),
+    };
+
+    let section = CustomSection {
+        name: SIGNATURE_SECTION_NAME.into(),
+        data: serde_json::to_vec(&embedded)?.into(),
+    };
+
+    output.push(section.id());
+    section.encode(&mut output);
+    Ok(output)
+}
+
+/// Verifies the signature embedded in a component.
+///
+/// If `trusted` is not empty, the signature must also have been made with one
+/// of the given keys.
+///
+/// Returns the embedded signature if it is valid.
+pub fn verify(bytes: &[u8], trusted: &[PublicKey]) -> Result<EmbeddedSignature> {
+    let (embedded, signed) = extract(bytes)?.context("component has no embedded signature")?;
+    let public_key = embedded.public_key()?;
+    if public_key.fingerprint().to_string() != embedded.key_id {
+        bail!(
+            "embedded signature has key id `{id}`, which does not match its public key",
+            id = embedded.key_id
+        );
+    }
+
+    if !trusted.is_empty()
+        && !trusted
+            .iter()
+            .any(|k| k.fingerprint().to_string() == embedded.key_id)
+    {
+        bail!(
+            "component was signed with key `{id}`, which is not a trusted key",
+            id = embedded.key_id
+        );
+    }
+
+    let signature: Signature = embedded.signature.parse().with_context(|| {
+        format!(
+            "embedded signature `{signature}` is invalid",
+            signature = embedded.signature
+        )
+    })?;
+
+    public_key.verify(&signed, &signature).map_err(|_| {
+        anyhow::anyhow!(
+            "embedded signature of key `{id}` does not match the contents of the component",
+            id = embedded.key_id
+        )
+    })?;
+
+    Ok(embedded)
+}
+
+/// Extracts the signature embedded in a component.
+///
+/// Returns the signature and the bytes of the component it covers, or `None`
+/// if the component is not signed.
+pub fn extract(bytes: &[u8]) -> Result<Option<(EmbeddedSignature, Vec<u8>)>> {
+    let mut embedded = None;
+    let mut signed = Vec::with_capacity(bytes.len());
+    for section in sections(bytes)? {
+        match section.signature {
+            Some(data) => {
+                let signature = serde_json::from_slice(data)
+                    .context("failed to parse the embedded signature of the component")?;
+                if embedded.replace(signature).is_some() {
+                    bail!("component has more than one embedded signature");
+                }
+            }
+            None => signed.extend_from_slice(section.bytes),
+        }
+    }
+
+    Ok(embedded.map(|e| (e, signed)))
+}
+
+/// Removes any embedded signature from a component.
+fn strip(bytes: &[u8]) -> Result<Vec<u8>> {
+    Ok(sections(bytes)?
+        .into_iter()
+        .filter(|s| s.signature.is_none())
+        .flat_map(|s| s.bytes.iter().copied())
+        .collect())
+}
+
+/// Represents a raw top-level section (or the header) of a WebAssembly binary.
+struct RawSection<'a> {
+    /// The bytes of the entire section.
+    bytes: &'a [u8],
+    /// The data of the section if it is an embedded signature.
+    signature: Option<&'a [u8]>,
+}
+
+/// Splits a WebAssembly binary into its header and top-level sections.
+fn sections(bytes: &[u8]) -> Result<Vec<RawSection<'_>>> {
+    const HEADER_LEN: usize = 8;
+
+    if bytes.len() < HEADER_LEN || &bytes[..4] != b"\0asm" {
+        bail!("input is not a WebAssembly binary");
+    }
+
+    let mut sections = vec![RawSection {
+        bytes: &bytes[..HEADER_LEN],
+        signature: None,
+    }];
+
+    let mut offset = HEADER_LEN;
+    while offset < bytes.len() {
+        let start = offset;
+        let id = bytes[offset];
+        offset += 1;
+
+        let size = read_u32(bytes, &mut offset)? as usize;
+        let end = offset
+            .checked_add(size)
+            .filter(|end| *end <= bytes.len())
+            .context("WebAssembly binary has a truncated section")?;
+
+        let mut signature = None;
+        if id == 0 {
+            let mut name_offset = offset;
+            let len = read_u32(bytes, &mut name_offset)? as usize;
+            let name = bytes
+                .get(name_offset..name_offset + len)
+                .filter(|_| name_offset + len <= end)
+                .context("WebAssembly binary has a malformed custom section")?;
+            if name == SIGNATURE_SECTION_NAME.as_bytes() {
+                signature = Some(&bytes[name_offset + len..end]);
+            }
+        }
+
+        sections.push(RawSection {
+            bytes: &bytes[start..end],
+            signature,
+        });
+        offset = end;
+    }
+
+    Ok(sections)
+}
@@ -0,0 +1,208 @@
+//! Module for reporting the size of a component by section.
+
+use crate::config::SizeReportFormat;
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::{fmt::Write, path::Path};
+use wasmparser::{Encoding, Name, NameSectionReader, Parser, Payload};
+
+/// The name prefix of the modules generated by `wit-component` to connect
+/// the core module and its adapters.
+const SHIM_MODULE_PREFIX: &str = "wit-component:";
+
+/// Represents a breakdown of the size of a component.
+#[derive(Debug, Serialize)]
+pub struct SizeReport {
+    /// The total size of the component, in bytes.
+    pub total: usize,
+    /// The size of each part of the component, in bytes.
+    ///
+    /// The parts add up to the total size.
+    pub parts: IndexMap<String, usize>,
+}
+
+/// The sizes of the sections of a core module.
+#[derive(Default)]
+struct ModuleSizes {
+    code: usize,
+    data: usize,
+    other: usize,
+    name: Option<String>,
+}
+
+impl ModuleSizes {
+    fn total(&self) -> usize {
+        self.code + self.data + self.other
+    }
+}
+
+/// A module or component being parsed.
+enum Frame {
+    Module(ModuleSizes),
+    Component { start: usize },
+}
+
+impl SizeReport {
+    /// Creates a size report for the given component.
+    ///
+    /// The first core module of the component is its core module; the other
+    /// core modules are its adapters, apart from the small shim modules that
+    /// `wit-component` generates to connect them. Nested components (e.g. the
+    /// dependencies of a composed component) are reported individually.
+    pub fn new(component: &[u8]) -> Result<Self> {
+        fn add(parts: &mut IndexMap<String, usize>, name: String, size: usize) {
+            *parts.entry(name).or_insert(0) += size;
+        }
+
+        let mut parts = IndexMap::new();
+
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut modules = 0;
+        let mut components = 0;
+        for payload in Parser::new(0).parse_all(component) {
+            let payload = payload.context("failed to parse component")?;
+            match &payload {
+                Payload::Version {
+                    encoding, range, ..
+                } => {
+                    stack.push(match encoding {
+                        Encoding::Module => Frame::Module(ModuleSizes::default()),
+                        Encoding::Component => Frame::Component { start: range.start },
+                    });
+                    continue;
+                }
+                Payload::End(offset) => {
+                    let frame = stack.pop().expect("frame should be present");
+                    // Only account for the direct children of the component
+                    if stack.len() != 1 {
+                        continue;
+                    }
+
+                    match frame {
+                        Frame::Module(sizes) => {
+                            let module = modules;
+                            modules += 1;
+                            if module == 0 {
+                                add(&mut parts, "core module code".to_string(), sizes.code);
+                                add(&mut parts, "core module data".to_string(), sizes.data);
+                                add(
+                                    &mut parts,
+                                    "core module other sections".to_string(),
+                                    sizes.other,
+                                );
+                            } else if sizes
+                                .name
+                                .as_deref()
+                                .map(|n| n.starts_with(SHIM_MODULE_PREFIX))
+                                .unwrap_or(false)
+                            {
+                                add(&mut parts, "adapter shims".to_string(), sizes.total());
+                            } else {
+                                add(&mut parts, "adapter".to_string(), sizes.total());
+                            }
+                        }
+                        Frame::Component { start } => {
+                            add(
+                                &mut parts,
+                                format!("nested component {components}"),
+                                *offset - start,
+                            );
+                            components += 1;
+                        }
+                    }
+
+                    continue;
+                }
+                _ => {}
+            }
+
+            let size = match payload.as_section() {
+                Some((_, range)) => range.len(),
+                None => continue,
+            };
+
+            let depth = stack.len();
+            match stack.last_mut() {
+                Some(Frame::Module(sizes)) => match &payload {
+                    Payload::CodeSectionStart { .. } => sizes.code += size,
+                    Payload::DataSection(_) => sizes.data += size,
+                    Payload::CustomSection(reader) => {
+                        if reader.name() == "name" {
+                            for name in NameSectionReader::new(reader.data(), reader.data_offset())
+                            {
+                                if let Ok(Name::Module { name, .. }) = name {
+                                    sizes.name = Some(name.to_string());
+                                }
+                            }
+                        }
+                        sizes.other += size;
+                    }
+                    _ => sizes.other += size,
+                },
+                Some(Frame::Component { .. }) if depth == 1 => {
+                    let name = match &payload {
+                        Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => {
+                            // Accounted for when the module or component ends
+                            continue;
+                        }
+                        Payload::CustomSection(reader) => {
+                            format!("metadata `{name}`", name = reader.name())
+                        }
+                        Payload::CoreTypeSection(_)
+                        | Payload::ComponentTypeSection(_)
+                        | Payload::ComponentImportSection(_)
+                        | Payload::ComponentExportSection(_) => "type information".to_string(),
+                        _ => "instantiation".to_string(),
+                    };
+
+                    add(&mut parts, name, size);
+                }
+                _ => {}
+            }
+        }
+
+        // Attribute the section headers and the like to the remainder
+        let accounted: usize = parts.values().sum();
+        parts.insert(
+            "other".to_string(),
+            component.len().saturating_sub(accounted),
+        );
+        parts.retain(|_, size| *size > 0);
+
+        Ok(Self {
+            total: component.len(),
+            parts,
+        })
+    }
+
+    /// Formats the report for the given component path.
+    pub fn format(&self, path: &Path, format: SizeReportFormat) -> Result<String> {
+        match format {
+            SizeReportFormat::Text => {
+                let width = self.parts.keys().map(String::len).max().unwrap_or(0);
+                let mut output = format!(
+                    "component `{path}`: {total} bytes\n",
+                    path = path.display(),
+                    total = self.total
+                );
+                for (name, size) in &self.parts {
+                    writeln!(
+                        output,
+                        "  {name:width$}  {size:>10}  {percent:>5.1}%",
+                        percent = *size as f64 * 100.0 / self.total.max(1) as f64
+                    )?;
+                }
+                Ok(output)
+            }
+            SizeReportFormat::Json => Ok(format!(
+                "{}\n",
+                serde_json::to_string(&serde_json::json!({
+                    "path": path,
+                    "total": self.total,
+                    "parts": self.parts,
+                }))?
+            )),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Module for collecting the WIT sources of a local target package.
+//!
+//! A local target is parsed from its `path` (the `wit` directory by default)
+//! and the files, directories, and glob patterns of its `include` field, so
+//! that the interfaces of one package may be organized in several
+//! directories (e.g. `wit/**/*.wit` and a shared `../common-wit`).
+//!
+//! Glob patterns support `*` and `?` within a path component and `**` for
+//! any number of directories; `deps` directories are skipped by `**`, as
+//! they contain the dependencies of a package rather than the package itself.
+
+use anyhow::{bail, Context, Result};
+use indexmap::{IndexMap, IndexSet};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use wit_parser::{SourceMap, UnresolvedPackage};
+
+/// Collects the WIT files of a local target from its path and includes.
+///
+/// Files matched more than once are only returned once.
+pub fn collect(path: Option<&Path>, include: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = IndexSet::new();
+    let mut add = |file: PathBuf| {
+        let file = fs::canonicalize(&file).unwrap_or(file);
+        files.insert(file);
+    };
+
+    if let Some(path) = path {
+        if path.is_dir() {
+            wit_files(path)?.into_iter().for_each(&mut add);
+        } else {
+            add(path.to_path_buf());
+        }
+    }
+
+    for entry in include {
+        if is_pattern(entry) {
+            let matches = expand(entry)?;
+            if matches.is_empty() {
+                bail!(
+                    "`include` pattern `{entry}` of the target did not match any WIT files",
+                    entry = entry.display()
+                );
+            }
+
+            matches.into_iter().for_each(&mut add);
+        } else if entry.is_dir() {
+            wit_files(entry)?.into_iter().for_each(&mut add);
+        } else if entry.is_file() {
+            add(entry.to_path_buf());
+        } else {
+            bail!(
+                "`include` path `{entry}` of the target does not exist",
+                entry = entry.display()
+            );
+        }
+    }
+
+    Ok(files.into_iter().collect())
+}
+
+/// Parses the given WIT files as a single package.
+///
+/// Before parsing, the top-level interfaces and worlds of the files are
+/// checked for definitions of the same name in more than one file.
+pub fn parse(files: &[PathBuf]) -> Result<UnresolvedPackage> {
+    let mut definitions: IndexMap<(&str, String), Vec<&Path>> = IndexMap::new();
+    let mut map = SourceMap::default();
+    for file in files {
+        let contents = fs::read_to_string(file)
+            .with_context(|| format!("failed to read WIT file `{path}`", path = file.display()))?;
+
+        for (kind, name) in top_level_items(&contents) {
+            definitions.entry((kind, name)).or_default().push(file);
+        }
+
+        map.push(file, contents);
+    }
+
+    for ((kind, name), files) in &definitions {
+        if files.len() > 1 {
+            bail!(
+                "{kind} `{name}` is defined more than once in the target package, in {files}",
+                files = files
+                    .iter()
+                    .map(|f| format!("`{f}`", f = f.display()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+
+    map.parse()
+        .context("failed to parse the WIT files of the target package")
+}
+
+/// Gets the path containing every file an include entry may match.
+///
+/// For a glob pattern, this is its longest leading path without wildcards.
+pub fn base(entry: &Path) -> PathBuf {
+    entry
+        .components()
+        .take_while(|c| !is_pattern(Path::new(c.as_os_str())))
+        .collect()
+}
+
+/// Gets the WIT files directly in a directory, sorted by name.
+fn wit_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+    {
+        let path = entry
+            .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+            .path();
+        if path.is_file() && path.extension().and_then(|e| e.to_str()) == Some("wit") {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Determines if an include entry is a glob pattern.
+fn is_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?'])
+}
+
+/// Expands a glob pattern to the WIT files matching it, sorted by path.
+fn expand(pattern: &Path) -> Result<Vec<PathBuf>> {
+    // Start from the longest leading path without wildcards
+    let base = base(pattern);
+    let mut components = Vec::new();
+    for component in pattern.components().skip(base.components().count()) {
+        match component {
+            Component::Normal(name) => components.push(name.to_string_lossy().into_owned()),
+            _ => bail!(
+                "`include` pattern `{pattern}` of the target may only have wildcards in normal path components",
+                pattern = pattern.display()
+            ),
+        }
+    }
+
+    let mut files = Vec::new();
+    if base.is_dir() {
+        visit(&base, &components, &mut files)?;
+    }
+
+    files.retain(|f: &PathBuf| f.extension().and_then(|e| e.to_str()) == Some("wit"));
+    files.sort();
+    files.dedup();
+    return Ok(files);
+
+    fn visit(dir: &Path, components: &[String], files: &mut Vec<PathBuf>) -> Result<()> {
+        let (first, rest) = match components.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        // `**` matches the directory itself and, recursively, its subdirectories
+        if first == "**" {
+            visit(dir, rest, files)?;
+        }
+
+        for entry in fs::read_dir(dir)
+            .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+        {
+            let path = entry
+                .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+                .path();
+            let name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if first == "**" {
+                if path.is_dir() && name != "deps" {
+                    visit(&path, components, files)?;
+                }
+            } else if matches(first, name) {
+                if rest.is_empty() {
+                    if path.is_file() {
+                        files.push(path);
+                    }
+                } else if path.is_dir() {
+                    visit(&path, rest, files)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Determines if a file name matches a pattern with `*` and `?` wildcards.
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // The positions to backtrack to for the last `*` seen
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Finds the names of the interfaces and worlds defined at the top level of
+/// a WIT file.
+///
+/// Top-level items are expected to start at the beginning of a line, as
+/// `wit fmt` formats them.
+fn top_level_items(contents: &str) -> Vec<(&'static str, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (kind, rest) = if let Some(rest) = line.strip_prefix("interface ") {
+                ("interface", rest)
+            } else if let Some(rest) = line.strip_prefix("world ") {
+                ("world", rest)
+            } else {
+                return None;
+            };
+
+            let name: String = rest
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '%')
+                .collect();
+            (!name.is_empty()).then_some((kind, name.trim_start_matches('%').to_string()))
+        })
+        .collect()
+}
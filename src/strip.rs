@@ -0,0 +1,105 @@
+//! Module for stripping custom sections from components.
+//!
+//! The DWARF debug information, name sections, and producers sections of a
+//! component (including those of its nested core modules and components) may
+//! each be kept, stripped, or split into a separate debug file. The debug file
+//! is a copy of the component before it was stripped, so traps in the
+//! stripped component can still be symbolized with it; the stripped component
+//! references it with an `external_debug_info` custom section.
+
+use crate::{
+    metadata::{SectionPolicies, SectionPolicy},
+    minimize::{read_u32, write_u32},
+};
+use anyhow::{bail, Context, Result};
+use wasm_encoder::{CustomSection, Encode, Section};
+
+/// The name of the custom section referencing a separate debug file.
+const EXTERNAL_DEBUG_INFO_SECTION_NAME: &str = "external_debug_info";
+
+/// The id of a custom section.
+const CUSTOM_SECTION_ID: u8 = 0;
+
+/// The id of a core module section of a component.
+const CORE_MODULE_SECTION_ID: u8 = 1;
+
+/// The id of a nested component section of a component.
+const COMPONENT_SECTION_ID: u8 = 4;
+
+/// Gets the policy of the given custom section.
+pub fn section_policy(policies: &SectionPolicies, name: &str) -> SectionPolicy {
+    match name {
+        "name" | "component-name" => policies.name_section,
+        "producers" => policies.producers,
+        name if name.starts_with(".debug_") => policies.debug_info,
+        _ => SectionPolicy::Keep,
+    }
+}
+
+/// Removes the custom sections of a module or component for which the given
+/// function returns `true`.
+///
+/// The sections of nested core modules and components are also removed.
+pub fn strip_sections(bytes: &[u8], remove: &dyn Fn(&str) -> bool) -> Result<Vec<u8>> {
+    if bytes.len() < 8 || &bytes[..4] != b"\0asm" {
+        bail!("input is not a WebAssembly module or component");
+    }
+
+    // The layer of the binary format follows the version
+    let component = bytes[6] == 1;
+    let mut output = bytes[..8].to_vec();
+    let mut offset = 8;
+    while offset < bytes.len() {
+        let id = bytes[offset];
+        offset += 1;
+        let size = read_u32(bytes, &mut offset)? as usize;
+        let contents = bytes
+            .get(offset..offset + size)
+            .context("WebAssembly binary has a truncated section")?;
+        offset += size;
+
+        match id {
+            CUSTOM_SECTION_ID => {
+                let mut pos = 0;
+                let len = read_u32(contents, &mut pos)? as usize;
+                let name = contents
+                    .get(pos..pos + len)
+                    .and_then(|n| std::str::from_utf8(n).ok())
+                    .context("WebAssembly binary has an invalid custom section name")?;
+                if remove(name) {
+                    continue;
+                }
+            }
+            CORE_MODULE_SECTION_ID | COMPONENT_SECTION_ID if component => {
+                let nested = strip_sections(contents, remove)?;
+                output.push(id);
+                write_u32(&mut output, nested.len() as u32);
+                output.extend_from_slice(&nested);
+                continue;
+            }
+            _ => {}
+        }
+
+        output.push(id);
+        write_u32(&mut output, size as u32);
+        output.extend_from_slice(contents);
+    }
+
+    Ok(output)
+}
+
+/// Adds a custom section referencing a separate debug file to a component.
+pub fn add_external_debug_info(bytes: &[u8], file_name: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    file_name.encode(&mut data);
+
+    let section = CustomSection {
+        name: EXTERNAL_DEBUG_INFO_SECTION_NAME.into(),
+        data: data.into(),
+    };
+
+    let mut output = bytes.to_vec();
+    output.push(section.id());
+    section.encode(&mut output);
+    output
+}
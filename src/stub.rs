@@ -0,0 +1,799 @@
+//! Module for generating `todo!()` stubs for the unimplemented exports of a
+//! component's target world.
+//!
+//! The sources of the crate are scanned for implementations of the export
+//! traits of the world. Implementations that are missing are appended to the
+//! crate root and functions missing from an existing implementation (e.g.
+//! after the WIT gained a function) are inserted into it.
+//!
+//! The scan is textual rather than a full parse: it understands `use`
+//! declarations, but not implementations that are expanded from macros.
+
+use crate::generator::print_stub_func;
+use anyhow::{Context, Result};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use wit_parser::{
+    Function, FunctionKind, Resolve, TypeDefKind, TypeId, WorldId, WorldItem, WorldKey,
+};
+
+/// Represents a stub to add to the sources of a crate.
+pub struct Stub {
+    /// The path of the source file to add the stub to.
+    pub path: PathBuf,
+    /// The offset in the source file to insert the stub at.
+    ///
+    /// If `None`, the stub is appended to the source file.
+    pub offset: Option<usize>,
+    /// The source of the stub.
+    pub source: String,
+    /// A description of what was stubbed.
+    pub description: String,
+}
+
+/// Generates the stubs for the exports of the given world that are not
+/// implemented by the crate with the given root source file.
+///
+/// Every Rust source file in the directory of the crate root is scanned.
+pub fn generate(resolve: &Resolve, world: WorldId, root: &Path) -> Result<Vec<Stub>> {
+    let dir = root.parent().unwrap_or_else(|| Path::new("."));
+    let mut paths = Vec::new();
+    find_sources(dir, &mut paths)?;
+
+    let mut scans = Vec::new();
+    for path in paths {
+        let source = fs::read_to_string(&path).with_context(|| {
+            format!("failed to read source file `{path}`", path = path.display())
+        })?;
+        scans.push((path, scan(&source)));
+    }
+
+    let declared: HashSet<&str> = scans
+        .iter()
+        .flat_map(|(_, s)| s.declared.iter().map(String::as_str))
+        .collect();
+
+    let mut stubs = Vec::new();
+    let mut structs = Vec::new();
+    let mut impls = Vec::new();
+    for export in export_traits(resolve, world) {
+        let trait_path = export.path.join("::");
+        let existing = scans.iter().find_map(|(path, scan)| {
+            scan.impls
+                .iter()
+                .find(|imp| imp.implements(&export.path))
+                .map(|imp| (path, imp))
+        });
+
+        match existing {
+            Some((path, imp)) => {
+                let missing: Vec<_> = export
+                    .functions
+                    .iter()
+                    .filter(|f| !imp.functions.contains(&function_name(f)))
+                    .collect();
+
+                for func in missing {
+                    let mut source = String::from("\n");
+                    print_stub_func(resolve, world, func, &mut source)?;
+                    stubs.push(Stub {
+                        path: path.clone(),
+                        offset: Some(imp.end),
+                        source,
+                        description: format!(
+                            "function `{name}` of `{trait_path}`",
+                            name = function_name(func)
+                        ),
+                    });
+                }
+            }
+            None => {
+                if !declared.contains(export.implementor.as_str())
+                    && !structs.contains(&export.implementor)
+                {
+                    structs.push(export.implementor.clone());
+                }
+
+                let mut source = String::new();
+                writeln!(
+                    source,
+                    "impl {trait_path} for {implementor} {{",
+                    implementor = export.implementor
+                )?;
+                for (i, func) in export.functions.iter().enumerate() {
+                    if i > 0 {
+                        source.push('\n');
+                    }
+                    print_stub_func(resolve, world, func, &mut source)?;
+                }
+                source.push_str("}\n");
+
+                impls.push((
+                    source,
+                    format!(
+                        "implementation of `{trait_path}` for `{implementor}`",
+                        implementor = export.implementor
+                    ),
+                ));
+            }
+        }
+    }
+
+    for name in structs {
+        stubs.push(Stub {
+            path: root.to_path_buf(),
+            offset: None,
+            source: format!("\nstruct {name};\n"),
+            description: format!("type `{name}`"),
+        });
+    }
+
+    for (source, description) in impls {
+        stubs.push(Stub {
+            path: root.to_path_buf(),
+            offset: None,
+            source: format!("\n{source}"),
+            description,
+        });
+    }
+
+    Ok(stubs)
+}
+
+/// Adds the given stubs to the sources of the crate.
+///
+/// Returns the paths of the source files that were modified.
+pub fn apply(stubs: &[Stub]) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<(&Path, Vec<&Stub>)> = Vec::new();
+    for stub in stubs {
+        match files.iter_mut().find(|(p, _)| *p == stub.path) {
+            Some((_, stubs)) => stubs.push(stub),
+            None => files.push((&stub.path, vec![stub])),
+        }
+    }
+
+    let mut modified = Vec::new();
+    for (path, stubs) in files {
+        let mut source = fs::read_to_string(path).with_context(|| {
+            format!("failed to read source file `{path}`", path = path.display())
+        })?;
+
+        // Insert from the end of the file so that earlier offsets remain valid
+        let mut insertions: Vec<_> = stubs
+            .iter()
+            .filter_map(|s| s.offset.map(|o| (o, s.source.as_str())))
+            .collect();
+        insertions.sort_by(|a, b| b.0.cmp(&a.0));
+        for (offset, stub) in insertions {
+            source.insert_str(offset, stub);
+        }
+
+        for stub in stubs.iter().filter(|s| s.offset.is_none()) {
+            if !source.is_empty() && !source.ends_with('\n') {
+                source.push('\n');
+            }
+            source.push_str(&stub.source);
+        }
+
+        fs::write(path, source).with_context(|| {
+            format!(
+                "failed to write source file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        modified.push(path.to_path_buf());
+    }
+
+    Ok(modified)
+}
+
+/// Represents an export trait that a crate must implement.
+pub(crate) struct ExportTrait<'a> {
+    /// The path of the trait, starting with the `bindings` module.
+    pub path: Vec<String>,
+    /// The name of the type that implements the trait.
+    pub implementor: String,
+    /// The functions of the trait.
+    pub functions: Vec<&'a Function>,
+}
+
+/// Gets the export traits of the given world.
+///
+/// This mirrors the export traits and implementors used by the generated
+/// bindings (see the `emit` module).
+pub(crate) fn export_traits(resolve: &Resolve, world: WorldId) -> Vec<ExportTrait<'_>> {
+    let mut traits = Vec::new();
+    let mut functions = Vec::new();
+    let mut resources = Vec::new();
+
+    for (key, item) in &resolve.worlds[world].exports {
+        match item {
+            WorldItem::Function(f) => functions.push(f),
+            WorldItem::Interface(id) => {
+                let interface = &resolve.interfaces[*id];
+                let mut module = vec!["bindings".to_string(), "exports".to_string()];
+                match key {
+                    WorldKey::Name(name) => module.push(name.to_snake_case()),
+                    WorldKey::Interface(_) => {
+                        let pkg = &resolve.packages
+                            [interface.package.expect("interface should have a package")];
+                        module.extend([
+                            pkg.name.namespace.to_snake_case(),
+                            pkg.name.name.to_snake_case(),
+                            interface
+                                .name
+                                .as_deref()
+                                .expect("unnamed interface")
+                                .to_snake_case(),
+                        ]);
+                    }
+                }
+
+                traits.push(ExportTrait {
+                    path: module
+                        .iter()
+                        .cloned()
+                        .chain(["Guest".to_string()])
+                        .collect(),
+                    implementor: "Component".to_string(),
+                    functions: interface
+                        .functions
+                        .values()
+                        .filter(|f| resource_of(f).is_none())
+                        .collect(),
+                });
+
+                for (name, ty) in &interface.types {
+                    if let TypeDefKind::Resource = resolve.types[*ty].kind {
+                        let name = name.to_upper_camel_case();
+                        traits.push(ExportTrait {
+                            path: module
+                                .iter()
+                                .cloned()
+                                .chain([format!("Guest{name}")])
+                                .collect(),
+                            implementor: name,
+                            functions: interface
+                                .functions
+                                .values()
+                                .filter(|f| resource_of(f) == Some(*ty))
+                                .collect(),
+                        });
+                    }
+                }
+            }
+            WorldItem::Type(id) => {
+                if let (TypeDefKind::Resource, WorldKey::Name(name)) =
+                    (&resolve.types[*id].kind, key)
+                {
+                    resources.push((*id, name.to_upper_camel_case()));
+                }
+            }
+        }
+    }
+
+    if functions.iter().any(|f| resource_of(f).is_none()) {
+        traits.push(ExportTrait {
+            path: vec!["bindings".to_string(), "Guest".to_string()],
+            implementor: "Component".to_string(),
+            functions: functions
+                .iter()
+                .copied()
+                .filter(|f| resource_of(f).is_none())
+                .collect(),
+        });
+    }
+
+    for (id, name) in resources {
+        traits.push(ExportTrait {
+            path: vec!["bindings".to_string(), format!("Guest{name}")],
+            implementor: name,
+            functions: functions
+                .iter()
+                .copied()
+                .filter(|f| resource_of(f) == Some(id))
+                .collect(),
+        });
+    }
+
+    traits
+}
+
+/// Gets the resource a function belongs to, if any.
+fn resource_of(func: &Function) -> Option<TypeId> {
+    match func.kind {
+        FunctionKind::Freestanding => None,
+        FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+            Some(id)
+        }
+    }
+}
+
+/// Gets the name of the Rust function of an export trait for a WIT function.
+pub(crate) fn function_name(func: &Function) -> String {
+    match func.kind {
+        FunctionKind::Freestanding => wit_bindgen_rust::to_rust_ident(&func.name),
+        FunctionKind::Constructor(_) => "new".to_string(),
+        FunctionKind::Method(_) | FunctionKind::Static(_) => {
+            wit_bindgen_rust::to_rust_ident(func.item_name())
+        }
+    }
+}
+
+/// Recursively finds the Rust source files in a directory.
+pub(crate) fn find_sources(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| {
+        format!(
+            "failed to read source directory `{path}`",
+            path = dir.display()
+        )
+    })?;
+
+    let mut entries = entries
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| {
+            format!(
+                "failed to read source directory `{path}`",
+                path = dir.display()
+            )
+        })?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            find_sources(&path, paths)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("rs") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Represents an implementation of a trait found in a source file.
+struct Impl {
+    /// The possible paths of the implemented trait.
+    ///
+    /// There is more than one candidate when the trait may have been
+    /// imported by a glob `use` declaration.
+    candidates: Vec<Vec<String>>,
+    /// The names of the functions of the implementation.
+    functions: HashSet<String>,
+    /// The offset of the closing brace of the implementation.
+    end: usize,
+}
+
+impl Impl {
+    /// Determines if this implementation is of the trait with the given path.
+    fn implements(&self, path: &[String]) -> bool {
+        // A single segment can't distinguish the traits of different interfaces
+        self.candidates
+            .iter()
+            .any(|c| c.len() >= 2 && path.ends_with(c))
+    }
+}
+
+/// Represents the result of scanning a source file.
+#[derive(Default)]
+struct Scan {
+    /// The trait implementations in the file.
+    impls: Vec<Impl>,
+    /// The names of the types declared or imported by the file.
+    declared: HashSet<String>,
+}
+
+/// Scans a source file for trait implementations and type declarations.
+fn scan(source: &str) -> Scan {
+    let tokens = tokenize(source);
+    let mut aliases = HashMap::new();
+    let mut globs = Vec::new();
+    let mut found = Vec::new();
+    let mut scan = Scan::default();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i].text {
+            "use" => {
+                i += 1;
+                parse_use_tree(&tokens, &mut i, Vec::new(), &mut aliases, &mut globs);
+                continue;
+            }
+            "impl" => {
+                if let Some(imp) = parse_impl(&tokens, i) {
+                    found.push(imp);
+                }
+            }
+            "struct" | "enum" | "union" | "type" => {
+                if let Some(name) = tokens.get(i + 1).filter(|t| is_ident(t.text)) {
+                    scan.declared.insert(name.text.to_string());
+                }
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    for (alias, path) in &aliases {
+        if path.first().map(String::as_str) != Some("bindings") {
+            scan.declared.insert(alias.clone());
+        }
+    }
+
+    scan.impls = found
+        .into_iter()
+        .map(|(path, functions, end)| {
+            let path = normalize(path);
+            let mut candidates = Vec::new();
+            match path.first().and_then(|s| aliases.get(s)) {
+                Some(alias) => {
+                    candidates.push(alias.iter().chain(&path[1..]).cloned().collect());
+                }
+                None => {
+                    if path.len() == 1 {
+                        for glob in &globs {
+                            candidates.push(glob.iter().chain(&path).cloned().collect());
+                        }
+                    }
+                    candidates.push(path);
+                }
+            }
+
+            Impl {
+                candidates,
+                functions,
+                end,
+            }
+        })
+        .collect();
+
+    scan
+}
+
+/// Removes the leading `crate`, `self`, and `super` segments of a path.
+fn normalize(path: Vec<String>) -> Vec<String> {
+    path.into_iter()
+        .skip_while(|s| s == "crate" || s == "self" || s == "super")
+        .collect()
+}
+
+/// Parses a `use` tree, recording the names it imports.
+fn parse_use_tree(
+    tokens: &[Token],
+    i: &mut usize,
+    mut path: Vec<String>,
+    aliases: &mut HashMap<String, Vec<String>>,
+    globs: &mut Vec<Vec<String>>,
+) {
+    while let Some(token) = tokens.get(*i) {
+        match token.text {
+            "::" => *i += 1,
+            "{" => {
+                *i += 1;
+                while let Some(token) = tokens.get(*i) {
+                    match token.text {
+                        "}" => {
+                            *i += 1;
+                            break;
+                        }
+                        "," => *i += 1,
+                        ";" => return,
+                        _ => parse_use_tree(tokens, i, path.clone(), aliases, globs),
+                    }
+                }
+                return;
+            }
+            "*" => {
+                *i += 1;
+                globs.push(normalize(path));
+                return;
+            }
+            "self" if !path.is_empty() => {
+                *i += 1;
+                let name = path.last().unwrap().clone();
+                let name = parse_alias(tokens, i).unwrap_or(name);
+                aliases.insert(name, normalize(path));
+                return;
+            }
+            text if is_ident(text) => {
+                *i += 1;
+                path.push(text.to_string());
+                if tokens.get(*i).map(|t| t.text) == Some("::") {
+                    continue;
+                }
+
+                let name = parse_alias(tokens, i).unwrap_or_else(|| text.to_string());
+                if name != "_" {
+                    aliases.insert(name, normalize(path));
+                }
+                return;
+            }
+            _ => return,
+        }
+    }
+}
+
+/// Parses an optional `as` alias of a `use` tree.
+fn parse_alias(tokens: &[Token], i: &mut usize) -> Option<String> {
+    if tokens.get(*i).map(|t| t.text) != Some("as") {
+        return None;
+    }
+
+    let alias = tokens.get(*i + 1)?.text.to_string();
+    *i += 2;
+    Some(alias)
+}
+
+/// Parses a trait implementation starting at the given `impl` token.
+///
+/// Returns the path of the trait, the names of the implementation's functions,
+/// and the offset of its closing brace.
+fn parse_impl(tokens: &[Token], start: usize) -> Option<(Vec<String>, HashSet<String>, usize)> {
+    let mut i = start + 1;
+    let mut depth = 0;
+    let mut path = Vec::new();
+    let mut is_trait = false;
+
+    // Skip the generic parameters of the implementation
+    if tokens.get(i)?.text == "<" {
+        loop {
+            match tokens.get(i)?.text {
+                "<" => depth += 1,
+                ">" if !is_arrow(tokens, i) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        i += 1;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    // Read the path of the trait, ignoring any generic arguments
+    loop {
+        let token = tokens.get(i)?;
+        match token.text {
+            "{" | ";" => break,
+            "<" => depth += 1,
+            ">" if !is_arrow(tokens, i) => depth -= 1,
+            "for" if depth == 0 && !is_trait => is_trait = true,
+            text if depth == 0 && !is_trait && is_ident(text) => path.push(text.to_string()),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if !is_trait || path.is_empty() || tokens[i].text != "{" {
+        return None;
+    }
+
+    let mut functions = HashSet::new();
+    let mut depth = 0;
+    loop {
+        let token = tokens.get(i)?;
+        match token.text {
+            "{" => depth += 1,
+            "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((path, functions, token.offset));
+                }
+            }
+            "fn" if depth == 1 => {
+                if let Some(name) = tokens.get(i + 1).filter(|t| is_ident(t.text)) {
+                    functions.insert(name.text.to_string());
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Determines if the `>` token at the given index is part of a `->`.
+fn is_arrow(tokens: &[Token], i: usize) -> bool {
+    i > 0 && tokens[i - 1].text == "-" && tokens[i - 1].offset + 1 == tokens[i].offset
+}
+
+/// Represents a token of Rust source.
+struct Token<'a> {
+    /// The text of the token.
+    text: &'a str,
+    /// The offset of the token in the source.
+    offset: usize,
+}
+
+/// Determines if the given token text is an identifier.
+fn is_ident(text: &str) -> bool {
+    text.bytes()
+        .next()
+        .map(|b| b == b'_' || b.is_ascii_alphabetic() || !b.is_ascii())
+        .unwrap_or(false)
+}
+
+/// Determines if the given byte may be part of an identifier.
+fn is_ident_byte(b: u8) -> bool {
+    b == b'_' || b.is_ascii_alphanumeric() || !b.is_ascii()
+}
+
+/// Splits Rust source into identifiers, path separators, and punctuation.
+///
+/// Comments, literals, and lifetimes are skipped.
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match b {
+            b'/' if next == Some(b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if next == Some(b'*') => {
+                let mut depth = 0;
+                while i < bytes.len() {
+                    if bytes[i..].starts_with(b"/*") {
+                        depth += 1;
+                        i += 2;
+                    } else if bytes[i..].starts_with(b"*/") {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            b'"' => i = skip_string(bytes, i + 1),
+            b'\'' => i = skip_char_or_lifetime(bytes, i),
+            b':' if next == Some(b':') => {
+                tokens.push(Token {
+                    text: &source[i..i + 2],
+                    offset: i,
+                });
+                i += 2;
+            }
+            b if b.is_ascii_digit() => {
+                while i < bytes.len() && is_ident_byte(bytes[i]) {
+                    i += 1;
+                }
+            }
+            b if is_ident_byte(b) => {
+                let start = i;
+                while i < bytes.len() && is_ident_byte(bytes[i]) {
+                    i += 1;
+                }
+
+                let text = &source[start..i];
+                match (text, bytes.get(i).copied()) {
+                    ("b", Some(b'"')) => i = skip_string(bytes, i + 1),
+                    ("b", Some(b'\'')) => i = skip_char_or_lifetime(bytes, i),
+                    ("r" | "br", Some(b'"')) => i = skip_raw_string(bytes, i),
+                    ("r" | "br", Some(b'#'))
+                        if !bytes
+                            .get(i + 1)
+                            .copied()
+                            .map(is_ident_byte)
+                            .unwrap_or(false) =>
+                    {
+                        i = skip_raw_string(bytes, i)
+                    }
+                    // Raw identifiers
+                    ("r", Some(b'#')) => {
+                        let start = i + 1;
+                        i = start;
+                        while i < bytes.len() && is_ident_byte(bytes[i]) {
+                            i += 1;
+                        }
+                        tokens.push(Token {
+                            text: &source[start..i],
+                            offset: start,
+                        });
+                    }
+                    _ => tokens.push(Token {
+                        text,
+                        offset: start,
+                    }),
+                }
+            }
+            b if b.is_ascii_whitespace() => i += 1,
+            _ => {
+                tokens.push(Token {
+                    text: &source[i..i + 1],
+                    offset: i,
+                });
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Skips a string literal, returning the offset after its closing quote.
+fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+
+    i
+}
+
+/// Skips a raw string literal starting at its hashes or opening quote.
+fn skip_raw_string(bytes: &[u8], mut i: usize) -> usize {
+    let mut hashes = 0;
+    while bytes.get(i) == Some(&b'#') {
+        hashes += 1;
+        i += 1;
+    }
+
+    // Skip the opening quote
+    i += 1;
+    while i < bytes.len() {
+        if bytes[i] == b'"'
+            && bytes[i + 1..]
+                .iter()
+                .take(hashes)
+                .filter(|b| **b == b'#')
+                .count()
+                == hashes
+        {
+            return i + 1 + hashes;
+        }
+        i += 1;
+    }
+
+    i
+}
+
+/// Skips a character literal or a lifetime starting at its quote.
+fn skip_char_or_lifetime(bytes: &[u8], i: usize) -> usize {
+    if bytes.get(i + 1) == Some(&b'\\') {
+        // An escaped character literal
+        let mut j = i + 3;
+        while j < bytes.len() && bytes[j] != b'\'' {
+            j += 1;
+        }
+        return j + 1;
+    }
+
+    // Find the length of the (possibly multi-byte) character after the quote
+    let len = match bytes.get(i + 1) {
+        Some(b) if *b < 0x80 => 1,
+        Some(b) if *b >= 0xf0 => 4,
+        Some(b) if *b >= 0xe0 => 3,
+        Some(_) => 2,
+        None => return i + 1,
+    };
+
+    if bytes.get(i + 1 + len) == Some(&b'\'') {
+        return i + 2 + len;
+    }
+
+    // A lifetime or label
+    let mut j = i + 1;
+    while j < bytes.len() && is_ident_byte(bytes[j]) {
+        j += 1;
+    }
+    j
+}
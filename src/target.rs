@@ -7,35 +7,37 @@ use std::{
 
 use crate::config::Config;
 
-pub fn install_wasm32_wasi(config: &Config) -> Result<()> {
+/// Installs the given WebAssembly target with `rustup` if the toolchain
+/// does not already have it.
+pub fn install_wasm_target(config: &Config, target: &str) -> Result<()> {
     let sysroot = get_sysroot()?;
-    if sysroot.join("lib/rustlib/wasm32-wasi").exists() {
+    if sysroot.join("lib/rustlib").join(target).exists() {
         return Ok(());
     }
 
     if env::var_os("RUSTUP_TOOLCHAIN").is_none() {
         bail!(
-            "failed to find the `wasm32-wasi` target \
+            "failed to find the `{target}` target \
              and `rustup` is not available. If you're using rustup \
              make sure that it's correctly installed; if not, make sure to \
-             install the `wasm32-wasi` target before using this command"
+             install the `{target}` target before using this command"
         );
     }
 
     config
         .terminal()
-        .status("Installing", "wasm32-wasi target")?;
+        .status("Installing", format!("{target} target"))?;
 
     let output = Command::new("rustup")
         .arg("target")
         .arg("add")
-        .arg("wasm32-wasi")
+        .arg(target)
         .stderr(Stdio::inherit())
         .stdout(Stdio::inherit())
         .output()?;
 
     if !output.status.success() {
-        bail!("failed to install the `wasm32-wasi` target");
+        bail!("failed to install the `{target}` target; make sure the toolchain supports it");
     }
 
     Ok(())
@@ -0,0 +1,184 @@
+//! Module for the project templates used by `cargo component new`.
+//!
+//! A template is a set of files that are copied into a new package after it
+//! has been generated, replacing any generated file with the same path. The
+//! paths and (UTF-8) contents of the files may use the following variables:
+//!
+//! * `{{name}}` - the name of the component package (e.g. `my-component`).
+//! * `{{namespace}}` - the namespace of the component package.
+//! * `{{target}}` - the target world of the component, if any.
+//!
+//! References to other variables are left as they are.
+//!
+//! A template may come from a local directory, a git repository, or a
+//! registry package. A template package is a WebAssembly file with a
+//! `cargo-component-template` custom section containing a JSON object that
+//! maps the relative path of each file to its contents.
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::git::CHECKOUT_COMPLETE_FILE;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use wasmparser::{Parser, Payload};
+
+/// The name of the custom section containing the files of a template package.
+pub const TEMPLATE_SECTION_NAME: &str = "cargo-component-template";
+
+/// Gets the git repository URL of a template, if it refers to one.
+///
+/// A template refers to a git repository if it is prefixed with `git+` or is
+/// an HTTP, SSH, or scp-like (`git@host:path`) URL.
+pub fn git_url(template: &str) -> Option<&str> {
+    if let Some(url) = template.strip_prefix("git+") {
+        return Some(url);
+    }
+
+    ["https://", "http://", "ssh://", "git@"]
+        .iter()
+        .any(|prefix| template.starts_with(prefix))
+        .then_some(template)
+}
+
+/// Represents a project template.
+#[derive(Debug)]
+pub struct Template {
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl Template {
+    /// Loads a template from the files of a directory.
+    ///
+    /// Version control directories are not part of the template.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        fn visit(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, Vec<u8>)>) -> Result<()> {
+            let mut entries = fs::read_dir(dir)
+                .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+                .collect::<std::io::Result<Vec<_>>>()
+                .with_context(|| {
+                    format!("failed to read directory `{dir}`", dir = dir.display())
+                })?;
+            entries.sort_by_key(|e| e.file_name());
+
+            for entry in entries {
+                let path = entry.path();
+                let name = entry.file_name();
+                if name == ".git" || name == CHECKOUT_COMPLETE_FILE {
+                    continue;
+                }
+
+                if path.is_dir() {
+                    visit(root, &path, files)?;
+                    continue;
+                }
+
+                let contents = fs::read(&path).with_context(|| {
+                    format!(
+                        "failed to read template file `{path}`",
+                        path = path.display()
+                    )
+                })?;
+                files.push((
+                    path.strip_prefix(root)
+                        .expect("path should be in the template directory")
+                        .to_path_buf(),
+                    contents,
+                ));
+            }
+
+            Ok(())
+        }
+
+        let mut files = Vec::new();
+        visit(dir, dir, &mut files)?;
+
+        if files.is_empty() {
+            bail!(
+                "template directory `{dir}` does not contain any files",
+                dir = dir.display()
+            );
+        }
+
+        Ok(Self { files })
+    }
+
+    /// Loads a template from the contents of a template package.
+    pub fn from_package(bytes: &[u8]) -> Result<Self> {
+        for payload in Parser::new(0).parse_all(bytes) {
+            if let Payload::CustomSection(reader) = payload.context("failed to parse package")? {
+                if reader.name() != TEMPLATE_SECTION_NAME {
+                    continue;
+                }
+
+                let files: BTreeMap<String, String> = serde_json::from_slice(reader.data())
+                    .with_context(|| {
+                        format!("failed to parse the `{TEMPLATE_SECTION_NAME}` custom section")
+                    })?;
+
+                return Ok(Self {
+                    files: files
+                        .into_iter()
+                        .map(|(path, contents)| (PathBuf::from(path), contents.into_bytes()))
+                        .collect(),
+                });
+            }
+        }
+
+        bail!("package does not have a `{TEMPLATE_SECTION_NAME}` custom section")
+    }
+
+    /// Renders the files of the template with the given variables.
+    ///
+    /// The paths of the returned files are relative to the package directory.
+    pub fn render(&self, vars: &HashMap<&str, String>) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        self.files
+            .iter()
+            .map(|(path, contents)| {
+                let display = path.display();
+                let rendered = PathBuf::from(substitute(&path.to_string_lossy(), vars));
+
+                if !rendered
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+                {
+                    bail!("template file `{display}` is not relative to the package directory");
+                }
+
+                // Files that aren't text are copied as they are
+                let contents = match std::str::from_utf8(contents) {
+                    Ok(text) => substitute(text, vars).into_bytes(),
+                    Err(_) => contents.clone(),
+                };
+
+                Ok((rendered, contents))
+            })
+            .collect()
+    }
+}
+
+/// Substitutes the `{{variable}}` references of a string.
+///
+/// References to unknown variables are left as they are, so that templates
+/// can contain files that use the same syntax (e.g. GitHub Actions workflows).
+fn substitute(s: &str, vars: &HashMap<&str, String>) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+            break;
+        };
+
+        result.push_str(&rest[..start]);
+        match vars.get(rest[start + 2..end].trim()) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..end + 2]),
+        }
+
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    result
+}
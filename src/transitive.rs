@@ -0,0 +1,184 @@
+//! Module for controlling how the interfaces used by exports are provided.
+//!
+//! An exported interface that uses the types of another interface (e.g. with
+//! `use my:types/shapes.{shape}`) requires the component to either import the
+//! other interface, referencing it by its versioned name, or to also export
+//! it, in which case the exported interfaces share the exported types.
+//!
+//! The `transitive` table of a target entry chooses between the two for each
+//! interface by name: `use` imports the interface and `export` re-exports it.
+//! Choices that would leave an imported item using the types of an exported
+//! interface are rejected, as a component cannot import types it defines.
+
+use crate::{metadata::TransitiveInterface, remap::names};
+use anyhow::{bail, Result};
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashMap;
+use wit_parser::{
+    InterfaceId, Resolve, Type, TypeDefKind, TypeOwner, WorldId, WorldItem, WorldKey,
+};
+
+/// Applies the `transitive` table of a target entry to its target world.
+pub fn apply(
+    resolve: &mut Resolve,
+    world: WorldId,
+    transitive: &HashMap<String, TransitiveInterface>,
+) -> Result<()> {
+    if transitive.is_empty() {
+        return Ok(());
+    }
+
+    let used = used_by_exports(resolve, world);
+
+    let mut entries: Vec<_> = transitive.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut exported = IndexSet::new();
+    for (name, choice) in entries {
+        let id = match used
+            .iter()
+            .copied()
+            .find(|id| names(resolve, *id).contains(name))
+        {
+            Some(id) => id,
+            None => bail!(
+                "interface `{name}` in `package.metadata.component.target.transitive` is not used by an export of the target world"
+            ),
+        };
+
+        let key = WorldKey::Interface(id);
+        let target = &mut resolve.worlds[world];
+        match choice {
+            TransitiveInterface::Use => {
+                if target.exports.contains_key(&key) {
+                    bail!(
+                        "interface `{name}` is set to `use` in `package.metadata.component.target.transitive`, but the target world exports it"
+                    );
+                }
+            }
+            TransitiveInterface::Export => {
+                target.imports.shift_remove(&key);
+                if !target.exports.contains_key(&key) {
+                    exported.insert(id);
+                }
+            }
+        }
+    }
+
+    // Every other used interface is imported
+    for id in &used {
+        let key = WorldKey::Interface(*id);
+        let target = &mut resolve.worlds[world];
+        if !exported.contains(id) && !target.exports.contains_key(&key) {
+            target
+                .imports
+                .entry(key)
+                .or_insert(WorldItem::Interface(*id));
+        }
+    }
+
+    // An imported item cannot use the types of an exported interface
+    let target = &resolve.worlds[world];
+    for (key, item) in &target.imports {
+        let deps = match item {
+            WorldItem::Interface(id) => dependencies(resolve, *id),
+            WorldItem::Type(ty) => type_owner(resolve, *ty).into_iter().collect(),
+            WorldItem::Function(_) => continue,
+        };
+
+        if let Some(dep) = deps.iter().find(|dep| exported.contains(*dep)) {
+            bail!(
+                "imported `{import}` uses the types of interface `{dep}`, which is set to `export` in `package.metadata.component.target.transitive`; set it to `use` instead",
+                import = resolve.name_world_key(key),
+                dep = interface_name(resolve, *dep),
+            );
+        }
+    }
+
+    // The re-exported interfaces are exported before the interfaces using
+    // them, with their own dependencies first
+    let mut order = IndexSet::new();
+    for id in &exported {
+        visit(resolve, *id, &exported, &mut order);
+    }
+
+    let target = &mut resolve.worlds[world];
+    let mut exports: IndexMap<_, _> = order
+        .into_iter()
+        .map(|id| (WorldKey::Interface(id), WorldItem::Interface(id)))
+        .collect();
+    exports.extend(std::mem::take(&mut target.exports));
+    target.exports = exports;
+
+    return Ok(());
+
+    fn visit(
+        resolve: &Resolve,
+        id: InterfaceId,
+        exported: &IndexSet<InterfaceId>,
+        order: &mut IndexSet<InterfaceId>,
+    ) {
+        if order.contains(&id) {
+            return;
+        }
+
+        for dep in dependencies(resolve, id) {
+            if exported.contains(&dep) {
+                visit(resolve, dep, exported, order);
+            }
+        }
+
+        order.insert(id);
+    }
+}
+
+/// Gets the interfaces used, directly or indirectly, by the exported
+/// interfaces of a world.
+fn used_by_exports(resolve: &Resolve, world: WorldId) -> IndexSet<InterfaceId> {
+    let mut used = IndexSet::new();
+    let mut pending: Vec<_> = resolve.worlds[world]
+        .exports
+        .values()
+        .filter_map(|item| match item {
+            WorldItem::Interface(id) => Some(*id),
+            _ => None,
+        })
+        .flat_map(|id| dependencies(resolve, id))
+        .collect();
+
+    while let Some(id) = pending.pop() {
+        if used.insert(id) {
+            pending.extend(dependencies(resolve, id));
+        }
+    }
+
+    used
+}
+
+/// Gets the interfaces whose types are used by the given interface.
+pub fn dependencies(resolve: &Resolve, id: InterfaceId) -> IndexSet<InterfaceId> {
+    resolve.interfaces[id]
+        .types
+        .values()
+        .filter_map(|ty| type_owner(resolve, *ty))
+        .filter(|owner| *owner != id)
+        .collect()
+}
+
+/// Gets the interface owning the type aliased by the given type, if any.
+fn type_owner(resolve: &Resolve, ty: wit_parser::TypeId) -> Option<InterfaceId> {
+    match resolve.types[ty].kind {
+        TypeDefKind::Type(Type::Id(aliased)) => match resolve.types[aliased].owner {
+            TypeOwner::Interface(owner) => Some(owner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Gets the versioned name of an interface for error messages.
+fn interface_name(resolve: &Resolve, id: InterfaceId) -> String {
+    names(resolve, id)
+        .pop()
+        .unwrap_or_else(|| "<unnamed>".to_string())
+}
@@ -0,0 +1,384 @@
+//! Module for WAC composition scripts.
+//!
+//! A package may describe how it is composed with its component dependencies
+//! in a `compose.wac` file using a subset of the
+//! [WAC language](https://github.com/peterhuene/wac):
+//!
+//! ```wac
+//! package my:app;
+//!
+//! let logger = new my:logger { ... };
+//! let app = new my:component { log: logger.log, ... };
+//!
+//! export app...;
+//! ```
+//!
+//! Each `let` statement instantiates a package, passing the instances (or
+//! exports of instances) defined before it as arguments; imports that are not
+//! given explicitly are satisfied implicitly by dependencies of the same name.
+//! Package references are resolved from the package's component dependencies,
+//! so they use the versions recorded in the lock file; the package itself is
+//! referenced by its `package` id (`component:<name>` by default).
+//!
+//! Exactly one instance may be exported and every export of it is exported by
+//! the composed component; `export <instance>.<name>;` statements may name
+//! the exports, but must all refer to the same instance.
+
+use anyhow::{bail, Context, Result};
+use semver::Version;
+use std::{collections::HashSet, path::PathBuf, str::FromStr};
+use warg_protocol::registry::PackageId;
+
+/// The name of the instantiation of the root component in a composition.
+const ROOT_INSTANTIATION_NAME: &str = "$input";
+
+/// Represents an argument of an instantiation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument {
+    /// The name of the instance passed as the argument.
+    pub instance: String,
+    /// The name of the export of the instance passed, if any.
+    pub export: Option<String>,
+}
+
+/// Represents an instantiation of a package in a composition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    /// The name of the instance.
+    pub name: String,
+    /// The id of the instantiated package.
+    pub package: PackageId,
+    /// The version of the package, if specified.
+    pub version: Option<Version>,
+    /// The arguments of the instantiation by import name.
+    pub arguments: Vec<(String, Argument)>,
+}
+
+/// Represents a parsed composition script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Composition {
+    /// The name of the composed package, if specified.
+    pub package: Option<String>,
+    /// The instances of the composition, in order.
+    pub instances: Vec<Instance>,
+    /// The name of the exported instance.
+    pub export: String,
+}
+
+impl FromStr for Composition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Parser::new(s).parse()
+    }
+}
+
+impl Composition {
+    /// Converts the composition to a `wasm-compose` configuration.
+    ///
+    /// The given function resolves a package reference to the path of its
+    /// component. Returns the path of the root component with the
+    /// configuration used to compose it.
+    pub fn to_config(
+        &self,
+        dir: PathBuf,
+        mut resolve: impl FnMut(&PackageId, Option<&Version>) -> Result<PathBuf>,
+    ) -> Result<(PathBuf, wasm_compose::config::Config)> {
+        let mut config = wasm_compose::config::Config {
+            dir,
+            ..Default::default()
+        };
+
+        let mut root = None;
+        for instance in &self.instances {
+            let path = resolve(&instance.package, instance.version.as_ref())?;
+            let arguments = instance
+                .arguments
+                .iter()
+                .map(|(name, arg)| {
+                    (
+                        name.clone(),
+                        wasm_compose::config::InstantiationArg {
+                            instance: arg.instance.clone(),
+                            export: arg.export.clone(),
+                        },
+                    )
+                })
+                .collect();
+
+            let name = if instance.name == self.export {
+                root = Some(path);
+                ROOT_INSTANTIATION_NAME.to_string()
+            } else {
+                config.dependencies.insert(
+                    instance.name.clone(),
+                    wasm_compose::config::Dependency { path },
+                );
+                instance.name.clone()
+            };
+
+            config.instantiations.insert(
+                name,
+                wasm_compose::config::Instantiation {
+                    dependency: (instance.name != self.export).then(|| instance.name.clone()),
+                    arguments,
+                },
+            );
+        }
+
+        Ok((root.expect("exported instance should be defined"), config))
+    }
+}
+
+/// A parser for composition scripts.
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, pos: 0 }
+    }
+
+    fn parse(mut self) -> Result<Composition> {
+        let mut package = None;
+        let mut instances: Vec<Instance> = Vec::new();
+        let mut export: Option<String> = None;
+        let mut names = HashSet::new();
+
+        loop {
+            self.skip_trivia()?;
+            if self.pos == self.source.len() {
+                break;
+            }
+
+            match self.word()?.as_str() {
+                "package" => {
+                    if package.is_some() || !instances.is_empty() || export.is_some() {
+                        bail!(self.error("`package` must be the first statement"));
+                    }
+
+                    package = Some(self.reference()?);
+                    self.expect(";")?;
+                }
+                "let" => {
+                    if export.is_some() {
+                        bail!(self.error("instances must be defined before the export"));
+                    }
+
+                    let name = self.word()?;
+                    if !names.insert(name.clone()) {
+                        bail!(self.error(&format!("duplicate instance `{name}`")));
+                    }
+
+                    self.expect("=")?;
+                    if self.word()? != "new" {
+                        bail!(self.error("expected `new`"));
+                    }
+
+                    let reference = self.reference()?;
+                    let (id, version) = match reference.split_once('@') {
+                        Some((id, version)) => (
+                            id,
+                            Some(version.parse().map_err(|_| {
+                                anyhow::anyhow!(self.error(&format!("invalid version `{version}`")))
+                            })?),
+                        ),
+                        None => (reference.as_str(), None),
+                    };
+
+                    let package_id = id.parse().map_err(|_| {
+                        anyhow::anyhow!(self.error(&format!("invalid package reference `{id}`")))
+                    })?;
+
+                    let arguments = self.arguments(&names, &name)?;
+                    self.expect(";")?;
+                    instances.push(Instance {
+                        name,
+                        package: package_id,
+                        version,
+                        arguments,
+                    });
+                }
+                "export" => {
+                    let name = self.word()?;
+                    if !names.contains(&name) {
+                        bail!(self.error(&format!("undefined instance `{name}`")));
+                    }
+
+                    // Either all exports (`...`) or a named export of the instance
+                    if !self.eat("...") {
+                        self.expect(".")?;
+                        self.word()?;
+                    }
+                    self.expect(";")?;
+
+                    match &export {
+                        Some(existing) if *existing != name => bail!(
+                            self.error("only the exports of a single instance may be exported")
+                        ),
+                        _ => export = Some(name),
+                    }
+                }
+                word => bail!(self.error(&format!(
+                    "unexpected `{word}`; expected `package`, `let`, or `export`"
+                ))),
+            }
+        }
+
+        let export = export.context("composition script does not export an instance")?;
+        Ok(Composition {
+            package,
+            instances,
+            export,
+        })
+    }
+
+    /// Parses the arguments of an instantiation.
+    fn arguments(
+        &mut self,
+        names: &HashSet<String>,
+        instance: &str,
+    ) -> Result<Vec<(String, Argument)>> {
+        let mut arguments = Vec::new();
+        self.expect("{")?;
+
+        loop {
+            self.skip_trivia()?;
+            if self.eat("}") {
+                break;
+            }
+
+            // Implicit arguments are always allowed
+            if self.eat("...") {
+                self.skip_trivia()?;
+                self.expect("}")?;
+                break;
+            }
+
+            let name = self.word()?;
+            let arg = if self.eat(":") {
+                let instance = self.word()?;
+                let export = if self.eat(".") {
+                    Some(self.word()?)
+                } else if self.eat("[") {
+                    let export = self.string()?;
+                    self.expect("]")?;
+                    Some(export)
+                } else {
+                    None
+                };
+
+                Argument { instance, export }
+            } else {
+                // `name` is shorthand for `name: name`
+                Argument {
+                    instance: name.clone(),
+                    export: None,
+                }
+            };
+
+            if arg.instance == instance || !names.contains(&arg.instance) {
+                bail!(self.error(&format!("undefined instance `{}`", arg.instance)));
+            }
+
+            arguments.push((name, arg));
+
+            self.skip_trivia()?;
+            if !self.eat(",") {
+                self.expect("}")?;
+                break;
+            }
+        }
+
+        Ok(arguments)
+    }
+
+    /// Parses an identifier, which may be prefixed with `%` to escape keywords.
+    fn word(&mut self) -> Result<String> {
+        self.skip_trivia()?;
+        self.eat("%");
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            bail!(self.error("expected an identifier"));
+        }
+
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    /// Parses a package reference (e.g. `my:pkg@1.0.0`).
+    fn reference(&mut self) -> Result<String> {
+        self.skip_trivia()?;
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '{' | ';'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            bail!(self.error("expected a package reference"));
+        }
+
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    /// Parses a string literal.
+    fn string(&mut self) -> Result<String> {
+        self.skip_trivia()?;
+        self.expect("\"")?;
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .find('"')
+            .with_context(|| self.error("unterminated string"))?;
+        self.pos += len + 1;
+        Ok(rest[..len].to_string())
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let _ = self.skip_trivia();
+        if self.source[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if !self.eat(token) {
+            bail!(self.error(&format!("expected `{token}`")));
+        }
+
+        Ok(())
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) -> Result<()> {
+        loop {
+            let rest = &self.source[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                let end = trimmed
+                    .find("*/")
+                    .with_context(|| self.error("unterminated comment"))?;
+                self.pos += end + 2;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Formats an error message with the line of the current position.
+    fn error(&self, msg: &str) -> String {
+        let line = self.source[..self.pos].matches('\n').count() + 1;
+        format!("{msg} (line {line})")
+    }
+}
@@ -0,0 +1,427 @@
+//! Module for rebuilding component packages when their inputs change.
+
+use crate::{
+    config::{CargoArguments, Config},
+    execute_cargo_command, load_component_metadata, load_metadata,
+    lock::LOCK_FILE_NAME,
+    metadata::Adapter,
+    sources, CargoCommandError, PackageComponentMetadata,
+};
+use anyhow::{Context, Result};
+use cargo_component_core::registry::Dependency;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    time::{Duration, SystemTime},
+};
+
+/// The interval at which the watched paths are polled for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The interval at which the watched paths are polled after a change until
+/// they stop changing, so that saving several files rebuilds only once.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Represents the last modified times of the watched files.
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Runs the cargo command whenever the inputs of the packages change.
+///
+/// The workspace metadata is reloaded before each build so that changes to
+/// the manifests (e.g. new dependencies) are picked up.
+///
+/// If `exec` is specified, it is spawned with the system shell after each
+/// successful build; a previously spawned command that is still running
+/// (e.g. a server) is killed before rebuilding.
+///
+/// Build failures do not stop watching; this function only returns if the
+/// workspace metadata cannot be loaded before the first build or if the
+/// command cannot be spawned.
+pub(crate) async fn watch(
+    config: &Config,
+    subcommand: Option<&str>,
+    cargo_args: &CargoArguments,
+    spawn_args: &[String],
+    exec: Option<&str>,
+) -> Result<()> {
+    let mut paths = BTreeSet::new();
+    let mut child: Option<Child> = None;
+
+    loop {
+        match load_metadata(cargo_args.manifest_path.as_deref()) {
+            Ok(metadata) => {
+                match load_component_metadata(
+                    &metadata,
+                    cargo_args.packages.iter(),
+                    cargo_args.workspace,
+                ) {
+                    Ok(packages) => {
+                        paths = watched_paths(metadata.workspace_root.as_std_path(), &packages);
+                        let snapshot = snapshot(&paths);
+
+                        if let Some(mut child) = child.take() {
+                            log::debug!("killing the previously spawned command");
+                            child.kill().ok();
+                            child.wait().ok();
+                        }
+
+                        match execute_cargo_command(
+                            config, &metadata, &packages, subcommand, cargo_args, spawn_args,
+                        )
+                        .await
+                        {
+                            Ok(_) => {
+                                if let Some(exec) = exec {
+                                    child = Some(spawn_exec(config, exec)?);
+                                }
+                            }
+                            // Cargo has already reported the errors
+                            Err(e) if e.is::<CargoCommandError>() => {}
+                            Err(e) => config.terminal().error(format!("{e:?}"))?,
+                        }
+
+                        wait_for_changes(config, &paths, snapshot).await?;
+                        continue;
+                    }
+                    Err(e) => config.terminal().error(format!("{e:?}"))?,
+                }
+            }
+            Err(e) if paths.is_empty() => return Err(e),
+            Err(e) => config.terminal().error(format!("{e:?}"))?,
+        }
+
+        // The metadata could not be loaded; wait for the previously watched
+        // paths to change before trying again
+        let snapshot = snapshot(&paths);
+        wait_for_changes(config, &paths, snapshot).await?;
+    }
+}
+
+/// Gets the paths to watch for the given packages.
+///
+/// This includes the manifest and sources of each package, its local target
+/// worlds, local dependencies, and adapter, and the component lock file.
+fn watched_paths(
+    workspace_root: &Path,
+    packages: &[PackageComponentMetadata<'_>],
+) -> BTreeSet<PathBuf> {
+    let mut paths = BTreeSet::new();
+    paths.insert(workspace_root.join("Cargo.toml"));
+    paths.insert(workspace_root.join(LOCK_FILE_NAME));
+
+    for PackageComponentMetadata { package, metadata } in packages {
+        let manifest_path = package.manifest_path.as_std_path();
+        paths.insert(manifest_path.to_path_buf());
+
+        let manifest_dir = manifest_path.parent().unwrap();
+        paths.insert(manifest_dir.join("src"));
+        paths.insert(manifest_dir.join("build.rs"));
+
+        let metadata = match metadata {
+            Some(metadata) => metadata,
+            None => continue,
+        };
+
+        let section = &metadata.section;
+        for target in std::iter::once(&section.target).chain(section.targets.values()) {
+            if let Some(path) = metadata.local_target_path(target) {
+                paths.insert(path.into_owned());
+            }
+
+            for entry in target.include() {
+                paths.insert(sources::base(entry));
+            }
+
+            for dependency in target.dependencies().values() {
+                if let Dependency::Local(path) = dependency {
+                    paths.insert(path.clone());
+                }
+            }
+        }
+
+        for dependency in section
+            .dependencies
+            .values()
+            .chain(section.dev_dependencies.values())
+            .chain(section.patch.values())
+        {
+            if let Dependency::Local(path) = dependency {
+                paths.insert(path.clone());
+            }
+        }
+
+        if let Some(Adapter::Path(path)) = &section.adapter {
+            paths.insert(path.clone());
+        }
+
+        if let Some(generate) = &section.generate {
+            paths.extend(generate.inputs.iter().cloned());
+        }
+    }
+
+    paths
+}
+
+/// Takes a snapshot of the last modified times of the files in the given paths.
+///
+/// Directories are walked recursively; paths that do not exist are ignored.
+fn snapshot(paths: &BTreeSet<PathBuf>) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    for path in paths {
+        add_to_snapshot(path, &mut snapshot);
+    }
+
+    snapshot
+}
+
+fn add_to_snapshot(path: &Path, snapshot: &mut Snapshot) {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return,
+    };
+
+    if metadata.is_dir() {
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            add_to_snapshot(&entry.path(), snapshot);
+        }
+
+        return;
+    }
+
+    if let Ok(modified) = metadata.modified() {
+        snapshot.insert(path.to_path_buf(), modified);
+    }
+}
+
+async fn wait_for_changes(
+    config: &Config,
+    paths: &BTreeSet<PathBuf>,
+    snapshot: Snapshot,
+) -> Result<()> {
+    config
+        .terminal()
+        .status("Watching", "for changes (press Ctrl-C to stop)")?;
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let current = self::snapshot(paths);
+        if let Some(path) = changed_path(&snapshot, &current) {
+            log::debug!("detected change to `{path}`", path = path.display());
+            debounce(|| self::snapshot(paths), current, DEBOUNCE_INTERVAL).await;
+            return Ok(());
+        }
+    }
+}
+
+/// Gets a path that changed between two snapshots.
+///
+/// Returns `None` if the snapshots are the same.
+fn changed_path<'a>(previous: &'a Snapshot, current: &'a Snapshot) -> Option<&'a Path> {
+    current
+        .iter()
+        .find(|(path, modified)| previous.get(*path) != Some(modified))
+        .map(|(path, _)| path)
+        .or_else(|| previous.keys().find(|path| !current.contains_key(*path)))
+        .map(PathBuf::as_path)
+}
+
+/// Waits for the watched paths to stop changing.
+///
+/// Snapshots are taken at the given interval until one is the same as the
+/// previous one, which is returned.
+async fn debounce(
+    mut take: impl FnMut() -> Snapshot,
+    mut current: Snapshot,
+    interval: Duration,
+) -> Snapshot {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let next = take();
+        if next == current {
+            return current;
+        }
+
+        log::debug!("waiting for changes to settle");
+        current = next;
+    }
+}
+
+fn spawn_exec(config: &Config, exec: &str) -> Result<Child> {
+    config.terminal().status("Running", format!("`{exec}`"))?;
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+
+    cmd.arg(exec)
+        .spawn()
+        .with_context(|| format!("failed to spawn `{exec}`"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{collections::VecDeque, time::UNIX_EPOCH};
+
+    fn snapshot_of(files: &[(&str, u64)]) -> Snapshot {
+        files
+            .iter()
+            .map(|(path, secs)| (PathBuf::from(path), UNIX_EPOCH + Duration::from_secs(*secs)))
+            .collect()
+    }
+
+    #[test]
+    fn it_collects_watched_paths() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("src"))?;
+        fs::write(dir.path().join("src/lib.rs"), "")?;
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"[package]
+name = "watched"
+version = "0.1.0"
+edition = "2021"
+
+[package.metadata.component]
+adapter = "adapter.wasm"
+
+[package.metadata.component.target]
+path = "wit"
+include = ["common/**/*.wit"]
+
+[package.metadata.component.target.dependencies]
+"foo:bar" = { path = "deps/bar" }
+
+[package.metadata.component.dependencies]
+"baz:qux" = { path = "qux.wasm" }
+
+[package.metadata.component.generate]
+command = "idl-to-wit"
+inputs = ["idl"]
+"#,
+        )?;
+
+        let metadata = load_metadata(Some(&dir.path().join("Cargo.toml")))?;
+        let packages = vec![PackageComponentMetadata::new(
+            &metadata,
+            &metadata.packages[0],
+        )?];
+
+        let root = metadata.workspace_root.as_std_path();
+        let paths = watched_paths(root, &packages);
+        let expected: BTreeSet<PathBuf> = [
+            "Cargo.toml",
+            LOCK_FILE_NAME,
+            "src",
+            "build.rs",
+            "wit",
+            "common",
+            "deps/bar",
+            "qux.wasm",
+            "adapter.wasm",
+            "idl",
+        ]
+        .into_iter()
+        .map(|path| root.join(path))
+        .collect();
+
+        assert_eq!(paths, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn it_snapshots_files_in_directories() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("src/nested"))?;
+        fs::write(dir.path().join("src/lib.rs"), "")?;
+        fs::write(dir.path().join("src/nested/mod.rs"), "")?;
+
+        let paths = [dir.path().join("src"), dir.path().join("missing")]
+            .into_iter()
+            .collect();
+        let snapshot = snapshot(&paths);
+        assert_eq!(
+            snapshot.keys().cloned().collect::<Vec<_>>(),
+            [
+                dir.path().join("src/lib.rs"),
+                dir.path().join("src/nested/mod.rs")
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_finds_the_changed_path() {
+        let previous = snapshot_of(&[("a", 1), ("b", 1)]);
+        assert_eq!(changed_path(&previous, &previous.clone()), None);
+
+        let modified = snapshot_of(&[("a", 1), ("b", 2)]);
+        assert_eq!(changed_path(&previous, &modified), Some(Path::new("b")));
+
+        let added = snapshot_of(&[("a", 1), ("b", 1), ("c", 1)]);
+        assert_eq!(changed_path(&previous, &added), Some(Path::new("c")));
+
+        let removed = snapshot_of(&[("b", 1)]);
+        assert_eq!(changed_path(&previous, &removed), Some(Path::new("a")));
+    }
+
+    #[tokio::test]
+    async fn it_waits_for_changes_to_settle() {
+        let mut snapshots: VecDeque<Snapshot> = [
+            snapshot_of(&[("a", 2)]),
+            snapshot_of(&[("a", 2), ("b", 2)]),
+            snapshot_of(&[("a", 2), ("b", 3)]),
+            snapshot_of(&[("a", 2), ("b", 3)]),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut taken = 0;
+        let settled = debounce(
+            || {
+                taken += 1;
+                snapshots.pop_front().expect("too many snapshots taken")
+            },
+            snapshot_of(&[("a", 1)]),
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert_eq!(settled, snapshot_of(&[("a", 2), ("b", 3)]));
+        assert_eq!(taken, 4);
+    }
+
+    #[tokio::test]
+    async fn it_does_not_wait_if_nothing_else_changes() {
+        let current = snapshot_of(&[("a", 2)]);
+        let mut taken = 0;
+        let settled = debounce(
+            || {
+                taken += 1;
+                current.clone()
+            },
+            current.clone(),
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert_eq!(settled, current);
+        assert_eq!(taken, 1);
+    }
+}
@@ -0,0 +1,277 @@
+//! Module for explaining why a package is in the resolved dependency graph of
+//! a component package.
+
+use crate::{
+    bindings::BindingsEncoder,
+    explain::{describe_dependency, key_name, world_name},
+    registry::PackageDependencyResolution,
+};
+use anyhow::{bail, Context, Result};
+use cargo_component_core::registry::DependencyResolution;
+use indexmap::IndexSet;
+use semver::VersionReq;
+use std::{collections::HashSet, fmt::Write};
+use wit_parser::{
+    InterfaceId, PackageId, PackageName, Resolve, Type, TypeDefKind, TypeOwner, WorldItem, WorldKey,
+};
+
+/// A direct dependency of a component package.
+struct Source<'a> {
+    /// The kind of the dependency (e.g. `target dependency`).
+    kind: &'static str,
+    /// The id of the dependency.
+    id: &'a warg_protocol::registry::PackageId,
+    /// The resolution of the dependency.
+    dependency: &'a DependencyResolution,
+    /// The name of the package of the dependency.
+    package: PackageName,
+    /// The names of all the packages contained in the dependency.
+    contained: HashSet<PackageName>,
+}
+
+/// Explains why a package is in the resolved dependency graph of a target
+/// world.
+///
+/// The world is resolved exactly as it is for bindings generation, so the
+/// explanation reflects the packages actually used by the component.
+pub struct PackageExplainer<'a> {
+    encoder: BindingsEncoder<'a>,
+    sources: Vec<Source<'a>>,
+}
+
+impl<'a> PackageExplainer<'a> {
+    /// Creates a new package explainer for the given package dependency resolution.
+    ///
+    /// If `name` is `None`, the package's default target is used; otherwise
+    /// the named target is used.
+    pub fn new(
+        resolution: &'a PackageDependencyResolution<'a>,
+        name: Option<&str>,
+        features: &HashSet<String>,
+    ) -> Result<Self> {
+        let encoder = BindingsEncoder::new(resolution, name, features)?;
+
+        let target = resolution
+            .target_resolutions(name)
+            .expect("target should have resolutions")
+            .iter()
+            .map(|(id, dependency)| ("target dependency", id, dependency));
+        let components = resolution
+            .resolutions
+            .iter()
+            .filter(|(id, _)| resolution.metadata.is_dependency_enabled(id, features))
+            .map(|(id, dependency)| ("component dependency", id, dependency));
+
+        let mut sources = Vec::new();
+        for (kind, id, dependency) in target.chain(components) {
+            let decoded = dependency.decode()?;
+            let package = decoded.package_name().clone();
+            let (resolve, _, _) = decoded.resolve()?;
+            sources.push(Source {
+                kind,
+                id,
+                dependency,
+                package,
+                contained: resolve
+                    .packages
+                    .iter()
+                    .map(|(_, p)| p.name.clone())
+                    .collect(),
+            });
+        }
+
+        Ok(Self { encoder, sources })
+    }
+
+    /// Explains why packages matching the given name are in the resolved
+    /// dependency graph, returning a human-readable description.
+    ///
+    /// The name is of the form `namespace:name`, optionally followed by
+    /// `@<version requirement>`.
+    pub fn explain(&self, name: &str) -> Result<String> {
+        let (package, requirement) = match name.split_once('@') {
+            Some((package, requirement)) => (
+                package,
+                Some(
+                    requirement
+                        .parse::<VersionReq>()
+                        .with_context(|| format!("invalid version requirement `{requirement}`"))?,
+                ),
+            ),
+            None => (name, None),
+        };
+
+        let (namespace, package) = package.split_once(':').with_context(|| {
+            format!("invalid package name `{name}`: expected `namespace:name[@version]`")
+        })?;
+
+        let resolve = self.encoder.resolve();
+        let matches: Vec<_> = resolve
+            .packages
+            .iter()
+            .filter(|(_, p)| {
+                p.name.namespace == namespace
+                    && p.name.name == package
+                    && match (&requirement, &p.name.version) {
+                        (Some(requirement), Some(version)) => requirement.matches(version),
+                        (Some(_), None) => false,
+                        (None, _) => true,
+                    }
+            })
+            .map(|(id, _)| id)
+            .collect();
+
+        if matches.is_empty() {
+            bail!("package `{name}` is not in the resolved dependency graph of the target world");
+        }
+
+        let mut output = String::new();
+        for (i, package) in matches.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(output).unwrap();
+            }
+
+            self.explain_package(&mut output, package);
+        }
+
+        Ok(output)
+    }
+
+    fn explain_package(&self, output: &mut String, package: PackageId) {
+        let resolve = self.encoder.resolve();
+        let world = &resolve.worlds[self.encoder.world()];
+        let name = &resolve.packages[package].name;
+
+        writeln!(output, "package `{name}`").unwrap();
+
+        if world.package == Some(package) {
+            writeln!(output, "  is the package of the target world").unwrap();
+        }
+
+        for source in &self.sources {
+            if source.package == *name {
+                writeln!(
+                    output,
+                    "  is {kind} `{id}` ({description})",
+                    kind = source.kind,
+                    id = source.id,
+                    description = describe_source(source.dependency)
+                )
+                .unwrap();
+            } else if source.contained.contains(name) {
+                writeln!(
+                    output,
+                    "  is a dependency of {kind} `{id}` ({description})",
+                    kind = source.kind,
+                    id = source.id,
+                    description = describe_source(source.dependency)
+                )
+                .unwrap();
+            }
+        }
+
+        for reason in self.dependents(package) {
+            writeln!(output, "  {reason}").unwrap();
+        }
+    }
+
+    /// Gets the reasons the interfaces and worlds of other packages depend on
+    /// the given package.
+    fn dependents(&self, package: PackageId) -> IndexSet<String> {
+        let resolve = self.encoder.resolve();
+        let target = self.encoder.world();
+        let from_package = |ty: wit_parser::TypeId| match resolve.types[ty].kind {
+            TypeDefKind::Type(Type::Id(used)) => match resolve.types[used].owner {
+                TypeOwner::Interface(owner)
+                    if resolve.interfaces[owner].package == Some(package) =>
+                {
+                    Some(owner)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let mut reasons = IndexSet::new();
+        for (id, interface) in resolve.interfaces.iter() {
+            if interface.package == Some(package) {
+                continue;
+            }
+
+            for ty in interface.types.values() {
+                if let Some(used) = from_package(*ty) {
+                    reasons.insert(format!(
+                        "used by interface `{user}` through `use {used}`",
+                        user = interface_name(resolve, id),
+                        used = interface_name(resolve, used)
+                    ));
+                }
+            }
+        }
+
+        for (id, world) in resolve.worlds.iter() {
+            let name = if id == target {
+                "the target world".to_string()
+            } else if world.package == Some(package) {
+                continue;
+            } else {
+                format!(
+                    "world `{name}`",
+                    name = world_name(resolve, world.package, &world.name)
+                )
+            };
+
+            for included in &world.includes {
+                let included = &resolve.worlds[*included];
+                if included.package == Some(package) {
+                    reasons.insert(format!(
+                        "included by {name} through `include {included}`",
+                        included = world_name(resolve, included.package, &included.name)
+                    ));
+                }
+            }
+
+            for (kind, items) in [("imported", &world.imports), ("exported", &world.exports)] {
+                for (key, item) in items {
+                    match item {
+                        WorldItem::Interface(i)
+                            if resolve.interfaces[*i].package == Some(package) =>
+                        {
+                            reasons.insert(format!(
+                                "{kind} by {name} as `{key}`",
+                                key = key_name(resolve, key)
+                            ));
+                        }
+                        WorldItem::Type(ty) => {
+                            if let Some(used) = from_package(*ty) {
+                                reasons.insert(format!(
+                                    "used by {name} through `use {used}`",
+                                    used = interface_name(resolve, used)
+                                ));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        reasons
+    }
+}
+
+fn interface_name(resolve: &Resolve, id: InterfaceId) -> String {
+    key_name(resolve, &WorldKey::Interface(id))
+}
+
+fn describe_source(dependency: &DependencyResolution) -> String {
+    match dependency {
+        DependencyResolution::Registry(resolution) => format!(
+            "{description}; version requirement `{requirement}` selected v{version}",
+            description = describe_dependency(dependency),
+            requirement = resolution.requirement,
+            version = resolution.version
+        ),
+        _ => describe_dependency(dependency),
+    }
+}
@@ -104,6 +104,186 @@ async fn adds_dependencies_to_target_component() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_dependencies_from_a_named_registry() -> Result<()> {
+    let root = create_root()?;
+    let (_public, config) = spawn_server(&root.join("public")).await?;
+    let (_private, private_config) = spawn_server(&root.join("private")).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_component(&config, "foo:public", "1.0.0", "(component)", true).await?;
+    publish_component(&private_config, "foo:private", "2.0.0", "(component)", true).await?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    let private_url = private_config.default_url.unwrap();
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["registries"]["internal"] =
+            value(private_url.as_str());
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("add foo:public")
+        .assert()
+        .stderr(contains(
+            "Added dependency `foo:public` with version `1.0.0`",
+        ))
+        .success();
+
+    project
+        .cargo_component("add --registry internal foo:private")
+        .assert()
+        .stderr(contains(
+            "Added dependency `foo:private` with version `2.0.0`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#""foo:public" = "1.0.0""#).eval(&manifest));
+    assert!(
+        contains(r#""foo:private" = { version = "2.0.0", registry = "internal" }"#).eval(&manifest)
+    );
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    // The lock file records which registry each package came from
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(contains(r#"id = "foo:private""#).eval(&lock_file));
+    assert!(contains(r#"registry = "internal""#).eval(&lock_file));
+
+    Ok(())
+}
+
+#[test]
+fn adds_dependencies_from_a_static_registry() -> Result<()> {
+    let root = create_root()?;
+    let dir = root.join("static");
+    publish_static(
+        &dir,
+        "foo:bar",
+        "1.0.0",
+        &wat::parse_str("(component)")?,
+        false,
+    )?;
+    publish_static(
+        &dir,
+        "foo:bar",
+        "1.1.0",
+        &wat::parse_str("(component)")?,
+        true,
+    )?;
+    let url = spawn_static_registry(&dir)?;
+
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["registries"]["static"] = value(url.as_str());
+        Ok(doc)
+    })?;
+
+    // The yanked release is not selected
+    project
+        .cargo_component("add --registry static foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `1.0.0`"))
+        .success();
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(contains(r#"registry = "static""#).eval(&lock_file));
+    assert!(contains(r#"version = "1.0.0""#).eval(&lock_file));
+
+    // Content that doesn't match the index is rejected
+    fs::remove_dir_all(root.join("content"))?;
+    fs::write(
+        dir.join("pkgs/foo/bar/1.0.0.wasm"),
+        wat::parse_str("(component (type (func)))")?,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("but the index specifies digest"))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn merges_interfaces_into_the_target_world() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.2.3",
+        r#"package foo:bar@1.2.3
+interface baz {
+    baz: func() -> string
+}
+interface qux {
+    qux: func()
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("add foo:bar --target")
+        .assert()
+        .stderr(
+            contains("Added dependency `foo:bar` with version `1.2.3`")
+                .and(contains(
+                    "Merged `import foo:bar/baz@1.2.3` into world `example`",
+                ))
+                .and(contains(
+                    "Merged `import foo:bar/qux@1.2.3` into world `example`",
+                ))
+                .and(contains("Generated bindings for package `component`")),
+        )
+        .success();
+
+    let world = fs::read_to_string(project.root().join("wit/world.wit"))?;
+    assert!(contains(
+        "world example {\n    import foo:bar/baz@1.2.3\n    import foo:bar/qux@1.2.3\n"
+    )
+    .eval(&world));
+    assert!(project.build_dir().join("bindings/component").is_dir());
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    Ok(())
+}
+
 #[test]
 fn checks_for_duplicate_dependencies() -> Result<()> {
     let project = Project::new("foo")?;
@@ -150,6 +330,61 @@ async fn prints_modified_manifest_for_dry_run() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn requires_locked_dependencies_with_locked() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_component(&config, "foo:bar", "1.2.3", "(component)", true).await?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+
+    project
+        .cargo_component("add --locked foo:bar")
+        .assert()
+        .stderr(contains(
+            "needs to be updated but --locked was passed to prevent this",
+        ))
+        .failure();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(!contains(r#""foo:bar" = "1.2.3""#).eval(&manifest));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_pre_release_dependencies_only_with_pre() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_component(&config, "foo:bar", "0.2.0", "(component)", true).await?;
+    publish_component(&config, "foo:bar", "0.3.0-rc1", "(component)", false).await?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+
+    project
+        .cargo_component("add --dry-run foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `0.2.0`"))
+        .success();
+
+    project
+        .cargo_component("add --pre foo:bar")
+        .assert()
+        .stderr(contains(
+            "Added dependency `foo:bar` with version `0.3.0-rc1`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#""foo:bar" = "0.3.0-rc1""#).eval(&manifest));
+
+    Ok(())
+}
+
 #[test]
 fn validate_add_from_path() -> Result<()> {
     let project = Project::new("foo")?;
@@ -0,0 +1,131 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::fs;
+use toml_edit::value;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help audit", "audit -h", "audit --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Audit the locked registry dependencies for advisories and yanked releases",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_audits_locked_dependencies() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    for (version, init) in [("1.0.0", true), ("1.1.0", false)] {
+        publish_wit(
+            &config,
+            "foo:bar",
+            version,
+            &format!(
+                r#"package foo:bar@{version}
+world foo {{
+    export bar: func() -> string
+}}"#
+            ),
+            init,
+        )
+        .await?;
+    }
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("audit")
+        .assert()
+        .stderr(contains("the workspace has no component lock file"))
+        .success();
+
+    project
+        .cargo_component("add foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `1.1.0`"))
+        .success();
+
+    project.cargo_component("build").assert().success();
+
+    project
+        .cargo_component("audit")
+        .assert()
+        .stderr(contains("no advisory database was specified"))
+        .stderr(contains("Audited locked component dependencies"))
+        .success();
+
+    let db = root.join("advisories");
+    fs::create_dir_all(db.join("foo"))?;
+    fs::write(
+        db.join("foo/CMA-0001.toml"),
+        r#"[advisory]
+id = "CMA-0001"
+package = "foo:bar"
+title = "Strings are not validated"
+url = "https://example.com/CMA-0001"
+
+[versions]
+patched = [">=1.2.0"]
+unaffected = ["<1.1.0"]
+"#,
+    )?;
+
+    project
+        .cargo_component(&format!("audit --db {db}", db = db.display()))
+        .assert()
+        .stderr(contains(
+            "version 1.1.0 of package `foo:bar` is affected by advisory CMA-0001: Strings are not validated (patched in `>=1.2.0`)",
+        ))
+        .stderr(contains("see https://example.com/CMA-0001"))
+        .stderr(contains(
+            "found 1 problem(s) with the locked component dependencies",
+        ))
+        .failure();
+
+    // Yank the locked release
+    let publisher = Project::with_root(&root, "publisher", "")?;
+    publisher.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["package"] = value("foo:bar");
+        Ok(doc)
+    })?;
+
+    publisher
+        .cargo_component("yank 1.1.0")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .success();
+
+    project
+        .cargo_component("audit")
+        .assert()
+        .stderr(contains(
+            "version 1.1.0 of package `foo:bar` was yanked from component registry `default`",
+        ))
+        .stderr(contains(
+            "Audited locked component dependencies with 1 warning(s)",
+        ))
+        .success();
+
+    project
+        .cargo_component("audit --deny-warnings --message-format json")
+        .assert()
+        .stdout(contains(r#""kind":"yanked""#))
+        .stdout(contains(r#""reason":"audit-finding""#))
+        .failure();
+
+    Ok(())
+}
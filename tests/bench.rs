@@ -0,0 +1,70 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+use toml_edit::{value, ArrayOfTables, Item, Table};
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help bench", "bench -h", "bench --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Build benchmark targets as components and time their runs",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_benchmarks_a_component() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut bench = Table::new();
+        bench["name"] = value("speed");
+        bench["harness"] = value(false);
+        let mut benches = ArrayOfTables::new();
+        benches.push(bench);
+        doc["bench"] = Item::ArrayOfTables(benches);
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("benches"))?;
+    fs::write(
+        project.root().join("benches/speed.rs"),
+        "fn main() {\n    println!(\"{}\", (0..1000u64).sum::<u64>());\n}\n",
+    )?;
+
+    // Time the runtime without running the component
+    project
+        .cargo_component("bench --iterations 3 --message-format json")
+        .env("CARGO_COMPONENT_RUNTIME", "true")
+        .assert()
+        .stderr(contains("Benchmarking"))
+        .stdout(
+            contains(r#""package":"foo""#)
+                .and(contains(r#""name":"speed""#))
+                .and(contains(r#""iterations":3"#))
+                .and(contains(r#""baseline-ns":"#))
+                // Fuel is only measured with `wasmtime`
+                .and(contains(r#""fuel""#).not()),
+        )
+        .success();
+
+    project
+        .cargo_component("bench --iterations 3")
+        .env("CARGO_COMPONENT_RUNTIME", "true")
+        .assert()
+        .stdout(
+            contains("bench `speed` of package `foo`: median")
+                .and(contains("over 3 iterations, excluding a baseline of"))
+                .and(contains("from the previous run")),
+        )
+        .success();
+
+    Ok(())
+}
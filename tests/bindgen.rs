@@ -0,0 +1,86 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help bindgen", "bindgen -h", "bindgen --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Generate bindings for other languages from a component's target world",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_generates_c_bindings_for_the_target_world() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("bindgen --lang c --out-dir bindings-c")
+        .assert()
+        .stderr(contains("Generated c bindings in `bindings-c`"))
+        .success();
+
+    let dir = project.root().join("bindings-c");
+    assert!(dir.join("example.h").is_file());
+    assert!(dir.join("example.c").is_file());
+
+    Ok(())
+}
+
+#[test]
+fn it_generates_go_bindings_from_a_built_component() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    let component = project.release_wasm("component");
+    project
+        .cargo_component(&format!(
+            "bindgen --lang go --out-dir bindings-go --component {path}",
+            path = component.display()
+        ))
+        .assert()
+        .stderr(contains("Generated go bindings in `bindings-go`"))
+        .success();
+
+    let dir = project.root().join("bindings-go");
+    assert!(std::fs::read_dir(&dir)?.flatten().any(|e| e
+        .path()
+        .extension()
+        .map(|e| e == "go")
+        .unwrap_or(false)));
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_unsupported_languages() -> Result<()> {
+    let project = Project::new("component")?;
+
+    project
+        .cargo_component("bindgen --lang python --out-dir bindings")
+        .assert()
+        .stderr(contains("generating Python bindings is not supported"))
+        .failure();
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help bindings", "bindings -h", "bindings --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Emit the Rust bindings of a component's target world as a source file",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_emits_and_checks_bindings_source() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("bindings --check --emit src/bindings.rs")
+        .assert()
+        .stderr(contains("bindings file `src/bindings.rs` does not exist"))
+        .failure();
+
+    project
+        .cargo_component("bindings --emit src/bindings.rs")
+        .assert()
+        .stderr(contains("Generated Rust bindings in `src/bindings.rs`"))
+        .success();
+
+    let source = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(source.contains("DO NOT EDIT!"));
+    assert!(source.contains("cargo component bindings --emit src/bindings.rs"));
+
+    let lib = project.root().join("src/lib.rs");
+    fs::write(
+        &lib,
+        fs::read_to_string(&lib)?.replace(
+            "cargo_component_bindings::generate!();",
+            "#[rustfmt::skip]\nmod bindings;",
+        ),
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("component"))?;
+
+    project
+        .cargo_component("bindings --check --emit src/bindings.rs")
+        .assert()
+        .stderr(contains("bindings file `src/bindings.rs` is up to date"))
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:component
+
+/// An example world for the component to target.
+world example {
+    export hello-world: func() -> string
+    export goodbye-world: func() -> string
+}
+",
+    )?;
+
+    project
+        .cargo_component("bindings --check --emit src/bindings.rs")
+        .assert()
+        .stderr(
+            contains("differs from the generated bindings at line").and(contains(
+                "bindings file `src/bindings.rs` is out of date; run `cargo component bindings --emit src/bindings.rs` to regenerate it",
+            )),
+        )
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_emits_a_host_crate() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("bindings --emit src/bindings.rs --host host")
+        .assert()
+        .stderr(
+            contains("Generated Rust bindings in `src/bindings.rs`")
+                .and(contains("Generated host crate in `host`")),
+        )
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("host/Cargo.toml"))?;
+    assert!(manifest.contains(r#"name = "component-host""#));
+    assert!(manifest.contains("wasmtime"));
+
+    let source = fs::read_to_string(project.root().join("host/src/lib.rs"))?;
+    assert!(source.contains("wasmtime::component::bindgen!"));
+    assert!(source.contains(r#"world: "example""#));
+
+    let wit = fs::read_to_string(project.root().join("host/wit/world.wit"))?;
+    assert!(wit.contains("package component:component"));
+    assert!(wit.contains("world example"));
+
+    project
+        .cargo_component("bindings --check --emit src/bindings.rs --host host")
+        .assert()
+        .stderr(contains("host crate `host` is up to date"))
+        .success();
+
+    fs::write(project.root().join("host/wit/world.wit"), "")?;
+
+    project
+        .cargo_component("bindings --check --emit src/bindings.rs --host host")
+        .assert()
+        .stderr(contains("is out of date"))
+        .failure();
+
+    Ok(())
+}
@@ -1,9 +1,13 @@
 use crate::support::*;
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component::{
+    build_component, config::Config, generate_bindings, BuildOptions, CargoCommandError,
+};
+use cargo_component_core::terminal::{Color, Terminal, Verbosity};
 use predicates::{prelude::PredicateBooleanExt, str::contains};
 use std::fs;
-use toml_edit::{value, Item, Table};
+use toml_edit::{value, Array, InlineTable, Item, Table};
 
 mod support;
 
@@ -30,6 +34,29 @@ fn it_builds_debug() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_emits_json_messages() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --message-format json")
+        .assert()
+        .stdout(contains(r#""reason":"target-encoded""#))
+        .stdout(contains(r#""reason":"component-written""#))
+        .stdout(contains(r#""kind":"reactor""#))
+        .stdout(contains(r#""sha256":"#))
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
 #[test]
 fn it_builds_a_bin_project() -> Result<()> {
     let project = Project::new_bin("foo")?;
@@ -49,6 +76,288 @@ fn it_builds_a_bin_project() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_emits_the_core_module() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --emit core-wasm")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let path = project.debug_wasm("foo");
+    validate_component(&path)?;
+
+    for extension in ["core.wasm", "adapter.wasm"] {
+        let bytes = fs::read(path.with_extension(extension))?;
+        assert!(bytes.starts_with(b"\0asm\x01\0\0\0"));
+    }
+
+    project
+        .cargo_component("build --emit asm")
+        .assert()
+        .stderr(contains("unsupported `--emit` kind `asm`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_prints_a_size_report() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --size-report")
+        .assert()
+        .stdout(
+            contains("foo.wasm`: ")
+                .and(contains("core module code"))
+                .and(contains("adapter"))
+                .and(contains("metadata `producers`")),
+        )
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let output = project
+        .cargo_component("build --size-report=json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output)?;
+    let total = report["total"].as_u64().context("expected a total size")?;
+    let parts: u64 = report["parts"]
+        .as_object()
+        .context("expected the parts of the component")?
+        .values()
+        .filter_map(|v| v.as_u64())
+        .sum();
+    assert_eq!(total, parts);
+    assert_eq!(total, fs::metadata(project.debug_wasm("foo"))?.len());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_builds_with_the_library_api() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    let config = Config::new(Terminal::new(Verbosity::Quiet, Color::Never))?;
+    let mut options = BuildOptions::new();
+    options.manifest_path = Some(project.root().join("Cargo.toml"));
+
+    generate_bindings(&config, &options).await?;
+    assert!(project.build_dir().join("bindings").join("foo").is_dir());
+
+    let outputs = build_component(&config, &options).await?;
+    assert_eq!(outputs, [project.debug_wasm("foo")]);
+    validate_component(&outputs[0])?;
+
+    // The options of `cargo component build` are also available
+    let manifest = project.root().join("deploy.json");
+    options.minimize_imports = true;
+    options.manifest_out = Some(manifest.clone());
+    let outputs = build_component(&config, &options).await?;
+    validate_component(&outputs[0])?;
+    assert!(manifest.is_file());
+
+    fs::write(project.root().join("src/lib.rs"), "invalid")?;
+    let e = build_component(&config, &options).await.unwrap_err();
+    assert!(!e
+        .downcast_ref::<CargoCommandError>()
+        .context("expected a cargo command error")?
+        .status
+        .success());
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_reproducibly() -> Result<()> {
+    let mut outputs = Vec::new();
+
+    // Build the same package in two different directories
+    for _ in 0..2 {
+        let project = Project::new("foo")?;
+        project.update_manifest(|mut doc| {
+            redirect_bindings_crate(&mut doc);
+            Ok(doc)
+        })?;
+
+        project
+            .cargo_component("build --release --reproducible")
+            .assert()
+            .stderr(contains("Finished release [optimized] target(s)"))
+            .success();
+
+        let path = project.release_wasm("foo");
+        validate_component(&path)?;
+        outputs.push(fs::read(&path)?);
+    }
+
+    assert!(outputs[0] == outputs[1], "reproducible builds differ");
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_configured_rustflags_when_building_reproducibly() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    let lib = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        format!(
+            "#[cfg(not(configured))]\ncompile_error!(\"the configured rustflags were not used\");\n{lib}"
+        ),
+    )?;
+
+    fs::create_dir_all(project.root().join(".cargo"))?;
+    for config in [
+        "[build]\nrustflags = [\"--cfg\", \"configured\"]\n",
+        "[target.'cfg(target_arch = \"wasm32\")']\nrustflags = \"--cfg configured\"\n",
+    ] {
+        fs::write(project.root().join(".cargo/config.toml"), config)?;
+
+        project
+            .cargo_component("build --reproducible")
+            .assert()
+            .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+            .success();
+
+        validate_component(&project.debug_wasm("foo"))?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_minimized_imports() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --minimize-imports --emit core-wasm")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let path = project.debug_wasm("foo");
+    validate_component(&path)?;
+
+    // The rewritten core module must still be valid
+    wasmparser::Validator::new().validate_all(&fs::read(path.with_extension("core.wasm"))?)?;
+
+    project
+        .cargo_component("build --minimize-imports=yes")
+        .assert()
+        .stderr(contains(
+            "argument `--minimize-imports` does not take a value",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_raw_exports_and_custom_sections() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let raw = &mut doc["package"]["metadata"]["component"]["raw"];
+        raw["exports"] = value(Array::from_iter(["host_abi_version"]));
+        raw["custom-sections"]["my-host-abi"] = value("abi.json");
+        Ok(doc)
+    })?;
+
+    fs::write(project.root().join("abi.json"), r#"{"version":1}"#)?;
+
+    let mut source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    source.push_str(
+        r#"
+#[no_mangle]
+pub extern "C" fn host_abi_version() -> u32 {
+    1
+}
+"#,
+    );
+    fs::write(project.root().join("src/lib.rs"), source)?;
+
+    project
+        .cargo_component("build --emit core-wasm")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let path = project.debug_wasm("foo");
+    validate_component(&path)?;
+
+    let mut found = false;
+    let bytes = fs::read(&path)?;
+    let mut depth = 0;
+    for payload in wasmparser::Parser::new(0).parse_all(&bytes) {
+        match payload? {
+            wasmparser::Payload::ModuleSection { .. }
+            | wasmparser::Payload::ComponentSection { .. } => depth += 1,
+            wasmparser::Payload::End(_) => depth -= 1,
+            wasmparser::Payload::CustomSection(reader)
+                if depth == 0 && reader.name() == "my-host-abi" =>
+            {
+                assert_eq!(reader.data(), br#"{"version":1}"#);
+                found = true;
+            }
+            _ => {}
+        }
+    }
+    assert!(found, "expected the custom section in the component");
+
+    let core = fs::read(path.with_extension("core.wasm"))?;
+    let mut exported = false;
+    for payload in wasmparser::Parser::new(0).parse_all(&core) {
+        if let wasmparser::Payload::ExportSection(reader) = payload? {
+            for export in reader {
+                exported |= export?.name == "host_abi_version";
+            }
+        }
+    }
+    assert!(exported, "expected the raw export in the core module");
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["raw"]["exports"] =
+            value(Array::from_iter(["host_abi_version", "missing"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("raw export `missing` is not exported by module"))
+        .failure();
+
+    Ok(())
+}
+
 #[test]
 fn it_builds_a_workspace() -> Result<()> {
     let project = project()?
@@ -126,6 +435,64 @@ fn it_supports_wit_keywords() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_annotates_deprecated_items() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+            package foo:bar
+
+            world bar {
+                import baz: interface {
+                    /// Gets the value.
+                    ///
+                    /// @deprecated use `get-value` instead
+                    get: func() -> u32
+                    get-value: func() -> u32
+                }
+
+                /// @deprecated
+                export hello: func() -> u32
+            }
+        ",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+            cargo_component_bindings::generate!();
+
+            struct Component;
+
+            impl bindings::Guest for Component {
+                fn hello() -> u32 {
+                    bindings::baz::get() + bindings::baz::get_value()
+                }
+            }
+        "#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "warning: exported function `hello` is deprecated (exported by package `foo`)",
+        ))
+        .stderr(contains("use of deprecated function `bindings::baz::get`"))
+        .stderr(contains("use `get-value` instead"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
 #[test]
 fn it_adds_a_producers_field() -> Result<()> {
     let project = Project::new("foo")?;
@@ -185,6 +552,50 @@ fn it_builds_wasm32_unknown_unknown() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_builds_wasm32_wasip2() -> Result<()> {
+    // Only toolchains that support the target produce components natively
+    let targets = std::process::Command::new("rustc")
+        .args(["--print", "target-list"])
+        .output()?;
+    if !String::from_utf8_lossy(&targets.stdout)
+        .lines()
+        .any(|t| t == "wasm32-wasip2")
+    {
+        return Ok(());
+    }
+
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --target wasm32-wasip2")
+        .assert()
+        .stderr(contains("Validating component").and(contains("Creating component").not()))
+        .success();
+
+    let path = project
+        .build_dir()
+        .join("wasm32-wasip2")
+        .join("debug")
+        .join("foo.wasm");
+    validate_component(&path)?;
+
+    let wasm = fs::read(&path)
+        .with_context(|| format!("failed to read wasm file `{path}`", path = path.display()))?;
+    let section = wasm_metadata::Producers::from_wasm(&wasm)?.expect("missing producers section");
+    assert!(section
+        .get("processed-by")
+        .expect("missing processed-by field")
+        .get(env!("CARGO_PKG_NAME"))
+        .is_some());
+
+    Ok(())
+}
+
 #[test]
 fn it_regenerates_target_if_wit_changed() -> Result<()> {
     let project = Project::new("foo")?;
@@ -220,43 +631,177 @@ fn it_regenerates_target_if_wit_changed() -> Result<()> {
 }
 
 #[test]
-fn it_builds_with_local_wit_deps() -> Result<()> {
+fn it_does_not_regenerate_target_if_wit_content_is_unchanged() -> Result<()> {
     let project = Project::new("foo")?;
     project.update_manifest(|mut doc| {
         redirect_bindings_crate(&mut doc);
-        let mut dependencies = Table::new();
-        dependencies["foo:bar"]["path"] = value("wit/deps/foo-bar");
-        dependencies["bar:baz"]["path"] = value("wit/deps/bar-baz/qux.wit");
-        dependencies["baz:qux"]["path"] = value("wit/deps/foo-bar/deps/baz-qux/qux.wit");
-
-        let target =
-            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
-        target["dependencies"] = Item::Table(dependencies);
         Ok(doc)
     })?;
 
-    // Create the foo-bar wit package
-    fs::create_dir_all(project.root().join("wit/deps/foo-bar/deps/baz-qux"))?;
-    fs::write(
-        project.root().join("wit/deps/foo-bar/deps/baz-qux/qux.wit"),
-        "package baz:qux
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Encoding target"))
+        .success();
 
-interface qux {
-    type ty = u32
-}",
-    )?;
+    let target = project
+        .root()
+        .join("target/bindings/foo/target.wasm")
+        .metadata()?
+        .modified()?;
 
-    fs::write(
-        project.root().join("wit/deps/foo-bar/bar.wit"),
-        "package foo:bar
+    // Touch both the WIT file and the manifest without changing the target world
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let wit = fs::read_to_string(project.root().join("wit/world.wit"))?;
+    fs::write(project.root().join("wit/world.wit"), wit)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["description"] = value("unchanged target");
+        Ok(doc)
+    })?;
 
-interface baz {
-    use baz:qux/qux.{ty}
-    baz: func() -> ty
-}",
-    )?;
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Encoding target").not())
+        .success();
 
-    fs::create_dir_all(project.root().join("wit/deps/bar-baz"))?;
+    assert_eq!(
+        project
+            .root()
+            .join("target/bindings/foo/target.wasm")
+            .metadata()?
+            .modified()?,
+        target
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_regenerates_target_when_sibling_wit_changes() -> Result<()> {
+    let project = Project::new("foo")?;
+    let sibling = project.root().parent().unwrap().join("sibling/wit");
+    fs::create_dir_all(&sibling)?;
+    fs::write(
+        sibling.join("types.wit"),
+        "package sibling:types
+
+interface types {
+    get: func() -> u32
+}
+",
+    )?;
+
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut dependencies = Table::new();
+        dependencies["sibling:types"]["path"] = value("../sibling/wit");
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo
+
+world example {
+    import sibling:types/types
+    export hello-world: func() -> string
+}
+",
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Encoding target"))
+        .success();
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Encoding target").not())
+        .success();
+
+    // Editing a file of the sibling's WIT directory regenerates the target
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    fs::write(
+        sibling.join("types.wit"),
+        "package sibling:types
+
+interface types {
+    get: func() -> u64
+}
+",
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Encoding target"))
+        .success();
+
+    // Adding a file to it is detected even though no existing file changed
+    fs::write(
+        sibling.join("more.wit"),
+        "package sibling:types
+
+interface more {
+    put: func(value: u64)
+}
+",
+    )?;
+
+    project.cargo_component("build").assert().success();
+
+    let inputs = fs::read_to_string(project.root().join("target/bindings/foo/inputs"))?;
+    assert!(inputs.contains("types.wit"));
+    assert!(inputs.contains("more.wit"));
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_local_wit_deps() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["path"] = value("wit/deps/foo-bar");
+        dependencies["bar:baz"]["path"] = value("wit/deps/bar-baz/qux.wit");
+        dependencies["baz:qux"]["path"] = value("wit/deps/foo-bar/deps/baz-qux/qux.wit");
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    // Create the foo-bar wit package
+    fs::create_dir_all(project.root().join("wit/deps/foo-bar/deps/baz-qux"))?;
+    fs::write(
+        project.root().join("wit/deps/foo-bar/deps/baz-qux/qux.wit"),
+        "package baz:qux
+
+interface qux {
+    type ty = u32
+}",
+    )?;
+
+    fs::write(
+        project.root().join("wit/deps/foo-bar/bar.wit"),
+        "package foo:bar
+
+interface baz {
+    use baz:qux/qux.{ty}
+    baz: func() -> ty
+}",
+    )?;
+
+    fs::create_dir_all(project.root().join("wit/deps/bar-baz"))?;
 
     fs::write(
         project.root().join("wit/deps/bar-baz/qux.wit"),
@@ -309,6 +854,75 @@ impl Qux for Component {
     Ok(())
 }
 
+#[test]
+fn it_reports_implementation_changes_when_exports_change() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo
+
+world example {
+    export greet: func(name: string) -> string
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(
+            contains("the exports of the target world of package `foo` changed since the last successful build")
+                .and(contains("remove from the implementation of `bindings::Guest`:"))
+                .and(contains("fn hello_world() -> String"))
+                .and(contains("add to the implementation of `bindings::Guest`:"))
+                .and(contains("fn greet(name: String) -> String")),
+        )
+        .failure();
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet(name: String) -> String {
+        format!("Hello, {name}!")
+    }
+}
+"#,
+    )?;
+
+    // The implementation matches the exports once it builds successfully
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("changed since the last successful build").not())
+        .success();
+
+    Ok(())
+}
+
 #[test]
 fn it_builds_with_a_specified_implementor() -> Result<()> {
     let project = Project::new("foo")?;
@@ -722,115 +1336,2027 @@ impl Guest for Component {
 }
 
 #[test]
-fn it_builds_with_adapter() -> Result<()> {
-    let project = Project::new("foo")?;
-    project.update_manifest(|mut doc| {
+fn it_builds_with_an_optional_component_dependency() -> Result<()> {
+    let root = create_root()?;
+
+    let comp1 = Project::with_root(&root, "comp1", "")?;
+    comp1.update_manifest(|mut doc| {
         redirect_bindings_crate(&mut doc);
-        doc["package"]["metadata"]["component"]["adapter"] = value("not-a-valid-path");
         Ok(doc)
     })?;
 
-    project
+    comp1
         .cargo_component("build")
         .assert()
-        .stderr(contains("error: failed to read module adapter"))
-        .failure();
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
 
-    let project = Project::new("foo")?;
-    project.update_manifest(|mut doc| {
+    let dep = comp1.debug_wasm("comp1");
+    validate_component(&dep)?;
+
+    let comp2 = Project::with_root(&root, "comp2", "")?;
+    comp2.update_manifest(|mut doc| {
         redirect_bindings_crate(&mut doc);
-        doc["package"]["metadata"]["component"]["adapter"] = value(format!(
-            "../../../../../adapters/{version}/wasi_snapshot_preview1.reactor.wasm",
-            version = env!("WASI_ADAPTER_VERSION")
-        ));
+        let dependency = &mut doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"];
+        dependency["path"] = value(dep.display().to_string());
+        dependency["optional"] = value(true);
+        dependency["feature"] = value("full");
+        doc["features"]["full"] = value(Array::new());
         Ok(doc)
     })?;
 
-    project
+    fs::write(
+        comp2.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::{comp1, Guest};
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        comp1::hello_world()
+    }
+}
+"#,
+    )?;
+
+    // The dependency is not imported without the feature
+    comp2
         .cargo_component("build")
         .assert()
+        .stderr(contains("no `comp1` in `bindings`"))
+        .failure();
+
+    comp2
+        .cargo_component("build --features full")
+        .assert()
         .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
         .success();
 
-    validate_component(&project.debug_wasm("foo"))?;
+    validate_component(&comp2.debug_wasm("comp2"))?;
+
+    // Disabling the feature again should encode the target without the dependency
+    comp2
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Encoding target"))
+        .stderr(contains("no `comp1` in `bindings`"))
+        .failure();
 
     Ok(())
 }
 
 #[test]
-fn it_errors_if_adapter_is_not_wasm() -> Result<()> {
+fn it_errors_if_optional_dependency_feature_is_undefined() -> Result<()> {
     let project = Project::new("foo")?;
     project.update_manifest(|mut doc| {
         redirect_bindings_crate(&mut doc);
-        doc["package"]["metadata"]["component"]["adapter"] = value("foo.wasm");
+        let dependency = &mut doc["package"]["metadata"]["component"]["dependencies"]["my:bar"];
+        dependency["path"] = value("bar.wasm");
+        dependency["optional"] = value(true);
         Ok(doc)
     })?;
 
-    fs::write(project.root().join("foo.wasm"), "not wasm")?;
-
     project
         .cargo_component("build")
         .assert()
-        .stderr(contains("error: failed to load adapter module"))
+        .stderr(contains(
+            "optional dependency `my:bar` is enabled by feature `bar`, which is not defined",
+        ))
         .failure();
 
     Ok(())
 }
 
 #[test]
-fn it_adds_additional_derives() -> Result<()> {
+fn it_builds_with_target_world_parts_enabled_by_features() -> Result<()> {
     let project = Project::new("foo")?;
     project.update_manifest(|mut doc| {
         redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["features"]["telemetry"]["include"] =
+            value(Array::from_iter(["telemetry"]));
+        doc["features"]["telemetry"] = value(Array::new());
         Ok(doc)
     })?;
 
-    std::process::Command::new("cargo")
-        .args(["add", "serde", "--features", "derive"])
-        .current_dir(project.root())
-        .assert()
-        .success();
-    std::process::Command::new("cargo")
-        .args(["add", "serde_json"])
-        .current_dir(project.root())
-        .assert()
-        .success();
-
     fs::write(
         project.root().join("wit/world.wit"),
-        "
-package my:derive
+        r#"package component:foo
 
-interface foo {
-    record bar {
-        value: u32,
-    }
+world example {
+    export hello-world: func() -> string
 }
 
-world foo-world {
-    use foo.{bar}
-
-    export baz: func(thing: bar) -> list<u8>
+world telemetry {
+    export report: func() -> string
 }
-",
+"#,
     )?;
+
     fs::write(
         project.root().join("src/lib.rs"),
-        r#"cargo_component_bindings::generate!({
-    additional_derives: [serde::Serialize, serde::Deserialize]
-});
+        r#"
+cargo_component_bindings::generate!();
 
 use bindings::Guest;
-use bindings::my::derive::foo::Bar;
 
 struct Component;
 
 impl Guest for Component {
-    fn baz(thing: Bar) -> Vec<u8> {
-        let stuff = serde_json::to_vec(&thing).unwrap();
-        // Check we got the derived deserialize
-        let _thing: Bar = serde_json::from_slice(&stuff).unwrap();
-        stuff
+    fn hello_world() -> String {
+        "Hello, World!".to_string()
+    }
+
+    #[cfg(feature = "telemetry")]
+    fn report() -> String {
+        "ok".to_string()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component("build --features telemetry")
+        .assert()
+        .stderr(contains("Encoding target"))
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component("explain --features telemetry")
+        .assert()
+        .stdout(
+            contains("function `report`")
+                .and(contains("included from world `component:foo/telemetry`")),
+        )
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_target_feature_is_undefined() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["features"]["telemetry"]["include"] =
+            value(Array::from_iter(["telemetry"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "the target has parts enabled by feature `telemetry`, which is not defined",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_named_instances_of_an_interface() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let instances = &mut doc["package"]["metadata"]["component"]["target"]["instances"];
+        instances["admin"] = value("component:foo/handler");
+        instances["public"] = value("component:foo/handler");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo
+
+interface handler {
+    handle: func() -> string
+}
+
+world example {
+    export hello-world: func() -> string
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::{exports::{admin, public}, Guest};
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        "Hello, World!".to_string()
+    }
+}
+
+impl admin::Guest for Component {
+    fn handle() -> String {
+        "admin".to_string()
+    }
+}
+
+impl public::Guest for Component {
+    fn handle() -> String {
+        "public".to_string()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component("inspect")
+        .assert()
+        .stdout(contains("export: interface `admin`").and(contains("export: interface `public`")))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_instance_interface_is_unknown() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["instances"]["admin"] =
+            value("component:foo/missing");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "interface `component:foo/missing` of instance `admin` in `package.metadata.component.target.instances` was not found",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_included_wit_files() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut include = Array::default();
+        include.push("wit/**/*.wit");
+        include.push("common-wit");
+        doc["package"]["metadata"]["component"]["target"]["include"] = value(include);
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("wit/nested"))?;
+    fs::write(
+        project.root().join("wit/nested/types.wit"),
+        r#"package component:foo
+
+interface types {
+    type greeting = string
+}
+"#,
+    )?;
+
+    fs::create_dir_all(project.root().join("common-wit"))?;
+    fs::write(
+        project.root().join("common-wit/greeter.wit"),
+        r#"package component:foo
+
+interface greeter {
+    use types.{greeting}
+
+    greet: func() -> greeting
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo
+
+world example {
+    export greeter
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::exports::component::foo::greeter::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet() -> String {
+        "Hello, World!".to_string()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_included_wit_files_define_an_item_twice() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut include = Array::default();
+        include.push("common-wit/*.wit");
+        doc["package"]["metadata"]["component"]["target"]["include"] = value(include);
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("common-wit"))?;
+    fs::write(
+        project.root().join("common-wit/world.wit"),
+        r#"package component:foo
+
+world example {
+    export hello-world: func() -> string
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "world `example` is defined more than once in the target package",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn it_builds_with_generated_wit_files() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut generate = Table::new();
+        let mut command = Array::default();
+        command.push("sh");
+        command.push("gen.sh");
+        command.push("idl/greeter service.idl");
+        generate["command"] = value(command);
+        let mut inputs = Array::default();
+        inputs.push("idl");
+        generate["inputs"] = value(inputs);
+        doc["package"]["metadata"]["component"]["generate"] = Item::Table(generate);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("gen.sh"),
+        "cp \"$1\" \"$OUT_DIR/greeter.wit\"\n",
+    )?;
+
+    fs::create_dir_all(project.root().join("idl"))?;
+    fs::write(
+        project.root().join("idl/greeter service.idl"),
+        r#"package component:foo
+
+interface greeter {
+    greet: func() -> string
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo
+
+world example {
+    export greeter
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::exports::component::foo::greeter::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet() -> String {
+        "Hello, World!".to_string()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(
+            contains("Generating WIT files for package `foo`")
+                .and(contains("Finished dev [unoptimized + debuginfo] target(s)")),
+        )
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    // The command only runs again when its inputs change
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Generating").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_a_no_std_component() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let no_std = &mut doc["package"]["metadata"]["component"]["bindings"]["no-std"];
+        no_std["panic-handler"] = value(true);
+        no_std["global-allocator"] = value("crate::Allocator");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"#![no_std]
+
+extern crate alloc;
+
+cargo_component_bindings::generate!();
+
+use alloc::string::{String, ToString};
+use bindings::Guest;
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+};
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        "Hello, World!".to_string()
+    }
+}
+
+const HEAP_SIZE: usize = 65536;
+
+static mut HEAP: [u8; HEAP_SIZE] = [0; HEAP_SIZE];
+
+/// A bump allocator that never frees.
+struct Allocator;
+
+struct Next(Cell<usize>);
+
+// Components are single-threaded
+unsafe impl Sync for Next {}
+
+static NEXT: Next = Next(Cell::new(0));
+
+unsafe impl GlobalAlloc for Allocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let base = core::ptr::addr_of_mut!(HEAP) as usize;
+        let start = (base + NEXT.0.get() + layout.align() - 1) & !(layout.align() - 1);
+        let end = start + layout.size();
+        if end > base + HEAP_SIZE {
+            return core::ptr::null_mut();
+        }
+
+        NEXT.0.set(end - base);
+        start as *mut u8
+    }
+
+    unsafe fn dealloc(&self, _: *mut u8, _: Layout) {}
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_re_exported_transitive_interfaces() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["transitive"]["component:foo/types"] =
+            value("export");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo
+
+interface types {
+    record point {
+        x: u32,
+        y: u32,
+    }
+}
+
+interface shapes {
+    use types.{point}
+
+    area: func(corner: point) -> u32
+}
+
+world example {
+    export shapes
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::exports::component::foo::{shapes::Guest, types::Point};
+
+struct Component;
+
+impl Guest for Component {
+    fn area(corner: Point) -> u32 {
+        corner.x * corner.y
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component("inspect")
+        .assert()
+        .stdout(contains("export: interface `component:foo/types`"))
+        .success();
+
+    // An import cannot use the types of the re-exported interface
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo
+
+interface types {
+    record point {
+        x: u32,
+        y: u32,
+    }
+}
+
+interface shapes {
+    use types.{point}
+
+    area: func(corner: point) -> u32
+}
+
+interface logger {
+    use types.{point}
+
+    log: func(at: point)
+}
+
+world example {
+    import logger
+    export shapes
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "imported `component:foo/logger` uses the types of interface `component:foo/types`, which is set to `export`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_renamed_component_dependency_modules() -> Result<()> {
+    let root = create_root()?;
+
+    let comp1 = Project::with_root(&root, "comp1", "")?;
+    comp1.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp1.root().join("wit/world.wit"),
+        "
+package my:comp1
+
+interface greeting {
+    hello: func() -> string
+}
+
+world example {
+    export greeting
+    export version: func() -> u32
+}
+",
+    )?;
+
+    fs::write(
+        comp1.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::{exports::my::comp1::greeting, Guest};
+
+struct Component;
+
+impl Guest for Component {
+    fn version() -> u32 {
+        1
+    }
+}
+
+impl greeting::Guest for Component {
+    fn hello() -> String {
+        "hello".to_string()
+    }
+}
+"#,
+    )?;
+
+    comp1
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let dep = comp1.debug_wasm("comp1");
+    validate_component(&dep)?;
+
+    let comp2 = Project::with_root(&root, "comp2", "")?;
+    comp2.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let dependency = &mut doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"];
+        dependency["path"] = value(dep.display().to_string());
+        dependency["module"] = value("greeter");
+        dependency["interfaces"]["greeting"] = value("messages");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp2.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::{greeter, greeter_messages, Guest};
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        format!("{} {}", greeter_messages::hello(), greeter::version())
+    }
+}
+"#,
+    )?;
+
+    comp2
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&comp2.debug_wasm("comp2"))?;
+
+    // Renaming an interface that is not exported is an error
+    comp2.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"]["interfaces"]
+            ["missing"] = value("other");
+        Ok(doc)
+    })?;
+
+    comp2
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "the dependency does not export an interface named `missing`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_exports_interfaces_at_additional_versions() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["export-versions"]["my:greeting/greeter"] =
+            value(Array::from_iter(["0.2.0", "0.2.1"]));
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package my:greeting@0.2.3
+
+interface greeter {
+    greet: func(name: string) -> string
+}
+
+world example {
+    export greeter
+}
+",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::exports::my::greeting::greeter::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet(name: String) -> String {
+        format!("Hello, {name}!")
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let path = project.debug_wasm("foo");
+    validate_component(&path)?;
+
+    let bytes = fs::read(&path)?;
+    let mut exports = Vec::new();
+    let mut depth = 0;
+    for payload in wasmparser::Parser::new(0).parse_all(&bytes) {
+        match payload? {
+            wasmparser::Payload::ModuleSection { .. }
+            | wasmparser::Payload::ComponentSection { .. } => depth += 1,
+            wasmparser::Payload::End(_) => depth -= 1,
+            wasmparser::Payload::ComponentExportSection(reader) if depth == 0 => {
+                for export in reader {
+                    exports.push(export?.name.0.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    assert_eq!(
+        exports,
+        [
+            "my:greeting/greeter@0.2.3",
+            "my:greeting/greeter@0.2.0",
+            "my:greeting/greeter@0.2.1"
+        ]
+    );
+
+    // Exporting a version of an interface that is not exported is an error
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["export-versions"]["my:greeting/missing"] =
+            value(Array::from_iter(["0.1.0"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "component does not export a version of interface `my:greeting/missing`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_adapter() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["adapter"] = value("not-a-valid-path");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("error: failed to read module adapter"))
+        .failure();
+
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["adapter"] = value(format!(
+            "../../../../../adapters/{version}/wasi_snapshot_preview1.reactor.wasm",
+            version = env!("WASI_ADAPTER_VERSION")
+        ));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_builds_with_an_adapter_from_a_registry() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let adapter = fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/adapters/",
+        env!("WASI_ADAPTER_VERSION"),
+        "/wasi_snapshot_preview1.reactor.wasm"
+    ))?;
+    publish(
+        &config,
+        &"wasi:reactor-adapter".parse()?,
+        "1.0.0",
+        adapter,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let adapter = &mut doc["package"]["metadata"]["component"]["adapter"];
+        adapter["package"] = value("wasi:reactor-adapter");
+        adapter["version"] = value("1.0.0");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    // The adapter is pinned in the lock file
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains(r#"id = "wasi:reactor-adapter""#));
+    assert!(lock_file.contains(r#"version = "1.0.0""#));
+    assert!(lock_file.contains("digest = "));
+
+    // Building again does not need the registry
+    project
+        .cargo_component("build --offline")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_builds_with_a_patched_registry_package() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.0.0",
+        r#"package foo:bar@1.0.0
+world foo {
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "--target foo:bar@1.0.0")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["patch"]["foo:bar"] =
+            value(InlineTable::from_iter([("path", "patch/foo.wit")]));
+        Ok(doc)
+    })?;
+
+    // The unreleased version of the package adds an export
+    fs::create_dir_all(project.root().join("patch"))?;
+    fs::write(
+        project.root().join("patch/foo.wit"),
+        r#"package foo:bar@1.0.0
+world foo {
+    export bar: func() -> string
+    export baz: func() -> string
+}"#,
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn bar() -> String {
+        "bar".to_string()
+    }
+
+    fn baz() -> String {
+        "baz".to_string()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("component"))?;
+
+    // Patched packages are not locked
+    let lock_file =
+        fs::read_to_string(project.root().join("Cargo-component.lock")).unwrap_or_default();
+    assert!(!lock_file.contains(r#"id = "foo:bar""#));
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_a_git_dependency() -> Result<()> {
+    let root = create_root()?;
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    // The repository contains a component crate with a custom target path
+    let repo = root.join("repo");
+    let commit = git_commit(
+        &repo,
+        &[
+            (
+                "Cargo.toml",
+                r#"[package]
+name = "bar"
+version = "0.1.0"
+
+[package.metadata.component.target]
+path = "interface"
+"#,
+            ),
+            (
+                "interface/bar.wit",
+                "package foo:bar
+
+interface baz {
+    baz: func() -> u32
+}",
+            ),
+        ],
+    )?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["git"] = value(repo.to_str().unwrap());
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo
+
+world example {
+    export foo:bar/baz
+}",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        "cargo_component_bindings::generate!();
+use bindings::exports::foo::bar::baz::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz() -> u32 {
+        42
+    }
+}
+",
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Updating git repository"))
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    // The resolved commit is recorded in the lock file
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("[[git]]"));
+    assert!(lock_file.contains(&commit));
+
+    project
+        .cargo_component("build --offline")
+        .assert()
+        .stderr(contains("Updating git repository").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_optimizes_the_core_module() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["profile"]["dev"]["optimize"] = value("s");
+        Ok(doc)
+    })?;
+
+    // An explicitly configured optimization fails without `wasm-opt`
+    project
+        .cargo_component("build")
+        .env("CARGO_COMPONENT_WASM_OPT", "wasm-opt-does-not-exist")
+        .assert()
+        .stderr(contains(
+            "failed to run `wasm-opt-does-not-exist` to optimize module",
+        ))
+        .failure();
+
+    // The default optimization of release builds is skipped without `wasm-opt`
+    project
+        .cargo_component("build --release")
+        .env("CARGO_COMPONENT_WASM_OPT", "wasm-opt-does-not-exist")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    validate_component(&project.release_wasm("foo"))?;
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["profile"]["dev"]["optimize"] = value("fast");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("invalid optimization level `fast`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_strips_custom_sections() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let profile = &mut doc["package"]["metadata"]["component"]["profile"]["dev"];
+        profile["debug-info"] = value("split");
+        profile["name-section"] = value("strip");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Stripped component"))
+        .success();
+
+    let path = project.debug_wasm("foo");
+    validate_component(&path)?;
+
+    let custom_sections = |bytes: &[u8]| -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+            if let wasmparser::Payload::CustomSection(reader) = payload? {
+                names.push(reader.name().to_string());
+            }
+        }
+        Ok(names)
+    };
+
+    let names = custom_sections(&fs::read(&path)?)?;
+    assert!(!names
+        .iter()
+        .any(|n| n.starts_with(".debug_") || n == "name"));
+    assert!(names.iter().any(|n| n == "external_debug_info"));
+    assert!(names.iter().any(|n| n == "producers"));
+
+    // The debug file keeps the debug information
+    let names = custom_sections(&fs::read(path.with_extension("debug.wasm"))?)?;
+    assert!(names.iter().any(|n| n.starts_with(".debug_")));
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["profile"]["dev"]["producers"] = value("remove");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("unknown variant `remove`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_debug_info_with_debug_info_argument() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["profile"]["release"]["debug-info"] =
+            value("strip");
+        Ok(doc)
+    })?;
+
+    let has_debug_info = |bytes: &[u8]| -> Result<bool> {
+        for payload in wasmparser::Parser::new(0).parse_all(bytes) {
+            if let wasmparser::Payload::CustomSection(reader) = payload? {
+                if reader.name().starts_with(".debug_") {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    };
+
+    // The argument takes precedence over the setting of the profile
+    project
+        .cargo_component("build --release --debug-info keep")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    let path = project.release_wasm("foo");
+    validate_component(&path)?;
+    assert!(has_debug_info(&fs::read(&path)?)?);
+
+    project
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("Stripped component"))
+        .success();
+
+    validate_component(&path)?;
+    assert!(!has_debug_info(&fs::read(&path)?)?);
+
+    project
+        .cargo_component("build --release --debug-info full")
+        .assert()
+        .stderr(contains("unsupported `--debug-info` value `full`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_deployment_manifest() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["config"]["greeting"] = value("hello");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --manifest-out deploy.json")
+        .assert()
+        .stderr(contains("Created deployment manifest `deploy.json`"))
+        .success();
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(project.root().join("deploy.json"))?)?;
+    let component = &manifest["components"][0];
+    assert_eq!(component["package"], "foo");
+    assert_eq!(component["exports"], serde_json::json!(["hello-world"]));
+    assert_eq!(component["config"], serde_json::json!(["greeting"]));
+    assert!(component["capabilities"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|c| c.as_str().unwrap().starts_with("wasi:")));
+    assert!(
+        component["limits"]["memory-initial-bytes"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+
+    project
+        .cargo_component("build --manifest-out deploy/manifest.yaml")
+        .assert()
+        .stderr(contains("Created deployment manifest"))
+        .success();
+
+    let yaml = fs::read_to_string(project.root().join("deploy/manifest.yaml"))?;
+    assert!(yaml.starts_with("components:\n- "));
+    assert!(yaml.contains("  package: \"foo\"\n"));
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_multiple_bin_targets() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["bin"]["bar"]["adapter"] = value("command");
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("src/bin"))?;
+    fs::write(
+        project.root().join("src/bin/bar.rs"),
+        r#"fn main() {
+    println!("bar");
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+    validate_component(&project.debug_wasm("bar"))?;
+
+    // Only the selected binary is componentized
+    project
+        .cargo_component("build --release --bin bar")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    validate_component(&project.release_wasm("bar"))?;
+    assert!(!project.release_wasm("foo").exists());
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_profile_and_bin_adapters() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let component = &mut doc["package"]["metadata"]["component"];
+        component["adapter"] = value("reactor");
+        component["profile"]["release"]["adapter"] = value("not-a-valid-path");
+        component["bin"]["foo"]["adapter"] = value("command");
+        Ok(doc)
+    })?;
+
+    // The binary target's adapter takes precedence over the global and profile adapters
+    project
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    validate_component(&project.release_wasm("foo"))?;
+
+    project.update_manifest(|mut doc| {
+        let component = &mut doc["package"]["metadata"]["component"];
+        component.as_table_mut().unwrap().remove("bin");
+        component["adapter"] = value("command");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("error: failed to read module adapter"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_adapter_bin_target_does_not_exist() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["bin"]["bar"]["adapter"] = value("command");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("binary target `bar` in the component metadata"))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        let component = &mut doc["package"]["metadata"]["component"];
+        component.as_table_mut().unwrap().remove("bin");
+        component["adapter"] = value("proxy");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("there is no built-in `proxy` adapter"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_adapter_is_not_wasm() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["adapter"] = value("foo.wasm");
+        Ok(doc)
+    })?;
+
+    fs::write(project.root().join("foo.wasm"), "not wasm")?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("error: failed to load adapter module"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_adds_additional_derives() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    std::process::Command::new("cargo")
+        .args(["add", "serde", "--features", "derive"])
+        .current_dir(project.root())
+        .assert()
+        .success();
+    std::process::Command::new("cargo")
+        .args(["add", "serde_json"])
+        .current_dir(project.root())
+        .assert()
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:derive
+
+interface foo {
+    record bar {
+        value: u32,
+    }
+}
+
+world foo-world {
+    use foo.{bar}
+
+    export baz: func(thing: bar) -> list<u8>
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!({
+    additional_derives: [serde::Serialize, serde::Deserialize]
+});
+
+use bindings::Guest;
+use bindings::my::derive::foo::Bar;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz(thing: Bar) -> Vec<u8> {
+        let stuff = serde_json::to_vec(&thing).unwrap();
+        // Check we got the derived deserialize
+        let _thing: Bar = serde_json::from_slice(&stuff).unwrap();
+        stuff
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_adds_additional_derives_from_the_manifest() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+
+        let mut bindings = Table::new();
+        bindings["additional-derives"] = value(Array::from_iter(["PartialEq", "Eq", "Hash"]));
+        bindings["ownership"] = value("owning");
+        doc["package"]["metadata"]["component"]["bindings"] = Item::Table(bindings);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:derive
+
+interface foo {
+    record bar {
+        value: u32,
+    }
+}
+
+world foo-world {
+    use foo.{bar}
+
+    export baz: func(things: list<bar>) -> u32
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!({
+    additional_derives: [PartialEq]
+});
+
+use bindings::Guest;
+use bindings::my::derive::foo::Bar;
+use std::collections::HashSet;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz(things: Vec<Bar>) -> u32 {
+        // Check we got the derives from the manifest
+        things.into_iter().collect::<HashSet<_>>().len() as u32
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_generates_conveniences_for_configured_types() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+
+        let mut point = Table::new();
+        point["builder"] = value(true);
+        point["default"] = value(true);
+        let mut failure = Table::new();
+        failure["error"] = value(true);
+        let mut types = Table::new();
+        types["my:conv/types/point"] = Item::Table(point);
+        types["my:conv/types/failure"] = Item::Table(failure);
+        let mut bindings = Table::new();
+        bindings["types"] = Item::Table(types);
+        doc["package"]["metadata"]["component"]["bindings"] = Item::Table(bindings);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:conv
+
+interface types {
+    record point {
+        x: s32,
+        y: s32,
+        label: option<string>,
+    }
+
+    enum failure {
+        /// The value was out of range.
+        out-of-range,
+        unknown,
+    }
+}
+
+world foo-world {
+    use types.{point}
+
+    export make: func(x: s32) -> point
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::my::conv::types::{Failure, Point};
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn make(x: i32) -> Point {
+        let error: &dyn std::error::Error = &Failure::OutOfRange;
+        assert_eq!(error.to_string(), "The value was out of range.");
+        assert!(Point::builder().y(1).build().is_err());
+
+        let default = Point::default();
+        Point::builder()
+            .x(x)
+            .y(default.y)
+            .label("hello")
+            .build()
+            .unwrap()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["types"]["my:conv/types/missing"]
+            ["default"] = value(true);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "type `my:conv/types/missing` was not found in the target world",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_named_targets() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["world"] = value("foo");
+
+        let mut plugin = Table::new();
+        plugin["world"] = value("plugin");
+        let mut targets = Table::new();
+        targets["plugin"] = Item::Table(plugin);
+        doc["package"]["metadata"]["component"]["targets"] = Item::Table(targets);
+
+        doc["features"]["plugin"] = value(toml_edit::Array::new());
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:targets
+
+world foo {
+    export hello: func() -> string
+}
+
+world plugin {
+    export run: func(input: string) -> string
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn hello() -> String {
+        "hello".to_string()
+    }
+}
+
+#[cfg(feature = "plugin")]
+mod plugin {
+    cargo_component_bindings::generate!({
+        target: "plugin",
+    });
+
+    use bindings::Guest;
+
+    struct Component;
+
+    impl Guest for Component {
+        fn run(input: String) -> String {
+            input
+        }
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component("build --features plugin")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_a_bindings_crate() -> Result<()> {
+    let project = project()?
+        .file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["foo", "foo-bindings", "helper"]
+"#,
+        )?
+        .file(
+            "foo-bindings/Cargo.toml",
+            r#"[package]
+name = "foo-bindings"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+cargo-component-bindings = { path = "../../../../../crates/bindings" }
+"#,
+        )?
+        .file(
+            "foo-bindings/src/lib.rs",
+            "cargo_component_bindings::generate!();\n",
+        )?
+        .file(
+            "helper/Cargo.toml",
+            r#"[package]
+name = "helper"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+foo-bindings = { path = "../foo-bindings" }
+"#,
+        )?
+        .file(
+            "helper/src/lib.rs",
+            "pub fn greeting() -> String {
+    format!(\"Hello, {name}!\", name = foo_bindings::bindings::component::foo::host::get_name())
+}
+",
+        )?
+        .build();
+
+    project
+        .cargo_component("new --reactor foo")
+        .assert()
+        .stderr(contains("Updated manifest of package `foo`"))
+        .success();
+
+    let member = ProjectBuilder::new(project.root().join("foo")).build();
+    member.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["bindings"]["crate"] = value("foo-bindings");
+        doc["dependencies"]["helper"] = value(InlineTable::from_iter([("path", "../helper")]));
+        Ok(doc)
+    })?;
+
+    fs::write(
+        member.root().join("wit/world.wit"),
+        "package component:foo
+
+interface host {
+    get-name: func() -> string
+}
+
+world example {
+    import host
+    export hello-world: func() -> string
+}
+",
+    )?;
+
+    fs::write(
+        member.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        helper::greeting()
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_artifact_dependencies() -> Result<()> {
+    let project = project()?
+        .file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["guest", "host"]
+"#,
+        )?
+        .file(
+            "host/Cargo.toml",
+            r#"[package]
+name = "host"
+version = "0.1.0"
+edition = "2021"
+
+[package.metadata]
+component-artifacts = ["guest"]
+"#,
+        )?
+        .file(
+            "host/src/main.rs",
+            r#"const GUEST: &[u8] = include_bytes!(env!("CARGO_COMPONENT_ARTIFACT_GUEST"));
+
+fn main() {
+    assert_eq!(&GUEST[..4], b"\0asm");
+}
+"#,
+        )?
+        .build();
+
+    project
+        .cargo_component("new --reactor guest")
+        .assert()
+        .stderr(contains("Updated manifest of package `guest`"))
+        .success();
+
+    let member = ProjectBuilder::new(project.root().join("guest")).build();
+    member.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    // The host is built for the host target after the guest component
+    project
+        .cargo_component("build -p host")
+        .assert()
+        .stderr(contains("Building artifact dependency `guest`"))
+        .success();
+
+    validate_component(&project.debug_wasm("guest"))?;
+    assert!(project
+        .build_dir()
+        .join("debug")
+        .join(format!(
+            "host{suffix}",
+            suffix = std::env::consts::EXE_SUFFIX
+        ))
+        .is_file());
+
+    member.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component-artifacts"] = value(Array::from_iter(["host"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build -p host")
+        .assert()
+        .stderr(contains(
+            "cyclic artifact dependency: `host` -> `guest` -> `host`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_remapped_interfaces() -> Result<()> {
+    let project = project()?
+        .file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["foo", "foo-bindings"]
+"#,
+        )?
+        .file(
+            "foo-bindings/Cargo.toml",
+            r#"[package]
+name = "foo-bindings"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+cargo-component-bindings = { path = "../../../../../crates/bindings" }
+"#,
+        )?
+        .file(
+            "foo-bindings/src/lib.rs",
+            "cargo_component_bindings::generate!();\n",
+        )?
+        .build();
+
+    project
+        .cargo_component("new --reactor foo")
+        .assert()
+        .stderr(contains("Updated manifest of package `foo`"))
+        .success();
+
+    let member = ProjectBuilder::new(project.root().join("foo")).build();
+    member.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let bindings = &mut doc["package"]["metadata"]["component"]["bindings"];
+        bindings["crate"] = value("foo-bindings");
+        bindings["with"]["component:foo/host"] =
+            value("foo_bindings::bindings::component::foo::host");
+        doc["dependencies"]["foo-bindings"] =
+            value(InlineTable::from_iter([("path", "../foo-bindings")]));
+        Ok(doc)
+    })?;
+
+    fs::write(
+        member.root().join("wit/world.wit"),
+        "package component:foo
+
+interface host {
+    record point {
+        x: u32,
+        y: u32,
+    }
+
+    get-point: func() -> point
+}
+
+world example {
+    import host
+    export hello: func() -> u32
+}
+",
+    )?;
+
+    // The remapped types are the types of the bindings crate
+    fs::write(
+        member.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+fn sum(point: foo_bindings::bindings::component::foo::host::Point) -> u32 {
+    point.x + point.y
+}
+
+struct Component;
+
+impl Guest for Component {
+    fn hello() -> u32 {
+        sum(bindings::component::foo::host::get_point())
     }
 }
 "#,
@@ -844,5 +3370,173 @@ impl Guest for Component {
 
     validate_component(&project.debug_wasm("foo"))?;
 
+    member.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["with"]["component:foo/missing"] =
+            value("foo_bindings::bindings");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "interface `component:foo/missing` in `package.metadata.component.bindings.with` is not imported by the target world",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_bindings_crate_is_not_a_workspace_member() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["bindings"]["crate"] = value("missing");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "bindings crate `missing` of package `foo` is not a member of the workspace",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_resolves_several_packages_from_a_cold_cache() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    // The `a` and `b` packages of each registry have the same content, so
+    // their downloads race to write the same digest to the cache
+    let components = [
+        ("a", "(component)"),
+        ("b", "(component)"),
+        ("c", "(component (core module))"),
+    ];
+
+    let dir = root.join("static");
+    for (name, wat) in components {
+        publish_component(&config, &format!("warg:{name}"), "1.0.0", wat, true).await?;
+        publish_static(
+            &dir,
+            &format!("static:{name}"),
+            "1.0.0",
+            &wat::parse_str(wat)?,
+            false,
+        )?;
+    }
+    let url = spawn_static_registry(&dir)?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["registries"]["static"] = value(url.as_str());
+        let mut dependencies = Table::new();
+        for (name, _) in components {
+            dependencies[&format!("warg:{name}")] = value("1.0.0");
+            dependencies[&format!("static:{name}")] = value(InlineTable::from_iter([
+                ("version", "1.0.0"),
+                ("registry", "static"),
+            ]));
+        }
+        doc["package"]["metadata"]["component"]["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    for (name, _) in components {
+        assert!(lock_file.contains(&format!(r#"id = "warg:{name}""#)));
+        assert!(lock_file.contains(&format!(r#"id = "static:{name}""#)));
+    }
+
+    // Each distinct content is cached once, without any temporary files
+    let cached: Vec<_> = fs::read_dir(root.join("content/components/sha256"))?
+        .map(|e| e.map(|e| e.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(cached.len(), 2, "unexpected cache contents: {cached:?}");
+
+    // Building again from the populated caches doesn't download anything
+    project
+        .cargo_component("build --offline")
+        .assert()
+        .stderr(contains("Downloading").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_resolves_namespace_patterns_with_fallback_registries() -> Result<()> {
+    let root = create_root()?;
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    // The mirror only has some of the packages of the public registry
+    let mirror = root.join("mirror");
+    let public = root.join("public");
+    let component = wat::parse_str("(component)")?;
+    publish_static(&mirror, "wasi:io", "1.0.0", &component, false)?;
+    publish_static(&mirror, "mycorp-tools:a", "1.0.0", &component, false)?;
+    publish_static(&public, "mycorp-tools:a", "1.0.0", &component, false)?;
+    publish_static(&public, "mycorp-libs:b", "1.0.0", &component, false)?;
+    let mirror_url = spawn_static_registry(&mirror)?;
+    let public_url = spawn_static_registry(&public)?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let component = &mut doc["package"]["metadata"]["component"];
+        component["registries"]["mirror"] = value(mirror_url.as_str());
+        component["registries"]["public"] = value(public_url.as_str());
+        component["namespaces"]["wasi:*"] = value("mirror");
+        component["namespaces"]["mycorp-*"] = value(Array::from_iter(["mirror", "public"]));
+        for id in ["wasi:io", "mycorp-tools:a", "mycorp-libs:b"] {
+            component["dependencies"][id] = value("1.0.0");
+        }
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    for (id, registry) in [
+        ("wasi:io", "mirror"),
+        ("mycorp-tools:a", "mirror"),
+        ("mycorp-libs:b", "public"),
+    ] {
+        assert!(
+            lock_file.contains(&format!("id = \"{id}\"\nregistry = \"{registry}\"")),
+            "expected `{id}` to be locked to registry `{registry}`:\n{lock_file}"
+        );
+    }
+
+    // The locked registries are used without searching the registries again
+    project
+        .cargo_component("build --offline")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
     Ok(())
 }
@@ -0,0 +1,99 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help cache", "cache -h", "cache --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Manage the cache of downloaded packages shared by every project",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_manages_the_cache() -> Result<()> {
+    let root = create_root()?;
+    let content = root.join("content");
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(content.clone()),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    fs::create_dir_all(content.join("sha256"))?;
+    fs::write(content.join("sha256").join("a"), "warg")?;
+    fs::create_dir_all(content.join("components").join("sha256"))?;
+    fs::write(content.join("components").join("sha256").join("b"), "oci")?;
+    fs::create_dir_all(
+        content
+            .join("git")
+            .join("checkouts")
+            .join("repo")
+            .join("commit"),
+    )?;
+    fs::write(
+        content
+            .join("git")
+            .join("checkouts")
+            .join("repo")
+            .join("commit")
+            .join("lib.rs"),
+        "",
+    )?;
+
+    cargo_component("cache dir")
+        .current_dir(&root)
+        .assert()
+        .stdout(contains(format!(
+            "warg {path}",
+            path = content.join("sha256").display()
+        )))
+        .stdout(contains(format!(
+            "git {path}",
+            path = content.join("git").display()
+        )))
+        .success();
+
+    cargo_component("cache gc")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains("Removed 0 files (0 bytes) from the cache"))
+        .success();
+
+    assert!(content.join("sha256").join("a").is_file());
+
+    cargo_component("cache gc --max-age 0")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains("Removed 3 files (7 bytes) from the cache"))
+        .success();
+
+    assert!(!content.join("sha256").join("a").exists());
+    assert!(!content
+        .join("git")
+        .join("checkouts")
+        .join("repo")
+        .join("commit")
+        .exists());
+
+    fs::write(content.join("components").join("sha256").join("b"), "oci")?;
+
+    cargo_component("cache clean")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains("Removed 1 file (3 bytes) from the cache"))
+        .success();
+
+    assert!(!content.join("components").join("sha256").exists());
+
+    Ok(())
+}
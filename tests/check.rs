@@ -99,3 +99,36 @@ edition = "2021"
 
     Ok(())
 }
+
+#[test]
+fn it_checks_without_componentizing() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("check")
+        .assert()
+        .stderr(contains("Encoding target for foo").and(contains("Checking foo v0.1.0")))
+        .success();
+
+    assert!(project
+        .build_dir()
+        .join("bindings/foo/target.wasm")
+        .is_file());
+    assert!(!project.debug_wasm("foo").exists());
+
+    // The bindings generated by `check` are reused by `build`
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .stderr(contains("Encoding target").not())
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
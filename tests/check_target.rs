@@ -0,0 +1,80 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in [
+        "help check-target",
+        "check-target -h",
+        "check-target --help",
+    ] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Check that a component is compatible with the world of a host",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_requires_a_built_component() -> Result<()> {
+    let project = Project::new("component")?;
+
+    project
+        .cargo_component("check-target --host host.wit")
+        .assert()
+        .stderr(contains("run `cargo component build` first"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_checks_a_component_against_a_host() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    fs::write(
+        project.root().join("host.wit"),
+        "package test:host;
+
+world host {
+    export run: func();
+}
+",
+    )?;
+
+    project
+        .cargo_component("check-target --host host.wit")
+        .assert()
+        .stderr(
+            contains("the host expects export `run`, which the component does not have")
+                .and(contains("is not compatible with host `host.wit`")),
+        )
+        .failure();
+
+    // A component is always compatible with its own world
+    let wasm = project.debug_wasm("component");
+    project
+        .cargo_component(&format!("check-target --host {}", wasm.display()))
+        .assert()
+        .stderr(contains("Compatible component"))
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help clean", "clean -h", "clean --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Remove generated bindings, componentized outputs, and downloaded packages",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_cleans_component_artifacts() -> Result<()> {
+    let root = create_root()?;
+    let content = root.join("content");
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(content.clone()),
+    }
+    .write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build --emit core-wasm")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let wasm = project.debug_wasm("foo");
+    assert!(wasm.is_file());
+    assert!(wasm.with_extension("core.wasm").is_file());
+
+    fs::create_dir_all(content.join("sha256"))?;
+    fs::write(content.join("sha256").join("a"), "warg")?;
+
+    project
+        .cargo_component("clean --bindings")
+        .assert()
+        .stderr(contains("Removed"))
+        .success();
+
+    assert!(!project.build_dir().join("bindings").exists());
+    assert!(wasm.is_file());
+    assert!(content.join("sha256").join("a").is_file());
+
+    project
+        .cargo_component("clean --release")
+        .assert()
+        .stderr(contains("Removed"))
+        .success();
+
+    assert!(wasm.is_file());
+    assert!(!content.join("sha256").join("a").exists());
+
+    project
+        .cargo_component("clean")
+        .assert()
+        .stderr(contains("Removed"))
+        .success();
+
+    assert!(!wasm.exists());
+    assert!(!wasm.with_extension("core.wasm").exists());
+    assert!(project
+        .build_dir()
+        .join("wasm32-wasi")
+        .join("debug")
+        .join("deps")
+        .is_dir());
+
+    // Building again creates the component from scratch
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Creating component"))
+        .success();
+
+    validate_component(&wasm)?;
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::{fs, path::Path};
+use toml_edit::value;
+use wasmparser::{Parser, Payload};
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help compose", "compose -h", "compose --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Compose a component with its component dependencies",
+            ))
+            .success();
+    }
+}
+
+/// Gets the names of the top-level imports of a component.
+fn component_imports(path: &Path) -> Result<Vec<String>> {
+    let bytes = fs::read(path)?;
+    let mut imports = Vec::new();
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(&bytes) {
+        match payload? {
+            Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+            Payload::End(_) => depth -= 1,
+            Payload::ComponentImportSection(reader) if depth == 0 => {
+                for import in reader {
+                    imports.push(import?.name.0.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(imports)
+}
+
+#[test]
+fn it_composes_with_a_component_dependency() -> Result<()> {
+    let root = create_root()?;
+
+    let comp1 = Project::with_root(&root, "comp1", "")?;
+    comp1.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp1.root().join("wit/world.wit"),
+        "
+package my:comp1
+
+world greeter {
+    export greet: func(name: string) -> string
+}
+",
+    )?;
+
+    fs::write(
+        comp1.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet(name: String) -> String {
+        format!("Hello, {name}!")
+    }
+}
+"#,
+    )?;
+
+    comp1
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    let dep = comp1.release_wasm("comp1");
+    validate_component(&dep)?;
+
+    let comp2 = Project::with_root(&root, "comp2", "")?;
+    comp2.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"]["path"] =
+            value(dep.display().to_string());
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp2.root().join("wit/world.wit"),
+        "
+package my:comp2
+
+world app {
+    export run: func() -> string
+}
+",
+    )?;
+
+    fs::write(
+        comp2.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::{Guest, comp1};
+
+struct Component;
+
+impl Guest for Component {
+    fn run() -> String {
+        comp1::greet("World")
+    }
+}
+"#,
+    )?;
+
+    comp2
+        .cargo_component("compose --release")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .stderr(contains("Composed component"))
+        .success();
+
+    let built = comp2.release_wasm("comp2");
+    assert!(component_imports(&built)?.contains(&"comp1".to_string()));
+
+    let composed = built.with_extension("composed.wasm");
+    validate_component(&composed)?;
+    assert!(!component_imports(&composed)?.contains(&"comp1".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn it_composes_with_a_wac_script_on_build() -> Result<()> {
+    let root = create_root()?;
+
+    let comp1 = Project::with_root(&root, "comp1", "")?;
+    comp1.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp1.root().join("wit/world.wit"),
+        "
+package my:comp1
+
+world greeter {
+    export greet: func(name: string) -> string
+}
+",
+    )?;
+
+    fs::write(
+        comp1.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet(name: String) -> String {
+        format!("Hello, {name}!")
+    }
+}
+"#,
+    )?;
+
+    comp1
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .success();
+
+    let dep = comp1.release_wasm("comp1");
+    validate_component(&dep)?;
+
+    let comp2 = Project::with_root(&root, "comp2", "")?;
+    comp2.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"]["path"] =
+            value(dep.display().to_string());
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp2.root().join("wit/world.wit"),
+        "
+package my:comp2
+
+world app {
+    export run: func() -> string
+}
+",
+    )?;
+
+    fs::write(
+        comp2.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::{Guest, comp1};
+
+struct Component;
+
+impl Guest for Component {
+    fn run() -> String {
+        comp1::greet("World")
+    }
+}
+"#,
+    )?;
+
+    fs::write(
+        comp2.root().join("compose.wac"),
+        "
+// The application with its greeter
+package my:app;
+
+let greeter = new my:comp1 { ... };
+let app = new component:comp2 { comp1: greeter, ... };
+
+export app...;
+",
+    )?;
+
+    comp2
+        .cargo_component("build --release")
+        .assert()
+        .stderr(contains("Finished release [optimized] target(s)"))
+        .stderr(contains("Composed `my:app`"))
+        .success();
+
+    let built = comp2.release_wasm("comp2");
+    assert!(component_imports(&built)?.contains(&"comp1".to_string()));
+
+    let composed = built.with_extension("composed.wasm");
+    validate_component(&composed)?;
+    assert!(!component_imports(&composed)?.contains(&"comp1".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_on_an_unknown_wac_package() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("compose.wac"),
+        "let dep = new my:missing { ... };\nexport dep...;\n",
+    )?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains(
+            "package `my:missing` referenced by composition script",
+        ))
+        .failure();
+
+    Ok(())
+}
@@ -33,3 +33,88 @@ fn it_documents() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_documents_the_target_world() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package my:foo
+
+/// Types used by <the> greeter.
+interface types {
+    /// A greeting.
+    record greeting {
+        /// The text of the greeting.
+        text: string,
+    }
+
+    /// A counter of greetings.
+    resource counter {
+        constructor(start: u32)
+        /// Gets the current count.
+        get: func() -> u32
+    }
+}
+
+/// An example world for the component to target.
+world example {
+    use types.{greeting}
+
+    /// Greet someone.
+    export greet: func(name: string) -> greeting
+}
+"#,
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"cargo_component_bindings::generate!();
+
+use bindings::{Greeting, Guest};
+
+struct Component;
+
+impl Guest for Component {
+    fn greet(name: String) -> Greeting {
+        Greeting {
+            text: format!("Hello, {name}!"),
+        }
+    }
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("doc")
+        .assert()
+        .stderr(contains("Documenting foo v0.1.0"))
+        .stderr(contains("Documented target world of `foo`"))
+        .success();
+
+    let html = fs::read_to_string(project.root().join("target/wasm32-wasi/doc/foo/wit.html"))?;
+    for expected in [
+        "World <code>my:foo/example</code>",
+        "An example world for the component to target.",
+        "Interface <code>my:foo/types</code>",
+        "Types used by &lt;the&gt; greeter.",
+        "<code>record greeting</code>",
+        "<code>text: string</code>",
+        "The text of the greeting.",
+        "<code>resource counter</code>",
+        "constructor(start: u32)",
+        "get: func() -&gt; u32",
+        "Greet someone.",
+        "greet: func(name: string) -&gt; greeting",
+        "href=\"bindings/index.html\"",
+    ] {
+        assert!(html.contains(expected), "missing `{expected}` in:\n{html}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+use toml_edit::value;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help explain", "explain -h", "explain --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Explain the fully resolved target world of a component",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_explains_the_target_world() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.2.3",
+        r#"package foo:bar@1.2.3
+interface types {
+    record greeting {
+        text: string,
+    }
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["dependencies"]["foo:bar"] =
+            value("1.2.3");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package my:component
+
+interface api {
+    use foo:bar/types@1.2.3.{greeting}
+    greet: func() -> greeting
+}
+
+world example {
+    export api
+    import log: func(message: string)
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("explain")
+        .assert()
+        .stdout(
+            contains("world `my:component/example`")
+                .and(contains(
+                    "  interface `foo:bar/types@1.2.3`\n    from package `foo:bar@1.2.3` (registry package `foo:bar@1.2.3`)\n    used by `my:component/api`",
+                ))
+                .and(contains("  function `log`\n    from the target world"))
+                .and(contains(
+                    "exports:\n  interface `my:component/api`\n    from the target world",
+                )),
+        )
+        .success();
+
+    project
+        .cargo_component("explain missing")
+        .assert()
+        .stderr(contains(
+            "package `component` does not have a target named `missing`",
+        ))
+        .failure();
+
+    Ok(())
+}
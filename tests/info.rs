@@ -0,0 +1,105 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help info", "info -h", "info --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Display information about a package in a registry",
+            ))
+            .success();
+    }
+
+    for arg in ["help search", "search -h", "search --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains("Search a registry for packages"))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_displays_package_information() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "my:world",
+        "1.0.0",
+        r#"package my:%world@1.0.0
+interface types {
+    type id = u32
+}
+world foo {
+    import types
+}"#,
+        true,
+    )
+    .await?;
+
+    publish_wit(
+        &config,
+        "my:world",
+        "1.1.0",
+        r#"package my:%world@1.1.0
+interface types {
+    type id = u32
+}
+world foo {
+    import types
+}
+world bar {
+    export types
+}"#,
+        false,
+    )
+    .await?;
+
+    cargo_component("info my:world")
+        .current_dir(&root)
+        .assert()
+        .stdout(contains("package: my:world"))
+        .stdout(contains("versions: 1.0.0, 1.1.0"))
+        .stdout(contains("inspected version: 1.1.0"))
+        .stdout(contains("kind: WIT package"))
+        .stdout(contains("world: bar"))
+        .stdout(contains("interface: types"))
+        .success();
+
+    cargo_component("info my:world@1.0.0")
+        .current_dir(&root)
+        .assert()
+        .stdout(contains("inspected version: 1.0.0"))
+        .stdout(contains("world: bar").not())
+        .success();
+
+    cargo_component("info my:missing")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains("package `my:missing` was not found"))
+        .failure();
+
+    cargo_component("search my:world")
+        .current_dir(&root)
+        .assert()
+        .stdout(contains("my:world = \"1.1.0\""))
+        .success();
+
+    cargo_component("search nothing-matches")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains(
+            "no packages matching `nothing-matches` were found",
+        ))
+        .success();
+
+    Ok(())
+}
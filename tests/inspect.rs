@@ -0,0 +1,69 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help inspect", "inspect -h", "inspect --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Inspect the world, metadata, and structure of a component",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_requires_a_built_component() -> Result<()> {
+    let project = Project::new("component")?;
+
+    project
+        .cargo_component("inspect")
+        .assert()
+        .stderr(contains("run `cargo component build` first"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_inspects_a_component() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    project
+        .cargo_component("inspect")
+        .assert()
+        .stdout(
+            contains("kind: component")
+                .and(contains("export: function `hello-world`"))
+                .and(contains("structure:"))
+                .and(contains("module (")),
+        )
+        .success();
+
+    let wasm = project.debug_wasm("component");
+    project
+        .cargo_component(&format!("inspect --message-format json {}", wasm.display()))
+        .assert()
+        .stdout(
+            contains(r#""kind":"component""#)
+                .and(contains(r#""exports":["function `hello-world`"]"#)),
+        )
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{
+    prelude::PredicateBooleanExt,
+    str::{contains, diff},
+};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help install", "install -h", "install --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Install a command component from a registry, git repository, or local path",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_installs_a_command_component_from_a_path() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    let install_root = project.root().join("install");
+    let args = format!(
+        "install --path {path} --root {root}",
+        path = project.root().display(),
+        root = install_root.display()
+    );
+
+    project
+        .cargo_component(&args)
+        .assert()
+        .stderr(contains("Creating component").and(contains("Installed command `foo`")))
+        .success();
+
+    let component = install_root.join("components/foo.wasm");
+    validate_component(&component)?;
+
+    let launcher = fs::read_to_string(install_root.join("bin/foo"))?;
+    assert!(launcher.contains(&component.display().to_string()));
+    assert!(launcher.contains("CARGO_COMPONENT_RUNTIME"));
+
+    project
+        .cargo_component(&args)
+        .assert()
+        .stderr(contains("command `foo` is already installed"))
+        .failure();
+
+    project
+        .cargo_component(&format!("{args} --force --name bar"))
+        .assert()
+        .stderr(contains("Installed command `bar`"))
+        .success();
+
+    validate_component(&install_root.join("components/bar.wasm"))?;
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn it_quotes_the_component_path_in_the_launcher() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    // Characters the shell would otherwise expand or treat as quotes
+    let install_root = project.root().join("in$HOME`id`'s\"root");
+    project
+        .cargo_component(&format!(
+            "install --path {path} --root {root}",
+            path = project.root().display(),
+            root = install_root.display()
+        ))
+        .assert()
+        .stderr(contains("Installed command `foo`"))
+        .success();
+
+    let component = install_root.join("components/foo.wasm");
+    std::process::Command::new(install_root.join("bin/foo"))
+        .arg("--flag")
+        .env("CARGO_COMPONENT_RUNTIME", "echo")
+        .assert()
+        .stdout(diff(format!("{path} --flag\n", path = component.display())))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_components_that_are_not_commands() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_component(&config, "test:bar", "1.0.0", "(component)", true).await?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project
+        .cargo_component(&format!(
+            "install test:bar --root {root}",
+            root = root.join("install").display()
+        ))
+        .assert()
+        .stderr(
+            contains("package `test:bar` v1.0.0 cannot be installed")
+                .and(contains("is not a command component")),
+        )
+        .failure();
+
+    assert!(!root.join("install/bin/bar").exists());
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use crate::support::*;
+use anyhow::Result;
 use assert_cmd::prelude::*;
 use predicates::str::contains;
+use std::fs;
 
 mod support;
 
@@ -33,6 +35,31 @@ fn help() {
             .success();
     }
 
+    for arg in ["help key list", "key list -h", "key list --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains("Lists the signing keys in the local keyring"))
+            .success();
+    }
+
+    for arg in ["help key import", "key import -h", "key import --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Imports a signing key for a registry from a file into the local keyring",
+            ))
+            .success();
+    }
+
+    for arg in ["help key export", "key export -h", "key export --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Exports the signing key or public key for a registry from the local keyring",
+            ))
+            .success();
+    }
+
     for arg in ["help key delete", "key delete -h", "key delete --help"] {
         cargo_component(arg)
             .assert()
@@ -46,3 +73,84 @@ fn help() {
 // NOTE: properly testing these commands requires access to the system keyring,
 // and that may show a modal dialog that interferes with the test.
 // Therefore, these commands are not fully tested here.
+
+#[test]
+fn it_rejects_importing_an_invalid_key() -> Result<()> {
+    let root = create_root()?;
+    let path = root.join("key.txt");
+    fs::write(&path, "not-a-key")?;
+
+    cargo_component(&format!(
+        "key import https://example.com {path}",
+        path = path.display()
+    ))
+    .assert()
+    .stderr(contains("signing key is not in the correct format"))
+    .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_imports_a_key_into_a_keystore() -> Result<()> {
+    let root = create_root()?;
+    let key = root.join("key.txt");
+    let keystore = root.join("keys.json");
+    fs::write(&key, test_signing_key())?;
+
+    cargo_component(&format!(
+        "key import --keystore {keystore} https://example.com {key}",
+        keystore = keystore.display(),
+        key = key.display()
+    ))
+    .env("SIGNING_KEYSTORE_PASSPHRASE", "correct horse")
+    .assert()
+    .stderr(contains("Imported signing key `default`"))
+    .success();
+
+    // The keystore does not contain the signing key in the clear
+    let contents = fs::read_to_string(&keystore)?;
+    assert!(contents.contains("https://example.com"));
+    assert!(!contents.contains(test_signing_key()));
+
+    cargo_component(&format!(
+        "key id --keystore {keystore} https://example.com",
+        keystore = keystore.display()
+    ))
+    .env("SIGNING_KEYSTORE_PASSPHRASE", "correct horse")
+    .assert()
+    .stdout(contains("sha256:"))
+    .success();
+
+    cargo_component(&format!(
+        "key id --keystore {keystore} https://example.com",
+        keystore = keystore.display()
+    ))
+    .env("SIGNING_KEYSTORE_PASSPHRASE", "battery staple")
+    .assert()
+    .stderr(contains(
+        "the passphrase is incorrect or the keystore was modified",
+    ))
+    .failure();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&keystore)?.permissions().mode() & 0o777, 0o600);
+    }
+
+    // An entry moved to another registry fails to decrypt
+    fs::write(&keystore, contents.replace("example.com", "other.com"))?;
+    cargo_component(&format!(
+        "key id --keystore {keystore} https://other.com",
+        keystore = keystore.display()
+    ))
+    .env("SIGNING_KEYSTORE_PASSPHRASE", "correct horse")
+    .assert()
+    .stderr(contains(
+        "the passphrase is incorrect or the keystore was modified",
+    ))
+    .failure();
+
+    Ok(())
+}
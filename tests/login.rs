@@ -0,0 +1,47 @@
+use crate::support::*;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help login", "login -h", "login --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Store an authentication token for a registry in the local keyring",
+            ))
+            .success();
+    }
+
+    for arg in ["help logout", "logout -h", "logout --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Remove the authentication token for a registry from the local keyring",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_requires_a_registry_url() {
+    cargo_component("login")
+        .assert()
+        .stderr(contains(
+            "the following required arguments were not provided",
+        ))
+        .failure();
+}
+
+#[test]
+fn it_rejects_warg_registries() {
+    cargo_component("login --token-stdin https://registry.example.com")
+        .write_stdin("secret")
+        .assert()
+        .stderr(contains(
+            "registry `https://registry.example.com` does not support authentication tokens",
+        ))
+        .failure();
+}
@@ -2,6 +2,8 @@ use crate::support::*;
 use anyhow::Result;
 use assert_cmd::prelude::*;
 use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+use toml_edit::value;
 
 mod support;
 
@@ -91,3 +93,60 @@ edition = "2021"
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_prints_the_component_graph() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.2.3",
+        r#"package foo:bar@1.2.3
+interface types {
+    type greeting = string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["target"]["dependencies"]["foo:bar"] =
+            value("1.2.3");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package my:component
+
+interface api {
+    use foo:bar/types@1.2.3.{greeting}
+    greet: func() -> greeting
+}
+
+world example {
+    export api
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("metadata")
+        .assert()
+        .stdout(
+            contains(r#""version":1"#)
+                .and(contains(r#""name":"component","version":"0.1.0""#))
+                .and(contains(r#""world":"my:component/example""#))
+                .and(contains(r#""exports":["my:component/api"]"#))
+                .and(contains(r#""dependency":"foo:bar","digest":"sha256:"#))
+                .and(contains(r#""source":"registry","version":"1.2.3""#))
+                .and(contains("bindings/component")),
+        )
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help migrate", "migrate -h", "migrate --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Migrate a `wit-bindgen` project to a `cargo-component` project",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_migrates_a_wit_bindgen_project() -> Result<()> {
+    let root = create_root()?;
+    let project = ProjectBuilder::new(root.join("component"))
+        .file(
+            "Cargo.toml",
+            r#"[package]
+name = "component"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+"#,
+        )?
+        .file(
+            "wit/world.wit",
+            "package my:component
+
+world example {
+    export hello-world: func() -> string
+}
+",
+        )?
+        .file(
+            "src/lib.rs",
+            r#"wit_bindgen::generate!({
+    // The world of the component
+    world: "example",
+    exports: {
+        world: Component,
+    },
+    macro_export,
+});
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        "Hello, World!".to_string()
+    }
+}
+"#,
+        )?
+        .build();
+
+    project
+        .cargo_component("migrate")
+        .assert()
+        .stderr(
+            contains("option `macro_export` of `wit_bindgen::generate!` is not supported")
+                .and(contains("Updated source file"))
+                .and(contains(
+                    "Migrated package `component` to `cargo-component`",
+                )),
+        )
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(manifest.contains(r#"package = "my:component""#));
+    assert!(manifest.contains(r#"world = "example""#));
+    assert!(manifest.contains("cargo-component-bindings"));
+
+    let source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    assert!(source.contains("cargo_component_bindings::generate!({\n    implementor: Component,"));
+    assert!(source.contains("use bindings::*;"));
+    assert!(!source.contains("wit_bindgen"));
+
+    project
+        .cargo_component("migrate")
+        .assert()
+        .stderr(contains("already has component metadata"))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("component"))?;
+
+    Ok(())
+}
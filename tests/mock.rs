@@ -0,0 +1,90 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help mock", "mock -h", "mock --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Generate a mock component for the imported interfaces of a component's target world",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_generates_a_mock_for_imported_interfaces() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:component
+
+interface greeter {
+    greet: func(name: string) -> string
+}
+
+/// An example world for the component to target.
+world example {
+    import greeter
+    export hello-world: func() -> string
+}
+",
+    )?;
+
+    project
+        .cargo_component("mock --interface component:component/unknown")
+        .assert()
+        .stderr(contains(
+            "the target world does not import interface `component:component/unknown`",
+        ))
+        .failure();
+
+    project
+        .cargo_component("mock")
+        .assert()
+        .stderr(
+            contains("Created mock component `component-mock`")
+                .and(contains("Stubbed interface `component:component/greeter`"))
+                .and(contains("Added dev-dependency `component:component-mock`")),
+        )
+        .success();
+
+    let mock = project.root().join("mocks/component-mock");
+    let wit = fs::read_to_string(mock.join("wit/world.wit"))?;
+    assert!(wit.contains("export component:component/greeter"));
+
+    let source = fs::read_to_string(mock.join("src/lib.rs"))?;
+    assert!(source.contains("fn greet("));
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(manifest.contains(r#""component:component-mock" = { path = "mocks/component-mock" }"#));
+
+    project
+        .cargo_component("mock")
+        .assert()
+        .stderr(contains(
+            "already has a dev-dependency named `component:component-mock`",
+        ))
+        .failure();
+
+    project
+        .cargo_component("build --manifest-path mocks/component-mock/Cargo.toml")
+        .assert()
+        .stderr(contains("Finished"))
+        .success();
+
+    validate_component(&mock.join("target/wasm32-wasi/debug/component_mock.wasm"))?;
+
+    Ok(())
+}
@@ -181,3 +181,108 @@ async fn it_errors_if_target_does_not_exist() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_targets_a_well_known_world() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "wasi:http",
+        "0.2.0",
+        r#"package wasi:http@0.2.0
+interface types {
+    record request {
+        path: string,
+    }
+    record response {
+        status: u16,
+    }
+}
+interface incoming-handler {
+    use types.{request, response}
+    handle: func(request: request) -> response
+}
+world proxy {
+    export incoming-handler
+}"#,
+        true,
+    )
+    .await?;
+
+    // The `--target` option implies `--reactor`
+    cargo_component("new --target wasi:http/proxy component")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains(
+            "Generated source file `src/lib.rs` for target `wasi:http` v0.2.0",
+        ))
+        .success();
+
+    let project = ProjectBuilder::new(root.join("component")).build();
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#"target = "wasi:http/proxy@0.2.0""#).eval(&manifest));
+    assert!(contains(r#"crate-type = ["cdylib"]"#).eval(&manifest));
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_a_target_for_command_components() -> Result<()> {
+    cargo_component("new --command --target wasi:http/proxy component")
+        .assert()
+        .stderr(contains("cannot be used with"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_applies_a_template() -> Result<()> {
+    let root = create_root()?;
+    let template = root.join("template");
+    fs::create_dir_all(template.join("wit"))?;
+    fs::write(
+        template.join("wit/world.wit"),
+        "package {{namespace}}:{{name}}\n\nworld example {\n    export greet: func() -> string\n}\n",
+    )?;
+    fs::write(
+        template.join("{{name}}.md"),
+        "# {{ name }}\n\nTargets `{{target}}`; unknown variables like ${{ secrets.TOKEN }} are kept.\n",
+    )?;
+
+    cargo_component("new --reactor --namespace my-org foo --template template")
+        .current_dir(&root)
+        .assert()
+        .stderr(contains("Applied template `template` (2 files)"))
+        .success();
+
+    let proj_dir = root.join("foo");
+    let world = fs::read_to_string(proj_dir.join("wit/world.wit"))?;
+    assert!(world.contains("package my-org:foo"));
+    assert!(world.contains("export greet: func() -> string"));
+
+    let readme = fs::read_to_string(proj_dir.join("foo.md"))?;
+    assert!(readme.contains("# foo"));
+    assert!(readme.contains("Targets `my-org:foo/example`"));
+    assert!(readme.contains("${{ secrets.TOKEN }}"));
+
+    // Generated files that are not in the template are kept
+    assert!(proj_dir.join("src/lib.rs").is_file());
+
+    Ok(())
+}
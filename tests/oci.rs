@@ -0,0 +1,198 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component::signature;
+use predicates::{prelude::*, str::contains};
+use std::{fs, path::Path};
+use toml_edit::{value, InlineTable};
+
+mod support;
+
+fn write_warg_config(root: &Path) -> Result<()> {
+    warg_client::Config {
+        default_url: None,
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+    }
+    .write_to_file(&root.join("warg-config.json"))
+}
+
+/// Encodes a component that differs for each index but has the same type.
+fn component(index: usize) -> Result<Vec<u8>> {
+    Ok(wat::parse_str(format!(
+        "(component {modules})",
+        modules = "(core module)".repeat(index)
+    ))?)
+}
+
+#[test]
+fn it_publishes_to_an_oci_registry_with_basic_auth() -> Result<()> {
+    let root = create_root()?;
+    let registry = spawn_oci_registry(OciAuth::Basic, 100)?;
+    write_warg_config(&root)?;
+
+    let project = Project::with_root(&root, "foo", "--namespace test")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["version"] = value("0.1.0+build.1");
+        doc["package"]["metadata"]["component"]["registries"]["oci"] =
+            value(format!("{url}/prefix", url = registry.url()));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("publish --registry oci")
+        .assert()
+        .stderr(contains("OCI registry requires authentication"))
+        .failure();
+
+    project
+        .cargo_component("publish --registry oci")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .assert()
+        .stderr(contains("Published package `test:foo` v0.1.0+build.1"))
+        .success();
+
+    // Build metadata is stored in the tag with `_` in place of `+`
+    assert_eq!(registry.tags("prefix/test/foo"), ["0.1.0_build.1"]);
+    let built = fs::read(project.release_wasm("foo"))?;
+    assert_eq!(
+        registry.content("prefix/test/foo", "0.1.0_build.1"),
+        Some(built.clone())
+    );
+
+    // Publishing with an embedded signature pushes the signed component
+    project
+        .cargo_component("publish --registry oci --embed-signature")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `test:foo` v0.1.0+build.1"))
+        .success();
+
+    let signed = registry
+        .content("prefix/test/foo", "0.1.0_build.1")
+        .expect("published content");
+    assert_ne!(signed, built);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn it_embeds_a_signature_from_a_signing_command() -> Result<()> {
+    let root = create_root()?;
+    let registry = spawn_oci_registry(OciAuth::None, 100)?;
+    write_warg_config(&root)?;
+
+    // The signing command signs digests with a key that stays in its own file
+    let status = std::process::Command::new("openssl")
+        .args(["ecparam", "-name", "prime256v1", "-genkey", "-noout"])
+        .arg("-out")
+        .arg(root.join("key.pem"))
+        .status()?;
+    assert!(status.success());
+    fs::write(
+        root.join("signer.sh"),
+        format!(
+            "#!/bin/sh\nset -e\ncd {root}\ncase \"$SIGNING_OPERATION\" in\n  public-key) openssl ec -in key.pem -pubout 2>/dev/null ;;\n  sign) openssl pkeyutl -sign -inkey key.pem | base64 ;;\n  *) exit 1 ;;\nesac\n",
+            root = root.display()
+        ),
+    )?;
+
+    let project = Project::with_root(&root, "foo", "--namespace test")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["registries"]["oci"] = value(registry.url());
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("publish --registry oci --embed-signature --signing-command false")
+        .assert()
+        .stderr(contains("signing command `false` failed"))
+        .failure();
+
+    let command = format!("sh {path}", path = root.join("signer.sh").display());
+    project
+        .cargo_component("publish --registry oci --embed-signature")
+        .env("CARGO_COMPONENT_SIGNING_COMMAND", &command)
+        .assert()
+        .stderr(contains("Signing component").and(contains("Published package `test:foo` v0.1.0")))
+        .success();
+
+    let signed = registry
+        .content("test/foo", "0.1.0")
+        .expect("published content");
+    signature::verify(&signed, &[])?;
+
+    Ok(())
+}
+
+#[test]
+fn it_resolves_from_an_oci_registry_with_bearer_auth() -> Result<()> {
+    let root = create_root()?;
+    let registry = spawn_oci_registry(OciAuth::Bearer, 2)?;
+    for (index, tag) in ["1.0.0", "1.1.0", "1.1.1_build.5", "latest"]
+        .into_iter()
+        .enumerate()
+    {
+        registry.push("foo/bar", tag, &component(index)?);
+    }
+    write_warg_config(&root)?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["registries"]["oci"] = value(registry.url());
+        let mut dependency = InlineTable::new();
+        dependency.insert("version", "1.1".into());
+        dependency.insert("registry", "oci".into());
+        doc["package"]["metadata"]["component"]["dependencies"]["foo:bar"] = value(dependency);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    // The latest matching version is on the second page of tags
+    assert!(registry.tag_requests() >= 2);
+    let lock_path = project.root().join("Cargo-component.lock");
+    let lock_file = fs::read_to_string(&lock_path)?;
+    assert!(contains(r#"registry = "oci""#).eval(&lock_file));
+    assert!(contains(r#"version = "1.1.1+build.5""#).eval(&lock_file));
+
+    // A locked version that no longer satisfies the requirement is not used,
+    // even though its content is cached
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["dependencies"]["foo:bar"]["version"] =
+            value("~1.0");
+        Ok(doc)
+    })?;
+    assert!(lock_file.contains(r#"requirement = "^1.1""#));
+    fs::write(
+        &lock_path,
+        lock_file.replace(r#"requirement = "^1.1""#, r#"requirement = "~1.0""#),
+    )?;
+
+    project
+        .cargo_component("build")
+        .env("OCI_USERNAME", OCI_USERNAME)
+        .env("OCI_PASSWORD", OCI_PASSWORD)
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(&lock_path)?;
+    assert!(contains(r#"version = "1.0.0""#).eval(&lock_file));
+    assert!(!lock_file.contains("1.1.1+build.5"));
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use crate::support::*;
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
-use predicates::str::contains;
+use predicates::{
+    prelude::PredicateBooleanExt,
+    str::{contains, is_match},
+};
 use semver::Version;
 use std::fs;
 use toml_edit::{value, Array};
@@ -68,6 +71,31 @@ world foo {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_verifies_a_reproducible_build_before_publishing() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "--namespace test")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("publish --init --verify-reproducible")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Verifying that package `foo` builds reproducibly"))
+        .stderr(contains("Published package `test:foo` v0.1.0"))
+        .success();
+
+    validate_component(&project.release_wasm("foo"))?;
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_fails_if_package_does_not_exist() -> Result<()> {
     let root = create_root()?;
@@ -278,3 +306,233 @@ async fn it_publishes_with_registry_metadata() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_a_workspace_in_dependency_order() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "my:world",
+        "1.0.0",
+        r#"package my:%world@1.0.0
+world foo {
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = ProjectBuilder::new(root.clone())
+        .file(
+            "Cargo.toml",
+            r#"[workspace]
+members = ["bar", "foo"]
+"#,
+        )?
+        .build();
+
+    project
+        .cargo_component("new --reactor --namespace test --target my:world/foo foo")
+        .assert()
+        .success();
+
+    let foo = ProjectBuilder::new(root.join("foo")).build();
+    foo.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("new --reactor --namespace test --target my:world bar")
+        .assert()
+        .success();
+
+    let bar = ProjectBuilder::new(root.join("bar")).build();
+    bar.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        // Depend on an older version of `test:foo` that will be rewritten on publish
+        doc["package"]["metadata"]["component"]["dependencies"]["test:foo"] = value("0.0.1");
+        Ok(doc)
+    })?;
+
+    let source = r#"cargo_component_bindings::generate!();
+use bindings::Guest;
+struct Component;
+impl Guest for Component {
+    fn bar() -> String {
+        bindings::foo::bar()
+    }
+}
+"#;
+
+    fs::write(bar.root().join("src/lib.rs"), source)?;
+
+    project
+        .cargo_component("publish --workspace --init")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(
+            is_match(
+                "(?s)Published package `test:foo` v0.1.0.*\
+                 Updating dependency `test:foo` of package `bar` to v0.1.0.*\
+                 Published package `test:bar` v0.1.0",
+            )
+            .unwrap(),
+        )
+        .success();
+
+    let manifest = fs::read_to_string(bar.root().join("Cargo.toml"))?;
+    assert!(manifest.contains("\"test:foo\" = \"0.1.0\""));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_with_a_bill_of_materials() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "my:world",
+        "1.0.0",
+        r#"package my:%world@1.0.0
+world foo {
+    import foo: func() -> string
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "foo", "--namespace test --target my:world")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("publish --init --sbom sbom.json")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(
+            contains("Created bill of materials `sbom.json`")
+                .and(contains("Published package `test:foo` v0.1.0")),
+        )
+        .success();
+
+    let sbom = fs::read(project.root().join("sbom.json"))?;
+    let json: serde_json::Value = serde_json::from_slice(&sbom)?;
+    assert_eq!(json["bomFormat"], "CycloneDX");
+    assert_eq!(json["metadata"]["component"]["name"], "test:foo");
+    assert_eq!(json["metadata"]["component"]["version"], "0.1.0");
+
+    let components = json["components"]
+        .as_array()
+        .context("missing components")?;
+    let world = components
+        .iter()
+        .find(|c| c["name"] == "my:world")
+        .context("missing target world component")?;
+    assert_eq!(world["version"], "1.0.0");
+    assert_eq!(world["hashes"][0]["alg"], "SHA-256");
+    assert!(components.iter().any(|c| c["purl"]
+        .as_str()
+        .unwrap_or_default()
+        .starts_with("pkg:cargo/")));
+
+    let client = Client::new_with_config(None, &config)?;
+    let download = client
+        .download_exact(&PackageId::new("test:foo")?, &Version::parse("0.1.0")?)
+        .await?;
+
+    let bytes = fs::read(&download.path)?;
+    let embedded = wasmparser::Parser::new(0)
+        .parse_all(&bytes)
+        .find_map(|payload| match payload {
+            Ok(wasmparser::Payload::CustomSection(reader)) if reader.name() == "sbom" => {
+                Some(reader.data().to_vec())
+            }
+            _ => None,
+        })
+        .context("missing bill of materials section")?;
+    assert_eq!(embedded, sbom);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_with_git_provenance() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    git_commit(project.root(), &[(".gitignore", "/target\n")])?;
+    git(
+        project.root(),
+        "remote add origin https://example.com/repo.git",
+    )?;
+
+    let lib = project.root().join("src/lib.rs");
+    let source = fs::read_to_string(&lib)?;
+    fs::write(&lib, format!("{source}\n// uncommitted\n"))?;
+
+    project
+        .cargo_component("publish --init --provenance-git")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(
+            contains("1 files in the working directory of package `foo` contain changes that were not yet committed into git")
+                .and(contains("src/lib.rs"))
+                .and(contains("pass the `--allow-dirty` flag")),
+        )
+        .failure();
+
+    let commit = git_commit(project.root(), &[])?;
+
+    project
+        .cargo_component("publish --init --provenance-git")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `component:foo` v0.1.0"))
+        .success();
+
+    let client = Client::new_with_config(None, &config)?;
+    let download = client
+        .download_exact(&PackageId::new("component:foo")?, &Version::parse("0.1.0")?)
+        .await?;
+
+    let bytes = fs::read(&download.path)?;
+    let metadata =
+        wasm_metadata::RegistryMetadata::from_wasm(&bytes)?.expect("missing registry metadata");
+    let links = metadata.get_links().expect("missing links");
+
+    assert_eq!(
+        links
+            .iter()
+            .find(|link| link.ty == LinkType::Repository)
+            .expect("missing repository")
+            .value,
+        "https://example.com/repo.git"
+    );
+    assert_eq!(
+        links
+            .iter()
+            .find(|link| link.ty == LinkType::Custom("vcs".to_string()))
+            .expect("missing vcs link")
+            .value,
+        format!("git+https://example.com/repo.git#{commit}")
+    );
+
+    Ok(())
+}
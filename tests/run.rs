@@ -0,0 +1,188 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::fs;
+use toml_edit::{value, Item, Table};
+
+mod support;
+
+#[test]
+fn it_runs_a_command_component() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("run")
+        .assert()
+        .stderr(contains("Creating component"))
+        .stdout(contains("Hello, world!"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_runs_with_capabilities() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("data"))?;
+    fs::write(project.root().join("data/message.txt"), "from a file")?;
+    fs::write(
+        project.root().join("src/main.rs"),
+        r#"cargo_component_bindings::generate!();
+
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    println!("args: {args:?}");
+    println!("env: {}", std::env::var("MESSAGE").unwrap());
+    println!("file: {}", std::fs::read_to_string("/data/message.txt").unwrap());
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("run --env MESSAGE=hello --dir data::/data -- --dir foo")
+        .assert()
+        .stdout(contains("args: [\"--dir\", \"foo\"]"))
+        .stdout(contains("env: hello"))
+        .stdout(contains("file: from a file"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_passes_configuration_to_the_runtime() -> Result<()> {
+    let project = Project::new_bin("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        let mut config = Table::new();
+        config["greeting"] = value("hello");
+        config["retries"] = value(3);
+        doc["package"]["metadata"]["component"]["config"] = Item::Table(config);
+        Ok(doc)
+    })?;
+
+    // Print the runtime arguments instead of running the component
+    project
+        .cargo_component("run --config-var greeting=hi")
+        .env("CARGO_COMPONENT_RUNTIME", "echo")
+        .assert()
+        .stderr(contains(
+            "package `foo` declares configuration values, but component",
+        ))
+        .stdout(contains(
+            "-S config -S config-var=greeting=hi -S config-var=retries=3",
+        ))
+        .success();
+
+    project
+        .cargo_component("run --config-var greeting")
+        .env("CARGO_COMPONENT_RUNTIME", "echo")
+        .assert()
+        .stderr(contains(
+            "argument `--config-var` expects a value of the form `KEY=VALUE`, but found `greeting`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_satisfies_imports_with_dev_dependencies() -> Result<()> {
+    let root = create_root()?;
+
+    let mock = Project::with_root(&root, "mock", "")?;
+    mock.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        mock.root().join("wit/world.wit"),
+        "
+package my:mock
+
+interface greeter {
+    greet: func(name: string) -> string
+}
+
+world mock {
+    export greeter
+}
+",
+    )?;
+
+    fs::write(
+        mock.root().join("src/lib.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::exports::my::mock::greeter::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn greet(name: String) -> String {
+        format!("Hello from the mock, {name}!")
+    }
+}
+"#,
+    )?;
+
+    mock.cargo_component("build").assert().success();
+    let mock_wasm = mock.debug_wasm("mock");
+    validate_component(&mock_wasm)?;
+
+    let app = Project::new_bin_with_root(&root, "app")?;
+    let mock_wit = mock.root().join("wit");
+    app.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["path"] = value("wit");
+        doc["package"]["metadata"]["component"]["target"]["dependencies"]["my:mock"]["path"] =
+            value(mock_wit.display().to_string());
+        doc["package"]["metadata"]["component"]["dev-dependencies"]["my:mock"]["path"] =
+            value(mock_wasm.display().to_string());
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(app.root().join("wit"))?;
+    fs::write(
+        app.root().join("wit/world.wit"),
+        "
+package my:app
+
+world app {
+    import my:mock/greeter
+}
+",
+    )?;
+
+    fs::write(
+        app.root().join("src/main.rs"),
+        r#"
+cargo_component_bindings::generate!();
+
+use bindings::my::mock::greeter;
+
+fn main() {
+    println!("{}", greeter::greet("World"));
+}
+"#,
+    )?;
+
+    app.cargo_component("run")
+        .assert()
+        .stdout(contains("Hello from the mock, World!"))
+        .success();
+
+    Ok(())
+}
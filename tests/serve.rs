@@ -0,0 +1,38 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help serve", "serve -h", "serve --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Build a `wasi:http/proxy` component and serve it over HTTP",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_rejects_components_that_are_not_proxies() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("serve")
+        .env("CARGO_COMPONENT_SERVE_RUNTIME", "false")
+        .assert()
+        .stderr(contains(
+            "does not export `wasi:http/incoming-handler`; only components targeting the `wasi:http/proxy` world can be served",
+        ))
+        .failure();
+
+    Ok(())
+}
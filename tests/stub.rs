@@ -0,0 +1,102 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help stub", "stub -h", "stub --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Generate stub implementations for the unimplemented exports of a component's target world",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_stubs_unimplemented_exports() -> Result<()> {
+    let project = Project::new("component")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("stub")
+        .assert()
+        .stderr(contains(
+            "every export of the target world is already implemented",
+        ))
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:component
+
+interface counters {
+    resource counter {
+        constructor(start: u32)
+        increment: func()
+        get: func() -> u32
+    }
+
+    total: func() -> u64
+}
+
+/// An example world for the component to target.
+world example {
+    export hello-world: func() -> string
+    export goodbye-world: func(name: string) -> string
+    export counters
+}
+",
+    )?;
+
+    project
+        .cargo_component("stub --dry-run")
+        .assert()
+        .stdout(contains("fn goodbye_world(name: String) -> String"))
+        .success();
+
+    project
+        .cargo_component("stub")
+        .assert()
+        .stderr(
+            contains("Stubbed function `goodbye_world` of `bindings::Guest`")
+                .and(contains("Stubbed type `Counter`"))
+                .and(contains(
+                    "Stubbed implementation of `bindings::exports::component::component::counters::Guest` for `Component`",
+                ))
+                .and(contains(
+                    "Stubbed implementation of `bindings::exports::component::component::counters::GuestCounter` for `Counter`",
+                )),
+        )
+        .success();
+
+    let source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    assert!(source.contains("fn new(start: u32) -> Self {\n        todo!()\n    }"));
+    assert!(source.contains("fn get(&self) -> u32 {\n        todo!()\n    }"));
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    validate_component(&project.debug_wasm("component"))?;
+
+    project
+        .cargo_component("stub")
+        .assert()
+        .stderr(contains(
+            "every export of the target world is already implemented",
+        ))
+        .success();
+
+    Ok(())
+}
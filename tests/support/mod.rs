@@ -2,8 +2,12 @@
 
 use anyhow::{bail, Context, Result};
 use assert_cmd::prelude::OutputAssertExt;
+use cargo_component_core::static_registry::{IndexEntry, PackageIndex};
+use sha2::{Digest, Sha256};
 use std::{
     env, fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
     path::{Path, PathBuf},
     process::Command,
     sync::atomic::{AtomicUsize, Ordering::SeqCst},
@@ -22,6 +26,10 @@ use warg_server::{policy::content::WasmContentPolicy, Config, Server};
 use wasmparser::{Chunk, Encoding, Parser, Payload, Validator, WasmFeatures};
 use wit_parser::{Resolve, UnresolvedPackage};
 
+mod oci;
+
+pub use oci::*;
+
 pub fn test_operator_key() -> &'static str {
     "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk="
 }
@@ -179,6 +187,10 @@ pub async fn publish_wit(
     wit: &str,
     init: bool,
 ) -> Result<()> {
+    publish(config, &id.parse()?, version, encode_wit(wit)?, init).await
+}
+
+fn encode_wit(wit: &str) -> Result<Vec<u8>> {
     let mut resolve = Resolve::new();
     let pkg = resolve
         .push(
@@ -187,10 +199,90 @@ pub async fn publish_wit(
         )
         .context("failed to resolve wit for publishing")?;
 
-    let bytes =
-        wit_component::encode(&resolve, pkg).context("failed to encode wit for publishing")?;
+    wit_component::encode(&resolve, pkg).context("failed to encode wit for publishing")
+}
 
-    publish(config, &id.parse()?, version, bytes, init).await
+/// Publishes content to the static registry in the given directory.
+///
+/// The version is added to the index of the package.
+pub fn publish_static(
+    dir: &Path,
+    id: &str,
+    version: &str,
+    content: &[u8],
+    yanked: bool,
+) -> Result<()> {
+    let id: PackageId = id.parse()?;
+    let package_dir = dir.join("pkgs").join(id.namespace()).join(id.name());
+    fs::create_dir_all(&package_dir)?;
+    fs::write(package_dir.join(format!("{version}.wasm")), content)?;
+
+    let index_path = package_dir.join("index.json");
+    let mut index: PackageIndex = match fs::read(&index_path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)?,
+        Err(_) => PackageIndex::default(),
+    };
+
+    index.versions.push(IndexEntry {
+        version: version.parse()?,
+        digest: format!("sha256:{hash}", hash = hex::encode(Sha256::digest(content))).parse()?,
+        yanked,
+    });
+
+    fs::write(index_path, serde_json::to_vec_pretty(&index)?)?;
+    Ok(())
+}
+
+pub fn publish_static_wit(dir: &Path, id: &str, version: &str, wit: &str) -> Result<()> {
+    publish_static(dir, id, version, &encode_wit(wit)?, false)
+}
+
+/// Spawns a static registry serving the files of the given directory.
+///
+/// The server runs on a background thread for the remainder of the test.
+///
+/// Returns the URL of the registry.
+pub fn spawn_static_registry(dir: &Path) -> Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let dir = dir.to_path_buf();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            if reader.read_line(&mut request).is_err() {
+                continue;
+            }
+
+            // Skip the headers of the request
+            let mut line = String::new();
+            while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+                line.clear();
+            }
+
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            let response = match fs::read(dir.join(path.trim_start_matches('/'))) {
+                Ok(body) => {
+                    let mut response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {len}\r\nConnection: close\r\n\r\n",
+                        len = body.len()
+                    )
+                    .into_bytes();
+                    response.extend(body);
+                    response
+                }
+                Err(_) => {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_vec()
+                }
+            };
+
+            stream.write_all(&response).ok();
+        }
+    });
+
+    Ok(format!("static+http://{addr}"))
 }
 
 pub struct ServerInstance {
@@ -300,6 +392,17 @@ impl Project {
         })
     }
 
+    pub fn new_bin_with_root(root: &Path, name: &str) -> Result<Self> {
+        cargo_component(&format!("new {name}"))
+            .current_dir(root)
+            .assert()
+            .try_success()?;
+
+        Ok(Self {
+            root: root.join(name),
+        })
+    }
+
     pub fn with_root(root: &Path, name: &str, args: &str) -> Result<Self> {
         cargo_component(&format!("new --reactor {name} {args}"))
             .current_dir(root)
@@ -354,6 +457,48 @@ impl Project {
     }
 }
 
+/// Runs a git command in a directory, returning its trimmed output.
+pub fn git(dir: &Path, args: &str) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args.split_whitespace())
+        .env("GIT_AUTHOR_NAME", "test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .output()
+        .context("failed to run `git`")?;
+
+    if !output.status.success() {
+        bail!(
+            "`git {args}` failed: {stderr}",
+            stderr = String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+/// Commits the given files to a git repository, creating it if needed.
+///
+/// Returns the hash of the new commit.
+pub fn git_commit(repo: &Path, files: &[(&str, &str)]) -> Result<String> {
+    if !repo.join(".git").is_dir() {
+        fs::create_dir_all(repo)?;
+        git(repo, "init --quiet")?;
+    }
+
+    for (path, contents) in files {
+        let path = repo.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+    }
+
+    git(repo, "add --all")?;
+    git(repo, "commit --quiet --message commit")?;
+    git(repo, "rev-parse HEAD")
+}
+
 pub fn validate_component(path: &Path) -> Result<()> {
     let bytes = fs::read(path)
         .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
@@ -0,0 +1,325 @@
+//! A mock OCI registry for tests.
+//!
+//! This module is shared with the tests of the `wit` crate.
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+/// The user name accepted by a mock OCI registry.
+pub const OCI_USERNAME: &str = "user";
+
+/// The password accepted by a mock OCI registry.
+pub const OCI_PASSWORD: &str = "secret";
+
+/// The base64 encoding of `user:secret` sent for basic authentication.
+const OCI_BASIC_CREDENTIALS: &str = "dXNlcjpzZWNyZXQ=";
+
+/// The token issued by the token endpoint of a mock OCI registry.
+const OCI_TOKEN: &str = "mock-token";
+
+/// The authentication required by a mock OCI registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OciAuth {
+    /// Requests are not authenticated.
+    None,
+    /// Requests must use basic authentication.
+    Basic,
+    /// Requests must use a bearer token issued by the registry's token endpoint.
+    Bearer,
+}
+
+#[derive(Default)]
+struct OciState {
+    /// The manifests of each repository by tag.
+    manifests: HashMap<String, BTreeMap<String, Vec<u8>>>,
+    blobs: HashMap<String, Vec<u8>>,
+    /// The number of tag list requests served.
+    tag_requests: usize,
+}
+
+/// A mock OCI registry serving repositories from memory.
+#[derive(Clone)]
+pub struct OciRegistry {
+    url: String,
+    state: Arc<Mutex<OciState>>,
+}
+
+impl OciRegistry {
+    /// Gets the `oci+http` URL of the registry.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Pushes content to a repository as an artifact with the given tag.
+    pub fn push(&self, repository: &str, tag: &str, content: &[u8]) {
+        let config = b"{}".to_vec();
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "config": {
+                "mediaType": "application/vnd.wasm.config.v0+json",
+                "digest": sha256_digest(&config),
+                "size": config.len(),
+            },
+            "layers": [{
+                "mediaType": "application/wasm",
+                "digest": sha256_digest(content),
+                "size": content.len(),
+            }],
+        });
+
+        let mut state = self.state.lock().unwrap();
+        state.blobs.insert(sha256_digest(&config), config);
+        state.blobs.insert(sha256_digest(content), content.to_vec());
+        state
+            .manifests
+            .entry(repository.to_string())
+            .or_default()
+            .insert(tag.to_string(), serde_json::to_vec(&manifest).unwrap());
+    }
+
+    /// Gets the tags of a repository.
+    pub fn tags(&self, repository: &str) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .manifests
+            .get(repository)
+            .map(|m| m.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Gets the content of the Wasm layer of a tagged artifact.
+    pub fn content(&self, repository: &str, tag: &str) -> Option<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        let manifest: serde_json::Value =
+            serde_json::from_slice(state.manifests.get(repository)?.get(tag)?).ok()?;
+        let digest = manifest["layers"][0]["digest"].as_str()?;
+        state.blobs.get(digest).cloned()
+    }
+
+    /// Gets the number of tag list requests served.
+    pub fn tag_requests(&self) -> usize {
+        self.state.lock().unwrap().tag_requests
+    }
+
+    fn handle(
+        &self,
+        auth: OciAuth,
+        page_size: usize,
+        method: &str,
+        target: &str,
+        headers: &HashMap<String, String>,
+        body: Vec<u8>,
+    ) -> (u16, Vec<(String, String)>, Vec<u8>) {
+        let url = url::Url::parse(&format!("http://registry{target}")).unwrap();
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let path = url.path();
+
+        let authorization = headers.get("authorization").map(String::as_str);
+        if path == "/token" {
+            return if authorization == Some(&format!("Basic {OCI_BASIC_CREDENTIALS}")) {
+                (
+                    200,
+                    Vec::new(),
+                    serde_json::to_vec(&serde_json::json!({ "token": OCI_TOKEN })).unwrap(),
+                )
+            } else {
+                (401, Vec::new(), Vec::new())
+            };
+        }
+
+        let (expected, challenge) = match auth {
+            OciAuth::None => (None, String::new()),
+            OciAuth::Basic => (
+                Some(format!("Basic {OCI_BASIC_CREDENTIALS}")),
+                r#"Basic realm="mock""#.to_string(),
+            ),
+            OciAuth::Bearer => {
+                let host = headers.get("host").cloned().unwrap_or_default();
+                (
+                    Some(format!("Bearer {OCI_TOKEN}")),
+                    format!(r#"Bearer realm="http://{host}/token",service="mock""#),
+                )
+            }
+        };
+
+        if let Some(expected) = expected {
+            if authorization != Some(expected.as_str()) {
+                return (
+                    401,
+                    vec![("WWW-Authenticate".to_string(), challenge)],
+                    Vec::new(),
+                );
+            }
+        }
+
+        let path = match path.strip_prefix("/v2/") {
+            Some(path) => path,
+            None => return (404, Vec::new(), Vec::new()),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(repository) = path.strip_suffix("/tags/list") {
+            state.tag_requests += 1;
+            let tags = match state.manifests.get(repository) {
+                Some(manifests) => manifests,
+                None => return (404, Vec::new(), Vec::new()),
+            };
+
+            let page: Vec<&String> = tags
+                .keys()
+                .filter(|t| query.get("last").map(|l| *t > l).unwrap_or(true))
+                .take(page_size)
+                .collect();
+
+            let mut response_headers = Vec::new();
+            if let Some(last) = page.last() {
+                if tags.keys().any(|t| t > *last) {
+                    response_headers.push((
+                        "Link".to_string(),
+                        format!(
+                            r#"</v2/{repository}/tags/list?n={page_size}&last={last}>; rel="next""#
+                        ),
+                    ));
+                }
+            }
+
+            let body = serde_json::json!({ "name": repository, "tags": page });
+            return (200, response_headers, serde_json::to_vec(&body).unwrap());
+        }
+
+        if let Some((repository, tag)) = path.split_once("/manifests/") {
+            return match method {
+                "GET" => match state.manifests.get(repository).and_then(|m| m.get(tag)) {
+                    Some(manifest) => (200, Vec::new(), manifest.clone()),
+                    None => (404, Vec::new(), Vec::new()),
+                },
+                "PUT" => {
+                    state
+                        .manifests
+                        .entry(repository.to_string())
+                        .or_default()
+                        .insert(tag.to_string(), body);
+                    (201, Vec::new(), Vec::new())
+                }
+                _ => (405, Vec::new(), Vec::new()),
+            };
+        }
+
+        if let Some((repository, upload)) = path.split_once("/blobs/uploads/") {
+            return match method {
+                "POST" => (
+                    202,
+                    vec![(
+                        "Location".to_string(),
+                        format!(
+                            "/v2/{repository}/blobs/uploads/{id}",
+                            id = state.blobs.len()
+                        ),
+                    )],
+                    Vec::new(),
+                ),
+                "PUT" if !upload.is_empty() => match query.get("digest") {
+                    Some(digest) if *digest == sha256_digest(&body) => {
+                        state.blobs.insert(digest.clone(), body);
+                        (201, Vec::new(), Vec::new())
+                    }
+                    _ => (400, Vec::new(), Vec::new()),
+                },
+                _ => (405, Vec::new(), Vec::new()),
+            };
+        }
+
+        if let Some((_, digest)) = path.split_once("/blobs/") {
+            return match state.blobs.get(digest) {
+                Some(blob) if method == "GET" => (200, Vec::new(), blob.clone()),
+                Some(blob) => (
+                    200,
+                    vec![("Content-Length".to_string(), blob.len().to_string())],
+                    Vec::new(),
+                ),
+                None => (404, Vec::new(), Vec::new()),
+            };
+        }
+
+        (404, Vec::new(), Vec::new())
+    }
+}
+
+fn sha256_digest(bytes: &[u8]) -> String {
+    format!("sha256:{hash}", hash = hex::encode(Sha256::digest(bytes)))
+}
+
+/// Spawns a mock OCI registry requiring the given authentication.
+///
+/// Tag lists are returned in pages of the given size. The server runs on a
+/// background thread for the remainder of the test.
+pub fn spawn_oci_registry(auth: OciAuth, page_size: usize) -> Result<OciRegistry> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let registry = OciRegistry {
+        url: format!("oci+http://{addr}", addr = listener.local_addr()?),
+        state: Default::default(),
+    };
+
+    let server = registry.clone();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut request = String::new();
+            if reader.read_line(&mut request).is_err() {
+                continue;
+            }
+
+            let mut headers = HashMap::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+                }
+                line.clear();
+            }
+
+            let mut body = vec![
+                0;
+                headers
+                    .get("content-length")
+                    .and_then(|l| l.parse().ok())
+                    .unwrap_or(0)
+            ];
+            if reader.read_exact(&mut body).is_err() {
+                continue;
+            }
+
+            let mut parts = request.split_whitespace();
+            let method = parts.next().unwrap_or("GET").to_string();
+            let target = parts.next().unwrap_or("/").to_string();
+            let (status, response_headers, body) =
+                server.handle(auth, page_size, &method, &target, &headers, body);
+
+            let mut response = format!("HTTP/1.1 {status} Mock\r\nConnection: close\r\n");
+            if !response_headers
+                .iter()
+                .any(|(name, _)| name == "Content-Length")
+            {
+                response.push_str(&format!("Content-Length: {len}\r\n", len = body.len()));
+            }
+            for (name, value) in response_headers {
+                response.push_str(&format!("{name}: {value}\r\n"));
+            }
+            response.push_str("\r\n");
+
+            let mut response = response.into_bytes();
+            response.extend(body);
+            stream.write_all(&response).ok();
+        }
+    });
+
+    Ok(registry)
+}
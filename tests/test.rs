@@ -0,0 +1,56 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{boolean::PredicateBooleanExt, str::contains};
+use std::{fmt::Write, fs};
+
+mod support;
+
+#[test]
+fn it_runs_tests_as_a_component() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    let mut src = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    write!(
+        &mut src,
+        "\n\n#[test]\nfn it_passes() {{\n  assert_eq!(1 + 1, 2);\n}}\n"
+    )?;
+    fs::write(project.root().join("src/lib.rs"), src)?;
+
+    project
+        .cargo_component("test")
+        .assert()
+        .stderr(contains("Creating component"))
+        .stdout(contains("test it_passes ... ok").and(contains("test result: ok")))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_reports_test_failures() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    let mut src = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    write!(
+        &mut src,
+        "\n\n#[test]\nfn it_fails() {{\n  assert_eq!(1 + 1, 3);\n}}\n"
+    )?;
+    fs::write(project.root().join("src/lib.rs"), src)?;
+
+    project
+        .cargo_component("test")
+        .assert()
+        .stdout(contains("test it_fails ... FAILED").and(contains("test result: FAILED")))
+        .failure();
+
+    Ok(())
+}
@@ -320,3 +320,126 @@ async fn update_with_changed_dependencies() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn update_with_precise_version() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_component(&config, "foo:bar", "1.0.0", "(component)", true).await?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("add foo:bar")
+        .assert()
+        .stderr(contains("Added dependency `foo:bar` with version `1.0.0`"))
+        .success();
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    publish_component(&config, "foo:bar", "1.1.0", "(component)", false).await?;
+    publish_component(&config, "foo:bar", "1.2.0", "(component)", false).await?;
+
+    project
+        .cargo_component("update -p foo:bar --precise 1.1.0 --dry-run")
+        .assert()
+        .stderr(contains(
+            "Would update dependency `foo:bar` v1.0.0 -> v1.1.0",
+        ))
+        .success();
+
+    project
+        .cargo_component("update -p foo:bar --precise 1.1.0")
+        .assert()
+        .stderr(contains("Updating dependency `foo:bar` v1.0.0 -> v1.1.0"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("version = \"1.1.0\""));
+
+    project
+        .cargo_component("update -p foo:bar --precise 2.0.0")
+        .assert()
+        .stderr(contains(
+            "version 2.0.0 of package `foo:bar` does not satisfy the version requirement `^1.0.0`",
+        ))
+        .failure();
+
+    project
+        .cargo_component("update --precise 1.2.0")
+        .assert()
+        .stderr(contains("--package <PACKAGE>"))
+        .failure();
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn update_only_the_given_package() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_component(&config, "foo:bar", "1.0.0", "(component)", true).await?;
+    publish_component(&config, "foo:baz", "1.0.0", "(component)", true).await?;
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    for dep in ["foo:bar", "foo:baz"] {
+        project
+            .cargo_component(&format!("add {dep}"))
+            .assert()
+            .stderr(contains(format!(
+                "Added dependency `{dep}` with version `1.0.0`"
+            )))
+            .success();
+    }
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    publish_component(&config, "foo:bar", "1.1.0", "(component)", false).await?;
+    publish_component(&config, "foo:baz", "1.1.0", "(component)", false).await?;
+
+    project
+        .cargo_component("update -p foo:bar")
+        .assert()
+        .stderr(
+            contains("Updating dependency `foo:bar` v1.0.0 -> v1.1.0")
+                .and(contains("foo:baz").not()),
+        )
+        .success();
+
+    project
+        .cargo_component("update -p foo:qux")
+        .assert()
+        .stderr(contains(
+            "package `foo:qux` is not a dependency in the component lock file",
+        ))
+        .failure();
+
+    Ok(())
+}
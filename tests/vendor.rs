@@ -0,0 +1,116 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use std::fs;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help vendor", "vendor -h", "vendor --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Vendor all registry dependencies for offline builds",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_builds_offline_with_vendored_dependencies() -> Result<()> {
+    let root = create_root()?;
+    let (server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.0.0",
+        r#"package foo:bar@1.0.0
+world foo {
+    import foo: func() -> string
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "--target foo:bar@1.0.0")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("vendor")
+        .assert()
+        .stderr(contains("Vendoring dependency `foo:bar` v1.0.0"))
+        .stderr(contains("Vendored 1 dependencies"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("vendor = \"vendor/components\""));
+    assert_eq!(
+        fs::read_dir(project.root().join("vendor/components"))?.count(),
+        1
+    );
+
+    // Shut down the registry and remove the local content cache
+    drop(server);
+    fs::remove_dir_all(root.join("content"))?;
+
+    project
+        .cargo_component("build --offline")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_modified_vendored_dependencies() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.0.0",
+        r#"package foo:bar@1.0.0
+world foo {
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "--target foo:bar@1.0.0")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project.cargo_component("vendor").assert().success();
+
+    let vendored = fs::read_dir(project.root().join("vendor/components"))?
+        .next()
+        .expect("expected a vendored dependency")?
+        .path();
+    fs::write(&vendored, "(component)")?;
+
+    project
+        .cargo_component("build --offline")
+        .assert()
+        .stderr(contains(
+            "failed to verify the content of component registry package `foo:bar` (v`1.0.0`)",
+        ))
+        .stderr(contains("the content may have been modified or corrupted"))
+        .failure();
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use toml_edit::{value, Array};
+use warg_crypto::signing::PrivateKey;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help verify", "verify -h", "verify --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Verify the signature embedded in a component package from a registry",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_verifies_embedded_signatures() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "my:world",
+        "1.0.0",
+        r#"package my:%world@1.0.0
+world foo {
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "foo", "--namespace test --target my:world")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("publish --init --embed-signature")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Signing component").and(contains("Published package `test:foo` v0.1.0")))
+        .success();
+
+    let signing_key = PrivateKey::decode(test_signing_key().to_string())?.public_key();
+    let other_key = PrivateKey::decode(test_operator_key().to_string())?.public_key();
+
+    project
+        .cargo_component("verify test:foo")
+        .assert()
+        .stderr(contains(
+            "no trusted keys were given for package `test:foo`",
+        ))
+        .failure();
+
+    project
+        .cargo_component(&format!("verify test:foo@0.1.0 --key {signing_key}"))
+        .assert()
+        .stderr(contains("Verified package `test:foo` v0.1.0").and(contains("warning").not()))
+        .success();
+
+    project
+        .cargo_component(&format!("verify test:foo --key {other_key}"))
+        .assert()
+        .stderr(contains(format!(
+            "component was signed with key `{id}`, which is not a trusted key",
+            id = signing_key.fingerprint()
+        )))
+        .failure();
+
+    project
+        .cargo_component(&format!("verify my:world --key {signing_key}"))
+        .assert()
+        .stderr(contains("component has no embedded signature"))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["trusted-keys"]["test:foo"] =
+            value(Array::from_iter([signing_key.to_string()]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("verify test:foo")
+        .assert()
+        .stderr(contains(format!(
+            "Verified package `test:foo` v0.1.0 was signed by key `{id}`",
+            id = signing_key.fingerprint()
+        )))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_verifies_dependencies_with_trusted_keys() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "my:world",
+        "1.0.0",
+        r#"package my:%world@1.0.0
+world foo {
+    export bar: func() -> string
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "foo", "--target my:world")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(contains("Finished dev [unoptimized + debuginfo] target(s)"))
+        .success();
+
+    let key = PrivateKey::decode(test_signing_key().to_string())?.public_key();
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["trusted-keys"]["my"] =
+            value(Array::from_iter([key.to_string()]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("build")
+        .assert()
+        .stderr(
+            contains("failed to verify the signature of package `my:world` v1.0.0")
+                .and(contains("component has no embedded signature")),
+        )
+        .failure();
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use std::fs;
+use toml_edit::value;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help why", "why -h", "why --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains(
+                "Explain why a package is in the resolved dependency graph of a component",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_explains_why_a_package_is_a_dependency() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    publish_wit(
+        &config,
+        "foo:bar",
+        "1.2.3",
+        r#"package foo:bar@1.2.3
+interface types {
+    record greeting {
+        text: string,
+    }
+}"#,
+        true,
+    )
+    .await?;
+
+    let project = Project::with_root(&root, "component", "")?;
+    project.update_manifest(|mut doc| {
+        redirect_bindings_crate(&mut doc);
+        doc["package"]["metadata"]["component"]["target"]["dependencies"]["foo:bar"] =
+            value("1.2.3");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package my:component
+
+interface api {
+    use foo:bar/types@1.2.3.{greeting}
+    greet: func() -> greeting
+}
+
+world example {
+    export api
+}
+"#,
+    )?;
+
+    project
+        .cargo_component("why foo:bar@1")
+        .assert()
+        .stdout(
+            contains("package `foo:bar@1.2.3`")
+                .and(contains(
+                    "  is target dependency `foo:bar` (registry package `foo:bar@1.2.3`; version requirement `^1.2.3` selected v1.2.3)",
+                ))
+                .and(contains(
+                    "  used by interface `my:component/api` through `use foo:bar/types@1.2.3`",
+                )),
+        )
+        .success();
+
+    project
+        .cargo_component("why foo:bar@2")
+        .assert()
+        .stderr(contains(
+            "package `foo:bar@2` is not in the resolved dependency graph of the target world",
+        ))
+        .failure();
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use crate::support::*;
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use predicates::str::contains;
+use toml_edit::value;
+
+mod support;
+
+#[test]
+fn help() {
+    for arg in ["help yank", "yank -h", "yank --help"] {
+        cargo_component(arg)
+            .assert()
+            .stdout(contains("Yank a release of a package from a registry"))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_yanks_a_release() -> Result<()> {
+    let root = create_root()?;
+    let (_server, config) = spawn_server(&root).await?;
+    config.write_to_file(&root.join("warg-config.json"))?;
+
+    for (version, init) in [("1.0.0", true), ("1.1.0", false)] {
+        publish_wit(
+            &config,
+            "test:world",
+            version,
+            &format!(
+                r#"package test:%world@{version}
+world foo {{
+    export bar: func() -> string
+}}"#
+            ),
+            init,
+        )
+        .await?;
+    }
+
+    let project = Project::with_root(&root, "foo", "")?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["package"] = value("test:world");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("yank 1.1.0")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Yanked package `test:world` v1.1.0"))
+        .success();
+
+    project
+        .cargo_component("yank 1.1.0")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "version 1.1.0 of package `test:world` is already yanked",
+        ))
+        .failure();
+
+    project
+        .cargo_component("yank 2.0.0")
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "package `test:world` has no release with version 2.0.0",
+        ))
+        .failure();
+
+    // The yanked release is no longer selected for new dependencies
+    let project = Project::with_root(&root, "bar", "")?;
+    project
+        .cargo_component("add test:world")
+        .assert()
+        .stderr(contains(
+            "Added dependency `test:world` with version `1.0.0`",
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_undo_a_yank() -> Result<()> {
+    let project = Project::new("foo")?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["package"] = value("test:foo");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component("yank --undo 0.1.0")
+        .assert()
+        .stderr(contains(
+            "component registries do not support undoing the yank of a release",
+        ))
+        .failure();
+
+    Ok(())
+}